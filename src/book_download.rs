@@ -0,0 +1,284 @@
+use anyhow::{Context, Result, anyhow};
+use log::{error, info};
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+/// Downloaded books are cached here, under the home directory
+const DOWNLOADS_DIRNAME: &str = ".bookokrat_downloads";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+const READ_TIMEOUT: Duration = Duration::from_secs(60);
+/// Progress is reported at most this often
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+pub enum DownloadEvent {
+    Progress { received: u64, total: Option<u64> },
+    Finished(Result<PathBuf, String>),
+}
+
+/// An EPUB being fetched from a URL on a background thread
+pub struct BookDownload {
+    url: String,
+    receiver: Receiver<DownloadEvent>,
+}
+
+impl BookDownload {
+    /// Start fetching `url` into `cache_dir`. A book already cached for the
+    /// same URL is reused without touching the network.
+    pub fn start(url: &str, cache_dir: &Path) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let dest = cache_path(cache_dir, url);
+        let thread_url = url.to_string();
+
+        std::thread::spawn(move || {
+            let result = fetch_book(&thread_url, &dest, &sender).map_err(|e| {
+                error!("Failed to download {thread_url}: {e:#}");
+                format!("{e:#}")
+            });
+            let _ = sender.send(DownloadEvent::Finished(result));
+        });
+
+        Self {
+            url: url.to_string(),
+            receiver,
+        }
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Events received since the last poll
+    pub fn poll(&self) -> Vec<DownloadEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+pub fn is_url(arg: &str) -> bool {
+    arg.starts_with("http://") || arg.starts_with("https://")
+}
+
+pub fn downloads_dir() -> Option<PathBuf> {
+    home::home_dir().map(|home| home.join(DOWNLOADS_DIRNAME))
+}
+
+/// Cache file for `url`: a hash of the whole URL keeps distinct URLs apart,
+/// the readable suffix keeps the cache browsable
+pub fn cache_path(cache_dir: &Path, url: &str) -> PathBuf {
+    let hash = format!("{:x}", md5::compute(url.as_bytes()));
+    let name: String = display_name(url)
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    cache_dir.join(format!("{}-{}.epub", &hash[..16], name))
+}
+
+/// Book name for the library list, from the last path segment of the URL
+pub fn display_name(url: &str) -> String {
+    let without_query = url.split(['?', '#']).next().unwrap_or(url);
+    let after_scheme = without_query
+        .split_once("://")
+        .map_or(without_query, |(_, rest)| rest);
+    let segment = after_scheme
+        .split_once('/')
+        .and_then(|(_host, path)| path.trim_end_matches('/').rsplit('/').next())
+        .unwrap_or_default();
+    let name = segment.strip_suffix(".epub").unwrap_or(segment);
+    if name.is_empty() {
+        "downloaded-book".to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+fn fetch_book(url: &str, dest: &Path, sender: &Sender<DownloadEvent>) -> Result<PathBuf> {
+    if dest.exists() {
+        if validate_epub(dest).is_ok() {
+            info!("Opening cached download of {url} from {dest:?}");
+            return Ok(dest.to_path_buf());
+        }
+        let _ = fs::remove_file(dest);
+    }
+
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(CONNECT_TIMEOUT)
+        .timeout_read(READ_TIMEOUT)
+        .build();
+    let response = agent.get(url).call().map_err(|e| match e {
+        ureq::Error::Status(code, response) => {
+            anyhow!("Server answered {code} {}", response.status_text())
+        }
+        ureq::Error::Transport(transport) => anyhow!("Network error: {transport}"),
+    })?;
+    let total = response
+        .header("Content-Length")
+        .and_then(|len| len.parse().ok());
+
+    if let Some(dir) = dest.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Cannot create {}", dir.display()))?;
+    }
+    // Download next to the destination so a failed transfer never looks cached
+    let partial = dest.with_extension("part");
+    let mut last_report = Instant::now();
+    let copied = copy_with_progress(response.into_reader(), &partial, |received| {
+        if last_report.elapsed() >= PROGRESS_INTERVAL {
+            last_report = Instant::now();
+            let _ = sender.send(DownloadEvent::Progress { received, total });
+        }
+    });
+
+    let checked = copied.and_then(|_| validate_epub(&partial));
+    if let Err(e) = checked {
+        let _ = fs::remove_file(&partial);
+        return Err(e);
+    }
+    fs::rename(&partial, dest).context("Cannot move download into the cache")?;
+    info!("Downloaded {url} to {dest:?}");
+    Ok(dest.to_path_buf())
+}
+
+/// Stream `reader` into `dest`, reporting the bytes received so far
+pub fn copy_with_progress(
+    mut reader: impl Read,
+    dest: &Path,
+    mut progress: impl FnMut(u64),
+) -> Result<u64> {
+    let mut file =
+        File::create(dest).with_context(|| format!("Cannot create {}", dest.display()))?;
+    let mut buffer = [0u8; 64 * 1024];
+    let mut received = 0u64;
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(anyhow!("Download interrupted: {e}")),
+        };
+        file.write_all(&buffer[..read])?;
+        received += read as u64;
+        progress(received);
+    }
+
+    file.flush()?;
+    Ok(received)
+}
+
+/// Check that `path` is a zip archive shaped like an EPUB
+pub fn validate_epub(path: &Path) -> Result<()> {
+    let file = File::open(path)?;
+    let mut archive =
+        zip::ZipArchive::new(file).map_err(|_| anyhow!("Downloaded file is not an EPUB"))?;
+
+    let mut mimetype = String::new();
+    if let Ok(mut entry) = archive.by_name("mimetype") {
+        entry.read_to_string(&mut mimetype)?;
+    }
+    if mimetype.trim() == "application/epub+zip"
+        || archive.by_name("META-INF/container.xml").is_ok()
+    {
+        Ok(())
+    } else {
+        Err(anyhow!("Downloaded archive is not an EPUB"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn write_epub(path: &Path) {
+        let mut zip = zip::ZipWriter::new(File::create(path).unwrap());
+        let options = zip::write::FileOptions::default();
+        zip.start_file("mimetype", options).unwrap();
+        zip.write_all(b"application/epub+zip").unwrap();
+        zip.finish().unwrap();
+    }
+
+    fn wait_for_result(download: &BookDownload) -> Result<PathBuf, String> {
+        let deadline = Instant::now() + Duration::from_secs(20);
+        while Instant::now() < deadline {
+            for event in download.poll() {
+                if let DownloadEvent::Finished(result) = event {
+                    return result;
+                }
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        panic!("download did not finish");
+    }
+
+    #[test]
+    fn test_cache_path_is_stable_per_url() {
+        let dir = Path::new("/cache");
+        let url = "https://example.com/books/moby-dick.epub?token=1";
+        assert_eq!(cache_path(dir, url), cache_path(dir, url));
+        assert_ne!(
+            cache_path(dir, url),
+            cache_path(dir, "https://example.com/other/moby-dick.epub")
+        );
+        let name = cache_path(dir, url);
+        assert!(name.to_string_lossy().ends_with("-moby-dick.epub"));
+        assert_eq!(display_name("https://example.com/"), "downloaded-book");
+    }
+
+    #[test]
+    fn test_validate_epub_rejects_other_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let book = temp_dir.path().join("book.epub");
+        write_epub(&book);
+        assert!(validate_epub(&book).is_ok());
+
+        let page = temp_dir.path().join("page.epub");
+        fs::write(&page, "<html>Not found</html>").unwrap();
+        assert!(validate_epub(&page).is_err());
+    }
+
+    #[test]
+    fn test_copy_reports_progress() {
+        let temp_dir = TempDir::new().unwrap();
+        let dest = temp_dir.path().join("out");
+        let data = vec![7u8; 200 * 1024];
+        let mut reports = Vec::new();
+
+        let copied = copy_with_progress(&data[..], &dest, |received| reports.push(received));
+        assert_eq!(copied.unwrap(), data.len() as u64);
+        assert_eq!(reports.last(), Some(&(data.len() as u64)));
+        assert!(reports.len() > 1);
+        assert_eq!(fs::read(&dest).unwrap(), data);
+    }
+
+    #[test]
+    fn test_cached_book_opens_without_network() {
+        let temp_dir = TempDir::new().unwrap();
+        // Nothing listens here, so only the cache can satisfy the request
+        let url = "http://127.0.0.1:9/book.epub";
+        write_epub(&cache_path(temp_dir.path(), url));
+
+        let download = BookDownload::start(url, temp_dir.path());
+        assert_eq!(
+            wait_for_result(&download),
+            Ok(cache_path(temp_dir.path(), url))
+        );
+    }
+
+    #[test]
+    fn test_network_failure_is_reported() {
+        let temp_dir = TempDir::new().unwrap();
+        let url = "http://127.0.0.1:9/book.epub";
+
+        let download = BookDownload::start(url, temp_dir.path());
+        let error = wait_for_result(&download).unwrap_err();
+        assert!(error.contains("Network error"), "{error}");
+        assert!(!cache_path(temp_dir.path(), url).exists());
+    }
+}
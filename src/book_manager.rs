@@ -1,3 +1,6 @@
+use crate::book_metadata::{BookMetadata, MetadataCache, file_mtime};
+use crate::library_scan::NewBooks;
+use crate::pinned_books::PinnedBooks;
 use epub::doc::EpubDoc;
 use log::{error, info};
 use std::io::BufReader;
@@ -6,12 +9,30 @@ use std::path::Path;
 pub struct BookManager {
     pub books: Vec<BookInfo>,
     scan_directory: String,
+    /// Books from outside the scanned directory (e.g. downloads), kept across rescans
+    extra_books: Vec<BookInfo>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct BookInfo {
     pub path: String,
+    /// "Title — Author" from the book's metadata, or its file name
     pub display_name: String,
+    /// Seconds since the epoch of the file's modification time when scanned
+    pub mtime: u64,
+    /// Added since the library was last scanned and not opened yet
+    pub is_new: bool,
+    /// Pinned to the top of the list
+    pub is_pinned: bool,
+    /// What the book's OPF says of it, empty until it has been read
+    pub metadata: BookMetadata,
+}
+
+impl BookInfo {
+    /// File name shown when the metadata has no title
+    pub fn file_name(&self) -> String {
+        BookManager::extract_display_name(&self.path)
+    }
 }
 
 impl Default for BookManager {
@@ -27,20 +48,26 @@ impl BookManager {
 
     pub fn new_with_directory(directory: &str) -> Self {
         let scan_directory = directory.to_string();
-        let mut books = Self::discover_books_in_dir(&scan_directory);
-        books.sort_by(|a, b| a.display_name.cmp(&b.display_name));
-        Self {
-            books,
+        let mut manager = Self {
+            books: Self::discover_books_in_dir(&scan_directory),
             scan_directory,
-        }
+            extra_books: Vec::new(),
+        };
+        manager.sort_books();
+        manager
     }
 
     fn discover_books_in_dir(dir: &str) -> Vec<BookInfo> {
-        std::fs::read_dir(dir)
-            .unwrap_or_else(|e| {
+        let entries = match std::fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                // A missing directory leaves the library empty so onboarding can pick another
                 error!("Failed to read directory {dir}: {e}");
-                panic!("Failed to read directory {dir}: {e}");
-            })
+                return Vec::new();
+            }
+        };
+
+        entries
             .filter_map(|entry| {
                 let entry = entry.ok()?;
                 let path = entry.path();
@@ -48,9 +75,14 @@ impl BookManager {
                 if extension == "epub" || extension == "html" || extension == "htm" {
                     let path_str = path.to_str()?.to_string();
                     let display_name = Self::extract_display_name(&path_str);
+                    let mtime = file_mtime(&path_str).unwrap_or(0);
                     Some(BookInfo {
                         path: path_str,
                         display_name,
+                        mtime,
+                        is_new: false,
+                        is_pinned: false,
+                        metadata: BookMetadata::default(),
                     })
                 } else {
                     None
@@ -80,6 +112,74 @@ impl BookManager {
             .to_string()
     }
 
+    /// Show the cached titles and sort the library by them. Books without
+    /// a title keep their name.
+    pub fn apply_metadata(&mut self, metadata: &MetadataCache) {
+        for book in &mut self.books {
+            let Some(cached) = metadata.get(&book.path) else {
+                continue;
+            };
+            if let Some(name) = cached.display_name() {
+                book.display_name = name;
+            }
+            book.metadata = cached.clone();
+        }
+        self.sort_books();
+    }
+
+    /// Flag the books added since the last scan and list them first
+    pub fn apply_new_books(&mut self, new_books: &NewBooks) {
+        for book in &mut self.books {
+            book.is_new = new_books.is_new(&book.path);
+        }
+        self.sort_books();
+    }
+
+    /// Flag the pinned books and list them first
+    pub fn apply_pinned(&mut self, pinned: &PinnedBooks) {
+        for book in &mut self.books {
+            book.is_pinned = pinned.is_pinned(&book.path);
+        }
+        self.sort_books();
+    }
+
+    /// Clear a book's new flag in place, keeping the library's order;
+    /// returns whether it was set
+    pub fn clear_new(&mut self, path: &str) -> bool {
+        match self.books.iter_mut().find(|book| book.path == path) {
+            Some(book) if book.is_new => {
+                book.is_new = false;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Pinned books first, then new ones, each by name
+    fn sort_books(&mut self) {
+        self.books.sort_by(|a, b| {
+            b.is_pinned
+                .cmp(&a.is_pinned)
+                .then_with(|| b.is_new.cmp(&a.is_new))
+                .then_with(|| a.display_name.cmp(&b.display_name))
+        });
+    }
+
+    /// Store a book's metadata and retitle it in place, keeping the
+    /// library's order; returns whether anything changed
+    pub fn set_metadata(&mut self, path: &str, metadata: &BookMetadata) -> bool {
+        let Some(book) = self.books.iter_mut().find(|book| book.path == path) else {
+            return false;
+        };
+        let name = metadata.display_name().unwrap_or_else(|| book.file_name());
+        if book.display_name == name && book.metadata == *metadata {
+            return false;
+        }
+        book.display_name = name;
+        book.metadata = metadata.clone();
+        true
+    }
+
     pub fn get_book_info(&self, index: usize) -> Option<&BookInfo> {
         self.books.get(index)
     }
@@ -337,8 +437,50 @@ impl BookManager {
         None
     }
 
+    pub fn scan_directory(&self) -> &str {
+        &self.scan_directory
+    }
+
     pub fn refresh_books(&mut self) {
         self.books = Self::discover_books_in_dir(&self.scan_directory);
+        for book in &self.extra_books {
+            if !self.contains_book(&book.path) {
+                self.books.push(book.clone());
+            }
+        }
+    }
+
+    /// Add a book that lives outside the scanned directory and return its index
+    pub fn add_book(&mut self, path: &str, display_name: &str) -> usize {
+        if let Some(index) = self.find_book_index_by_path(path) {
+            return index;
+        }
+        let book = BookInfo {
+            path: path.to_string(),
+            display_name: display_name.to_string(),
+            mtime: file_mtime(path).unwrap_or(0),
+            is_new: false,
+            is_pinned: false,
+            metadata: BookMetadata::default(),
+        };
+        self.extra_books.push(book.clone());
+        self.books.push(book);
+        self.books.len() - 1
+    }
+
+    /// Point the library at another directory and rescan it
+    pub fn set_scan_directory(&mut self, directory: &str) {
+        self.scan_directory = directory.to_string();
+        self.refresh_books();
+        self.sort_books();
+    }
+
+    /// Number of EPUB files directly inside `dir`
+    pub fn count_epubs_in_dir(dir: &str) -> usize {
+        Self::discover_books_in_dir(dir)
+            .iter()
+            .filter(|book| book.path.ends_with(".epub"))
+            .count()
     }
 
     pub fn find_book_index_by_path(&self, path: &str) -> Option<usize> {
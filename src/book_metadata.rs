@@ -0,0 +1,449 @@
+//! Titles, authors and the rest of the books' OPF metadata, shown instead of
+//! file names and in the book info popup.
+//!
+//! Reading them means opening every EPUB, so the library is scanned on a
+//! background thread after startup. Results are kept in `book_metadata.json`
+//! next to the bookmarks file, keyed by path, and a book is read again when
+//! its file's modification time changes or the cache predates fields read
+//! since.
+
+use epub::doc::{EpubDoc, MetadataItem};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Seek};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::UNIX_EPOCH;
+
+pub const BOOK_METADATA_FILENAME: &str = "book_metadata.json";
+/// Bumped when more of the metadata is read, so cached books are read again
+const METADATA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    #[serde(default)]
+    pub publisher: Option<String>,
+    /// Language code as the book gives it, e.g. "en" or "fr-CA"
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub series: Option<String>,
+    /// Place in the series, e.g. "2" or "2.5"
+    #[serde(default)]
+    pub series_index: Option<String>,
+}
+
+impl BookMetadata {
+    /// "Title — Author", or just the title; None without a title
+    pub fn display_name(&self) -> Option<String> {
+        let title = self.title.as_deref()?;
+        Some(match self.author.as_deref() {
+            Some(author) => format!("{title} — {author}"),
+            None => title.to_string(),
+        })
+    }
+
+    /// "Series #2", or just the series; None outside a series
+    pub fn series_label(&self) -> Option<String> {
+        let series = self.series.as_deref()?;
+        Some(match self.series_index.as_deref() {
+            Some(index) => format!("{series} #{index}"),
+            None => series.to_string(),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedMetadata {
+    /// Seconds since the epoch of the file's modification time when read
+    mtime: u64,
+    /// `METADATA_VERSION` when read; 0 for caches from before it
+    #[serde(default)]
+    version: u32,
+    #[serde(flatten)]
+    metadata: BookMetadata,
+}
+
+#[derive(Debug, Default)]
+pub struct MetadataCache {
+    entries: HashMap<String, CachedMetadata>,
+    file_path: Option<String>,
+}
+
+impl MetadataCache {
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        let Some(path) = file_path else {
+            return Self::default();
+        };
+        let entries = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse book metadata cache {path}: {e}");
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                log::error!("Failed to load book metadata cache from {path}: {e}");
+                HashMap::new()
+            }
+        };
+        Self {
+            entries,
+            file_path: Some(path.to_string()),
+        }
+    }
+
+    /// Metadata last read for the book, possibly of an older file
+    pub fn get(&self, path: &str) -> Option<&BookMetadata> {
+        self.entries.get(path).map(|entry| &entry.metadata)
+    }
+
+    /// Title to show for the book, None when its metadata has none
+    pub fn display_name(&self, path: &str) -> Option<String> {
+        self.get(path)?.display_name()
+    }
+
+    pub fn insert(&mut self, path: &str, mtime: u64, metadata: BookMetadata) {
+        self.entries.insert(
+            path.to_string(),
+            CachedMetadata {
+                mtime,
+                version: METADATA_VERSION,
+                metadata,
+            },
+        );
+    }
+
+    /// Start reading the books not cached yet or changed since
+    pub fn scan(&self, paths: &[String]) -> MetadataScan {
+        let known: HashMap<String, u64> = paths
+            .iter()
+            .filter_map(|path| {
+                let entry = self.entries.get(path)?;
+                (entry.version == METADATA_VERSION).then(|| (path.clone(), entry.mtime))
+            })
+            .collect();
+        MetadataScan::start(paths.to_vec(), known)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            fs::write(path, serde_json::to_string_pretty(&self.entries)?)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) fn file_mtime(path: &str) -> Option<u64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Metadata from the EPUB's OPF; empty for other files and EPUBs that
+/// can't be opened
+pub fn read_metadata(path: &str) -> BookMetadata {
+    if Path::new(path)
+        .extension()
+        .is_none_or(|extension| extension != "epub")
+    {
+        return BookMetadata::default();
+    }
+    match EpubDoc::new(path) {
+        Ok(doc) => metadata_of(&doc),
+        Err(e) => {
+            log::warn!("Failed to read metadata of {path}: {e}");
+            BookMetadata::default()
+        }
+    }
+}
+
+/// Metadata of an opened EPUB
+pub fn metadata_of<R: Read + Seek>(doc: &EpubDoc<R>) -> BookMetadata {
+    metadata_from_items(&doc.metadata)
+}
+
+/// Title, first author, publisher, language and series from the OPF's
+/// metadata. The series is the EPUB 3 collection (a `series` one first),
+/// else Calibre's `calibre:series`.
+fn metadata_from_items(items: &[MetadataItem]) -> BookMetadata {
+    let clean = |value: &str| {
+        Some(value.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|value| !value.is_empty())
+    };
+    let value = |property: &str| {
+        items
+            .iter()
+            .filter(|item| item.property == property)
+            .find_map(|item| clean(&item.value))
+    };
+    let refinement = |item: &MetadataItem, property: &str| {
+        item.refined
+            .iter()
+            .filter(|refinement| refinement.property == property)
+            .find_map(|refinement| clean(&refinement.value))
+    };
+
+    let collections: Vec<&MetadataItem> = items
+        .iter()
+        .filter(|item| item.property == "belongs-to-collection" && clean(&item.value).is_some())
+        .collect();
+    let collection = collections
+        .iter()
+        .find(|item| refinement(item, "collection-type").as_deref() == Some("series"))
+        .or(collections.first());
+    let (series, series_index) = match collection {
+        Some(item) => (clean(&item.value), refinement(item, "group-position")),
+        None => (value("calibre:series"), value("calibre:series_index")),
+    };
+
+    BookMetadata {
+        title: value("title"),
+        author: value("creator"),
+        publisher: value("publisher"),
+        language: value("language"),
+        series_index: series.as_ref().and(series_index).map(|index| {
+            // Calibre writes whole numbers as "2.0"
+            match index.parse::<f64>() {
+                Ok(n) if n.fract() == 0.0 && n >= 0.0 => format!("{n:.0}"),
+                _ => index,
+            }
+        }),
+        series,
+    }
+}
+
+/// Metadata read for one book, with its file's modification time
+pub struct ScannedBook {
+    pub path: String,
+    pub mtime: u64,
+    pub metadata: BookMetadata,
+}
+
+/// Books being read on a background thread, which skips those whose
+/// cached modification time still matches
+pub struct MetadataScan {
+    receiver: Receiver<ScannedBook>,
+}
+
+impl MetadataScan {
+    fn start(paths: Vec<String>, known: HashMap<String, u64>) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            for path in paths {
+                let Some(mtime) = file_mtime(&path) else {
+                    continue;
+                };
+                if known.get(&path) == Some(&mtime) {
+                    continue;
+                }
+                let metadata = read_metadata(&path);
+                if sender
+                    .send(ScannedBook {
+                        path,
+                        mtime,
+                        metadata,
+                    })
+                    .is_err()
+                {
+                    // Dropped, e.g. after the library was rescanned
+                    return;
+                }
+            }
+        });
+        Self { receiver }
+    }
+
+    /// Books read since the last call, and whether the scan is finished
+    pub fn take_results(&self) -> (Vec<ScannedBook>, bool) {
+        let mut books = Vec::new();
+        loop {
+            match self.receiver.try_recv() {
+                Ok(book) => books.push(book),
+                Err(TryRecvError::Empty) => return (books, false),
+                Err(TryRecvError::Disconnected) => return (books, true),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn finish(scan: &MetadataScan) -> Vec<ScannedBook> {
+        let deadline = Instant::now() + Duration::from_secs(10);
+        let mut books = Vec::new();
+        loop {
+            let (mut scanned, done) = scan.take_results();
+            books.append(&mut scanned);
+            if done {
+                return books;
+            }
+            assert!(Instant::now() < deadline, "scan never finished");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn test_display_name_falls_back_without_a_title() {
+        let metadata = BookMetadata {
+            title: Some("Moby-Dick".to_string()),
+            author: Some("Herman Melville".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            metadata.display_name().as_deref(),
+            Some("Moby-Dick — Herman Melville")
+        );
+        let untitled = BookMetadata {
+            title: None,
+            author: Some("Herman Melville".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(untitled.display_name(), None);
+    }
+
+    /// Metadata of an EPUB whose OPF has these `<metadata>` children
+    fn metadata_of_opf(metadata: &str) -> BookMetadata {
+        use std::io::{Cursor, Write};
+        use zip::write::FileOptions;
+
+        let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let files = [
+            ("mimetype", "application/epub+zip".to_string()),
+            (
+                "META-INF/container.xml",
+                r#"<?xml version="1.0" encoding="UTF-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>"#
+                    .to_string(),
+            ),
+            (
+                "content.opf",
+                format!(
+                    r#"<?xml version="1.0" encoding="UTF-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="id">test</dc:identifier>
+    {metadata}
+  </metadata>
+  <manifest>
+    <item id="text" href="text.xhtml" media-type="application/xhtml+xml"/>
+  </manifest>
+  <spine>
+    <itemref idref="text"/>
+  </spine>
+</package>"#
+                ),
+            ),
+            (
+                "text.xhtml",
+                "<html><body><p>Text</p></body></html>".to_string(),
+            ),
+        ];
+        for (name, content) in files {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(content.as_bytes()).unwrap();
+        }
+        let epub = zip.finish().unwrap().into_inner();
+        metadata_of(&EpubDoc::from_reader(Cursor::new(epub)).unwrap())
+    }
+
+    #[test]
+    fn test_reads_publisher_language_and_series() {
+        let calibre = metadata_of_opf(
+            r#"<dc:title> The  Fellowship
+      of the Ring </dc:title>
+    <dc:creator>J. R. R. Tolkien</dc:creator>
+    <dc:creator>Alan Lee</dc:creator>
+    <dc:publisher>Allen &amp; Unwin</dc:publisher>
+    <dc:language>en</dc:language>
+    <meta name="calibre:series" content="The Lord of the Rings"/>
+    <meta name="calibre:series_index" content="1.0"/>"#,
+        );
+        assert_eq!(
+            calibre,
+            BookMetadata {
+                title: Some("The Fellowship of the Ring".to_string()),
+                author: Some("J. R. R. Tolkien".to_string()),
+                publisher: Some("Allen & Unwin".to_string()),
+                language: Some("en".to_string()),
+                series: Some("The Lord of the Rings".to_string()),
+                series_index: Some("1".to_string()),
+            }
+        );
+        assert_eq!(
+            calibre.series_label().as_deref(),
+            Some("The Lord of the Rings #1")
+        );
+
+        // EPUB 3 collections win over Calibre's, a series over a set
+        let epub3 = metadata_of_opf(
+            r##"<meta property="belongs-to-collection" id="set">Fantasy Classics</meta>
+    <meta refines="#set" property="collection-type">set</meta>
+    <meta property="belongs-to-collection" id="series">Earthsea</meta>
+    <meta refines="#series" property="collection-type">series</meta>
+    <meta refines="#series" property="group-position">2.5</meta>
+    <meta name="calibre:series" content="Other"/>"##,
+        );
+        assert_eq!(epub3.series_label().as_deref(), Some("Earthsea #2.5"));
+        assert_eq!(epub3.publisher, None);
+
+        // An index without a series means nothing
+        let stray = metadata_of_opf(r#"<meta name="calibre:series_index" content="3"/>"#);
+        assert_eq!(stray, BookMetadata::default());
+    }
+
+    #[test]
+    fn test_scan_rereads_only_changed_files_and_cache_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let book = dir.path().join("notes.html");
+        fs::write(&book, "<p>Not an EPUB</p>").unwrap();
+        let book = book.to_str().unwrap().to_string();
+        let missing = dir.path().join("gone.epub").to_str().unwrap().to_string();
+        let cache_path = dir.path().join(BOOK_METADATA_FILENAME);
+        let cache_path = cache_path.to_str().unwrap();
+
+        let mut cache = MetadataCache::load_or_ephemeral(Some(cache_path));
+        let scanned = finish(&cache.scan(&[book.clone(), missing]));
+        assert_eq!(scanned.len(), 1);
+        assert_eq!(scanned[0].metadata, BookMetadata::default());
+
+        let titled = BookMetadata {
+            title: Some("Notes".to_string()),
+            ..Default::default()
+        };
+        cache.insert(&book, scanned[0].mtime, titled.clone());
+        cache.save().unwrap();
+
+        let cache = MetadataCache::load_or_ephemeral(Some(cache_path));
+        assert_eq!(cache.get(&book), Some(&titled));
+        assert!(finish(&cache.scan(std::slice::from_ref(&book))).is_empty());
+
+        // A cached time that no longer matches the file is read again
+        let mut cache = cache;
+        cache.insert(&book, 0, titled);
+        assert_eq!(finish(&cache.scan(std::slice::from_ref(&book))).len(), 1);
+
+        // So is a book cached before the fields read now
+        let old = serde_json::json!({
+            book.as_str(): { "mtime": scanned[0].mtime, "title": "Notes", "author": null }
+        });
+        fs::write(cache_path, old.to_string()).unwrap();
+        let cache = MetadataCache::load_or_ephemeral(Some(cache_path));
+        assert_eq!(
+            cache.get(&book).and_then(|m| m.title.as_deref()),
+            Some("Notes")
+        );
+        assert_eq!(finish(&cache.scan(&[book])).len(), 1);
+    }
+}
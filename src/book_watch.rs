@@ -0,0 +1,106 @@
+//! Noticing when the open book's file is rewritten, e.g. by Calibre.
+//!
+//! The file's modification time is checked every couple of seconds from the
+//! run loop's tick. A new time is only reported once it has held for a
+//! whole interval, so a book still being written is not read half done.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the book's modification time is checked
+pub const BOOK_WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+pub struct BookWatch {
+    path: PathBuf,
+    /// Modification time of the file as it was loaded
+    loaded: Option<SystemTime>,
+    /// A different time seen at the last check, waiting to hold
+    changed: Option<SystemTime>,
+    last_check: Instant,
+}
+
+impl BookWatch {
+    pub fn new(path: &str, now: Instant) -> Self {
+        let path = PathBuf::from(path);
+        Self {
+            loaded: modified(&path),
+            path,
+            changed: None,
+            last_check: now,
+        }
+    }
+
+    /// Check when the interval is up; true once the file has changed and
+    /// stayed unchanged since the previous check
+    pub fn poll(&mut self, now: Instant) -> bool {
+        if now.duration_since(self.last_check) < BOOK_WATCH_INTERVAL {
+            return false;
+        }
+        self.last_check = now;
+
+        // A file briefly missing while it is replaced counts as no change
+        let Some(mtime) = modified(&self.path) else {
+            return false;
+        };
+        if self.loaded == Some(mtime) {
+            self.changed = None;
+            return false;
+        }
+        if self.changed != Some(mtime) {
+            self.changed = Some(mtime);
+            return false;
+        }
+        self.loaded = Some(mtime);
+        self.changed = None;
+        true
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch(path: &Path, seconds: u64) {
+        let file = fs::File::options().write(true).open(path).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_changes_are_reported_once_they_hold() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("moby.epub");
+        fs::write(&path, "v1").unwrap();
+        touch(&path, 1_000);
+
+        let start = Instant::now();
+        let mut watch = BookWatch::new(path.to_str().unwrap(), start);
+        let check = |n: u32| start + n * BOOK_WATCH_INTERVAL;
+        assert!(!watch.poll(check(1)));
+
+        // Not checked again until the interval is up
+        touch(&path, 2_000);
+        assert!(!watch.poll(check(1) + Duration::from_millis(500)));
+        // Seen changed, then reported once it held for an interval
+        assert!(!watch.poll(check(2)));
+        assert!(watch.poll(check(3)));
+        assert!(!watch.poll(check(4)));
+
+        // Still being written: each check sees a new time
+        touch(&path, 3_000);
+        assert!(!watch.poll(check(5)));
+        touch(&path, 4_000);
+        assert!(!watch.poll(check(6)));
+        assert!(watch.poll(check(7)));
+
+        // Missing while replaced
+        fs::remove_file(&path).unwrap();
+        assert!(!watch.poll(check(8)));
+        assert!(!watch.poll(check(9)));
+    }
+}
@@ -1,8 +1,10 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+pub const BOOKMARKS_FILENAME: &str = "bookmarks.json";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Bookmark {
     pub chapter_href: String,
@@ -17,12 +19,78 @@ pub struct Bookmark {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub total_chapters: Option<usize>,
+
+    /// Deepest chapter reached, which going back to reread doesn't lower
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub furthest_chapter: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub furthest_node: Option<usize>,
+}
+
+impl Bookmark {
+    /// Chapter and node read furthest to, the current position for
+    /// bookmarks saved before it was tracked
+    pub fn furthest(&self) -> Option<(usize, usize)> {
+        match self.furthest_chapter {
+            Some(chapter) => Some((chapter, self.furthest_node.unwrap_or(0))),
+            None => self
+                .chapter_index
+                .map(|chapter| (chapter, self.node_index.unwrap_or(0))),
+        }
+    }
+}
+
+/// A named reading position kept next to a book's primary bookmark, for
+/// reading a book in more than one place at once
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReadingThread {
+    pub name: String,
+
+    pub chapter_href: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_index: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chapter_index: Option<usize>,
+
+    pub last_read: chrono::DateTime<chrono::Utc>,
+}
+
+/// How far a chapter has been read; chapters never opened have no state
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChapterReadState {
+    InProgress,
+    Read,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Bookmarks {
     books: HashMap<String, Bookmark>,
 
+    /// Books read with OCR hyphenation cleanup turned on
+    #[serde(default, skip_serializing_if = "HashSet::is_empty")]
+    dehyphenated: HashSet<String>,
+
+    /// Books read with typographic punctuation on or off against the
+    /// `typography` setting
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    typography: HashMap<String, bool>,
+
+    /// Content margins chosen for books instead of the `margin` setting
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    margins: HashMap<String, u16>,
+
+    /// Reading threads of each book, in creation order
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    threads: HashMap<String, Vec<ReadingThread>>,
+
+    /// Read state of each book's chapters by chapter href
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    read_state: HashMap<String, HashMap<String, ChapterReadState>>,
+
     #[serde(skip)]
     file_path: Option<String>,
 }
@@ -31,6 +99,11 @@ impl Bookmarks {
     pub fn ephemeral() -> Self {
         Self {
             books: HashMap::new(),
+            dehyphenated: HashSet::new(),
+            typography: HashMap::new(),
+            margins: HashMap::new(),
+            threads: HashMap::new(),
+            read_state: HashMap::new(),
             file_path: None,
         }
     }
@@ -38,6 +111,11 @@ impl Bookmarks {
     pub fn with_file(file_path: &str) -> Self {
         Self {
             books: HashMap::new(),
+            dehyphenated: HashSet::new(),
+            typography: HashMap::new(),
+            margins: HashMap::new(),
+            threads: HashMap::new(),
+            read_state: HashMap::new(),
             file_path: Some(file_path.to_string()),
         }
     }
@@ -73,6 +151,9 @@ impl Bookmarks {
     }
 
     pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
         match &self.file_path {
             Some(path) => {
                 let content = serde_json::to_string_pretty(self)?;
@@ -83,6 +164,10 @@ impl Bookmarks {
         }
     }
 
+    pub fn file_path(&self) -> Option<&str> {
+        self.file_path.as_deref()
+    }
+
     pub fn get_bookmark(&self, path: &str) -> Option<&Bookmark> {
         self.books.get(path)
     }
@@ -94,6 +179,17 @@ impl Bookmarks {
             .map(|(path, bookmark)| (path.clone(), bookmark))
     }
 
+    /// Paths of the most recently read books, newest first
+    pub fn recent_books(&self, limit: usize) -> Vec<String> {
+        let mut books: Vec<(&String, &Bookmark)> = self.books.iter().collect();
+        books.sort_by_key(|(_, bookmark)| std::cmp::Reverse(bookmark.last_read));
+        books
+            .into_iter()
+            .take(limit)
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
     pub fn update_bookmark(
         &mut self,
         path: &str,
@@ -102,6 +198,12 @@ impl Bookmarks {
         chapter_index: Option<usize>,
         total_chapters: Option<usize>,
     ) {
+        let current = chapter_index.map(|chapter| (chapter, node_index.unwrap_or(0)));
+        let furthest = self
+            .books
+            .get(path)
+            .and_then(Bookmark::furthest)
+            .max(current);
         self.books.insert(
             path.to_string(),
             Bookmark {
@@ -110,6 +212,8 @@ impl Bookmarks {
                 last_read: chrono::Utc::now(),
                 chapter_index,
                 total_chapters,
+                furthest_chapter: furthest.map(|(chapter, _)| chapter),
+                furthest_node: furthest.map(|(_, node)| node),
             },
         );
 
@@ -120,7 +224,369 @@ impl Bookmarks {
         }
     }
 
+    /// Forget a book; returns false if it had no bookmark
+    pub fn remove_bookmark(&mut self, path: &str) -> bool {
+        if self.books.remove(path).is_none() {
+            return false;
+        }
+        self.dehyphenated.remove(path);
+        self.typography.remove(path);
+        self.margins.remove(path);
+        self.threads.remove(path);
+        self.read_state.remove(path);
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+        true
+    }
+
+    /// Keep reading the book without moving its primary bookmark, so that
+    /// it still sorts as recently read
+    pub fn touch_bookmark(&mut self, path: &str) {
+        if let Some(bookmark) = self.books.get_mut(path) {
+            bookmark.last_read = chrono::Utc::now();
+        }
+    }
+
+    pub fn threads(&self, path: &str) -> &[ReadingThread] {
+        self.threads.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn get_thread(&self, path: &str, name: &str) -> Option<&ReadingThread> {
+        self.threads(path).iter().find(|thread| thread.name == name)
+    }
+
+    /// Add a thread; returns false if the book already has one with this name
+    pub fn add_thread(&mut self, path: &str, thread: ReadingThread) -> bool {
+        let threads = self.threads.entry(path.to_string()).or_default();
+        if threads.iter().any(|existing| existing.name == thread.name) {
+            return false;
+        }
+        threads.push(thread);
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+        true
+    }
+
+    /// Move a thread to a new position; written to disk with the next save
+    pub fn update_thread(
+        &mut self,
+        path: &str,
+        name: &str,
+        chapter_href: String,
+        node_index: Option<usize>,
+        chapter_index: Option<usize>,
+    ) {
+        let thread = self
+            .threads
+            .get_mut(path)
+            .and_then(|threads| threads.iter_mut().find(|thread| thread.name == name));
+        if let Some(thread) = thread {
+            thread.chapter_href = chapter_href;
+            thread.node_index = node_index;
+            thread.chapter_index = chapter_index;
+            thread.last_read = chrono::Utc::now();
+        }
+    }
+
+    /// Delete a thread; returns false if there was no such thread
+    pub fn remove_thread(&mut self, path: &str, name: &str) -> bool {
+        let Some(threads) = self.threads.get_mut(path) else {
+            return false;
+        };
+        let count = threads.len();
+        threads.retain(|thread| thread.name != name);
+        if threads.len() == count {
+            return false;
+        }
+        if threads.is_empty() {
+            self.threads.remove(path);
+        }
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+        true
+    }
+
+    pub fn chapter_state(&self, path: &str, chapter_href: &str) -> Option<ChapterReadState> {
+        self.read_state.get(path)?.get(chapter_href).copied()
+    }
+
+    /// Set a chapter's read state, None marking it unread; returns whether
+    /// it changed. Written to disk with the next save.
+    pub fn set_chapter_state(
+        &mut self,
+        path: &str,
+        chapter_href: &str,
+        state: Option<ChapterReadState>,
+    ) -> bool {
+        if self.chapter_state(path, chapter_href) == state {
+            return false;
+        }
+        match state {
+            Some(state) => {
+                self.read_state
+                    .entry(path.to_string())
+                    .or_default()
+                    .insert(chapter_href.to_string(), state);
+            }
+            None => {
+                if let Some(chapters) = self.read_state.get_mut(path) {
+                    chapters.remove(chapter_href);
+                    if chapters.is_empty() {
+                        self.read_state.remove(path);
+                    }
+                }
+            }
+        }
+        true
+    }
+
+    /// Hrefs of the book's chapters marked read
+    pub fn read_chapters(&self, path: &str) -> HashSet<String> {
+        self.read_state
+            .get(path)
+            .into_iter()
+            .flatten()
+            .filter(|(_, state)| **state == ChapterReadState::Read)
+            .map(|(href, _)| href.clone())
+            .collect()
+    }
+
+    /// Chapters read out of those tracked; None for books without read state
+    pub fn chapters_read(&self, path: &str) -> Option<usize> {
+        let chapters = self.read_state.get(path)?;
+        Some(
+            chapters
+                .values()
+                .filter(|state| **state == ChapterReadState::Read)
+                .count(),
+        )
+    }
+
+    /// Forget the read state of chapters no longer in the book's spine, e.g.
+    /// after the file was replaced by a new edition
+    pub fn retain_chapters(&mut self, path: &str, spine_hrefs: &HashSet<String>) {
+        let Some(chapters) = self.read_state.get_mut(path) else {
+            return;
+        };
+        let count = chapters.len();
+        chapters.retain(|href, _| spine_hrefs.contains(href));
+        if chapters.len() == count {
+            return;
+        }
+        if chapters.is_empty() {
+            self.read_state.remove(path);
+        }
+        if let Err(e) = self.save() {
+            log::error!("Failed to save bookmarks: {e}");
+        }
+    }
+
+    pub fn is_dehyphenated(&self, path: &str) -> bool {
+        self.dehyphenated.contains(path)
+    }
+
+    pub fn set_dehyphenated(&mut self, path: &str, dehyphenated: bool) {
+        let changed = if dehyphenated {
+            self.dehyphenated.insert(path.to_string())
+        } else {
+            self.dehyphenated.remove(path)
+        };
+        if changed {
+            if let Err(e) = self.save() {
+                log::error!("Failed to save bookmarks: {e}");
+            }
+        }
+    }
+
+    /// Typographic punctuation chosen for the book, None when it follows
+    /// the setting
+    pub fn typography(&self, path: &str) -> Option<bool> {
+        self.typography.get(path).copied()
+    }
+
+    pub fn set_typography(&mut self, path: &str, typography: Option<bool>) {
+        let changed = match typography {
+            Some(typography) => {
+                self.typography.insert(path.to_string(), typography) != Some(typography)
+            }
+            None => self.typography.remove(path).is_some(),
+        };
+        if changed {
+            if let Err(e) = self.save() {
+                log::error!("Failed to save bookmarks: {e}");
+            }
+        }
+    }
+
+    /// Content margin chosen for the book, None when it follows the setting
+    pub fn margin(&self, path: &str) -> Option<u16> {
+        self.margins.get(path).copied()
+    }
+
+    pub fn set_margin(&mut self, path: &str, margin: Option<u16>) {
+        let changed = match margin {
+            Some(margin) => self.margins.insert(path.to_string(), margin) != Some(margin),
+            None => self.margins.remove(path).is_some(),
+        };
+        if changed {
+            if let Err(e) = self.save() {
+                log::error!("Failed to save bookmarks: {e}");
+            }
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = (&String, &Bookmark)> {
         self.books.iter()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn thread(name: &str, chapter_index: usize) -> ReadingThread {
+        ReadingThread {
+            name: name.to_string(),
+            chapter_href: format!("ch{chapter_index}.xhtml"),
+            node_index: Some(3),
+            chapter_index: Some(chapter_index),
+            last_read: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_single_bookmark_files_still_load() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BOOKMARKS_FILENAME);
+        fs::write(
+            &path,
+            r#"{"books":{"book.epub":{"chapter_href":"ch2.xhtml","last_read":"2024-05-10T12:00:00Z"}}}"#,
+        )
+        .unwrap();
+
+        let bookmarks = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(
+            bookmarks.get_bookmark("book.epub").unwrap().chapter_href,
+            "ch2.xhtml"
+        );
+        assert!(bookmarks.threads("book.epub").is_empty());
+
+        // Books without threads are written in the old shape
+        bookmarks.save().unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("threads"));
+    }
+
+    #[test]
+    fn test_furthest_position_only_moves_forward() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BOOKMARKS_FILENAME);
+        // Saved before the furthest position was kept
+        fs::write(
+            &path,
+            r#"{"books":{"book.epub":{"chapter_href":"ch4.xhtml","node_index":7,"chapter_index":4,"last_read":"2024-05-10T12:00:00Z"}}}"#,
+        )
+        .unwrap();
+        let mut bookmarks = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        let furthest =
+            |bookmarks: &Bookmarks| bookmarks.get_bookmark("book.epub").unwrap().furthest();
+        assert_eq!(furthest(&bookmarks), Some((4, 7)));
+
+        // Skipping ahead moves it, going back to reread doesn't
+        bookmarks.update_bookmark("book.epub", "ch9.xhtml".into(), Some(2), Some(9), Some(12));
+        bookmarks.update_bookmark("book.epub", "ch3.xhtml".into(), Some(30), Some(3), Some(12));
+        assert_eq!(furthest(&bookmarks), Some((9, 2)));
+        bookmarks.update_bookmark("book.epub", "ch9.xhtml".into(), Some(5), Some(9), Some(12));
+        assert_eq!(furthest(&bookmarks), Some((9, 5)));
+
+        let loaded = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        let bookmark = loaded.get_bookmark("book.epub").unwrap();
+        assert_eq!(bookmark.chapter_index, Some(9));
+        assert_eq!(
+            (bookmark.furthest_chapter, bookmark.furthest_node),
+            (Some(9), Some(5))
+        );
+    }
+
+    #[test]
+    fn test_threads_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BOOKMARKS_FILENAME);
+        let mut bookmarks = Bookmarks::with_file(path.to_str().unwrap());
+
+        assert!(bookmarks.add_thread("book.epub", thread("appendix", 9)));
+        assert!(!bookmarks.add_thread("book.epub", thread("appendix", 2)));
+        bookmarks.update_thread("book.epub", "appendix", "ch10.xhtml".into(), None, Some(10));
+        bookmarks.save().unwrap();
+
+        let mut loaded = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        let appendix = loaded.get_thread("book.epub", "appendix").unwrap();
+        assert_eq!(appendix.chapter_index, Some(10));
+        assert_eq!(appendix.chapter_href, "ch10.xhtml");
+
+        assert!(loaded.remove_thread("book.epub", "appendix"));
+        assert!(!loaded.remove_thread("book.epub", "appendix"));
+        assert!(loaded.threads("book.epub").is_empty());
+    }
+
+    #[test]
+    fn test_margins_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BOOKMARKS_FILENAME);
+        let mut bookmarks = Bookmarks::with_file(path.to_str().unwrap());
+
+        bookmarks.set_margin("manual.epub", Some(1));
+        bookmarks.set_margin("novel.epub", Some(12));
+        bookmarks.set_margin("novel.epub", None);
+
+        let loaded = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.margin("manual.epub"), Some(1));
+        assert_eq!(loaded.margin("novel.epub"), None);
+    }
+
+    #[test]
+    fn test_read_state_round_trip_and_spine_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(BOOKMARKS_FILENAME);
+        let mut bookmarks = Bookmarks::with_file(path.to_str().unwrap());
+
+        assert!(bookmarks.set_chapter_state(
+            "book.epub",
+            "ch1.xhtml",
+            Some(ChapterReadState::Read)
+        ));
+        bookmarks.set_chapter_state("book.epub", "ch2.xhtml", Some(ChapterReadState::InProgress));
+        bookmarks.set_chapter_state("book.epub", "old.xhtml", Some(ChapterReadState::Read));
+        assert!(!bookmarks.set_chapter_state(
+            "book.epub",
+            "ch1.xhtml",
+            Some(ChapterReadState::Read)
+        ));
+        bookmarks.save().unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("in_progress"));
+
+        let mut loaded = Bookmarks::load_from_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded.chapters_read("book.epub"), Some(2));
+        assert_eq!(
+            loaded.chapter_state("book.epub", "ch2.xhtml"),
+            Some(ChapterReadState::InProgress)
+        );
+        assert_eq!(loaded.chapters_read("other.epub"), None);
+
+        // A new edition without old.xhtml drops its state
+        let spine = HashSet::from(["ch1.xhtml".to_string(), "ch2.xhtml".to_string()]);
+        loaded.retain_chapters("book.epub", &spine);
+        assert_eq!(
+            loaded.read_chapters("book.epub"),
+            HashSet::from(["ch1.xhtml".to_string()])
+        );
+
+        loaded.set_chapter_state("book.epub", "ch1.xhtml", None);
+        loaded.set_chapter_state("book.epub", "ch2.xhtml", None);
+        loaded.save().unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("read_state"));
+    }
+}
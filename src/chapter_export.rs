@@ -0,0 +1,180 @@
+//! Saving the chapter being read, or the blocks a selection covers, to a
+//! file for notes.
+//!
+//! Chapters are written as Markdown named after the chapter title, or as
+//! the original HTML from the raw HTML view. The book's images can be
+//! copied into a `<name>_images` directory next to the Markdown file so
+//! its image links keep working.
+
+use crate::markdown::Node;
+use crate::parsing::markdown_export::export_markdown;
+use anyhow::{Context, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Finds the extracted file of an image by its URL in the book
+pub type ImageFile<'a> = &'a dyn Fn(&str) -> Option<PathBuf>;
+
+/// Where exports go unless `export_directory` is set
+pub const DEFAULT_EXPORT_DIRECTORY: &str = "~/bookokrat-exports";
+
+/// Write `nodes` as Markdown into `directory`, copying the images
+/// `image_file` finds next to it
+pub fn write_markdown(
+    directory: &Path,
+    title: &str,
+    nodes: &[Node],
+    image_file: Option<ImageFile>,
+) -> Result<PathBuf> {
+    let path = unused_path(directory, &file_stem(title), "md")?;
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let images_dir_name = format!("{stem}_images");
+    let images_dir = directory.join(&images_dir_name);
+
+    // Image URL to its path relative to the Markdown file
+    let copied: RefCell<HashMap<String, String>> = RefCell::default();
+    let copy_error = RefCell::new(None);
+    let image_url = |url: &str| -> String {
+        let Some(image_file) = image_file else {
+            return url.to_string();
+        };
+        if let Some(relative) = copied.borrow().get(url) {
+            return relative.clone();
+        }
+        let Some(source) = image_file(url) else {
+            return url.to_string();
+        };
+        let name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "image".to_string());
+        // Keep images of the same name from different folders apart
+        let taken = copied.borrow().len();
+        let name = if copied
+            .borrow()
+            .values()
+            .any(|relative| relative.ends_with(&format!("/{name}")))
+        {
+            format!("{taken}-{name}")
+        } else {
+            name
+        };
+        let copy =
+            fs::create_dir_all(&images_dir).and_then(|_| fs::copy(&source, images_dir.join(&name)));
+        if let Err(e) = copy {
+            copy_error
+                .borrow_mut()
+                .get_or_insert(format!("cannot copy image {}: {e}", source.display()));
+            return url.to_string();
+        }
+        let relative = format!("{images_dir_name}/{name}");
+        copied
+            .borrow_mut()
+            .insert(url.to_string(), relative.clone());
+        relative
+    };
+
+    let markdown = format!("# {title}\n\n{}", export_markdown(nodes, &image_url));
+    if let Some(e) = copy_error.into_inner() {
+        log::warn!("Exporting {title}: {e}");
+    }
+    fs::write(&path, markdown).with_context(|| format!("cannot write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Write the chapter's original HTML into `directory`
+pub fn write_html(directory: &Path, title: &str, html: &str) -> Result<PathBuf> {
+    let path = unused_path(directory, &file_stem(title), "html")?;
+    fs::write(&path, html).with_context(|| format!("cannot write {}", path.display()))?;
+    Ok(path)
+}
+
+/// The title without characters file systems reject
+fn file_stem(title: &str) -> String {
+    let stem: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let stem = stem.split_whitespace().collect::<Vec<_>>().join(" ");
+    let stem = stem.trim_matches('.').trim();
+    if stem.is_empty() {
+        "chapter".to_string()
+    } else {
+        stem.chars().take(100).collect()
+    }
+}
+
+/// `stem.extension` in `directory`, numbered when that is taken so
+/// earlier exports are kept
+fn unused_path(directory: &Path, stem: &str, extension: &str) -> Result<PathBuf> {
+    fs::create_dir_all(directory)
+        .with_context(|| format!("cannot create {}", directory.display()))?;
+    let mut path = directory.join(format!("{stem}.{extension}"));
+    let mut number = 2;
+    while path.exists() {
+        path = directory.join(format!("{stem} ({number}).{extension}"));
+        number += 1;
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    #[test]
+    fn test_export_copies_images_and_keeps_earlier_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let extracted = dir.path().join("extracted");
+        fs::create_dir_all(&extracted).unwrap();
+        fs::write(extracted.join("whale.png"), b"png").unwrap();
+        let exports = dir.path().join("exports");
+
+        let doc = HtmlToMarkdownConverter::new().convert(
+            r#"<p>The whale:</p><p><img src="../images/whale.png" alt="whale"/></p>
+            <p><img src="missing.png" alt="gone"/></p>"#,
+        );
+        let image_file = |url: &str| {
+            url.ends_with("whale.png")
+                .then(|| extracted.join("whale.png"))
+        };
+
+        let path = write_markdown(
+            &exports,
+            "Chapter 1: Loomings",
+            &doc.blocks,
+            Some(&image_file),
+        )
+        .unwrap();
+        assert_eq!(path, exports.join("Chapter 1- Loomings.md"));
+        let markdown = fs::read_to_string(&path).unwrap();
+        assert!(markdown.starts_with("# Chapter 1: Loomings\n\nThe whale:"));
+        assert!(markdown.contains("![whale](Chapter%201-%20Loomings_images/whale.png)"));
+        assert!(markdown.contains("![gone](missing.png)"));
+        assert!(
+            exports
+                .join("Chapter 1- Loomings_images/whale.png")
+                .exists()
+        );
+
+        let again = write_html(&exports, "Chapter 1: Loomings", "<p>Raw</p>").unwrap();
+        assert_eq!(again, exports.join("Chapter 1- Loomings.html"));
+        let third = write_markdown(&exports, "Chapter 1: Loomings", &doc.blocks, None).unwrap();
+        assert_eq!(third, exports.join("Chapter 1- Loomings (2).md"));
+        assert!(
+            fs::read_to_string(third)
+                .unwrap()
+                .contains("![whale](../images/whale.png)")
+        );
+    }
+}
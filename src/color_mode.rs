@@ -1,23 +1,214 @@
+use once_cell::sync::Lazy;
 use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
 use std::env;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::time::Duration;
+
+/// How many colors the terminal can show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorLevel {
+    Ansi8,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+/// The `color_mode` setting: automatic detection, a forced color level, or no color
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorMode {
+    #[default]
+    Auto,
+    Truecolor,
+    Ansi256,
+    Ansi16,
+    Ansi8,
+    /// Monochrome: only bold, underline, dim and reverse attributes
+    None,
+}
+
+static DETECTED_LEVEL: Lazy<ColorLevel> = Lazy::new(|| {
+    detect_color_level(
+        env::var("COLORTERM").ok().as_deref(),
+        env::var("TERM").ok().as_deref(),
+    )
+});
+
+/// Forced color level, 0 when auto-detected
+static FORCED_LEVEL: AtomicU8 = AtomicU8::new(0);
+static MONOCHROME: AtomicBool = AtomicBool::new(false);
+/// Detected terminal background: 0 unknown, 1 dark, 2 light
+static BACKGROUND: AtomicU8 = AtomicU8::new(0);
+
+/// Whether the terminal draws dark text on a light background or the reverse
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TerminalBackground {
+    Dark,
+    Light,
+}
+
+/// Apply the color mode. Must run before the first theme palette is built.
+pub fn set_color_mode(mode: ColorMode) {
+    // Monochrome keeps full-color palettes internally so the frame can still
+    // tell text, muted text and accents apart before the colors are stripped
+    let forced = match mode {
+        ColorMode::Auto => 0,
+        ColorMode::Ansi8 => 1,
+        ColorMode::Ansi16 => 2,
+        ColorMode::Ansi256 => 3,
+        ColorMode::Truecolor | ColorMode::None => 4,
+    };
+    FORCED_LEVEL.store(forced, Ordering::Relaxed);
+    MONOCHROME.store(mode == ColorMode::None, Ordering::Relaxed);
+}
+
+pub fn color_level() -> ColorLevel {
+    match FORCED_LEVEL.load(Ordering::Relaxed) {
+        1 => ColorLevel::Ansi8,
+        2 => ColorLevel::Ansi16,
+        3 => ColorLevel::Ansi256,
+        4 => ColorLevel::TrueColor,
+        _ => *DETECTED_LEVEL,
+    }
+}
+
+/// True when colors are off and styling falls back to text attributes
+pub fn is_monochrome() -> bool {
+    MONOCHROME.load(Ordering::Relaxed)
+}
+
+/// `NO_COLOR` (https://no-color.org) is set to a non-empty value
+pub fn no_color_env() -> bool {
+    env::var("NO_COLOR").is_ok_and(|value| !value.is_empty())
+}
 
 /// Detect if the terminal supports true color (24-bit RGB)
 pub fn supports_true_color() -> bool {
-    if let Ok(colorterm) = env::var("COLORTERM") {
-        let colorterm_lower = colorterm.to_lowercase();
-        if colorterm_lower == "truecolor" || colorterm_lower == "24bit" {
-            return true;
-        }
+    !is_monochrome() && color_level() == ColorLevel::TrueColor
+}
+
+/// Ask the terminal for its background color, falling back to `COLORFGBG`,
+/// and remember the answer for `terminal_background`. Must run in raw mode
+/// before terminal events are read.
+pub fn detect_terminal_background() -> Option<TerminalBackground> {
+    let background = query_background_color(Duration::from_millis(500))
+        .map(|(r, g, b)| background_of(r, g, b))
+        .or_else(|| background_from_colorfgbg(env::var("COLORFGBG").ok()?.as_str()));
+    BACKGROUND.store(
+        match background {
+            None => 0,
+            Some(TerminalBackground::Dark) => 1,
+            Some(TerminalBackground::Light) => 2,
+        },
+        Ordering::Relaxed,
+    );
+    background
+}
+
+/// The background found by `detect_terminal_background`, if any
+pub fn terminal_background() -> Option<TerminalBackground> {
+    match BACKGROUND.load(Ordering::Relaxed) {
+        1 => Some(TerminalBackground::Dark),
+        2 => Some(TerminalBackground::Light),
+        _ => None,
     }
+}
+
+/// Send an OSC 11 background color query followed by a device status
+/// report, which every terminal answers, so reading stops even when the
+/// first query is ignored
+#[cfg(not(windows))]
+fn query_background_color(timeout: Duration) -> Option<(f32, f32, f32)> {
+    use std::io::{Read, Write};
+    use std::sync::mpsc;
 
-    if let Ok(term) = env::var("TERM") {
-        let term_lower = term.to_lowercase();
-        if term_lower.contains("truecolor") || term_lower.contains("24bit") {
-            return true;
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let mut stdout = std::io::stdout();
+        if stdout
+            .write_all(b"\x1b]11;?\x07\x1b[5n")
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return;
         }
+        let mut response = Vec::new();
+        let mut byte = [0u8; 1];
+        while response.len() < 256 && std::io::stdin().read(&mut byte).is_ok_and(|n| n == 1) {
+            response.push(byte[0]);
+            if response.ends_with(b"\x1b[0n") {
+                break;
+            }
+        }
+        let _ = sender.send(String::from_utf8_lossy(&response).into_owned());
+    });
+    parse_background_response(&receiver.recv_timeout(timeout).ok()?)
+}
+
+#[cfg(windows)]
+fn query_background_color(_timeout: Duration) -> Option<(f32, f32, f32)> {
+    None
+}
+
+/// The color of an `ESC ] 11 ; rgb:RRRR/GGGG/BBBB` reply, each channel
+/// scaled to 0-1 whatever its number of hex digits
+fn parse_background_response(response: &str) -> Option<(f32, f32, f32)> {
+    let start = response.find("]11;rgb:")? + "]11;rgb:".len();
+    let color = response[start..].split(['\x07', '\x1b']).next()?;
+    let mut channels = color.split('/').map(|channel| {
+        let value = u32::from_str_radix(channel, 16).ok()?;
+        let max = 16u32.checked_pow(channel.len() as u32)? - 1;
+        (1..=4)
+            .contains(&channel.len())
+            .then(|| value as f32 / max as f32)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    Some((r, g, b))
+}
+
+fn background_of(r: f32, g: f32, b: f32) -> TerminalBackground {
+    if 0.2126 * r + 0.7152 * g + 0.0722 * b > 0.5 {
+        TerminalBackground::Light
+    } else {
+        TerminalBackground::Dark
+    }
+}
+
+/// `COLORFGBG` ("15;0", or "15;default;0" in rxvt) ends with the ANSI
+/// index of the background color
+fn background_from_colorfgbg(value: &str) -> Option<TerminalBackground> {
+    match value.rsplit(';').next()?.parse::<u8>().ok()? {
+        7 | 9..=15 => Some(TerminalBackground::Light),
+        0..=6 | 8 => Some(TerminalBackground::Dark),
+        _ => None,
+    }
+}
+
+/// Color level from the `COLORTERM` and `TERM` environment variables.
+/// Unknown terminals are assumed to handle 256 colors.
+pub fn detect_color_level(colorterm: Option<&str>, term: Option<&str>) -> ColorLevel {
+    let colorterm = colorterm.unwrap_or_default().to_lowercase();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return ColorLevel::TrueColor;
     }
 
-    false
+    let term = term.unwrap_or_default().to_lowercase();
+    if term.contains("truecolor") || term.contains("24bit") || term.contains("direct") {
+        ColorLevel::TrueColor
+    } else if term.contains("256color") {
+        ColorLevel::Ansi256
+    } else if term.contains("16color") || term == "linux" {
+        ColorLevel::Ansi16
+    } else if matches!(
+        term.as_str(),
+        "xterm" | "xterm-color" | "screen" | "vt100" | "vt220" | "ansi" | "rxvt"
+    ) || term.ends_with("-8color")
+    {
+        ColorLevel::Ansi8
+    } else {
+        ColorLevel::Ansi256
+    }
 }
 
 /// Convert RGB color to nearest 256-color palette index
@@ -74,16 +265,136 @@ fn rgb_to_256color(r: u8, g: u8, b: u8) -> u8 {
     cube_color
 }
 
-pub fn smart_color(rgb: u32) -> Color {
-    if supports_true_color() {
-        Color::from_u32(rgb)
+/// Map an RGB color to the nearest ANSI color. Saturated colors keep their
+/// hue so accents never collapse into gray; grays map by brightness.
+fn rgb_to_ansi(r: u8, g: u8, b: u8, level: ColorLevel) -> Color {
+    let max = r.max(g).max(b) as f32 / 255.0;
+    let min = r.min(g).min(b) as f32 / 255.0;
+    let lightness = (max + min) / 2.0;
+    let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+    let bright = level >= ColorLevel::Ansi16;
+
+    if saturation < 0.25 || !(0.2..=0.9).contains(&lightness) {
+        return match level {
+            ColorLevel::Ansi8 if lightness < 0.35 => Color::Black,
+            ColorLevel::Ansi8 => Color::Gray,
+            _ if lightness < 0.2 => Color::Black,
+            _ if lightness < 0.45 => Color::DarkGray,
+            _ if lightness < 0.8 => Color::Gray,
+            _ => Color::White,
+        };
+    }
+
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    let delta = r.max(g).max(b) - r.min(g).min(b);
+    let hue = if r >= g && r >= b {
+        60.0 * (((g - b) / delta).rem_euclid(6.0))
+    } else if g >= b {
+        60.0 * ((b - r) / delta + 2.0)
     } else {
-        let r = ((rgb >> 16) & 0xFF) as u8;
-        let g = ((rgb >> 8) & 0xFF) as u8;
-        let b = (rgb & 0xFF) as u8;
+        60.0 * ((r - g) / delta + 4.0)
+    };
 
-        let color_index = rgb_to_256color(r, g, b);
-        Color::Indexed(color_index)
+    let light = bright && lightness > 0.6;
+    match hue {
+        h if !(30.0..330.0).contains(&h) => {
+            if light {
+                Color::LightRed
+            } else {
+                Color::Red
+            }
+        }
+        h if h < 90.0 => {
+            if light {
+                Color::LightYellow
+            } else {
+                Color::Yellow
+            }
+        }
+        h if h < 150.0 => {
+            if light {
+                Color::LightGreen
+            } else {
+                Color::Green
+            }
+        }
+        h if h < 210.0 => {
+            if light {
+                Color::LightCyan
+            } else {
+                Color::Cyan
+            }
+        }
+        h if h < 270.0 => {
+            if light {
+                Color::LightBlue
+            } else {
+                Color::Blue
+            }
+        }
+        _ => {
+            if light {
+                Color::LightMagenta
+            } else {
+                Color::Magenta
+            }
+        }
+    }
+}
+
+/// Represent an RGB color at the given color level
+pub fn color_for_level(rgb: u32, level: ColorLevel) -> Color {
+    let r = ((rgb >> 16) & 0xFF) as u8;
+    let g = ((rgb >> 8) & 0xFF) as u8;
+    let b = (rgb & 0xFF) as u8;
+
+    match level {
+        ColorLevel::TrueColor => Color::from_u32(rgb),
+        ColorLevel::Ansi256 => Color::Indexed(rgb_to_256color(r, g, b)),
+        ColorLevel::Ansi16 | ColorLevel::Ansi8 => rgb_to_ansi(r, g, b, level),
+    }
+}
+
+pub fn smart_color(rgb: u32) -> Color {
+    color_for_level(rgb, color_level())
+}
+
+/// Downsample a color that is too rich for `level`, e.g. a hard-coded RGB value
+pub fn downsample(color: Color, level: ColorLevel) -> Color {
+    match (color, level) {
+        (_, ColorLevel::TrueColor) => color,
+        (Color::Rgb(r, g, b), _) => {
+            color_for_level(((r as u32) << 16) | ((g as u32) << 8) | b as u32, level)
+        }
+        (Color::Indexed(index), ColorLevel::Ansi16 | ColorLevel::Ansi8) if index >= 16 => {
+            let (r, g, b) = indexed_to_rgb(index);
+            rgb_to_ansi(r, g, b, level)
+        }
+        (Color::DarkGray, ColorLevel::Ansi8) => Color::Black,
+        (Color::White, ColorLevel::Ansi8) => Color::Gray,
+        (Color::LightRed, ColorLevel::Ansi8) => Color::Red,
+        (Color::LightGreen, ColorLevel::Ansi8) => Color::Green,
+        (Color::LightYellow, ColorLevel::Ansi8) => Color::Yellow,
+        (Color::LightBlue, ColorLevel::Ansi8) => Color::Blue,
+        (Color::LightMagenta, ColorLevel::Ansi8) => Color::Magenta,
+        (Color::LightCyan, ColorLevel::Ansi8) => Color::Cyan,
+        _ => color,
+    }
+}
+
+/// RGB of a 256-color index from the standard xterm cube and grayscale ramp
+fn indexed_to_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        16..=231 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = index - 16;
+            (level(i / 36), level((i / 6) % 6), level(i % 6))
+        }
+        232..=255 => {
+            let v = 8 + (index - 232) * 10;
+            (v, v, v)
+        }
+        _ => (128, 128, 128),
     }
 }
 
@@ -120,6 +431,74 @@ mod tests {
         assert!(gray_idx >= 232); // Grayscale palette
     }
 
+    #[test]
+    fn test_detect_color_level() {
+        assert_eq!(
+            detect_color_level(Some("truecolor"), Some("xterm")),
+            ColorLevel::TrueColor
+        );
+        assert_eq!(
+            detect_color_level(None, Some("xterm-256color")),
+            ColorLevel::Ansi256
+        );
+        assert_eq!(detect_color_level(None, Some("linux")), ColorLevel::Ansi16);
+        assert_eq!(detect_color_level(None, Some("xterm")), ColorLevel::Ansi8);
+        assert_eq!(detect_color_level(None, None), ColorLevel::Ansi256);
+    }
+
+    #[test]
+    fn test_ansi_mapping_keeps_accent_hues() {
+        // Muted Oceanic Next blue and green must not collapse into gray
+        assert_eq!(color_for_level(0x6699CC, ColorLevel::Ansi16), Color::Blue);
+        assert_eq!(color_for_level(0x99C794, ColorLevel::Ansi8), Color::Green);
+        assert_eq!(
+            color_for_level(0xFAC863, ColorLevel::Ansi16),
+            Color::LightYellow
+        );
+        assert_eq!(color_for_level(0xFAC863, ColorLevel::Ansi8), Color::Yellow);
+        // Dark backgrounds stay black
+        assert_eq!(color_for_level(0x1B2B34, ColorLevel::Ansi16), Color::Black);
+    }
+
+    #[test]
+    fn test_background_from_terminal_replies() {
+        let reply = "\x1b]11;rgb:fdfd/f6f6/e3e3\x1b\\\x1b[0n";
+        let (r, g, b) = parse_background_response(reply).unwrap();
+        assert_eq!(background_of(r, g, b), TerminalBackground::Light);
+
+        let reply = "\x1b]11;rgb:1b/2b/34\x07\x1b[0n";
+        let (r, g, b) = parse_background_response(reply).unwrap();
+        assert!((r - 27.0 / 255.0).abs() < 1e-6);
+        assert_eq!(background_of(r, g, b), TerminalBackground::Dark);
+
+        // Terminals without OSC 11 only answer the status report
+        assert_eq!(parse_background_response("\x1b[0n"), None);
+        assert_eq!(parse_background_response("\x1b]11;rgb:zz/00/00\x07"), None);
+
+        assert_eq!(
+            background_from_colorfgbg("0;15"),
+            Some(TerminalBackground::Light)
+        );
+        assert_eq!(
+            background_from_colorfgbg("15;default;0"),
+            Some(TerminalBackground::Dark)
+        );
+        assert_eq!(background_from_colorfgbg("15;default"), None);
+    }
+
+    #[test]
+    fn test_downsample_stray_colors() {
+        assert_eq!(
+            downsample(Color::Rgb(10, 10, 10), ColorLevel::Ansi16),
+            Color::Black
+        );
+        assert_eq!(downsample(Color::LightCyan, ColorLevel::Ansi8), Color::Cyan);
+        assert_eq!(
+            downsample(Color::Rgb(1, 2, 3), ColorLevel::TrueColor),
+            Color::Rgb(1, 2, 3)
+        );
+    }
+
     #[test]
     fn test_rgb_to_256color_mixed() {
         // Test a mid-tone color
@@ -5,7 +5,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(tag = "target_kind", rename_all = "snake_case")]
 pub enum CommentTarget {
     Paragraph {
@@ -18,6 +18,8 @@ pub enum CommentTarget {
         /// Inclusive line range within the code block.
         line_range: (usize, usize),
     },
+    /// An image in the node, by its source
+    Image { paragraph_index: usize, src: String },
 }
 
 impl CommentTarget {
@@ -28,6 +30,9 @@ impl CommentTarget {
             }
             | CommentTarget::CodeBlock {
                 paragraph_index, ..
+            }
+            | CommentTarget::Image {
+                paragraph_index, ..
             } => *paragraph_index,
         }
     }
@@ -35,13 +40,13 @@ impl CommentTarget {
     pub fn word_range(&self) -> Option<(usize, usize)> {
         match self {
             CommentTarget::Paragraph { word_range, .. } => *word_range,
-            CommentTarget::CodeBlock { .. } => None,
+            CommentTarget::CodeBlock { .. } | CommentTarget::Image { .. } => None,
         }
     }
 
     pub fn line_range(&self) -> Option<(usize, usize)> {
         match self {
-            CommentTarget::Paragraph { .. } => None,
+            CommentTarget::Paragraph { .. } | CommentTarget::Image { .. } => None,
             CommentTarget::CodeBlock { line_range, .. } => Some(*line_range),
         }
     }
@@ -50,6 +55,21 @@ impl CommentTarget {
         match self {
             CommentTarget::Paragraph { .. } => 0,
             CommentTarget::CodeBlock { .. } => 1,
+            CommentTarget::Image { .. } => 2,
+        }
+    }
+
+    pub fn set_node_index(&mut self, node_index: usize) {
+        match self {
+            CommentTarget::Paragraph {
+                paragraph_index, ..
+            }
+            | CommentTarget::CodeBlock {
+                paragraph_index, ..
+            }
+            | CommentTarget::Image {
+                paragraph_index, ..
+            } => *paragraph_index = node_index,
         }
     }
 
@@ -59,6 +79,15 @@ impl CommentTarget {
                 .map(|(start, end)| (start, end))
                 .unwrap_or((0, 0)),
             CommentTarget::CodeBlock { line_range, .. } => *line_range,
+            CommentTarget::Image { .. } => (0, 0),
+        }
+    }
+
+    /// Source of the image an image comment is on
+    pub fn image_src(&self) -> Option<&str> {
+        match self {
+            CommentTarget::Image { src, .. } => Some(src),
+            CommentTarget::Paragraph { .. } | CommentTarget::CodeBlock { .. } => None,
         }
     }
 }
@@ -69,6 +98,46 @@ pub struct Comment {
     pub target: CommentTarget,
     pub content: String,
     pub updated_at: DateTime<Utc>,
+    /// Normalized prefix of the commented node's text, used to re-anchor the
+    /// comment when node indices shift between editions of a book.
+    pub fingerprint: Option<String>,
+}
+
+/// Maximum number of characters kept in a comment fingerprint.
+const FINGERPRINT_LEN: usize = 80;
+
+/// How far (in nodes) to look around the stored index before scanning the whole chapter.
+const RELOCATION_WINDOW: usize = 10;
+
+/// Builds the fingerprint for a node's text: lowercased, whitespace-collapsed
+/// and truncated. Returns `None` for text without any content.
+pub fn text_fingerprint(text: &str) -> Option<String> {
+    let normalized: String = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .chars()
+        .take(FINGERPRINT_LEN)
+        .collect();
+
+    if normalized.is_empty() {
+        None
+    } else {
+        Some(normalized)
+    }
+}
+
+/// Whether a comment still points at the node it was written for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AnchorStatus {
+    /// The stored node index matches (or the comment predates fingerprints).
+    #[default]
+    Anchored,
+    /// The node moved and the comment was re-anchored by its fingerprint.
+    Relocated,
+    /// No node in the chapter matches the fingerprint anymore.
+    Orphaned,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -78,6 +147,8 @@ struct CommentModernSerde {
     pub target: CommentTarget,
     pub content: String,
     pub updated_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -108,6 +179,7 @@ impl From<CommentLegacySerde> for Comment {
             },
             content: legacy.content,
             updated_at: legacy.updated_at,
+            fingerprint: None,
         }
     }
 }
@@ -119,6 +191,7 @@ impl From<CommentModernSerde> for Comment {
             target: modern.target,
             content: modern.content,
             updated_at: modern.updated_at,
+            fingerprint: modern.fingerprint,
         }
     }
 }
@@ -130,6 +203,7 @@ impl From<&Comment> for CommentModernSerde {
             target: comment.target.clone(),
             content: comment.content.clone(),
             updated_at: comment.updated_at,
+            fingerprint: comment.fingerprint.clone(),
         }
     }
 }
@@ -169,19 +243,64 @@ impl Comment {
     }
 }
 
+/// A book's comments file, as listed in the comment storage popup
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommentFileInfo {
+    pub path: PathBuf,
+    pub size: u64,
+    /// None when the file can't be read as comments
+    pub comment_count: Option<usize>,
+}
+
+impl CommentFileInfo {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+/// What merging another comments file into a book's did
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MergeSummary {
+    /// Comments on passages the book had no comment on
+    pub added: usize,
+    /// Comments on passages that already had a different one; both are kept
+    pub conflicts: usize,
+    /// Comments the book already had
+    pub duplicates: usize,
+}
+
+/// Subdirectory of the comments directory that deleted files are moved to
+const TRASH_DIR: &str = "trash";
+
 pub struct BookComments {
     pub file_path: PathBuf,
     comments: Vec<Comment>,
     // chapter_href -> node_index -> comment indices
     comments_by_location: HashMap<String, HashMap<usize, Vec<usize>>>,
+    // Anchor checks made this session; comments not listed are assumed anchored
+    anchor_statuses: HashMap<CommentKey, AnchorStatus>,
+}
+
+/// Identifies a comment independently of the node it is anchored to, so two
+/// comments on one passage, or one relocated onto another's, stay apart
+type CommentKey = (String, DateTime<Utc>);
+
+fn comment_key(comment: &Comment) -> CommentKey {
+    (comment.chapter_href.clone(), comment.updated_at)
 }
 
 impl BookComments {
     pub fn new(book_path: &Path) -> Result<Self> {
-        let book_hash = Self::compute_book_hash(book_path);
-        let comments_dir = Self::get_comments_dir()?;
-        let file_path = comments_dir.join(format!("book_{book_hash}.yaml"));
-        Self::new_with_path(file_path)
+        let comments_dir = Self::comments_dir()?;
+        Self::new_with_path(comments_dir.join(Self::file_name_for(book_path)))
+    }
+
+    /// Name of the book's comments file in the comments directory
+    pub fn file_name_for(book_path: &Path) -> String {
+        format!("book_{}.yaml", Self::compute_book_hash(book_path))
     }
 
     #[cfg(test)]
@@ -194,7 +313,8 @@ impl BookComments {
         Self::new_with_path(file_path)
     }
 
-    fn new_with_path(file_path: PathBuf) -> Result<Self> {
+    /// The comments kept in `file_path`, which need not exist yet
+    pub fn new_with_path(file_path: PathBuf) -> Result<Self> {
         let comments = if file_path.exists() {
             Self::load_from_file(&file_path)?
         } else {
@@ -205,6 +325,7 @@ impl BookComments {
             file_path,
             comments: Vec::new(),
             comments_by_location: HashMap::new(),
+            anchor_statuses: HashMap::new(),
         };
 
         for comment in comments {
@@ -216,7 +337,10 @@ impl BookComments {
     }
 
     pub fn add_comment(&mut self, comment: Comment) -> Result<()> {
-        if matches!(comment.target, CommentTarget::Paragraph { .. }) {
+        if matches!(
+            comment.target,
+            CommentTarget::Paragraph { .. } | CommentTarget::Image { .. }
+        ) {
             if let Some(existing_idx) =
                 self.find_comment_index(&comment.chapter_href, &comment.target)
             {
@@ -243,8 +367,15 @@ impl BookComments {
             .find_comment_index(chapter_href, target)
             .context("Comment not found")?;
 
+        let status = self
+            .anchor_statuses
+            .remove(&comment_key(&self.comments[idx]));
         self.comments[idx].content = new_content;
         self.comments[idx].updated_at = Utc::now();
+        if let Some(status) = status {
+            self.anchor_statuses
+                .insert(comment_key(&self.comments[idx]), status);
+        }
 
         self.save_to_disk()
     }
@@ -254,7 +385,8 @@ impl BookComments {
             .find_comment_index(chapter_href, target)
             .context("Comment not found")?;
 
-        let _comment = self.comments.remove(idx);
+        let comment = self.comments.remove(idx);
+        self.anchor_statuses.remove(&comment_key(&comment));
 
         self.rebuild_indices();
 
@@ -282,10 +414,133 @@ impl BookComments {
             .unwrap_or_default()
     }
 
+    /// Number of comments in each chapter that has any, keyed by chapter href
+    pub fn chapter_comment_counts(&self) -> HashMap<String, usize> {
+        self.comments_by_location
+            .iter()
+            .map(|(chapter_href, nodes)| (chapter_href.clone(), nodes.values().map(Vec::len).sum()))
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+
     pub fn get_all_comments(&self) -> &[Comment] {
         &self.comments
     }
 
+    pub fn anchor_status(&self, comment: &Comment) -> AnchorStatus {
+        self.anchor_statuses
+            .get(&comment_key(comment))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Verify the comments of a chapter against its current node texts and
+    /// re-anchor the ones whose node moved. Comments saved without a
+    /// fingerprint adopt the node they currently point at.
+    pub fn relocate_chapter_comments(
+        &mut self,
+        chapter_href: &str,
+        node_texts: &[String],
+    ) -> Result<()> {
+        let node_fingerprints: Vec<Option<String>> = node_texts
+            .iter()
+            .map(|text| text_fingerprint(text))
+            .collect();
+        let mut changed = false;
+
+        for comment in self
+            .comments
+            .iter_mut()
+            .filter(|c| c.chapter_href == chapter_href)
+        {
+            let stored_index = comment.node_index();
+            let Some(fingerprint) = comment.fingerprint.clone() else {
+                if let Some(Some(current)) = node_fingerprints.get(stored_index) {
+                    comment.fingerprint = Some(current.clone());
+                    changed = true;
+                }
+                continue;
+            };
+
+            let key = comment_key(comment);
+            let status =
+                match Self::find_relocated_index(&node_fingerprints, &fingerprint, stored_index) {
+                    Some(idx) if idx == stored_index => self
+                        .anchor_statuses
+                        .get(&key)
+                        .copied()
+                        .filter(|status| *status == AnchorStatus::Relocated)
+                        .unwrap_or(AnchorStatus::Anchored),
+                    Some(idx) => {
+                        comment.target.set_node_index(idx);
+                        changed = true;
+                        AnchorStatus::Relocated
+                    }
+                    None => AnchorStatus::Orphaned,
+                };
+
+            self.anchor_statuses.insert(key, status);
+        }
+
+        if changed {
+            self.sort_comments();
+            self.save_to_disk()?;
+        }
+
+        Ok(())
+    }
+
+    /// Node of `node_texts` the comment belongs to: its own while the text
+    /// there still matches, else the nearby node the text moved to. None
+    /// when the commented passage is gone.
+    pub fn locate_in(comment: &Comment, node_texts: &[String]) -> Option<usize> {
+        let stored_index = comment.node_index();
+        let Some(fingerprint) = &comment.fingerprint else {
+            return (stored_index < node_texts.len()).then_some(stored_index);
+        };
+        let node_fingerprints: Vec<Option<String>> = node_texts
+            .iter()
+            .map(|text| text_fingerprint(text))
+            .collect();
+        Self::find_relocated_index(&node_fingerprints, fingerprint, stored_index)
+    }
+
+    fn find_relocated_index(
+        node_fingerprints: &[Option<String>],
+        fingerprint: &str,
+        stored_index: usize,
+    ) -> Option<usize> {
+        let matches = |idx: usize| {
+            node_fingerprints
+                .get(idx)
+                .and_then(|fp| fp.as_deref())
+                .is_some_and(|fp| fp == fingerprint)
+        };
+
+        if matches(stored_index) {
+            return Some(stored_index);
+        }
+
+        for distance in 1..=RELOCATION_WINDOW {
+            if let Some(idx) = stored_index.checked_sub(distance) {
+                if matches(idx) {
+                    return Some(idx);
+                }
+            }
+            if matches(stored_index + distance) {
+                return Some(stored_index + distance);
+            }
+        }
+
+        // Further away only a unique match is trusted: short or repeated
+        // paragraphs ("* * *", an epigraph) would re-anchor to the wrong node
+        let mut far_matches = (0..node_fingerprints.len()).filter(|&idx| matches(idx));
+        match (far_matches.next(), far_matches.next()) {
+            (Some(idx), None) => Some(idx),
+            _ => None,
+        }
+    }
+
     fn compute_book_hash(book_path: &Path) -> String {
         let filename = book_path
             .file_name()
@@ -299,9 +554,13 @@ impl BookComments {
         format!("{digest:x}")
     }
 
-    fn get_comments_dir() -> Result<PathBuf> {
+    /// Where comments are kept: BOOKOKRAT_COMMENTS_DIR, the `comments_dir`
+    /// setting, or `.bookokrat_comments` in the current directory
+    pub fn comments_dir() -> Result<PathBuf> {
         let comments_dir = if let Ok(custom_dir) = std::env::var("BOOKOKRAT_COMMENTS_DIR") {
             PathBuf::from(custom_dir)
+        } else if let Some(dir) = crate::settings::get_comments_dir() {
+            crate::widget::onboarding::expand_tilde(&dir)
         } else {
             std::env::current_dir()
                 .context("Could not determine current directory")?
@@ -315,6 +574,158 @@ impl BookComments {
         Ok(comments_dir)
     }
 
+    /// The books' comments files in `dir`, by file name
+    pub fn list_comment_files(dir: &Path) -> Result<Vec<CommentFileInfo>> {
+        let mut files: Vec<CommentFileInfo> = Self::comment_file_paths(dir)?
+            .into_iter()
+            .map(|path| CommentFileInfo {
+                size: fs::metadata(&path).map(|meta| meta.len()).unwrap_or(0),
+                comment_count: Self::load_from_file(&path).ok().map(|c| c.len()),
+                path,
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// A random comment from one of `book_paths`, with the book it is in.
+    /// Books are tried in random order and only their files are read, one
+    /// at a time until one has comments.
+    pub fn random_comment<R: rand::Rng>(
+        dir: &Path,
+        book_paths: &[String],
+        rng: &mut R,
+    ) -> Option<(String, Comment)> {
+        use rand::seq::SliceRandom;
+
+        let mut files: Vec<(&String, PathBuf)> = book_paths
+            .iter()
+            .map(|book| (book, dir.join(Self::file_name_for(Path::new(book)))))
+            .filter(|(_, file)| file.is_file())
+            .collect();
+        files.shuffle(rng);
+
+        files.into_iter().find_map(|(book, file)| {
+            let comments = Self::load_from_file(&file).ok()?;
+            let comment = comments.choose(rng)?.clone();
+            Some((book.clone(), comment))
+        })
+    }
+
+    fn comment_file_paths(dir: &Path) -> Result<Vec<PathBuf>> {
+        let entries =
+            fs::read_dir(dir).with_context(|| format!("Failed to read {}", dir.display()))?;
+        Ok(entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .is_some_and(|name| name.starts_with("book_") && name.ends_with(".yaml"))
+            })
+            .collect())
+    }
+
+    /// Delete a comments file by moving it to the `trash` subdirectory of
+    /// its directory, under a name stamped with the time; returns where it
+    /// went
+    pub fn trash_comment_file(path: &Path) -> Result<PathBuf> {
+        let dir = path.parent().context("Comments file has no directory")?;
+        let trash = dir.join(TRASH_DIR);
+        fs::create_dir_all(&trash).context("Failed to create the trash directory")?;
+
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let stamp = Utc::now().format("%Y%m%d-%H%M%S");
+        let mut target = trash.join(format!("{stem}.{stamp}.yaml"));
+        let mut number = 2;
+        while target.exists() {
+            target = trash.join(format!("{stem}.{stamp}-{number}.yaml"));
+            number += 1;
+        }
+        fs::rename(path, &target)
+            .with_context(|| format!("Failed to move {} to the trash", path.display()))?;
+        Ok(target)
+    }
+
+    /// Add the comments of another file, e.g. one synced from another
+    /// machine. Comments already here are skipped; one on a passage that has
+    /// a different comment is kept next to it.
+    pub fn merge_from_file(&mut self, source: &Path) -> Result<MergeSummary> {
+        if !source.is_file() {
+            anyhow::bail!("{} is not a file", source.display());
+        }
+        let incoming = Self::load_from_file(source)?;
+        let mut summary = MergeSummary::default();
+
+        for comment in incoming {
+            let mut same_passage = self
+                .comments
+                .iter()
+                .filter(|c| c.matches_location(&comment.chapter_href, &comment.target))
+                .peekable();
+            if same_passage.peek().is_none() {
+                summary.added += 1;
+            } else if same_passage.any(|c| c.content == comment.content) {
+                summary.duplicates += 1;
+                continue;
+            } else {
+                summary.conflicts += 1;
+            }
+            self.comments.push(comment);
+        }
+
+        if summary.added + summary.conflicts > 0 {
+            self.sort_comments();
+            self.save_to_disk()?;
+        }
+        Ok(summary)
+    }
+
+    /// Copy the comments files in `from` to `to` and check that each copy
+    /// reads back the same; returns how many there are. Files already in
+    /// `to` must be identical, which is checked before anything is copied,
+    /// and nothing in `from` is touched.
+    pub fn copy_comments_dir(from: &Path, to: &Path) -> Result<usize> {
+        if to.exists() && fs::canonicalize(from).ok() == fs::canonicalize(to).ok() {
+            anyhow::bail!("Comments are already kept in {}", to.display());
+        }
+        let files = Self::comment_file_paths(from)?;
+        fs::create_dir_all(to).with_context(|| format!("Failed to create {}", to.display()))?;
+
+        let mut copies = Vec::new();
+        for source in &files {
+            let target = to.join(source.file_name().context("Comments file has no name")?);
+            let content =
+                fs::read(source).with_context(|| format!("Failed to read {}", source.display()))?;
+            if target.exists() && fs::read(&target)? != content {
+                anyhow::bail!(
+                    "{} already holds other comments in {}",
+                    target.file_name().unwrap_or_default().to_string_lossy(),
+                    to.display()
+                );
+            }
+            copies.push((target, content));
+        }
+
+        for (target, content) in &copies {
+            if !target.exists() {
+                fs::write(target, content)
+                    .with_context(|| format!("Failed to write {}", target.display()))?;
+            }
+            if fs::read(target)? != *content {
+                anyhow::bail!(
+                    "{} differs from the original after copying",
+                    target.display()
+                );
+            }
+        }
+        Ok(copies.len())
+    }
+
     fn load_from_file(file_path: &Path) -> Result<Vec<Comment>> {
         let content = fs::read_to_string(file_path).context("Failed to read comments file")?;
 
@@ -326,6 +737,9 @@ impl BookComments {
     }
 
     fn save_to_disk(&self) -> Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
         let yaml = serde_yaml::to_string(&self.comments).context("Failed to serialize comments")?;
 
         fs::write(&self.file_path, yaml).context("Failed to write comments file")?;
@@ -404,6 +818,7 @@ mod tests {
             },
             content: content.to_string(),
             updated_at: Utc::now(),
+            fingerprint: None,
         }
     }
 
@@ -421,6 +836,7 @@ mod tests {
             },
             content: content.to_string(),
             updated_at: Utc::now(),
+            fingerprint: None,
         }
     }
 
@@ -473,6 +889,36 @@ mod tests {
         assert!(comments.is_empty());
     }
 
+    #[test]
+    fn test_chapter_comment_counts() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 1, "One"))
+            .unwrap();
+        book_comments
+            .add_comment(create_code_comment("ch1.xhtml", 1, (0, 2), "Two"))
+            .unwrap();
+        let last = create_paragraph_comment("ch3.xhtml", 4, "Three");
+        book_comments.add_comment(last.clone()).unwrap();
+
+        let counts = book_comments.chapter_comment_counts();
+        assert_eq!(counts.len(), 2);
+        assert_eq!(counts["ch1.xhtml"], 2);
+        assert_eq!(counts["ch3.xhtml"], 1);
+
+        book_comments
+            .delete_comment("ch3.xhtml", &last.target)
+            .unwrap();
+        assert!(
+            !book_comments
+                .chapter_comment_counts()
+                .contains_key("ch3.xhtml")
+        );
+    }
+
     #[test]
     fn test_code_block_comments() {
         let (_temp_dir, book_path, comments_dir) = create_test_env();
@@ -515,6 +961,40 @@ mod tests {
         assert_eq!(parsed[0].target, comment.target);
     }
 
+    #[test]
+    fn test_image_comment_roundtrip_and_replace() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        let image_comment = |content: &str| Comment {
+            chapter_href: "chapter.xhtml".to_string(),
+            target: CommentTarget::Image {
+                paragraph_index: 4,
+                src: "images/fig1.png".to_string(),
+            },
+            content: content.to_string(),
+            updated_at: Utc::now(),
+            fingerprint: None,
+        };
+
+        book_comments
+            .add_comment(create_paragraph_comment("chapter.xhtml", 4, "Text"))
+            .unwrap();
+        book_comments.add_comment(image_comment("First")).unwrap();
+        book_comments.add_comment(image_comment("Second")).unwrap();
+
+        let yaml = fs::read_to_string(&book_comments.file_path).unwrap();
+        assert!(yaml.contains("target_kind: image"), "{yaml}");
+        assert!(yaml.contains("src: images/fig1.png"), "{yaml}");
+
+        let reloaded = BookComments::new_with_path(book_comments.file_path.clone()).unwrap();
+        let comments = reloaded.get_node_comments("chapter.xhtml", 4);
+        assert_eq!(comments.len(), 2);
+        assert!(comments[0].is_paragraph_comment());
+        assert_eq!(comments[1].target.image_src(), Some("images/fig1.png"));
+        assert_eq!(comments[1].content, "Second");
+    }
+
     #[test]
     fn test_legacy_comment_deserialize() {
         let legacy_yaml = r#"
@@ -554,4 +1034,373 @@ mod tests {
         assert!(all[1].is_paragraph_comment());
         assert!(matches!(all[2].target, CommentTarget::CodeBlock { .. }));
     }
+
+    fn create_fingerprinted_comment(chapter: &str, node: usize, text: &str) -> Comment {
+        let mut comment = create_paragraph_comment(chapter, node, "note");
+        comment.fingerprint = text_fingerprint(text);
+        comment
+    }
+
+    fn node_texts(texts: &[&str]) -> Vec<String> {
+        texts.iter().map(|text| text.to_string()).collect()
+    }
+
+    #[test]
+    fn test_text_fingerprint_normalizes() {
+        assert_eq!(
+            text_fingerprint("  Hello\n   World  "),
+            Some("hello world".to_string())
+        );
+        assert_eq!(text_fingerprint("   "), None);
+        assert_eq!(
+            text_fingerprint(&"a".repeat(200)).unwrap().chars().count(),
+            FINGERPRINT_LEN
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_serialization_roundtrip() {
+        let comment = create_fingerprinted_comment("chapter.xhtml", 1, "Some paragraph");
+        let yaml = serde_yaml::to_string(&vec![comment.clone()]).unwrap();
+
+        let parsed: Vec<Comment> = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(parsed[0].fingerprint, comment.fingerprint);
+    }
+
+    #[test]
+    fn test_relocate_shifted_paragraph() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        book_comments
+            .add_comment(create_fingerprinted_comment(
+                "chapter.xhtml",
+                1,
+                "The moved paragraph",
+            ))
+            .unwrap();
+
+        let texts = node_texts(&["Intro", "New paragraph", "Another", "The  moved paragraph"]);
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+
+        let comments = book_comments.get_node_comments("chapter.xhtml", 3);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(
+            book_comments.anchor_status(comments[0]),
+            AnchorStatus::Relocated
+        );
+        assert!(
+            book_comments
+                .get_node_comments("chapter.xhtml", 1)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_relocate_marks_unmatched_as_orphaned() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        book_comments
+            .add_comment(create_fingerprinted_comment("chapter.xhtml", 0, "Gone"))
+            .unwrap();
+
+        let texts = node_texts(&["Something else", "Entirely"]);
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+
+        let comments = book_comments.get_node_comments("chapter.xhtml", 0);
+        assert_eq!(
+            book_comments.anchor_status(comments[0]),
+            AnchorStatus::Orphaned
+        );
+    }
+
+    #[test]
+    fn test_relocate_far_away_only_to_a_unique_match() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        book_comments
+            .add_comment(create_fingerprinted_comment("chapter.xhtml", 0, "* * *"))
+            .unwrap();
+
+        let mut texts = vec!["Text".to_string(); 30];
+        texts[15] = "* * *".to_string();
+        texts[25] = "* * *".to_string();
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+
+        let comments = book_comments.get_node_comments("chapter.xhtml", 0);
+        assert_eq!(comments.len(), 1);
+        assert_eq!(
+            book_comments.anchor_status(comments[0]),
+            AnchorStatus::Orphaned
+        );
+
+        texts[25] = "Text".to_string();
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+        let comments = book_comments.get_node_comments("chapter.xhtml", 15);
+        assert_eq!(
+            book_comments.anchor_status(comments[0]),
+            AnchorStatus::Relocated
+        );
+    }
+
+    #[test]
+    fn test_comments_sharing_a_node_keep_their_own_status() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        let mut moved = create_fingerprinted_comment("chapter.xhtml", 1, "Kept");
+        moved.target = CommentTarget::CodeBlock {
+            paragraph_index: 1,
+            line_range: (0, 1),
+        };
+        let mut anchored = create_fingerprinted_comment("chapter.xhtml", 3, "Kept");
+        anchored.target = CommentTarget::CodeBlock {
+            paragraph_index: 3,
+            line_range: (0, 1),
+        };
+        anchored.updated_at = moved.updated_at + chrono::Duration::seconds(1);
+        book_comments.add_comment(moved).unwrap();
+        book_comments.add_comment(anchored).unwrap();
+
+        let texts = node_texts(&["Intro", "New", "Another", "Kept"]);
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+
+        let comments = book_comments.get_node_comments("chapter.xhtml", 3);
+        let statuses: Vec<_> = comments
+            .iter()
+            .map(|comment| book_comments.anchor_status(comment))
+            .collect();
+        assert_eq!(
+            statuses,
+            vec![AnchorStatus::Relocated, AnchorStatus::Anchored]
+        );
+    }
+
+    #[test]
+    fn test_relocate_backfills_legacy_fingerprint() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+
+        book_comments
+            .add_comment(create_paragraph_comment("chapter.xhtml", 1, "legacy"))
+            .unwrap();
+
+        let texts = node_texts(&["First", "Second Paragraph"]);
+        book_comments
+            .relocate_chapter_comments("chapter.xhtml", &texts)
+            .unwrap();
+
+        let comments = book_comments.get_node_comments("chapter.xhtml", 1);
+        assert_eq!(comments[0].fingerprint.as_deref(), Some("second paragraph"));
+        assert_eq!(
+            book_comments.anchor_status(comments[0]),
+            AnchorStatus::Anchored
+        );
+    }
+
+    #[test]
+    fn test_comment_files_are_listed_with_counts() {
+        let (temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 1, "One"))
+            .unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 2, "Two"))
+            .unwrap();
+        fs::write(comments_dir.join("book_broken.yaml"), "not: [comments").unwrap();
+        fs::write(comments_dir.join("notes.txt"), "unrelated").unwrap();
+
+        let files = BookComments::list_comment_files(&comments_dir).unwrap();
+        assert_eq!(files.len(), 2);
+        let own = files
+            .iter()
+            .find(|file| file.file_name() == BookComments::file_name_for(&book_path))
+            .unwrap();
+        assert_eq!(own.comment_count, Some(2));
+        assert_eq!(own.size, fs::metadata(&own.path).unwrap().len());
+        let broken = files
+            .iter()
+            .find(|file| file.file_name() == "book_broken.yaml")
+            .unwrap();
+        assert_eq!(broken.comment_count, None);
+
+        assert!(BookComments::list_comment_files(&temp_dir.path().join("missing")).is_err());
+    }
+
+    #[test]
+    fn test_random_comment_skips_books_without_comments() {
+        use rand::SeedableRng;
+
+        let (temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 1, "One"))
+            .unwrap();
+        let emptied = temp_dir.path().join("emptied.epub");
+        fs::write(comments_dir.join(BookComments::file_name_for(&emptied)), "").unwrap();
+
+        let books = vec![
+            "never_commented.epub".to_string(),
+            emptied.to_string_lossy().into_owned(),
+            book_path.to_string_lossy().into_owned(),
+        ];
+        let mut rng = rand::rngs::StdRng::seed_from_u64(7);
+        for _ in 0..5 {
+            let (book, comment) =
+                BookComments::random_comment(&comments_dir, &books, &mut rng).unwrap();
+            assert_eq!(book, books[2]);
+            assert_eq!(comment.content, "One");
+        }
+        assert!(BookComments::random_comment(&comments_dir, &books[..2], &mut rng).is_none());
+    }
+
+    #[test]
+    fn test_deleted_files_go_to_the_trash() {
+        let (_temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 1, "Keep me safe"))
+            .unwrap();
+        let path = book_comments.file_path.clone();
+        let content = fs::read_to_string(&path).unwrap();
+
+        let trashed = BookComments::trash_comment_file(&path).unwrap();
+        assert!(!path.exists());
+        assert!(trashed.starts_with(comments_dir.join(TRASH_DIR)));
+        assert_eq!(fs::read_to_string(&trashed).unwrap(), content);
+        // The trash isn't listed, and the book starts over without comments
+        assert!(
+            BookComments::list_comment_files(&comments_dir)
+                .unwrap()
+                .is_empty()
+        );
+        let reopened = BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        assert!(reopened.get_all_comments().is_empty());
+
+        // A second deletion in the same second doesn't replace the first
+        fs::write(&path, &content).unwrap();
+        let again = BookComments::trash_comment_file(&path).unwrap();
+        assert_ne!(again, trashed);
+        assert!(trashed.exists() && again.exists());
+
+        assert!(BookComments::trash_comment_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_merge_skips_duplicates_and_keeps_both_on_conflict() {
+        let (temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        let shared = create_paragraph_comment("ch1.xhtml", 1, "Same on both machines");
+        book_comments.add_comment(shared.clone()).unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 2, "Mine"))
+            .unwrap();
+
+        let other_dir = temp_dir.path().join("synced");
+        let mut other = BookComments::new_with_custom_dir(&book_path, &other_dir).unwrap();
+        other.add_comment(shared).unwrap();
+        other
+            .add_comment(create_paragraph_comment("ch1.xhtml", 2, "Theirs"))
+            .unwrap();
+        other
+            .add_comment(create_code_comment("ch2.xhtml", 5, (0, 1), "New"))
+            .unwrap();
+
+        let summary = book_comments.merge_from_file(&other.file_path).unwrap();
+        assert_eq!(
+            summary,
+            MergeSummary {
+                added: 1,
+                conflicts: 1,
+                duplicates: 1,
+            }
+        );
+
+        let reloaded = BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        assert_eq!(reloaded.get_all_comments().len(), 4);
+        let mut contents: Vec<&str> = reloaded
+            .get_node_comments("ch1.xhtml", 2)
+            .iter()
+            .map(|c| c.content.as_str())
+            .collect();
+        contents.sort();
+        assert_eq!(contents, vec!["Mine", "Theirs"]);
+        assert_eq!(reloaded.get_node_comments("ch2.xhtml", 5).len(), 1);
+
+        // Merging the same file again changes nothing
+        let mut reloaded = reloaded;
+        let again = reloaded.merge_from_file(&other.file_path).unwrap();
+        assert_eq!(again.added + again.conflicts, 0);
+        assert_eq!(again.duplicates, 3);
+
+        assert!(
+            reloaded
+                .merge_from_file(&temp_dir.path().join("missing.yaml"))
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_copying_the_directory_verifies_and_never_overwrites() {
+        let (temp_dir, book_path, comments_dir) = create_test_env();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &comments_dir).unwrap();
+        book_comments
+            .add_comment(create_paragraph_comment("ch1.xhtml", 1, "Travels along"))
+            .unwrap();
+        fs::write(comments_dir.join("book_other.yaml"), "").unwrap();
+        let name = BookComments::file_name_for(&book_path);
+
+        let target = temp_dir.path().join("moved");
+        assert_eq!(
+            BookComments::copy_comments_dir(&comments_dir, &target).unwrap(),
+            2
+        );
+        assert_eq!(
+            fs::read(target.join(&name)).unwrap(),
+            fs::read(comments_dir.join(&name)).unwrap()
+        );
+        // The originals stay where they were
+        assert!(comments_dir.join(&name).exists());
+        // Copying again onto identical files is fine
+        assert_eq!(
+            BookComments::copy_comments_dir(&comments_dir, &target).unwrap(),
+            2
+        );
+
+        // A different file of the same name stops the copy before it starts
+        let clashing = temp_dir.path().join("clashing");
+        fs::create_dir_all(&clashing).unwrap();
+        fs::write(clashing.join(&name), "- other comments").unwrap();
+        assert!(BookComments::copy_comments_dir(&comments_dir, &clashing).is_err());
+        assert_eq!(
+            fs::read_to_string(clashing.join(&name)).unwrap(),
+            "- other comments"
+        );
+        assert!(!clashing.join("book_other.yaml").exists());
+
+        assert!(BookComments::copy_comments_dir(&comments_dir, &comments_dir).is_err());
+    }
 }
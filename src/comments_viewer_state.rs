@@ -0,0 +1,109 @@
+//! Where the comments viewer was left in each book.
+//!
+//! The viewer's mode, the chapter selected in its chapter list and the
+//! scroll and selection of each list it showed are kept in
+//! `comments_viewer.json` next to the bookmarks file, keyed by book path, so
+//! reviewing can pick up where it stopped after a restart.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+
+pub const COMMENTS_VIEWER_FILENAME: &str = "comments_viewer.json";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ViewerPosition {
+    /// Listing every comment of the book rather than one chapter's
+    #[serde(default)]
+    pub all_comments: bool,
+    /// Chapter selected in the chapter list
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chapter_href: Option<String>,
+    /// Scroll offset and selected entry of each chapter's list
+    #[serde(default)]
+    pub chapter_positions: HashMap<String, (usize, usize)>,
+    /// Scroll offset and selected entry of the list of every comment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub all_comments_position: Option<(usize, usize)>,
+}
+
+#[derive(Debug, Default)]
+pub struct ViewerPositions {
+    positions: HashMap<String, ViewerPosition>,
+    file_path: Option<String>,
+}
+
+impl ViewerPositions {
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        let Some(path) = file_path else {
+            return Self::default();
+        };
+        let positions = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse comments viewer positions {path}: {e}");
+                HashMap::new()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => {
+                log::error!("Failed to load comments viewer positions from {path}: {e}");
+                HashMap::new()
+            }
+        };
+        Self {
+            positions,
+            file_path: Some(path.to_string()),
+        }
+    }
+
+    pub fn get(&self, book_path: &str) -> Option<&ViewerPosition> {
+        self.positions.get(book_path)
+    }
+
+    pub fn set(&mut self, book_path: &str, position: ViewerPosition) {
+        self.positions.insert(book_path.to_string(), position);
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            fs::write(path, serde_json::to_string_pretty(&self.positions)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions_survive_reloading() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(COMMENTS_VIEWER_FILENAME);
+        let path = path.to_str().unwrap();
+
+        let mut positions = ViewerPositions::load_or_ephemeral(Some(path));
+        assert!(positions.get("moby.epub").is_none());
+        let position = ViewerPosition {
+            all_comments: true,
+            chapter_href: Some("ch03.xhtml".to_string()),
+            chapter_positions: HashMap::from([("ch03.xhtml".to_string(), (12, 4))]),
+            all_comments_position: Some((40, 9)),
+        };
+        positions.set("moby.epub", position.clone());
+        positions.save().unwrap();
+
+        let reloaded = ViewerPositions::load_or_ephemeral(Some(path));
+        assert_eq!(reloaded.get("moby.epub"), Some(&position));
+
+        // A damaged file starts over instead of failing
+        fs::write(path, "{not json").unwrap();
+        assert!(
+            ViewerPositions::load_or_ephemeral(Some(path))
+                .get("moby.epub")
+                .is_none()
+        );
+    }
+}
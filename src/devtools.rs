@@ -0,0 +1,56 @@
+//! Developer tools behind the `devtools` feature: terminal screenshots as
+//! SVG, and replaying `--record` files into one.
+
+use crate::bookmarks::BOOKMARKS_FILENAME;
+use crate::inputs::event_recorder::{ReplayEventSource, load_recording};
+use crate::main_app::{App, run_app_with_event_source};
+use crate::screen_capture::buffer_to_ansi;
+use anyhow::{Context, Result};
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use std::path::{Path, PathBuf};
+
+/// How much faster than recorded a replay runs
+pub const REPLAY_SPEEDUP: u32 = 10;
+
+/// Convert terminal to SVG
+pub fn terminal_to_svg(terminal: &Terminal<TestBackend>) -> String {
+    let ansi_output = buffer_to_ansi(terminal.backend().buffer());
+    let term = anstyle_svg::Term::new();
+    term.render_svg(&ansi_output)
+}
+
+/// Replay a recording headlessly at the recorded terminal size and write the
+/// final screen next to it as an SVG, whose path is returned.
+///
+/// The app runs on a copy of the bookmarks file, so the replay starts from
+/// the current bookmarks but never changes them. Resize events reach the app,
+/// but the screen keeps the size the recording started with.
+pub fn replay(recording: &Path) -> Result<PathBuf> {
+    let (header, events) = load_recording(recording)?;
+
+    let scratch = tempfile::tempdir().context("cannot create a scratch directory")?;
+    let bookmark_file = scratch.path().join(BOOKMARKS_FILENAME);
+    if Path::new(BOOKMARKS_FILENAME).exists() {
+        std::fs::copy(BOOKMARKS_FILENAME, &bookmark_file)
+            .context("cannot copy the bookmarks file")?;
+    }
+    let books_directory = header
+        .books_directory
+        .or_else(crate::settings::get_books_directory)
+        .unwrap_or_else(|| ".".to_string());
+
+    let mut app = App::new_with_config(
+        Some(&books_directory),
+        Some(&bookmark_file.to_string_lossy()),
+        true,
+    );
+    let mut terminal = Terminal::new(TestBackend::new(header.width, header.height))?;
+    let mut event_source = ReplayEventSource::new(events, REPLAY_SPEEDUP);
+    run_app_with_event_source(&mut terminal, &mut app, &mut event_source)?;
+
+    let svg_path = recording.with_extension("svg");
+    std::fs::write(&svg_path, terminal_to_svg(&terminal))
+        .with_context(|| format!("cannot write {}", svg_path.display()))?;
+    Ok(svg_path)
+}
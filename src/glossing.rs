@@ -0,0 +1,451 @@
+//! Rare word glossing for language learners.
+//!
+//! A user-supplied word frequency list (one word per line, most frequent
+//! first) ranks the vocabulary. Each chapter is scanned once on a background
+//! thread; words ranked past the threshold, or missing from the list, are
+//! marked in the text unless the reader has added them to the known words.
+
+use crate::markdown::{Block, Document, Inline, Node, Text, TextOrInline};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+
+pub const KNOWN_WORDS_FILENAME: &str = "known_words.txt";
+
+/// Shorter words are never glossed
+const MIN_WORD_CHARS: usize = 3;
+/// Characters of surrounding text shown on each side of a rare word
+const CONTEXT_CHARS: usize = 30;
+
+/// Rank of each word in a frequency list; 1 is the most frequent
+#[derive(Debug, Default)]
+pub struct WordFrequencies {
+    ranks: HashMap<String, usize>,
+}
+
+impl WordFrequencies {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        let frequencies = Self::parse(&content);
+        if frequencies.ranks.is_empty() {
+            anyhow::bail!("No words found in {}", path.display());
+        }
+        Ok(frequencies)
+    }
+
+    /// Lines hold a word optionally followed by other columns (usually a
+    /// count), which are ignored; `#` starts a comment line
+    pub fn parse(content: &str) -> Self {
+        let mut ranks = HashMap::new();
+        let words = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_whitespace().next());
+        for word in words {
+            let rank = ranks.len() + 1;
+            ranks.entry(normalize(word)).or_insert(rank);
+        }
+        Self { ranks }
+    }
+
+    pub fn rank(&self, word: &str) -> Option<usize> {
+        self.ranks.get(word).copied()
+    }
+}
+
+/// Words the reader no longer wants glossed, one per line next to the
+/// bookmarks file so the list is easy to edit by hand
+#[derive(Debug, Default)]
+pub struct KnownWords {
+    words: BTreeSet<String>,
+    file_path: Option<String>,
+}
+
+impl KnownWords {
+    pub fn ephemeral() -> Self {
+        Self::default()
+    }
+
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        let Some(path) = file_path else {
+            return Self::ephemeral();
+        };
+        let words = match fs::read_to_string(path) {
+            Ok(content) => content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty())
+                .map(normalize)
+                .collect(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeSet::new(),
+            Err(e) => {
+                log::error!("Failed to load known words from {path}: {e}");
+                BTreeSet::new()
+            }
+        };
+        Self {
+            words,
+            file_path: Some(path.to_string()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            let mut content = String::new();
+            for word in &self.words {
+                content.push_str(word);
+                content.push('\n');
+            }
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+
+    /// Returns false if the word was already known
+    pub fn insert(&mut self, word: &str) -> bool {
+        self.words.insert(normalize(word))
+    }
+
+    pub fn contains(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    pub fn snapshot(&self) -> HashSet<String> {
+        self.words.iter().cloned().collect()
+    }
+}
+
+/// Frequency list, threshold and known words for glossing a book
+pub struct Glossary {
+    pub frequencies: Arc<WordFrequencies>,
+    /// Words ranked past this are glossed
+    pub max_rank: usize,
+    pub known_words: KnownWords,
+}
+
+impl Glossary {
+    /// Scan `document` for rare words on a background thread
+    pub fn analyze(&self, document: Arc<Document>) -> GlossAnalysis {
+        GlossAnalysis::start(
+            document,
+            self.frequencies.clone(),
+            self.max_rank,
+            self.known_words.snapshot(),
+        )
+    }
+}
+
+/// First occurrence of a rare word in a chapter
+#[derive(Debug, Clone)]
+pub struct RareWord {
+    pub word: String,
+    /// None when the word is missing from the frequency list
+    pub rank: Option<usize>,
+    /// Top-level block of the first occurrence
+    pub block: usize,
+    /// Text around the first occurrence
+    pub context: String,
+}
+
+/// Rare words of a chapter, per top-level block, for rendering the markers
+#[derive(Debug, Default)]
+pub struct GlossTargets {
+    blocks: HashMap<usize, HashSet<String>>,
+    words: Vec<RareWord>,
+}
+
+impl GlossTargets {
+    pub fn words_in_block(&self, block: usize) -> Option<&HashSet<String>> {
+        self.blocks.get(&block)
+    }
+
+    /// In order of first appearance
+    pub fn words(&self) -> &[RareWord] {
+        &self.words
+    }
+
+    pub fn remove_word(&mut self, word: &str) {
+        self.words.retain(|rare| rare.word != word);
+        for words in self.blocks.values_mut() {
+            words.remove(word);
+        }
+        self.blocks.retain(|_, words| !words.is_empty());
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+/// Rare word scan of one chapter running on a background thread
+pub struct GlossAnalysis {
+    receiver: Receiver<GlossTargets>,
+}
+
+impl GlossAnalysis {
+    pub fn start(
+        document: Arc<Document>,
+        frequencies: Arc<WordFrequencies>,
+        max_rank: usize,
+        known: HashSet<String>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let targets = find_rare_words(&document, &frequencies, max_rank, &known);
+            // The reader may have moved to another chapter meanwhile
+            let _ = sender.send(targets);
+        });
+        Self { receiver }
+    }
+
+    /// The result, once the scan has finished
+    pub fn poll(&self) -> Option<GlossTargets> {
+        self.receiver.try_recv().ok()
+    }
+}
+
+pub fn find_rare_words(
+    document: &Document,
+    frequencies: &WordFrequencies,
+    max_rank: usize,
+    known: &HashSet<String>,
+) -> GlossTargets {
+    let mut targets = GlossTargets::default();
+    let mut seen = HashSet::new();
+
+    for (block_index, node) in document.blocks.iter().enumerate() {
+        let mut text = String::new();
+        collect_block_text(&node.block, &mut text);
+
+        for (range, word) in word_ranges(&text) {
+            if known.contains(&word) {
+                continue;
+            }
+            let rank = frequencies.rank(&word);
+            let is_rare = match rank {
+                Some(rank) => rank > max_rank,
+                // Capitalized words missing from the list are mostly names
+                None => !text[range.clone()].starts_with(char::is_uppercase),
+            };
+            if !is_rare {
+                continue;
+            }
+
+            targets
+                .blocks
+                .entry(block_index)
+                .or_default()
+                .insert(word.clone());
+            if seen.insert(word.clone()) {
+                targets.words.push(RareWord {
+                    word,
+                    rank,
+                    block: block_index,
+                    context: context_around(&text, range),
+                });
+            }
+        }
+    }
+
+    targets
+}
+
+/// Byte ranges of the words in `text` with their lookup form. Words are runs
+/// of letters and apostrophes; short words and possessive endings are dropped.
+pub fn word_ranges(text: &str) -> Vec<(Range<usize>, String)> {
+    let mut words = Vec::new();
+    let mut start = None;
+
+    let is_word_char = |c: char| c.is_alphabetic() || c == '\'' || c == '\u{2019}';
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (start, is_word_char(c)) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                start = None;
+                let token = text[s..i].trim_matches(['\'', '\u{2019}']);
+                let offset = s + text[s..i].find(token).unwrap_or(0);
+                let token = token
+                    .strip_suffix("'s")
+                    .or_else(|| token.strip_suffix("\u{2019}s"))
+                    .unwrap_or(token);
+                if token.chars().count() < MIN_WORD_CHARS {
+                    continue;
+                }
+                words.push((offset..offset + token.len(), normalize(token)));
+            }
+            _ => {}
+        }
+    }
+    words
+}
+
+fn normalize(word: &str) -> String {
+    word.to_lowercase().replace('\u{2019}', "'")
+}
+
+fn context_around(text: &str, range: Range<usize>) -> String {
+    let before: String = {
+        let chars: Vec<char> = text[..range.start]
+            .chars()
+            .rev()
+            .take(CONTEXT_CHARS)
+            .collect();
+        chars.into_iter().rev().collect()
+    };
+    let after: String = text[range.end..].chars().take(CONTEXT_CHARS).collect();
+    let mut context = format!("{before}{}{after}", &text[range.clone()]);
+    context = context.split_whitespace().collect::<Vec<_>>().join(" ");
+    if before.chars().count() == CONTEXT_CHARS {
+        context.insert(0, '…');
+    }
+    if after.chars().count() == CONTEXT_CHARS {
+        context.push('…');
+    }
+    context
+}
+
+fn collect_block_text(block: &Block, out: &mut String) {
+    match block {
+        Block::Heading { content, .. } | Block::Paragraph { content } => collect_text(content, out),
+        Block::Quote {
+            content,
+            attribution,
+            ..
+        } => {
+            collect_nodes_text(content, out);
+            if let Some(attribution) = attribution {
+                collect_text(attribution, out);
+            }
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                collect_nodes_text(&item.content, out);
+            }
+        }
+        Block::Table { header, rows, .. } => {
+            for row in header.iter().chain(rows.iter()) {
+                for cell in &row.cells {
+                    collect_text(&cell.content, out);
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                collect_text(&item.term, out);
+                for definition in &item.definitions {
+                    collect_nodes_text(definition, out);
+                }
+            }
+        }
+        Block::EpubBlock { content, .. } => collect_nodes_text(content, out),
+        Block::Collapsible { summary, content } => {
+            collect_text(summary, out);
+            collect_nodes_text(content, out);
+        }
+        Block::CodeBlock { .. } | Block::ThematicBreak => {}
+    }
+}
+
+fn collect_nodes_text(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        collect_block_text(&node.block, out);
+    }
+}
+
+fn collect_text(text: &Text, out: &mut String) {
+    for item in text.iter() {
+        match item {
+            TextOrInline::Text(node) => out.push_str(&node.content),
+            TextOrInline::Inline(Inline::Link { text, .. }) => collect_text(text, out),
+            TextOrInline::Inline(Inline::Ruby { base, .. }) => out.push_str(base),
+            TextOrInline::Inline(_) => {}
+        }
+    }
+    out.push(' ');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use tempfile::TempDir;
+
+    const FREQUENCIES: &str = "# rank word list\nthe 100\nwalked 90\ndog 80\nquickly 70\nmoor 10\n";
+
+    fn rare_words(html: &str, known: &[&str]) -> GlossTargets {
+        let document = HtmlToMarkdownConverter::new().convert(html);
+        let known = known.iter().map(|word| word.to_string()).collect();
+        find_rare_words(&document, &WordFrequencies::parse(FREQUENCIES), 4, &known)
+    }
+
+    #[test]
+    fn test_frequency_list_ranks_by_line() {
+        let frequencies = WordFrequencies::parse(FREQUENCIES);
+        assert_eq!(frequencies.rank("the"), Some(1));
+        assert_eq!(frequencies.rank("moor"), Some(5));
+        assert_eq!(frequencies.rank("100"), None);
+    }
+
+    #[test]
+    fn test_rare_words_skip_known_words_and_names() {
+        let targets = rare_words(
+            "<p>The dog walked quickly.</p><p>Heathcliff's dog crossed the moor.</p><p>A moor.</p>",
+            &["crossed"],
+        );
+
+        let words: Vec<&str> = targets.words().iter().map(|w| w.word.as_str()).collect();
+        assert_eq!(words, vec!["moor"]);
+        assert_eq!(targets.words()[0].block, 1);
+        assert_eq!(targets.words()[0].rank, Some(5));
+        assert!(targets.words_in_block(0).is_none());
+        assert!(targets.words_in_block(2).unwrap().contains("moor"));
+
+        let mut targets = targets;
+        targets.remove_word("moor");
+        assert!(targets.is_empty());
+        assert!(targets.words_in_block(1).is_none());
+    }
+
+    #[test]
+    fn test_word_ranges_drop_possessives_and_quotes() {
+        let text = "'Tis Heathcliff’s moor, isn't it's";
+        let words: Vec<(&str, String)> = word_ranges(text)
+            .into_iter()
+            .map(|(range, word)| (&text[range], word))
+            .collect();
+        assert_eq!(
+            words,
+            vec![
+                ("Tis", "tis".to_string()),
+                ("Heathcliff", "heathcliff".to_string()),
+                ("moor", "moor".to_string()),
+                ("isn't", "isn't".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_known_words_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(KNOWN_WORDS_FILENAME);
+        let path = path.to_str().unwrap();
+
+        let mut known = KnownWords::load_or_ephemeral(Some(path));
+        assert!(known.insert("Moor"));
+        assert!(!known.insert("moor"));
+        known.save().unwrap();
+
+        let reloaded = KnownWords::load_or_ephemeral(Some(path));
+        assert!(reloaded.contains("moor"));
+    }
+}
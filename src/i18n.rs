@@ -0,0 +1,205 @@
+//! Interface strings by key, in the language the `language` setting picks
+//! ($LC_ALL, $LC_MESSAGES or $LANG by default), and dates written the way
+//! that language writes them.
+//!
+//! Translations are flat JSON files embedded from `src/i18n/`, one per
+//! language. A key a translation lacks falls back to English, and a key
+//! English lacks shows as the key itself.
+
+use chrono::{Datelike, NaiveDate, NaiveDateTime};
+use log::warn;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::env;
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// The `language` setting: from the environment, or a forced language
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    #[default]
+    Auto,
+    En,
+    Ru,
+}
+
+/// A language there are translations for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En = 0,
+    Ru = 1,
+}
+
+impl Locale {
+    const ALL: [Locale; 2] = [Locale::En, Locale::Ru];
+
+    fn messages(self) -> &'static str {
+        match self {
+            Locale::En => include_str!("i18n/en.json"),
+            Locale::Ru => include_str!("i18n/ru.json"),
+        }
+    }
+
+    /// The locale of a POSIX locale name such as `ru_RU.UTF-8`, None for
+    /// languages without a translation
+    fn from_posix(name: &str) -> Option<Locale> {
+        let language = name.split(['_', '.', '@', '-']).next()?;
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::En),
+            "ru" => Some(Locale::Ru),
+            _ => None,
+        }
+    }
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::En as u8);
+
+static MESSAGES: Lazy<Vec<HashMap<String, String>>> = Lazy::new(|| {
+    Locale::ALL
+        .iter()
+        .map(|locale| {
+            serde_json::from_str(locale.messages()).unwrap_or_else(|e| {
+                warn!("Failed to parse the {locale:?} translations: {e}");
+                HashMap::new()
+            })
+        })
+        .collect()
+});
+
+#[cfg(test)]
+thread_local! {
+    /// Locale of the strings fetched on this thread, so a test can switch
+    /// language without changing it for tests running alongside
+    static TEST_LOCALE: std::cell::Cell<Option<Locale>> = const { std::cell::Cell::new(None) };
+}
+
+/// Apply the language setting. Runs before the first frame is drawn.
+pub fn set_language(language: Language) {
+    let locale = match language {
+        Language::Auto => locale_from_env(|name| env::var(name).ok()),
+        Language::En => Locale::En,
+        Language::Ru => Locale::Ru,
+    };
+    LOCALE.store(locale as u8, Ordering::Relaxed);
+}
+
+/// The first of $LC_ALL, $LC_MESSAGES and $LANG that is set decides, as it
+/// does for other programs; English when there is no translation for it
+fn locale_from_env(var: impl Fn(&str) -> Option<String>) -> Locale {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|name| var(name))
+        .find(|value| !value.is_empty())
+        .and_then(|value| Locale::from_posix(&value))
+        .unwrap_or(Locale::En)
+}
+
+pub fn locale() -> Locale {
+    #[cfg(test)]
+    if let Some(locale) = TEST_LOCALE.with(|locale| locale.get()) {
+        return locale;
+    }
+    match LOCALE.load(Ordering::Relaxed) {
+        1 => Locale::Ru,
+        _ => Locale::En,
+    }
+}
+
+/// Run `f` with the strings of `locale`, on this thread only
+#[cfg(test)]
+pub(crate) fn with_locale<R>(locale: Locale, f: impl FnOnce() -> R) -> R {
+    let previous = TEST_LOCALE.with(|current| current.replace(Some(locale)));
+    let result = f();
+    TEST_LOCALE.with(|current| current.set(previous));
+    result
+}
+
+/// The string for `key` in the current language
+pub fn tr(key: &'static str) -> &'static str {
+    let messages = &*MESSAGES;
+    messages[locale() as usize]
+        .get(key)
+        .or_else(|| messages[Locale::En as usize].get(key))
+        .map_or(key, String::as_str)
+}
+
+/// The string for `key` with each `{name}` in it replaced by its argument
+pub fn tr_args(key: &'static str, args: &[(&str, &dyn Display)]) -> String {
+    args.iter()
+        .fold(tr(key).to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{name}}}"), &value.to_string())
+        })
+}
+
+/// A day as the language writes it in full: 2024-05-03, 03.05.2024
+pub fn format_date(date: NaiveDate) -> String {
+    date.format(tr("date.format")).to_string()
+}
+
+/// A day and time to the minute
+pub fn format_date_time(time: NaiveDateTime) -> String {
+    time.format(tr("date.date_time")).to_string()
+}
+
+/// The short day and time comment headers carry
+pub fn format_comment_time(time: NaiveDateTime) -> String {
+    time.format(tr("date.comment_time")).to_string()
+}
+
+/// A day without its year, for charts: May 3, 3 мая
+pub fn format_month_day(date: NaiveDate) -> String {
+    const MONTHS: [&str; 12] = [
+        "date.month.1",
+        "date.month.2",
+        "date.month.3",
+        "date.month.4",
+        "date.month.5",
+        "date.month.6",
+        "date.month.7",
+        "date.month.8",
+        "date.month.9",
+        "date.month.10",
+        "date.month.11",
+        "date.month.12",
+    ];
+    tr_args(
+        "date.month_day",
+        &[
+            ("month", &tr(MONTHS[date.month0() as usize])),
+            ("day", &date.day()),
+        ],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translations_fall_back_to_english() {
+        let keys = |locale: Locale| {
+            let mut keys: Vec<&String> = MESSAGES[locale as usize].keys().collect();
+            keys.sort();
+            keys
+        };
+        assert!(!keys(Locale::En).is_empty());
+        assert_eq!(keys(Locale::Ru), keys(Locale::En));
+
+        let date = NaiveDate::from_ymd_opt(2024, 5, 3).unwrap();
+        assert_eq!(format_month_day(date), "May 3");
+        assert_eq!(format_date(date), "2024-05-03");
+        with_locale(Locale::Ru, || {
+            assert_eq!(format_month_day(date), "3 мая");
+            assert_eq!(format_date(date), "03.05.2024");
+            assert_eq!(tr("history.today"), "Сегодня");
+            assert_eq!(tr("no.such.key"), "no.such.key");
+        });
+
+        let env = |lang: &'static str| move |name: &str| (name == "LANG").then(|| lang.to_string());
+        assert_eq!(locale_from_env(env("ru_RU.UTF-8")), Locale::Ru);
+        assert_eq!(locale_from_env(env("de_DE.UTF-8")), Locale::En);
+        assert_eq!(locale_from_env(|_| None), Locale::En);
+    }
+}
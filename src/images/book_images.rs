@@ -1,4 +1,5 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
+use epub::doc::EpubDoc;
 use fast_image_resize as fr;
 use image::{DynamicImage, GenericImageView, ImageBuffer};
 use imagesize;
@@ -7,6 +8,24 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 use super::image_storage::ImageStorage;
+use crate::markdown::{Block, Document, Inline, Node, TextOrInline};
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+/// Images smaller than this either way are icons or ornaments, which the
+/// reader leaves out
+pub const MIN_FIGURE_SIZE: u32 = 64;
+
+/// An image of the book, for the table of figures
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookFigure {
+    /// Spine index of the chapter showing the image
+    pub chapter_index: usize,
+    /// Source as referenced in the chapter text
+    pub src: String,
+    pub alt_text: String,
+    /// `<figcaption>` of the figure holding the image
+    pub caption: Option<String>,
+}
 
 /// Abstraction for managing book images
 /// Encapsulates the relationship with ImageStorage and provides
@@ -74,6 +93,45 @@ impl BookImages {
         }
     }
 
+    /// The extracted file of an image as referenced in the book text
+    pub fn image_file(&self, image_src: &str, chapter_path: Option<&str>) -> Option<PathBuf> {
+        let epub_path = self.current_epub_path.as_ref()?;
+        self.storage
+            .resolve_image_path_with_context(epub_path, image_src, chapter_path)
+    }
+
+    /// Every image the reader shows in the loaded book, in reading order.
+    /// Converts each chapter, so it takes a moment on a long book.
+    pub fn book_figures(&self) -> Result<Vec<BookFigure>> {
+        let epub_path = self.current_epub_path.as_ref().context("No book loaded")?;
+        let mut doc = EpubDoc::new(epub_path)
+            .with_context(|| format!("Failed to open EPUB: {epub_path:?}"))?;
+
+        let mut figures = Vec::new();
+        for chapter_index in 0..doc.spine.len() {
+            let idref = doc.spine[chapter_index].idref.clone();
+            let chapter_path = doc
+                .resources
+                .get(&idref)
+                .map(|resource| resource.path.to_string_lossy().to_string());
+            let Some((html, _mime)) = doc.get_resource_str(&idref) else {
+                continue;
+            };
+            let document = HtmlToMarkdownConverter::new().convert(&html);
+            figures.extend(
+                chapter_figures(&document, chapter_index)
+                    .into_iter()
+                    .filter(|figure| {
+                        matches!(
+                            self.get_image_size_with_context(&figure.src, chapter_path.as_deref()),
+                            Some((w, h)) if w >= MIN_FIGURE_SIZE && h >= MIN_FIGURE_SIZE
+                        )
+                    }),
+            );
+        }
+        Ok(figures)
+    }
+
     /// Get a DynamicImage from its source path (as referenced in the book text)
     pub fn get_image(&self, image_src: &str) -> Option<DynamicImage> {
         self.get_image_with_context(image_src, None)
@@ -215,5 +273,79 @@ impl BookImages {
     }
 }
 
+/// The images of a chapter in the blocks the reader shows images in, each
+/// source once
+fn chapter_figures(document: &Document, chapter_index: usize) -> Vec<BookFigure> {
+    fn collect(node: &Node, chapter_index: usize, figures: &mut Vec<BookFigure>) {
+        match &node.block {
+            Block::Paragraph { content } => {
+                for item in content.iter() {
+                    let TextOrInline::Inline(Inline::Image {
+                        url,
+                        alt_text,
+                        caption,
+                        ..
+                    }) = item
+                    else {
+                        continue;
+                    };
+                    if figures.iter().any(|figure| figure.src == *url) {
+                        continue;
+                    }
+                    figures.push(BookFigure {
+                        chapter_index,
+                        src: url.clone(),
+                        alt_text: alt_text.trim().to_string(),
+                        caption: caption.clone(),
+                    });
+                }
+            }
+            Block::Quote { content, .. } | Block::EpubBlock { content, .. } => {
+                for inner in content {
+                    collect(inner, chapter_index, figures);
+                }
+            }
+            Block::List { items, .. } => {
+                for inner in items.iter().flat_map(|item| &item.content) {
+                    collect(inner, chapter_index, figures);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut figures = Vec::new();
+    for node in &document.blocks {
+        collect(node, chapter_index, &mut figures);
+    }
+    figures
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chapter_figures_are_listed_once_in_order() {
+        let html = r#"<p><img src="a.png" alt=" Map "/></p>
+            <figure><img src="b.png" alt="Chart"/><figcaption>Sales by year</figcaption></figure>
+            <blockquote><p><img src="a.png" alt="Map again"/><img src="c.png"/></p></blockquote>
+            <table><tr><td><img src="d.png" alt="In a table"/></td></tr></table>"#;
+        let document = HtmlToMarkdownConverter::new().convert(html);
+
+        let figures = chapter_figures(&document, 4);
+        let listed: Vec<(&str, &str, Option<&str>)> = figures
+            .iter()
+            .map(|f| (f.src.as_str(), f.alt_text.as_str(), f.caption.as_deref()))
+            .collect();
+        assert_eq!(
+            listed,
+            [
+                ("a.png", "Map", None),
+                ("b.png", "Chart", Some("Sales by year")),
+                ("c.png", "", None),
+            ]
+        );
+        assert!(figures.iter().all(|f| f.chapter_index == 4));
+    }
+}
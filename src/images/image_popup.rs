@@ -1,36 +1,113 @@
+use crate::i18n::{tr, tr_args};
 use crate::ratatui_image::{Image, Resize, ViewportOptions, picker::Picker, protocol::Protocol};
+use crate::theme::current_theme;
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
 use image::{DynamicImage, GenericImageView};
 use log::debug;
 use ratatui::{
     Frame,
     layout::{Alignment, Rect},
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph},
 };
 use std::sync::Arc;
 use std::time::Instant;
 
+/// Lines of a figure caption shown beneath the image; longer ones are cut
+const MAX_CAPTION_LINES: u16 = 4;
+/// Rows the caption band can take, its separator included. Images with a
+/// caption are scaled to leave this much room.
+pub const CAPTION_BAND_HEIGHT: u16 = MAX_CAPTION_LINES + 1;
+/// Narrowest the popup gets for a caption, so small images keep it readable
+const MIN_CAPTION_WIDTH: u16 = 40;
+
 pub struct ImagePopup {
     pub image: Arc<DynamicImage>,
     pub protocol: Option<Protocol>,
     pub src_path: String,
+    /// `<figcaption>` of the figure holding the image
+    pub caption: Option<String>,
     pub picker: Picker,
     pub is_loading: bool,
     pub load_start: Option<Instant>,
     pub popup_area: Option<Rect>, // Check if this completes a key sequence (Space+d for stats)
+    /// Sources of the chapter's images in reading order, stepped through
+    /// with h/l
+    chapter_images: Vec<String>,
+    /// Place of the image shown in `chapter_images`
+    figure_index: Option<usize>,
 }
 
 impl ImagePopup {
-    pub fn new(image: Arc<DynamicImage>, picker: &Picker, src_path: String) -> Self {
+    pub fn new(
+        image: Arc<DynamicImage>,
+        picker: &Picker,
+        src_path: String,
+        caption: Option<String>,
+    ) -> Self {
         Self {
             image,
             protocol: None,
             src_path,
+            caption,
             picker: picker.clone(),
             is_loading: true,
             load_start: Some(Instant::now()),
             popup_area: None,
+            chapter_images: Vec::new(),
+            figure_index: None,
+        }
+    }
+
+    /// Let h/l step through the chapter's images, `images` in reading order
+    pub fn with_chapter_images(mut self, images: Vec<String>) -> Self {
+        self.figure_index = images.iter().position(|src| *src == self.src_path);
+        self.chapter_images = images;
+        self
+    }
+
+    /// Images to try showing in turn after this one, the nearest first;
+    /// past the last image (or before the first) only with `wrap`
+    pub fn step_candidates(&self, forward: bool, wrap: bool) -> Vec<String> {
+        let Some(index) = self.figure_index else {
+            return Vec::new();
+        };
+        let count = self.chapter_images.len();
+        (1..count)
+            .map(|step| {
+                if forward {
+                    index + step
+                } else {
+                    index + count * 2 - step
+                }
+            })
+            .filter(|&i| wrap || (forward && i < count) || (!forward && i >= count * 2))
+            .map(|i| self.chapter_images[i % count].clone())
+            .collect()
+    }
+
+    fn title(&self) -> String {
+        let (width, height) = self.image.dimensions();
+        match self.figure_index {
+            Some(index) => tr_args(
+                "image.figure_title",
+                &[
+                    ("index", &(index + 1)),
+                    ("total", &self.chapter_images.len()),
+                    ("path", &self.src_path),
+                    ("width", &width),
+                    ("height", &height),
+                ],
+            ),
+            None => tr_args(
+                "image.title",
+                &[
+                    ("path", &self.src_path),
+                    ("width", &width),
+                    ("height", &height),
+                ],
+            ),
         }
     }
 
@@ -45,13 +122,23 @@ impl ImagePopup {
         let clear_duration = clear_start.elapsed();
 
         let (width, height) = self.image.dimensions();
-        let title = format!(" {} [{}x{} px] ", self.src_path, width, height);
         let block = Block::default()
             .borders(Borders::ALL)
-            .title(title)
+            .title(self.title())
             .style(Style::default().bg(Color::Black));
 
         let inner_area = block.inner(popup_area);
+        let caption_lines = self.caption_lines(inner_area.width);
+        let band_height = Self::band_height(&caption_lines).min(inner_area.height);
+        let image_area = Rect {
+            height: inner_area.height - band_height,
+            ..inner_area
+        };
+        let band_area = Rect {
+            y: image_area.bottom(),
+            height: band_height,
+            ..inner_area
+        };
 
         let block_start = Instant::now();
         f.render_widget(block, popup_area);
@@ -64,13 +151,13 @@ impl ImagePopup {
             block_duration.as_millis()
         );
 
-        let size_text = format!("{width}x{height} pixels");
+        let size_text = tr_args("image.size", &[("width", &width), ("height", &height)]);
 
         let loading_text = vec![
             Line::from(""),
             Line::from(""),
             Line::from(Span::styled(
-                "⏳ Loading image...",
+                tr("image.loading"),
                 Style::default()
                     .fg(Color::Yellow)
                     .add_modifier(ratatui::style::Modifier::BOLD),
@@ -79,7 +166,7 @@ impl ImagePopup {
             Line::from(Span::styled(size_text, Style::default().fg(Color::Gray))),
             Line::from(""),
             Line::from(Span::styled(
-                "Processing image data, please wait",
+                tr("image.processing"),
                 Style::default().fg(Color::DarkGray),
             )),
         ];
@@ -88,7 +175,7 @@ impl ImagePopup {
             .style(Style::default().bg(Color::Black));
 
         let loading_start = Instant::now();
-        f.render_widget(loading_paragraph, inner_area);
+        f.render_widget(loading_paragraph, image_area);
         let loading_duration = loading_start.elapsed();
 
         debug!(
@@ -102,7 +189,7 @@ impl ImagePopup {
             .picker
             .new_protocol(
                 self.image.as_ref().clone(),
-                self.calculate_optimal_popup_area(terminal_size),
+                image_area,
                 Resize::Viewport(ViewportOptions {
                     y_offset: 0,
                     x_offset: 0,
@@ -123,7 +210,6 @@ impl ImagePopup {
             total_time.as_millis()
         );
 
-        let image_area = inner_area;
         let image_widget = Image::new(self.protocol.as_ref().unwrap());
 
         let total_time = self.load_start.map(|s| s.elapsed()).unwrap_or(duration);
@@ -139,6 +225,10 @@ impl ImagePopup {
         f.render_widget(image_widget, image_area);
         let render_duration = render_start.elapsed();
 
+        if band_height > 0 {
+            Self::render_caption(f, band_area, caption_lines);
+        }
+
         debug!(
             "--Image widget render time for '{}': {}ms",
             self.src_path,
@@ -156,6 +246,57 @@ impl ImagePopup {
         self.popup_area = Some(popup_area)
     }
 
+    /// The caption wrapped to `width`, cut to `MAX_CAPTION_LINES` with an
+    /// ellipsis
+    fn caption_lines(&self, width: u16) -> Vec<String> {
+        let Some(caption) = &self.caption else {
+            return Vec::new();
+        };
+        let mut lines: Vec<String> = textwrap::wrap(caption, width.max(1) as usize)
+            .into_iter()
+            .map(|line| line.into_owned())
+            .collect();
+        if lines.len() > MAX_CAPTION_LINES as usize {
+            lines.truncate(MAX_CAPTION_LINES as usize);
+            if let Some(last) = lines.last_mut() {
+                last.push('…');
+            }
+        }
+        lines
+    }
+
+    /// Rows for the caption band: its lines and a separator above them
+    fn band_height(caption_lines: &[String]) -> u16 {
+        if caption_lines.is_empty() {
+            0
+        } else {
+            caption_lines.len() as u16 + 1
+        }
+    }
+
+    fn render_caption(f: &mut Frame, area: Rect, caption_lines: Vec<String>) {
+        let palette = current_theme();
+        let caption = Paragraph::new(
+            caption_lines
+                .into_iter()
+                .map(Line::from)
+                .collect::<Vec<_>>(),
+        )
+        .alignment(Alignment::Center)
+        .block(
+            Block::default()
+                .borders(Borders::TOP)
+                .border_style(Style::default().fg(palette.base_03)),
+        )
+        .style(
+            Style::default()
+                .fg(palette.base_05)
+                .bg(palette.base_00)
+                .add_modifier(Modifier::ITALIC),
+        );
+        f.render_widget(caption, area);
+    }
+
     /// Calculate the optimal popup area based on image dimensions and terminal size
     fn calculate_optimal_popup_area(&self, terminal_size: Rect) -> Rect {
         let (img_width, img_height) = self.image.dimensions();
@@ -169,13 +310,25 @@ impl ImagePopup {
         let image_width_cells = (img_width as f32 / cell_width_pixels).ceil() as u16;
         let image_height_cells = (img_height as f32 / cell_height_pixels).ceil() as u16;
 
-        // Reserve minimal space for borders (2) only - no help text
-        let max_width = terminal_size.width.saturating_sub(4);
-        let max_height = terminal_size.height.saturating_sub(2);
+        // Reserve minimal space for borders (2) only - no help text. The
+        // largest size can be picked with Ctrl+arrows or +/-.
+        let bounds = popup_rect(PopupKind::Image, terminal_size);
+        let max_width = bounds.width.saturating_sub(4);
+        let max_height = bounds.height.saturating_sub(2);
 
-        // Since image is pre-scaled, just ensure it fits on screen
-        let content_width = image_width_cells.min(max_width);
-        let content_height = image_height_cells.min(max_height);
+        // Since image is pre-scaled, just ensure it fits on screen. A caption
+        // widens small images and takes a band beneath the image.
+        let content_width = match &self.caption {
+            Some(caption) => {
+                let caption_width = (caption.chars().count() as u16).min(MIN_CAPTION_WIDTH);
+                image_width_cells.max(caption_width).min(max_width)
+            }
+            None => image_width_cells.min(max_width),
+        };
+        let band_height = Self::band_height(&self.caption_lines(content_width));
+        let content_height = image_height_cells
+            .min(max_height.saturating_sub(band_height))
+            .saturating_add(band_height);
 
         // Add space for borders (1 on each side)
         let popup_width = content_width.saturating_add(2);
@@ -204,3 +357,95 @@ impl ImagePopup {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::{capture_terminal_state, create_test_terminal};
+
+    fn popup(caption: Option<&str>) -> ImagePopup {
+        // 20x5 cells with 10x20 px cells
+        let image = Arc::new(DynamicImage::new_rgb8(200, 100));
+        let picker = Picker::from_fontsize((10, 20));
+        ImagePopup::new(
+            image,
+            &picker,
+            "whale.png".to_string(),
+            caption.map(String::from),
+        )
+    }
+
+    #[test]
+    fn test_caption_band_below_the_image() {
+        let terminal = Rect::new(0, 0, 100, 40);
+        let area = popup(None).calculate_optimal_popup_area(terminal);
+        assert_eq!((area.width, area.height), (22, 7));
+
+        // A separator and the caption line
+        let area = popup(Some("The whale")).calculate_optimal_popup_area(terminal);
+        assert_eq!((area.width, area.height), (22, 9));
+
+        // Long captions widen the popup and are cut after a few lines
+        let long = "Call me Ishmael. ".repeat(20);
+        let captioned = popup(Some(&long));
+        let area = captioned.calculate_optimal_popup_area(terminal);
+        assert_eq!(area.width, MIN_CAPTION_WIDTH + 2);
+        assert_eq!(area.height, 5 + CAPTION_BAND_HEIGHT + 2);
+        let lines = captioned.caption_lines(MIN_CAPTION_WIDTH);
+        assert_eq!(lines.len(), MAX_CAPTION_LINES as usize);
+        assert!(lines[3].ends_with('…'));
+    }
+
+    #[test]
+    fn test_steps_through_the_chapter_images() {
+        let images: Vec<String> = ["a.png", "b.png", "c.png"].map(String::from).into();
+        let picker = Picker::from_fontsize((10, 20));
+        let open = |src: &str| {
+            let image = Arc::new(DynamicImage::new_rgb8(320, 40));
+            ImagePopup::new(image, &picker, src.to_string(), None)
+                .with_chapter_images(images.clone())
+        };
+        let snapshot = |popup: &mut ImagePopup| {
+            let mut terminal = create_test_terminal(44, 6);
+            terminal.draw(|f| popup.render(f, f.area())).unwrap();
+            capture_terminal_state(&terminal)
+        };
+
+        let mut first = open("a.png");
+        assert_eq!(first.step_candidates(true, false), ["b.png", "c.png"]);
+        assert!(first.step_candidates(false, false).is_empty());
+        assert_eq!(first.step_candidates(false, true), ["c.png", "b.png"]);
+        let rows = "▀".repeat(32);
+        assert_eq!(
+            snapshot(&mut first),
+            [
+                "",
+                "     ┌ figure 1/3 · a.png [320x40 px] ┐",
+                &format!("     │{rows}│"),
+                &format!("     │{rows}│"),
+                "     └────────────────────────────────┘",
+            ]
+            .join("\n")
+        );
+
+        let next = first.step_candidates(true, false).remove(0);
+        let mut second = open(&next);
+        assert_eq!(second.step_candidates(false, false), ["a.png"]);
+        assert_eq!(
+            snapshot(&mut second),
+            [
+                "",
+                "     ┌ figure 2/3 · b.png [320x40 px] ┐",
+                &format!("     │{rows}│"),
+                &format!("     │{rows}│"),
+                "     └────────────────────────────────┘",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            open("c.png").step_candidates(true, true),
+            ["a.png", "b.png"]
+        );
+        assert!(open("other.png").step_candidates(true, true).is_empty());
+    }
+}
@@ -7,6 +7,9 @@ use std::io::BufReader;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
+/// Where images are extracted to, relative to the working directory
+pub const IMAGE_CACHE_DIR: &str = "temp_images";
+
 #[derive(Clone)]
 pub struct ImageStorage {
     base_dir: PathBuf,
@@ -25,10 +28,19 @@ impl ImageStorage {
     }
 
     pub fn new_in_project_temp() -> Result<Self> {
-        let base_dir = PathBuf::from("temp_images");
+        let base_dir = PathBuf::from(IMAGE_CACHE_DIR);
         Self::new(base_dir)
     }
 
+    /// Name of the directory a book's images are extracted to
+    pub fn book_dir_name(epub_path: &Path) -> String {
+        let book_name = epub_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown");
+        sanitize_filename(book_name)
+    }
+
     pub fn extract_images(&self, epub_path: &Path) -> Result<()> {
         let epub_path_str = epub_path.to_string_lossy().to_string();
         info!("Starting image extraction for: {epub_path_str}");
@@ -38,12 +50,7 @@ impl ImageStorage {
             return Ok(());
         }
 
-        let book_name = epub_path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown");
-        let safe_book_name = sanitize_filename(book_name);
-        let book_dir = self.base_dir.join(&safe_book_name);
+        let book_dir = self.base_dir.join(Self::book_dir_name(epub_path));
 
         // Check if directory exists and already contains images
         if book_dir.exists() {
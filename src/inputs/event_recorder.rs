@@ -0,0 +1,260 @@
+//! Recording of input events for bug reports, and their replay.
+//!
+//! `--record <file>` writes a JSON-lines file: a header with the terminal
+//! size and the books directory, then one line per event with the
+//! milliseconds since recording started. `--replay <file>` feeds the events
+//! back through [`ReplayEventSource`].
+
+use crate::event_source::{Event, EventSource};
+use anyhow::{Context, Result, bail};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Bumped when the file layout changes
+pub const RECORDING_VERSION: u32 = 1;
+
+const PRIVACY_NOTE: &str = "Only key, mouse, paste and resize events are recorded, no book \
+     contents or files. Typed text such as search queries and comments is included; check \
+     it before sharing.";
+
+/// First line of a recording
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordingHeader {
+    pub bookokrat_recording: u32,
+    pub note: String,
+    pub width: u16,
+    pub height: u16,
+    pub books_directory: Option<String>,
+}
+
+impl RecordingHeader {
+    pub fn new(width: u16, height: u16, books_directory: Option<String>) -> Self {
+        Self {
+            bookokrat_recording: RECORDING_VERSION,
+            note: PRIVACY_NOTE.to_string(),
+            width,
+            height,
+            books_directory,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordedEvent {
+    /// Milliseconds since recording started
+    pub ms: u64,
+    pub event: Event,
+}
+
+/// Passes events through from another source, appending each to a recording
+pub struct RecordingEventSource<S: EventSource> {
+    inner: S,
+    writer: BufWriter<File>,
+    started: Instant,
+}
+
+impl<S: EventSource> RecordingEventSource<S> {
+    pub fn create(path: &Path, header: &RecordingHeader, inner: S) -> Result<Self> {
+        let file = File::create(path)
+            .with_context(|| format!("cannot create recording {}", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, header)?;
+        writer.write_all(b"\n")?;
+        writer.flush()?;
+        Ok(Self {
+            inner,
+            writer,
+            started: Instant::now(),
+        })
+    }
+
+    fn append(&mut self, event: &Event) -> Result<()> {
+        let recorded = RecordedEvent {
+            ms: self.started.elapsed().as_millis() as u64,
+            event: event.clone(),
+        };
+        serde_json::to_writer(&mut self.writer, &recorded)?;
+        self.writer.write_all(b"\n")?;
+        // Flushed per event so a crash still leaves the events leading up to it
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+impl<S: EventSource> EventSource for RecordingEventSource<S> {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        self.inner.poll(timeout)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        let event = self.inner.read()?;
+        if let Err(e) = self.append(&event) {
+            log::error!("Failed to record event: {e}");
+        }
+        Ok(event)
+    }
+
+    fn set_mouse_capture(&mut self, enabled: bool) -> Result<()> {
+        self.inner.set_mouse_capture(enabled)
+    }
+
+    fn suspend(&mut self, mouse_capture: bool) -> Result<()> {
+        self.inner.suspend(mouse_capture)
+    }
+}
+
+pub fn load_recording(path: &Path) -> Result<(RecordingHeader, Vec<RecordedEvent>)> {
+    let file =
+        File::open(path).with_context(|| format!("cannot open recording {}", path.display()))?;
+    let mut lines = BufReader::new(file).lines();
+    let Some(first) = lines.next() else {
+        bail!("{} is empty", path.display());
+    };
+    let header: RecordingHeader = serde_json::from_str(&first?)
+        .with_context(|| format!("{} is not a bookokrat recording", path.display()))?;
+    if header.bookokrat_recording != RECORDING_VERSION {
+        bail!(
+            "recording version {} is not supported (expected {RECORDING_VERSION})",
+            header.bookokrat_recording
+        );
+    }
+
+    let mut events = Vec::new();
+    for (number, line) in lines.enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        events.push(
+            serde_json::from_str(&line)
+                .with_context(|| format!("bad event on line {}", number + 2))?,
+        );
+    }
+    Ok((header, events))
+}
+
+/// Plays recorded events back at `speedup` times their recorded pace
+pub struct ReplayEventSource {
+    events: Vec<RecordedEvent>,
+    next: usize,
+    speedup: u32,
+    started: Instant,
+    /// Time left after the last event for images and notifications to settle
+    settle: Duration,
+}
+
+impl ReplayEventSource {
+    pub fn new(events: Vec<RecordedEvent>, speedup: u32) -> Self {
+        Self {
+            events,
+            next: 0,
+            speedup: speedup.max(1),
+            started: Instant::now(),
+            settle: Duration::from_millis(500),
+        }
+    }
+
+    /// Time since start at which an event is replayed
+    fn due_at(&self, event: &RecordedEvent) -> Duration {
+        Duration::from_millis(event.ms) / self.speedup
+    }
+}
+
+impl EventSource for ReplayEventSource {
+    fn poll(&mut self, timeout: Duration) -> Result<bool> {
+        let Some(event) = self.events.get(self.next) else {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        };
+        let wait = self.due_at(event).saturating_sub(self.started.elapsed());
+        if wait > timeout {
+            std::thread::sleep(timeout);
+            return Ok(false);
+        }
+        std::thread::sleep(wait);
+        Ok(true)
+    }
+
+    fn read(&mut self) -> Result<Event> {
+        let Some(event) = self.events.get(self.next) else {
+            bail!("the recording has no more events");
+        };
+        self.next += 1;
+        Ok(event.event.clone())
+    }
+
+    fn finished(&self) -> bool {
+        match self.events.last() {
+            Some(last) if self.next == self.events.len() => {
+                self.started.elapsed() >= self.due_at(last) + self.settle
+            }
+            Some(_) => false,
+            None => self.started.elapsed() >= self.settle,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event_source::SimulatedEventSource;
+
+    #[test]
+    fn test_recorded_events_load_back_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.jsonl");
+        let header = RecordingHeader::new(100, 30, Some("/books".to_string()));
+        let events = vec![
+            SimulatedEventSource::char_key('j'),
+            SimulatedEventSource::mouse_scroll_down(10, 5),
+            Event::Resize(80, 24),
+        ];
+
+        let mut source =
+            RecordingEventSource::create(&path, &header, SimulatedEventSource::new(events.clone()))
+                .unwrap();
+        while source.poll(Duration::ZERO).unwrap() {
+            source.read().unwrap();
+        }
+
+        let (loaded_header, recorded) = load_recording(&path).unwrap();
+        assert_eq!(loaded_header, header);
+        assert!(loaded_header.note.contains("Only key, mouse"));
+        let loaded: Vec<Event> = recorded.into_iter().map(|e| e.event).collect();
+        assert_eq!(loaded, events);
+
+        std::fs::write(&path, "{\"not\": \"a recording\"}\n").unwrap();
+        assert!(load_recording(&path).is_err());
+    }
+
+    #[test]
+    fn test_replay_waits_for_each_event_then_finishes() {
+        let events = vec![
+            RecordedEvent {
+                ms: 0,
+                event: SimulatedEventSource::char_key('j'),
+            },
+            RecordedEvent {
+                ms: 60_000,
+                event: SimulatedEventSource::char_key('k'),
+            },
+        ];
+        let mut replay = ReplayEventSource::new(events, 1);
+        replay.settle = Duration::ZERO;
+
+        assert!(replay.poll(Duration::ZERO).unwrap());
+        assert_eq!(replay.read().unwrap(), SimulatedEventSource::char_key('j'));
+        assert!(!replay.poll(Duration::ZERO).unwrap());
+        assert!(!replay.finished());
+
+        // A minute of recording is due at once when sped up enough
+        replay.speedup = 60_000;
+        assert!(replay.poll(Duration::from_millis(10)).unwrap());
+        replay.read().unwrap();
+        assert!(replay.finished());
+        assert!(replay.read().is_err());
+    }
+}
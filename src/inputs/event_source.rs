@@ -9,6 +9,24 @@ pub trait EventSource {
 
     /// Read the next event
     fn read(&mut self) -> Result<Event>;
+
+    /// Whether the source has run out for good and the app should stop, as
+    /// a replayed recording does
+    fn finished(&self) -> bool {
+        false
+    }
+
+    /// Take the mouse from the terminal, or hand it back for the terminal's
+    /// own selection; sources not reading a terminal have nothing to change
+    fn set_mouse_capture(&mut self, _enabled: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Give the terminal back to the shell and stop until resumed (Ctrl+Z),
+    /// then take it again with the mouse captured as before
+    fn suspend(&mut self, _mouse_capture: bool) -> Result<()> {
+        Ok(())
+    }
 }
 
 /// Real keyboard event source using crossterm
@@ -22,6 +40,22 @@ impl EventSource for KeyboardEventSource {
     fn read(&mut self) -> Result<Event> {
         Ok(crossterm::event::read()?)
     }
+
+    fn set_mouse_capture(&mut self, enabled: bool) -> Result<()> {
+        use crossterm::event::{DisableMouseCapture, EnableMouseCapture};
+
+        let mut stdout = std::io::stdout();
+        if enabled {
+            crossterm::execute!(stdout, EnableMouseCapture)?;
+        } else {
+            crossterm::execute!(stdout, DisableMouseCapture)?;
+        }
+        Ok(())
+    }
+
+    fn suspend(&mut self, mouse_capture: bool) -> Result<()> {
+        Ok(crate::terminal_state::suspend(mouse_capture)?)
+    }
 }
 
 /// Simulated event source for testing
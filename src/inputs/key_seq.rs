@@ -49,7 +49,11 @@ impl KeySeq {
         self.last_key_time = None;
     }
 
+    /// The keys typed so far; empty once the sequence has timed out
     pub fn current_sequence(&self) -> String {
+        if self.should_reset_key_sequence() {
+            return String::new();
+        }
         self.key_sequence.iter().collect()
     }
 }
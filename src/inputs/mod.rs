@@ -1,3 +1,4 @@
+pub mod event_recorder;
 pub mod event_source;
 pub mod key_seq;
 pub mod mouse_tracker;
@@ -0,0 +1,190 @@
+use log::{info, warn};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Lock file created next to the bookmarks file, holding the owner's pid
+pub const LOCK_FILENAME: &str = "bookokrat.lock";
+
+/// Set when another instance owns the lock: bookmarks, comments and reading
+/// goals are then never written, so that instance's data is not clobbered
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+pub enum LockStatus {
+    Acquired(InstanceLock),
+    /// A live process other than this one holds the lock
+    HeldBy {
+        pid: u32,
+    },
+}
+
+/// Pid-stamped lock file; removed on drop if this process still owns it
+pub struct InstanceLock {
+    path: PathBuf,
+}
+
+impl InstanceLock {
+    pub fn lock_path_for(bookmark_file: &Path) -> PathBuf {
+        bookmark_file.with_file_name(LOCK_FILENAME)
+    }
+
+    /// Take the lock unless a live process holds it. A lock left behind by
+    /// a process that is gone (unclean shutdown) is replaced.
+    pub fn acquire(path: &Path) -> io::Result<LockStatus> {
+        let own_pid = std::process::id();
+
+        for _ in 0..3 {
+            match OpenOptions::new().write(true).create_new(true).open(path) {
+                Ok(mut file) => {
+                    write!(file, "{own_pid}")?;
+                    return Ok(LockStatus::Acquired(InstanceLock {
+                        path: path.to_path_buf(),
+                    }));
+                }
+                Err(e) if e.kind() == io::ErrorKind::AlreadyExists => match read_pid(path) {
+                    Some(pid) if pid != own_pid && process_alive(pid) => {
+                        return Ok(LockStatus::HeldBy { pid });
+                    }
+                    stale => {
+                        info!("Removing stale lock file {path:?} (pid {stale:?})");
+                        match fs::remove_file(path) {
+                            Ok(()) => {}
+                            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                            Err(e) => return Err(e),
+                        }
+                    }
+                },
+                Err(e) => return Err(e),
+            }
+        }
+
+        Err(io::Error::other(format!(
+            "Lock file {} keeps reappearing",
+            path.display()
+        )))
+    }
+
+    /// Claim the lock even though another instance holds it
+    pub fn take_over(path: &Path) -> io::Result<InstanceLock> {
+        warn!("Taking over lock file {path:?}");
+        fs::write(path, std::process::id().to_string())?;
+        Ok(InstanceLock {
+            path: path.to_path_buf(),
+        })
+    }
+}
+
+impl Drop for InstanceLock {
+    fn drop(&mut self) {
+        // The lock may have been taken over by another instance meanwhile
+        if read_pid(&self.path) == Some(std::process::id()) {
+            let _ = fs::remove_file(&self.path);
+        }
+    }
+}
+
+fn read_pid(path: &Path) -> Option<u32> {
+    fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    if pid <= 0 {
+        return false;
+    }
+    // Signal 0 only checks for existence; EPERM means it exists but is not ours
+    let alive = unsafe { libc::kill(pid, 0) } == 0;
+    alive || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    // Without a cheap liveness check, let the user decide whether to take over
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A pid no live process can have
+    const DEAD_PID: u32 = 999_999_999;
+
+    #[test]
+    fn test_acquire_creates_and_drop_removes_lock() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILENAME);
+
+        let LockStatus::Acquired(lock) = InstanceLock::acquire(&path).unwrap() else {
+            panic!("expected to acquire a fresh lock");
+        };
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILENAME);
+        fs::write(&path, DEAD_PID.to_string()).unwrap();
+
+        let status = InstanceLock::acquire(&path).unwrap();
+        assert!(matches!(status, LockStatus::Acquired(_)));
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+
+        fs::write(&path, "not a pid").unwrap();
+        drop(status);
+        assert!(matches!(
+            InstanceLock::acquire(&path).unwrap(),
+            LockStatus::Acquired(_)
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_live_holder_is_reported_and_can_be_taken_over() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILENAME);
+        // pid 1 (init) is always alive
+        fs::write(&path, "1").unwrap();
+
+        assert!(matches!(
+            InstanceLock::acquire(&path).unwrap(),
+            LockStatus::HeldBy { pid: 1 }
+        ));
+        assert_eq!(read_pid(&path), Some(1));
+
+        let lock = InstanceLock::take_over(&path).unwrap();
+        assert_eq!(read_pid(&path), Some(std::process::id()));
+        drop(lock);
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_drop_keeps_lock_taken_over_by_another_instance() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILENAME);
+
+        let LockStatus::Acquired(lock) = InstanceLock::acquire(&path).unwrap() else {
+            panic!("expected to acquire a fresh lock");
+        };
+        fs::write(&path, "1").unwrap();
+        drop(lock);
+        assert_eq!(read_pid(&path), Some(1));
+    }
+}
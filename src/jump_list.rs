@@ -76,6 +76,17 @@ impl JumpList {
         }
     }
 
+    /// Chapters visited in `epub_path`, most recent first and without duplicates
+    pub fn recent_chapters(&self, epub_path: &str) -> Vec<usize> {
+        let mut chapters = Vec::new();
+        for location in self.entries.iter().rev() {
+            if location.epub_path == epub_path && !chapters.contains(&location.chapter_index) {
+                chapters.push(location.chapter_index);
+            }
+        }
+        chapters
+    }
+
     /// Clear the jump list
     #[allow(dead_code)]
     pub fn clear(&mut self) {
@@ -136,4 +147,20 @@ mod tests {
         // Should only have 3 entries (2, 3, 4)
         assert_eq!(list.entries.len(), 3);
     }
+
+    #[test]
+    fn test_recent_chapters_newest_first_per_book() {
+        let mut list = JumpList::new(10);
+        for (path, chapter_index) in [("a.epub", 1), ("a.epub", 3), ("b.epub", 2), ("a.epub", 1)] {
+            list.push(JumpLocation {
+                epub_path: path.to_string(),
+                chapter_index,
+                node_index: 0,
+            });
+        }
+
+        assert_eq!(list.recent_chapters("a.epub"), vec![1, 3]);
+        assert_eq!(list.recent_chapters("b.epub"), vec![2]);
+        assert!(list.recent_chapters("c.epub").is_empty());
+    }
 }
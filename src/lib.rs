@@ -1,16 +1,29 @@
 // Export modules for use in tests
+pub mod book_download;
 pub mod book_manager;
+pub mod book_metadata;
+pub mod book_watch;
 pub mod bookmarks;
+pub mod chapter_export;
 pub mod color_mode;
 pub mod comments;
+pub mod comments_viewer_state;
 pub use inputs::event_source;
 pub mod components;
+#[cfg(feature = "devtools")]
+pub mod devtools;
+pub mod glossing;
+pub mod i18n;
 pub mod images;
 // Vendored ratatui-image
 pub mod vendored;
 pub use vendored::ratatui_image;
 pub mod inputs;
+pub mod instance_lock;
 pub mod jump_list;
+pub mod library_doctor;
+pub mod library_scan;
+pub mod logging;
 pub mod main_app;
 pub mod markdown;
 pub mod notification;
@@ -24,10 +37,18 @@ pub use widget::reading_history;
 pub use widget::text_reader as markdown_text_reader;
 pub mod panic_handler;
 pub mod parsing;
+pub mod pinned_books;
+pub mod reading_goal;
+pub mod resume_stack;
+pub mod screen_capture;
 pub mod search;
 pub mod search_engine;
+pub mod search_history;
+pub mod session_books;
 pub mod settings;
+pub mod system_appearance;
 pub mod system_command;
+pub mod terminal_state;
 pub use components::table;
 pub mod theme;
 pub mod types;
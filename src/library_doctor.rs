@@ -0,0 +1,514 @@
+//! `bookokrat --doctor [dir]`: a check of the library that runs without
+//! the reader.
+//!
+//! It opens every book of the directory and every bookmarked book, and
+//! reports the books that fail to open, bookmarks and comments that point
+//! at books, chapters or paragraphs that are gone, and image caches no book
+//! uses. With `--fix` the bookmarks of missing books and the unused caches
+//! are removed; everything else is only reported, since it may come back
+//! with the book.
+
+use crate::book_manager::BookManager;
+use crate::bookmarks::{BOOKMARKS_FILENAME, Bookmarks};
+use crate::comments::{BookComments, Comment};
+use crate::images::image_storage::{IMAGE_CACHE_DIR, ImageStorage};
+use crate::main_app::App;
+use crate::markdown::Node;
+use crate::widget::comments_viewer::CommentsViewer;
+use anyhow::Result;
+use epub::doc::EpubDoc;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// The library and the files the reader keeps about it
+pub struct DoctorPaths {
+    pub books_dir: String,
+    pub bookmarks_file: PathBuf,
+    /// None when the comments directory can't be found
+    pub comments_dir: Option<PathBuf>,
+    pub image_cache_dir: PathBuf,
+}
+
+impl DoctorPaths {
+    /// The files the reader uses when started in the current directory
+    pub fn for_library(books_dir: &str) -> Self {
+        DoctorPaths {
+            books_dir: books_dir.to_string(),
+            bookmarks_file: PathBuf::from(BOOKMARKS_FILENAME),
+            comments_dir: BookComments::comments_dir().ok(),
+            image_cache_dir: PathBuf::from(IMAGE_CACHE_DIR),
+        }
+    }
+}
+
+/// Why a book fails to open
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BookProblem {
+    /// The file can't be read at all
+    Unreadable(String),
+    NotAnArchive,
+    /// A zip archive without the container an EPUB starts from
+    NotAnEpub,
+    /// An EPUB the parser rejects
+    Malformed(String),
+    NoChapters,
+}
+
+impl fmt::Display for BookProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BookProblem::Unreadable(e) => write!(f, "can't be read: {e}"),
+            BookProblem::NotAnArchive => write!(f, "not a zip archive"),
+            BookProblem::NotAnEpub => write!(f, "a zip archive without META-INF/container.xml"),
+            BookProblem::Malformed(e) => write!(f, "malformed EPUB: {e}"),
+            BookProblem::NoChapters => write!(f, "no chapters in the spine"),
+        }
+    }
+}
+
+/// A bookmark or reading thread whose chapter is no longer in the book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleBookmark {
+    pub book: String,
+    pub chapter_href: String,
+    /// None for the primary bookmark
+    pub thread: Option<String>,
+}
+
+/// What a comment lost
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentProblem {
+    ChapterMissing,
+    ParagraphMissing,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StaleComment {
+    pub book: String,
+    pub chapter_href: String,
+    pub node_index: usize,
+    pub problem: CommentProblem,
+}
+
+#[derive(Debug, Default)]
+pub struct DoctorReport {
+    pub books_checked: usize,
+    pub broken_books: Vec<(String, BookProblem)>,
+    /// Set when the bookmarks file can't be parsed; nothing is pruned then
+    pub bookmarks_error: Option<String>,
+    /// Bookmarked books whose file is gone; `--fix` forgets them
+    pub missing_books: Vec<String>,
+    pub stale_bookmarks: Vec<StaleBookmark>,
+    pub stale_comments: Vec<StaleComment>,
+    /// Comment files of no book in the library or the bookmarks
+    pub unmatched_comment_files: Vec<PathBuf>,
+    /// Image cache directories of no book; `--fix` deletes them
+    pub orphaned_caches: Vec<PathBuf>,
+}
+
+/// What `--fix` removed
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FixSummary {
+    pub bookmarks: usize,
+    pub caches: usize,
+}
+
+impl DoctorReport {
+    pub fn is_clean(&self) -> bool {
+        self.broken_books.is_empty()
+            && self.bookmarks_error.is_none()
+            && self.missing_books.is_empty()
+            && self.stale_bookmarks.is_empty()
+            && self.stale_comments.is_empty()
+            && self.unmatched_comment_files.is_empty()
+            && self.orphaned_caches.is_empty()
+    }
+
+    /// Forget the bookmarks of missing books and delete the unused caches
+    pub fn fix(&self, paths: &DoctorPaths) -> Result<FixSummary> {
+        let mut summary = FixSummary::default();
+        if self.bookmarks_error.is_none() && !self.missing_books.is_empty() {
+            let mut bookmarks = Bookmarks::load_from_file(&paths.bookmarks_file.to_string_lossy())?;
+            for book in &self.missing_books {
+                if bookmarks.remove_bookmark(book) {
+                    summary.bookmarks += 1;
+                }
+            }
+        }
+        for cache in &self.orphaned_caches {
+            fs::remove_dir_all(cache)?;
+            summary.caches += 1;
+        }
+        Ok(summary)
+    }
+}
+
+impl fmt::Display for DoctorReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Checked {} books", self.books_checked)?;
+        if self.is_clean() {
+            return writeln!(f, "No problems found");
+        }
+
+        if !self.broken_books.is_empty() {
+            writeln!(f, "\nBooks that fail to open:")?;
+            for (book, problem) in &self.broken_books {
+                writeln!(f, "  {book}: {problem}")?;
+            }
+        }
+        if let Some(e) = &self.bookmarks_error {
+            writeln!(f, "\nBookmarks can't be read: {e}")?;
+        }
+        if !self.missing_books.is_empty() {
+            writeln!(f, "\nBookmarks of missing books (--fix removes them):")?;
+            for book in &self.missing_books {
+                writeln!(f, "  {book}")?;
+            }
+        }
+        if !self.stale_bookmarks.is_empty() {
+            writeln!(f, "\nBookmarks whose chapter is gone:")?;
+            for stale in &self.stale_bookmarks {
+                match &stale.thread {
+                    Some(thread) => writeln!(
+                        f,
+                        "  {}: {} (thread \"{thread}\")",
+                        stale.book, stale.chapter_href
+                    )?,
+                    None => writeln!(f, "  {}: {}", stale.book, stale.chapter_href)?,
+                }
+            }
+        }
+        if !self.stale_comments.is_empty() {
+            writeln!(f, "\nComments that lost their place:")?;
+            for stale in &self.stale_comments {
+                let problem = match stale.problem {
+                    CommentProblem::ChapterMissing => "chapter not found",
+                    CommentProblem::ParagraphMissing => "paragraph not found",
+                };
+                writeln!(
+                    f,
+                    "  {}: {}, paragraph {}: {problem}",
+                    stale.book,
+                    stale.chapter_href,
+                    stale.node_index + 1
+                )?;
+            }
+        }
+        if !self.unmatched_comment_files.is_empty() {
+            writeln!(f, "\nComment files of no known book:")?;
+            for file in &self.unmatched_comment_files {
+                writeln!(f, "  {}", file.display())?;
+            }
+        }
+        if !self.orphaned_caches.is_empty() {
+            writeln!(f, "\nUnused image caches (--fix removes them):")?;
+            for cache in &self.orphaned_caches {
+                writeln!(f, "  {}", cache.display())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Check the library at `paths`; changes nothing on disk
+pub fn check_library(paths: &DoctorPaths) -> DoctorReport {
+    let mut report = DoctorReport::default();
+    let mut manager = BookManager::new_with_directory(&paths.books_dir);
+
+    let bookmarks = match Bookmarks::load_from_file(&paths.bookmarks_file.to_string_lossy()) {
+        Ok(bookmarks) => bookmarks,
+        Err(e) => {
+            report.bookmarks_error = Some(e.to_string());
+            Bookmarks::ephemeral()
+        }
+    };
+    let mut bookmarked: Vec<&String> = bookmarks.iter().map(|(path, _)| path).collect();
+    bookmarked.sort();
+    for path in bookmarked {
+        if !Path::new(path).exists() {
+            report.missing_books.push(path.clone());
+        } else if !manager.contains_book(path) {
+            let name = Path::new(path).file_stem().unwrap_or_default();
+            manager.add_book(path, &name.to_string_lossy());
+        }
+    }
+
+    let comment_files: HashMap<String, PathBuf> = paths
+        .comments_dir
+        .as_deref()
+        .and_then(|dir| BookComments::list_comment_files(dir).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .map(|info| (info.file_name(), info.path))
+        .collect();
+    let mut matched_comment_files = HashSet::new();
+    let mut cache_dirs = HashSet::new();
+
+    let mut books: Vec<String> = manager.books.iter().map(|book| book.path.clone()).collect();
+    books.sort();
+    for book in &books {
+        report.books_checked += 1;
+        cache_dirs.insert(ImageStorage::book_dir_name(Path::new(book)));
+        let comments_name = BookComments::file_name_for(Path::new(book));
+        let comments_file = comment_files.get(&comments_name);
+        if comments_file.is_some() {
+            matched_comment_files.insert(comments_name);
+        }
+
+        let mut doc = match open_book(&manager, book) {
+            Ok(doc) => doc,
+            Err(problem) => {
+                report.broken_books.push((book.clone(), problem));
+                continue;
+            }
+        };
+        check_bookmarks(&doc, book, &bookmarks, &mut report);
+        if let Some(file) = comments_file {
+            check_comments(&mut doc, book, file, &mut report);
+        }
+    }
+
+    let mut unmatched: Vec<PathBuf> = comment_files
+        .into_iter()
+        .filter(|(name, _)| !matched_comment_files.contains(name))
+        .map(|(_, path)| path)
+        .collect();
+    unmatched.sort();
+    report.unmatched_comment_files = unmatched;
+    report.orphaned_caches = orphaned_caches(&paths.image_cache_dir, &cache_dirs);
+    report
+}
+
+fn open_book(manager: &BookManager, path: &str) -> Result<EpubDoc<BufReader<File>>, BookProblem> {
+    match manager.load_epub(path) {
+        Ok(doc) if doc.spine.is_empty() => Err(BookProblem::NoChapters),
+        Ok(doc) => Ok(doc),
+        Err(e) => Err(classify_failure(Path::new(path), &e)),
+    }
+}
+
+/// Tell apart a file that isn't an EPUB at all from one the parser rejects
+fn classify_failure(path: &Path, error: &str) -> BookProblem {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => return BookProblem::Unreadable(e.to_string()),
+    };
+    if path.extension().is_some_and(|ext| ext != "epub") {
+        return BookProblem::Unreadable(error.to_string());
+    }
+    let Ok(mut archive) = zip::ZipArchive::new(file) else {
+        return BookProblem::NotAnArchive;
+    };
+    if archive.by_name("META-INF/container.xml").is_err() {
+        return BookProblem::NotAnEpub;
+    }
+    BookProblem::Malformed(error.to_string())
+}
+
+fn check_bookmarks(
+    doc: &EpubDoc<BufReader<File>>,
+    book: &str,
+    bookmarks: &Bookmarks,
+    report: &mut DoctorReport,
+) {
+    let primary = bookmarks
+        .get_bookmark(book)
+        .map(|bookmark| (None, &bookmark.chapter_href));
+    let threads = bookmarks
+        .threads(book)
+        .iter()
+        .map(|thread| (Some(&thread.name), &thread.chapter_href));
+    for (thread, chapter_href) in primary.into_iter().chain(threads) {
+        if App::find_chapter_index_by_href(doc, chapter_href).is_none() {
+            report.stale_bookmarks.push(StaleBookmark {
+                book: book.to_string(),
+                chapter_href: chapter_href.clone(),
+                thread: thread.cloned(),
+            });
+        }
+    }
+}
+
+fn check_comments(
+    doc: &mut EpubDoc<BufReader<File>>,
+    book: &str,
+    comments_file: &Path,
+    report: &mut DoctorReport,
+) {
+    let Ok(comments) = BookComments::new_with_path(comments_file.to_path_buf()) else {
+        return;
+    };
+    let mut chapters: HashMap<String, Option<Vec<String>>> = HashMap::new();
+    for comment in comments.get_all_comments() {
+        let node_texts = chapters
+            .entry(comment.chapter_href.clone())
+            .or_insert_with(|| {
+                CommentsViewer::load_chapter_document(doc, &comment.chapter_href)
+                    .map(|document| document.blocks.iter().map(Node::plain_text).collect())
+            });
+        if let Some(problem) = comment_problem(comment, node_texts.as_deref()) {
+            report.stale_comments.push(StaleComment {
+                book: book.to_string(),
+                chapter_href: comment.chapter_href.clone(),
+                node_index: comment.node_index(),
+                problem,
+            });
+        }
+    }
+}
+
+/// What keeps a comment from being shown, given its chapter's node texts
+/// (None when the chapter isn't in the book)
+fn comment_problem(comment: &Comment, node_texts: Option<&[String]>) -> Option<CommentProblem> {
+    match node_texts {
+        None => Some(CommentProblem::ChapterMissing),
+        Some(node_texts) => BookComments::locate_in(comment, node_texts)
+            .is_none()
+            .then_some(CommentProblem::ParagraphMissing),
+    }
+}
+
+/// Directories of the image cache named after no book in `known`
+fn orphaned_caches(cache_dir: &Path, known: &HashSet<String>) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(cache_dir) else {
+        return Vec::new();
+    };
+    let mut orphaned: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_dir())
+        .filter(|path| {
+            path.file_name()
+                .is_some_and(|name| !known.contains(&*name.to_string_lossy()))
+        })
+        .collect();
+    orphaned.sort();
+    orphaned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comments::CommentTarget;
+    use crate::comments::text_fingerprint;
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn library() -> (TempDir, DoctorPaths) {
+        let root = TempDir::new().unwrap();
+        let paths = DoctorPaths {
+            books_dir: root.path().join("books").to_string_lossy().into_owned(),
+            bookmarks_file: root.path().join(BOOKMARKS_FILENAME),
+            comments_dir: Some(root.path().join("comments")),
+            image_cache_dir: root.path().join(IMAGE_CACHE_DIR),
+        };
+        fs::create_dir_all(&paths.books_dir).unwrap();
+        fs::create_dir_all(paths.comments_dir.as_ref().unwrap()).unwrap();
+        (root, paths)
+    }
+
+    #[test]
+    fn test_reports_and_prunes_a_broken_library() {
+        let (_root, paths) = library();
+        let books = Path::new(&paths.books_dir);
+
+        let garbage = books.join("garbage.epub");
+        fs::write(&garbage, b"not a book").unwrap();
+        let plain_zip = books.join("plain_zip.epub");
+        let mut zip = zip::ZipWriter::new(File::create(&plain_zip).unwrap());
+        zip.start_file("readme.txt", zip::write::FileOptions::default())
+            .unwrap();
+        zip.write_all(b"hello").unwrap();
+        zip.finish().unwrap();
+
+        let mut bookmarks = Bookmarks::with_file(&paths.bookmarks_file.to_string_lossy());
+        let gone = books.join("gone.epub").to_string_lossy().into_owned();
+        bookmarks.update_bookmark(&gone, "ch1.xhtml".to_string(), Some(0), Some(0), Some(3));
+        bookmarks.save().unwrap();
+
+        fs::create_dir_all(paths.image_cache_dir.join("garbage")).unwrap();
+        fs::create_dir_all(paths.image_cache_dir.join("Removed Book/images")).unwrap();
+        let stray = paths.comments_dir.as_ref().unwrap().join("book_0123.yaml");
+        fs::write(&stray, "[]").unwrap();
+
+        let report = check_library(&paths);
+        assert_eq!(report.books_checked, 2);
+        assert_eq!(
+            report.broken_books,
+            vec![
+                (
+                    garbage.to_string_lossy().into_owned(),
+                    BookProblem::NotAnArchive
+                ),
+                (
+                    plain_zip.to_string_lossy().into_owned(),
+                    BookProblem::NotAnEpub
+                ),
+            ]
+        );
+        assert_eq!(report.missing_books, vec![gone.clone()]);
+        assert_eq!(report.unmatched_comment_files, vec![stray.clone()]);
+        assert_eq!(
+            report.orphaned_caches,
+            vec![paths.image_cache_dir.join("Removed Book")]
+        );
+        assert!(
+            report
+                .to_string()
+                .contains("garbage.epub: not a zip archive")
+        );
+
+        let summary = report.fix(&paths).unwrap();
+        assert_eq!(
+            summary,
+            FixSummary {
+                bookmarks: 1,
+                caches: 1
+            }
+        );
+        let bookmarks = Bookmarks::load_from_file(&paths.bookmarks_file.to_string_lossy()).unwrap();
+        assert!(bookmarks.get_bookmark(&gone).is_none());
+        assert!(paths.image_cache_dir.join("garbage").exists());
+        assert!(stray.exists());
+
+        let report = check_library(&paths);
+        assert!(report.missing_books.is_empty() && report.orphaned_caches.is_empty());
+    }
+
+    #[test]
+    fn test_comment_loses_its_place_when_the_passage_is_gone() {
+        let mut comment = Comment {
+            chapter_href: "ch1.xhtml".to_string(),
+            target: CommentTarget::Paragraph {
+                paragraph_index: 2,
+                word_range: None,
+            },
+            content: "note".to_string(),
+            updated_at: chrono::Utc::now(),
+            fingerprint: text_fingerprint("The moved paragraph"),
+        };
+        let texts = |texts: &[&str]| texts.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+
+        let moved = texts(&["Intro", "The moved paragraph", "Other", "More"]);
+        assert_eq!(comment_problem(&comment, Some(&moved)), None);
+        let rewritten = texts(&["Intro", "Other", "More"]);
+        assert_eq!(
+            comment_problem(&comment, Some(&rewritten)),
+            Some(CommentProblem::ParagraphMissing)
+        );
+        assert_eq!(
+            comment_problem(&comment, None),
+            Some(CommentProblem::ChapterMissing)
+        );
+
+        comment.fingerprint = None;
+        assert_eq!(comment_problem(&comment, Some(&rewritten)), None);
+        assert_eq!(
+            comment_problem(&comment, Some(&texts(&["Intro"]))),
+            Some(CommentProblem::ParagraphMissing)
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Books dropped into the library since it was last scanned.
+//!
+//! Each scan stores its time in `library_scan.json` next to the bookmarks
+//! file. Books whose files were modified after the previous scan are marked
+//! new and listed first until they are opened; the very first scan has
+//! nothing to compare with and marks none.
+
+use crate::book_manager::BookInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+
+pub const LIBRARY_SCAN_FILENAME: &str = "library_scan.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct LibraryScan {
+    /// Seconds since the epoch of the last scan
+    last_scan: Option<u64>,
+    /// Paths of the books found new and not opened since
+    #[serde(default)]
+    new_books: BTreeSet<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct NewBooks {
+    scan: LibraryScan,
+    file_path: Option<String>,
+}
+
+impl NewBooks {
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        let Some(path) = file_path else {
+            return Self::default();
+        };
+        let scan = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse library scan {path}: {e}");
+                LibraryScan::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LibraryScan::default(),
+            Err(e) => {
+                log::error!("Failed to load library scan from {path}: {e}");
+                LibraryScan::default()
+            }
+        };
+        Self {
+            scan,
+            file_path: Some(path.to_string()),
+        }
+    }
+
+    /// Record a scan at `now` (seconds since the epoch) that found `books`:
+    /// those modified since the last scan become new, and books no longer
+    /// there are forgotten
+    pub fn update(&mut self, books: &[BookInfo], now: u64) {
+        if let Some(last_scan) = self.scan.last_scan {
+            self.scan.new_books.extend(
+                books
+                    .iter()
+                    .filter(|book| book.mtime > last_scan)
+                    .map(|book| book.path.clone()),
+            );
+        }
+        self.scan
+            .new_books
+            .retain(|path| books.iter().any(|book| &book.path == path));
+        self.scan.last_scan = Some(now);
+    }
+
+    pub fn is_new(&self, path: &str) -> bool {
+        self.scan.new_books.contains(path)
+    }
+
+    /// Stop showing the book as new; returns whether it was
+    pub fn mark_opened(&mut self, path: &str) -> bool {
+        self.scan.new_books.remove(path)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            fs::write(path, serde_json::to_string_pretty(&self.scan)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book(path: &str, mtime: u64) -> BookInfo {
+        BookInfo {
+            path: path.to_string(),
+            display_name: path.to_string(),
+            mtime,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_books_added_since_the_last_scan_are_new_until_opened() {
+        let dir = tempfile::tempdir().unwrap();
+        let scan_path = dir.path().join(LIBRARY_SCAN_FILENAME);
+        let scan_path = scan_path.to_str().unwrap();
+
+        // The first scan only records its time
+        let mut new_books = NewBooks::load_or_ephemeral(Some(scan_path));
+        new_books.update(&[book("old.epub", 100)], 1_000);
+        assert!(!new_books.is_new("old.epub"));
+        new_books.save().unwrap();
+
+        let mut new_books = NewBooks::load_or_ephemeral(Some(scan_path));
+        let books = [
+            book("old.epub", 100),
+            book("dropped.epub", 1_500),
+            book("another.epub", 1_600),
+        ];
+        new_books.update(&books, 2_000);
+        assert!(!new_books.is_new("old.epub"));
+        assert!(new_books.is_new("dropped.epub"));
+        assert!(new_books.is_new("another.epub"));
+
+        // Opening clears the flag, and a later scan doesn't bring it back
+        assert!(new_books.mark_opened("dropped.epub"));
+        assert!(!new_books.mark_opened("dropped.epub"));
+        new_books.save().unwrap();
+
+        // Books stay new across scans until opened, unless they are removed
+        let mut new_books = NewBooks::load_or_ephemeral(Some(scan_path));
+        new_books.update(&books, 3_000);
+        assert!(!new_books.is_new("dropped.epub"));
+        assert!(new_books.is_new("another.epub"));
+        new_books.update(&books[..2], 4_000);
+        new_books.update(&books, 5_000);
+        assert!(!new_books.is_new("another.epub"));
+    }
+}
@@ -0,0 +1,131 @@
+//! Where the log is written and how much goes into it.
+//!
+//! `BOOKOKRAT_LOG` picks the level (error, warn, info, debug, trace or off)
+//! and `BOOKOKRAT_LOG_FILE` the file, ahead of the `log_level` and
+//! `log_file` settings. By default info and above go to
+//! `~/.bookokrat_logs/bookokrat.log`, so the log doesn't pile up in
+//! whatever directory the reader was started from.
+
+use crate::settings;
+use crate::widget::onboarding::expand_tilde;
+use anyhow::{Context, Result};
+use log::{LevelFilter, warn};
+use simplelog::{ConfigBuilder, WriteLogger};
+use std::fs::{self, OpenOptions};
+use std::path::PathBuf;
+
+pub const LOG_LEVEL_ENV: &str = "BOOKOKRAT_LOG";
+pub const LOG_FILE_ENV: &str = "BOOKOKRAT_LOG_FILE";
+pub const DEFAULT_LOG_LEVEL: LevelFilter = LevelFilter::Info;
+const LOG_DIRNAME: &str = ".bookokrat_logs";
+const LOG_FILENAME: &str = "bookokrat.log";
+
+/// The level named by `value`, in any case; None for anything else
+fn parse_level(value: &str) -> Option<LevelFilter> {
+    value.trim().parse().ok()
+}
+
+/// The level of the environment variable, else of the setting, else the
+/// default; also the value that was ignored, if one was
+fn log_level(env: Option<&str>, setting: Option<&str>) -> (LevelFilter, Option<String>) {
+    let mut ignored = None;
+    for value in [env, setting].into_iter().flatten() {
+        match parse_level(value) {
+            Some(level) => return (level, ignored),
+            None => {
+                ignored.get_or_insert_with(|| value.to_string());
+            }
+        }
+    }
+    (DEFAULT_LOG_LEVEL, ignored)
+}
+
+/// The file of the environment variable, else of the setting, else the
+/// default in the home directory (the current one without a home)
+fn log_path(env: Option<&str>, setting: Option<&str>) -> PathBuf {
+    match env.or(setting).filter(|path| !path.trim().is_empty()) {
+        Some(path) => expand_tilde(path),
+        None => home::home_dir()
+            .map(|home| home.join(LOG_DIRNAME).join(LOG_FILENAME))
+            .unwrap_or_else(|| PathBuf::from(LOG_FILENAME)),
+    }
+}
+
+/// Start logging as the environment and settings say, ahead of loading
+/// the settings so their warnings are kept. Returns the file written to.
+pub fn init() -> Result<PathBuf> {
+    let env_level = std::env::var(LOG_LEVEL_ENV).ok();
+    let env_file = std::env::var(LOG_FILE_ENV).ok();
+    let settings = settings::read_log_settings();
+    let (level, ignored) = log_level(env_level.as_deref(), settings.log_level.as_deref());
+    let path = log_path(env_file.as_deref(), settings.log_file.as_deref());
+
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create log directory {}", parent.display()))?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .open(&path)
+        .with_context(|| format!("Failed to open log file {}", path.display()))?;
+
+    WriteLogger::init(
+        level,
+        ConfigBuilder::new()
+            .set_max_level(level)
+            .set_target_level(LevelFilter::Error)
+            .set_time_format_rfc3339()
+            .add_filter_ignore_str("html5ever")
+            .build(),
+        file,
+    )?;
+
+    if let Some(value) = ignored {
+        warn!("Unknown log level {value:?}, logging at {level}");
+    }
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_and_file_from_env_then_settings() {
+        assert_eq!(log_level(None, None), (LevelFilter::Info, None));
+        assert_eq!(log_level(Some("DEBUG"), Some("warn")).0, LevelFilter::Debug);
+        assert_eq!(log_level(None, Some(" off ")).0, LevelFilter::Off);
+        assert_eq!(
+            log_level(Some("loud"), Some("trace")),
+            (LevelFilter::Trace, Some("loud".to_string()))
+        );
+        assert_eq!(
+            log_level(Some("loud"), None),
+            (DEFAULT_LOG_LEVEL, Some("loud".to_string()))
+        );
+
+        assert_eq!(
+            log_path(Some("/tmp/b.log"), Some("/tmp/a.log")),
+            PathBuf::from("/tmp/b.log")
+        );
+        assert_eq!(
+            log_path(None, Some("/tmp/a.log")),
+            PathBuf::from("/tmp/a.log")
+        );
+        let default = log_path(None, Some(""));
+        assert!(default.ends_with(".bookokrat_logs/bookokrat.log"));
+    }
+
+    #[test]
+    fn test_log_settings_are_picked_out_of_the_settings_file() {
+        let parsed: settings::LogSettings =
+            serde_yaml::from_str("version: 1\ntheme: Oceanic Next\nlog_level: debug\n").unwrap();
+        assert_eq!(parsed.log_level.as_deref(), Some("debug"));
+        assert_eq!(parsed.log_file, None);
+    }
+}
@@ -1,65 +1,106 @@
-use std::{fs::File, io::stdout};
+use std::io::stdout;
 
 use anyhow::Result;
-use crossterm::{
-    event::{DisableMouseCapture, EnableMouseCapture},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
-};
 use log::{error, info};
 use ratatui::{Terminal, backend::CrosstermBackend};
-use simplelog::{LevelFilter, WriteLogger};
 
 // Use modules from the library crate
-use bookokrat::event_source::KeyboardEventSource;
+use bookokrat::book_download;
+use bookokrat::color_mode::{self, ColorMode};
+use bookokrat::event_source::{EventSource, KeyboardEventSource};
+use bookokrat::i18n;
+use bookokrat::inputs::event_recorder::{RecordingEventSource, RecordingHeader};
+use bookokrat::library_doctor;
+use bookokrat::logging;
 use bookokrat::main_app::{App, run_app_with_event_source};
 use bookokrat::panic_handler;
 use bookokrat::settings;
+use bookokrat::terminal_state;
 use bookokrat::theme::load_custom_themes;
 
 fn main() -> Result<()> {
     // Initialize panic handler first, before any other setup
     panic_handler::initialize_panic_handler();
 
-    // Initialize logging with html5ever DEBUG logs filtered out
-    WriteLogger::init(
-        LevelFilter::Debug,
-        simplelog::ConfigBuilder::new()
-            .set_max_level(LevelFilter::Debug)
-            .add_filter_ignore_str("html5ever")
-            .build(),
-        File::create("bookokrat.log")?,
-    )?;
+    // The log starts first so problems with the settings file are logged;
+    // it reads the logging settings on its own
+    let log_path = logging::init()?;
 
-    info!("Starting Bookokrat EPUB reader");
+    // Load settings from ~/.bookokrat_settings.yaml
+    settings::load_settings();
 
-    // Terminal initialization
-    enable_raw_mode()?;
-    let mut stdout = stdout();
+    info!(
+        "Starting Bookokrat EPUB reader, logging to {}",
+        log_path.display()
+    );
+
+    let no_color_flag = std::env::args().skip(1).any(|arg| arg == "--no-color");
+    let book_url = std::env::args()
+        .skip(1)
+        .find(|arg| book_download::is_url(arg));
+    let record_path = flag_value("--record");
+
+    if let Some(recording) = flag_value("--replay") {
+        return replay(&recording);
+    }
+    if std::env::args().skip(1).any(|arg| arg == "--doctor") {
+        return doctor();
+    }
 
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-    let backend = CrosstermBackend::new(stdout);
+    // Terminal initialization
+    terminal_state::claim_terminal(true)?;
+    terminal_state::install_quit_signal_handlers();
+    let backend = CrosstermBackend::new(stdout());
     let mut terminal = Terminal::new(backend)?;
 
-    // Load settings from ~/.bookokrat_settings.yaml
-    settings::load_settings();
+    i18n::set_language(settings::get_language());
+
+    // Pick the color mode before any theme palette is built
+    let color_mode = if no_color_flag || color_mode::no_color_env() {
+        ColorMode::None
+    } else {
+        settings::get_color_mode()
+    };
+    color_mode::set_color_mode(color_mode);
+    info!(
+        "Color mode: {color_mode:?}, level: {:?}",
+        color_mode::color_level()
+    );
+    // Only a theme left on auto depends on the terminal's background
+    if settings::get_theme_name() == settings::AUTO_THEME {
+        let background = color_mode::detect_terminal_background();
+        info!("Terminal background: {background:?}");
+    }
 
     // Load custom themes from settings and apply saved theme
     load_custom_themes();
 
     // Create app and run it
     let mut app = App::new();
-    let mut event_source = KeyboardEventSource;
-    let res = run_app_with_event_source(&mut terminal, &mut app, &mut event_source);
+    if let Some(url) = book_url {
+        app.open_url(&url);
+    }
+    let mut event_source: Box<dyn EventSource> = match record_path {
+        Some(path) => {
+            let size = terminal.size()?;
+            let header =
+                RecordingHeader::new(size.width, size.height, settings::get_books_directory());
+            info!("Recording input events to {path}");
+            Box::new(RecordingEventSource::create(
+                std::path::Path::new(&path),
+                &header,
+                KeyboardEventSource,
+            )?)
+        }
+        None => Box::new(KeyboardEventSource),
+    };
+    let res = run_app_with_event_source(&mut terminal, &mut app, event_source.as_mut());
+
+    // Also covers the loop ending on an error, e.g. when the terminal goes away
+    app.save_session();
 
     // Restore terminal state
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    terminal_state::release_terminal()?;
 
     if let Err(err) = res {
         error!("Application error: {err:?}");
@@ -69,3 +110,52 @@ fn main() -> Result<()> {
     info!("Shutting down Bookokrat");
     Ok(())
 }
+
+/// The argument following `flag`, e.g. the file of `--record <file>`
+fn flag_value(flag: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    args.find(|arg| arg == flag)?;
+    args.next()
+}
+
+/// Check the library given after `--doctor`, or the configured one, and
+/// print what is wrong with it; `--fix` then prunes what can be pruned
+fn doctor() -> Result<()> {
+    let books_dir = flag_value("--doctor")
+        .filter(|arg| !arg.starts_with("--"))
+        .or_else(settings::get_books_directory)
+        .unwrap_or_else(|| ".".to_string());
+    let paths = library_doctor::DoctorPaths::for_library(&books_dir);
+    println!("Checking the library in {books_dir}");
+    let report = library_doctor::check_library(&paths);
+    print!("{report}");
+
+    if std::env::args().skip(1).any(|arg| arg == "--fix") {
+        let fixed = report.fix(&paths)?;
+        println!(
+            "\nRemoved {} bookmarks and {} image caches",
+            fixed.bookmarks, fixed.caches
+        );
+    }
+    Ok(())
+}
+
+/// Replay a `--record` file without a terminal and print where the final
+/// screen was saved
+#[cfg(feature = "devtools")]
+fn replay(recording: &str) -> Result<()> {
+    i18n::set_language(settings::get_language());
+    color_mode::set_color_mode(settings::get_color_mode());
+    load_custom_themes();
+
+    let svg_path = bookokrat::devtools::replay(std::path::Path::new(recording))?;
+    println!("Final screen written to {}", svg_path.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "devtools"))]
+fn replay(_recording: &str) -> Result<()> {
+    anyhow::bail!(
+        "--replay needs a build with the devtools feature (cargo run --features devtools)"
+    )
+}
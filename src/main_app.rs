@@ -1,28 +1,62 @@
+use crate::book_download::{self, BookDownload, DownloadEvent};
 use crate::book_manager::BookManager;
+use crate::book_metadata::{self, BOOK_METADATA_FILENAME, MetadataCache, MetadataScan};
 use crate::book_search::{BookSearch, BookSearchAction};
 use crate::book_stat::{BookStat, BookStatAction};
-use crate::bookmarks::Bookmarks;
+use crate::book_watch::BookWatch;
+use crate::bookmarks::{BOOKMARKS_FILENAME, Bookmarks, ChapterReadState, ReadingThread};
+use crate::chapter_export;
+use crate::color_mode::TerminalBackground;
 use crate::comments::BookComments;
+use crate::comments_viewer_state::{COMMENTS_VIEWER_FILENAME, ViewerPositions};
 use crate::event_source::EventSource;
+use crate::glossing::{Glossary, KNOWN_WORDS_FILENAME, KnownWords, WordFrequencies};
+use crate::i18n;
 use crate::images::book_images::BookImages;
-use crate::images::image_popup::ImagePopup;
+use crate::images::image_popup::{CAPTION_BAND_HEIGHT, ImagePopup};
 use crate::images::image_storage::ImageStorage;
 use crate::inputs::{ClickType, KeySeq, MouseTracker, map_keys_to_input};
+use crate::instance_lock::{self, InstanceLock, LockStatus};
 use crate::jump_list::{JumpList, JumpLocation};
-use crate::markdown_text_reader::MarkdownTextReader;
+use crate::library_scan::{LIBRARY_SCAN_FILENAME, NewBooks};
+use crate::markdown_text_reader::{ActiveSection, MarkdownTextReader};
 use crate::navigation_panel::{CurrentBookInfo, NavigationPanel, TableOfContents};
 use crate::notification::NotificationManager;
+use crate::parsing::front_matter;
+use crate::parsing::private_use::GlyphMap;
 use crate::parsing::text_generator::TextGenerator;
-use crate::parsing::toc_parser::TocParser;
-use crate::reading_history::ReadingHistory;
+use crate::parsing::toc_parser::{Landmark, LandmarkKind, TocParser};
+use crate::pinned_books::{PINNED_BOOKS_FILENAME, PinnedBooks};
+use crate::reading_goal::{READING_GOALS_FILENAME, ReadingGoals};
+use crate::reading_history::{ReadingHistory, ReadingHistoryAction};
+use crate::resume_stack::ResumeStack;
 use crate::search::{SearchMode, SearchablePanel};
-use crate::search_engine::SearchEngine;
-use crate::settings;
+use crate::search_engine::{MatchTarget, SearchEngine, SearchUnit};
+use crate::search_history::{SEARCH_HISTORY_FILENAME, SearchHistory, SearchScope};
+use crate::session_books::{SessionBooks, SessionPosition};
+use crate::settings::{self, BookBoundary, CtrlZ, NavPanelMode};
+use crate::system_appearance::{AppearanceProbe, RealAppearanceProbe, SystemAppearance};
 use crate::system_command::{RealSystemCommandExecutor, SystemCommandExecutor};
 use crate::table_of_contents::TocItem;
+use crate::terminal_state;
 use crate::theme::{current_theme, current_theme_name};
 use crate::types::LinkInfo;
-use crate::widget::help_popup::{HelpPopup, HelpPopupAction};
+use crate::widget::book_info::BookInfoPopup;
+use crate::widget::book_switcher::{BookSwitcher, BookSwitcherAction, SwitcherEntry};
+use crate::widget::chapter_picker::{ChapterPicker, ChapterPickerAction};
+use crate::widget::command_palette::{CommandPalette, CommandPaletteAction};
+use crate::widget::comment_storage::{CommentFileEntry, CommentStorage, CommentStorageAction};
+use crate::widget::comments_viewer::CommentsViewerAction;
+use crate::widget::figure_list::{FigureEntry, FigureList, FigureListAction};
+use crate::widget::help_bar::{self, HelpBarAction, HelpBarItem, HelpBarMenu, HelpBarMenuAction};
+use crate::widget::help_popup::HelpPopup;
+use crate::widget::lock_warning::{LockWarningAction, LockWarningPopup};
+use crate::widget::onboarding::{OnboardingAction, OnboardingPopup};
+use crate::widget::popup_geometry::{self, PopupKind};
+use crate::widget::rare_words::{RareWords, RareWordsAction};
+use crate::widget::reading_stats::ReadingStats;
+use crate::widget::reading_threads::{ReadingThreads, ReadingThreadsAction, ThreadEntry};
+use crate::widget::resurfaced_comment::{ResurfacedComment, ResurfacedCommentAction};
 use crate::widget::theme_selector::{ThemeSelector, ThemeSelectorAction};
 use image::GenericImageView;
 use log::warn;
@@ -34,10 +68,11 @@ pub enum ChapterDirection {
 }
 
 use std::io::BufReader;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind};
 use epub::doc::EpubDoc;
 use log::{debug, error, info};
@@ -46,16 +81,30 @@ use ratatui::{
     layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Paragraph},
+    widgets::{Block, Borders, Paragraph, Wrap},
 };
 
+mod command_line;
+pub mod commands;
+mod quit_confirm;
+#[cfg(any(test, feature = "test-utils"))]
+mod test_api;
+#[cfg(any(test, feature = "test-utils"))]
+pub use test_api::{ReadingPosition, TestApi};
+
 struct EpubBook {
     file: String,
     epub: EpubDoc<BufReader<std::fs::File>>,
+    landmarks: Vec<Landmark>,
 }
 impl EpubBook {
-    fn new(file: String, doc: EpubDoc<BufReader<std::fs::File>>) -> Self {
-        Self { file, epub: doc }
+    fn new(file: String, mut doc: EpubDoc<BufReader<std::fs::File>>) -> Self {
+        let landmarks = TocParser::parse_landmarks(&mut doc);
+        Self {
+            file,
+            epub: doc,
+            landmarks,
+        }
     }
 
     fn total_chapters(&self) -> usize {
@@ -67,6 +116,22 @@ impl EpubBook {
     }
 }
 
+/// Reading time stops counting after this long without any input
+const READING_IDLE_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+/// Gaps between ticks longer than this (e.g. a suspended laptop) are not counted
+const READING_MAX_TICK_GAP: Duration = Duration::from_secs(10);
+const READING_GOALS_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Navigation panel width bounds and keyboard step, in percent of the terminal width
+const NAV_PANEL_MIN_PERCENT: u16 = 15;
+const NAV_PANEL_MAX_PERCENT: u16 = 60;
+const NAV_PANEL_STEP_PERCENT: u16 = 2;
+/// Columns left for the navigation panel while collapsed in auto mode
+const NAV_PANEL_STRIP_WIDTH: u16 = 3;
+
+/// How many recently read books Space+[ / Space+] cycle through
+const RESUME_STACK_SIZE: usize = 10;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AppAction {
     Quit,
@@ -85,19 +150,98 @@ pub struct App {
     last_bookmark_save: std::time::Instant,
     mouse_tracker: MouseTracker,
     key_sequence: KeySeq,
+    /// Key that starts the Space+ sequences (the `space_key` setting)
+    leader_key: char,
+    /// What Ctrl+z does (the `ctrl_z` setting)
+    ctrl_z: CtrlZ,
     reading_history: Option<ReadingHistory>,
+    reading_threads: Option<ReadingThreads>,
+    comment_storage: Option<CommentStorage>,
+    /// Reading thread of the open book that saves follow; None for the primary bookmark
+    active_thread: Option<String>,
+    /// Set when rare word glossing is on and the frequency list loaded
+    glossary: Option<Glossary>,
+    rare_words: Option<RareWords>,
+    reading_stats: Option<ReadingStats>,
+    resurfaced_comment: Option<ResurfacedComment>,
+    book_info: Option<BookInfoPopup>,
     image_popup: Option<ImagePopup>,
+    figure_list: Option<FigureList>,
     terminal_size: Rect,
     profiler: Arc<Mutex<Option<pprof::ProfilerGuard<'static>>>>,
     book_stat: BookStat,
     jump_list: JumpList,
+    resume_stack: Option<ResumeStack>,
+    session_books: SessionBooks,
+    book_switcher: Option<BookSwitcher>,
     book_search: Option<BookSearch>,
+    search_history: SearchHistory,
     help_popup: Option<HelpPopup>,
     comments_viewer: Option<crate::widget::comments_viewer::CommentsViewer>,
     theme_selector: Option<ThemeSelector>,
+    chapter_picker: Option<ChapterPicker>,
+    command_palette: Option<CommandPalette>,
+    /// Name keys of the commands run from the palette, most recent first
+    recent_commands: Vec<&'static str>,
+    onboarding: Option<OnboardingPopup>,
+    lock_warning: Option<LockWarningPopup>,
+    /// Focus to restore once the lock warning is answered
+    focus_before_lock_warning: FocusedPanel,
+    instance_lock: Option<InstanceLock>,
+    book_download: Option<BookDownload>,
+    /// Titles and authors read from the books, shown instead of file names
+    book_metadata: MetadataCache,
+    metadata_scan: Option<MetadataScan>,
+    /// Books added since the library was last scanned, listed first
+    new_books: NewBooks,
+    pinned_books: PinnedBooks,
+    comments_viewer_positions: ViewerPositions,
+    /// The OS dark/light mode, followed with `theme: system`
+    system_appearance: Option<SystemAppearance>,
+    /// Modification time of the open book's file, to reload it when rewritten
+    book_watch: Option<BookWatch>,
+    /// The book changed while a comment was typed or text selected
+    book_reload_pending: bool,
     notifications: NotificationManager,
-    help_bar_area: Rect,
+    /// Buttons of the help bar where they were last drawn
+    help_bar_items: Vec<(HelpBarItem, Rect)>,
+    help_bar_menu: Option<HelpBarMenu>,
     zen_mode: bool,
+    reading_goals: ReadingGoals,
+    last_reading_tick: Instant,
+    last_user_input: Instant,
+    /// No input for `idle_timeout`; cleared by the next key or mouse event
+    idle: bool,
+    idle_timeout: Option<Duration>,
+    idle_dim: bool,
+    last_reading_goals_save: Instant,
+    last_scroll_position: Option<(usize, usize)>,
+    /// Word count of each spine chapter, used for whole-book progress;
+    /// None until the chapter is indexed
+    chapter_word_counts: Vec<Option<usize>>,
+    /// Spine chapters with (almost) no text, such as covers and ad pages
+    empty_chapters: Vec<bool>,
+    /// Chapters of the open book whose unknown glyphs were already reported
+    glyph_notices: std::collections::HashSet<usize>,
+    nav_panel_percent: u16,
+    nav_panel_mode: NavPanelMode,
+    /// Whether the last draw showed the navigation panel collapsed
+    nav_panel_collapsed: bool,
+    /// Set while the border between the panels is being dragged
+    resizing_nav_panel: bool,
+    quit_confirm: quit_confirm::QuitConfirm,
+    command_line: command_line::CommandLine,
+    /// Off while the terminal's own selection has the mouse (Space+m)
+    mouse_capture: bool,
+    /// Capture to apply on the terminal once the current events are handled
+    mouse_capture_change: Option<bool>,
+    /// Save the next frame drawn to files (Space+?)
+    screen_capture_pending: bool,
+    /// Give the terminal back to the shell once the current events are
+    /// handled (Ctrl+Z)
+    suspend_pending: bool,
+    /// Query of the last `*` search, which another `*` takes to the book search
+    selection_search: Option<String>,
 }
 
 pub trait VimNavMotions {
@@ -132,6 +276,19 @@ pub enum PopupWindow {
     Help,
     CommentsViewer,
     ThemeSelector,
+    ChapterPicker,
+    CommandPalette,
+    BookSwitcher,
+    FigureList,
+    Onboarding,
+    InstanceLock,
+    ReadingThreads,
+    CommentStorage,
+    RareWords,
+    ReadingStats,
+    ResurfacedComment,
+    HelpBarMenu,
+    BookInfo,
 }
 
 impl Default for App {
@@ -142,7 +299,8 @@ impl Default for App {
 
 impl App {
     pub fn new() -> Self {
-        Self::new_with_config(Some("/Users/yunzou/Desktop/current_reading_book"), Some("bookmarks.json"), true)
+        let books_directory = settings::get_books_directory();
+        Self::new_with_config(Some(books_directory.as_deref().unwrap_or("/Users/yunzou/Desktop/current_reading_book")), Some(BOOKMARKS_FILENAME), true)
     }
 
     /// Helper method to check if focus is on a main panel (not a popup)
@@ -279,15 +437,109 @@ impl App {
         auto_load_recent: bool,
         system_executor: Box<dyn SystemCommandExecutor>,
     ) -> Self {
-        let book_manager = match book_directory {
+        let mut book_manager = match book_directory {
             Some(dir) => BookManager::new_with_directory(dir),
             None => BookManager::new(),
         };
+        let book_metadata_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(BOOK_METADATA_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let book_metadata = MetadataCache::load_or_ephemeral(book_metadata_file.as_deref());
+        book_manager.apply_metadata(&book_metadata);
+        let pinned_books_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(PINNED_BOOKS_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let pinned_books = PinnedBooks::load_or_ephemeral(pinned_books_file.as_deref());
+        book_manager.apply_pinned(&pinned_books);
+        let library_scan_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(LIBRARY_SCAN_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let mut new_books = NewBooks::load_or_ephemeral(library_scan_file.as_deref());
+        let comments_viewer_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(COMMENTS_VIEWER_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let comments_viewer_positions =
+            ViewerPositions::load_or_ephemeral(comments_viewer_file.as_deref());
+        Self::record_library_scan(&mut book_manager, &mut new_books);
 
         let navigation_panel = NavigationPanel::new(&book_manager);
         let mut text_reader = MarkdownTextReader::new();
         text_reader.set_margin(settings::get_margin());
+        text_reader.set_render_images(settings::get_render_images());
+        text_reader.set_ruby_mode(settings::get_ruby_mode());
+        text_reader.set_copy_ruby_readings(settings::get_copy_ruby_readings());
+        text_reader.set_copy_quote_bars(settings::get_copy_quote_bars());
+        text_reader.set_copy_typography(settings::get_copy_typography());
+        text_reader.set_copy_image_alt_text(settings::get_copy_image_alt_text());
+        text_reader.set_max_blank_lines(settings::get_max_blank_lines());
+        text_reader.set_table_stack_width(settings::get_table_stack_width());
+        text_reader.set_list_indent(settings::get_list_indent());
+        text_reader.set_rule_style(settings::get_rule_style());
+        text_reader.set_rule_glyph(settings::get_rule_glyph());
+        text_reader.set_scroll_off(settings::get_scroll_off());
+        text_reader.set_jump_highlight(
+            Duration::from_millis(settings::get_jump_highlight_ms()),
+            settings::get_jump_highlight_style(),
+        );
+        text_reader.set_click_action(settings::get_click_action());
+        text_reader.set_show_section_progress(settings::get_show_section_progress());
+        text_reader.set_progress_bar(settings::get_progress_bar());
+        text_reader.set_reading_ruler(settings::get_reading_ruler());
+        text_reader.set_reading_ruler_row(settings::get_reading_ruler_row());
+        text_reader.set_strip_invisible(settings::get_strip_invisible_chars());
+        text_reader.set_glyph_map(GlyphMap::new(&settings::get_glyph_substitutions()));
+        text_reader.set_chapter_cache_capacity(
+            settings::get_prefetch_radius().map(|radius| 2 * radius + 1),
+        );
         let bookmarks = Bookmarks::load_or_ephemeral(bookmark_file);
+        let reading_goals_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(READING_GOALS_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let reading_goals = ReadingGoals::load_or_ephemeral(reading_goals_file.as_deref());
+        let search_history_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(SEARCH_HISTORY_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let search_history = SearchHistory::load_or_ephemeral(
+            search_history_file.as_deref(),
+            settings::get_merge_search_history(),
+        );
+        let known_words_file = bookmark_file.map(|file| {
+            std::path::Path::new(file)
+                .with_file_name(KNOWN_WORDS_FILENAME)
+                .to_string_lossy()
+                .into_owned()
+        });
+        let (glossary, glossary_error) = match load_glossary(known_words_file.as_deref()) {
+            Ok(glossary) => (glossary, None),
+            Err(e) => {
+                error!("Rare word glossing is off: {e:#}");
+                (
+                    None,
+                    Some(i18n::tr_args(
+                        "notify.glossary_failed",
+                        &[("error", &format!("{e:#}"))],
+                    )),
+                )
+            }
+        };
 
         let image_storage = Arc::new(ImageStorage::new_in_project_temp().unwrap_or_else(|e| {
             error!("Failed to initialize image storage: {e}. Using fallback.");
@@ -317,28 +569,105 @@ impl App {
             last_bookmark_save: std::time::Instant::now(),
             mouse_tracker: MouseTracker::new(),
             key_sequence: KeySeq::new(),
+            leader_key: settings::get_leader_key(),
+            ctrl_z: settings::get_ctrl_z(),
             reading_history: None,
+            reading_threads: None,
+            comment_storage: None,
+            active_thread: None,
+            glossary,
+            rare_words: None,
+            reading_stats: None,
+            resurfaced_comment: None,
+            book_info: None,
             image_popup: None,
+            figure_list: None,
             terminal_size,
             profiler: Arc::new(Mutex::new(None)),
             book_stat: BookStat::new(),
             jump_list: JumpList::new(20),
+            resume_stack: None,
+            session_books: SessionBooks::new(),
+            book_switcher: None,
             book_search: None,
+            search_history,
             help_popup: None,
             comments_viewer: None,
             theme_selector: None,
+            chapter_picker: None,
+            command_palette: None,
+            recent_commands: Vec::new(),
+            onboarding: None,
+            lock_warning: None,
+            focus_before_lock_warning: FocusedPanel::Main(MainPanel::NavigationList),
+            instance_lock: None,
+            book_download: None,
+            book_metadata,
+            new_books,
+            pinned_books,
+            comments_viewer_positions,
+            metadata_scan: None,
+            system_appearance: None,
+            book_watch: None,
+            book_reload_pending: false,
             notifications: NotificationManager::new(),
-            help_bar_area: Rect::default(),
+            help_bar_items: Vec::new(),
+            help_bar_menu: None,
             zen_mode: false,
+            reading_goals,
+            last_reading_tick: Instant::now(),
+            last_user_input: Instant::now(),
+            idle: false,
+            idle_timeout: match settings::get_idle_timeout_minutes() {
+                0 => None,
+                minutes => Some(Duration::from_secs(minutes * 60)),
+            },
+            idle_dim: settings::get_idle_dim(),
+            last_reading_goals_save: Instant::now(),
+            last_scroll_position: None,
+            chapter_word_counts: Vec::new(),
+            empty_chapters: Vec::new(),
+            glyph_notices: std::collections::HashSet::new(),
+            nav_panel_percent: settings::get_nav_panel_width()
+                .clamp(NAV_PANEL_MIN_PERCENT, NAV_PANEL_MAX_PERCENT),
+            nav_panel_mode: settings::get_nav_panel_mode(),
+            nav_panel_collapsed: false,
+            resizing_nav_panel: false,
+            quit_confirm: quit_confirm::QuitConfirm::default(),
+            command_line: command_line::CommandLine::default(),
+            mouse_capture: true,
+            mouse_capture_change: None,
+            screen_capture_pending: false,
+            suspend_pending: false,
+            selection_search: None,
         };
 
-        if auto_load_recent
+        if let Some(message) = glossary_error {
+            app.show_error(message);
+        }
+
+        // Only the interactive app competes with other instances for the
+        // bookmarks; lock before auto-loading could save anything
+        if auto_load_recent {
+            app.acquire_instance_lock();
+            app.start_metadata_scan();
+            if settings::get_theme_name() == settings::SYSTEM_THEME {
+                app.set_appearance_probe(Box::new(RealAppearanceProbe));
+            }
+        }
+
+        if auto_load_recent && app.book_manager.books.is_empty() {
+            // Empty library - help first-time users pick a books directory
+            app.previous_main_panel = MainPanel::NavigationList;
+            app.onboarding = Some(OnboardingPopup::new(app.book_manager.scan_directory()));
+            app.focused_panel = FocusedPanel::Popup(PopupWindow::Onboarding);
+        } else if auto_load_recent
             && let Some((recent_path, _)) = app.bookmarks.get_most_recent()
             && app.book_manager.contains_book(&recent_path)
         {
             if let Err(e) = app.open_book_for_reading_by_path(&recent_path) {
                 error!("Failed to auto-load most recent book: {e}");
-                app.show_error(format!("Failed to auto-load recent book: {e}"));
+                app.show_error(i18n::tr_args("notify.auto_load_failed", &[("error", &e)]));
             }
         } else if auto_load_recent && app.bookmarks.get_most_recent().is_none() {
             // No bookmarks exist - show help popup for first-time users
@@ -348,6 +677,20 @@ impl App {
             app.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
         }
 
+        if app.nav_panel_mode == NavPanelMode::Never && app.current_book.is_some() {
+            app.zen_mode = true;
+            app.set_main_panel_focus(MainPanel::Content);
+        }
+
+        if auto_load_recent && settings::get_resurface_comments() && app.current_book.is_some() {
+            app.open_resurfaced_comment(true);
+        }
+
+        if app.lock_warning.is_some() {
+            app.focus_before_lock_warning = app.focused_panel;
+            app.focused_panel = FocusedPanel::Popup(PopupWindow::InstanceLock);
+        }
+
         app
     }
 
@@ -388,6 +731,11 @@ impl App {
             self.save_bookmark_with_throttle(true);
             self.load_epub(&path, false)?;
 
+            if self.new_books.mark_opened(&path) {
+                self.book_manager.clear_new(&path);
+                self.navigation_panel.update_book_names(&self.book_manager);
+                self.save_library_scan();
+            }
             self.navigation_panel.current_book_index = Some(book_index);
             self.focused_panel = FocusedPanel::Main(MainPanel::Content);
 
@@ -397,6 +745,211 @@ impl App {
         }
     }
 
+    /// Download the EPUB at `url` (or reuse the cached copy) and open it once
+    /// it arrives; progress shows in the status bar
+    pub fn open_url(&mut self, url: &str) {
+        let Some(cache_dir) = book_download::downloads_dir() else {
+            self.show_error(i18n::tr("notify.download_no_home"));
+            return;
+        };
+        self.show_info(i18n::tr_args("notify.downloading", &[("url", &url)]));
+        self.book_download = Some(BookDownload::start(url, &cache_dir));
+    }
+
+    /// Handle progress of a running download; returns true when the screen
+    /// needs a redraw
+    pub fn poll_book_download(&mut self) -> bool {
+        let Some(download) = self.book_download.as_ref() else {
+            return false;
+        };
+        let events = download.poll();
+        let name = book_download::display_name(download.url());
+        let changed = !events.is_empty();
+
+        for event in events {
+            match event {
+                DownloadEvent::Progress { received, total } => {
+                    let received_mb = received as f64 / (1024.0 * 1024.0);
+                    let size = format!("{received_mb:.1}");
+                    let message = match total {
+                        Some(total) if total > 0 => i18n::tr_args(
+                            "notify.download_progress",
+                            &[
+                                ("name", &name),
+                                ("percent", &(received * 100 / total)),
+                                ("size", &size),
+                            ],
+                        ),
+                        _ => i18n::tr_args(
+                            "notify.download_size",
+                            &[("name", &name), ("size", &size)],
+                        ),
+                    };
+                    self.show_info(message);
+                }
+                DownloadEvent::Finished(Ok(path)) => {
+                    self.book_download = None;
+                    self.open_downloaded_book(&path.to_string_lossy(), &name);
+                }
+                DownloadEvent::Finished(Err(e)) => {
+                    self.book_download = None;
+                    self.show_error(i18n::tr_args(
+                        "notify.download_failed",
+                        &[("name", &name), ("error", &e)],
+                    ));
+                }
+            }
+        }
+        changed
+    }
+
+    /// Note the books added since the last scan, list them first and store
+    /// the time of this one
+    fn record_library_scan(book_manager: &mut BookManager, new_books: &mut NewBooks) {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or(0);
+        new_books.update(&book_manager.books, now);
+        book_manager.apply_new_books(new_books);
+    }
+
+    /// Persist the library scan; kept out of startup so that building an
+    /// `App` writes nothing
+    fn save_library_scan(&self) {
+        if let Err(e) = self.new_books.save() {
+            error!("Failed to save library scan: {e}");
+        }
+    }
+
+    /// Read titles and authors of the books not in the metadata cache yet
+    fn start_metadata_scan(&mut self) {
+        let paths: Vec<String> = self
+            .book_manager
+            .books
+            .iter()
+            .map(|book| book.path.clone())
+            .collect();
+        self.metadata_scan = Some(self.book_metadata.scan(&paths));
+    }
+
+    pub fn poll_metadata_scan(&mut self) -> bool {
+        let Some(scan) = self.metadata_scan.as_ref() else {
+            return false;
+        };
+        let (books, done) = scan.take_results();
+        let mut changed = false;
+        for book in books {
+            changed |= self.book_manager.set_metadata(&book.path, &book.metadata);
+            self.book_metadata
+                .insert(&book.path, book.mtime, book.metadata);
+        }
+        if changed {
+            self.navigation_panel.update_book_names(&self.book_manager);
+        }
+        if done {
+            self.metadata_scan = None;
+            if let Err(e) = self.book_metadata.save() {
+                error!("Failed to save book metadata: {e}");
+            }
+        }
+        changed
+    }
+
+    /// Pin the book to the top of the list, or return it to its place;
+    /// the selection stays on it
+    fn toggle_pinned(&mut self, book_index: usize) {
+        let Some(book) = self.book_manager.get_book_info(book_index) else {
+            return;
+        };
+        let path = book.path.clone();
+        let name = book.display_name.clone();
+        let pinned = self.pinned_books.toggle(&path);
+        if let Err(e) = self.pinned_books.save() {
+            error!("Failed to save pinned books: {e}");
+        }
+        self.book_manager.apply_pinned(&self.pinned_books);
+
+        let index_of = |path: &str| self.book_manager.find_book_index_by_path(path);
+        let current = self
+            .current_book
+            .as_ref()
+            .and_then(|book| index_of(&book.file));
+        let selected = index_of(&path).unwrap_or(0);
+        self.navigation_panel
+            .reorder_book_list(&self.book_manager, selected, current);
+        self.show_info(i18n::tr_args(
+            if pinned {
+                "notify.pinned"
+            } else {
+                "notify.unpinned"
+            },
+            &[("name", &name)],
+        ));
+    }
+
+    /// Take the metadata of the book being opened, in case the scan hasn't
+    /// read it yet or the cache is out of date
+    fn refresh_book_metadata(&mut self, path: &str, doc: &EpubDoc<BufReader<std::fs::File>>) {
+        let metadata = book_metadata::metadata_of(doc);
+        if !self.book_manager.set_metadata(path, &metadata) {
+            return;
+        }
+        self.navigation_panel.update_book_names(&self.book_manager);
+        if let Some(mtime) = book_metadata::file_mtime(path) {
+            self.book_metadata.insert(path, mtime, metadata);
+            if let Err(e) = self.book_metadata.save() {
+                error!("Failed to save book metadata: {e}");
+            }
+        }
+    }
+
+    /// Follow the OS dark/light mode as `probe` reports it
+    pub fn set_appearance_probe(&mut self, probe: Box<dyn AppearanceProbe>) {
+        self.system_appearance = Some(SystemAppearance::new(probe));
+    }
+
+    /// Switch between the light and dark theme when the OS mode changed;
+    /// true when the theme did
+    pub fn poll_system_appearance(&mut self) -> bool {
+        let Some(appearance) = self.system_appearance.as_mut() else {
+            return false;
+        };
+        let Some(mode) = appearance.poll(Instant::now()) else {
+            return false;
+        };
+        let Some(name) = crate::theme::apply_auto_theme(mode) else {
+            return false;
+        };
+        self.text_reader.invalidate_render_cache();
+        let key = match mode {
+            TerminalBackground::Dark => "notify.theme_system.dark",
+            TerminalBackground::Light => "notify.theme_system.light",
+        };
+        self.show_info(i18n::tr_args(key, &[("name", &name)]));
+        true
+    }
+
+    fn open_downloaded_book(&mut self, path: &str, name: &str) {
+        self.book_manager.add_book(path, name);
+        self.navigation_panel.refresh_book_list(&self.book_manager);
+        self.start_metadata_scan();
+
+        let lock_warning_open = self.lock_warning.is_some();
+        match self.open_book_for_reading_by_path(path) {
+            Ok(()) => self.show_info(i18n::tr_args("notify.opened", &[("name", &name)])),
+            Err(e) => self.show_error(i18n::tr_args(
+                "notify.open_failed",
+                &[("name", &name), ("error", &e)],
+            )),
+        }
+        if lock_warning_open {
+            // The lock warning still needs an answer before reading
+            self.focus_before_lock_warning = self.focused_panel;
+            self.focused_panel = FocusedPanel::Popup(PopupWindow::InstanceLock);
+        }
+    }
+
     pub fn open_book_for_reading_by_path(&mut self, path: &str) -> Result<()> {
         let book_index = self
             .book_manager
@@ -408,6 +961,137 @@ impl App {
         self.open_book_for_reading(book_index)
     }
 
+    /// Resume the book read before the current one (Space+[)
+    pub fn resume_previous(&mut self) {
+        self.resume_book(true);
+    }
+
+    /// Step back towards the most recently read book (Space+])
+    pub fn resume_next(&mut self) {
+        self.resume_book(false);
+    }
+
+    fn resume_book(&mut self, older: bool) {
+        let current = self.current_book.as_ref().map(|book| book.file.clone());
+
+        // Keep cycling through the same snapshot while the last resumed book
+        // is still open; anything else starts a new cycle
+        let continuing = matches!(
+            (&self.resume_stack, &current),
+            (Some(stack), Some(path)) if stack.current() == Some(path.as_str())
+        );
+        if !continuing {
+            self.save_bookmark_with_throttle(true);
+            let books = self
+                .bookmarks
+                .recent_books(RESUME_STACK_SIZE)
+                .into_iter()
+                .filter(|path| self.book_manager.contains_book(path))
+                .collect();
+            self.resume_stack = ResumeStack::new(books, current.as_deref());
+        }
+
+        let Some(stack) = self.resume_stack.as_mut() else {
+            self.show_info(i18n::tr("notify.no_recent_books"));
+            return;
+        };
+        if stack.len() < 2 && current.is_some() && stack.current().is_some() {
+            self.show_info(i18n::tr("notify.no_other_recent_books"));
+            return;
+        }
+
+        let path = if older {
+            stack.older().to_string()
+        } else {
+            stack.newer().to_string()
+        };
+        let (position, total) = (stack.position().unwrap_or(0), stack.len());
+
+        match self.open_book_for_reading_by_path(&path) {
+            Ok(()) => {
+                let name = self
+                    .book_manager
+                    .books
+                    .iter()
+                    .find(|book| book.path == path)
+                    .map_or(path.as_str(), |book| book.display_name.as_str())
+                    .to_string();
+                self.show_info(i18n::tr_args(
+                    "notify.resumed",
+                    &[("name", &name), ("position", &position), ("total", &total)],
+                ));
+            }
+            Err(e) => {
+                error!("Failed to resume {path}: {e}");
+                self.show_error(i18n::tr_args("notify.resume_failed", &[("error", &e)]));
+                self.resume_stack = None;
+            }
+        }
+    }
+
+    /// Note where the open book is left, to come back to exactly there
+    /// this session
+    fn leave_session_book(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let position = SessionPosition {
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+            line_offset: self.text_reader.get_current_node_line_offset(),
+            thread: self.active_thread.clone(),
+        };
+        let path = book.file.clone();
+        self.session_books.left(&path, position);
+    }
+
+    /// Switch to the book open before this one (Ctrl+6)
+    pub fn switch_to_alternate_book(&mut self) {
+        let current = self.current_book.as_ref().map(|book| book.file.clone());
+        match self.session_books.alternate(current.as_deref()) {
+            Some(path) => {
+                let path = path.to_string();
+                self.switch_to_session_book(&path);
+            }
+            None => self.show_info(i18n::tr("notify.no_other_session_book")),
+        }
+    }
+
+    /// Open a book of this session where it was left, rather than at its
+    /// bookmark
+    fn switch_to_session_book(&mut self, path: &str) {
+        let position = self.session_books.position(path).cloned();
+        if let Err(e) = self.open_book_for_reading_by_path(path) {
+            error!("Failed to switch to {path}: {e}");
+            self.show_error(i18n::tr_args("notify.switch_failed", &[("error", &e)]));
+            self.session_books.remove(path);
+            return;
+        }
+
+        if let Some(position) = position {
+            // Opening the book went back to its primary bookmark
+            self.active_thread = position.thread;
+            if self
+                .current_book
+                .as_ref()
+                .is_some_and(|book| book.current_chapter() != position.chapter_index)
+                && let Err(e) = self.navigate_to_chapter(position.chapter_index)
+            {
+                warn!("Failed to restore chapter {}: {e}", position.chapter_index);
+            }
+            self.text_reader
+                .restore_to_node_line(position.node_index, position.line_offset);
+        }
+        let name = self
+            .book_manager
+            .books
+            .iter()
+            .find(|book| book.path == path)
+            .map_or(path, |book| book.display_name.as_str())
+            .to_string();
+        self.show_info(i18n::tr_args("notify.switched", &[("name", &name)]));
+    }
+
     /// Navigate to a specific chapter - ensures all state is properly updated
     pub fn navigate_to_chapter(&mut self, chapter_index: usize) -> Result<()> {
         if let Some(doc) = &mut self.current_book {
@@ -429,24 +1113,236 @@ impl App {
         }
     }
 
+    /// Go to the top of the first chapter (`g0`) or the bottom of the last
+    /// one (`g$`), leaving the jump list a way back
+    fn go_to_book_edge(&mut self, to_end: bool) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let target = if to_end {
+            book.total_chapters().saturating_sub(1)
+        } else {
+            0
+        };
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        });
+
+        let changes_chapter = book.current_chapter() != target;
+        if changes_chapter && let Err(e) = self.navigate_to_chapter(target) {
+            error!("Failed to navigate to chapter {target}: {e}");
+            return;
+        }
+        if !to_end {
+            self.text_reader.handle_gg();
+        } else if changes_chapter {
+            // The new chapter's length is only known once it is laid out
+            self.text_reader.scroll_to_end_after_render();
+        } else {
+            self.text_reader.handle_upper_g();
+        }
+        self.save_bookmark();
+    }
+
+    /// Go where the book's landmark of `kind` points: `gs` the start of the
+    /// text past the cover and title pages, `gc` the cover, `gt` the contents
+    /// page. Without a body matter landmark, the text starts where the book
+    /// opens the first time.
+    pub fn go_to_landmark(&mut self, kind: LandmarkKind) {
+        let Some(book) = &mut self.current_book else {
+            return;
+        };
+        let landmark = book
+            .landmarks
+            .iter()
+            .find(|landmark| landmark.kind == kind)
+            .cloned();
+        let start_chapter = if landmark.is_none() && kind == LandmarkKind::BodyMatter {
+            front_matter::find_start_chapter(&mut book.epub).map(|start| start.chapter)
+        } else {
+            None
+        };
+        if landmark.is_none() && start_chapter.is_none() {
+            self.show_info(i18n::tr(match kind {
+                LandmarkKind::Cover => "notify.no_landmark.cover",
+                LandmarkKind::Toc => "notify.no_landmark.contents",
+                LandmarkKind::BodyMatter => "notify.no_landmark.start",
+            }));
+            return;
+        }
+
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        });
+        if let Some(landmark) = landmark {
+            self.navigate_to_toc_entry(&landmark.href, landmark.anchor);
+        } else if let Some(chapter) = start_chapter
+            && let Err(e) = self.navigate_to_chapter(chapter)
+        {
+            error!("Failed to navigate to chapter {chapter}: {e}");
+        }
+    }
+
+    /// Go to the next (`]a`) or previous (`[a`) chapter with comments
+    fn jump_to_annotated_chapter(&mut self, forward: bool) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let counts = self
+            .text_reader
+            .get_comments()
+            .lock()
+            .map(|comments| comments.chapter_comment_counts())
+            .unwrap_or_default();
+        let current = book.current_chapter();
+        let annotated = |index: &usize| {
+            Self::get_chapter_href(&book.epub, *index)
+                .and_then(|href| counts.get(&href).copied())
+                .map(|count| (*index, count))
+        };
+        let target = if forward {
+            (current + 1..book.total_chapters()).find_map(|index| annotated(&index))
+        } else {
+            (0..current).rev().find_map(|index| annotated(&index))
+        };
+        let Some((target, count)) = target else {
+            self.show_info(i18n::tr(if forward {
+                "notify.no_annotated_after"
+            } else {
+                "notify.no_annotated_before"
+            }));
+            return;
+        };
+
+        self.jump_list.push(JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: current,
+            node_index: self.text_reader.get_current_node_index(),
+        });
+        if let Err(e) = self.navigate_to_chapter(target) {
+            error!("Failed to navigate to chapter {target}: {e}");
+            return;
+        }
+        let key = if count == 1 {
+            "notify.chapter_comments.one"
+        } else {
+            "notify.chapter_comments.other"
+        };
+        self.show_info(i18n::tr_args(key, &[("count", &count)]));
+    }
+
     /// Navigate to next or previous chapter - maintains all state consistency
     pub fn navigate_chapter_relative(&mut self, direction: ChapterDirection) -> Result<()> {
-        if let Some(book) = &mut self.current_book {
-            if (direction == ChapterDirection::Next && book.epub.go_next())
-                || (direction == ChapterDirection::Previous && book.epub.go_prev())
-            {
-                self.update_content();
-                self.update_toc_state();
-                self.save_bookmark_with_throttle(true);
-                Ok(())
-            } else {
-                anyhow::bail!("Already at the end/beginning of the book")
+        let Some((target, skipped)) = self.relative_chapter_target(direction) else {
+            if self.current_book.is_none() {
+                anyhow::bail!("No document loaded")
             }
-        } else {
+            anyhow::bail!("Already at the end/beginning of the book")
+        };
+        let Some(book) = &mut self.current_book else {
             anyhow::bail!("No document loaded")
+        };
+        if !book.epub.set_current_chapter(target) {
+            anyhow::bail!("Failed to move to chapter {target}")
+        }
+
+        self.update_content();
+        self.update_toc_state();
+        self.save_bookmark_with_throttle(true);
+        match skipped {
+            0 => {}
+            1 => self.show_info(i18n::tr("notify.skipped_empty.one")),
+            n => self.show_info(i18n::tr_args(
+                "notify.skipped_empty.other",
+                &[("count", &n)],
+            )),
+        }
+        Ok(())
+    }
+
+    /// Chapter that `h`/`l` move to, with the number of empty chapters
+    /// passed over when `skip_empty_chapters` is on; None at the book boundary
+    fn relative_chapter_target(&self, direction: ChapterDirection) -> Option<(usize, usize)> {
+        let book = self.current_book.as_ref()?;
+        let current = book.current_chapter();
+        let candidates: Vec<usize> = match direction {
+            ChapterDirection::Next => (current + 1..book.total_chapters()).collect(),
+            ChapterDirection::Previous => (0..current).rev().collect(),
+        };
+
+        if !settings::get_skip_empty_chapters() {
+            return candidates.first().map(|&target| (target, 0));
+        }
+        candidates
+            .into_iter()
+            .enumerate()
+            .find(|&(_, target)| !self.empty_chapters.get(target).copied().unwrap_or(false))
+            .map(|(skipped, target)| (target, skipped))
+    }
+
+    /// Chapter step from `h`/`l`: at the book boundaries applies the
+    /// `book_boundary` setting, and reports any other failure
+    fn step_chapter(&mut self, direction: ChapterDirection) {
+        if self.current_book.is_none() {
+            return;
+        }
+        // Only empty chapters left counts as the boundary too
+        if self.relative_chapter_target(direction).is_none() {
+            self.handle_book_boundary(direction, settings::get_book_boundary());
+        } else if let Err(e) = self.navigate_chapter_relative(direction) {
+            error!("Failed to change chapter: {e}");
+            self.show_error(i18n::tr_args(
+                "notify.change_chapter_failed",
+                &[("error", &e)],
+            ));
+        }
+    }
+
+    fn handle_book_boundary(&mut self, direction: ChapterDirection, boundary: BookBoundary) {
+        let boundary_message = i18n::tr(match direction {
+            ChapterDirection::Next => "notify.end_of_book",
+            ChapterDirection::Previous => "notify.start_of_book",
+        });
+
+        match boundary {
+            BookBoundary::Nothing => {}
+            BookBoundary::NextBook if direction == ChapterDirection::Next => {
+                match self.next_library_book() {
+                    Some(book_index) => {
+                        if let Err(e) = self.open_book_for_reading(book_index) {
+                            error!("Failed to open next book: {e}");
+                            self.show_error(i18n::tr_args(
+                                "notify.next_book_failed",
+                                &[("error", &e)],
+                            ));
+                        } else if let Err(e) = self.navigate_to_chapter(0) {
+                            error!("Failed to go to the first chapter: {e}");
+                        } else if let Some(book) = self.book_manager.get_book_info(book_index) {
+                            let name = book.display_name.clone();
+                            self.show_info(i18n::tr_args(
+                                "notify.end_of_book_opened",
+                                &[("name", &name)],
+                            ));
+                        }
+                    }
+                    None => self.show_info(i18n::tr("notify.end_of_library")),
+                }
+            }
+            BookBoundary::Notify | BookBoundary::NextBook => self.show_info(boundary_message),
         }
     }
 
+    /// Library index of the book after the one being read
+    fn next_library_book(&self) -> Option<usize> {
+        let path = &self.current_book.as_ref()?.file;
+        let index = self.book_manager.find_book_index_by_path(path)?;
+        (index + 1 < self.book_manager.books.len()).then_some(index + 1)
+    }
+
     pub fn navigate_to_chapter_by_href(&mut self, href: &str) -> Result<()> {
         if let Some(ref mut book) = self.current_book {
             let chapter_path = std::path::PathBuf::from(href);
@@ -471,9 +1367,10 @@ impl App {
     // These methods should only be called by high-level actions above
 
     pub fn load_epub(&mut self, path: &str, ignore_bookmarks: bool) -> Result<()> {
+        self.leave_session_book();
         let mut doc = self.book_manager.load_epub(path).map_err(|e| {
             error!("Failed to load EPUB document: {e}");
-            self.show_error(format!("Failed to load EPUB: {e}"));
+            self.show_error(i18n::tr_args("notify.load_epub_failed", &[("error", &e)]));
             anyhow::anyhow!("Failed to load EPUB: {}", e)
         })?;
 
@@ -488,18 +1385,30 @@ impl App {
         if let Err(e) = self.book_images.load_book(&path_buf) {
             error!("Failed to load book in BookImages: {e}");
         }
+        self.book_watch = Some(BookWatch::new(path, Instant::now()));
+        self.book_reload_pending = false;
+        if !self.book_manager.is_html_file(path) {
+            self.refresh_book_metadata(path, &doc);
+        }
 
-        self.initialize_search_engine(&mut doc);
+        self.text_reader
+            .set_dehyphenate(self.bookmarks.is_dehyphenated(path));
+        self.text_reader.set_typography(
+            self.bookmarks
+                .typography(path)
+                .unwrap_or_else(settings::get_typography),
+        );
+        self.text_reader.set_margin(
+            self.bookmarks
+                .margin(path)
+                .unwrap_or_else(settings::get_margin),
+        );
+        // Every book opens on its primary bookmark
+        self.active_thread = None;
+        self.glyph_notices.clear();
+        self.text_reader.clear_chapter_cache();
 
-        match BookComments::new(&path_buf) {
-            Ok(comments) => {
-                let comments_arc = Arc::new(Mutex::new(comments));
-                self.text_reader.set_book_comments(comments_arc);
-            }
-            Err(e) => {
-                warn!("Failed to initialize book comments: {e}");
-            }
-        }
+        self.load_book_comments(&path_buf);
 
         // Variables to store position to restore after content is loaded
         let mut node_to_restore = None;
@@ -522,38 +1431,41 @@ impl App {
             } else {
                 warn!("Could not find chapter for href: {}", bookmark.chapter_href);
             }
-        } else if doc.get_num_chapters() > 1 {
-            if doc.go_next() {
-                if doc.get_current_str().is_none() {
-                    error!(
-                        "WARNING: No content at new position {} after go_next()",
-                        doc.get_current_chapter()
-                    );
-                }
-            } else {
-                error!("Failed to move to next chapter with go_next()");
-                error!(
-                    "Current position: {}, Total chapters: {}",
-                    doc.get_current_chapter(),
-                    doc.get_num_chapters()
-                );
-
-                // Try alternative: set_current_chapter
-                info!("Attempting fallback: set_current_chapter(1)");
-                if doc.set_current_chapter(1) {
-                    info!("Fallback successful: moved to chapter 1 using set_current_chapter");
-                } else {
-                    error!("Fallback also failed - unable to navigate in this EPUB");
-                    // Don't fail completely - stay at chapter 0
-                    info!("Staying at chapter 0 as fallback");
+        } else if self.bookmarks.get_bookmark(path).is_none() {
+            // First open: skip the cover, title page and other front matter
+            match front_matter::find_start_chapter(&mut doc) {
+                Some(start) if start.chapter > 0 => {
+                    if doc.set_current_chapter(start.chapter) {
+                        info!(
+                            "Opening {} at chapter {} ({:?})",
+                            path, start.chapter, start.reason
+                        );
+                    } else {
+                        warn!("Failed to move to detected start chapter {}", start.chapter);
+                    }
                 }
+                Some(start) => info!("Opening {path} at the beginning ({:?})", start.reason),
+                None => info!("No body matter detected in {path}, opening at the beginning"),
             }
         }
 
+        // Indexed around the chapter the book opens on
+        self.initialize_search_engine(&mut doc);
+
         let current_book = EpubBook::new(path.to_string(), doc);
         self.switch_to_toc_mode(&current_book);
 
+        // Read state of chapters dropped from the spine, e.g. by a new edition
+        let spine_hrefs = (0..current_book.total_chapters())
+            .filter_map(|index| Self::get_chapter_href(&current_book.epub, index))
+            .collect();
+        self.bookmarks.retain_chapters(path, &spine_hrefs);
+
         self.current_book = Some(current_book);
+        self.session_books.opened(path);
+        self.refresh_toc_read_marks();
+        self.refresh_toc_annotation_marks();
+        self.refresh_toc_landmark_marks();
         self.update_content();
 
         if let Some(node_idx) = node_to_restore {
@@ -562,6 +1474,77 @@ impl App {
         Ok(())
     }
 
+    /// Reload the open book once its file has been rewritten, waiting while
+    /// a comment is typed or text is selected. Called on every tick; returns
+    /// true when the book was reloaded.
+    pub fn poll_book_file(&mut self) -> bool {
+        if self.current_book.is_none() {
+            return false;
+        }
+        if let Some(watch) = self.book_watch.as_mut()
+            && watch.poll(Instant::now())
+        {
+            self.book_reload_pending = true;
+        }
+        if !self.book_reload_pending
+            || self.text_reader.is_comment_input_active()
+            || self.text_reader.has_text_selection()
+        {
+            return false;
+        }
+        self.book_reload_pending = false;
+        if let Err(e) = self.reload_book() {
+            self.show_error(i18n::tr_args("notify.reload_failed", &[("error", &e)]));
+        }
+        true
+    }
+
+    /// Open the book again from its file, staying in the chapter and at the
+    /// node being read. A chapter the new file no longer has falls back to
+    /// the spine position it had.
+    fn reload_book(&mut self) -> Result<()> {
+        let Some(book) = &self.current_book else {
+            return Ok(());
+        };
+        let path = book.file.clone();
+        let chapter = book.current_chapter();
+        let chapter_href = Self::get_chapter_href(&book.epub, chapter);
+        let node_index = self.text_reader.get_current_node_index();
+
+        let mut doc = self
+            .book_manager
+            .load_epub(&path)
+            .map_err(|e| anyhow::anyhow!(e))?;
+        if let Err(e) = self.book_images.load_book(std::path::Path::new(&path)) {
+            error!("Failed to load book in BookImages: {e}");
+        }
+        self.text_reader.clear_chapter_cache();
+
+        let found = chapter_href
+            .as_deref()
+            .and_then(|href| Self::find_chapter_index_by_href(&doc, href));
+        let target = found.unwrap_or(chapter.min(doc.get_num_chapters().saturating_sub(1)));
+        if !doc.set_current_chapter(target) {
+            warn!("Failed to restore chapter {target} after reloading {path}");
+        }
+        self.initialize_search_engine(&mut doc);
+
+        let book = EpubBook::new(path.clone(), doc);
+        self.switch_to_toc_mode(&book);
+        self.current_book = Some(book);
+        self.refresh_toc_read_marks();
+        self.refresh_toc_annotation_marks();
+        self.refresh_toc_landmark_marks();
+        self.update_content();
+        if found.is_some() {
+            self.text_reader.restore_to_node_index(node_index);
+        }
+        self.save_bookmark();
+        info!("Reloaded {path} after it changed on disk");
+        self.show_info(i18n::tr("notify.book_reloaded"));
+        Ok(())
+    }
+
     /// Get the href/path for a chapter at a specific index using the EPUB spine
     fn get_chapter_href(
         doc: &EpubDoc<BufReader<std::fs::File>>,
@@ -577,7 +1560,7 @@ impl App {
     }
 
     /// Find chapter index by href/path
-    fn find_chapter_index_by_href(
+    pub(crate) fn find_chapter_index_by_href(
         doc: &EpubDoc<BufReader<std::fs::File>>,
         target_href: &str,
     ) -> Option<usize> {
@@ -595,9 +1578,21 @@ impl App {
         None
     }
 
-    fn switch_to_toc_mode(&mut self, book: &EpubBook) {
-        let toc_items = TocParser::parse_toc_structure(&book.epub);
-        let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
+    /// Bring the navigation panel up on the open book's contents (`:toc`)
+    fn show_toc(&mut self) {
+        if let Some(book) = self.current_book.take() {
+            self.switch_to_toc_mode(&book);
+            self.current_book = Some(book);
+        }
+        if self.zen_mode {
+            self.execute_command(commands::Command::ToggleZen, None);
+        }
+        self.set_main_panel_focus(MainPanel::NavigationList);
+    }
+
+    fn switch_to_toc_mode(&mut self, book: &EpubBook) {
+        let toc_items = TocParser::parse_toc_structure(&book.epub);
+        let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
         let available_anchors =
             TableOfContents::anchors_for_items(&toc_items, current_chapter_href.as_deref());
         let active_section = self.text_reader.get_active_section(
@@ -652,18 +1647,174 @@ impl App {
         self.save_bookmark_with_throttle(false);
     }
 
+    /// Mark the current chapter in progress once opened, and read once
+    /// scrolled past `chapter_read_percent`. Read chapters stay read until
+    /// toggled back.
+    fn track_chapter_read_state(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let Some(href) = Self::get_chapter_href(&book.epub, book.current_chapter()) else {
+            return;
+        };
+        let read_percent = settings::get_chapter_read_percent() as f32;
+        let finished = self
+            .text_reader
+            .rendered_progress_fraction()
+            .is_some_and(|fraction| fraction * 100.0 >= read_percent);
+        let state = if finished
+            || self.bookmarks.chapter_state(&book.file, &href) == Some(ChapterReadState::Read)
+        {
+            ChapterReadState::Read
+        } else {
+            ChapterReadState::InProgress
+        };
+        let file = book.file.clone();
+        if self.bookmarks.set_chapter_state(&file, &href, Some(state))
+            && state == ChapterReadState::Read
+        {
+            self.refresh_toc_read_marks();
+        }
+    }
+
+    /// Toggle a chapter between read and unread, by spine index
+    fn toggle_chapter_read(&mut self, chapter_index: usize) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let Some(href) = Self::get_chapter_href(&book.epub, chapter_index) else {
+            return;
+        };
+        let file = book.file.clone();
+        let read = self.bookmarks.chapter_state(&file, &href) == Some(ChapterReadState::Read);
+        let state = (!read).then_some(ChapterReadState::Read);
+        self.bookmarks.set_chapter_state(&file, &href, state);
+        if let Err(e) = self.bookmarks.save() {
+            error!("Failed to save bookmarks: {e}");
+        }
+        self.refresh_toc_read_marks();
+        self.show_info(i18n::tr(if read {
+            "notify.marked_unread"
+        } else {
+            "notify.marked_read"
+        }));
+    }
+
+    /// Chapter hrefs of the TOC entries paired with the spine hrefs that
+    /// bookmarks and comments use
+    fn toc_spine_hrefs(&self) -> Vec<(String, String)> {
+        let Some(book) = &self.current_book else {
+            return Vec::new();
+        };
+        let mut hrefs = Vec::new();
+        let mut pending = self.navigation_panel.get_toc_items();
+        while let Some(item) = pending.pop() {
+            if let TocItem::Section { children, .. } = &item {
+                pending.extend(children.iter().cloned());
+            }
+            let Some(href) = item.href() else {
+                continue;
+            };
+            let base_href = ActiveSection::base_href(href);
+            if let Some(spine_href) = self
+                .find_spine_index_by_href(&base_href)
+                .and_then(|index| Self::get_chapter_href(&book.epub, index))
+            {
+                hrefs.push((base_href, spine_href));
+            }
+        }
+        hrefs
+    }
+
+    /// Check off the TOC entries of the chapters marked read
+    fn refresh_toc_read_marks(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let read = self.bookmarks.read_chapters(&book.file);
+        let marked = self
+            .toc_spine_hrefs()
+            .into_iter()
+            .filter(|(_, spine_href)| read.contains(spine_href))
+            .map(|(base_href, _)| base_href)
+            .collect();
+        self.navigation_panel
+            .table_of_contents
+            .set_read_chapters(marked);
+    }
+
+    /// Mark the TOC entries of chapters with comments with their count
+    fn refresh_toc_annotation_marks(&mut self) {
+        if self.current_book.is_none() {
+            return;
+        }
+        let counts = self
+            .text_reader
+            .get_comments()
+            .lock()
+            .map(|comments| comments.chapter_comment_counts())
+            .unwrap_or_default();
+        let marked = self
+            .toc_spine_hrefs()
+            .into_iter()
+            .filter_map(|(base_href, spine_href)| Some((base_href, *counts.get(&spine_href)?)))
+            .collect();
+        self.navigation_panel
+            .table_of_contents
+            .set_annotation_counts(marked);
+    }
+
+    /// Tag the TOC entries of the chapters the book's landmarks point at
+    fn refresh_toc_landmark_marks(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let landmarks: Vec<(String, LandmarkKind)> = book
+            .landmarks
+            .iter()
+            .filter_map(|landmark| {
+                let index = self.find_spine_index_by_href(&landmark.href)?;
+                Some((Self::get_chapter_href(&book.epub, index)?, landmark.kind))
+            })
+            .collect();
+        let marked = self
+            .toc_spine_hrefs()
+            .into_iter()
+            .filter_map(|(base_href, spine_href)| {
+                let (_, kind) = landmarks.iter().find(|(href, _)| *href == spine_href)?;
+                Some((base_href, *kind))
+            })
+            .collect();
+        self.navigation_panel
+            .table_of_contents
+            .set_landmarks(marked);
+    }
+
     pub fn save_bookmark_with_throttle(&mut self, force: bool) {
+        self.track_chapter_read_state();
         if let Some(book) = &self.current_book {
             let chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter())
                 .unwrap_or_else(|| format!("chapter_{}", book.current_chapter()));
 
-            self.bookmarks.update_bookmark(
-                &book.file,
-                chapter_href,
-                Some(self.text_reader.get_current_node_index()),
-                Some(book.current_chapter()),
-                Some(book.total_chapters()),
-            );
+            match &self.active_thread {
+                Some(name) => {
+                    self.bookmarks.update_thread(
+                        &book.file,
+                        name,
+                        chapter_href,
+                        Some(self.text_reader.get_current_node_index()),
+                        Some(book.current_chapter()),
+                    );
+                    self.bookmarks.touch_bookmark(&book.file);
+                }
+                None => self.bookmarks.update_bookmark(
+                    &book.file,
+                    chapter_href,
+                    Some(self.text_reader.get_current_node_index()),
+                    Some(book.current_chapter()),
+                    Some(book.total_chapters()),
+                ),
+            }
 
             // Only save to disk if enough time has passed or if forced
             let now = std::time::Instant::now();
@@ -679,7 +1830,196 @@ impl App {
         }
     }
 
+    /// Save the reading position once no input came for `idle_timeout`,
+    /// dimming the screen if `idle_dim` is set. Called on every tick;
+    /// returns true when the screen needs a redraw.
+    pub fn poll_idle(&mut self) -> bool {
+        let Some(timeout) = self.idle_timeout else {
+            return false;
+        };
+        if self.idle || self.last_user_input.elapsed() < timeout {
+            return false;
+        }
+        self.idle = true;
+        if self.current_book.is_some() {
+            self.save_bookmark_with_throttle(true);
+            info!("No input for {timeout:?}, reading position saved");
+        }
+        self.idle_dim
+    }
+
+    /// A key or mouse event: restarts the idle timer and wakes the screen
+    fn note_user_input(&mut self) {
+        self.last_user_input = Instant::now();
+        self.idle = false;
+    }
+
+    /// Account reading time, scrolled lines and the words on the lines
+    /// scrolled past towards the daily goal.
+    /// Called on every tick; returns true when the help bar needs a redraw.
+    pub fn update_reading_goal(&mut self) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_reading_tick);
+        self.last_reading_tick = now;
+
+        let Some(book) = &self.current_book else {
+            self.last_scroll_position = None;
+            return false;
+        };
+        let book_file = book.file.clone();
+        let position = (book.current_chapter(), self.text_reader.get_scroll_offset());
+        let indicator_before = self.reading_goal_indicator();
+        let local_now = chrono::Local::now();
+
+        if elapsed <= READING_MAX_TICK_GAP
+            && now.duration_since(self.last_user_input) < READING_IDLE_TIMEOUT
+        {
+            self.reading_goals
+                .record_time(&book_file, local_now, elapsed);
+        }
+
+        if let Some((chapter, offset)) = self.last_scroll_position {
+            if chapter == position.0 && offset != position.1 {
+                self.reading_goals.record_lines(
+                    &book_file,
+                    local_now,
+                    offset.abs_diff(position.1) as u64,
+                );
+                // Scrolling back to re-read doesn't count the words again
+                if position.1 > offset {
+                    let words = self.text_reader.count_words_in_lines(offset..position.1);
+                    self.reading_goals
+                        .record_words(&book_file, local_now, words as u64);
+                }
+            }
+        }
+        self.last_scroll_position = Some(position);
+
+        let mut needs_redraw = indicator_before != self.reading_goal_indicator();
+
+        if let Some(goal) = settings::get_daily_goal() {
+            if self
+                .reading_goals
+                .take_goal_celebration(&goal, local_now.date_naive())
+            {
+                self.show_info(i18n::tr_args(
+                    "notify.goal_reached",
+                    &[("target", &goal.target), ("unit", &goal.unit.label())],
+                ));
+                self.save_reading_goals();
+                needs_redraw = true;
+            }
+        }
+
+        if now.duration_since(self.last_reading_goals_save) > READING_GOALS_SAVE_INTERVAL {
+            self.save_reading_goals();
+        }
+
+        needs_redraw
+    }
+
+    /// Quit on `q`, asking for confirmation first when enabled in settings,
+    /// unless `q` comes twice within a second
+    fn request_quit(&mut self) -> Option<AppAction> {
+        if self
+            .quit_confirm
+            .request(Instant::now(), settings::get_confirm_quit())
+        {
+            return self.quit();
+        }
+        None
+    }
+
+    fn quit(&mut self) -> Option<AppAction> {
+        self.quit_confirm.cancel();
+        self.save_session();
+        Some(AppAction::Quit)
+    }
+
+    /// Persist everything that must survive the app exiting
+    pub fn save_session(&mut self) {
+        self.save_bookmark_with_throttle(true);
+        self.save_reading_goals();
+        self.save_library_scan();
+    }
+
+    /// Lock the bookmarks file against other instances. When another live
+    /// instance holds it, go read-only until the lock warning is answered.
+    fn acquire_instance_lock(&mut self) {
+        let Some(bookmark_file) = self.bookmarks.file_path() else {
+            return;
+        };
+        let lock_path = InstanceLock::lock_path_for(std::path::Path::new(bookmark_file));
+
+        match InstanceLock::acquire(&lock_path) {
+            Ok(LockStatus::Acquired(lock)) => self.instance_lock = Some(lock),
+            Ok(LockStatus::HeldBy { pid }) => {
+                warn!("Bookmarks are locked by running instance {pid}");
+                instance_lock::set_read_only(true);
+                self.lock_warning = Some(LockWarningPopup::new(pid));
+            }
+            Err(e) => error!("Failed to create lock file {lock_path:?}: {e}"),
+        }
+    }
+
+    fn close_lock_warning(&mut self) {
+        self.lock_warning = None;
+        self.focused_panel = self.focus_before_lock_warning;
+    }
+
+    fn take_over_instance_lock(&mut self) {
+        let Some(bookmark_file) = self.bookmarks.file_path() else {
+            return;
+        };
+        let lock_path = InstanceLock::lock_path_for(std::path::Path::new(bookmark_file));
+
+        match InstanceLock::take_over(&lock_path) {
+            Ok(lock) => {
+                self.instance_lock = Some(lock);
+                instance_lock::set_read_only(false);
+                self.show_info(i18n::tr("notify.took_over"));
+            }
+            Err(e) => self.show_error(i18n::tr_args("notify.take_over_failed", &[("error", &e)])),
+        }
+    }
+
+    pub fn save_reading_goals(&mut self) {
+        if let Err(e) = self.reading_goals.save() {
+            error!("Failed to save reading goals: {e}");
+        }
+        self.last_reading_goals_save = Instant::now();
+    }
+
+    fn reading_goal_indicator(&self) -> Option<String> {
+        let goal = settings::get_daily_goal()?;
+        let progress = self
+            .reading_goals
+            .progress(&goal, chrono::Local::now().date_naive());
+        Some(i18n::tr_args(
+            "goal.indicator",
+            &[
+                ("progress", &progress),
+                ("target", &goal.target),
+                ("unit", &goal.unit.label()),
+            ],
+        ))
+    }
+
+    fn reading_streak(&self) -> Option<u32> {
+        let goal = settings::get_daily_goal()?;
+        Some(
+            self.reading_goals
+                .streak(&goal, chrono::Local::now().date_naive()),
+        )
+    }
+
     fn update_content(&mut self) {
+        let next_chapter_title = if settings::get_auto_advance_chapters() {
+            self.relative_chapter_target(ChapterDirection::Next)
+                .map(|(target, _)| self.toc_chapter_title(Some(target)))
+        } else {
+            None
+        };
         if let Some(book) = &mut self.current_book {
             let (content, title) = match book.epub.get_current_str() {
                 Some((raw_html, _mime)) => {
@@ -692,6 +2032,11 @@ impl App {
                 }
             };
 
+            // Content goes first so the chapter's comments are checked against it
+            self.text_reader
+                .set_chapter_content(book.current_chapter(), &content, title);
+            self.text_reader.set_next_chapter_title(next_chapter_title);
+
             if let Some(chapter_file) = Self::get_chapter_href(&book.epub, book.current_chapter()) {
                 self.text_reader
                     .set_current_chapter_file(Some(chapter_file));
@@ -699,38 +2044,209 @@ impl App {
                 self.text_reader.set_current_chapter_file(None);
             }
 
-            self.text_reader.set_content_from_string(&content, title);
-            self.text_reader.preload_image_dimensions(&self.book_images);
+            if self.text_reader.is_converting() {
+                self.notifications
+                    .show_info(i18n::tr("notify.large_chapter"));
+            } else {
+                self.prepare_converted_chapter();
+            }
         } else {
             error!("No EPUB document loaded");
             self.text_reader.clear_content();
         }
     }
 
+    /// Glyph notice, rare word scan and image preloading, which need the
+    /// chapter's document, and prefetching the chapters around it
+    fn prepare_converted_chapter(&mut self) {
+        self.index_around_current_chapter();
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let unknown_glyphs = self.text_reader.unknown_glyphs();
+        if unknown_glyphs > 0 && self.glyph_notices.insert(book.current_chapter()) {
+            let key = if unknown_glyphs == 1 {
+                "notify.unknown_glyphs.one"
+            } else {
+                "notify.unknown_glyphs.other"
+            };
+            self.notifications
+                .show_info(i18n::tr_args(key, &[("count", &unknown_glyphs)]));
+        }
+        if self.text_reader.is_plain_text_fallback() {
+            self.notifications
+                .show_warning(i18n::tr("notify.plain_text_fallback"));
+        }
+        if let Some(glossary) = &self.glossary {
+            self.text_reader.start_gloss_analysis(glossary);
+        }
+        self.text_reader.preload_image_dimensions(&self.book_images);
+    }
+
+    /// Finish a large chapter once its background conversion is done.
+    /// Returns true while one is pending, so its spinner keeps turning.
+    pub fn poll_chapter_conversion(&mut self) -> bool {
+        if !self.text_reader.is_converting() {
+            return false;
+        }
+        if self.text_reader.check_for_converted_chapter() {
+            self.prepare_converted_chapter();
+        }
+        true
+    }
+
+    /// Turn OCR hyphenation cleanup on or off for the open book, then
+    /// reconvert the chapter and rebuild the search index with it
+    fn toggle_dehyphenation(&mut self) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            return;
+        };
+        let dehyphenate = !self.text_reader.is_dehyphenating();
+        self.bookmarks.set_dehyphenated(&path, dehyphenate);
+        self.text_reader.set_dehyphenate(dehyphenate);
+
+        let current_node = self.text_reader.get_current_node_index();
+        self.update_content();
+        self.text_reader.restore_to_node_index(current_node);
+
+        if let Some(mut book) = self.current_book.take() {
+            let chapter = book.current_chapter();
+            self.initialize_search_engine(&mut book.epub);
+            book.epub.set_current_chapter(chapter);
+            self.current_book = Some(book);
+        }
+
+        if dehyphenate {
+            self.show_info(i18n::tr("notify.dehyphenate_on"));
+        } else {
+            self.show_info(i18n::tr("notify.dehyphenate_off"));
+        }
+    }
+
+    /// Turn typographic punctuation on or off for the open book; the book
+    /// follows the `typography` setting again when toggled back to it
+    fn toggle_typography(&mut self) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            return;
+        };
+        let typography = !self.text_reader.is_typesetting();
+        let book_override = (typography != settings::get_typography()).then_some(typography);
+        self.bookmarks.set_typography(&path, book_override);
+
+        let current_node = self.text_reader.get_current_node_index();
+        self.text_reader.set_typography(typography);
+        self.text_reader.restore_to_node_index(current_node);
+
+        if typography {
+            self.show_info(i18n::tr("notify.typography_on"));
+        } else {
+            self.show_info(i18n::tr("notify.typography_off"));
+        }
+    }
+
+    /// Widen or narrow the margin of the open book; the book follows the
+    /// `margin` setting again when brought back to it
+    fn adjust_margin(&mut self, wider: bool) {
+        let margin = self.text_reader.get_margin();
+        self.set_book_margin(if wider {
+            margin.saturating_add(1)
+        } else {
+            margin.saturating_sub(1)
+        });
+    }
+
+    /// Give the open book its own margin (`:set margin 4`), kept unless
+    /// it is the `margin` setting's
+    fn set_book_margin(&mut self, margin: u16) {
+        let current_node = self.text_reader.get_current_node_index();
+        self.text_reader.set_margin(margin);
+        self.text_reader.restore_to_node_index(current_node);
+
+        if let Some(book) = &self.current_book {
+            let margin = self.text_reader.get_margin();
+            let book_override = (margin != settings::get_margin()).then_some(margin);
+            self.bookmarks.set_margin(&book.file, book_override);
+        }
+    }
+
     pub fn scroll_down(&mut self) {
+        if self.auto_advance_chapter(ChapterDirection::Next) {
+            return;
+        }
         self.text_reader.scroll_down();
         self.save_bookmark();
         self.update_toc_state(); // This will update active section
     }
 
     pub fn scroll_up(&mut self) {
+        if self.auto_advance_chapter(ChapterDirection::Previous) {
+            return;
+        }
         self.text_reader.scroll_up();
         self.save_bookmark();
         self.update_toc_state(); // This will update active section
     }
 
     pub fn scroll_half_screen_down(&mut self, screen_height: usize) {
+        if self.auto_advance_chapter(ChapterDirection::Next) {
+            return;
+        }
         self.text_reader.scroll_half_screen_down(screen_height);
         self.save_bookmark();
         self.update_toc_state(); // This will update active section
     }
 
     fn scroll_half_screen_up(&mut self, screen_height: usize) {
+        if self.auto_advance_chapter(ChapterDirection::Previous) {
+            return;
+        }
         self.text_reader.scroll_half_screen_up(screen_height);
         self.save_bookmark();
         self.update_toc_state(); // This will update active section
     }
 
+    fn scroll_full_screen_down(&mut self, screen_height: usize) {
+        if self.auto_advance_chapter(ChapterDirection::Next) {
+            return;
+        }
+        self.text_reader.scroll_full_screen_down(screen_height);
+        self.save_bookmark();
+        self.update_toc_state(); // This will update active section
+    }
+
+    fn scroll_full_screen_up(&mut self, screen_height: usize) {
+        if self.auto_advance_chapter(ChapterDirection::Previous) {
+            return;
+        }
+        self.text_reader.scroll_full_screen_up(screen_height);
+        self.save_bookmark();
+        self.update_toc_state(); // This will update active section
+    }
+
+    /// With `auto_advance_chapters` on, scrolling on from the end of a
+    /// chapter opens the next one at its top, and from the top the previous
+    /// one at its end. Only one chapter is loaded at a time, so this is a
+    /// jump rather than a view scrolling across the boundary. Returns whether
+    /// it changed chapter.
+    fn auto_advance_chapter(&mut self, direction: ChapterDirection) -> bool {
+        if !settings::get_auto_advance_chapters()
+            || !self
+                .text_reader
+                .is_at_scroll_edge(direction == ChapterDirection::Next)
+            || self.relative_chapter_target(direction).is_none()
+        {
+            return false;
+        }
+        if let Err(e) = self.navigate_chapter_relative(direction) {
+            error!("Failed to advance to the adjacent chapter: {e}");
+            return false;
+        }
+        if direction == ChapterDirection::Previous {
+            self.text_reader.scroll_to_end_after_render();
+        }
+        true
+    }
+
     /// Handle a mouse event with optional batching for scroll events
     /// When event_source is provided, scroll events will be batched for smoother scrolling
     ///
@@ -742,6 +2258,8 @@ impl App {
     ) {
         use std::time::Duration;
 
+        self.note_user_input();
+
         let is_scroll_event = matches!(
             initial_mouse_event.kind,
             MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
@@ -857,6 +2375,16 @@ impl App {
                     return; // Block all other interactions
                 }
 
+                if self.focused_panel == FocusedPanel::Popup(PopupWindow::BookSearch) {
+                    if let Some(ref mut search) = self.book_search {
+                        if search.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            search.close();
+                            self.close_popup_to_previous();
+                        }
+                    }
+                    return; // Block all other interactions
+                }
+
                 // Handle help popup mouse clicks
                 if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Help)) {
                     let click_x = mouse_event.column;
@@ -965,7 +2493,7 @@ impl App {
                     if let Some(ref mut viewer) = self.comments_viewer {
                         // Check if click is outside popup area - close it
                         if viewer.is_outside_popup_area(click_x, click_y) {
-                            viewer.save_position();
+                            self.remember_comments_viewer_position();
                             self.comments_viewer = None;
                             self.close_popup_to_previous();
                             return;
@@ -984,7 +2512,7 @@ impl App {
                                     if let Some(entry) = viewer.selected_comment() {
                                         let chapter_href = entry.chapter_href.clone();
                                         let node_index = entry.primary_comment().node_index();
-                                        viewer.save_position();
+                                        self.remember_comments_viewer_position();
                                         self.comments_viewer = None;
                                         self.close_popup_to_previous();
                                         self.set_main_panel_focus(MainPanel::Content);
@@ -998,8 +2526,9 @@ impl App {
                                             error!(
                                                 "Failed to navigate to chapter {chapter_href}: {e}"
                                             );
-                                            self.show_error(format!(
-                                                "Failed to navigate to comment: {e}"
+                                            self.show_error(i18n::tr_args(
+                                                "notify.navigate_comment_failed",
+                                                &[("error", &e)],
                                             ));
                                         }
                                     }
@@ -1037,19 +2566,15 @@ impl App {
                                 if selector.handle_mouse_click(mouse_event.column, mouse_event.row)
                                 {
                                     // Apply theme on double-click
-                                    if let Some(action) = selector.handle_key(
-                                        crossterm::event::KeyEvent::new(
-                                            crossterm::event::KeyCode::Enter,
-                                            crossterm::event::KeyModifiers::NONE,
-                                        ),
-                                        &mut self.key_sequence,
-                                    ) {
+                                    if let Some(action) =
+                                        selector.handle_command(commands::PopupCommand::Select)
+                                    {
                                         match action {
                                             ThemeSelectorAction::ThemeChanged => {
                                                 self.text_reader.invalidate_render_cache();
-                                                self.show_info(&format!(
-                                                    "Theme: {}",
-                                                    current_theme_name()
+                                                self.show_info(i18n::tr_args(
+                                                    "notify.theme",
+                                                    &[("name", &current_theme_name())],
                                                 ));
                                                 self.theme_selector = None;
                                                 self.close_popup_to_previous();
@@ -1067,42 +2592,254 @@ impl App {
                     return;
                 }
 
-                let nav_panel_width = self.nav_panel_width();
-                if mouse_event.column < nav_panel_width {
-                    self.focused_panel = FocusedPanel::Main(MainPanel::NavigationList);
-                    self.text_reader.clear_selection();
-
-                    let nav_area = self.get_navigation_panel_area();
-                    let click_type = self
-                        .mouse_tracker
-                        .detect_click_type(mouse_event.column, mouse_event.row);
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::InstanceLock)
+                ) {
+                    // The warning must be answered with a key
+                    return;
+                }
 
-                    match click_type {
-                        ClickType::Single | ClickType::Triple => {
-                            self.navigation_panel.handle_mouse_click(
-                                mouse_event.column,
-                                mouse_event.row,
-                                nav_area,
-                            );
-                        }
-                        ClickType::Double => {
-                            if self.navigation_panel.handle_mouse_click(
-                                mouse_event.column,
-                                mouse_event.row,
-                                nav_area,
-                            ) {
-                                self.handle_navigation_panel_enter();
-                            }
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::Onboarding)
+                ) {
+                    if let Some(ref onboarding) = self.onboarding {
+                        if onboarding.is_outside_popup_area(mouse_event.column, mouse_event.row) {
+                            self.onboarding = None;
+                            self.close_popup_to_previous();
                         }
                     }
-                } else {
-                    // Click in content area (right 70% of screen)
-                    if !self.is_main_panel(MainPanel::Content) {
-                        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
-                        // Clear manual navigation flag when switching to content
-                        self.navigation_panel
-                            .table_of_contents
-                            .clear_manual_navigation();
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ReadingThreads)
+                ) {
+                    if self.reading_threads.as_ref().is_some_and(|threads| {
+                        threads.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.reading_threads = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::CommentStorage)
+                ) {
+                    if self.comment_storage.as_ref().is_some_and(|storage| {
+                        storage.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.comment_storage = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::RareWords)
+                ) {
+                    if self.rare_words.as_ref().is_some_and(|rare_words| {
+                        rare_words.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.rare_words = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ResurfacedComment)
+                ) {
+                    if self.resurfaced_comment.as_ref().is_some_and(|popup| {
+                        popup.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.resurfaced_comment = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ReadingStats)
+                ) {
+                    if self.reading_stats.as_ref().is_some_and(|stats| {
+                        stats.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.reading_stats = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::BookInfo)
+                ) {
+                    if self.book_info.as_ref().is_some_and(|popup| {
+                        popup.is_outside_popup_area(mouse_event.column, mouse_event.row)
+                    }) {
+                        self.book_info = None;
+                        self.close_popup_to_previous();
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::HelpBarMenu)
+                ) {
+                    let action = self
+                        .help_bar_menu
+                        .as_mut()
+                        .and_then(|menu| menu.handle_click(mouse_event.column, mouse_event.row));
+                    if let Some(action) = action {
+                        self.handle_help_bar_menu_action(action);
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::ChapterPicker)
+                ) {
+                    let click_x = mouse_event.column;
+                    let click_y = mouse_event.row;
+
+                    let action = if let Some(ref mut picker) = self.chapter_picker {
+                        if picker.is_outside_popup_area(click_x, click_y) {
+                            Some(ChapterPickerAction::Close)
+                        } else {
+                            let click_type = self.mouse_tracker.detect_click_type(click_x, click_y);
+                            if picker.handle_mouse_click(click_x, click_y)
+                                && click_type == ClickType::Double
+                            {
+                                picker.handle_command(commands::PopupCommand::Select)
+                            } else {
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+
+                    if let Some(action) = action {
+                        self.handle_chapter_picker_action(action);
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::CommandPalette)
+                ) {
+                    let click_x = mouse_event.column;
+                    let click_y = mouse_event.row;
+                    // Clicks select; Enter runs, so the command can quit
+                    let outside = match self.command_palette.as_mut() {
+                        Some(palette) if palette.is_outside_popup_area(click_x, click_y) => true,
+                        Some(palette) => {
+                            palette.handle_mouse_click(click_x, click_y);
+                            false
+                        }
+                        None => false,
+                    };
+                    if outside {
+                        self.handle_command_palette_action(CommandPaletteAction::Close);
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::BookSwitcher)
+                ) {
+                    let click_x = mouse_event.column;
+                    let click_y = mouse_event.row;
+                    let action = match self.book_switcher.as_mut() {
+                        Some(switcher) if switcher.is_outside_popup_area(click_x, click_y) => {
+                            Some(BookSwitcherAction::Close)
+                        }
+                        Some(switcher) => switcher.handle_mouse_click(click_x, click_y),
+                        None => None,
+                    };
+                    if let Some(action) = action {
+                        self.handle_book_switcher_action(action);
+                    }
+                    return;
+                }
+
+                if matches!(
+                    self.focused_panel,
+                    FocusedPanel::Popup(PopupWindow::FigureList)
+                ) {
+                    let click_x = mouse_event.column;
+                    let click_y = mouse_event.row;
+                    let action = match self.figure_list.as_mut() {
+                        Some(list) if list.is_outside_popup_area(click_x, click_y) => {
+                            Some(FigureListAction::Close)
+                        }
+                        Some(list) => list.handle_mouse_click(click_x, click_y),
+                        None => None,
+                    };
+                    if let Some(action) = action {
+                        self.handle_figure_list_action(action);
+                    }
+                    return;
+                }
+
+                if self.is_on_panel_border(mouse_event.column, mouse_event.row) {
+                    self.resizing_nav_panel = true;
+                    return;
+                }
+
+                let nav_panel_width = self.nav_panel_width();
+                if mouse_event.column < nav_panel_width {
+                    let was_collapsed = self.is_nav_panel_collapsed();
+                    self.focused_panel = FocusedPanel::Main(MainPanel::NavigationList);
+                    self.text_reader.clear_selection();
+                    if was_collapsed {
+                        // Clicking the strip only expands the panel
+                        return;
+                    }
+
+                    let nav_area = self.get_navigation_panel_area();
+                    let click_type = self
+                        .mouse_tracker
+                        .detect_click_type(mouse_event.column, mouse_event.row);
+
+                    match click_type {
+                        ClickType::Single | ClickType::Triple => {
+                            self.navigation_panel.handle_mouse_click(
+                                mouse_event.column,
+                                mouse_event.row,
+                                nav_area,
+                            );
+                        }
+                        ClickType::Double => {
+                            if self.navigation_panel.handle_mouse_click(
+                                mouse_event.column,
+                                mouse_event.row,
+                                nav_area,
+                            ) {
+                                self.handle_navigation_panel_enter();
+                            }
+                        }
+                    }
+                } else {
+                    // Click in content area (right 70% of screen)
+                    if !self.is_main_panel(MainPanel::Content) {
+                        self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+                        // Clear manual navigation flag when switching to content
+                        self.navigation_panel
+                            .table_of_contents
+                            .clear_manual_navigation();
                     }
 
                     let click_type = self
@@ -1112,7 +2849,17 @@ impl App {
                     match click_type {
                         ClickType::Single => {
                             // Check if click is on a link first
-                            if let Some(image_src) = self
+                            if let Some(node_index) = self
+                                .text_reader
+                                .check_summary_click(mouse_event.column, mouse_event.row)
+                            {
+                                self.text_reader.toggle_collapsible(node_index);
+                            } else if let Some((chapter_href, target)) = self
+                                .text_reader
+                                .check_image_note_click(mouse_event.column, mouse_event.row)
+                            {
+                                self.text_reader.start_editing_comment(chapter_href, target);
+                            } else if let Some(image_src) = self
                                 .text_reader
                                 .check_image_click(mouse_event.column, mouse_event.row)
                             {
@@ -1134,6 +2881,12 @@ impl App {
                 }
             }
             MouseEventKind::Up(MouseButton::Left) => {
+                if self.resizing_nav_panel {
+                    self.resizing_nav_panel = false;
+                    settings::set_nav_panel_width(self.nav_panel_percent);
+                    return;
+                }
+
                 // Block mouse up events for all popups
                 if self.has_active_popup() {
                     return;
@@ -1157,6 +2910,13 @@ impl App {
                     return;
                 }
 
+                if self.resizing_nav_panel {
+                    let total_width = self.terminal_size.width.max(1) as u32;
+                    let percent = ((mouse_event.column as u32 + 1) * 100 / total_width) as u16;
+                    self.set_nav_panel_percent(percent);
+                    return;
+                }
+
                 let nav_panel_width = self.nav_panel_width();
                 if mouse_event.column >= nav_panel_width {
                     let old_scroll_offset = self.text_reader.get_scroll_offset();
@@ -1242,15 +3002,30 @@ impl App {
     fn scroll_to_anchor(&mut self, anchor_id: &str) -> std::io::Result<bool> {
         if let Some(target_line) = self.text_reader.get_anchor_position(anchor_id) {
             self.text_reader.scroll_to_line(target_line);
-            self.text_reader
-                .highlight_line_temporarily(target_line, Duration::from_secs(2));
+            self.text_reader.highlight_jump_target(target_line);
             Ok(true)
         } else {
             warn!("Anchor '{anchor_id}' not found in current chapter");
+            self.show_warning(i18n::tr_args(
+                "notify.link_target_missing",
+                &[("anchor", &anchor_id)],
+            ));
             Ok(false)
         }
     }
 
+    /// Tell about a link's anchor the chapter it led to doesn't have
+    pub fn poll_missing_anchor(&mut self) -> bool {
+        let Some(anchor_id) = self.text_reader.take_missing_anchor() else {
+            return false;
+        };
+        self.show_warning(i18n::tr_args(
+            "notify.link_target_missing",
+            &[("anchor", &anchor_id)],
+        ));
+        true
+    }
+
     fn navigate_to_chapter_by_file(
         &mut self,
         chapter_file: &str,
@@ -1322,1566 +3097,2422 @@ impl App {
         None
     }
 
-    /// Find the spine index for a given href
-    fn find_spine_index_by_href(&self, href: &str) -> Option<usize> {
-        fn normalize_href(href: &str) -> String {
-            let normalized = href
-                .trim_start_matches("../")
-                .trim_start_matches("./")
-                .trim_start_matches("OEBPS/");
+    /// Navigate to a TOC entry, scrolling to its anchor once the chapter is rendered
+    fn navigate_to_toc_entry(&mut self, href: &str, anchor: Option<String>) {
+        if let Some(chapter_index) = self.find_spine_index_by_href(href) {
+            let _ = self.navigate_to_chapter(chapter_index);
+            let nav_area = self.get_navigation_panel_area();
+            let toc_height = nav_area.height as usize;
+            self.navigation_panel
+                .table_of_contents
+                .set_active_from_hint(href, anchor.as_deref(), Some(toc_height));
 
-            // Remove fragment identifiers (e.g., "#ch1", "#tit") for matching
-            if let Some(fragment_pos) = normalized.find('#') {
-                normalized[..fragment_pos].to_string()
-            } else {
-                normalized.to_string()
+            if let Some(anchor_id) = anchor {
+                self.text_reader.store_pending_anchor_scroll(anchor_id);
             }
+            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
         }
+    }
 
-        let book = self.current_book.as_ref()?;
+    /// Save `directory` as the books directory and rescan the library
+    fn use_books_directory(&mut self, directory: &str) {
+        settings::set_books_directory(directory);
+        self.book_manager.set_scan_directory(directory);
+        self.book_manager.apply_metadata(&self.book_metadata);
+        self.book_manager.apply_pinned(&self.pinned_books);
+        Self::record_library_scan(&mut self.book_manager, &mut self.new_books);
+        self.save_library_scan();
+        self.navigation_panel.refresh_book_list(&self.book_manager);
+        self.start_metadata_scan();
+        self.set_main_panel_focus(MainPanel::NavigationList);
+
+        let count = self.book_manager.books.len();
+        let key = if count == 1 {
+            "notify.found_books.one"
+        } else {
+            "notify.found_books.other"
+        };
+        self.show_info(i18n::tr_args(
+            key,
+            &[("count", &count), ("directory", &directory)],
+        ));
+    }
 
-        let normalized_href = normalize_href(href);
+    fn open_chapter_picker(&mut self) {
+        let Some(book) = self.current_book.as_ref() else {
+            return;
+        };
 
-        for (index, spine_item) in book.epub.spine.iter().enumerate() {
-            if let Some(resource) = book.epub.resources.get(&spine_item.idref) {
-                let path_str = resource.path.to_string_lossy();
-                let normalized_path = normalize_href(&path_str);
+        // The current chapter first, then chapters from the jump list
+        let mut recent = vec![book.current_chapter()];
+        for chapter_index in self.jump_list.recent_chapters(&book.file) {
+            if !recent.contains(&chapter_index) {
+                recent.push(chapter_index);
+            }
+        }
 
-                if normalized_path == normalized_href
-                    || normalized_path.ends_with(&normalized_href)
-                    || normalized_href.ends_with(&normalized_path)
-                {
-                    return Some(index);
+        let toc_items = self.navigation_panel.get_toc_items();
+        let picker = ChapterPicker::new(&toc_items, |href| {
+            let chapter_index = self.find_spine_index_by_href(href)?;
+            recent.iter().position(|&idx| idx == chapter_index)
+        });
+
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.chapter_picker = Some(picker);
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ChapterPicker);
+    }
+
+    fn handle_chapter_picker_action(&mut self, action: ChapterPickerAction) {
+        self.chapter_picker = None;
+        match action {
+            ChapterPickerAction::Close => {
+                self.close_popup_to_previous();
+            }
+            ChapterPickerAction::NavigateToChapter { href, anchor } => {
+                self.close_popup_to_previous();
+                if let Some(book) = &self.current_book {
+                    let current_location = JumpLocation {
+                        epub_path: book.file.clone(),
+                        chapter_index: book.current_chapter(),
+                        node_index: self.text_reader.get_current_node_index(),
+                    };
+                    self.jump_list.push(current_location);
                 }
+                self.navigate_to_toc_entry(&href, anchor);
             }
         }
+    }
 
-        None
+    fn open_book_switcher(&mut self) {
+        let current = self.current_book.as_ref().map(|book| book.file.clone());
+        let entries: Vec<SwitcherEntry> = self
+            .session_books
+            .paths()
+            .map(|path| SwitcherEntry {
+                path: path.to_string(),
+                name: self
+                    .book_manager
+                    .books
+                    .iter()
+                    .find(|book| book.path == path)
+                    .map_or(path, |book| book.display_name.as_str())
+                    .to_string(),
+                chapter_index: if current.as_deref() == Some(path) {
+                    None
+                } else {
+                    self.session_books
+                        .position(path)
+                        .map(|position| position.chapter_index)
+                },
+            })
+            .collect();
+        if entries.is_empty() {
+            self.show_info(i18n::tr("notify.no_session_books"));
+            return;
+        }
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.book_switcher = Some(BookSwitcher::new(entries, current));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::BookSwitcher);
     }
 
-    fn handle_image_click(&mut self, image_src: &str, terminal_size: Rect) {
-        let picker = match self.text_reader.get_image_picker() {
-            Some(picker) => picker,
-            None => {
-                // image picker not available
-                return;
+    fn handle_book_switcher_action(&mut self, action: BookSwitcherAction) {
+        self.book_switcher = None;
+        self.close_popup_to_previous();
+        if let BookSwitcherAction::Switch { path } = action {
+            if self.current_book.as_ref().map(|book| book.file.as_str()) != Some(path.as_str()) {
+                self.switch_to_session_book(&path);
             }
-        };
+        }
+    }
 
-        let original_image = if let Some(image) = self.text_reader.get_loaded_image(image_src) {
-            image
-        } else if let Some(image) = self.book_images.get_image(image_src) {
-            Arc::new(image)
-        } else {
-            debug!("Image not loaded and could not be loaded: {image_src}");
+    fn open_figure_list(&mut self) {
+        let Some(book) = self.current_book.as_ref() else {
             return;
         };
+        let current_chapter = book.current_chapter();
+        let figures = match self.book_images.book_figures() {
+            Ok(figures) => figures,
+            Err(e) => {
+                error!("Failed to list the book's images: {e}");
+                self.show_error(i18n::tr_args("notify.list_images_failed", &[("error", &e)]));
+                return;
+            }
+        };
+        if figures.is_empty() {
+            self.show_info(i18n::tr("notify.no_book_images"));
+            return;
+        }
+        let entries = figures
+            .into_iter()
+            .map(|figure| FigureEntry {
+                chapter_index: figure.chapter_index,
+                chapter_title: self.toc_chapter_title(Some(figure.chapter_index)),
+                src: figure.src,
+                alt_text: figure.alt_text,
+                caption: figure.caption,
+            })
+            .collect();
 
-        let font_size = picker.font_size();
-        let (img_width, img_height) = original_image.dimensions();
-
-        // Calculate 2x scaled dimensions in pixels
-        let scaled_width = img_width * 2;
-        let scaled_height = img_height * 2;
-
-        // Calculate max dimensions that fit on screen (in pixels)
-        let max_width_pixels = terminal_size.width.saturating_sub(6) as u32 * font_size.0 as u32;
-        let max_height_pixels = terminal_size.height.saturating_sub(6) as u32 * font_size.1 as u32;
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.figure_list = Some(FigureList::new(entries, current_chapter));
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::FigureList);
+    }
 
-        // Determine final dimensions maintaining aspect ratio
-        let (final_width, final_height) =
-            if scaled_width <= max_width_pixels && scaled_height <= max_height_pixels {
-                // 2x scale fits
-                (scaled_width, scaled_height)
+    fn handle_figure_list_action(&mut self, action: FigureListAction) {
+        self.figure_list = None;
+        self.close_popup_to_previous();
+        if let FigureListAction::Show { chapter_index, src } = action {
+            if let Some(book) = &self.current_book {
+                let current_location = JumpLocation {
+                    epub_path: book.file.clone(),
+                    chapter_index: book.current_chapter(),
+                    node_index: self.text_reader.get_current_node_index(),
+                };
+                self.jump_list.push(current_location);
+            }
+            self.set_main_panel_focus(MainPanel::Content);
+            if let Err(e) = self.navigate_to_chapter(chapter_index) {
+                error!("Failed to navigate to chapter {chapter_index}: {e}");
+                self.show_error(i18n::tr_args(
+                    "notify.navigate_chapter_failed",
+                    &[("error", &e)],
+                ));
             } else {
-                // Scale to fit screen
-                let width_scale = max_width_pixels as f32 / img_width as f32;
-                let height_scale = max_height_pixels as f32 / img_height as f32;
-                let scale = width_scale.min(height_scale);
+                self.text_reader
+                    .store_pending_match_scroll(MatchTarget::Figure { src });
+            }
+        }
+    }
 
-                (
-                    (img_width as f32 * scale) as u32,
-                    (img_height as f32 * scale) as u32,
-                )
-            };
+    fn open_command_palette(&mut self) {
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.command_palette = Some(
+            CommandPalette::new(&self.recent_commands)
+                .with_leader_key(self.leader_key)
+                .with_ctrl_z(self.ctrl_z),
+        );
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::CommandPalette);
+    }
 
-        // Pre-scale the image using fast_image_resize for better performance
-        let prescaled_image = if final_width != img_width || final_height != img_height {
-            match self
-                .book_images
-                .resize_image_to(&original_image, final_width, final_height)
-            {
-                Ok(resized) => Arc::new(resized),
-                Err(e) => {
-                    warn!("Failed to pre-scale image with fast_image_resize: {e}, using original");
-                    original_image
-                }
+    fn handle_command_palette_action(&mut self, action: CommandPaletteAction) -> Option<AppAction> {
+        self.command_palette = None;
+        self.close_popup_to_previous();
+        match action {
+            CommandPaletteAction::Close => None,
+            CommandPaletteAction::Run { key, command } => {
+                self.recent_commands.retain(|recent| *recent != key);
+                self.recent_commands.insert(0, key);
+                // Run from the panel the palette was opened over
+                self.execute_command(command, None)
             }
-        } else {
-            original_image
+        }
+    }
+
+    fn open_reading_threads(&mut self, new_thread: bool) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            return;
         };
+        // Record where the reader is, so the active entry shows it
+        self.save_bookmark_with_throttle(true);
+
+        let mut entries = Vec::new();
+        if let Some(bookmark) = self.bookmarks.get_bookmark(&path) {
+            entries.push(ThreadEntry {
+                name: None,
+                chapter_title: self.toc_chapter_title(bookmark.chapter_index),
+                last_read: bookmark.last_read,
+            });
+        }
+        for thread in self.bookmarks.threads(&path) {
+            entries.push(ThreadEntry {
+                name: Some(thread.name.clone()),
+                chapter_title: self.toc_chapter_title(thread.chapter_index),
+                last_read: thread.last_read,
+            });
+        }
 
-        // Save current main panel before opening image popup
+        let threads = ReadingThreads::new(entries, self.active_thread.clone());
+        self.reading_threads = Some(if new_thread {
+            threads.naming()
+        } else {
+            threads
+        });
         if let FocusedPanel::Main(panel) = self.focused_panel {
             self.previous_main_panel = panel;
         }
-
-        let popup = ImagePopup::new(prescaled_image, picker, image_src.to_string());
-        self.image_popup = Some(popup);
-        self.focused_panel = FocusedPanel::Popup(PopupWindow::ImagePopup);
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ReadingThreads);
     }
 
-    /// Apply scroll events (positive for down, negative for up)
-    fn apply_scroll(&mut self, scroll_amount: i32, column: u16) {
-        if scroll_amount == 0 {
-            return;
+    fn load_book_comments(&mut self, book_path: &Path) {
+        match BookComments::new(book_path) {
+            Ok(comments) => {
+                let comments_arc = Arc::new(Mutex::new(comments));
+                self.text_reader.set_book_comments(comments_arc);
+            }
+            Err(e) => {
+                warn!("Failed to initialize book comments: {e}");
+            }
         }
+    }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::BookSearch)
-        ) {
-            if let Some(ref mut book_search) = self.book_search {
-                let search_height = self.terminal_size.height;
-                if scroll_amount > 0 {
-                    for _ in 0..scroll_amount.min(10) {
-                        book_search.scroll_down(search_height);
-                    }
-                } else {
-                    for _ in 0..(-scroll_amount).min(10) {
-                        book_search.scroll_up(search_height);
-                    }
-                }
+    /// The comments files in the comments directory, with the library book
+    /// each belongs to
+    fn comment_file_entries(&self) -> anyhow::Result<(PathBuf, Vec<CommentFileEntry>)> {
+        let directory = BookComments::comments_dir()?;
+        let entries = BookComments::list_comment_files(&directory)?
+            .into_iter()
+            .map(|info| {
+                let file_name = info.file_name();
+                let book_name = self
+                    .book_manager
+                    .books
+                    .iter()
+                    .find(|book| BookComments::file_name_for(Path::new(&book.path)) == file_name)
+                    .map(|book| book.display_name.clone());
+                CommentFileEntry { info, book_name }
+            })
+            .collect();
+        Ok((directory, entries))
+    }
+
+    fn open_comment_storage(&mut self) {
+        match self.comment_file_entries() {
+            Ok((directory, entries)) => {
+                self.comment_storage = Some(CommentStorage::new(directory, entries));
+            }
+            Err(e) => {
+                self.show_error(i18n::tr_args(
+                    "notify.list_comment_files_failed",
+                    &[("error", &e)],
+                ));
+                return;
             }
+        }
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::CommentStorage);
+    }
+
+    fn handle_comment_storage_action(&mut self, action: CommentStorageAction) {
+        if matches!(action, CommentStorageAction::Close) {
+            self.comment_storage = None;
+            self.close_popup_to_previous();
+            return;
+        }
+        if instance_lock::is_read_only() {
+            self.show_error(i18n::tr("notify.comments_read_only"));
             return;
         }
 
-        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Help)) {
-            if let Some(ref mut help_popup) = self.help_popup {
-                if scroll_amount > 0 {
-                    for _ in 0..scroll_amount.min(10) {
-                        help_popup.scroll_down();
+        match action {
+            CommentStorageAction::Close => {}
+            CommentStorageAction::Delete { path } => {
+                match BookComments::trash_comment_file(&path) {
+                    Ok(trashed) => self.show_info(i18n::tr_args(
+                        "notify.moved_to",
+                        &[("path", &trashed.display())],
+                    )),
+                    Err(e) => {
+                        self.show_error(i18n::tr_args("notify.delete_failed", &[("error", &e)]))
                     }
-                } else {
-                    for _ in 0..(-scroll_amount).min(10) {
-                        help_popup.scroll_up();
+                }
+            }
+            CommentStorageAction::Merge { target, source } => {
+                let source = crate::widget::onboarding::expand_tilde(&source);
+                let merged = BookComments::new_with_path(target)
+                    .and_then(|mut comments| comments.merge_from_file(&source));
+                match merged {
+                    Ok(summary) => self.show_info(i18n::tr_args(
+                        "notify.merged",
+                        &[
+                            ("added", &summary.added),
+                            ("conflicts", &summary.conflicts),
+                            ("duplicates", &summary.duplicates),
+                        ],
+                    )),
+                    Err(e) => {
+                        self.show_error(i18n::tr_args("notify.merge_failed", &[("error", &e)]))
                     }
                 }
             }
-            return;
-        }
-
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::BookStats)
-        ) {
-            if scroll_amount > 0 {
-                for _ in 0..scroll_amount.min(10) {
-                    self.book_stat.handle_j();
+            CommentStorageAction::Migrate { directory } => {
+                if std::env::var_os("BOOKOKRAT_COMMENTS_DIR").is_some() {
+                    self.show_error(i18n::tr("notify.comments_dir_from_env"));
+                    return;
                 }
-            } else {
-                for _ in 0..(-scroll_amount).min(10) {
-                    self.book_stat.handle_k();
+                let copied = BookComments::comments_dir().and_then(|from| {
+                    let to = crate::widget::onboarding::expand_tilde(&directory);
+                    BookComments::copy_comments_dir(&from, &to)
+                });
+                match copied {
+                    Ok(count) => {
+                        settings::set_comments_dir(&directory);
+                        self.show_info(i18n::tr_args(
+                            "notify.comments_copied",
+                            &[("count", &count), ("directory", &directory)],
+                        ));
+                    }
+                    Err(e) => self
+                        .show_error(i18n::tr_args("notify.comments_not_moved", &[("error", &e)])),
                 }
             }
-            return;
         }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::ReadingHistory)
-        ) {
-            if let Some(ref mut history) = self.reading_history {
-                if scroll_amount > 0 {
-                    for _ in 0..scroll_amount.min(10) {
-                        history.handle_j();
-                    }
-                } else {
-                    for _ in 0..(-scroll_amount).min(10) {
-                        history.handle_k();
-                    }
+        // The open book may have gained or lost comments
+        if let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) {
+            self.load_book_comments(Path::new(&path));
+            self.text_reader.invalidate_render_cache();
+        }
+        match self.comment_file_entries() {
+            Ok((directory, entries)) => {
+                if let Some(storage) = &mut self.comment_storage {
+                    storage.refresh(directory, entries);
                 }
             }
-            return;
+            Err(e) => self.show_error(i18n::tr_args(
+                "notify.list_comment_files_failed",
+                &[("error", &e)],
+            )),
         }
+    }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::CommentsViewer)
-        ) {
-            if let Some(ref mut viewer) = self.comments_viewer {
-                if !viewer.handle_mouse_scroll(column, scroll_amount) {
-                    if scroll_amount > 0 {
-                        for _ in 0..scroll_amount.min(10) {
-                            viewer.handle_j();
-                        }
-                    } else {
-                        for _ in 0..(-scroll_amount).min(10) {
-                            viewer.handle_k();
-                        }
-                    }
+    /// Title of the TOC entry for a spine chapter, for the threads popup and
+    /// the auto-advance separator
+    fn toc_chapter_title(&self, chapter_index: Option<usize>) -> String {
+        fn find_title(app: &App, items: &[TocItem], chapter_index: usize) -> Option<String> {
+            items.iter().find_map(|item| {
+                let matches = item
+                    .href()
+                    .and_then(|href| app.find_spine_index_by_href(href))
+                    == Some(chapter_index);
+                if matches {
+                    return Some(item.title().to_string());
                 }
-            }
-            return;
+                match item {
+                    TocItem::Section { children, .. } => find_title(app, children, chapter_index),
+                    TocItem::Chapter { .. } => None,
+                }
+            })
         }
 
-        // Block scrolling for other popups
-        if self.has_active_popup() {
+        let Some(chapter_index) = chapter_index else {
+            return "Unknown chapter".to_string();
+        };
+        find_title(self, &self.navigation_panel.get_toc_items(), chapter_index)
+            .unwrap_or_else(|| format!("Chapter {}", chapter_index + 1))
+    }
+
+    /// Save the chapter, or the blocks the selection covers, as Markdown
+    /// (the original HTML in the raw HTML view) to the export directory
+    fn export_chapter(&mut self) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let title = self.toc_chapter_title(Some(book.current_chapter()));
+        let directory = crate::widget::onboarding::expand_tilde(&settings::get_export_directory());
+
+        let result = if let Some(html) = self.text_reader.raw_html_export() {
+            chapter_export::write_html(&directory, &title, html)
+        } else if let Some(nodes) = self.text_reader.export_nodes() {
+            let chapter_file = self.text_reader.get_current_chapter_file().clone();
+            let image_file = |url: &str| self.book_images.image_file(url, chapter_file.as_deref());
+            let image_file: Option<chapter_export::ImageFile> =
+                settings::get_export_images().then_some(&image_file);
+            chapter_export::write_markdown(&directory, &title, &nodes, image_file)
+        } else {
             return;
+        };
+        match result {
+            Ok(path) => self.show_info(i18n::tr_args(
+                "notify.exported",
+                &[("path", &path.display())],
+            )),
+            Err(e) => self.show_error(i18n::tr_args("notify.export_failed", &[("error", &e)])),
         }
+    }
 
-        let is_nav_panel = column < self.nav_panel_width();
+    fn open_rare_words(&mut self) {
+        if self.current_book.is_none() {
+            return;
+        }
+        if self.glossary.is_none() {
+            self.show_info(i18n::tr("notify.glossing_off"));
+            return;
+        }
+        let Some(targets) = self.text_reader.gloss_targets() else {
+            self.show_info(i18n::tr("notify.glossing_pending"));
+            return;
+        };
 
-        if is_nav_panel {
-            let nav_panel_height = self.terminal_size.height.saturating_sub(2);
-            if scroll_amount > 0 {
-                for _ in 0..scroll_amount.min(10) {
-                    self.navigation_panel.scroll_down(nav_panel_height);
-                }
-            } else {
-                for _ in 0..(-scroll_amount).min(10) {
-                    self.navigation_panel.scroll_up(nav_panel_height);
-                }
-            }
-        } else if scroll_amount > 0 {
-            for _ in 0..scroll_amount.min(10) {
-                self.scroll_down();
-            }
-        } else {
-            for _ in 0..(-scroll_amount).min(10) {
-                self.scroll_up();
-            }
+        self.rare_words = Some(RareWords::new(targets.words().to_vec()));
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::RareWords);
     }
 
-    pub fn open_with_system_viewer(&mut self) {
-        if let Some(book) = &self.current_book {
-            match self
-                .system_command_executor
-                .open_file_at_chapter(&book.file, book.current_chapter())
-            {
-                Ok(_) => {
-                    info!(
-                        "Successfully opened EPUB with system viewer at chapter {}",
-                        book.current_chapter()
-                    );
-                    self.show_info("Opened in external viewer");
-                }
-                Err(e) => {
-                    error!("Failed to open EPUB with system viewer: {e}");
-                    self.show_error(format!("Failed to open in external viewer: {e}"));
-                }
-            }
+    fn toggle_mouse_capture(&mut self) {
+        self.mouse_capture = !self.mouse_capture;
+        self.mouse_capture_change = Some(self.mouse_capture);
+        if self.mouse_capture {
+            self.show_info(i18n::tr("notify.mouse_captured"));
         } else {
-            error!("No EPUB file currently loaded");
-            self.show_error("No EPUB file currently loaded");
+            self.show_info(i18n::tr("notify.mouse_released"));
         }
     }
 
-    pub fn get_scroll_offset(&self) -> usize {
-        self.text_reader.get_scroll_offset()
+    pub fn is_mouse_captured(&self) -> bool {
+        self.mouse_capture
     }
 
-    fn jump_to_location(&mut self, location: JumpLocation) -> Result<()> {
-        if self.current_book.as_ref().map(|x| &x.file) != Some(&location.epub_path) {
-            self.load_epub(&location.epub_path, true)?;
-        }
+    /// Mouse capture to set on the terminal, once after each toggle
+    pub fn take_mouse_capture_change(&mut self) -> Option<bool> {
+        self.mouse_capture_change.take()
+    }
 
-        if self.current_book.as_ref().map(|x| x.current_chapter()) != Some(location.chapter_index) {
-            self.navigate_to_chapter(location.chapter_index)?;
-        }
+    /// Whether the frame being drawn is to be saved, once per Space+?
+    pub fn take_screen_capture(&mut self) -> bool {
+        std::mem::take(&mut self.screen_capture_pending)
+    }
 
-        self.text_reader.restore_to_node_index(location.node_index);
+    /// Suspend to the shell as Ctrl+Z does elsewhere, with the session saved in
+    /// case the job is never resumed
+    fn request_suspend(&mut self) {
+        if cfg!(unix) {
+            self.save_session();
+            self.suspend_pending = true;
+        } else {
+            self.show_info(i18n::tr("notify.suspend_unsupported"));
+        }
+    }
 
-        self.save_bookmark();
+    /// Whether to suspend before the next frame, once per Ctrl+Z
+    pub fn take_suspend_request(&mut self) -> bool {
+        std::mem::take(&mut self.suspend_pending)
+    }
 
-        Ok(())
+    /// Back from the shell, maybe in another terminal or at another size
+    pub fn handle_resume(&mut self) {
+        self.text_reader.requery_image_picker();
+        self.handle_resize();
     }
 
-    /// Handle Ctrl+O - jump back in history
-    fn jump_back(&mut self) {
-        if let Some(location) = self.jump_list.jump_back() {
-            if let Err(e) = self.jump_to_location(location) {
-                error!("Failed to jump back: {e}");
-                self.show_error(format!("Failed to jump back: {e}"));
+    /// Write a drawn frame to the export directory for a bug report
+    pub fn save_screen_capture(&mut self, buffer: &ratatui::buffer::Buffer) {
+        let directory = crate::widget::onboarding::expand_tilde(&settings::get_export_directory());
+        match crate::screen_capture::write_capture(&directory, buffer) {
+            Ok(paths) => {
+                let names: Vec<String> = paths
+                    .iter()
+                    .filter_map(|path| path.file_name())
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .collect();
+                self.show_info(i18n::tr_args(
+                    "notify.screen_saved",
+                    &[
+                        ("directory", &directory.display()),
+                        ("files", &names.join(", ")),
+                    ],
+                ));
             }
+            Err(e) => self.show_error(i18n::tr_args(
+                "notify.screen_capture_failed",
+                &[("error", &e)],
+            )),
         }
     }
 
-    /// Handle Ctrl+I - jump forward in history
-    fn jump_forward(&mut self) {
-        if let Some(location) = self.jump_list.jump_forward() {
-            if let Err(e) = self.jump_to_location(location) {
-                error!("Failed to jump forward: {e}");
-                self.show_error(format!("Failed to jump forward: {e}"));
+    /// Show a random comment from any book of the library; `quiet` when
+    /// there is none to show, as on startup
+    fn open_resurfaced_comment(&mut self, quiet: bool) {
+        let book_paths: Vec<String> = self
+            .book_manager
+            .books
+            .iter()
+            .map(|book| book.path.clone())
+            .collect();
+        let picked = BookComments::comments_dir().ok().and_then(|dir| {
+            BookComments::random_comment(&dir, &book_paths, &mut rand::thread_rng())
+        });
+        let Some((book_path, comment)) = picked else {
+            if !quiet {
+                self.show_info(i18n::tr("notify.no_library_comments"));
+            }
+            return;
+        };
+
+        let book_title = self
+            .book_manager
+            .find_book_index_by_path(&book_path)
+            .and_then(|index| self.book_manager.get_book_info(index))
+            .map(|book| book.display_name.clone())
+            .unwrap_or_else(|| book_path.clone());
+        let (chapter_title, quote) = match self.book_manager.load_epub(&book_path) {
+            Ok(mut epub) => {
+                use crate::widget::comments_viewer::CommentsViewer;
+                let toc_items = TocParser::parse_toc_structure(&epub);
+                let chapter_title = CommentsViewer::find_chapter_title(
+                    &comment.chapter_href,
+                    &toc_items,
+                    &mut epub,
+                );
+                let quote = CommentsViewer::load_chapter_document(&mut epub, &comment.chapter_href)
+                    .and_then(|doc| {
+                        doc.blocks
+                            .get(comment.node_index())
+                            .map(|node| node.plain_text())
+                    })
+                    .filter(|text| !text.trim().is_empty());
+                (chapter_title, quote)
+            }
+            Err(e) => {
+                warn!("Failed to open {book_path} for a resurfaced comment: {e}");
+                (comment.chapter_href.clone(), None)
             }
+        };
+
+        self.resurfaced_comment = Some(ResurfacedComment::new(
+            book_path,
+            book_title,
+            chapter_title,
+            &comment,
+            quote,
+        ));
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ResurfacedComment);
     }
 
-    /// Calculate the navigation panel width based on stored terminal width
-    fn nav_panel_width(&self) -> u16 {
-        if self.zen_mode {
-            0
-        } else {
-            // 30% of terminal width, minimum 20 columns
-            ((self.terminal_size.width * 30) / 100).max(20)
+    fn handle_resurfaced_comment_action(&mut self, action: ResurfacedCommentAction) {
+        let Some(popup) = self.resurfaced_comment.take() else {
+            return;
+        };
+        self.close_popup_to_previous();
+        let ResurfacedCommentAction::Jump = action else {
+            return;
+        };
+
+        let is_open = self
+            .current_book
+            .as_ref()
+            .is_some_and(|book| book.file == popup.book_path);
+        if !is_open {
+            if let Err(e) = self.open_book_for_reading_by_path(&popup.book_path) {
+                error!("Failed to open {}: {e}", popup.book_path);
+                self.show_error(i18n::tr_args("notify.open_book_failed", &[("error", &e)]));
+                return;
+            }
+        }
+        self.set_main_panel_focus(MainPanel::Content);
+
+        // Set pending node restore before navigating
+        self.text_reader.restore_to_node_index(popup.node_index);
+        if let Err(e) = self.navigate_to_chapter_by_href(&popup.chapter_href) {
+            error!("Failed to navigate to chapter {}: {e}", popup.chapter_href);
+            self.show_error(i18n::tr_args(
+                "notify.navigate_comment_failed",
+                &[("error", &e)],
+            ));
         }
     }
 
-    /// Get the navigation panel area based on current terminal size
-    fn get_navigation_panel_area(&self) -> Rect {
-        if self.zen_mode {
-            return Rect::default(); // No navigation panel in zen mode
+    fn open_reading_stats(&mut self) {
+        let unit = settings::get_daily_goal()
+            .map(|goal| goal.unit)
+            .unwrap_or_default();
+        let book_manager = &self.book_manager;
+        self.reading_stats = Some(ReadingStats::new(
+            &self.reading_goals,
+            chrono::Local::now().date_naive(),
+            unit,
+            |path| {
+                book_manager
+                    .find_book_index_by_path(path)
+                    .and_then(|index| book_manager.get_book_info(index))
+                    .map(|book| book.display_name.clone())
+                    .unwrap_or_else(|| {
+                        std::path::Path::new(path)
+                            .file_stem()
+                            .map_or(path.to_string(), |stem| stem.to_string_lossy().into_owned())
+                    })
+            },
+        ));
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
-        use ratatui::layout::{Constraint, Direction, Layout};
-        // Calculate the same layout as in render
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Min(0), Constraint::Length(3)])
-            .split(self.terminal_size);
-        let main_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(chunks[0]);
-        main_chunks[0]
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ReadingStats);
     }
 
-    /// Handle Enter key press in navigation panel
-    fn handle_navigation_panel_enter(&mut self) {
-        use crate::navigation_panel::SelectedActionOwned;
-        match self.navigation_panel.get_selected_action() {
-            SelectedActionOwned::BookIndex(index) => {
-                if let Err(e) = self.open_book_for_reading(index) {
-                    error!("Failed to open book at index {index}: {e}");
-                    self.show_error(format!("Failed to open book: {e}"));
-                }
+    /// Show the metadata of the book selected in the book list, else of
+    /// the open book
+    fn open_book_info(&mut self) {
+        let in_book_list = self.focused_panel == FocusedPanel::Main(MainPanel::NavigationList)
+            && self.navigation_panel.is_in_book_mode();
+        let book = if in_book_list {
+            self.book_manager
+                .get_book_info(self.navigation_panel.get_selected_book_index())
+        } else {
+            self.current_book.as_ref().and_then(|book| {
+                self.book_manager
+                    .find_book_index_by_path(&book.file)
+                    .and_then(|index| self.book_manager.get_book_info(index))
+            })
+        };
+        let Some(book) = book else {
+            self.show_info(i18n::tr("notify.no_book_selected"));
+            return;
+        };
+        self.book_info = Some(BookInfoPopup::new(
+            book.file_name(),
+            &book.metadata,
+            &book.path,
+        ));
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::BookInfo);
+    }
+
+    fn handle_rare_words_action(&mut self, action: RareWordsAction) {
+        match action {
+            RareWordsAction::Close => {
+                self.rare_words = None;
+                self.close_popup_to_previous();
             }
-            SelectedActionOwned::BackToBooks => {
-                self.navigation_panel.switch_to_book_mode();
+            RareWordsAction::Show { block } => {
+                self.rare_words = None;
+                self.close_popup_to_previous();
+                self.text_reader.restore_to_node_index(block);
+                self.save_bookmark();
             }
-            SelectedActionOwned::TocItem(toc_item) => {
-                match toc_item {
-                    TocItem::Chapter { href, anchor, .. } => {
-                        // Find the spine index for this href
-                        if let Some(spine_index) = self.find_spine_index_by_href(&href) {
-                            let _ = self.navigate_to_chapter(spine_index);
-                            // Handle anchor if present
-                            if let Some(anchor_id) = anchor {
-                                self.text_reader
-                                    .store_pending_anchor_scroll(anchor_id.clone());
-                                self.text_reader.set_active_anchor(Some(anchor_id));
-                            } else {
-                                self.text_reader.set_active_anchor(None);
-                            }
+            RareWordsAction::MarkKnown { word } => {
+                let Some(glossary) = &mut self.glossary else {
+                    return;
+                };
+                glossary.known_words.insert(&word);
+                if let Err(e) = glossary.known_words.save() {
+                    error!("Failed to save known words: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.save_known_words_failed",
+                        &[("error", &e)],
+                    ));
+                }
+                self.text_reader.remove_gloss_word(&word);
+                self.show_info(i18n::tr_args("notify.word_known", &[("word", &word)]));
+            }
+        }
+    }
 
-                            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
-                            self.navigation_panel
-                                .table_of_contents
-                                .clear_manual_navigation();
-                            self.update_toc_state();
-                        } else {
-                            error!("Could not find spine index for href: {href}");
-                            self.show_error("Chapter not found in book");
-                        }
-                    }
-                    TocItem::Section { href, anchor, .. } => {
-                        if let Some(section_href) = href {
-                            // This section has content - navigate to it
-                            if let Some(spine_index) = self.find_spine_index_by_href(&section_href)
-                            {
-                                let _ = self.navigate_to_chapter(spine_index);
-                                // Handle anchor if present
-                                if let Some(anchor_id) = anchor {
-                                    self.text_reader
-                                        .store_pending_anchor_scroll(anchor_id.clone());
-                                    // Set this anchor as the active one
-                                    self.text_reader.set_active_anchor(Some(anchor_id));
-                                } else {
-                                    self.text_reader.set_active_anchor(None);
-                                }
-                                self.focused_panel = FocusedPanel::Main(MainPanel::Content);
-                                // Update the cache to reflect the correct active section
-                                self.update_toc_state();
-                            } else {
-                                error!(
-                                    "Could not find spine index for section href: {section_href}"
-                                );
-                                self.show_error("Section not found in book");
-                            }
-                        } else {
-                            // No href - just toggle expansion
-                            self.navigation_panel
-                                .table_of_contents
-                                .toggle_selected_expansion();
-                        }
-                    }
+    fn handle_reading_threads_action(&mut self, action: ReadingThreadsAction) {
+        let Some(path) = self.current_book.as_ref().map(|book| book.file.clone()) else {
+            return;
+        };
+        self.reading_threads = None;
+        self.close_popup_to_previous();
+
+        match action {
+            ReadingThreadsAction::Close => {}
+            ReadingThreadsAction::Switch { name } => {
+                if name != self.active_thread {
+                    self.switch_reading_thread(&path, name);
                 }
             }
-            SelectedActionOwned::None => {
-                // Nothing selected
+            ReadingThreadsAction::Create { name } => {
+                let Some(book) = &self.current_book else {
+                    return;
+                };
+                let thread = ReadingThread {
+                    name: name.clone(),
+                    chapter_href: Self::get_chapter_href(&book.epub, book.current_chapter())
+                        .unwrap_or_else(|| format!("chapter_{}", book.current_chapter())),
+                    node_index: Some(self.text_reader.get_current_node_index()),
+                    chapter_index: Some(book.current_chapter()),
+                    last_read: chrono::Utc::now(),
+                };
+                if self.bookmarks.add_thread(&path, thread) {
+                    self.active_thread = Some(name.clone());
+                    self.show_info(i18n::tr_args("notify.thread_started", &[("name", &name)]));
+                } else {
+                    self.show_error(i18n::tr_args("notify.thread_exists", &[("name", &name)]));
+                }
+            }
+            ReadingThreadsAction::Delete { name } => {
+                if !self.bookmarks.remove_thread(&path, &name) {
+                    return;
+                }
+                if self.active_thread.as_deref() == Some(name.as_str()) {
+                    // The deleted thread's position is gone; continue at the primary bookmark
+                    self.active_thread = None;
+                    self.go_to_thread_position(&path, None);
+                }
+                self.show_info(i18n::tr_args("notify.thread_deleted", &[("name", &name)]));
             }
         }
     }
 
-    pub fn draw(&mut self, f: &mut ratatui::Frame, fps_counter: &FPSCounter) {
-        let auto_scroll_updated = self.text_reader.update_auto_scroll();
-        if auto_scroll_updated {
-            self.save_bookmark();
+    /// Save the position of the thread being read, then continue at `name`
+    fn switch_reading_thread(&mut self, path: &str, name: Option<String>) {
+        self.save_bookmark_with_throttle(true);
+        if let Some(book) = &self.current_book {
+            self.jump_list.push(JumpLocation {
+                epub_path: book.file.clone(),
+                chapter_index: book.current_chapter(),
+                node_index: self.text_reader.get_current_node_index(),
+            });
         }
 
-        self.terminal_size = f.area();
-
-        let background_block = Block::default().style(Style::default().bg(current_theme().base_00));
-        f.render_widget(background_block, f.area());
-
-        if self.zen_mode {
-            // Zen mode: full screen content, no navigation panel or help bar
-            if let Some(ref book) = self.current_book {
-                self.text_reader.render(
-                    f,
-                    f.area(),
-                    book.current_chapter(),
-                    book.total_chapters(),
-                    &current_theme(),
-                    true, // always focused in zen mode
-                );
-            } else {
-                self.render_default_content(f, f.area(), "Select a file to view its content");
+        self.active_thread = name.clone();
+        self.go_to_thread_position(path, name.as_deref());
+        match name {
+            Some(name) => {
+                self.show_info(i18n::tr_args("notify.thread_switched", &[("name", &name)]))
             }
-            // Don't set help_bar_area in zen mode - it's hidden
-        } else {
-            // Normal mode: existing layout
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([Constraint::Min(0), Constraint::Length(3)])
-                .split(f.area());
-
-            let main_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-                .split(chunks[0]);
-
-            self.navigation_panel.render(
-                f,
-                main_chunks[0],
-                self.is_main_panel(MainPanel::NavigationList),
-                &current_theme(),
-                &self.book_manager,
-            );
+            None => self.show_info(i18n::tr("notify.main_bookmark")),
+        }
+    }
 
-            if let Some(ref book) = self.current_book {
-                self.text_reader.render(
-                    f,
-                    main_chunks[1],
-                    book.current_chapter(),
-                    book.total_chapters(),
-                    &current_theme(),
-                    self.is_main_panel(MainPanel::Content),
-                );
-            } else {
-                self.render_default_content(f, main_chunks[1], "Select a file to view its content");
-            }
+    fn go_to_thread_position(&mut self, path: &str, name: Option<&str>) {
+        let position = match name {
+            Some(name) => self
+                .bookmarks
+                .get_thread(path, name)
+                .map(|thread| (thread.chapter_href.clone(), thread.node_index)),
+            None => self
+                .bookmarks
+                .get_bookmark(path)
+                .map(|bookmark| (bookmark.chapter_href.clone(), bookmark.node_index)),
+        };
+        let Some((chapter_href, node_index)) = position else {
+            return;
+        };
+        let chapter_index = self
+            .current_book
+            .as_ref()
+            .and_then(|book| Self::find_chapter_index_by_href(&book.epub, &chapter_href));
+        let Some(chapter_index) = chapter_index else {
+            warn!("Could not find chapter for href: {chapter_href}");
+            return;
+        };
 
-            self.render_help_bar(f, chunks[1], fps_counter);
-            self.help_bar_area = chunks[1];
+        let location = JumpLocation {
+            epub_path: path.to_string(),
+            chapter_index,
+            node_index: node_index.unwrap_or(0),
+        };
+        if let Err(e) = self.jump_to_location(location) {
+            error!("Failed to open reading thread: {e}");
+            self.show_error(i18n::tr_args("notify.open_thread_failed", &[("error", &e)]));
         }
+    }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::ReadingHistory)
-        ) {
-            // First render a dimming overlay
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10)) // Very dark but not black
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+    /// Find the spine index for a given href
+    fn find_spine_index_by_href(&self, href: &str) -> Option<usize> {
+        fn normalize_href(href: &str) -> String {
+            let normalized = href
+                .trim_start_matches("../")
+                .trim_start_matches("./")
+                .trim_start_matches("OEBPS/");
 
-            if let Some(ref mut history) = self.reading_history {
-                history.render(f, f.area());
+            // Remove fragment identifiers (e.g., "#ch1", "#tit") for matching
+            if let Some(fragment_pos) = normalized.find('#') {
+                normalized[..fragment_pos].to_string()
+            } else {
+                normalized.to_string()
             }
         }
 
-        if let Some(ref mut image_popup) = self.image_popup {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10)) // todo: this is not from pallette
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+        let book = self.current_book.as_ref()?;
 
-            image_popup.render(f, f.area());
-        }
+        let normalized_href = normalize_href(href);
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::BookSearch)
-        ) {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+        for (index, spine_item) in book.epub.spine.iter().enumerate() {
+            if let Some(resource) = book.epub.resources.get(&spine_item.idref) {
+                let path_str = resource.path.to_string_lossy();
+                let normalized_path = normalize_href(&path_str);
 
-            if let Some(ref mut book_search) = self.book_search {
-                book_search.render(f, f.area(), &current_theme());
+                if normalized_path == normalized_href
+                    || normalized_path.ends_with(&normalized_href)
+                    || normalized_href.ends_with(&normalized_path)
+                {
+                    return Some(index);
+                }
             }
         }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::BookStats)
-        ) {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+        None
+    }
 
-            self.book_stat.render(f, f.area());
+    /// The open popup if it can be resized, and whether it is taking typed text
+    fn resizable_popup(&self) -> Option<(PopupKind, bool)> {
+        let FocusedPanel::Popup(popup) = self.focused_panel else {
+            return None;
+        };
+        match popup {
+            PopupWindow::ReadingHistory => Some((PopupKind::ReadingHistory, false)),
+            PopupWindow::CommentsViewer => Some((
+                PopupKind::CommentsViewer,
+                self.comments_viewer
+                    .as_ref()
+                    .is_some_and(|viewer| viewer.is_typing()),
+            )),
+            PopupWindow::Help => Some((PopupKind::Help, false)),
+            PopupWindow::BookSearch => Some((
+                PopupKind::BookSearch,
+                self.book_search
+                    .as_ref()
+                    .is_some_and(|search| search.is_typing()),
+            )),
+            PopupWindow::ThemeSelector => Some((PopupKind::ThemeSelector, false)),
+            PopupWindow::ImagePopup => Some((PopupKind::Image, false)),
+            _ => None,
         }
+    }
 
-        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Help)) {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+    /// Open the image popup on `image_src`; false when the image can't be
+    /// shown
+    fn handle_image_click(&mut self, image_src: &str, terminal_size: Rect) -> bool {
+        // The popup steps through the chapter's images in reading order
+        self.text_reader.complete_layout();
+        // An image used twice in the chapter is shown once
+        let mut seen = std::collections::HashSet::new();
+        let chapter_images: Vec<String> = self
+            .text_reader
+            .image_positions()
+            .into_iter()
+            .map(|(_, src)| src)
+            .filter(|src| seen.insert(src.clone()))
+            .collect();
 
-            if let Some(ref mut help_popup) = self.help_popup {
-                help_popup.render(f, f.area());
+        let picker = match self.text_reader.get_image_picker() {
+            Some(picker) => picker,
+            None => {
+                // image picker not available
+                return false;
             }
-        }
+        };
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::CommentsViewer)
-        ) {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+        let original_image = if let Some(image) = self.text_reader.get_loaded_image(image_src) {
+            image
+        } else if let Some(image) = self.book_images.get_image(image_src) {
+            Arc::new(image)
+        } else {
+            debug!("Image not loaded and could not be loaded: {image_src}");
+            return false;
+        };
 
-            if let Some(ref mut comments_viewer) = self.comments_viewer {
-                comments_viewer.render(f, f.area());
+        let font_size = picker.font_size();
+        let (img_width, img_height) = original_image.dimensions();
+
+        // Calculate 2x scaled dimensions in pixels
+        let scaled_width = img_width * 2;
+        let scaled_height = img_height * 2;
+
+        // Calculate max dimensions that fit in the popup's largest size (in pixels)
+        let bounds = popup_geometry::popup_rect(PopupKind::Image, terminal_size);
+        let max_width_pixels = bounds.width.saturating_sub(6) as u32 * font_size.0 as u32;
+        // A figure caption takes a band beneath the image
+        let caption = self.text_reader.image_caption(image_src);
+        let caption_rows = if caption.is_some() {
+            CAPTION_BAND_HEIGHT
+        } else {
+            0
+        };
+        let max_height_pixels =
+            bounds.height.saturating_sub(6 + caption_rows) as u32 * font_size.1 as u32;
+
+        // Determine final dimensions maintaining aspect ratio
+        let (final_width, final_height) =
+            if scaled_width <= max_width_pixels && scaled_height <= max_height_pixels {
+                // 2x scale fits
+                (scaled_width, scaled_height)
+            } else {
+                // Scale to fit screen
+                let width_scale = max_width_pixels as f32 / img_width as f32;
+                let height_scale = max_height_pixels as f32 / img_height as f32;
+                let scale = width_scale.min(height_scale);
+
+                (
+                    (img_width as f32 * scale) as u32,
+                    (img_height as f32 * scale) as u32,
+                )
+            };
+
+        // Pre-scale the image using fast_image_resize for better performance
+        let prescaled_image = if final_width != img_width || final_height != img_height {
+            match self
+                .book_images
+                .resize_image_to(&original_image, final_width, final_height)
+            {
+                Ok(resized) => Arc::new(resized),
+                Err(e) => {
+                    warn!("Failed to pre-scale image with fast_image_resize: {e}, using original");
+                    original_image
+                }
             }
+        } else {
+            original_image
+        };
+
+        // Save current main panel before opening image popup
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
 
-        if matches!(
-            self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::ThemeSelector)
-        ) {
-            let dim_block = Block::default().style(
-                Style::default()
-                    .bg(Color::Rgb(10, 10, 10))
-                    .add_modifier(Modifier::DIM),
-            );
-            f.render_widget(dim_block, f.area());
+        let popup = ImagePopup::new(prescaled_image, picker, image_src.to_string(), caption)
+            .with_chapter_images(chapter_images);
+        self.image_popup = Some(popup);
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ImagePopup);
+        true
+    }
 
-            if let Some(ref mut theme_selector) = self.theme_selector {
-                theme_selector.render(f, f.area());
+    /// Show the chapter's next (or previous) image in the popup, passing
+    /// over images that fail to load
+    fn step_popup_image(&mut self, forward: bool) {
+        let Some(popup) = &self.image_popup else {
+            return;
+        };
+        let candidates = popup.step_candidates(forward, settings::get_image_popup_wrap());
+        if candidates.is_empty() {
+            self.show_info(i18n::tr(if forward {
+                "notify.last_image"
+            } else {
+                "notify.first_image"
+            }));
+            return;
+        }
+
+        for (skipped, src) in candidates.into_iter().enumerate() {
+            if self.handle_image_click(&src, self.terminal_size) {
+                if skipped > 0 {
+                    self.show_info(i18n::tr_args(
+                        "notify.images_skipped",
+                        &[("count", &skipped)],
+                    ));
+                }
+                return;
             }
         }
+        self.show_info(i18n::tr("notify.no_loadable_image"));
     }
 
-    fn render_default_content(&self, f: &mut ratatui::Frame, area: Rect, content: &str) {
-        // Use focus-aware colors instead of hardcoded false
-        let (text_color, border_color, _bg_color) =
-            current_theme().get_panel_colors(self.is_main_panel(MainPanel::Content));
-
-        let content_border = Block::default()
-            .borders(Borders::ALL)
-            .title("Content")
-            .border_style(Style::default().fg(border_color))
-            .style(Style::default().bg(current_theme().base_00));
+    /// Scroll to the next (or previous) image in the chapter, wrapping at the ends
+    fn jump_to_image(&mut self, forward: bool, open_popup: bool) {
+        let Some(book) = &self.current_book else {
+            return;
+        };
+        let origin = JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        };
 
-        let paragraph = Paragraph::new(content)
-            .block(content_border)
-            .style(Style::default().fg(text_color).bg(current_theme().base_00));
+        let jump = if forward {
+            self.text_reader.scroll_to_next_image()
+        } else {
+            self.text_reader.scroll_to_prev_image()
+        };
 
-        f.render_widget(paragraph, area);
+        match jump {
+            Some(jump) => {
+                self.jump_list.push(origin);
+                self.save_bookmark();
+                if open_popup {
+                    self.handle_image_click(&jump.src, self.terminal_size);
+                } else {
+                    self.show_info(i18n::tr_args(
+                        "notify.image_position",
+                        &[("index", &(jump.index + 1)), ("total", &jump.total)],
+                    ));
+                }
+            }
+            None if !self.text_reader.is_rendering_images() => {
+                self.show_info(i18n::tr("notify.images_hidden_hint"));
+            }
+            None => self.show_info(i18n::tr("notify.no_chapter_images")),
+        }
     }
 
-    fn handle_help_bar_click(&mut self, click_x: u16, click_y: u16) -> bool {
-        let area = self.help_bar_area;
-        if click_y < area.y || click_y >= area.y + area.height {
-            return false;
-        }
-        if click_x < area.x || click_x >= area.x + area.width {
-            return false;
+    /// Apply scroll events (positive for down, negative for up)
+    fn apply_scroll(&mut self, scroll_amount: i32, column: u16) {
+        if scroll_amount == 0 {
+            return;
         }
 
-        let inner_x = click_x.saturating_sub(area.x + 1);
-        let inner_y = click_y.saturating_sub(area.y + 1);
-
-        if inner_y != 0 {
-            return false;
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookSearch)
+        ) {
+            if let Some(ref mut book_search) = self.book_search {
+                let search_height = self.terminal_size.height;
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        book_search.scroll_down(search_height);
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        book_search.scroll_up(search_height);
+                    }
+                }
+            }
+            return;
         }
 
-        let width = area.width.saturating_sub(2);
-        let comments_full = "[Space+a: Comments] ";
-        let history_full = "[Space+h: History] ";
-        let stats_full = "[Space+d: Stats] ";
-        let theme_full = "[Space+t: Theme] ";
-        let help_full = "[?: Help]";
-
-        let total_len = (comments_full.len()
-            + history_full.len()
-            + stats_full.len()
-            + theme_full.len()
-            + help_full.len()) as u16;
-        let section_start = width.saturating_sub(total_len);
-
-        let comments_start = section_start + "[".len() as u16;
-        let comments_end = comments_start + "Space+a: Comments".len() as u16;
-        let history_start = section_start + comments_full.len() as u16 + "[".len() as u16;
-        let history_end = history_start + "Space+h: History".len() as u16;
-        let stats_start = section_start
-            + comments_full.len() as u16
-            + history_full.len() as u16
-            + "[".len() as u16;
-        let stats_end = stats_start + "Space+d: Stats".len() as u16;
-        let theme_start = section_start
-            + comments_full.len() as u16
-            + history_full.len() as u16
-            + stats_full.len() as u16
-            + "[".len() as u16;
-        let theme_end = theme_start + "Space+t: Theme".len() as u16;
-        let help_start = section_start
-            + comments_full.len() as u16
-            + history_full.len() as u16
-            + stats_full.len() as u16
-            + theme_full.len() as u16
-            + "[".len() as u16;
-        let help_end = help_start + "?: Help".len() as u16;
-
-        if inner_x >= comments_start && inner_x < comments_end {
-            if self.current_book.is_some() {
-                if let FocusedPanel::Main(panel) = self.focused_panel {
-                    self.previous_main_panel = panel;
-                }
-                if let Some(ref mut book) = self.current_book {
-                    let toc_items = self.navigation_panel.get_toc_items();
-                    let current_chapter_href =
-                        Self::get_chapter_href(&book.epub, book.current_chapter());
-                    let mut viewer = crate::widget::comments_viewer::CommentsViewer::new(
-                        self.text_reader.get_comments(),
-                        &mut book.epub,
-                        &toc_items,
-                        current_chapter_href,
-                    );
-                    viewer.restore_position();
-                    self.comments_viewer = Some(viewer);
-                    self.focused_panel = FocusedPanel::Popup(PopupWindow::CommentsViewer);
+        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Help)) {
+            if let Some(ref mut help_popup) = self.help_popup {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        help_popup.scroll_down();
+                    }
+                } else {
+                    for _ in 0..(-scroll_amount).min(10) {
+                        help_popup.scroll_up();
+                    }
                 }
             }
-            return true;
+            return;
         }
 
-        if inner_x >= history_start && inner_x < history_end {
-            if let FocusedPanel::Main(panel) = self.focused_panel {
-                self.previous_main_panel = panel;
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookStats)
+        ) {
+            if scroll_amount > 0 {
+                for _ in 0..scroll_amount.min(10) {
+                    self.book_stat.handle_j();
+                }
+            } else {
+                for _ in 0..(-scroll_amount).min(10) {
+                    self.book_stat.handle_k();
+                }
             }
-            self.reading_history = Some(ReadingHistory::new(&self.bookmarks));
-            self.focused_panel = FocusedPanel::Popup(PopupWindow::ReadingHistory);
-            return true;
+            return;
         }
 
-        if inner_x >= stats_start && inner_x < stats_end {
-            if let Some(ref mut book) = self.current_book {
-                let terminal_size = (self.terminal_size.width, self.terminal_size.height);
-                if let Err(e) = self
-                    .book_stat
-                    .calculate_stats(&mut book.epub, terminal_size)
-                {
-                    error!("Failed to calculate book statistics: {e}");
-                    self.show_error(format!("Failed to calculate statistics: {e}"));
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingHistory)
+        ) {
+            if let Some(ref mut history) = self.reading_history {
+                if scroll_amount > 0 {
+                    for _ in 0..scroll_amount.min(10) {
+                        history.handle_j();
+                    }
                 } else {
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
+                    for _ in 0..(-scroll_amount).min(10) {
+                        history.handle_k();
                     }
-                    self.book_stat.show();
-                    self.focused_panel = FocusedPanel::Popup(PopupWindow::BookStats);
                 }
             }
-            return true;
+            return;
         }
 
-        if inner_x >= theme_start && inner_x < theme_end {
-            if let FocusedPanel::Main(panel) = self.focused_panel {
-                self.previous_main_panel = panel;
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::CommentsViewer)
+        ) {
+            if let Some(ref mut viewer) = self.comments_viewer {
+                if !viewer.handle_mouse_scroll(column, scroll_amount) {
+                    if scroll_amount > 0 {
+                        for _ in 0..scroll_amount.min(10) {
+                            viewer.handle_j();
+                        }
+                    } else {
+                        for _ in 0..(-scroll_amount).min(10) {
+                            viewer.handle_k();
+                        }
+                    }
+                }
             }
-            self.theme_selector = Some(ThemeSelector::new());
-            self.focused_panel = FocusedPanel::Popup(PopupWindow::ThemeSelector);
-            return true;
+            return;
         }
 
-        if inner_x >= help_start && inner_x < help_end {
-            if let FocusedPanel::Main(panel) = self.focused_panel {
-                self.previous_main_panel = panel;
-            }
-            self.help_popup = Some(HelpPopup::new());
-            self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
-            return true;
+        // Block scrolling for other popups
+        if self.has_active_popup() {
+            return;
         }
 
-        false
-    }
-
-    fn render_help_bar(&self, f: &mut ratatui::Frame, area: Rect, fps_counter: &FPSCounter) {
-        use crate::notification::NotificationLevel;
-        let (_, _, border_color, _, _) = current_theme().get_interface_colors(false);
+        let is_nav_panel = column < self.nav_panel_width();
 
-        let help_content = if let Some(notification) = self.notifications.get_current() {
-            let level_str = match notification.level {
-                NotificationLevel::Info => "INFO",
-                NotificationLevel::Warning => "WARNING",
-                NotificationLevel::Error => "ERROR",
-            };
-            format!("[{}] {} | ESC: Dismiss", level_str, notification.message)
-        } else if self.is_in_search_mode() {
-            let search_state = if self.navigation_panel.is_searching() {
-                self.navigation_panel.get_search_state()
-            } else {
-                self.text_reader.get_search_state()
-            };
-            match search_state.mode {
-                SearchMode::InputMode => {
-                    let query = &search_state.query;
-                    let match_info = if search_state.matches.is_empty() && !query.is_empty() {
-                        "No matches"
-                    } else if !search_state.matches.is_empty() {
-                        &format!("{} matches", search_state.matches.len())
-                    } else {
-                        ""
-                    };
-                    format!("/ {query}█  {match_info}  ESC: Cancel | Enter: Search")
+        if is_nav_panel {
+            let nav_panel_height = self.terminal_size.height.saturating_sub(2);
+            if scroll_amount > 0 {
+                for _ in 0..scroll_amount.min(10) {
+                    self.navigation_panel.scroll_down(nav_panel_height);
                 }
-                SearchMode::NavigationMode => {
-                    let query = &search_state.query;
-                    let match_info = search_state.get_match_info();
-                    format!("/{query}  {match_info}  n/N: Navigate | ESC: Exit")
+            } else {
+                for _ in 0..(-scroll_amount).min(10) {
+                    self.navigation_panel.scroll_up(nav_panel_height);
                 }
-                _ => "Search mode active".to_string(),
             }
-        } else if self.text_reader.has_text_selection() {
-            "a: Add comment | c/Ctrl+C: Copy to clipboard | ESC: Clear selection".to_string()
+        } else if scroll_amount > 0 {
+            for _ in 0..scroll_amount.min(10) {
+                self.scroll_down();
+            }
         } else {
-            let help_text = match self.focused_panel {
-                FocusedPanel::Main(MainPanel::NavigationList) => {
-                    "j/k: Navigate | Enter: Select | h/l: Fold/Unfold | H/L: Fold/Unfold All | Tab: Switch | q: Quit"
-                }
-                FocusedPanel::Main(MainPanel::Content) => {
-                    "j/k: Scroll | h/l: Chapter | Ctrl+d/u: Half-screen | Tab: Switch | Space+o: Open | q: Quit"
-                }
-                FocusedPanel::Popup(PopupWindow::ReadingHistory) => {
-                    "j/k/Scroll: Navigate | Enter/DblClick: Open | ESC: Close"
-                }
-                FocusedPanel::Popup(PopupWindow::BookStats) => {
-                    "j/k/Ctrl+d/u/Scroll: Scroll | Enter/DblClick: Jump | ESC: Close"
-                }
-                FocusedPanel::Popup(PopupWindow::ImagePopup) => "ESC/Any key: Close",
-                FocusedPanel::Popup(PopupWindow::BookSearch) => {
-                    "Space+f: Reopen | Space+F: New Search"
-                }
-                FocusedPanel::Popup(PopupWindow::Help) => {
-                    "j/k/Ctrl+d/u: Scroll | gg/G: Top/Bottom | ESC/?: Close"
-                }
-                FocusedPanel::Popup(PopupWindow::CommentsViewer) => {
-                    "j/k/Ctrl+d/u: Scroll | /: Search | Enter/DblClick: Jump | ESC: Close"
+            for _ in 0..(-scroll_amount).min(10) {
+                self.scroll_up();
+            }
+        }
+    }
+
+    pub fn open_with_system_viewer(&mut self) {
+        if let Some(book) = &self.current_book {
+            match self
+                .system_command_executor
+                .open_file_at_chapter(&book.file, book.current_chapter())
+            {
+                Ok(_) => {
+                    info!(
+                        "Successfully opened EPUB with system viewer at chapter {}",
+                        book.current_chapter()
+                    );
+                    self.show_info(i18n::tr("notify.opened_external"));
                 }
-                FocusedPanel::Popup(PopupWindow::ThemeSelector) => {
-                    "j/k: Navigate | Enter: Apply | ESC: Close"
+                Err(e) => {
+                    error!("Failed to open EPUB with system viewer: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.open_external_failed",
+                        &[("error", &e)],
+                    ));
                 }
-            };
-            help_text.to_string()
-        };
+            }
+        } else {
+            error!("No EPUB file currently loaded");
+            self.show_error(i18n::tr("notify.no_epub_loaded"));
+        }
+    }
 
-        let block = Block::default()
-            .borders(Borders::ALL)
-            .border_style(Style::default().fg(border_color))
-            .style(Style::default().bg(current_theme().base_00));
+    pub fn get_scroll_offset(&self) -> usize {
+        self.text_reader.get_scroll_offset()
+    }
 
-        let inner_area = block.inner(area);
-        f.render_widget(block, area);
+    fn jump_to_location(&mut self, location: JumpLocation) -> Result<()> {
+        if self.current_book.as_ref().map(|x| &x.file) != Some(&location.epub_path) {
+            self.load_epub(&location.epub_path, true)?;
+        }
 
-        let left_content = if self.is_profiling() {
-            format!("{} | FPS: {}", help_content, fps_counter.current_fps)
+        if self.current_book.as_ref().map(|x| x.current_chapter()) != Some(location.chapter_index) {
+            self.navigate_to_chapter(location.chapter_index)?;
+        }
+
+        self.text_reader.restore_to_node_index(location.node_index);
+
+        self.save_bookmark();
+
+        Ok(())
+    }
+
+    /// Handle Ctrl+O - jump back in history
+    fn jump_back(&mut self) {
+        if let Some(location) = self.jump_list.jump_back() {
+            if let Err(e) = self.jump_to_location(location) {
+                error!("Failed to jump back: {e}");
+                self.show_error(i18n::tr_args("notify.jump_back_failed", &[("error", &e)]));
+            }
+        }
+    }
+
+    /// Handle Ctrl+I - jump forward in history
+    fn jump_forward(&mut self) {
+        if let Some(location) = self.jump_list.jump_forward() {
+            if let Err(e) = self.jump_to_location(location) {
+                error!("Failed to jump forward: {e}");
+                self.show_error(i18n::tr_args(
+                    "notify.jump_forward_failed",
+                    &[("error", &e)],
+                ));
+            }
+        }
+    }
+
+    /// Calculate the navigation panel width based on stored terminal width
+    fn nav_panel_width(&self) -> u16 {
+        if self.zen_mode {
+            0
         } else {
-            help_content
+            self.get_navigation_panel_area().width
+        }
+    }
+
+    /// Split the area above the help bar into navigation panel and content
+    fn split_main_area(&self, area: Rect) -> std::rc::Rc<[Rect]> {
+        let constraints = if self.is_nav_panel_collapsed() {
+            [
+                Constraint::Length(NAV_PANEL_STRIP_WIDTH),
+                Constraint::Min(0),
+            ]
+        } else {
+            [
+                Constraint::Percentage(self.nav_panel_percent),
+                Constraint::Percentage(100 - self.nav_panel_percent),
+            ]
         };
-        let left_para = Paragraph::new(left_content).style(
-            Style::default()
-                .fg(current_theme().base_03)
-                .bg(current_theme().base_00),
+        Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(constraints)
+            .split(area)
+    }
+
+    /// In auto mode the navigation panel shrinks to a strip unless it has
+    /// focus (or had it before a popup opened)
+    fn is_nav_panel_collapsed(&self) -> bool {
+        let panel = match self.focused_panel {
+            FocusedPanel::Main(panel) => panel,
+            FocusedPanel::Popup(_) => self.previous_main_panel,
+        };
+        self.nav_panel_mode == NavPanelMode::Auto
+            && self.current_book.is_some()
+            && panel != MainPanel::NavigationList
+    }
+
+    fn render_collapsed_nav_panel(&self, f: &mut ratatui::Frame, area: Rect) {
+        let palette = current_theme();
+        let (_, border_color, _) = palette.get_panel_colors(false);
+        let strip = Paragraph::new("›")
+            .style(Style::default().fg(border_color))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::default().fg(border_color))
+                    .style(Style::default().bg(palette.base_00)),
+            );
+        f.render_widget(strip, area);
+    }
+
+    /// Change the navigation panel width, keeping the reading position
+    fn set_nav_panel_percent(&mut self, percent: u16) {
+        let percent = percent.clamp(NAV_PANEL_MIN_PERCENT, NAV_PANEL_MAX_PERCENT);
+        if percent == self.nav_panel_percent {
+            return;
+        }
+
+        let current_node = self.text_reader.get_current_node_index();
+        self.nav_panel_percent = percent;
+        self.text_reader.invalidate_render_cache();
+        self.text_reader.restore_to_node_index(current_node);
+    }
+
+    fn resize_nav_panel(&mut self, grow: bool) {
+        if self.zen_mode {
+            return;
+        }
+        let percent = if grow {
+            self.nav_panel_percent + NAV_PANEL_STEP_PERCENT
+        } else {
+            self.nav_panel_percent
+                .saturating_sub(NAV_PANEL_STEP_PERCENT)
+        };
+        self.set_nav_panel_percent(percent);
+        settings::set_nav_panel_width(self.nav_panel_percent);
+    }
+
+    /// Below the compact size the help bar is dropped and only one main panel
+    /// fits, like in zen mode
+    fn is_compact_terminal(&self) -> bool {
+        let (width, height) = settings::get_compact_terminal_size();
+        self.terminal_size.width < width || self.terminal_size.height < height
+    }
+
+    /// On a compact terminal the navigation panel takes the whole screen
+    /// while it has focus or there is no book to read
+    fn shows_full_screen_nav_panel(&self) -> bool {
+        let panel = match self.focused_panel {
+            FocusedPanel::Main(panel) => panel,
+            FocusedPanel::Popup(_) => self.previous_main_panel,
+        };
+        !self.zen_mode
+            && self.is_compact_terminal()
+            && (self.current_book.is_none() || panel == MainPanel::NavigationList)
+    }
+
+    fn render_terminal_too_small(&self, f: &mut ratatui::Frame, min_size: (u16, u16)) {
+        let area = f.area();
+        let palette = current_theme();
+        let lines = vec![
+            Line::from(Span::styled(
+                format!("Terminal too small: {}×{}", area.width, area.height),
+                Style::default()
+                    .fg(palette.base_08)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(Span::styled(
+                format!("need at least {}×{}", min_size.0, min_size.1),
+                Style::default().fg(palette.base_04),
+            )),
+        ];
+        let top = area.height.saturating_sub(lines.len() as u16) / 2;
+        let message_area = Rect {
+            y: area.y + top,
+            height: area.height - top,
+            ..area
+        };
+        f.render_widget(
+            Paragraph::new(lines)
+                .alignment(Alignment::Center)
+                .wrap(Wrap { trim: true }),
+            message_area,
         );
-        f.render_widget(left_para, inner_area);
+    }
+
+    /// Whether a click lands on the navigation panel's right border; the
+    /// content's left border next to it still starts a selection
+    fn is_on_panel_border(&self, column: u16, row: u16) -> bool {
+        if self.zen_mode || self.is_compact_terminal() || self.is_nav_panel_collapsed() {
+            return false;
+        }
+        let nav_area = self.get_navigation_panel_area();
+        let border = nav_area.right();
+        row >= nav_area.y && row < nav_area.bottom() && column + 1 == border
+    }
+
+    /// Get the navigation panel area based on current terminal size
+    fn get_navigation_panel_area(&self) -> Rect {
+        if self.zen_mode {
+            return Rect::default(); // No navigation panel in zen mode
+        }
+        if self.is_compact_terminal() {
+            return if self.shows_full_screen_nav_panel() {
+                self.terminal_size
+            } else {
+                Rect::default()
+            };
+        }
+        // Calculate the same layout as in render
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(3)])
+            .split(self.terminal_size);
+        self.split_main_area(chunks[0])[0]
+    }
+
+    /// Handle Enter key press in navigation panel
+    fn handle_navigation_panel_enter(&mut self) {
+        use crate::navigation_panel::SelectedActionOwned;
+        match self.navigation_panel.get_selected_action() {
+            SelectedActionOwned::BookIndex(index) => {
+                if let Err(e) = self.open_book_for_reading(index) {
+                    error!("Failed to open book at index {index}: {e}");
+                    self.show_error(i18n::tr_args("notify.open_book_failed", &[("error", &e)]));
+                }
+            }
+            SelectedActionOwned::BackToBooks => {
+                self.navigation_panel.switch_to_book_mode();
+            }
+            SelectedActionOwned::TocItem(toc_item) => {
+                match toc_item {
+                    TocItem::Chapter { href, anchor, .. } => {
+                        // Find the spine index for this href
+                        if let Some(spine_index) = self.find_spine_index_by_href(&href) {
+                            let _ = self.navigate_to_chapter(spine_index);
+                            // Handle anchor if present
+                            if let Some(anchor_id) = anchor {
+                                self.text_reader
+                                    .store_pending_anchor_scroll(anchor_id.clone());
+                                self.text_reader.set_active_anchor(Some(anchor_id));
+                            } else {
+                                self.text_reader.set_active_anchor(None);
+                            }
+
+                            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+                            self.navigation_panel
+                                .table_of_contents
+                                .clear_manual_navigation();
+                            self.update_toc_state();
+                        } else {
+                            error!("Could not find spine index for href: {href}");
+                            self.show_error(i18n::tr("notify.chapter_not_found"));
+                        }
+                    }
+                    TocItem::Section { href, anchor, .. } => {
+                        if let Some(section_href) = href {
+                            // This section has content - navigate to it
+                            if let Some(spine_index) = self.find_spine_index_by_href(&section_href)
+                            {
+                                let _ = self.navigate_to_chapter(spine_index);
+                                // Handle anchor if present
+                                if let Some(anchor_id) = anchor {
+                                    self.text_reader
+                                        .store_pending_anchor_scroll(anchor_id.clone());
+                                    // Set this anchor as the active one
+                                    self.text_reader.set_active_anchor(Some(anchor_id));
+                                } else {
+                                    self.text_reader.set_active_anchor(None);
+                                }
+                                self.focused_panel = FocusedPanel::Main(MainPanel::Content);
+                                // Update the cache to reflect the correct active section
+                                self.update_toc_state();
+                            } else {
+                                error!(
+                                    "Could not find spine index for section href: {section_href}"
+                                );
+                                self.show_error(i18n::tr("notify.section_not_found"));
+                            }
+                        } else {
+                            // No href - just toggle expansion
+                            self.navigation_panel
+                                .table_of_contents
+                                .toggle_selected_expansion();
+                        }
+                    }
+                }
+            }
+            SelectedActionOwned::None => {
+                // Nothing selected
+            }
+        }
+    }
+
+    pub fn draw(&mut self, f: &mut ratatui::Frame, fps_counter: &FPSCounter) {
+        let auto_scroll_updated = self.text_reader.update_auto_scroll();
+        if auto_scroll_updated {
+            self.save_bookmark();
+        }
+
+        self.terminal_size = f.area();
+
+        let background_block = Block::default().style(Style::default().bg(current_theme().base_00));
+        f.render_widget(background_block, f.area());
 
-        let text_color = current_theme().base_03;
-        let right_content = Line::from(vec![
-            Span::raw("["),
-            Span::styled(
-                "Space+a: Comments",
+        let min_size = settings::get_min_terminal_size();
+        if f.area().width < min_size.0 || f.area().height < min_size.1 {
+            // Keys keep working; the layout comes back once the terminal grows
+            self.render_terminal_too_small(f, min_size);
+            self.help_bar_items.clear();
+            return;
+        }
+
+        let book_progress = self.book_progress();
+        self.text_reader.set_book_progress(book_progress);
+
+        // Focus moved in auto mode: keep the reading position across the reflow
+        let nav_panel_collapsed = self.is_nav_panel_collapsed();
+        if nav_panel_collapsed != self.nav_panel_collapsed {
+            self.nav_panel_collapsed = nav_panel_collapsed;
+            let current_node = self.text_reader.get_current_node_index();
+            self.text_reader.restore_to_node_index(current_node);
+        }
+
+        if self.shows_full_screen_nav_panel() {
+            self.navigation_panel.render(
+                f,
+                f.area(),
+                self.is_main_panel(MainPanel::NavigationList),
+                current_theme(),
+                &self.book_manager,
+            );
+            self.help_bar_items.clear();
+        } else if self.zen_mode || self.is_compact_terminal() {
+            // Zen mode: full screen content, no navigation panel or help bar
+            if let Some(ref book) = self.current_book {
+                self.text_reader.render(
+                    f,
+                    f.area(),
+                    book.current_chapter(),
+                    book.total_chapters(),
+                    &current_theme(),
+                    true, // always focused in zen mode
+                );
+            } else {
+                self.render_default_content(f, f.area(), i18n::tr("content.empty"));
+            }
+            // The help bar is hidden in zen mode
+            self.help_bar_items.clear();
+        } else {
+            // Normal mode: existing layout
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(f.area());
+
+            let main_chunks = self.split_main_area(chunks[0]);
+
+            if nav_panel_collapsed {
+                self.render_collapsed_nav_panel(f, main_chunks[0]);
+            } else {
+                self.navigation_panel.render(
+                    f,
+                    main_chunks[0],
+                    self.is_main_panel(MainPanel::NavigationList),
+                    &current_theme(),
+                    &self.book_manager,
+                );
+            }
+
+            if let Some(ref book) = self.current_book {
+                self.text_reader.render(
+                    f,
+                    main_chunks[1],
+                    book.current_chapter(),
+                    book.total_chapters(),
+                    &current_theme(),
+                    self.is_main_panel(MainPanel::Content),
+                );
+            } else {
+                self.render_default_content(f, main_chunks[1], i18n::tr("content.empty"));
+            }
+
+            self.help_bar_items = self.render_help_bar(f, chunks[1], fps_counter);
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingHistory)
+        ) {
+            // First render a dimming overlay
+            let dim_block = Block::default().style(
                 Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "Space+h: History",
+                    .bg(Color::Rgb(10, 10, 10)) // Very dark but not black
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut history) = self.reading_history {
+                history.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingThreads)
+        ) {
+            let dim_block = Block::default().style(
                 Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "Space+d: Stats",
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut threads) = self.reading_threads {
+                threads.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::CommentStorage)
+        ) {
+            let dim_block = Block::default().style(
                 Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "Space+t: Theme",
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut storage) = self.comment_storage {
+                storage.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::RareWords)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut rare_words) = self.rare_words {
+                rare_words.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ResurfacedComment)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.resurfaced_comment {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingStats)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut reading_stats) = self.reading_stats {
+                reading_stats.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookInfo)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut popup) = self.book_info {
+                popup.render(f, f.area());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::HelpBarMenu)
+        ) {
+            let anchor = self
+                .help_bar_items
+                .iter()
+                .find(|(item, _)| *item == HelpBarItem::Menu)
+                .map(|(_, rect)| *rect);
+            if let Some(ref mut menu) = self.help_bar_menu {
+                menu.render(f, f.area(), anchor);
+            }
+        }
+
+        if let Some(ref mut image_popup) = self.image_popup {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10)) // todo: this is not from pallette
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            image_popup.render(f, f.area());
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookSearch)
+        ) {
+            let dim_block = Block::default().style(
                 Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("] "),
-            Span::raw("["),
-            Span::styled(
-                "?: Help",
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut book_search) = self.book_search {
+                book_search.render(f, f.area(), &current_theme());
+            }
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookStats)
+        ) {
+            let dim_block = Block::default().style(
                 Style::default()
-                    .fg(text_color)
-                    .add_modifier(Modifier::UNDERLINED),
-            ),
-            Span::raw("]"),
-        ]);
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
 
-        let right_para = Paragraph::new(right_content)
-            .alignment(Alignment::Right)
-            .style(Style::default().bg(current_theme().base_00));
-        f.render_widget(right_para, inner_area);
-    }
+            self.book_stat.render(f, f.area());
+        }
 
-    /// Check if a key is a global hotkey that should work regardless of focus
-    /// Returns true if the key was handled as a global hotkey
-    fn handle_global_hotkeys(&mut self, key: crossterm::event::KeyEvent) -> bool {
-        use crossterm::event::KeyCode;
+        if matches!(self.focused_panel, FocusedPanel::Popup(PopupWindow::Help)) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
 
-        match key.code {
-            KeyCode::Char('?') => {
-                // Save current main panel before opening help
-                if let FocusedPanel::Main(panel) = self.focused_panel {
-                    self.previous_main_panel = panel;
-                }
-                self.help_popup = Some(HelpPopup::new());
-                self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
-                true
-            }
-            KeyCode::Char(' ') => {
-                self.key_sequence.handle_key(' ');
-                true
-            }
-            KeyCode::Char(c) if self.key_sequence.current_sequence() == " " => {
-                // Handle space + key combinations (global across all panels)
-                self.handle_key_sequence(c)
+            if let Some(ref mut help_popup) = self.help_popup {
+                help_popup.render(f, f.area());
             }
-            _ => false,
         }
-    }
 
-    /// Handle a key sequence and return true if it was handled
-    fn handle_key_sequence(&mut self, key_char: char) -> bool {
-        let sequence: String = self.key_sequence.handle_key(key_char);
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::CommentsViewer)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
 
-        match sequence.as_str() {
-            "gg" => {
-                // Handle 'gg' motion - go to top
-                self.text_reader.handle_gg();
-                self.save_bookmark();
-                self.key_sequence.clear();
-                true
-            }
-            " s" => {
-                // Handle Space->s to show raw HTML source
-                if self.is_main_panel(MainPanel::Content) && self.current_book.is_some() {
-                    // Get raw HTML content for current chapter
-                    if let Some(ref mut book) = self.current_book {
-                        if let Some((raw_html, _)) = book.epub.get_current_str() {
-                            self.text_reader.set_raw_html(raw_html);
-                            self.text_reader.toggle_raw_html();
-                        }
-                    }
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " f" => {
-                // Handle Space->f to open book search (reuse existing search)
-                if self.current_book.is_some() {
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
-                    }
-                    self.open_book_search(false); // Don't clear input
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " F" => {
-                // Handle Space->F to open book search (clear input)
-                if self.current_book.is_some() {
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
-                    }
-                    self.open_book_search(true); // Clear input for new search
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " d" => {
-                if self.current_book.is_some() {
-                    if let Some(ref mut book) = self.current_book {
-                        let terminal_size = (self.terminal_size.width, self.terminal_size.height);
-                        if let Err(e) = self
-                            .book_stat
-                            .calculate_stats(&mut book.epub, terminal_size)
-                        {
-                            error!("Failed to calculate book statistics: {e}");
-                            self.show_error(format!("Failed to calculate statistics: {e}"));
-                        } else {
-                            if let FocusedPanel::Main(panel) = self.focused_panel {
-                                self.previous_main_panel = panel;
-                            }
-                            self.book_stat.show();
-                            self.focused_panel = FocusedPanel::Popup(PopupWindow::BookStats);
-                        }
-                    }
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " z" => {
-                // Handle Space->z to copy raw_text_lines for debugging
-                if self.is_main_panel(MainPanel::Content) {
-                    if let Err(e) = self.text_reader.copy_raw_text_lines_to_clipboard() {
-                        debug!("Copy raw_text_lines failed: {e}");
-                    } else {
-                        debug!("Successfully copied raw_text_lines to clipboard for debugging");
-                    }
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " c" => {
-                // Handle Space->c to copy entire chapter content
-                if self.is_main_panel(MainPanel::Content) {
-                    if let Err(e) = self.text_reader.copy_chapter_to_clipboard() {
-                        debug!("Copy chapter failed: {e}");
-                    } else {
-                        debug!("Successfully copied chapter content to clipboard");
-                    }
-                }
-                self.key_sequence.clear();
-                true
-            }
-            " o" => {
-                // Handle Space->o to open current EPUB with system viewer (global)
-                self.open_with_system_viewer();
-                self.key_sequence.clear();
-                true
+            if let Some(ref mut comments_viewer) = self.comments_viewer {
+                comments_viewer.render(f, f.area());
             }
-            " h" => {
-                // Handle Space->h to toggle reading history (global)
-                if matches!(
-                    self.focused_panel,
-                    FocusedPanel::Popup(PopupWindow::ReadingHistory)
-                ) {
-                    // Close history - return to previous panel
-                    self.close_popup_to_previous();
-                    self.reading_history = None;
-                } else {
-                    // Open history - save current main panel
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
-                    }
-                    self.reading_history = Some(ReadingHistory::new(&self.bookmarks));
-                    self.focused_panel = FocusedPanel::Popup(PopupWindow::ReadingHistory);
-                }
-                self.key_sequence.clear();
-                true
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ThemeSelector)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut theme_selector) = self.theme_selector {
+                theme_selector.render(f, f.area());
             }
-            " a" => {
-                // Handle Space->a to toggle comments viewer (global)
-                if matches!(
-                    self.focused_panel,
-                    FocusedPanel::Popup(PopupWindow::CommentsViewer)
-                ) {
-                    // Close comments viewer - return to previous panel
-                    if let Some(ref mut viewer) = self.comments_viewer {
-                        viewer.save_position();
-                    }
-                    self.close_popup_to_previous();
-                    self.comments_viewer = None;
-                } else if self.current_book.is_some() {
-                    // Open comments viewer - save current main panel
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
-                    }
-                    if let Some(ref mut book) = self.current_book {
-                        let toc_items = self.navigation_panel.get_toc_items();
-                        let current_chapter_href =
-                            Self::get_chapter_href(&book.epub, book.current_chapter());
-                        let mut viewer = crate::widget::comments_viewer::CommentsViewer::new(
-                            self.text_reader.get_comments(),
-                            &mut book.epub,
-                            &toc_items,
-                            current_chapter_href,
-                        );
-                        viewer.restore_position();
-                        self.comments_viewer = Some(viewer);
-                        self.focused_panel = FocusedPanel::Popup(PopupWindow::CommentsViewer);
-                    }
-                }
-                self.key_sequence.clear();
-                true
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ChapterPicker)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut chapter_picker) = self.chapter_picker {
+                chapter_picker.render(f, f.area());
             }
-            " t" => {
-                // Handle Space->t to toggle theme selector (global)
-                if matches!(
-                    self.focused_panel,
-                    FocusedPanel::Popup(PopupWindow::ThemeSelector)
-                ) {
-                    // Close theme selector - return to previous panel
-                    self.close_popup_to_previous();
-                    self.theme_selector = None;
-                } else {
-                    // Open theme selector - save current main panel
-                    if let FocusedPanel::Main(panel) = self.focused_panel {
-                        self.previous_main_panel = panel;
-                    }
-                    self.theme_selector = Some(ThemeSelector::new());
-                    self.focused_panel = FocusedPanel::Popup(PopupWindow::ThemeSelector);
-                }
-                self.key_sequence.clear();
-                true
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::CommandPalette)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut command_palette) = self.command_palette {
+                command_palette.render(f, f.area());
             }
-            _ if sequence.len() >= 2 => {
-                // Unknown sequence of 2+ chars, reset
-                self.key_sequence.clear();
-                false
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::BookSwitcher)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut switcher) = self.book_switcher {
+                switcher.render(f, f.area());
             }
-            _ => {
-                // Still building the sequence
-                false
+        }
+
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::FigureList)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref mut figure_list) = self.figure_list {
+                figure_list.render(f, f.area());
             }
         }
-    }
 
-    /// Handle a single key event with optional screen height for half-screen scrolling
-    pub fn handle_key_event_with_screen_height(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        screen_height: Option<usize>,
-    ) -> Option<AppAction> {
-        use crossterm::event::{KeyCode, KeyModifiers};
+        if matches!(
+            self.focused_panel,
+            FocusedPanel::Popup(PopupWindow::Onboarding)
+        ) {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
 
-        // If comment input is active, route all input to the text area
-        if self.text_reader.is_comment_input_active() {
-            if let Some(input) = map_keys_to_input(key) {
-                if self.text_reader.handle_comment_input(input) {
-                    return None;
-                }
+            if let Some(ref mut onboarding) = self.onboarding {
+                onboarding.render(f, f.area());
             }
         }
 
-        // If image popup is shown, close it on any key press
         if matches!(
             self.focused_panel,
-            FocusedPanel::Popup(PopupWindow::ImagePopup)
+            FocusedPanel::Popup(PopupWindow::InstanceLock)
         ) {
-            self.image_popup = None;
-            self.close_popup_to_previous();
-            return None;
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
+
+            if let Some(ref lock_warning) = self.lock_warning {
+                lock_warning.render(f, f.area());
+            }
+        }
+
+        if self.idle && self.idle_dim {
+            let dim_block = Block::default().style(
+                Style::default()
+                    .bg(Color::Rgb(10, 10, 10))
+                    .add_modifier(Modifier::DIM),
+            );
+            f.render_widget(dim_block, f.area());
         }
 
-        // If book search popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::BookSearch) {
-            let action = if let Some(ref mut book_search) = self.book_search {
-                book_search.handle_key_event(key)
-            } else {
-                None
-            };
+        crate::theme::adapt_buffer(f.buffer_mut());
+    }
+
+    fn render_default_content(&self, f: &mut ratatui::Frame, area: Rect, content: &str) {
+        // Use focus-aware colors instead of hardcoded false
+        let (text_color, border_color, _bg_color) =
+            current_theme().get_panel_colors(self.is_main_panel(MainPanel::Content));
+
+        let content_border = Block::default()
+            .borders(Borders::ALL)
+            .title(i18n::tr("content.title"))
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(current_theme().base_00));
+
+        let paragraph = Paragraph::new(content)
+            .block(content_border)
+            .style(Style::default().fg(text_color).bg(current_theme().base_00));
 
-            // Handle the action outside of the borrow
-            if let Some(action) = action {
-                match action {
-                    BookSearchAction::JumpToChapter {
-                        chapter_index,
-                        line_number,
-                    } => {
-                        self.set_main_panel_focus(MainPanel::Content);
-                        if let Err(e) = self.navigate_to_chapter(chapter_index) {
-                            error!("Failed to navigate to chapter {chapter_index}: {e}");
-                            self.show_error(format!("Failed to navigate to chapter: {e}"));
-                        } else {
-                            self.text_reader.scroll_to_line(line_number);
-                        }
-                    }
-                    BookSearchAction::Close => {
-                        self.close_popup_to_previous();
-                    }
-                }
-            }
-            return None;
-        }
+        f.render_widget(paragraph, area);
+    }
 
-        // If book stat popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::BookStats) {
-            match self.book_stat.handle_key(key, &mut self.key_sequence) {
-                Some(BookStatAction::Close) => {
-                    self.book_stat.hide();
+    fn handle_help_bar_click(&mut self, click_x: u16, click_y: u16) -> bool {
+        match help_bar::item_at(&self.help_bar_items, click_x, click_y) {
+            Some(HelpBarItem::Action(action)) => {
+                self.run_help_bar_action(action);
+                true
+            }
+            Some(HelpBarItem::Menu) => {
+                if self.focused_panel == FocusedPanel::Popup(PopupWindow::HelpBarMenu) {
+                    self.help_bar_menu = None;
                     self.close_popup_to_previous();
-                }
-                Some(BookStatAction::JumpToChapter { chapter_index }) => {
-                    self.book_stat.hide();
-                    self.set_main_panel_focus(MainPanel::Content);
-                    if let Err(e) = self.navigate_to_chapter(chapter_index) {
-                        error!("Failed to navigate to chapter {chapter_index}: {e}");
-                        self.show_error(format!("Failed to navigate to chapter: {e}"));
+                } else {
+                    if let FocusedPanel::Main(panel) = self.focused_panel {
+                        self.previous_main_panel = panel;
                     }
+                    self.help_bar_menu = Some(HelpBarMenu::new());
+                    self.focused_panel = FocusedPanel::Popup(PopupWindow::HelpBarMenu);
                 }
-                None => {}
+                true
             }
-            return None;
+            None => false,
         }
+    }
 
-        // If reading history popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::ReadingHistory) {
-            let action = if let Some(ref mut history) = self.reading_history {
-                history.handle_key(key, &mut self.key_sequence)
-            } else {
-                None
-            };
-
-            if let Some(action) = action {
-                use crate::reading_history::ReadingHistoryAction;
-                match action {
-                    ReadingHistoryAction::Close => {
-                        self.close_popup_to_previous();
-                        self.reading_history = None;
-                    }
-                    ReadingHistoryAction::OpenBook { path } => {
-                        if let Some(book_index) = self.book_manager.find_book_index_by_path(&path) {
-                            self.set_main_panel_focus(MainPanel::Content);
-                            self.reading_history = None;
-                            let _ = self.open_book_for_reading(book_index);
-                        }
-                    }
-                }
+    fn handle_help_bar_menu_action(&mut self, action: HelpBarMenuAction) {
+        match action {
+            HelpBarMenuAction::Run(action) => self.run_help_bar_action(action),
+            HelpBarMenuAction::Close => {
+                self.help_bar_menu = None;
+                self.close_popup_to_previous();
             }
-            return None;
         }
+    }
 
-        // If help popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::Help) {
-            let action = if let Some(ref mut help) = self.help_popup {
-                help.handle_key(key, &mut self.key_sequence)
-            } else {
-                None
-            };
+    /// Open the popup behind a help bar button
+    fn run_help_bar_action(&mut self, action: HelpBarAction) {
+        if self.help_bar_menu.take().is_some() {
+            self.close_popup_to_previous();
+        }
+        match action {
+            HelpBarAction::Comments => self.open_comments_viewer(),
+            HelpBarAction::History => self.open_reading_history(),
+            HelpBarAction::Stats => self.open_book_stats(),
+            HelpBarAction::Theme => self.open_theme_selector(),
+            HelpBarAction::Help => self.open_help(),
+        }
+    }
 
-            if let Some(HelpPopupAction::Close) = action {
-                self.close_popup_to_previous();
-                self.help_popup = None;
-            }
-            return None;
+    fn open_comments_viewer(&mut self) {
+        let Some(ref mut book) = self.current_book else {
+            return;
+        };
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
+        let toc_items = self.navigation_panel.get_toc_items();
+        let current_chapter_href = Self::get_chapter_href(&book.epub, book.current_chapter());
+        let mut viewer = crate::widget::comments_viewer::CommentsViewer::new(
+            self.text_reader.get_comments(),
+            &mut book.epub,
+            &toc_items,
+            current_chapter_href,
+        );
+        if let Some(saved) = self.comments_viewer_positions.get(&book.file) {
+            viewer.restore_saved_position(saved.clone());
+        }
+        self.comments_viewer = Some(viewer);
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::CommentsViewer);
+    }
 
-        // If comments viewer popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::CommentsViewer) {
-            let action = if let Some(ref mut viewer) = self.comments_viewer {
-                viewer.handle_key(key, &mut self.key_sequence)
-            } else {
-                None
-            };
+    /// Keep where the comments viewer is, for reopening it there
+    fn remember_comments_viewer_position(&mut self) {
+        let (Some(viewer), Some(book)) = (self.comments_viewer.as_mut(), &self.current_book) else {
+            return;
+        };
+        viewer.save_position();
+        self.comments_viewer_positions
+            .set(&book.file, viewer.saved_position());
+        if let Err(e) = self.comments_viewer_positions.save() {
+            error!("Failed to save comments viewer position: {e}");
+        }
+    }
 
-            if let Some(action) = action {
-                use crate::widget::comments_viewer::CommentsViewerAction;
-                match action {
-                    CommentsViewerAction::Close => {
-                        if let Some(ref mut viewer) = self.comments_viewer {
-                            viewer.save_position();
-                        }
-                        self.close_popup_to_previous();
-                        self.comments_viewer = None;
-                    }
-                    CommentsViewerAction::JumpToComment {
-                        chapter_href,
-                        target,
-                    } => {
-                        if let Some(ref mut viewer) = self.comments_viewer {
-                            viewer.save_position();
-                        }
-                        self.close_popup_to_previous();
-                        self.set_main_panel_focus(MainPanel::Content);
+    fn open_reading_history(&mut self) {
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.reading_history = Some(
+            ReadingHistory::new(&self.bookmarks)
+                .with_streak(self.reading_streak())
+                .with_titles(|path| self.book_metadata.display_name(path)),
+        );
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ReadingHistory);
+    }
 
-                        // Set pending node restore before navigating
-                        self.text_reader.restore_to_node_index(target.node_index());
+    fn open_book_stats(&mut self) {
+        let Some(ref mut book) = self.current_book else {
+            return;
+        };
+        let terminal_size = (self.terminal_size.width, self.terminal_size.height);
+        if let Err(e) =
+            self.book_stat
+                .calculate_stats(&mut book.epub, terminal_size, &self.empty_chapters)
+        {
+            error!("Failed to calculate book statistics: {e}");
+            self.show_error(i18n::tr_args("notify.stats_failed", &[("error", &e)]));
+            return;
+        }
+        let read = self.bookmarks.read_chapters(&book.file).len();
+        self.book_stat
+            .set_chapters_read(read, book.total_chapters());
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.book_stat.show();
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::BookStats);
+    }
 
-                        if let Err(e) = self.navigate_to_chapter_by_href(&chapter_href) {
-                            error!("Failed to navigate to chapter {chapter_href}: {e}");
-                            self.show_error(format!("Failed to navigate to comment: {e}"));
-                        }
-                    }
-                    CommentsViewerAction::DeleteSelectedComment => {
-                        if let Some(entry) = self
-                            .comments_viewer
-                            .as_ref()
-                            .and_then(|v| v.selected_comment().cloned())
-                        {
-                            let mut delete_success = false;
-                            let comments = self.text_reader.get_comments();
-                            match comments.lock() {
-                                Ok(mut guard) => {
-                                    for comment in &entry.comments {
-                                        if let Err(e) = guard
-                                            .delete_comment(&entry.chapter_href, &comment.target)
-                                        {
-                                            error!("Failed to delete comment: {e}");
-                                            self.show_error(format!(
-                                                "Failed to delete comment: {e}"
-                                            ));
-                                            delete_success = false;
-                                            break;
-                                        }
-                                        delete_success = true;
-                                    }
-                                }
-                                Err(_) => {
-                                    error!("Failed to lock comments for deletion");
-                                    self.show_error("Failed to delete comment: lock error");
-                                }
-                            }
+    fn open_theme_selector(&mut self) {
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
+        }
+        self.theme_selector = Some(ThemeSelector::new());
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::ThemeSelector);
+    }
 
-                            if delete_success {
-                                for comment in &entry.comments {
-                                    self.text_reader.delete_comment_by_location(
-                                        &entry.chapter_href,
-                                        &comment.target,
-                                    );
-                                }
-                                if let Some(ref mut viewer) = self.comments_viewer {
-                                    viewer.remove_selected_comment();
-                                }
-                                let msg = if entry.comments.len() > 1 {
-                                    "Comments deleted"
-                                } else {
-                                    "Comment deleted"
-                                };
-                                self.show_info(msg);
-                            }
-                        }
-                    }
-                }
-            }
-            return None;
+    fn open_help(&mut self) {
+        if let FocusedPanel::Main(panel) = self.focused_panel {
+            self.previous_main_panel = panel;
         }
+        self.help_popup = Some(HelpPopup::new());
+        self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
+    }
+
+    fn render_help_bar(
+        &self,
+        f: &mut ratatui::Frame,
+        area: Rect,
+        fps_counter: &FPSCounter,
+    ) -> Vec<(HelpBarItem, Rect)> {
+        use crate::notification::NotificationLevel;
+        let (_, _, border_color, _, _) = current_theme().get_interface_colors(false);
 
-        // If theme selector popup is shown, handle keys for it
-        if self.focused_panel == FocusedPanel::Popup(PopupWindow::ThemeSelector) {
-            let action = if let Some(ref mut selector) = self.theme_selector {
-                selector.handle_key(key, &mut self.key_sequence)
+        let help_content = if self.quit_confirm.is_pending() {
+            i18n::tr("help.quit_confirm").to_string()
+        } else if let Some(line) = self.command_line.text() {
+            format!(":{line}█  {}", i18n::tr("help.command_line"))
+        } else if let Some(notification) = self.notifications.get_current() {
+            let level_str = match notification.level {
+                NotificationLevel::Info => i18n::tr("help.level.info"),
+                NotificationLevel::Warning => i18n::tr("help.level.warning"),
+                NotificationLevel::Error => i18n::tr("help.level.error"),
+            };
+            i18n::tr_args(
+                "help.notification",
+                &[("level", &level_str), ("message", &notification.message)],
+            )
+        } else if self.is_in_search_mode() {
+            let search_state = if self.navigation_panel.is_searching() {
+                self.navigation_panel.get_search_state()
             } else {
-                None
+                self.text_reader.get_search_state()
             };
-
-            if let Some(action) = action {
-                match action {
-                    ThemeSelectorAction::Close => {
-                        self.close_popup_to_previous();
-                        self.theme_selector = None;
-                    }
-                    ThemeSelectorAction::ThemeChanged => {
-                        self.text_reader.invalidate_render_cache();
-                        self.show_info(&format!("Theme: {}", current_theme_name()));
-                        self.close_popup_to_previous();
-                        self.theme_selector = None;
-                    }
+            match search_state.mode {
+                SearchMode::InputMode => {
+                    let query = &search_state.query;
+                    let match_info = if search_state.matches.is_empty() && !query.is_empty() {
+                        i18n::tr("help.no_matches")
+                    } else if !search_state.matches.is_empty() {
+                        &i18n::tr_args("help.matches", &[("count", &search_state.matches.len())])
+                    } else {
+                        ""
+                    };
+                    let hint = i18n::tr("help.search_input");
+                    format!("/ {query}█  {match_info}  {hint}")
+                }
+                SearchMode::NavigationMode => {
+                    let query = &search_state.query;
+                    let match_info = search_state.get_match_info();
+                    let hint = i18n::tr("help.search_navigation");
+                    format!("/{query}  {match_info}  {hint}")
                 }
+                _ => i18n::tr("help.search_active").to_string(),
             }
-            return None;
-        }
+        } else if self.text_reader.has_text_selection() {
+            i18n::tr("help.selection").to_string()
+        } else {
+            help_bar::panel_hint(self.focused_panel).to_string()
+        };
+        let help_content = if instance_lock::is_read_only() {
+            format!("{} {help_content}", i18n::tr("help.read_only"))
+        } else {
+            help_content
+        };
+        let help_content = if self.mouse_capture {
+            help_content
+        } else {
+            format!("{} {help_content}", i18n::tr("help.mouse_off"))
+        };
 
-        if self.is_search_input_mode() {
-            match key.code {
-                KeyCode::Char(c) => self.handle_search_input(c),
-                KeyCode::Backspace => self.handle_search_backspace(),
-                KeyCode::Esc => self.cancel_current_search(),
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(border_color))
+            .style(Style::default().bg(current_theme().base_00));
 
-                KeyCode::Enter => {
-                    // Handle Enter in search mode
-                    if self.navigation_panel.is_searching() {
-                        self.navigation_panel.confirm_search();
-                    } else if self.text_reader.is_searching() {
-                        self.text_reader.confirm_search();
-                    }
-                }
-                _ => {}
-            }
-            return None;
-        }
+        let inner_area = block.inner(area);
+        f.render_widget(block, area);
 
-        // If navigation panel (file list) has focus, handle keys for it
-        if self.is_main_panel(MainPanel::NavigationList) && !self.is_search_input_mode() {
-            // Check for global hotkeys first
-            if self.handle_global_hotkeys(key) {
-                return None;
+        let help_content = match self.reading_goal_indicator() {
+            Some(indicator)
+                if self.notifications.get_current().is_none()
+                    && !self.quit_confirm.is_pending() =>
+            {
+                format!("{help_content} | {indicator}")
             }
+            _ => help_content,
+        };
+        let left_content = if self.is_profiling() {
+            format!("{} | FPS: {}", help_content, fps_counter.current_fps)
+        } else {
+            help_content
+        };
+        let left_para = Paragraph::new(left_content).style(
+            Style::default()
+                .fg(current_theme().base_03)
+                .bg(current_theme().base_00),
+        );
+        f.render_widget(left_para, inner_area);
 
-            let action = self
-                .navigation_panel
-                .handle_key(key, &mut self.key_sequence);
-            let mut bypass = false;
-            if let Some(action) = action {
-                use crate::navigation_panel::NavigationPanelAction;
-                match action {
-                    NavigationPanelAction::Bypass => {
-                        bypass = true;
-                    }
-                    NavigationPanelAction::SelectBook { book_index } => {
-                        if let Err(e) = self.open_book_for_reading(book_index) {
-                            self.show_error(format!("Failed to open book: {e}"));
-                        }
-                    }
-                    NavigationPanelAction::SwitchToBookList => {
-                        self.switch_to_book_list_mode();
-                    }
-                    NavigationPanelAction::NavigateToChapter { href, anchor } => {
-                        if let Some(chapter_index) = self.find_spine_index_by_href(&href) {
-                            let _ = self.navigate_to_chapter(chapter_index);
-                            let nav_area = self.get_navigation_panel_area();
-                            let toc_height = nav_area.height as usize;
-                            let anchor_ref = anchor.as_deref();
-                            self.navigation_panel
-                                .table_of_contents
-                                .set_active_from_hint(&href, anchor_ref, Some(toc_height));
+        let items = help_bar::layout_items(inner_area);
+        help_bar::render_items(f, &items);
+        items
+    }
 
-                            if let Some(anchor_id) = anchor {
-                                self.text_reader.store_pending_anchor_scroll(anchor_id);
-                            }
-                            self.focused_panel = FocusedPanel::Main(MainPanel::Content);
-                        }
-                    }
-                    NavigationPanelAction::ToggleSection => {
-                        self.navigation_panel
-                            .table_of_contents
-                            .toggle_selected_expansion();
-                    }
-                }
-            }
+    /// Handle a single key event with optional screen height for half-screen scrolling
+    pub fn handle_key_event_with_screen_height(
+        &mut self,
+        key: crossterm::event::KeyEvent,
+        screen_height: Option<usize>,
+    ) -> Option<AppAction> {
+        use crossterm::event::{KeyCode, KeyModifiers};
 
-            if key.code == KeyCode::Char('q') {
-                self.save_bookmark_with_throttle(true);
-                return Some(AppAction::Quit);
-            }
+        self.note_user_input();
 
-            if !bypass {
-                return None;
-            }
+        if self.quit_confirm.is_pending() {
+            return match key.code {
+                // Ctrl+C quits without asking here too
+                KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit(),
+                KeyCode::Char(c) if self.quit_confirm.answer(c) => self.quit(),
+                _ => {
+                    self.quit_confirm.cancel();
+                    None
+                }
+            };
         }
 
-        match key.code {
-            KeyCode::Char('/') => {
-                if self.is_main_panel(MainPanel::Content) {
-                    self.text_reader.start_search();
-                }
-            }
-            KeyCode::Char('n') if self.is_in_search_mode() => {
-                if self.navigation_panel.is_searching() {
-                    let search_state = self.navigation_panel.get_search_state();
-                    if search_state.mode == SearchMode::InputMode {
-                        self.handle_search_input('n');
-                    }
-                } else if self.text_reader.is_searching() {
-                    let search_state = self.text_reader.get_search_state();
-                    if search_state.mode == SearchMode::NavigationMode {
-                        self.text_reader.next_match();
-                    } else {
-                        self.handle_search_input('n');
-                    }
-                }
-            }
-            KeyCode::Char('N') if self.is_in_search_mode() => {
-                if self.navigation_panel.is_searching() {
-                    let search_state = self.navigation_panel.get_search_state();
-                    if search_state.mode == SearchMode::InputMode {
-                        self.handle_search_input('N');
-                    }
-                } else if self.text_reader.is_searching() {
-                    let search_state = self.text_reader.get_search_state();
-                    if search_state.mode == SearchMode::NavigationMode {
-                        self.text_reader.previous_match();
-                    } else {
-                        self.handle_search_input('N');
+        // If comment input is active, route all input to the text area
+        if self.text_reader.is_comment_input_active() {
+            if let Some(input) = map_keys_to_input(key) {
+                if self.text_reader.handle_comment_input(input) {
+                    if !self.text_reader.is_comment_input_active() {
+                        // Saved or cancelled
+                        self.refresh_toc_annotation_marks();
                     }
+                    return None;
                 }
             }
-            KeyCode::Char('f') => if self.handle_key_sequence('f') {},
-            KeyCode::Char('F') => if self.handle_key_sequence('F') {},
-            KeyCode::Char('s') => if self.handle_key_sequence('s') {},
-            KeyCode::Char(' ') => if !self.handle_key_sequence(' ') {},
-            KeyCode::Char('g') => if !self.handle_key_sequence('g') {},
+        }
 
-            KeyCode::Char('d') if !key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if self.handle_key_sequence('d') {
-                } else if !self.text_reader.is_comment_input_active() {
-                    match self.text_reader.delete_comment_at_cursor() {
-                        Ok(true) => {
-                            info!("Comment deleted successfully");
-                            self.show_info("Comment deleted");
-                        }
-                        Ok(false) => {
-                            // Cursor not on a comment, ignore
-                        }
-                        Err(e) => {
-                            error!("Failed to delete comment: {e}");
-                            self.show_error(format!("Failed to delete comment: {e}"));
-                        }
-                    }
-                }
-            }
-            KeyCode::Char('j') => {
-                self.scroll_down();
-            }
-            KeyCode::Char('k') => {
-                self.scroll_up();
-            }
-            KeyCode::Char('h') => {
-                if !self.handle_key_sequence('h') {
-                    let _ = self.navigate_chapter_relative(ChapterDirection::Previous);
-                }
-            }
-            KeyCode::Char('l') => {
-                let _ = self.navigate_chapter_relative(ChapterDirection::Next);
-            }
-            KeyCode::Char('i') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.jump_forward();
+        let context = self.key_context();
+        if let Some(command) = commands::command_for_key(&context, key) {
+            return self.execute_command(command, screen_height);
+        }
+        if self.is_main_panel(MainPanel::NavigationList) {
+            // Space followed by a key that makes no sequence: the key alone
+            if context.sequence == " " {
+                self.key_sequence.clear();
             }
-            KeyCode::Char('o') => {
-                if key.modifiers.contains(KeyModifiers::CONTROL) {
-                    self.jump_back();
-                } else if !self.handle_key_sequence('o') {
+            self.handle_navigation_key(key);
+        }
+        None
+    }
+
+    fn handle_lock_warning_action(&mut self, action: LockWarningAction) {
+        self.close_lock_warning();
+        match action {
+            LockWarningAction::ReadOnly => self.show_info(i18n::tr("notify.read_only_mode")),
+            LockWarningAction::TakeOver => self.take_over_instance_lock(),
+        }
+    }
+
+    fn handle_onboarding_action(&mut self, action: OnboardingAction) {
+        self.onboarding = None;
+        self.close_popup_to_previous();
+        if let OnboardingAction::UseDirectory(directory) = action {
+            self.use_books_directory(&directory);
+        }
+    }
+
+    fn handle_book_search_action(&mut self, action: BookSearchAction) {
+        match action {
+            BookSearchAction::JumpToChapter {
+                chapter_index,
+                line_number,
+                target,
+            } => {
+                self.set_main_panel_focus(MainPanel::Content);
+                if let Err(e) = self.navigate_to_chapter(chapter_index) {
+                    error!("Failed to navigate to chapter {chapter_index}: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.navigate_chapter_failed",
+                        &[("error", &e)],
+                    ));
+                } else if target == MatchTarget::Text {
+                    self.text_reader.scroll_to_line(line_number);
+                } else {
+                    self.text_reader.store_pending_match_scroll(target);
                 }
             }
-            KeyCode::Char('p') => {
-                self.toggle_profiling();
+            BookSearchAction::Searched(query) => {
+                self.record_search_query(SearchScope::Book, &query);
             }
-            KeyCode::Tab => {
-                if !self.has_active_popup() {
-                    match self.focused_panel {
-                        FocusedPanel::Main(MainPanel::NavigationList) => {
-                            self.navigation_panel
-                                .table_of_contents
-                                .clear_manual_navigation();
-                            self.set_main_panel_focus(MainPanel::Content);
-                        }
-                        FocusedPanel::Main(MainPanel::Content) => {
-                            self.set_main_panel_focus(MainPanel::NavigationList);
-                        }
-                        FocusedPanel::Popup(_) => {} // No tab switching in popups
-                    };
-                }
+            BookSearchAction::Close => {
+                self.close_popup_to_previous();
             }
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(visible_height) = screen_height {
-                    self.scroll_half_screen_down(visible_height);
+        }
+    }
+
+    fn handle_book_stat_action(&mut self, action: BookStatAction) {
+        match action {
+            BookStatAction::Close => {
+                self.book_stat.hide();
+                self.close_popup_to_previous();
+            }
+            BookStatAction::JumpToChapter { chapter_index } => {
+                self.book_stat.hide();
+                self.set_main_panel_focus(MainPanel::Content);
+                if let Err(e) = self.navigate_to_chapter(chapter_index) {
+                    error!("Failed to navigate to chapter {chapter_index}: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.navigate_chapter_failed",
+                        &[("error", &e)],
+                    ));
                 }
             }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if let Some(visible_height) = screen_height {
-                    self.scroll_half_screen_up(visible_height);
+        }
+    }
+
+    fn handle_reading_history_action(&mut self, action: ReadingHistoryAction) {
+        match action {
+            ReadingHistoryAction::Close => {
+                self.close_popup_to_previous();
+                self.reading_history = None;
+            }
+            ReadingHistoryAction::RemoveBooks { paths } => {
+                for path in &paths {
+                    self.bookmarks.remove_bookmark(path);
+                }
+                self.resume_stack = None;
+                match paths.as_slice() {
+                    [_] => self.show_info(i18n::tr("notify.history_removed")),
+                    _ => self.show_info(i18n::tr_args(
+                        "notify.history_removed_many",
+                        &[("count", &paths.len())],
+                    )),
                 }
             }
-            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                // Save current content position before toggling zen mode
-                let current_node = self.text_reader.get_current_node_index();
-                self.zen_mode = !self.zen_mode;
-                // Restore position after width change causes re-render
-                self.text_reader.restore_to_node_index(current_node);
-                // When entering zen mode while on NavigationList, switch to Content
-                if self.zen_mode
-                    && self.is_main_panel(MainPanel::NavigationList)
-                    && self.current_book.is_some()
-                {
+            ReadingHistoryAction::OpenBook { path } => {
+                if let Some(book_index) = self.book_manager.find_book_index_by_path(&path) {
                     self.set_main_panel_focus(MainPanel::Content);
+                    self.reading_history = None;
+                    let _ = self.open_book_for_reading(book_index);
                 }
             }
+        }
+    }
 
-            KeyCode::Char('G') => {
-                if self.current_book.is_some() {
-                    self.text_reader.handle_upper_g();
-                }
+    fn handle_comments_viewer_action(&mut self, action: CommentsViewerAction) {
+        match action {
+            CommentsViewerAction::Close => {
+                self.remember_comments_viewer_position();
+                self.close_popup_to_previous();
+                self.comments_viewer = None;
             }
-            KeyCode::Char('a') => {
-                if !self.handle_key_sequence('a')
-                    && self.text_reader.has_text_selection()
-                    && self.text_reader.start_comment_input()
-                {
-                    debug!("Started comment input mode");
+            CommentsViewerAction::JumpToComment {
+                chapter_href,
+                target,
+            } => {
+                self.remember_comments_viewer_position();
+                self.close_popup_to_previous();
+                self.set_main_panel_focus(MainPanel::Content);
+
+                // Set pending node restore before navigating
+                self.text_reader.restore_to_node_index(target.node_index());
+
+                if let Err(e) = self.navigate_to_chapter_by_href(&chapter_href) {
+                    error!("Failed to navigate to chapter {chapter_href}: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.navigate_comment_failed",
+                        &[("error", &e)],
+                    ));
                 }
             }
-            KeyCode::Char('c') => {
-                if !self.handle_key_sequence('c') {
-                    if let Err(e) = self.text_reader.copy_selection_to_clipboard() {
-                        error!("Copy failed: {e}");
+            CommentsViewerAction::DeleteSelectedComment => self.delete_selected_viewer_comment(),
+        }
+    }
+
+    /// Delete the comments viewer's selected entry, every comment of it
+    fn delete_selected_viewer_comment(&mut self) {
+        let Some(entry) = self
+            .comments_viewer
+            .as_ref()
+            .and_then(|v| v.selected_comment().cloned())
+        else {
+            return;
+        };
+        let mut delete_success = false;
+        let comments = self.text_reader.get_comments();
+        match comments.lock() {
+            Ok(mut guard) => {
+                for comment in &entry.comments {
+                    if let Err(e) = guard.delete_comment(&entry.chapter_href, &comment.target) {
+                        error!("Failed to delete comment: {e}");
+                        self.show_error(i18n::tr_args(
+                            "notify.delete_comment_failed",
+                            &[("error", &e)],
+                        ));
+                        delete_success = false;
+                        break;
                     }
+                    delete_success = true;
                 }
             }
-            KeyCode::Char('t') => {
-                self.handle_key_sequence('t');
+            Err(_) => {
+                error!("Failed to lock comments for deletion");
+                self.show_error(i18n::tr("notify.delete_comment_lock"));
             }
-            KeyCode::Char('?') => {
-                self.help_popup = Some(HelpPopup::new());
-                self.focused_panel = FocusedPanel::Popup(PopupWindow::Help);
+        }
+
+        if delete_success {
+            for comment in &entry.comments {
+                self.text_reader
+                    .delete_comment_by_location(&entry.chapter_href, &comment.target);
             }
-            KeyCode::Char('q') => {
-                self.save_bookmark_with_throttle(true);
-                return Some(AppAction::Quit);
+            if let Some(ref mut viewer) = self.comments_viewer {
+                viewer.remove_selected_comment();
             }
-            KeyCode::Esc => {
-                if self.notifications.has_notification() {
-                    self.notifications.dismiss();
-                } else if self.text_reader.has_text_selection() {
-                    self.text_reader.clear_selection();
-                } else if self.is_in_search_mode() {
-                    self.cancel_current_search();
-                }
+            self.refresh_toc_annotation_marks();
+            let msg = i18n::tr(if entry.comments.len() > 1 {
+                "notify.comments_deleted"
+            } else {
+                "notify.comment_deleted"
+            });
+            self.show_info(msg);
+        }
+    }
+
+    fn handle_theme_selector_action(&mut self, action: ThemeSelectorAction) {
+        match action {
+            ThemeSelectorAction::Close => {
+                self.close_popup_to_previous();
+                self.theme_selector = None;
             }
-            KeyCode::Char('=') | KeyCode::Char('+') => {
-                let current_node = self.text_reader.get_current_node_index();
-                self.text_reader.increase_margin();
-                self.text_reader.restore_to_node_index(current_node);
-                settings::set_margin(self.text_reader.get_margin());
+            ThemeSelectorAction::ThemeChanged => {
+                // A theme picked by hand stops following the system
+                self.system_appearance = None;
+                self.text_reader.invalidate_render_cache();
+                self.show_info(i18n::tr_args(
+                    "notify.theme",
+                    &[("name", &current_theme_name())],
+                ));
+                self.close_popup_to_previous();
+                self.theme_selector = None;
             }
-            KeyCode::Char('-') => {
-                let current_node = self.text_reader.get_current_node_index();
-                self.text_reader.decrease_margin();
-                self.text_reader.restore_to_node_index(current_node);
-                settings::set_margin(self.text_reader.get_margin());
+        }
+    }
+
+    /// Keys for the navigation list: moving through it and opening entries
+    fn handle_navigation_key(&mut self, key: crossterm::event::KeyEvent) {
+        let action = self
+            .navigation_panel
+            .handle_key(key, &mut self.key_sequence);
+        if let Some(action) = action {
+            use crate::navigation_panel::NavigationPanelAction;
+            match action {
+                NavigationPanelAction::SelectBook { book_index } => {
+                    if let Err(e) = self.open_book_for_reading(book_index) {
+                        self.show_error(i18n::tr_args("notify.open_book_failed", &[("error", &e)]));
+                    }
+                }
+                NavigationPanelAction::SwitchToBookList => {
+                    self.switch_to_book_list_mode();
+                }
+                NavigationPanelAction::NavigateToChapter { href, anchor } => {
+                    self.navigate_to_toc_entry(&href, anchor);
+                }
+                NavigationPanelAction::ToggleSection => {
+                    self.navigation_panel
+                        .table_of_contents
+                        .toggle_selected_expansion();
+                }
+                NavigationPanelAction::ToggleChapterRead { href } => {
+                    if let Some(chapter_index) = self.find_spine_index_by_href(&href) {
+                        self.toggle_chapter_read(chapter_index);
+                    }
+                }
+                NavigationPanelAction::TogglePinned { book_index } => {
+                    self.toggle_pinned(book_index);
+                }
             }
-            _ => {}
         }
-        None
     }
 
     pub fn handle_resize(&mut self) {
@@ -2890,84 +5521,147 @@ impl App {
     }
 
     //todo this does extra parsing of a book. damn claude is dumb
+    /// Start the book's search index with the chapters within
+    /// `prefetch_radius` of the current one; `index_chapters` adds the rest
     fn initialize_search_engine(&mut self, doc: &mut EpubDoc<BufReader<std::fs::File>>) {
-        fn extract_text_from_markdown_doc(doc: &crate::markdown::Document) -> String {
-            let mut lines = Vec::new();
+        let total = doc.get_num_chapters();
+        self.chapter_word_counts = vec![None; total];
+        self.empty_chapters = vec![false; total];
+
+        let mut book_search = BookSearch::new(SearchEngine::new());
+        book_search.set_history(self.search_history.queries(SearchScope::Book));
+        self.book_search = Some(book_search);
+
+        let radius = settings::get_prefetch_radius();
+        self.index_chapters(
+            doc,
+            prefetch_window(radius, doc.get_current_chapter(), total),
+        );
+    }
+
+    /// Index the chapters not indexed yet among `chapters`. Those within
+    /// `prefetch_radius` of the current chapter stay converted for the reader.
+    fn index_chapters(
+        &mut self,
+        doc: &mut EpubDoc<BufReader<std::fs::File>>,
+        chapters: std::ops::Range<usize>,
+    ) {
+        /// Paragraphs, image alt texts and table cells of a chapter, as
+        /// separate search units in reading order
+        fn extract_units_from_markdown_doc(doc: &crate::markdown::Document) -> Vec<SearchUnit> {
+            let mut units = Vec::new();
+            let mut tables = 0;
+            let mut collapsibles = 0;
             for node in &doc.blocks {
-                extract_text_from_block(&node.block, &mut lines);
+                let crate::markdown::Block::Collapsible { summary, content } = &node.block else {
+                    extract_units_from_block(&node.block, &mut units, &mut tables);
+                    continue;
+                };
+                push_text_units(summary, &mut units);
+                // Hidden content points at its block instead; its tables are
+                // not counted, as the reader leaves them out of the table order
+                let mut hidden = Vec::new();
+                for inner in content {
+                    extract_units_from_block(&inner.block, &mut hidden, &mut 0);
+                }
+                units.extend(hidden.into_iter().map(|unit| SearchUnit {
+                    target: MatchTarget::Collapsible {
+                        index: collapsibles,
+                        kind: unit.target.kind(),
+                    },
+                    text: unit.text,
+                }));
+                collapsibles += 1;
             }
-            lines.join("\n")
+            units
         }
 
-        fn extract_text_from_block(block: &crate::markdown::Block, lines: &mut Vec<String>) {
+        fn extract_units_from_block(
+            block: &crate::markdown::Block,
+            units: &mut Vec<SearchUnit>,
+            tables: &mut usize,
+        ) {
             use crate::markdown::Block;
 
             match block {
                 Block::Paragraph { content } | Block::Heading { content, .. } => {
-                    let plain_text = extract_text_from_text(content);
-                    if !plain_text.trim().is_empty() {
-                        lines.push(plain_text);
-                    }
+                    push_text_units(content, units);
                 }
                 Block::List { items, .. } => {
                     for item in items {
                         // ListItem content is Vec<Node>, so process each node
                         for node in &item.content {
-                            extract_text_from_block(&node.block, lines);
+                            extract_units_from_block(&node.block, units, tables);
                         }
                     }
                 }
-                Block::Quote { content } => {
+                Block::Quote {
+                    content,
+                    attribution,
+                    ..
+                } => {
                     for node in content {
-                        extract_text_from_block(&node.block, lines);
+                        extract_units_from_block(&node.block, units, tables);
+                    }
+                    if let Some(attribution) = attribution {
+                        push_text_units(attribution, units);
                     }
                 }
                 Block::CodeBlock { content, .. } => {
-                    lines.push(content.clone());
+                    units.extend(content.lines().map(SearchUnit::text));
                 }
                 Block::Table { rows, header, .. } => {
-                    if let Some(header_row) = header {
-                        let row_text: Vec<String> = header_row
-                            .cells
-                            .iter()
-                            .map(|cell| extract_text_from_text(&cell.content))
-                            .collect();
-                        if !row_text.is_empty() {
-                            lines.push(row_text.join(" "));
-                        }
-                    }
-                    for row in rows {
-                        let row_text: Vec<String> = row
-                            .cells
-                            .iter()
-                            .map(|cell| extract_text_from_text(&cell.content))
-                            .collect();
-                        if !row_text.is_empty() {
-                            lines.push(row_text.join(" "));
+                    let index = *tables;
+                    *tables += 1;
+                    for row in header.iter().chain(rows) {
+                        for cell in &row.cells {
+                            let mut figures = Vec::new();
+                            let text = extract_text_from_text(&cell.content, &mut figures);
+                            if !text.trim().is_empty() {
+                                units.push(SearchUnit {
+                                    text,
+                                    target: MatchTarget::Table { index },
+                                });
+                            }
+                            units.extend(figures);
                         }
                     }
                 }
                 Block::DefinitionList { items } => {
                     for item in items {
-                        lines.push(extract_text_from_text(&item.term));
+                        push_text_units(&item.term, units);
                         // Process each definition (Vec<Vec<Node>>)
                         for definition in &item.definitions {
                             for node in definition {
-                                extract_text_from_block(&node.block, lines);
+                                extract_units_from_block(&node.block, units, tables);
                             }
                         }
                     }
                 }
                 Block::EpubBlock { content, .. } => {
                     for node in content {
-                        extract_text_from_block(&node.block, lines);
+                        extract_units_from_block(&node.block, units, tables);
                     }
                 }
                 _ => {}
             }
         }
 
-        fn extract_text_from_text(text: &crate::markdown::Text) -> String {
+        /// The text as one unit, followed by the alt texts of its images
+        fn push_text_units(text: &crate::markdown::Text, units: &mut Vec<SearchUnit>) {
+            let mut figures = Vec::new();
+            let plain_text = extract_text_from_text(text, &mut figures);
+            if !plain_text.trim().is_empty() {
+                units.push(SearchUnit::text(plain_text));
+            }
+            units.extend(figures);
+        }
+
+        /// Plain text without image alt texts, which go to `figures`
+        fn extract_text_from_text(
+            text: &crate::markdown::Text,
+            figures: &mut Vec<SearchUnit>,
+        ) -> String {
             let mut result = String::new();
 
             for part in text.iter() {
@@ -2977,10 +5671,18 @@ impl App {
                     }
                     crate::markdown::TextOrInline::Inline(inline) => match inline {
                         crate::markdown::Inline::Link { text, .. } => {
-                            result.push_str(&extract_text_from_text(text));
+                            result.push_str(&extract_text_from_text(text, figures));
+                        }
+                        crate::markdown::Inline::Image { alt_text, url, .. }
+                            if !alt_text.trim().is_empty() =>
+                        {
+                            figures.push(SearchUnit {
+                                text: alt_text.trim().to_string(),
+                                target: MatchTarget::Figure { src: url.clone() },
+                            });
                         }
-                        crate::markdown::Inline::Image { alt_text, .. } => {
-                            result.push_str(alt_text);
+                        crate::markdown::Inline::Ruby { base, .. } => {
+                            result.push_str(base);
                         }
                         crate::markdown::Inline::LineBreak => {
                             result.push(' ');
@@ -2993,32 +5695,144 @@ impl App {
             result
         }
 
-        let mut search_engine = SearchEngine::new();
-        let mut chapters = Vec::new();
-        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
-        let mut converter = HtmlToMarkdownConverter::new();
+        let Some(book_search) = self.book_search.as_mut() else {
+            return;
+        };
+        let prefetched = prefetch_window(
+            settings::get_prefetch_radius(),
+            doc.get_current_chapter(),
+            doc.get_num_chapters(),
+        );
+        let min_words = settings::get_empty_chapter_words();
+        let mut indexed = Vec::new();
 
-        // Process all chapters to extract readable text
-        for chapter_index in 0..doc.get_num_chapters() {
-            if doc.set_current_chapter(chapter_index) {
-                if let Some((raw_html, _mime)) = doc.get_current_str() {
-                    let title = TextGenerator::extract_chapter_title(&raw_html)
-                        .unwrap_or_else(|| format!("Chapter {}", chapter_index + 1));
+        for chapter_index in chapters {
+            if book_search.search_engine_mut().is_indexed(chapter_index) {
+                continue;
+            }
+            let raw_html = doc
+                .spine
+                .get(chapter_index)
+                .map(|item| item.idref.clone())
+                .and_then(|idref| doc.get_resource_str(&idref))
+                .map(|(content, _mime)| content);
+            let Some(raw_html) = raw_html else {
+                // Chapters without readable text at all count as empty
+                if let Some(empty) = self.empty_chapters.get_mut(chapter_index) {
+                    *empty = true;
+                }
+                indexed.push((chapter_index, String::new(), Vec::new()));
+                continue;
+            };
+            let title = TextGenerator::extract_chapter_title(&raw_html)
+                .unwrap_or_else(|| format!("Chapter {}", chapter_index + 1));
 
-                    let markdown_doc = converter.convert(&raw_html);
+            let markdown_doc = self.text_reader.chapter_document(
+                chapter_index,
+                &raw_html,
+                prefetched.contains(&chapter_index),
+            );
+            let units = extract_units_from_markdown_doc(&markdown_doc);
 
-                    let clean_text = extract_text_from_markdown_doc(&markdown_doc);
-                    chapters.push((chapter_index, title, clean_text));
-                }
+            let text: Vec<&str> = units.iter().map(|unit| unit.text.as_str()).collect();
+            let text = text.join("\n");
+            if let Some(count) = self.chapter_word_counts.get_mut(chapter_index) {
+                *count = Some(text.split_whitespace().count());
             }
+            if let Some(empty) = self.empty_chapters.get_mut(chapter_index) {
+                *empty = is_empty_chapter(&text, min_words);
+            }
+            indexed.push((chapter_index, title, units));
+        }
+
+        book_search.search_engine_mut().add_chapter_units(indexed);
+    }
+
+    /// Index and prefetch the chapters around the current one
+    fn index_around_current_chapter(&mut self) {
+        if let Some(mut book) = self.current_book.take() {
+            let window = prefetch_window(
+                settings::get_prefetch_radius(),
+                book.current_chapter(),
+                book.total_chapters(),
+            );
+            self.index_chapters(&mut book.epub, window);
+            self.current_book = Some(book);
+        }
+    }
+
+    /// Index whatever is left of the book, for a search across all of it
+    fn index_whole_book(&mut self) {
+        if let Some(mut book) = self.current_book.take() {
+            let total = book.total_chapters();
+            self.index_chapters(&mut book.epub, 0..total);
+            self.current_book = Some(book);
+        }
+    }
+
+    /// Remember a searched query and refresh the book search's recall list,
+    /// which also shows chapter queries when the histories are merged
+    fn record_search_query(&mut self, scope: SearchScope, query: &str) {
+        self.search_history.add(scope, query);
+        if let Some(book_search) = self.book_search.as_mut() {
+            book_search.set_history(self.search_history.queries(SearchScope::Book));
         }
+    }
+
+    /// Whole-book progress percent: the words of all preceding chapters plus
+    /// the read part of the current one, over the book's total word count.
+    pub fn book_progress(&self) -> Option<u32> {
+        let book = self.current_book.as_ref()?;
+        // Chapters not indexed yet count as long as the average indexed one
+        let known: Vec<usize> = self.chapter_word_counts.iter().flatten().copied().collect();
+        let average = known.iter().sum::<usize>() / known.len().max(1);
+        let chapter_sizes: Vec<usize> = self
+            .chapter_word_counts
+            .iter()
+            .map(|count| count.unwrap_or(average))
+            .collect();
+        book_progress_percent(
+            &chapter_sizes,
+            book.current_chapter(),
+            self.text_reader.chapter_progress_fraction(),
+        )
+    }
 
-        search_engine.process_chapters(chapters);
+    /// `*` / `#`: search the chapter for the selection or the word last
+    /// clicked. `*` again while that search is on takes it to the whole book.
+    fn search_for_selection(&mut self, forward: bool) {
+        let search = self.text_reader.get_search_state();
+        let repeated = forward
+            && !self.text_reader.has_text_selection()
+            && search.mode == SearchMode::NavigationMode
+            && self.selection_search.as_deref() == Some(search.query.as_str());
+        if repeated {
+            let query = search.query.clone();
+            self.selection_search = None;
+            self.open_book_search(true);
+            if let Some(book_search) = self.book_search.as_mut() {
+                book_search.search_for(&query);
+            }
+            return;
+        }
 
-        self.book_search = Some(BookSearch::new(search_engine));
+        let Some(query) = self.text_reader.search_for_selection(forward) else {
+            self.show_info(i18n::tr("notify.search_needs_selection"));
+            return;
+        };
+        self.record_search_query(SearchScope::Chapter, &query);
+        let count = self.text_reader.get_search_state().matches.len();
+        let key = if count == 1 {
+            "notify.selection_matches.one"
+        } else {
+            "notify.selection_matches.other"
+        };
+        self.show_info(i18n::tr_args(key, &[("count", &count), ("query", &query)]));
+        self.selection_search = Some(query);
     }
 
     fn open_book_search(&mut self, clear_input: bool) {
+        self.index_whole_book();
         if let Some(ref mut book_search) = self.book_search {
             book_search.open(clear_input);
             self.focused_panel = FocusedPanel::Popup(PopupWindow::BookSearch);
@@ -3028,22 +5842,6 @@ impl App {
             );
         }
     }
-
-    #[doc(hidden)]
-    #[allow(dead_code)]
-    pub fn testing_current_chapter_file(&self) -> Option<String> {
-        self.text_reader.get_current_chapter_file().clone()
-    }
-
-    #[doc(hidden)]
-    #[allow(dead_code)]
-    pub fn testing_add_comment(&mut self, comment: crate::comments::Comment) {
-        let comments_arc = self.text_reader.get_comments();
-        if let Ok(mut guard) = comments_arc.lock() {
-            let _ = guard.add_comment(comment.clone());
-        }
-        self.text_reader.rebuild_chapter_comments();
-    }
 }
 
 pub struct FPSCounter {
@@ -3087,6 +5885,7 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
     let mut last_tick = std::time::Instant::now();
     let mut fps_counter = FPSCounter::new();
     let mut first_render = true; // Ensure we always render at least once on startup
+    let mut capture_saved = false; // Redraw to show where a screen capture went
     loop {
         let mut events_processed = 0;
         let mut should_quit = false;
@@ -3101,6 +5900,8 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
                         MouseEventKind::ScrollLeft | MouseEventKind::ScrollRight => {
                             // Completely ignore horizontal scroll events to prevent flooding
                         }
+                        // Left to the terminal; any still queued are dropped
+                        _ if !app.is_mouse_captured() => {}
                         _ => {
                             app.handle_and_drain_mouse_events(mouse_event, Some(event_source));
                         }
@@ -3125,17 +5926,51 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
             }
         }
 
-        let mut needs_redraw = events_processed > 0;
+        // The terminal closed or the app was told to stop; the caller saves
+        if terminal_state::quit_signalled() {
+            info!("Quit signal received, exiting");
+            return Ok(());
+        }
+
+        if let Some(enabled) = app.take_mouse_capture_change()
+            && let Err(e) = event_source.set_mouse_capture(enabled)
+        {
+            error!("Failed to change mouse capture: {e}");
+        }
+
+        let mut resumed = false;
+        if app.take_suspend_request() {
+            if let Err(e) = event_source.suspend(app.is_mouse_captured()) {
+                error!("Failed to suspend: {e}");
+            }
+            // The shell drew over the screen; nothing of the last frame holds
+            terminal.clear()?;
+            app.handle_resume();
+            resumed = true;
+        }
 
-        if first_render {
+        let mut needs_redraw = events_processed > 0 || resumed;
+
+        if first_render || capture_saved {
             needs_redraw = true;
             first_render = false;
+            capture_saved = false;
         }
 
         if last_tick.elapsed() >= tick_rate {
             let highlight_changed = app.text_reader.update_highlight(); // Update highlight state
             let images_loaded = app.text_reader.check_for_loaded_images();
+            let glosses_loaded = app.text_reader.check_for_gloss_targets();
+            let chapter_converting = app.poll_chapter_conversion();
             let notification_expired = app.notifications.update();
+            let goal_changed = app.update_reading_goal();
+            let download_changed = app.poll_book_download();
+            let names_changed = app.poll_metadata_scan();
+            let layout_extended = app.text_reader.continue_layout();
+            let theme_changed = app.poll_system_appearance();
+            let book_reloaded = app.poll_book_file();
+            let anchor_missing = app.poll_missing_anchor();
+            let went_idle = app.poll_idle();
             if images_loaded {
                 needs_redraw = true;
                 debug!("Images loaded, forcing redraw");
@@ -3144,7 +5979,18 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
                 needs_redraw = true;
                 debug!("Highlight expired, forcing redraw");
             }
-            if notification_expired {
+            if notification_expired
+                || goal_changed
+                || download_changed
+                || names_changed
+                || layout_extended
+                || theme_changed
+                || book_reloaded
+                || anchor_missing
+                || went_idle
+                || glosses_loaded
+                || chapter_converting
+            {
                 needs_redraw = true;
             }
             last_tick = std::time::Instant::now();
@@ -3152,8 +5998,13 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
 
         if needs_redraw {
             let draw_start = std::time::Instant::now();
-            terminal.draw(|f| app.draw(f, &fps_counter))?;
+            let frame = terminal.draw(|f| app.draw(f, &fps_counter))?;
             let draw_duration = draw_start.elapsed();
+            if app.take_screen_capture() {
+                let buffer = frame.buffer.clone();
+                app.save_screen_capture(&buffer);
+                capture_saved = true;
+            }
 
             // Log if drawing/flushing took longer than 10ms
             if draw_duration.as_millis() > 10 {
@@ -3169,11 +6020,644 @@ pub fn run_app_with_event_source<B: ratatui::backend::Backend>(
             let _ = event_source.poll(timeout);
         }
 
-        if should_quit {
+        if should_quit || event_source.finished() {
             return Ok(());
         }
     }
 }
 
+/// Frequency list and known words for rare word glossing, or None when
+/// glossing is switched off in the settings
+fn load_glossary(known_words_file: Option<&str>) -> anyhow::Result<Option<Glossary>> {
+    if !settings::get_gloss_rare_words() {
+        return Ok(None);
+    }
+    let Some(list) = settings::get_word_frequency_list() else {
+        anyhow::bail!("no word_frequency_list in the settings file");
+    };
+    let path = crate::widget::onboarding::expand_tilde(&list);
+    let frequencies = WordFrequencies::load(&path)
+        .with_context(|| format!("cannot read word list {}", path.display()))?;
+
+    Ok(Some(Glossary {
+        frequencies: Arc::new(frequencies),
+        max_rank: settings::get_gloss_rank(),
+        known_words: KnownWords::load_or_ephemeral(known_words_file),
+    }))
+}
+
+/// Whether a chapter's extracted text is too short to be worth landing on.
+/// Scripts written without spaces have few "words", so their letters count
+/// as well.
+fn is_empty_chapter(text: &str, min_words: usize) -> bool {
+    const LETTERS_PER_WORD: usize = 5;
+    let words = text.split_whitespace().count();
+    let letters = text.chars().filter(|c| c.is_alphanumeric()).count();
+    words < min_words && letters < min_words * LETTERS_PER_WORD
+}
+
+/// Chapters within `radius` of `center`, or all of them without a radius
+fn prefetch_window(radius: Option<usize>, center: usize, total: usize) -> std::ops::Range<usize> {
+    match radius {
+        Some(radius) => center.saturating_sub(radius)..center.saturating_add(radius + 1).min(total),
+        None => 0..total,
+    }
+}
+
+fn book_progress_percent(
+    chapter_sizes: &[usize],
+    current_chapter: usize,
+    chapter_fraction: f32,
+) -> Option<u32> {
+    let total: usize = chapter_sizes.iter().sum();
+    if total == 0 || current_chapter >= chapter_sizes.len() {
+        return None;
+    }
+
+    let preceding: usize = chapter_sizes[..current_chapter].iter().sum();
+    let current = chapter_sizes[current_chapter] as f64 * chapter_fraction.clamp(0.0, 1.0) as f64;
+    Some((((preceding as f64 + current) / total as f64) * 100.0) as u32)
+}
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::inputs::event_source::{EventSource, SimulatedEventSource};
+    use crate::test_utils::test_helpers::{
+        TempBookManager, capture_terminal_state, create_test_app, create_test_app_with_fake_books,
+        create_test_terminal,
+    };
+    use crossterm::event::{KeyCode, KeyEvent};
+
+    fn draw_at(app: &mut App, width: u16, height: u16) -> String {
+        let mut terminal = create_test_terminal(width, height);
+        terminal.draw(|f| app.draw(f, &FPSCounter::new())).unwrap();
+        capture_terminal_state(&terminal)
+    }
+
+    #[test]
+    fn test_tiny_terminal_shows_size_warning_and_keeps_handling_keys() {
+        let mut app = create_test_app();
+        let screen = draw_at(&mut app, 35, 10);
+
+        assert!(screen.contains("Terminal too small: 35×10"), "{screen}");
+        assert!(screen.contains("need at least 40×12"), "{screen}");
+        assert!(!screen.contains('┌'), "{screen}");
+        assert!(app.help_bar_items.is_empty());
+
+        app.handle_key_event_with_screen_height(KeyEvent::from(KeyCode::Char('j')), None);
+        assert_eq!(app.navigation_panel.get_selected_book_index(), 1);
+    }
+
+    #[test]
+    fn test_compact_terminal_drops_help_bar_and_side_panel() {
+        let mut app = create_test_app();
+        let screen = draw_at(&mut app, 55, 14);
+        let rows: Vec<&str> = screen.lines().collect();
+
+        // The library fills the screen, no help bar below it
+        assert!(
+            rows[0].starts_with('┌') && rows[0].ends_with('┐'),
+            "{screen}"
+        );
+        assert!(
+            rows[13].starts_with('└') && rows[13].ends_with('┘'),
+            "{screen}"
+        );
+        assert!(!screen.contains("Help"), "{screen}");
+        assert!(app.help_bar_items.is_empty());
+        assert_eq!(app.get_navigation_panel_area().width, 55);
+
+        let screen = draw_at(&mut app, 80, 24);
+        assert!(
+            screen.contains("Help") || screen.contains("Menu"),
+            "{screen}"
+        );
+        assert!(!app.help_bar_items.is_empty());
+    }
+
+    #[test]
+    fn test_idle_dims_the_screen_until_the_next_key() {
+        let mut app = create_test_app();
+        app.idle_timeout = Some(Duration::from_secs(60));
+        assert!(!app.poll_idle());
+
+        app.last_user_input = Instant::now() - Duration::from_secs(61);
+        assert!(app.poll_idle());
+        // Only the transition asks for a redraw
+        assert!(!app.poll_idle());
+        let mut terminal = create_test_terminal(80, 24);
+        terminal.draw(|f| app.draw(f, &FPSCounter::new())).unwrap();
+        let cell = &terminal.backend().buffer()[(40, 12)];
+        assert!(cell.modifier.contains(Modifier::DIM));
+
+        app.handle_key_event_with_screen_height(KeyEvent::from(KeyCode::Char('j')), None);
+        terminal.draw(|f| app.draw(f, &FPSCounter::new())).unwrap();
+        let cell = &terminal.backend().buffer()[(40, 12)];
+        assert!(!cell.modifier.contains(Modifier::DIM));
+        assert!(!app.poll_idle());
+    }
+
+    #[test]
+    fn test_book_progress_accumulates_preceding_chapters() {
+        let sizes = [100, 300, 0, 600];
+
+        assert_eq!(book_progress_percent(&sizes, 0, 0.0), Some(0));
+        assert_eq!(book_progress_percent(&sizes, 0, 1.0), Some(10));
+        assert_eq!(book_progress_percent(&sizes, 1, 0.5), Some(25));
+        // An empty chapter keeps the progress of everything before it
+        assert_eq!(book_progress_percent(&sizes, 2, 0.3), Some(40));
+        assert_eq!(book_progress_percent(&sizes, 3, 1.0), Some(100));
+    }
+
+    #[test]
+    fn test_book_progress_is_monotonic_across_chapters() {
+        let sizes = [120, 80, 400, 50];
+        let mut last = 0;
+        for chapter in 0..sizes.len() {
+            for step in 0..=10 {
+                let progress = book_progress_percent(&sizes, chapter, step as f32 / 10.0).unwrap();
+                assert!(progress >= last);
+                last = progress;
+            }
+        }
+    }
+
+    #[test]
+    fn test_book_progress_unknown_sizes() {
+        assert_eq!(book_progress_percent(&[], 0, 0.5), None);
+        assert_eq!(book_progress_percent(&[0, 0], 1, 0.5), None);
+        assert_eq!(book_progress_percent(&[10, 20], 2, 0.5), None);
+    }
+
+    #[test]
+    fn test_prefetch_window_stays_within_the_book() {
+        assert_eq!(prefetch_window(Some(2), 5, 20), 3..8);
+        assert_eq!(prefetch_window(Some(2), 0, 20), 0..3);
+        assert_eq!(prefetch_window(Some(2), 19, 20), 17..20);
+        assert_eq!(prefetch_window(Some(0), 4, 20), 4..5);
+        assert_eq!(prefetch_window(None, 4, 20), 0..20);
+    }
+
+    #[test]
+    fn test_empty_chapter_detection() {
+        assert!(is_empty_chapter("", 20));
+        assert!(is_empty_chapter("Cover image\n\nBuy our other books", 20));
+        assert!(!is_empty_chapter(&"word ".repeat(20), 20));
+        // A short chapter of Japanese text has long runs without spaces
+        assert!(!is_empty_chapter(&"日本語の文章です。".repeat(20), 20));
+        assert!(!is_empty_chapter("anything", 0));
+    }
+
+    fn press(app: &mut App, c: char) {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        app.handle_key_event_with_screen_height(
+            KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE),
+            None,
+        );
+    }
+
+    #[test]
+    fn test_bare_h_does_not_swallow_key_sequences() {
+        let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+        let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+        app.open_book_for_reading_by_path(&path).unwrap();
+        app.navigate_to_chapter(2).unwrap();
+        app.set_main_panel_focus(MainPanel::Content);
+
+        // A pending g must not turn h into an unknown sequence
+        press(&mut app, 'g');
+        press(&mut app, 'h');
+        assert_eq!(app.key_sequence.current_sequence(), "");
+
+        // A bare h goes to the previous chapter and doesn't linger to
+        // break the following Space+h
+        let chapter = app.current_book.as_ref().unwrap().current_chapter();
+        assert!(chapter >= 1);
+        press(&mut app, 'h');
+        assert_eq!(app.key_sequence.current_sequence(), "");
+        assert_eq!(
+            app.current_book.as_ref().unwrap().current_chapter(),
+            chapter - 1
+        );
+        press(&mut app, ' ');
+        press(&mut app, 'h');
+        assert!(matches!(
+            app.focused_panel,
+            FocusedPanel::Popup(PopupWindow::ReadingHistory)
+        ));
+    }
+
+    #[test]
+    fn test_pinned_book_moves_to_the_top_and_back() {
+        let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+        app.focused_panel = FocusedPanel::Main(MainPanel::NavigationList);
+        let order: Vec<String> = app
+            .book_manager
+            .books
+            .iter()
+            .map(|b| b.path.clone())
+            .collect();
+        assert!(order.len() > 1);
+
+        press(&mut app, 'G');
+        press(&mut app, 'p');
+        let last = order.last().unwrap();
+        assert_eq!(&app.book_manager.books[0].path, last);
+        assert!(app.book_manager.books[0].is_pinned);
+        assert_eq!(app.navigation_panel.get_selected_book_index(), 0);
+
+        press(&mut app, 'p');
+        let unpinned: Vec<String> = app
+            .book_manager
+            .books
+            .iter()
+            .map(|b| b.path.clone())
+            .collect();
+        assert_eq!(unpinned, order);
+        assert_eq!(
+            app.navigation_panel.get_selected_book_index(),
+            order.len() - 1
+        );
+    }
+
+    #[test]
+    fn test_book_edge_motions_need_g_prefix() {
+        let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+        app.focused_panel = FocusedPanel::Main(MainPanel::Content);
+
+        press(&mut app, '0');
+        press(&mut app, '$');
+        assert_eq!(app.key_sequence.current_sequence(), "");
+
+        press(&mut app, 'g');
+        press(&mut app, '$');
+        assert_eq!(app.key_sequence.current_sequence(), "");
+        press(&mut app, 'g');
+        assert_eq!(app.key_sequence.current_sequence(), "g");
+    }
+
+    #[test]
+    fn test_book_edge_motions_land_at_the_edges_of_the_book() {
+        let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+        let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+        app.open_book_for_reading_by_path(&path).unwrap();
+        app.navigate_to_chapter(0).unwrap();
+        app.set_main_panel_focus(MainPanel::Content);
+        draw_at(&mut app, 100, 30);
+        let last = app.current_book.as_ref().unwrap().total_chapters() - 1;
+
+        press(&mut app, 'g');
+        press(&mut app, '$');
+        draw_at(&mut app, 100, 30);
+        assert_eq!(app.current_book.as_ref().unwrap().current_chapter(), last);
+        assert_ne!(app.text_reader.get_max_scroll_offset(), 0);
+        assert_eq!(
+            app.text_reader.get_scroll_offset(),
+            app.text_reader.get_max_scroll_offset()
+        );
+
+        press(&mut app, 'g');
+        press(&mut app, '0');
+        draw_at(&mut app, 100, 30);
+        assert_eq!(app.current_book.as_ref().unwrap().current_chapter(), 0);
+        assert_eq!(app.text_reader.get_scroll_offset(), 0);
+    }
+
+    fn notification(app: &App) -> Option<String> {
+        app.notifications.get_current().map(|n| n.message.clone())
+    }
+
+    #[test]
+    fn test_jumping_to_an_image_is_recorded_in_the_jump_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let filler = "<p>Filler paragraph.</p>".repeat(40);
+        std::fs::write(
+            dir.path().join("chapter.html"),
+            format!("<html><body>{filler}<p><img src=\"fig.png\"/></p>{filler}</body></html>"),
+        )
+        .unwrap();
+        let mut app = App::new_with_config(Some(dir.path().to_str().unwrap()), None, false);
+        let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+        app.open_book_for_reading_by_path(&path).unwrap();
+        draw_at(&mut app, 100, 30);
+
+        app.jump_to_image(true, false);
+        assert_eq!(notification(&app).as_deref(), Some("Image 1/1"));
+        assert_ne!(app.text_reader.get_scroll_offset(), 0);
+
+        // Ctrl+O returns to where the jump started
+        let origin = app.jump_list.jump_back().unwrap();
+        assert_eq!((origin.epub_path, origin.node_index), (path, 0));
+    }
+
+    fn app_at_last_chapter() -> (App, TempBookManager) {
+        let (mut app, books) = create_test_app_with_fake_books();
+        app.open_book_for_reading(0).unwrap();
+        let last = app.current_book.as_ref().unwrap().total_chapters() - 1;
+        app.navigate_to_chapter(last).unwrap();
+        app.notifications.dismiss();
+        (app, books)
+    }
+
+    #[test]
+    fn test_book_boundary_notify_stays_on_the_last_chapter() {
+        let (mut app, _books) = app_at_last_chapter();
+        let file = app.current_book.as_ref().unwrap().file.clone();
+
+        app.handle_book_boundary(ChapterDirection::Next, BookBoundary::Notify);
+        assert_eq!(notification(&app).as_deref(), Some("End of book"));
+        assert_eq!(app.current_book.as_ref().unwrap().file, file);
+    }
+
+    #[test]
+    fn test_book_boundary_next_book_opens_the_following_book() {
+        let (mut app, _books) = app_at_last_chapter();
+        let next = app.book_manager.books[1].path.clone();
+
+        app.handle_book_boundary(ChapterDirection::Next, BookBoundary::NextBook);
+        let book = app.current_book.as_ref().unwrap();
+        assert_eq!(book.file, next);
+        assert_eq!(book.current_chapter(), 0);
+        assert!(
+            notification(&app)
+                .unwrap()
+                .starts_with("End of book, opened")
+        );
+
+        // The last book in the library has nothing after it
+        let last = book.total_chapters() - 1;
+        app.navigate_to_chapter(last).unwrap();
+        app.handle_book_boundary(ChapterDirection::Next, BookBoundary::NextBook);
+        assert_eq!(notification(&app).as_deref(), Some("End of library"));
+        assert_eq!(app.current_book.as_ref().unwrap().file, next);
+    }
+
+    #[test]
+    fn test_book_boundary_nothing_is_silent() {
+        let (mut app, _books) = app_at_last_chapter();
+        let file = app.current_book.as_ref().unwrap().file.clone();
+
+        app.handle_book_boundary(ChapterDirection::Next, BookBoundary::Nothing);
+        assert_eq!(notification(&app), None);
+        assert_eq!(app.current_book.as_ref().unwrap().file, file);
+    }
+
+    fn click(app: &mut App, column: u16, row: u16) {
+        use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        app.handle_non_scroll_mouse_event(MouseEvent {
+            kind: MouseEventKind::Down(MouseButton::Left),
+            column,
+            row,
+            modifiers: KeyModifiers::NONE,
+        });
+    }
+
+    fn auto_nav_panel_app() -> (App, TempBookManager) {
+        let (mut app, books) = create_test_app_with_fake_books();
+        app.nav_panel_mode = NavPanelMode::Auto;
+        app.open_book_for_reading(0).unwrap();
+        app.set_main_panel_focus(MainPanel::Content);
+        draw_at(&mut app, 100, 30);
+        (app, books)
+    }
+
+    #[test]
+    fn test_auto_nav_panel_collapses_while_reading() {
+        let (mut app, _books) = auto_nav_panel_app();
+        assert!(app.is_nav_panel_collapsed());
+        assert_eq!(app.get_navigation_panel_area().width, NAV_PANEL_STRIP_WIDTH);
+        let screen = draw_at(&mut app, 100, 30);
+        assert!(screen.lines().any(|row| row.starts_with("│›│")), "{screen}");
+
+        app.set_main_panel_focus(MainPanel::NavigationList);
+        assert!(!app.is_nav_panel_collapsed());
+        assert!(app.get_navigation_panel_area().width > NAV_PANEL_STRIP_WIDTH);
+
+        // Always mode never collapses
+        app.nav_panel_mode = NavPanelMode::Always;
+        app.set_main_panel_focus(MainPanel::Content);
+        assert!(!app.is_nav_panel_collapsed());
+    }
+
+    #[test]
+    fn test_clicking_the_collapsed_strip_only_expands_the_panel() {
+        let (mut app, _books) = auto_nav_panel_app();
+        let selected = app.navigation_panel.get_selected_book_index();
+
+        click(&mut app, 1, 5);
+        assert!(app.is_main_panel(MainPanel::NavigationList));
+        assert!(!app.is_nav_panel_collapsed());
+        assert_eq!(app.navigation_panel.get_selected_book_index(), selected);
+    }
+
+    #[test]
+    fn test_clicks_past_the_collapsed_strip_reach_the_content() {
+        let (mut app, _books) = auto_nav_panel_app();
+
+        // Inside the expanded width of the 100 column terminal, but right of
+        // the strip
+        let expanded = 100 * app.nav_panel_percent / 100;
+        assert!(expanded > NAV_PANEL_STRIP_WIDTH + 2);
+        click(&mut app, NAV_PANEL_STRIP_WIDTH + 2, 5);
+        assert!(app.is_main_panel(MainPanel::Content));
+        assert!(app.is_nav_panel_collapsed());
+        assert!(!app.is_on_panel_border(NAV_PANEL_STRIP_WIDTH, 5));
+    }
+
+    #[test]
+    fn test_dragging_the_panel_border_resizes_the_navigation_panel() {
+        use crossterm::event::{KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
+        let (mut app, _books) = create_test_app_with_fake_books();
+        app.open_book_for_reading(0).unwrap();
+        app.nav_panel_percent = 30;
+        draw_at(&mut app, 100, 30);
+
+        // Only the navigation panel's own border is grabbed
+        assert!(app.is_on_panel_border(29, 5));
+        assert!(!app.is_on_panel_border(30, 5));
+        assert!(!app.is_on_panel_border(29, 28));
+
+        click(&mut app, 29, 5);
+        let drag = |column| MouseEvent {
+            kind: MouseEventKind::Drag(MouseButton::Left),
+            column,
+            row: 5,
+            modifiers: KeyModifiers::NONE,
+        };
+        app.handle_non_scroll_mouse_event(drag(44));
+        assert_eq!(app.nav_panel_percent, 45);
+        assert!(!app.text_reader.has_text_selection());
+
+        // Dragged past the bounds it stops at the limits
+        app.handle_non_scroll_mouse_event(drag(95));
+        assert_eq!(app.nav_panel_percent, NAV_PANEL_MAX_PERCENT);
+        app.handle_non_scroll_mouse_event(drag(2));
+        assert_eq!(app.nav_panel_percent, NAV_PANEL_MIN_PERCENT);
+    }
+
+    #[test]
+    fn test_quitting_saves_the_bookmark_and_the_prompt_answers_keys() {
+        use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let bookmarks = data_dir.path().join("bookmarks.json");
+        let mut app = App::new_with_config(
+            Some("tests/testdata"),
+            Some(bookmarks.to_str().unwrap()),
+            false,
+        );
+        let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+        app.open_book_for_reading_by_path(&path).unwrap();
+        app.set_main_panel_focus(MainPanel::Content);
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+
+        // The prompt: any key but y or q cancels it
+        assert!(!app.quit_confirm.request(Instant::now(), true));
+        assert!(draw_at(&mut app, 100, 30).contains(i18n::tr("help.quit_confirm")));
+        assert_eq!(
+            app.handle_key_event_with_screen_height(key(KeyCode::Esc), None),
+            None
+        );
+        assert!(!app.quit_confirm.is_pending());
+        assert!(!bookmarks.exists());
+
+        // Ctrl+C quits at once, even while asking, and keeps the place
+        assert!(
+            !app.quit_confirm
+                .request(Instant::now() + Duration::from_secs(2), true)
+        );
+        assert_eq!(
+            app.handle_key_event_with_screen_height(
+                KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL),
+                None
+            ),
+            Some(AppAction::Quit)
+        );
+        let saved = Bookmarks::load_from_file(bookmarks.to_str().unwrap()).unwrap();
+        assert!(saved.get_bookmark(&path).is_some());
+
+        // Answering the prompt with y quits
+        assert!(
+            !app.quit_confirm
+                .request(Instant::now() + Duration::from_secs(5), true)
+        );
+        assert_eq!(
+            app.handle_key_event_with_screen_height(key(KeyCode::Char('y')), None),
+            Some(AppAction::Quit)
+        );
+    }
+
+    #[test]
+    fn test_words_count_once_when_scrolling_forward() {
+        let (mut app, _books) = create_test_app_with_fake_books();
+        app.open_book_for_reading(0).unwrap();
+        draw_at(&mut app, 50, 12);
+        app.update_reading_goal();
+        let today = chrono::Local::now().date_naive();
+        let totals = |app: &App| {
+            app.reading_goals
+                .day(today)
+                .map_or((0, 0), |day| (day.total_lines(), day.total_words()))
+        };
+
+        for _ in 0..6 {
+            app.text_reader.scroll_down();
+        }
+        app.update_reading_goal();
+        let expected_words = app.text_reader.count_words_in_lines(0..6) as u64;
+        assert!(expected_words > 0);
+        assert_eq!(totals(&app), (6, expected_words));
+
+        // Going back re-reads: the lines count, the words don't again
+        for _ in 0..4 {
+            app.text_reader.scroll_up();
+        }
+        app.update_reading_goal();
+        assert_eq!(totals(&app), (10, expected_words));
+    }
+
+    /// Simulated input that records what the app asks of mouse capture
+    struct CaptureRecorder {
+        events: SimulatedEventSource,
+        captures: Vec<bool>,
+    }
+
+    impl EventSource for CaptureRecorder {
+        fn poll(&mut self, timeout: Duration) -> anyhow::Result<bool> {
+            self.events.poll(timeout)
+        }
+
+        fn read(&mut self) -> anyhow::Result<Event> {
+            self.events.read()
+        }
+
+        fn set_mouse_capture(&mut self, enabled: bool) -> anyhow::Result<()> {
+            self.captures.push(enabled);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_mouse_capture_toggle_reaches_the_terminal_and_drops_mouse_events() {
+        let (mut app, _books) = create_test_app_with_fake_books();
+        let mut terminal = create_test_terminal(100, 30);
+        let click_second_book_and_quit = [
+            SimulatedEventSource::mouse_down(5, 2),
+            SimulatedEventSource::mouse_up(5, 2),
+            SimulatedEventSource::char_key('q'),
+        ];
+
+        // With capture off the click is left to the terminal
+        let mut source = CaptureRecorder {
+            events: SimulatedEventSource::new(
+                [
+                    SimulatedEventSource::char_key(' '),
+                    SimulatedEventSource::char_key('m'),
+                ]
+                .into_iter()
+                .chain(click_second_book_and_quit.clone())
+                .collect(),
+            ),
+            captures: Vec::new(),
+        };
+        run_app_with_event_source(&mut terminal, &mut app, &mut source).unwrap();
+        assert_eq!(source.captures, [false]);
+        assert!(!app.is_mouse_captured());
+        assert_eq!(app.navigation_panel.get_selected_book_index(), 0);
+        assert!(draw_at(&mut app, 100, 30).contains(i18n::tr("help.mouse_off")));
+
+        // Turned back on, the same click reaches the app
+        let mut source = CaptureRecorder {
+            events: SimulatedEventSource::new(
+                [
+                    SimulatedEventSource::char_key(' '),
+                    SimulatedEventSource::char_key('m'),
+                ]
+                .into_iter()
+                .chain(click_second_book_and_quit)
+                .collect(),
+            ),
+            captures: Vec::new(),
+        };
+        run_app_with_event_source(&mut terminal, &mut app, &mut source).unwrap();
+        assert_eq!(source.captures, [true]);
+        assert_eq!(app.navigation_panel.get_selected_book_index(), 1);
+        assert!(!draw_at(&mut app, 100, 30).contains(i18n::tr("help.mouse_off")));
+    }
+
+    #[test]
+    fn test_building_the_app_writes_no_files() {
+        let data_dir = tempfile::TempDir::new().unwrap();
+        let bookmarks = data_dir.path().join("bookmarks.json");
+        let mut app = App::new_with_config(
+            Some("tests/testdata"),
+            Some(bookmarks.to_str().unwrap()),
+            false,
+        );
+        assert_eq!(std::fs::read_dir(data_dir.path()).unwrap().count(), 0);
+
+        // The library scan is kept once the session ends
+        app.save_session();
+        assert!(data_dir.path().join(LIBRARY_SCAN_FILENAME).exists());
+    }
+}
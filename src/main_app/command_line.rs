@@ -0,0 +1,153 @@
+//! The `:` command line: what is being typed after the colon, and what the
+//! line means once Enter is pressed.
+//!
+//! The grammar is small on purpose:
+//!
+//! - `:40` goes to line 40 of the chapter
+//! - `:ch 3` / `:chapter 3` opens the third chapter
+//! - `:toc` shows the table of contents
+//! - `:set margin 4` sets the margin of the book
+//! - `:zen`, `:help`, `:q` and `:q!` do what their keys do
+
+use super::PopupWindow;
+use super::commands::Command;
+use crate::i18n::{tr, tr_args};
+
+/// A line the command line understood
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LineCommand {
+    /// A command of the keymap, e.g. `:q` for `q`
+    Run(Command),
+    /// 1-based line of the chapter
+    GoToLine(usize),
+    /// 1-based chapter of the book
+    GoToChapter(usize),
+    ShowToc,
+    SetMargin(u16),
+}
+
+/// What Enter does with `line`; the message to show when it means nothing
+pub fn parse(line: &str) -> Result<LineCommand, String> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let number = |word: &str, key: &'static str| {
+        word.parse::<usize>()
+            .ok()
+            .filter(|n| *n > 0)
+            .ok_or_else(|| tr_args(key, &[("word", &word)]))
+    };
+
+    match words.as_slice() {
+        [] => Err(tr("command_line.empty").to_string()),
+        [word] if word.chars().all(|c| c.is_ascii_digit()) => {
+            number(word, "command_line.not_a_line").map(LineCommand::GoToLine)
+        }
+        ["ch" | "chapter", n] => {
+            number(n, "command_line.not_a_chapter").map(LineCommand::GoToChapter)
+        }
+        ["toc"] => Ok(LineCommand::ShowToc),
+        ["set", "margin", n] => n
+            .parse()
+            .map(LineCommand::SetMargin)
+            .map_err(|_| tr_args("command_line.not_a_margin", &[("value", n)])),
+        ["set", option, ..] => Err(tr_args(
+            "command_line.unknown_option",
+            &[("option", option)],
+        )),
+        ["zen"] => Ok(LineCommand::Run(Command::ToggleZen)),
+        ["h" | "help"] => Ok(LineCommand::Run(Command::OpenPopup(PopupWindow::Help))),
+        ["q" | "quit"] => Ok(LineCommand::Run(Command::RequestQuit)),
+        ["q!" | "quit!"] => Ok(LineCommand::Run(Command::Quit)),
+        _ => Err(tr_args(
+            "command_line.unknown_command",
+            &[("command", &line.trim())],
+        )),
+    }
+}
+
+/// The line being typed after `:`, if the command line is open
+#[derive(Default)]
+pub struct CommandLine {
+    text: Option<String>,
+}
+
+impl CommandLine {
+    pub fn is_active(&self) -> bool {
+        self.text.is_some()
+    }
+
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    pub fn open(&mut self) {
+        self.text = Some(String::new());
+    }
+
+    pub fn push(&mut self, c: char) {
+        if let Some(text) = &mut self.text {
+            text.push(c);
+        }
+    }
+
+    /// Delete the last character; on an empty line, close it as vim does
+    pub fn backspace(&mut self) {
+        match &mut self.text {
+            Some(text) if !text.is_empty() => {
+                text.pop();
+            }
+            _ => self.text = None,
+        }
+    }
+
+    /// Close the command line, returning what was typed
+    pub fn take(&mut self) -> Option<String> {
+        self.text.take()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_the_command_grammar() {
+        assert_eq!(parse("40"), Ok(LineCommand::GoToLine(40)));
+        assert_eq!(parse(" chapter 3 "), Ok(LineCommand::GoToChapter(3)));
+        assert_eq!(parse("ch 2"), Ok(LineCommand::GoToChapter(2)));
+        assert_eq!(parse("toc"), Ok(LineCommand::ShowToc));
+        assert_eq!(parse("set margin 4"), Ok(LineCommand::SetMargin(4)));
+        assert_eq!(parse("q"), Ok(LineCommand::Run(Command::RequestQuit)));
+        assert_eq!(parse("q!"), Ok(LineCommand::Run(Command::Quit)));
+        assert_eq!(parse("zen"), Ok(LineCommand::Run(Command::ToggleZen)));
+
+        assert!(parse("0").is_err());
+        assert!(parse("ch x").is_err());
+        assert_eq!(parse("set margin -1"), Err("Not a margin: -1".to_string()));
+        assert_eq!(
+            parse("set width 3"),
+            Err("Unknown option: width".to_string())
+        );
+        assert_eq!(
+            parse("frobnicate"),
+            Err("Unknown command: frobnicate".to_string())
+        );
+        assert!(parse("  ").is_err());
+    }
+
+    #[test]
+    fn test_backspace_on_an_empty_line_closes_it() {
+        let mut line = CommandLine::default();
+        assert!(!line.is_active());
+        line.open();
+        line.push('q');
+        line.backspace();
+        assert_eq!(line.text(), Some(""));
+        line.backspace();
+        assert!(!line.is_active());
+
+        line.open();
+        line.push('4');
+        assert_eq!(line.take().as_deref(), Some("4"));
+        assert!(!line.is_active());
+    }
+}
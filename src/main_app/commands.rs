@@ -0,0 +1,2360 @@
+//! What the keys do, apart from doing it.
+//!
+//! `command_for_key` reads a key as a [`Command`] from where it was pressed:
+//! the focused panel, the key sequence typed so far (`gg`, `Space+h`, `]i`)
+//! and a few bits of reader state. It changes nothing, so the whole keymap
+//! can be checked without a terminal. `App::execute_command` carries the
+//! command out.
+//!
+//! Over a popup a key becomes a [`PopupCommand`] for the popup's widget to
+//! carry out. What a key means there depends on the popup and on whether it
+//! is taking typed text or waiting for a y/N answer ([`PopupMode`]).
+//! The navigation panel's list keys still go to its widget.
+
+use super::command_line::{self, LineCommand};
+use super::{App, AppAction, FocusedPanel, MainPanel, PopupWindow, VimNavMotions};
+use crate::i18n;
+use crate::jump_list::JumpLocation;
+use crate::parsing::toc_parser::LandmarkKind;
+use crate::search::SearchablePanel;
+use crate::settings::{self, CtrlZ, LEADER};
+use crate::widget::book_info::BookInfoAction;
+use crate::widget::help_popup::HelpPopupAction;
+use crate::widget::popup_geometry::{self, PopupKind};
+use crate::widget::reading_stats::ReadingStatsAction;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use log::{debug, error, info};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Command {
+    /// First key of a sequence (Space, g, [ or ])
+    BeginSequence(char),
+    /// Taken without doing anything, e.g. keys a search field has no use for
+    Ignore,
+
+    ScrollDown,
+    ScrollUp,
+    HalfPageDown,
+    HalfPageUp,
+    FullPageDown,
+    FullPageUp,
+    GoToTop,
+    GoToBottom,
+    /// g0 / g$: the start or end of the whole book
+    GoToBookEdge {
+        end: bool,
+    },
+    /// gs / gc / gt: the start of the text, the cover or the contents page
+    GoToLandmark(LandmarkKind),
+    NextChapter,
+    PreviousChapter,
+    JumpBack,
+    JumpForward,
+    JumpToImage {
+        forward: bool,
+        open: bool,
+    },
+    JumpToAnnotatedChapter {
+        forward: bool,
+    },
+    /// Space+[ / Space+]: the previous or next recently read book
+    ResumeBook {
+        next: bool,
+    },
+    /// Ctrl+6: the book open before this one, where it was left
+    AlternateBook,
+
+    StartSearch,
+    NextMatch,
+    PreviousMatch,
+    SearchSelection {
+        forward: bool,
+    },
+    OpenBookSearch {
+        clear_input: bool,
+    },
+    SearchInput(char),
+    SearchBackspace,
+    ConfirmSearch,
+    CancelSearch,
+    RecallSearch {
+        older: bool,
+    },
+
+    /// `:` opens the command line at the bottom
+    OpenCommandLine,
+    CommandLineInput(char),
+    CommandLineBackspace,
+    RunCommandLine,
+    CancelCommandLine,
+
+    ToggleCollapsible,
+    ToggleChapterRead,
+    /// Comment on the selection, or on the image in view without one
+    StartComment,
+    /// `a` in the image popup: a note on the image shown
+    CommentOnPopupImage,
+    /// h/l in the image popup: the chapter's previous or next image
+    StepPopupImage {
+        forward: bool,
+    },
+    CopySelection,
+    DeleteCommentAtCursor,
+    CopyChapter,
+    CopyRawTextLines,
+    ExportChapter,
+    ToggleRawHtml,
+    ToggleImages,
+    ToggleDehyphenation,
+    ToggleTypography,
+    ToggleReadingRuler,
+    MoveReadingRuler(i32),
+    AdjustMargin {
+        wider: bool,
+    },
+
+    SwitchPanel,
+    ToggleZen,
+    ResizeNavPanel {
+        wider: bool,
+    },
+    ResizePopup(PopupKind, (i16, i16)),
+    ToggleMouseCapture,
+    CaptureScreen,
+    /// Ctrl+Z: stop and hand the terminal to the shell until `fg`
+    Suspend,
+    ToggleProfiling,
+    OpenWithSystemViewer,
+
+    OpenPopup(PopupWindow),
+    /// Close the popup if it has focus, open it otherwise
+    TogglePopup(PopupWindow),
+    ClosePopup(PopupWindow),
+    /// A key meant for the focused popup, which its widget carries out
+    Popup(PopupCommand),
+    /// Space+B: reading threads, with a new one being named
+    StartReadingThread,
+
+    /// Quit, asking first when `confirm_quit` is set
+    RequestQuit,
+    Quit,
+    /// Dismiss the notification, the selection or the search, in that order
+    Escape,
+}
+
+/// What a key does in a popup. The widget of the focused popup carries it
+/// out, so each popup only needs the ones `popup_command` gives it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PopupCommand {
+    Down,
+    Up,
+    /// h / l: collapse and expand, or the cursor in a text field
+    Left,
+    Right,
+    Top,
+    Bottom,
+    HalfPageDown,
+    HalfPageUp,
+    /// Enter on the selected entry: open it, jump to it or run it
+    Select,
+    Close,
+    /// Tab: the popup's other view, pane or unit
+    SwitchView,
+    /// Narrow the list: missing books only, or the next kind of match
+    CycleFilter,
+    /// Take the selected entry off the list, asking first where that can't
+    /// be undone
+    Remove,
+    /// X in the reading history: every book whose file is gone
+    RemoveMissing,
+    /// n in the reading threads: start naming a new one
+    New,
+    /// m in the comment storage: merge another file into the selected one
+    Merge,
+    /// M in the comment storage: move every file to another directory
+    Migrate,
+    StartSearch,
+    NextMatch,
+    PreviousMatch,
+    /// ? in the comments viewer: search the whole library or this book
+    ToggleSearchScope,
+    /// Space in the book search results: back to the query
+    EditQuery,
+    /// t on the instance lock warning
+    TakeOver,
+    /// Ctrl+D in the onboarding: the directory bookokrat was started in
+    UseCurrentDirectory,
+
+    Input(char),
+    Backspace,
+    /// Ctrl+U: empty the text field
+    ClearInput,
+    /// Tab: complete the path typed
+    Complete,
+    /// Enter on the text typed
+    Submit,
+    /// Esc from the text field or a question, back to the list
+    Cancel,
+    /// y to a y/N question
+    Confirm,
+    /// A key for a text area that reads editing keys itself
+    EditText(KeyEvent),
+}
+
+/// What the focused popup is waiting for, which changes what keys mean
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum PopupMode {
+    /// Moving through the popup
+    #[default]
+    Browse,
+    /// Typing into its text field, which takes every printable key
+    Typing,
+    /// A y/N question; any key but y answers no
+    Confirming,
+}
+
+/// Where a key is pressed, as far as its meaning depends on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyContext {
+    pub focus: FocusedPanel,
+    /// Keys of an unfinished sequence typed before this one
+    pub sequence: String,
+    /// The open popup if it can be resized, and whether it takes typed text
+    pub resizable_popup: Option<(PopupKind, bool)>,
+    /// Whether the open popup is browsing, taking typed text or asking y/N
+    pub popup_mode: PopupMode,
+    /// Typing a search query, in the reader or the navigation panel
+    pub search_input: bool,
+    /// Typing a `:` command
+    pub command_line: bool,
+    /// A search is on, typed or being stepped through
+    pub searching: bool,
+    /// The search being typed is the reader's, which recalls older queries
+    pub reader_search: bool,
+    pub has_selection: bool,
+    pub reading_ruler: bool,
+    /// Key that starts the Space+ sequences; Space pages down when it is
+    /// another
+    pub leader_key: char,
+    pub ctrl_z: CtrlZ,
+}
+
+impl KeyContext {
+    pub fn new(focus: FocusedPanel) -> Self {
+        KeyContext {
+            focus,
+            sequence: String::new(),
+            resizable_popup: None,
+            popup_mode: PopupMode::Browse,
+            search_input: false,
+            command_line: false,
+            searching: false,
+            reader_search: false,
+            has_selection: false,
+            reading_ruler: false,
+            leader_key: LEADER,
+            ctrl_z: CtrlZ::default(),
+        }
+    }
+
+    fn ctrl_z_command(&self) -> Command {
+        ctrl_z_command(self.ctrl_z)
+    }
+}
+
+/// Suspend, or zen mode for those who kept Ctrl+z on it
+fn ctrl_z_command(ctrl_z: CtrlZ) -> Command {
+    match ctrl_z {
+        CtrlZ::Suspend => Command::Suspend,
+        CtrlZ::ZenMode => Command::ToggleZen,
+    }
+}
+
+/// Every command the palette (Space+:) lists, as (translation key of its
+/// name, keys, command); commands that only make sense on a selection or
+/// while searching are left out
+pub const PALETTE: &[(&str, &str, Command)] = &[
+    ("palette.go_to_top", "gg", Command::GoToTop),
+    ("palette.go_to_bottom", "G", Command::GoToBottom),
+    (
+        "palette.go_to_book_start",
+        "g0",
+        Command::GoToBookEdge { end: false },
+    ),
+    (
+        "palette.go_to_book_end",
+        "g$",
+        Command::GoToBookEdge { end: true },
+    ),
+    (
+        "palette.start_reading",
+        "gs",
+        Command::GoToLandmark(LandmarkKind::BodyMatter),
+    ),
+    (
+        "palette.go_to_cover",
+        "gc",
+        Command::GoToLandmark(LandmarkKind::Cover),
+    ),
+    (
+        "palette.go_to_contents",
+        "gt",
+        Command::GoToLandmark(LandmarkKind::Toc),
+    ),
+    ("palette.next_chapter", "l", Command::NextChapter),
+    ("palette.previous_chapter", "h", Command::PreviousChapter),
+    (
+        "palette.go_to_chapter",
+        "Space+p",
+        Command::TogglePopup(PopupWindow::ChapterPicker),
+    ),
+    ("palette.jump_back", "Ctrl+o", Command::JumpBack),
+    ("palette.jump_forward", "Ctrl+i", Command::JumpForward),
+    (
+        "palette.next_image",
+        "]i",
+        Command::JumpToImage {
+            forward: true,
+            open: false,
+        },
+    ),
+    (
+        "palette.previous_image",
+        "[i",
+        Command::JumpToImage {
+            forward: false,
+            open: false,
+        },
+    ),
+    (
+        "palette.open_next_image",
+        "]I",
+        Command::JumpToImage {
+            forward: true,
+            open: true,
+        },
+    ),
+    (
+        "palette.open_previous_image",
+        "[I",
+        Command::JumpToImage {
+            forward: false,
+            open: true,
+        },
+    ),
+    (
+        "palette.figure_list",
+        "Space+I",
+        Command::TogglePopup(PopupWindow::FigureList),
+    ),
+    (
+        "palette.next_annotated_chapter",
+        "]a",
+        Command::JumpToAnnotatedChapter { forward: true },
+    ),
+    (
+        "palette.previous_annotated_chapter",
+        "[a",
+        Command::JumpToAnnotatedChapter { forward: false },
+    ),
+    (
+        "palette.toggle_chapter_read",
+        "m",
+        Command::ToggleChapterRead,
+    ),
+    ("palette.search_chapter", "/", Command::StartSearch),
+    (
+        "palette.search_book",
+        "Space+f",
+        Command::OpenBookSearch { clear_input: false },
+    ),
+    (
+        "palette.new_search_book",
+        "Space+F",
+        Command::OpenBookSearch { clear_input: true },
+    ),
+    (
+        "palette.reading_history",
+        "Space+h",
+        Command::TogglePopup(PopupWindow::ReadingHistory),
+    ),
+    (
+        "palette.previous_recent_book",
+        "Space+[",
+        Command::ResumeBook { next: false },
+    ),
+    (
+        "palette.next_recent_book",
+        "Space+]",
+        Command::ResumeBook { next: true },
+    ),
+    ("palette.alternate_book", "Ctrl+6", Command::AlternateBook),
+    (
+        "palette.book_switcher",
+        "Space+l",
+        Command::TogglePopup(PopupWindow::BookSwitcher),
+    ),
+    (
+        "palette.open_system_viewer",
+        "Space+o",
+        Command::OpenWithSystemViewer,
+    ),
+    (
+        "palette.book_stats",
+        "Space+d",
+        Command::OpenPopup(PopupWindow::BookStats),
+    ),
+    (
+        "palette.reading_stats",
+        "Space+D",
+        Command::TogglePopup(PopupWindow::ReadingStats),
+    ),
+    (
+        "palette.book_info",
+        "Space+n",
+        Command::TogglePopup(PopupWindow::BookInfo),
+    ),
+    (
+        "palette.comments",
+        "Space+a",
+        Command::TogglePopup(PopupWindow::CommentsViewer),
+    ),
+    (
+        "palette.comment_storage",
+        "Space+A",
+        Command::TogglePopup(PopupWindow::CommentStorage),
+    ),
+    (
+        "palette.random_comment",
+        "Space+q",
+        Command::OpenPopup(PopupWindow::ResurfacedComment),
+    ),
+    (
+        "palette.reading_threads",
+        "Space+b",
+        Command::TogglePopup(PopupWindow::ReadingThreads),
+    ),
+    (
+        "palette.new_reading_thread",
+        "Space+B",
+        Command::StartReadingThread,
+    ),
+    (
+        "palette.rare_words",
+        "Space+w",
+        Command::TogglePopup(PopupWindow::RareWords),
+    ),
+    ("palette.copy_chapter", "Space+c", Command::CopyChapter),
+    ("palette.export_chapter", "Space+e", Command::ExportChapter),
+    ("palette.toggle_raw_html", "Space+s", Command::ToggleRawHtml),
+    ("palette.toggle_images", "Space+i", Command::ToggleImages),
+    (
+        "palette.toggle_dehyphenation",
+        "Space+-",
+        Command::ToggleDehyphenation,
+    ),
+    (
+        "palette.toggle_typography",
+        "Space+'",
+        Command::ToggleTypography,
+    ),
+    (
+        "palette.toggle_reading_ruler",
+        "Space+r",
+        Command::ToggleReadingRuler,
+    ),
+    (
+        "palette.wider_margins",
+        "+",
+        Command::AdjustMargin { wider: true },
+    ),
+    (
+        "palette.narrower_margins",
+        "-",
+        Command::AdjustMargin { wider: false },
+    ),
+    ("palette.switch_panel", "Tab", Command::SwitchPanel),
+    ("palette.toggle_zen", "Space+Z", Command::ToggleZen),
+    (
+        "palette.widen_nav_panel",
+        "Ctrl+Right",
+        Command::ResizeNavPanel { wider: true },
+    ),
+    (
+        "palette.narrow_nav_panel",
+        "Ctrl+Left",
+        Command::ResizeNavPanel { wider: false },
+    ),
+    (
+        "palette.change_theme",
+        "Space+t",
+        Command::TogglePopup(PopupWindow::ThemeSelector),
+    ),
+    (
+        "palette.toggle_mouse",
+        "Space+m",
+        Command::ToggleMouseCapture,
+    ),
+    ("palette.capture_screen", "Space+?", Command::CaptureScreen),
+    ("palette.help", "?", Command::OpenPopup(PopupWindow::Help)),
+    ("palette.suspend", "Ctrl+z", Command::Suspend),
+    ("palette.quit", "q", Command::RequestQuit),
+];
+
+/// `keys` of a `PALETTE` entry as typed with `leader_key`: Space+p is ,p
+/// when the leader is a comma
+pub fn keys_with_leader(keys: &str, leader_key: char) -> String {
+    if leader_key == LEADER {
+        keys.to_string()
+    } else {
+        keys.replace("Space+", &leader_key.to_string())
+    }
+}
+
+/// `keys` of a `PALETTE` entry as the `ctrl_z` setting leaves them: the
+/// command Ctrl+z runs lists it too, and one it no longer runs loses it
+pub fn keys_with_ctrl_z(keys: &str, command: Command, ctrl_z: CtrlZ) -> String {
+    let on_ctrl_z = ctrl_z_command(ctrl_z) == command;
+    match keys {
+        "Ctrl+z" if !on_ctrl_z => String::new(),
+        "Ctrl+z" => keys.to_string(),
+        _ if on_ctrl_z => format!("{keys}, Ctrl+z"),
+        _ => keys.to_string(),
+    }
+}
+
+/// The command a finished key sequence stands for
+pub fn command_for_sequence(sequence: &str) -> Option<Command> {
+    let command = match sequence {
+        "gg" => Command::GoToTop,
+        "g0" | "g$" => Command::GoToBookEdge {
+            end: sequence == "g$",
+        },
+        "gs" => Command::GoToLandmark(LandmarkKind::BodyMatter),
+        "gc" => Command::GoToLandmark(LandmarkKind::Cover),
+        "gt" => Command::GoToLandmark(LandmarkKind::Toc),
+        " s" => Command::ToggleRawHtml,
+        " f" | " F" => Command::OpenBookSearch {
+            clear_input: sequence == " F",
+        },
+        " d" => Command::OpenPopup(PopupWindow::BookStats),
+        " z" => Command::CopyRawTextLines,
+        " e" => Command::ExportChapter,
+        " c" => Command::CopyChapter,
+        " i" => Command::ToggleImages,
+        " I" => Command::TogglePopup(PopupWindow::FigureList),
+        " -" => Command::ToggleDehyphenation,
+        " '" => Command::ToggleTypography,
+        " b" => Command::TogglePopup(PopupWindow::ReadingThreads),
+        " B" => Command::StartReadingThread,
+        " A" => Command::TogglePopup(PopupWindow::CommentStorage),
+        " r" => Command::ToggleReadingRuler,
+        " m" => Command::ToggleMouseCapture,
+        " ?" => Command::CaptureScreen,
+        " Z" => Command::ToggleZen,
+        " q" => Command::OpenPopup(PopupWindow::ResurfacedComment),
+        " w" => Command::TogglePopup(PopupWindow::RareWords),
+        " D" => Command::TogglePopup(PopupWindow::ReadingStats),
+        " n" => Command::TogglePopup(PopupWindow::BookInfo),
+        " o" => Command::OpenWithSystemViewer,
+        " [" | " ]" => Command::ResumeBook {
+            next: sequence == " ]",
+        },
+        " h" => Command::TogglePopup(PopupWindow::ReadingHistory),
+        " l" => Command::TogglePopup(PopupWindow::BookSwitcher),
+        " a" => Command::TogglePopup(PopupWindow::CommentsViewer),
+        " t" => Command::TogglePopup(PopupWindow::ThemeSelector),
+        " p" => Command::TogglePopup(PopupWindow::ChapterPicker),
+        " :" => Command::TogglePopup(PopupWindow::CommandPalette),
+        "]i" | "[i" | "]I" | "[I" => Command::JumpToImage {
+            forward: sequence.starts_with(']'),
+            open: sequence.ends_with('I'),
+        },
+        "]a" | "[a" => Command::JumpToAnnotatedChapter {
+            forward: sequence.starts_with(']'),
+        },
+        _ => return None,
+    };
+    Some(command)
+}
+
+/// The command `key` stands for in `context`; None when the navigation list
+/// reads the key itself, or it does nothing
+pub fn command_for_key(context: &KeyContext, key: KeyEvent) -> Option<Command> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+    // Ctrl+C quits without confirmation unless there is a selection to copy
+    if key.code == KeyCode::Char('c') && ctrl && !context.has_selection {
+        return Some(Command::Quit);
+    }
+
+    // Ctrl+arrows and +/- resize the popup, which keeps that size
+    if let Some((kind, typing)) = context.resizable_popup
+        && let Some(steps) = popup_geometry::resize_steps(&key, typing)
+    {
+        return Some(Command::ResizePopup(kind, steps));
+    }
+
+    match context.focus {
+        // The image popup closes on any key but `a` and the ones stepping
+        // through the chapter's images
+        FocusedPanel::Popup(PopupWindow::ImagePopup) => {
+            return Some(match key.code {
+                KeyCode::Char('a') => Command::CommentOnPopupImage,
+                KeyCode::Char('l' | 'n') | KeyCode::Right => {
+                    Command::StepPopupImage { forward: true }
+                }
+                KeyCode::Char('h' | 'p') | KeyCode::Left => {
+                    Command::StepPopupImage { forward: false }
+                }
+                _ => Command::ClosePopup(PopupWindow::ImagePopup),
+            });
+        }
+        FocusedPanel::Popup(popup) => return popup_command(context, popup, key),
+        FocusedPanel::Main(_) => {}
+    }
+
+    if context.command_line {
+        return Some(command_line_command(key));
+    }
+
+    // Ctrl+P opens the chapter picker from anywhere outside popups
+    if key.code == KeyCode::Char('p') && ctrl && !context.search_input {
+        return Some(Command::OpenPopup(PopupWindow::ChapterPicker));
+    }
+    // Ctrl+6 (Ctrl+^ in vim) switches to the alternate book
+    if matches!(key.code, KeyCode::Char('6' | '^')) && ctrl && !context.search_input {
+        return Some(Command::AlternateBook);
+    }
+    // Ctrl+Left/Right resize the navigation panel
+    if matches!(key.code, KeyCode::Left | KeyCode::Right) && ctrl {
+        return Some(Command::ResizeNavPanel {
+            wider: key.code == KeyCode::Right,
+        });
+    }
+
+    if context.search_input {
+        return Some(search_input_command(context, key));
+    }
+
+    match context.focus {
+        FocusedPanel::Main(MainPanel::NavigationList) => navigation_command(context, key),
+        _ => reader_command(context, key),
+    }
+}
+
+/// Keys over a popup other than the image one
+fn popup_command(context: &KeyContext, popup: PopupWindow, key: KeyEvent) -> Option<Command> {
+    use PopupCommand::*;
+
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let typing = context.popup_mode == PopupMode::Typing;
+    if context.popup_mode == PopupMode::Confirming {
+        let answer = match key.code {
+            KeyCode::Char('y' | 'Y') => Confirm,
+            _ => Cancel,
+        };
+        return Some(Command::Popup(answer));
+    }
+
+    let vim_motions = matches!(
+        popup,
+        PopupWindow::ReadingHistory
+            | PopupWindow::BookStats
+            | PopupWindow::Help
+            | PopupWindow::ThemeSelector
+            | PopupWindow::CommentsViewer
+    );
+    if vim_motions
+        && !typing
+        && let Some(command) = vim_motion_command(context, key)
+    {
+        return Some(command);
+    }
+
+    let command = match popup {
+        PopupWindow::InstanceLock => match key.code {
+            KeyCode::Char('r') | KeyCode::Enter | KeyCode::Esc => Close,
+            KeyCode::Char('t') => TakeOver,
+            KeyCode::Char('q') => return Some(Command::Quit),
+            _ => return None,
+        },
+        PopupWindow::Onboarding => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Submit,
+            KeyCode::Tab => Complete,
+            KeyCode::Char('d') if ctrl => UseCurrentDirectory,
+            _ => EditText(key),
+        },
+        // Typed keys filter the list, so only the arrows and Ctrl move in it
+        PopupWindow::ChapterPicker | PopupWindow::CommandPalette => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Down => Down,
+            KeyCode::Up => Up,
+            KeyCode::Char('n' | 'j') if ctrl => Down,
+            KeyCode::Char('p' | 'k') if ctrl => Up,
+            KeyCode::Char('u') if ctrl => ClearInput,
+            KeyCode::Backspace => Backspace,
+            KeyCode::Char(c) if !ctrl => Input(c),
+            _ => return None,
+        },
+        // Up and Down recall past queries, or move on to the results
+        PopupWindow::BookSearch if typing => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Submit,
+            KeyCode::Up => Up,
+            KeyCode::Down => Down,
+            KeyCode::Left => Left,
+            KeyCode::Right => Right,
+            KeyCode::Char('u') if ctrl => ClearInput,
+            KeyCode::Char(c) => Input(c),
+            KeyCode::Backspace => Backspace,
+            _ => return None,
+        },
+        PopupWindow::BookSearch => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Tab => SwitchView,
+            KeyCode::Char('f') => CycleFilter,
+            KeyCode::Char('h') | KeyCode::Left => Left,
+            KeyCode::Char('l') | KeyCode::Right => Right,
+            KeyCode::Char(' ') if key.modifiers.is_empty() => EditQuery,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            KeyCode::Char('g') => Top,
+            KeyCode::Char('G') => Bottom,
+            _ => return None,
+        },
+        // A search query, a thread's name or a path
+        PopupWindow::CommentsViewer | PopupWindow::ReadingThreads | PopupWindow::CommentStorage
+            if typing =>
+        {
+            match key.code {
+                KeyCode::Esc => Cancel,
+                KeyCode::Enter => Submit,
+                KeyCode::Tab => Complete,
+                KeyCode::Backspace => Backspace,
+                KeyCode::Char(c) => Input(c),
+                _ => return None,
+            }
+        }
+        PopupWindow::CommentsViewer => match key.code {
+            KeyCode::Tab => SwitchView,
+            KeyCode::Char('h') => Left,
+            KeyCode::Char('l') => Right,
+            KeyCode::Char('/') => StartSearch,
+            KeyCode::Char('?') => ToggleSearchScope,
+            KeyCode::Char('n') => NextMatch,
+            KeyCode::Char('N') => PreviousMatch,
+            KeyCode::Char('d') if context.sequence == "d" => Remove,
+            KeyCode::Char('d') => return Some(Command::BeginSequence('d')),
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            _ => return None,
+        },
+        PopupWindow::ReadingHistory => match key.code {
+            KeyCode::Char('h') => Left,
+            KeyCode::Char('l') => Right,
+            KeyCode::Char('x') => Remove,
+            KeyCode::Char('X') => RemoveMissing,
+            KeyCode::Char('m') => CycleFilter,
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            _ => return None,
+        },
+        PopupWindow::BookStats | PopupWindow::ThemeSelector => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            _ => return None,
+        },
+        PopupWindow::Help => match key.code {
+            KeyCode::Esc | KeyCode::Char('?') => Close,
+            _ => return None,
+        },
+        PopupWindow::ReadingThreads => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            KeyCode::Char('n') => New,
+            KeyCode::Char('x') => Remove,
+            _ => return None,
+        },
+        PopupWindow::CommentStorage => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            KeyCode::Char('x') => Remove,
+            KeyCode::Char('m') => Merge,
+            KeyCode::Char('M') => Migrate,
+            _ => return None,
+        },
+        PopupWindow::RareWords => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            KeyCode::Char('x') => Remove,
+            _ => return None,
+        },
+        PopupWindow::FigureList => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            KeyCode::Char('g') | KeyCode::Home => Top,
+            KeyCode::Char('G') | KeyCode::End => Bottom,
+            _ => return None,
+        },
+        PopupWindow::BookSwitcher | PopupWindow::HelpBarMenu => match key.code {
+            KeyCode::Esc => Close,
+            KeyCode::Char('q') if popup == PopupWindow::HelpBarMenu => Close,
+            KeyCode::Enter => Select,
+            KeyCode::Char('j') | KeyCode::Down => Down,
+            KeyCode::Char('k') | KeyCode::Up => Up,
+            _ => return None,
+        },
+        PopupWindow::ReadingStats => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Close,
+            KeyCode::Tab | KeyCode::Char('u') => SwitchView,
+            _ => return None,
+        },
+        PopupWindow::ResurfacedComment => match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Close,
+            KeyCode::Enter => Select,
+            _ => return None,
+        },
+        PopupWindow::BookInfo => match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => Close,
+            _ => return None,
+        },
+        // Read with the app's keys in `command_for_key`
+        PopupWindow::ImagePopup => return None,
+    };
+    Some(Command::Popup(command))
+}
+
+/// j/k, gg/G and Ctrl+D/U, in the popups that move like the reader
+fn vim_motion_command(context: &KeyContext, key: KeyEvent) -> Option<Command> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let command = match key.code {
+        KeyCode::Char('j') => PopupCommand::Down,
+        KeyCode::Char('k') => PopupCommand::Up,
+        KeyCode::Char('g') if context.sequence == "g" => PopupCommand::Top,
+        KeyCode::Char('g') => return Some(Command::BeginSequence('g')),
+        KeyCode::Char('G') => PopupCommand::Bottom,
+        KeyCode::Char('d') if ctrl => PopupCommand::HalfPageDown,
+        KeyCode::Char('u') if ctrl => PopupCommand::HalfPageUp,
+        _ => return None,
+    };
+    Some(Command::Popup(command))
+}
+
+fn search_input_command(context: &KeyContext, key: KeyEvent) -> Command {
+    match key.code {
+        KeyCode::Char(c) => Command::SearchInput(c),
+        KeyCode::Backspace => Command::SearchBackspace,
+        KeyCode::Esc => Command::CancelSearch,
+        KeyCode::Up | KeyCode::Down if context.reader_search => Command::RecallSearch {
+            older: key.code == KeyCode::Up,
+        },
+        KeyCode::Enter => Command::ConfirmSearch,
+        _ => Command::Ignore,
+    }
+}
+
+fn command_line_command(key: KeyEvent) -> Command {
+    match key.code {
+        KeyCode::Char(c) => Command::CommandLineInput(c),
+        KeyCode::Backspace => Command::CommandLineBackspace,
+        KeyCode::Enter => Command::RunCommandLine,
+        KeyCode::Esc => Command::CancelCommandLine,
+        _ => Command::Ignore,
+    }
+}
+
+/// The few keys the app takes over the navigation list; the rest move
+/// through the list
+fn navigation_command(context: &KeyContext, key: KeyEvent) -> Option<Command> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let KeyCode::Char(c) = key.code else {
+        return (key.code == KeyCode::Tab).then_some(Command::SwitchPanel);
+    };
+    if context.sequence == " " && !ctrl {
+        return command_for_sequence(&format!(" {c}"));
+    }
+    match c {
+        c if c == context.leader_key => Some(Command::BeginSequence(LEADER)),
+        '?' => Some(Command::OpenPopup(PopupWindow::Help)),
+        ':' => Some(Command::OpenCommandLine),
+        '<' | '>' => Some(Command::ResizeNavPanel { wider: c == '>' }),
+        'z' if ctrl => Some(context.ctrl_z_command()),
+        'q' => Some(Command::RequestQuit),
+        _ => None,
+    }
+}
+
+fn reader_command(context: &KeyContext, key: KeyEvent) -> Option<Command> {
+    let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+    let content = context.focus == FocusedPanel::Main(MainPanel::Content);
+
+    if let KeyCode::Char(c) = key.code
+        && !ctrl
+        && let Some(pending) = context.sequence.chars().last()
+        && let Some(command) = command_for_sequence(&format!("{pending}{c}"))
+    {
+        return Some(command);
+    }
+
+    let command = match key.code {
+        KeyCode::Char(c) if c == context.leader_key && !ctrl => Command::BeginSequence(LEADER),
+        KeyCode::Char(c @ ('g' | '[' | ']')) if !ctrl => Command::BeginSequence(c),
+        KeyCode::Char(' ') if !ctrl => Command::FullPageDown,
+        KeyCode::Char('/') if content => Command::StartSearch,
+        KeyCode::Char(':') if !ctrl => Command::OpenCommandLine,
+        KeyCode::Enter if content => Command::ToggleCollapsible,
+        KeyCode::Char(c @ ('*' | '#')) if content => Command::SearchSelection { forward: c == '*' },
+        KeyCode::Char('n') if context.searching => Command::NextMatch,
+        KeyCode::Char('N') if context.searching => Command::PreviousMatch,
+        KeyCode::Char('d') if ctrl => Command::HalfPageDown,
+        KeyCode::Char('u') if ctrl => Command::HalfPageUp,
+        KeyCode::Char('f') if ctrl => Command::FullPageDown,
+        KeyCode::Char('b') if ctrl => Command::FullPageUp,
+        KeyCode::Char('d') => Command::DeleteCommentAtCursor,
+        KeyCode::Char('j') => Command::ScrollDown,
+        KeyCode::Char('k') => Command::ScrollUp,
+        KeyCode::Char('J') if content && context.reading_ruler => Command::MoveReadingRuler(1),
+        KeyCode::Char('K') if content && context.reading_ruler => Command::MoveReadingRuler(-1),
+        KeyCode::Char('h') => Command::PreviousChapter,
+        KeyCode::Char('l') => Command::NextChapter,
+        KeyCode::Char('m') if content => Command::ToggleChapterRead,
+        KeyCode::Char('i') if ctrl => Command::JumpForward,
+        KeyCode::Char('o') if ctrl => Command::JumpBack,
+        KeyCode::Char('z') if ctrl => context.ctrl_z_command(),
+        KeyCode::Char('p') => Command::ToggleProfiling,
+        KeyCode::Tab => Command::SwitchPanel,
+        KeyCode::Char('G') => Command::GoToBottom,
+        KeyCode::Char('a') => Command::StartComment,
+        KeyCode::Char('c') => Command::CopySelection,
+        KeyCode::Char('?') => Command::OpenPopup(PopupWindow::Help),
+        KeyCode::Char('q') => Command::RequestQuit,
+        KeyCode::Esc => Command::Escape,
+        KeyCode::Char('=') | KeyCode::Char('+') => Command::AdjustMargin { wider: true },
+        KeyCode::Char('-') => Command::AdjustMargin { wider: false },
+        _ => return None,
+    };
+    Some(command)
+}
+
+impl App {
+    /// What `command_for_key` needs to know of the app
+    pub fn key_context(&self) -> KeyContext {
+        KeyContext {
+            focus: self.focused_panel,
+            sequence: self.key_sequence.current_sequence(),
+            resizable_popup: self.resizable_popup(),
+            popup_mode: self.popup_mode(),
+            search_input: self.is_search_input_mode(),
+            command_line: self.command_line.is_active(),
+            searching: self.is_in_search_mode(),
+            reader_search: self.text_reader.is_searching(),
+            has_selection: self.text_reader.has_text_selection(),
+            reading_ruler: self.text_reader.has_reading_ruler(),
+            leader_key: self.leader_key,
+            ctrl_z: self.ctrl_z,
+        }
+    }
+
+    /// Carry out a command, as if its keys were pressed
+    pub fn execute_command(
+        &mut self,
+        command: Command,
+        screen_height: Option<usize>,
+    ) -> Option<AppAction> {
+        if let Command::BeginSequence(c) = command {
+            self.key_sequence.clear();
+            self.key_sequence.handle_key(c);
+            return None;
+        }
+        self.key_sequence.clear();
+
+        match command {
+            Command::BeginSequence(_) | Command::Ignore => {}
+
+            Command::ScrollDown => self.scroll_down(),
+            Command::ScrollUp => self.scroll_up(),
+            Command::HalfPageDown => {
+                if let Some(visible_height) = screen_height {
+                    self.scroll_half_screen_down(visible_height);
+                }
+            }
+            Command::HalfPageUp => {
+                if let Some(visible_height) = screen_height {
+                    self.scroll_half_screen_up(visible_height);
+                }
+            }
+            Command::FullPageDown => {
+                if let Some(visible_height) = screen_height {
+                    self.scroll_full_screen_down(visible_height);
+                }
+            }
+            Command::FullPageUp => {
+                if let Some(visible_height) = screen_height {
+                    self.scroll_full_screen_up(visible_height);
+                }
+            }
+            Command::GoToTop => {
+                self.text_reader.handle_gg();
+                self.save_bookmark();
+            }
+            Command::GoToBottom => {
+                if self.current_book.is_some() {
+                    self.text_reader.handle_upper_g();
+                }
+            }
+            Command::GoToBookEdge { end } => self.go_to_book_edge(end),
+            Command::GoToLandmark(kind) => self.go_to_landmark(kind),
+            Command::NextChapter => self.step_chapter(super::ChapterDirection::Next),
+            Command::PreviousChapter => self.step_chapter(super::ChapterDirection::Previous),
+            Command::JumpBack => self.jump_back(),
+            Command::JumpForward => self.jump_forward(),
+            Command::JumpToImage { forward, open } => self.jump_to_image(forward, open),
+            Command::JumpToAnnotatedChapter { forward } => self.jump_to_annotated_chapter(forward),
+            Command::ResumeBook { next } => {
+                if next {
+                    self.resume_next();
+                } else {
+                    self.resume_previous();
+                }
+            }
+            Command::AlternateBook => self.switch_to_alternate_book(),
+
+            Command::StartSearch => self.text_reader.start_search(),
+            Command::NextMatch | Command::PreviousMatch => {
+                let forward = command == Command::NextMatch;
+                let typed = if forward { 'n' } else { 'N' };
+                if self.navigation_panel.is_searching() {
+                    if self.navigation_panel.get_search_state().mode
+                        == crate::search::SearchMode::InputMode
+                    {
+                        self.handle_search_input(typed);
+                    }
+                } else if self.text_reader.is_searching() {
+                    if self.text_reader.get_search_state().mode
+                        == crate::search::SearchMode::NavigationMode
+                    {
+                        if forward {
+                            self.text_reader.next_match();
+                        } else {
+                            self.text_reader.previous_match();
+                        }
+                    } else {
+                        self.handle_search_input(typed);
+                    }
+                }
+            }
+            Command::SearchSelection { forward } => self.search_for_selection(forward),
+            Command::OpenBookSearch { clear_input } => {
+                if self.current_book.is_some() {
+                    if let FocusedPanel::Main(panel) = self.focused_panel {
+                        self.previous_main_panel = panel;
+                    }
+                    self.open_book_search(clear_input);
+                }
+            }
+            Command::SearchInput(c) => self.handle_search_input(c),
+            Command::SearchBackspace => self.handle_search_backspace(),
+            Command::ConfirmSearch => {
+                if self.navigation_panel.is_searching() {
+                    self.navigation_panel.confirm_search();
+                } else if self.text_reader.is_searching() {
+                    let query = self.text_reader.get_search_state().query.clone();
+                    self.text_reader.confirm_search();
+                    self.record_search_query(super::SearchScope::Chapter, &query);
+                }
+            }
+            Command::CancelSearch => self.cancel_current_search(),
+            Command::RecallSearch { older } => {
+                let queries = self.search_history.queries(super::SearchScope::Chapter);
+                self.text_reader.recall_search_query(&queries, older);
+            }
+
+            Command::OpenCommandLine => self.command_line.open(),
+            Command::CommandLineInput(c) => self.command_line.push(c),
+            Command::CommandLineBackspace => self.command_line.backspace(),
+            Command::RunCommandLine => return self.run_command_line(screen_height),
+            Command::CancelCommandLine => {
+                self.command_line.take();
+            }
+
+            Command::ToggleCollapsible => {
+                if !self.text_reader.toggle_visible_collapsible()
+                    && let Some((chapter_href, target)) = self.text_reader.visible_image_note()
+                {
+                    self.text_reader.start_editing_comment(chapter_href, target);
+                }
+            }
+            Command::ToggleChapterRead => {
+                if let Some(book) = &self.current_book {
+                    self.toggle_chapter_read(book.current_chapter());
+                }
+            }
+            Command::StartComment => {
+                if self.text_reader.has_text_selection() {
+                    if self.text_reader.start_comment_input() {
+                        debug!("Started comment input mode");
+                    }
+                } else if let Some(src) = self.text_reader.visible_image_src() {
+                    self.text_reader.start_image_comment(&src);
+                }
+            }
+            Command::CommentOnPopupImage => {
+                if let Some(popup) = self.image_popup.take() {
+                    self.close_popup_to_previous();
+                    self.text_reader.start_image_comment(&popup.src_path);
+                }
+            }
+            Command::StepPopupImage { forward } => self.step_popup_image(forward),
+            Command::CopySelection => {
+                if let Err(e) = self.text_reader.copy_selection_to_clipboard() {
+                    error!("Copy failed: {e}");
+                }
+            }
+            Command::DeleteCommentAtCursor => match self.text_reader.delete_comment_at_cursor() {
+                Ok(true) => {
+                    info!("Comment deleted successfully");
+                    self.refresh_toc_annotation_marks();
+                    self.show_info(i18n::tr("notify.comment_deleted"));
+                }
+                Ok(false) => {
+                    // Cursor not on a comment, ignore
+                }
+                Err(e) => {
+                    error!("Failed to delete comment: {e}");
+                    self.show_error(i18n::tr_args(
+                        "notify.delete_comment_failed",
+                        &[("error", &e)],
+                    ));
+                }
+            },
+            Command::CopyChapter => {
+                if self.is_main_panel(MainPanel::Content) {
+                    if let Err(e) = self.text_reader.copy_chapter_to_clipboard() {
+                        debug!("Copy chapter failed: {e}");
+                    } else {
+                        debug!("Successfully copied chapter content to clipboard");
+                    }
+                }
+            }
+            Command::CopyRawTextLines => {
+                // Debugging aid: the reader's wrapped lines as they are laid out
+                if self.is_main_panel(MainPanel::Content) {
+                    if let Err(e) = self.text_reader.copy_raw_text_lines_to_clipboard() {
+                        debug!("Copy raw_text_lines failed: {e}");
+                    } else {
+                        debug!("Successfully copied raw_text_lines to clipboard for debugging");
+                    }
+                }
+            }
+            Command::ExportChapter => {
+                if self.is_main_panel(MainPanel::Content) {
+                    self.export_chapter();
+                }
+            }
+            Command::ToggleRawHtml => {
+                if self.is_main_panel(MainPanel::Content)
+                    && let Some(book) = &mut self.current_book
+                    && let Some((raw_html, _)) = book.epub.get_current_str()
+                {
+                    self.text_reader.set_raw_html(raw_html);
+                    self.text_reader.toggle_raw_html();
+                }
+            }
+            Command::ToggleImages => {
+                let current_node = self.text_reader.get_current_node_index();
+                self.text_reader.toggle_render_images();
+                if self.text_reader.is_rendering_images() {
+                    self.text_reader.preload_image_dimensions(&self.book_images);
+                    self.show_info(i18n::tr("notify.images_shown"));
+                } else {
+                    self.show_info(i18n::tr("notify.images_hidden"));
+                }
+                self.text_reader.restore_to_node_index(current_node);
+                settings::set_render_images(self.text_reader.is_rendering_images());
+            }
+            Command::ToggleDehyphenation => self.toggle_dehyphenation(),
+            Command::ToggleTypography => self.toggle_typography(),
+            Command::ToggleReadingRuler => {
+                let reading_ruler = !self.text_reader.has_reading_ruler();
+                self.text_reader.set_reading_ruler(reading_ruler);
+                settings::set_reading_ruler(reading_ruler);
+                if reading_ruler {
+                    self.show_info(i18n::tr("notify.ruler_on"));
+                } else {
+                    self.show_info(i18n::tr("notify.ruler_off"));
+                }
+            }
+            Command::MoveReadingRuler(rows) => {
+                settings::set_reading_ruler_row(self.text_reader.move_reading_ruler(rows));
+            }
+            Command::AdjustMargin { wider } => self.adjust_margin(wider),
+
+            Command::SwitchPanel => match self.focused_panel {
+                FocusedPanel::Main(MainPanel::NavigationList) => {
+                    self.navigation_panel
+                        .table_of_contents
+                        .clear_manual_navigation();
+                    self.set_main_panel_focus(MainPanel::Content);
+                }
+                FocusedPanel::Main(MainPanel::Content) => {
+                    self.set_main_panel_focus(MainPanel::NavigationList);
+                }
+                FocusedPanel::Popup(_) => {} // No tab switching in popups
+            },
+            Command::ToggleZen => {
+                // Save current content position before toggling zen mode
+                let current_node = self.text_reader.get_current_node_index();
+                self.zen_mode = !self.zen_mode;
+                // Restore position after width change causes re-render
+                self.text_reader.restore_to_node_index(current_node);
+                // When entering zen mode while on NavigationList, switch to Content
+                if self.zen_mode
+                    && self.is_main_panel(MainPanel::NavigationList)
+                    && self.current_book.is_some()
+                {
+                    self.set_main_panel_focus(MainPanel::Content);
+                }
+            }
+            Command::ResizeNavPanel { wider } => self.resize_nav_panel(wider),
+            Command::ResizePopup(kind, steps) => {
+                let size = popup_geometry::resize(kind, steps);
+                if let Some(src) = self
+                    .image_popup
+                    .as_ref()
+                    .map(|popup| popup.src_path.clone())
+                {
+                    // Scaled again for its new largest size
+                    self.handle_image_click(&src, self.terminal_size);
+                }
+                self.show_info(i18n::tr_args(
+                    "notify.popup_size",
+                    &[("width", &size.width), ("height", &size.height)],
+                ));
+            }
+            Command::ToggleMouseCapture => self.toggle_mouse_capture(),
+            Command::CaptureScreen => self.screen_capture_pending = true,
+            Command::Suspend => self.request_suspend(),
+            Command::ToggleProfiling => self.toggle_profiling(),
+            Command::OpenWithSystemViewer => self.open_with_system_viewer(),
+
+            Command::OpenPopup(popup) => {
+                if popup == PopupWindow::ResurfacedComment
+                    && self.focused_panel == FocusedPanel::Popup(popup)
+                {
+                    // Another comment in its place
+                    self.close_popup(popup);
+                }
+                self.open_popup(popup);
+            }
+            Command::TogglePopup(popup) => {
+                if self.focused_panel == FocusedPanel::Popup(popup) {
+                    self.close_popup(popup);
+                } else {
+                    self.open_popup(popup);
+                }
+            }
+            Command::ClosePopup(popup) => self.close_popup(popup),
+            Command::Popup(command) => return self.run_popup_command(command),
+            Command::StartReadingThread => {
+                if self.focused_panel == FocusedPanel::Popup(PopupWindow::ReadingThreads) {
+                    self.close_popup(PopupWindow::ReadingThreads);
+                } else {
+                    self.open_reading_threads(true);
+                }
+            }
+
+            Command::RequestQuit => return self.request_quit(),
+            Command::Quit => return self.quit(),
+            Command::Escape => {
+                if self.notifications.has_notification() {
+                    self.notifications.dismiss();
+                } else if self.text_reader.has_text_selection() {
+                    self.text_reader.clear_selection();
+                } else if self.is_in_search_mode() {
+                    self.cancel_current_search();
+                }
+            }
+        }
+        None
+    }
+
+    /// Carry out the `:` line typed, or say why it can't be
+    fn run_command_line(&mut self, screen_height: Option<usize>) -> Option<AppAction> {
+        let line = self.command_line.take()?;
+        let command = match command_line::parse(&line) {
+            Ok(command) => command,
+            Err(message) => {
+                self.show_error(message);
+                return None;
+            }
+        };
+        let Some(book) = &self.current_book else {
+            if let LineCommand::Run(command) = command {
+                return self.execute_command(command, screen_height);
+            }
+            self.show_error(i18n::tr("notify.no_book_open"));
+            return None;
+        };
+
+        let total_chapters = book.total_chapters();
+        let here = JumpLocation {
+            epub_path: book.file.clone(),
+            chapter_index: book.current_chapter(),
+            node_index: self.text_reader.get_current_node_index(),
+        };
+
+        match command {
+            LineCommand::Run(command) => return self.execute_command(command, screen_height),
+            LineCommand::GoToLine(line) => {
+                self.jump_list.push(here);
+                self.text_reader.scroll_to_line(line - 1);
+                self.save_bookmark();
+                self.update_toc_state();
+            }
+            LineCommand::GoToChapter(chapter) if chapter > total_chapters => {
+                self.show_error(i18n::tr_args(
+                    "notify.chapter_count",
+                    &[("count", &total_chapters)],
+                ));
+            }
+            LineCommand::GoToChapter(chapter) => {
+                self.jump_list.push(here);
+                if let Err(e) = self.navigate_to_chapter(chapter - 1) {
+                    error!("Failed to navigate to chapter {chapter}: {e}");
+                }
+            }
+            LineCommand::ShowToc => self.show_toc(),
+            LineCommand::SetMargin(margin) => self.set_book_margin(margin),
+        }
+        None
+    }
+
+    /// What the focused popup is waiting for
+    fn popup_mode(&self) -> PopupMode {
+        let FocusedPanel::Popup(popup) = self.focused_panel else {
+            return PopupMode::Browse;
+        };
+        let typing = match popup {
+            PopupWindow::ChapterPicker | PopupWindow::CommandPalette | PopupWindow::Onboarding => {
+                true
+            }
+            PopupWindow::BookSearch => self
+                .book_search
+                .as_ref()
+                .is_some_and(|search| search.is_typing()),
+            PopupWindow::CommentsViewer => self
+                .comments_viewer
+                .as_ref()
+                .is_some_and(|viewer| viewer.is_typing()),
+            PopupWindow::ReadingHistory => {
+                return self
+                    .reading_history
+                    .as_ref()
+                    .map_or(PopupMode::Browse, |history| history.popup_mode());
+            }
+            PopupWindow::ReadingThreads => {
+                return self
+                    .reading_threads
+                    .as_ref()
+                    .map_or(PopupMode::Browse, |threads| threads.popup_mode());
+            }
+            PopupWindow::CommentStorage => {
+                return self
+                    .comment_storage
+                    .as_ref()
+                    .map_or(PopupMode::Browse, |storage| storage.popup_mode());
+            }
+            _ => false,
+        };
+        if typing {
+            PopupMode::Typing
+        } else {
+            PopupMode::Browse
+        }
+    }
+
+    /// Carry out a popup command on the focused popup's widget, and what
+    /// the widget asks for in return
+    fn run_popup_command(&mut self, command: PopupCommand) -> Option<AppAction> {
+        let FocusedPanel::Popup(popup) = self.focused_panel else {
+            return None;
+        };
+        match popup {
+            PopupWindow::InstanceLock => {
+                let action = self
+                    .lock_warning
+                    .as_ref()
+                    .and_then(|lock_warning| lock_warning.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_lock_warning_action(action);
+                }
+            }
+            PopupWindow::Onboarding => {
+                let action = self
+                    .onboarding
+                    .as_mut()
+                    .and_then(|onboarding| onboarding.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_onboarding_action(action);
+                }
+            }
+            PopupWindow::BookSearch => {
+                let action = self
+                    .book_search
+                    .as_mut()
+                    .and_then(|search| search.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_book_search_action(action);
+                }
+            }
+            PopupWindow::BookStats => {
+                if let Some(action) = self.book_stat.handle_command(command) {
+                    self.handle_book_stat_action(action);
+                }
+            }
+            PopupWindow::ReadingHistory => {
+                let action = self
+                    .reading_history
+                    .as_mut()
+                    .and_then(|history| history.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_reading_history_action(action);
+                }
+            }
+            PopupWindow::ReadingThreads => {
+                let action = self
+                    .reading_threads
+                    .as_mut()
+                    .and_then(|threads| threads.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_reading_threads_action(action);
+                }
+            }
+            PopupWindow::CommentStorage => {
+                let action = self
+                    .comment_storage
+                    .as_mut()
+                    .and_then(|storage| storage.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_comment_storage_action(action);
+                }
+            }
+            PopupWindow::RareWords => {
+                let action = self
+                    .rare_words
+                    .as_mut()
+                    .and_then(|rare_words| rare_words.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_rare_words_action(action);
+                }
+            }
+            PopupWindow::ResurfacedComment => {
+                let action = self
+                    .resurfaced_comment
+                    .as_ref()
+                    .and_then(|comment| comment.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_resurfaced_comment_action(action);
+                }
+            }
+            PopupWindow::ReadingStats => {
+                let action = self
+                    .reading_stats
+                    .as_mut()
+                    .and_then(|stats| stats.handle_command(command));
+                if let Some(ReadingStatsAction::Close) = action {
+                    self.close_popup(popup);
+                }
+            }
+            PopupWindow::BookInfo => {
+                let action = self
+                    .book_info
+                    .as_ref()
+                    .and_then(|book_info| book_info.handle_command(command));
+                if let Some(BookInfoAction::Close) = action {
+                    self.close_popup(popup);
+                }
+            }
+            PopupWindow::HelpBarMenu => {
+                let action = self
+                    .help_bar_menu
+                    .as_mut()
+                    .and_then(|menu| menu.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_help_bar_menu_action(action);
+                }
+            }
+            PopupWindow::Help => {
+                let action = self
+                    .help_popup
+                    .as_mut()
+                    .and_then(|help| help.handle_command(command));
+                if let Some(HelpPopupAction::Close) = action {
+                    self.close_popup(popup);
+                }
+            }
+            PopupWindow::CommentsViewer => {
+                let action = self
+                    .comments_viewer
+                    .as_mut()
+                    .and_then(|viewer| viewer.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_comments_viewer_action(action);
+                }
+            }
+            PopupWindow::ThemeSelector => {
+                let action = self
+                    .theme_selector
+                    .as_mut()
+                    .and_then(|selector| selector.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_theme_selector_action(action);
+                }
+            }
+            PopupWindow::ChapterPicker => {
+                let action = self
+                    .chapter_picker
+                    .as_mut()
+                    .and_then(|picker| picker.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_chapter_picker_action(action);
+                }
+            }
+            PopupWindow::CommandPalette => {
+                let action = self
+                    .command_palette
+                    .as_mut()
+                    .and_then(|palette| palette.handle_command(command));
+                return action.and_then(|action| self.handle_command_palette_action(action));
+            }
+            PopupWindow::BookSwitcher => {
+                let action = self
+                    .book_switcher
+                    .as_mut()
+                    .and_then(|switcher| switcher.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_book_switcher_action(action);
+                }
+            }
+            PopupWindow::FigureList => {
+                let action = self
+                    .figure_list
+                    .as_mut()
+                    .and_then(|figure_list| figure_list.handle_command(command));
+                if let Some(action) = action {
+                    self.handle_figure_list_action(action);
+                }
+            }
+            // Its keys are the app's own commands
+            PopupWindow::ImagePopup => {}
+        }
+        None
+    }
+
+    fn open_popup(&mut self, popup: PopupWindow) {
+        match popup {
+            PopupWindow::ReadingHistory => self.open_reading_history(),
+            PopupWindow::BookStats => self.open_book_stats(),
+            PopupWindow::Help => self.open_help(),
+            PopupWindow::CommentsViewer => self.open_comments_viewer(),
+            PopupWindow::ThemeSelector => self.open_theme_selector(),
+            PopupWindow::ChapterPicker => self.open_chapter_picker(),
+            PopupWindow::CommandPalette => self.open_command_palette(),
+            PopupWindow::BookSwitcher => self.open_book_switcher(),
+            PopupWindow::FigureList => self.open_figure_list(),
+            PopupWindow::ReadingThreads => self.open_reading_threads(false),
+            PopupWindow::CommentStorage => self.open_comment_storage(),
+            PopupWindow::RareWords => self.open_rare_words(),
+            PopupWindow::ReadingStats => self.open_reading_stats(),
+            PopupWindow::ResurfacedComment => self.open_resurfaced_comment(false),
+            PopupWindow::BookInfo => self.open_book_info(),
+            PopupWindow::BookSearch => self.open_book_search(false),
+            // Opened by what they show, not by a command
+            PopupWindow::ImagePopup
+            | PopupWindow::Onboarding
+            | PopupWindow::InstanceLock
+            | PopupWindow::HelpBarMenu => {}
+        }
+    }
+
+    fn close_popup(&mut self, popup: PopupWindow) {
+        match popup {
+            PopupWindow::ReadingHistory => self.reading_history = None,
+            PopupWindow::BookStats => self.book_stat.hide(),
+            PopupWindow::ImagePopup => self.image_popup = None,
+            PopupWindow::Help => self.help_popup = None,
+            PopupWindow::CommentsViewer => {
+                self.remember_comments_viewer_position();
+                self.comments_viewer = None;
+            }
+            PopupWindow::ThemeSelector => self.theme_selector = None,
+            PopupWindow::ChapterPicker => self.chapter_picker = None,
+            PopupWindow::CommandPalette => self.command_palette = None,
+            PopupWindow::BookSwitcher => self.book_switcher = None,
+            PopupWindow::FigureList => self.figure_list = None,
+            PopupWindow::ReadingThreads => self.reading_threads = None,
+            PopupWindow::CommentStorage => self.comment_storage = None,
+            PopupWindow::RareWords => self.rare_words = None,
+            PopupWindow::ReadingStats => self.reading_stats = None,
+            PopupWindow::ResurfacedComment => self.resurfaced_comment = None,
+            PopupWindow::BookInfo => self.book_info = None,
+            PopupWindow::HelpBarMenu => self.help_bar_menu = None,
+            PopupWindow::BookSearch | PopupWindow::Onboarding | PopupWindow::InstanceLock => {}
+        }
+        self.close_popup_to_previous();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ch(c: char) -> KeyEvent {
+        key(KeyCode::Char(c))
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    fn shift(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::SHIFT)
+    }
+
+    const CONTENT: FocusedPanel = FocusedPanel::Main(MainPanel::Content);
+    const NAVIGATION: FocusedPanel = FocusedPanel::Main(MainPanel::NavigationList);
+
+    fn after(focus: FocusedPanel, sequence: &str) -> KeyContext {
+        KeyContext {
+            sequence: sequence.to_string(),
+            ..KeyContext::new(focus)
+        }
+    }
+
+    fn check(context: &KeyContext, table: &[(KeyEvent, Option<Command>)]) {
+        for (key, expected) in table {
+            assert_eq!(
+                command_for_key(context, *key),
+                *expected,
+                "{key:?} in {context:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_reader_keys() {
+        use Command::*;
+        check(
+            &KeyContext::new(CONTENT),
+            &[
+                (ch('j'), Some(ScrollDown)),
+                (ch('k'), Some(ScrollUp)),
+                (ctrl('d'), Some(HalfPageDown)),
+                (ctrl('u'), Some(HalfPageUp)),
+                (ctrl('f'), Some(FullPageDown)),
+                (ctrl('b'), Some(FullPageUp)),
+                (shift('G'), Some(GoToBottom)),
+                (ch('h'), Some(PreviousChapter)),
+                (ch('l'), Some(NextChapter)),
+                (ctrl('o'), Some(JumpBack)),
+                (ctrl('i'), Some(JumpForward)),
+                (ch('/'), Some(StartSearch)),
+                (ch('*'), Some(SearchSelection { forward: true })),
+                (ch('#'), Some(SearchSelection { forward: false })),
+                (key(KeyCode::Enter), Some(ToggleCollapsible)),
+                (ch('m'), Some(ToggleChapterRead)),
+                (ch('a'), Some(StartComment)),
+                (ch('c'), Some(CopySelection)),
+                (ch('d'), Some(DeleteCommentAtCursor)),
+                (ch('+'), Some(AdjustMargin { wider: true })),
+                (ch('='), Some(AdjustMargin { wider: true })),
+                (ch('-'), Some(AdjustMargin { wider: false })),
+                (key(KeyCode::Tab), Some(SwitchPanel)),
+                (ctrl('z'), Some(Suspend)),
+                (ch('p'), Some(ToggleProfiling)),
+                (ctrl('p'), Some(OpenPopup(PopupWindow::ChapterPicker))),
+                (
+                    KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+                    Some(ResizeNavPanel { wider: true }),
+                ),
+                (ch('?'), Some(OpenPopup(PopupWindow::Help))),
+                (ch('q'), Some(RequestQuit)),
+                (ctrl('c'), Some(Quit)),
+                (key(KeyCode::Esc), Some(Escape)),
+                (ch(' '), Some(BeginSequence(' '))),
+                (ch('g'), Some(BeginSequence('g'))),
+                (ch(']'), Some(BeginSequence(']'))),
+                // Only meaningful in a search, or with the ruler on
+                (ch('n'), None),
+                (shift('J'), None),
+                // Only meaningful after g
+                (ch('0'), None),
+                (ch('$'), None),
+                (ch('x'), None),
+            ],
+        );
+
+        let reader_state = KeyContext {
+            searching: true,
+            has_selection: true,
+            reading_ruler: true,
+            ..KeyContext::new(CONTENT)
+        };
+        check(
+            &reader_state,
+            &[
+                (ch('n'), Some(NextMatch)),
+                (shift('N'), Some(PreviousMatch)),
+                (shift('J'), Some(MoveReadingRuler(1))),
+                (shift('K'), Some(MoveReadingRuler(-1))),
+                // Copies the selection instead of quitting
+                (ctrl('c'), Some(CopySelection)),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_key_sequences() {
+        use Command::*;
+        check(
+            &after(CONTENT, " "),
+            &[
+                (ch('h'), Some(TogglePopup(PopupWindow::ReadingHistory))),
+                (ch('a'), Some(TogglePopup(PopupWindow::CommentsViewer))),
+                (ch('t'), Some(TogglePopup(PopupWindow::ThemeSelector))),
+                (ch('p'), Some(TogglePopup(PopupWindow::ChapterPicker))),
+                (shift(':'), Some(TogglePopup(PopupWindow::CommandPalette))),
+                (ch('b'), Some(TogglePopup(PopupWindow::ReadingThreads))),
+                (shift('B'), Some(StartReadingThread)),
+                (shift('A'), Some(TogglePopup(PopupWindow::CommentStorage))),
+                (ch('w'), Some(TogglePopup(PopupWindow::RareWords))),
+                (shift('D'), Some(TogglePopup(PopupWindow::ReadingStats))),
+                (ch('n'), Some(TogglePopup(PopupWindow::BookInfo))),
+                (ch('d'), Some(OpenPopup(PopupWindow::BookStats))),
+                (ch('q'), Some(OpenPopup(PopupWindow::ResurfacedComment))),
+                (ch('f'), Some(OpenBookSearch { clear_input: false })),
+                (shift('F'), Some(OpenBookSearch { clear_input: true })),
+                (ch('s'), Some(ToggleRawHtml)),
+                (ch('c'), Some(CopyChapter)),
+                (ch('z'), Some(CopyRawTextLines)),
+                (shift('Z'), Some(ToggleZen)),
+                (ch('e'), Some(ExportChapter)),
+                (ch('i'), Some(ToggleImages)),
+                (shift('I'), Some(TogglePopup(PopupWindow::FigureList))),
+                (ch('-'), Some(ToggleDehyphenation)),
+                (ch('\''), Some(ToggleTypography)),
+                (ch('r'), Some(ToggleReadingRuler)),
+                (ch('m'), Some(ToggleMouseCapture)),
+                (shift('?'), Some(CaptureScreen)),
+                (ch('o'), Some(OpenWithSystemViewer)),
+                (ch('['), Some(ResumeBook { next: false })),
+                (ch(']'), Some(ResumeBook { next: true })),
+                // Not a sequence: the key's own meaning
+                (ch('j'), Some(ScrollDown)),
+                (ctrl('d'), Some(HalfPageDown)),
+            ],
+        );
+        check(
+            &after(CONTENT, "g"),
+            &[
+                (ch('g'), Some(GoToTop)),
+                (ch('0'), Some(GoToBookEdge { end: false })),
+                (ch('$'), Some(GoToBookEdge { end: true })),
+                (ch('s'), Some(GoToLandmark(LandmarkKind::BodyMatter))),
+                (ch('c'), Some(GoToLandmark(LandmarkKind::Cover))),
+                (ch('t'), Some(GoToLandmark(LandmarkKind::Toc))),
+                (ch('h'), Some(PreviousChapter)),
+                (ch(' '), Some(BeginSequence(' '))),
+            ],
+        );
+        check(
+            &after(CONTENT, "]"),
+            &[
+                (
+                    ch('i'),
+                    Some(JumpToImage {
+                        forward: true,
+                        open: false,
+                    }),
+                ),
+                (
+                    shift('I'),
+                    Some(JumpToImage {
+                        forward: true,
+                        open: true,
+                    }),
+                ),
+                (ch('a'), Some(JumpToAnnotatedChapter { forward: true })),
+            ],
+        );
+        check(
+            &after(CONTENT, "["),
+            &[
+                (
+                    ch('i'),
+                    Some(JumpToImage {
+                        forward: false,
+                        open: false,
+                    }),
+                ),
+                (ch('a'), Some(JumpToAnnotatedChapter { forward: false })),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_space_pages_down_with_another_leader() {
+        use Command::*;
+        let comma_leader = |focus, sequence: &str| KeyContext {
+            leader_key: ',',
+            ..after(focus, sequence)
+        };
+        check(
+            &comma_leader(CONTENT, ""),
+            &[
+                (ch(' '), Some(FullPageDown)),
+                (ch(','), Some(BeginSequence(LEADER))),
+                (ch('g'), Some(BeginSequence('g'))),
+            ],
+        );
+        check(
+            &comma_leader(CONTENT, " "),
+            &[
+                (ch('h'), Some(TogglePopup(PopupWindow::ReadingHistory))),
+                (ch(' '), Some(FullPageDown)),
+            ],
+        );
+        check(
+            &comma_leader(NAVIGATION, ""),
+            &[(ch(','), Some(BeginSequence(LEADER))), (ch(' '), None)],
+        );
+        check(
+            &comma_leader(NAVIGATION, " "),
+            &[(ch('t'), Some(TogglePopup(PopupWindow::ThemeSelector)))],
+        );
+        assert_eq!(keys_with_leader("Space+p", ','), ",p");
+        assert_eq!(keys_with_leader("Space+p", LEADER), "Space+p");
+    }
+
+    #[test]
+    fn test_palette_lists_the_keys_that_run_each_command() {
+        for ctrl_z in [CtrlZ::Suspend, CtrlZ::ZenMode] {
+            for (name, keys, command) in PALETTE {
+                let hint = keys_with_ctrl_z(keys, *command, ctrl_z);
+                for keys in hint.split(", ").filter(|keys| !keys.is_empty()) {
+                    let (context, key) = if let Some(rest) = keys.strip_prefix("Space+") {
+                        (after(CONTENT, " "), ch(rest.chars().next().unwrap()))
+                    } else if let Some(rest) = keys.strip_prefix("Ctrl+") {
+                        match rest {
+                            "Left" => (
+                                KeyContext::new(CONTENT),
+                                KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+                            ),
+                            "Right" => (
+                                KeyContext::new(CONTENT),
+                                KeyEvent::new(KeyCode::Right, KeyModifiers::CONTROL),
+                            ),
+                            _ => (KeyContext::new(CONTENT), ctrl(rest.chars().next().unwrap())),
+                        }
+                    } else if keys == "Tab" {
+                        (KeyContext::new(CONTENT), key(KeyCode::Tab))
+                    } else {
+                        let mut chars = keys.chars();
+                        let first = chars.next().unwrap();
+                        match chars.next() {
+                            Some(second) => (after(CONTENT, &first.to_string()), ch(second)),
+                            None => (KeyContext::new(CONTENT), ch(first)),
+                        }
+                    };
+                    let context = KeyContext { ctrl_z, ..context };
+                    assert_eq!(
+                        command_for_key(&context, key),
+                        Some(*command),
+                        "{name}: {keys} ({ctrl_z:?})"
+                    );
+                }
+            }
+        }
+        assert_eq!(
+            keys_with_ctrl_z("Space+Z", Command::ToggleZen, CtrlZ::ZenMode),
+            "Space+Z, Ctrl+z"
+        );
+        assert_eq!(
+            keys_with_ctrl_z("Ctrl+z", Command::Suspend, CtrlZ::ZenMode),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_navigation_panel_keys() {
+        use Command::*;
+        // The list's own keys go to its widget
+        check(
+            &KeyContext::new(NAVIGATION),
+            &[
+                (ch('j'), None),
+                (ch('g'), None),
+                (key(KeyCode::Enter), None),
+                (ch('/'), None),
+                (ch('m'), None),
+                (key(KeyCode::Tab), Some(SwitchPanel)),
+                (ch(' '), Some(BeginSequence(' '))),
+                (ch('?'), Some(OpenPopup(PopupWindow::Help))),
+                (ch('<'), Some(ResizeNavPanel { wider: false })),
+                (ch('>'), Some(ResizeNavPanel { wider: true })),
+                (ctrl('z'), Some(Suspend)),
+                (ch('q'), Some(RequestQuit)),
+                (ctrl('p'), Some(OpenPopup(PopupWindow::ChapterPicker))),
+            ],
+        );
+        check(
+            &after(NAVIGATION, " "),
+            &[
+                (ch('h'), Some(TogglePopup(PopupWindow::ReadingHistory))),
+                (shift('?'), Some(CaptureScreen)),
+                (shift('Z'), Some(ToggleZen)),
+                (ch('j'), None),
+            ],
+        );
+
+        let zen_on_ctrl_z = |focus| KeyContext {
+            ctrl_z: CtrlZ::ZenMode,
+            ..after(focus, "")
+        };
+        check(&zen_on_ctrl_z(NAVIGATION), &[(ctrl('z'), Some(ToggleZen))]);
+        check(&zen_on_ctrl_z(CONTENT), &[(ctrl('z'), Some(ToggleZen))]);
+    }
+
+    #[test]
+    fn test_search_input_keys() {
+        use Command::*;
+        let typing = KeyContext {
+            search_input: true,
+            searching: true,
+            reader_search: true,
+            ..KeyContext::new(CONTENT)
+        };
+        check(
+            &typing,
+            &[
+                (ch('q'), Some(SearchInput('q'))),
+                (ch(' '), Some(SearchInput(' '))),
+                (key(KeyCode::Backspace), Some(SearchBackspace)),
+                (key(KeyCode::Enter), Some(ConfirmSearch)),
+                (key(KeyCode::Esc), Some(CancelSearch)),
+                (key(KeyCode::Up), Some(RecallSearch { older: true })),
+                (key(KeyCode::Down), Some(RecallSearch { older: false })),
+                (key(KeyCode::Tab), Some(Ignore)),
+                // Taken by the search field rather than opening the picker
+                (ctrl('p'), Some(SearchInput('p'))),
+                (ctrl('c'), Some(Quit)),
+            ],
+        );
+        // The navigation panel's search has no history to recall
+        let typing = KeyContext {
+            focus: NAVIGATION,
+            reader_search: false,
+            ..typing
+        };
+        check(&typing, &[(key(KeyCode::Up), Some(Ignore))]);
+    }
+
+    #[test]
+    fn test_command_line_keys() {
+        use Command::*;
+        check(
+            &KeyContext::new(CONTENT),
+            &[(shift(':'), Some(OpenCommandLine))],
+        );
+        check(
+            &KeyContext::new(NAVIGATION),
+            &[(ch(':'), Some(OpenCommandLine))],
+        );
+        check(
+            &after(CONTENT, " "),
+            &[(shift(':'), Some(TogglePopup(PopupWindow::CommandPalette)))],
+        );
+
+        let typing = KeyContext {
+            command_line: true,
+            ..KeyContext::new(CONTENT)
+        };
+        check(
+            &typing,
+            &[
+                (ch('q'), Some(CommandLineInput('q'))),
+                (ch(' '), Some(CommandLineInput(' '))),
+                (ctrl('p'), Some(CommandLineInput('p'))),
+                (key(KeyCode::Backspace), Some(CommandLineBackspace)),
+                (key(KeyCode::Enter), Some(RunCommandLine)),
+                (key(KeyCode::Esc), Some(CancelCommandLine)),
+                (key(KeyCode::Tab), Some(Ignore)),
+                (ctrl('c'), Some(Quit)),
+            ],
+        );
+    }
+
+    fn over(popup: PopupWindow, popup_mode: PopupMode) -> KeyContext {
+        KeyContext {
+            popup_mode,
+            ..KeyContext::new(FocusedPanel::Popup(popup))
+        }
+    }
+
+    #[test]
+    fn test_popup_keys() {
+        use Command::*;
+        use PopupCommand as P;
+        use PopupMode::{Browse, Confirming, Typing};
+
+        // Popups that move like the reader
+        for popup in [
+            PopupWindow::ReadingHistory,
+            PopupWindow::BookStats,
+            PopupWindow::Help,
+            PopupWindow::ThemeSelector,
+            PopupWindow::CommentsViewer,
+        ] {
+            check(
+                &over(popup, Browse),
+                &[
+                    (ch('j'), Some(Popup(P::Down))),
+                    (ch('k'), Some(Popup(P::Up))),
+                    (ch('g'), Some(BeginSequence('g'))),
+                    (shift('G'), Some(Popup(P::Bottom))),
+                    (ctrl('d'), Some(Popup(P::HalfPageDown))),
+                    (ctrl('u'), Some(Popup(P::HalfPageUp))),
+                    (key(KeyCode::Esc), Some(Popup(P::Close))),
+                    (ctrl('p'), None),
+                    (ctrl('c'), Some(Quit)),
+                ],
+            );
+            check(
+                &KeyContext {
+                    sequence: "g".to_string(),
+                    ..over(popup, Browse)
+                },
+                &[(ch('g'), Some(Popup(P::Top)))],
+            );
+        }
+        check(
+            &over(PopupWindow::ReadingHistory, Browse),
+            &[
+                (ch('h'), Some(Popup(P::Left))),
+                (ch('l'), Some(Popup(P::Right))),
+                (ch('x'), Some(Popup(P::Remove))),
+                (shift('X'), Some(Popup(P::RemoveMissing))),
+                (ch('m'), Some(Popup(P::CycleFilter))),
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+                (ch('q'), None),
+            ],
+        );
+        // y/N questions: anything but y cancels
+        for popup in [
+            PopupWindow::ReadingHistory,
+            PopupWindow::ReadingThreads,
+            PopupWindow::CommentStorage,
+        ] {
+            check(
+                &over(popup, Confirming),
+                &[
+                    (ch('y'), Some(Popup(P::Confirm))),
+                    (shift('Y'), Some(Popup(P::Confirm))),
+                    (ch('n'), Some(Popup(P::Cancel))),
+                    (key(KeyCode::Esc), Some(Popup(P::Cancel))),
+                    (ch('j'), Some(Popup(P::Cancel))),
+                    (ctrl('c'), Some(Quit)),
+                ],
+            );
+        }
+        check(
+            &over(PopupWindow::BookStats, Browse),
+            &[
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+                (ch('h'), None),
+            ],
+        );
+        check(
+            &over(PopupWindow::ThemeSelector, Browse),
+            &[(key(KeyCode::Enter), Some(Popup(P::Select)))],
+        );
+        check(
+            &over(PopupWindow::Help, Browse),
+            &[
+                (shift('?'), Some(Popup(P::Close))),
+                (key(KeyCode::Enter), None),
+            ],
+        );
+
+        check(
+            &over(PopupWindow::CommentsViewer, Browse),
+            &[
+                (key(KeyCode::Tab), Some(Popup(P::SwitchView))),
+                (ch('h'), Some(Popup(P::Left))),
+                (ch('l'), Some(Popup(P::Right))),
+                (ch('/'), Some(Popup(P::StartSearch))),
+                (shift('?'), Some(Popup(P::ToggleSearchScope))),
+                (ch('n'), Some(Popup(P::NextMatch))),
+                (shift('N'), Some(Popup(P::PreviousMatch))),
+                (ch('d'), Some(BeginSequence('d'))),
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+            ],
+        );
+        check(
+            &KeyContext {
+                sequence: "d".to_string(),
+                ..over(PopupWindow::CommentsViewer, Browse)
+            },
+            &[(ch('d'), Some(Popup(P::Remove)))],
+        );
+        // Typing a search query, a thread's name or a path
+        for popup in [
+            PopupWindow::CommentsViewer,
+            PopupWindow::ReadingThreads,
+            PopupWindow::CommentStorage,
+        ] {
+            check(
+                &over(popup, Typing),
+                &[
+                    (ch('j'), Some(Popup(P::Input('j')))),
+                    (ch('g'), Some(Popup(P::Input('g')))),
+                    (ch('x'), Some(Popup(P::Input('x')))),
+                    (key(KeyCode::Backspace), Some(Popup(P::Backspace))),
+                    (key(KeyCode::Tab), Some(Popup(P::Complete))),
+                    (key(KeyCode::Enter), Some(Popup(P::Submit))),
+                    (key(KeyCode::Esc), Some(Popup(P::Cancel))),
+                    (ctrl('c'), Some(Quit)),
+                ],
+            );
+        }
+        check(
+            &over(PopupWindow::ReadingThreads, Browse),
+            &[
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+                (ch('j'), Some(Popup(P::Down))),
+                (key(KeyCode::Up), Some(Popup(P::Up))),
+                (ch('n'), Some(Popup(P::New))),
+                (ch('x'), Some(Popup(P::Remove))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+                (ch('g'), None),
+            ],
+        );
+        check(
+            &over(PopupWindow::CommentStorage, Browse),
+            &[
+                (ch('j'), Some(Popup(P::Down))),
+                (key(KeyCode::Up), Some(Popup(P::Up))),
+                (ch('x'), Some(Popup(P::Remove))),
+                (ch('m'), Some(Popup(P::Merge))),
+                (shift('M'), Some(Popup(P::Migrate))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+                (key(KeyCode::Enter), None),
+            ],
+        );
+
+        check(
+            &over(PopupWindow::BookSearch, Typing),
+            &[
+                (ch('j'), Some(Popup(P::Input('j')))),
+                (ch(' '), Some(Popup(P::Input(' ')))),
+                (key(KeyCode::Backspace), Some(Popup(P::Backspace))),
+                (ctrl('u'), Some(Popup(P::ClearInput))),
+                (key(KeyCode::Up), Some(Popup(P::Up))),
+                (key(KeyCode::Down), Some(Popup(P::Down))),
+                (key(KeyCode::Left), Some(Popup(P::Left))),
+                (key(KeyCode::Right), Some(Popup(P::Right))),
+                (key(KeyCode::Enter), Some(Popup(P::Submit))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+                (key(KeyCode::Tab), None),
+            ],
+        );
+        check(
+            &over(PopupWindow::BookSearch, Browse),
+            &[
+                (ch('j'), Some(Popup(P::Down))),
+                (key(KeyCode::Up), Some(Popup(P::Up))),
+                (ch('h'), Some(Popup(P::Left))),
+                (key(KeyCode::Right), Some(Popup(P::Right))),
+                (ch('g'), Some(Popup(P::Top))),
+                (shift('G'), Some(Popup(P::Bottom))),
+                (key(KeyCode::Tab), Some(Popup(P::SwitchView))),
+                (ch('f'), Some(Popup(P::CycleFilter))),
+                (ch(' '), Some(Popup(P::EditQuery))),
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+            ],
+        );
+
+        // Typed keys filter the list, so only the arrows and Ctrl move in it
+        for popup in [PopupWindow::ChapterPicker, PopupWindow::CommandPalette] {
+            check(
+                &over(popup, Typing),
+                &[
+                    (ch('j'), Some(Popup(P::Input('j')))),
+                    (ch('q'), Some(Popup(P::Input('q')))),
+                    (key(KeyCode::Down), Some(Popup(P::Down))),
+                    (ctrl('n'), Some(Popup(P::Down))),
+                    (ctrl('j'), Some(Popup(P::Down))),
+                    (key(KeyCode::Up), Some(Popup(P::Up))),
+                    (ctrl('p'), Some(Popup(P::Up))),
+                    (ctrl('k'), Some(Popup(P::Up))),
+                    (ctrl('u'), Some(Popup(P::ClearInput))),
+                    (key(KeyCode::Backspace), Some(Popup(P::Backspace))),
+                    (key(KeyCode::Enter), Some(Popup(P::Select))),
+                    (key(KeyCode::Esc), Some(Popup(P::Close))),
+                    (ctrl('x'), None),
+                ],
+            );
+        }
+
+        let onboarding_key = ch('~');
+        check(
+            &over(PopupWindow::Onboarding, Typing),
+            &[
+                (onboarding_key, Some(Popup(P::EditText(onboarding_key)))),
+                (key(KeyCode::Tab), Some(Popup(P::Complete))),
+                (ctrl('d'), Some(Popup(P::UseCurrentDirectory))),
+                (key(KeyCode::Enter), Some(Popup(P::Submit))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+            ],
+        );
+        check(
+            &over(PopupWindow::InstanceLock, Browse),
+            &[
+                (ch('r'), Some(Popup(P::Close))),
+                (key(KeyCode::Enter), Some(Popup(P::Close))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+                (ch('t'), Some(Popup(P::TakeOver))),
+                // Quits at once, before there is a session to lose
+                (ch('q'), Some(Quit)),
+                (ch('j'), None),
+            ],
+        );
+
+        for popup in [PopupWindow::RareWords, PopupWindow::FigureList] {
+            check(
+                &over(popup, Browse),
+                &[
+                    (ch('j'), Some(Popup(P::Down))),
+                    (key(KeyCode::Down), Some(Popup(P::Down))),
+                    (ch('k'), Some(Popup(P::Up))),
+                    (key(KeyCode::Up), Some(Popup(P::Up))),
+                    (key(KeyCode::Enter), Some(Popup(P::Select))),
+                    (key(KeyCode::Esc), Some(Popup(P::Close))),
+                    (ch('q'), None),
+                ],
+            );
+        }
+        check(
+            &over(PopupWindow::RareWords, Browse),
+            &[(ch('x'), Some(Popup(P::Remove)))],
+        );
+        check(
+            &over(PopupWindow::FigureList, Browse),
+            &[
+                (ch('g'), Some(Popup(P::Top))),
+                (key(KeyCode::Home), Some(Popup(P::Top))),
+                (shift('G'), Some(Popup(P::Bottom))),
+                (key(KeyCode::End), Some(Popup(P::Bottom))),
+            ],
+        );
+        for popup in [PopupWindow::BookSwitcher, PopupWindow::HelpBarMenu] {
+            check(
+                &over(popup, Browse),
+                &[
+                    (ch('j'), Some(Popup(P::Down))),
+                    (key(KeyCode::Up), Some(Popup(P::Up))),
+                    (key(KeyCode::Enter), Some(Popup(P::Select))),
+                    (key(KeyCode::Esc), Some(Popup(P::Close))),
+                ],
+            );
+        }
+        check(&over(PopupWindow::BookSwitcher, Browse), &[(ch('q'), None)]);
+        check(
+            &over(PopupWindow::HelpBarMenu, Browse),
+            &[(ch('q'), Some(Popup(P::Close)))],
+        );
+        check(
+            &over(PopupWindow::ReadingStats, Browse),
+            &[
+                (key(KeyCode::Tab), Some(Popup(P::SwitchView))),
+                (ch('u'), Some(Popup(P::SwitchView))),
+                (ch('q'), Some(Popup(P::Close))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+                (ch('j'), None),
+            ],
+        );
+        check(
+            &over(PopupWindow::ResurfacedComment, Browse),
+            &[
+                (key(KeyCode::Enter), Some(Popup(P::Select))),
+                (ch('q'), Some(Popup(P::Close))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+            ],
+        );
+        check(
+            &over(PopupWindow::BookInfo, Browse),
+            &[
+                (key(KeyCode::Enter), Some(Popup(P::Close))),
+                (ch('q'), Some(Popup(P::Close))),
+                (key(KeyCode::Esc), Some(Popup(P::Close))),
+            ],
+        );
+
+        let history = KeyContext {
+            resizable_popup: Some((PopupKind::ReadingHistory, false)),
+            ..KeyContext::new(FocusedPanel::Popup(PopupWindow::ReadingHistory))
+        };
+        check(
+            &history,
+            &[
+                (
+                    ch('+'),
+                    Some(ResizePopup(PopupKind::ReadingHistory, (1, 1))),
+                ),
+                (
+                    KeyEvent::new(KeyCode::Left, KeyModifiers::CONTROL),
+                    Some(ResizePopup(PopupKind::ReadingHistory, (-1, 0))),
+                ),
+            ],
+        );
+        // A popup taking typed text keeps + and - for it
+        let search = KeyContext {
+            resizable_popup: Some((PopupKind::BookSearch, true)),
+            ..over(PopupWindow::BookSearch, Typing)
+        };
+        check(&search, &[(ch('-'), Some(Popup(P::Input('-'))))]);
+
+        let image = KeyContext {
+            resizable_popup: Some((PopupKind::Image, false)),
+            ..KeyContext::new(FocusedPanel::Popup(PopupWindow::ImagePopup))
+        };
+        check(
+            &image,
+            &[
+                (ch('+'), Some(ResizePopup(PopupKind::Image, (1, 1)))),
+                (ch('j'), Some(ClosePopup(PopupWindow::ImagePopup))),
+                (ch('a'), Some(CommentOnPopupImage)),
+                (ch('l'), Some(StepPopupImage { forward: true })),
+                (key(KeyCode::Left), Some(StepPopupImage { forward: false })),
+                (ch('p'), Some(StepPopupImage { forward: false })),
+                (key(KeyCode::Esc), Some(ClosePopup(PopupWindow::ImagePopup))),
+            ],
+        );
+    }
+}
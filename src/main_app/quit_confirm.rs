@@ -0,0 +1,79 @@
+use std::time::{Duration, Instant};
+
+/// A second quit within this long quits without asking (qq)
+const DOUBLE_QUIT_WINDOW: Duration = Duration::from_secs(1);
+
+/// The `confirm_quit` prompt: whether a quit asks first, and how the key
+/// pressed while asking answers it
+#[derive(Default)]
+pub struct QuitConfirm {
+    pending: bool,
+    last_request: Option<Instant>,
+}
+
+impl QuitConfirm {
+    /// The prompt is showing, waiting for the next key
+    pub fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// A quit was asked for at `now`; true to quit, false when the prompt
+    /// is shown instead
+    pub fn request(&mut self, now: Instant, confirm: bool) -> bool {
+        let repeated = self
+            .last_request
+            .is_some_and(|last| now.duration_since(last) < DOUBLE_QUIT_WINDOW);
+        self.last_request = Some(now);
+        if !confirm || self.pending || repeated {
+            self.pending = false;
+            return true;
+        }
+        self.pending = true;
+        false
+    }
+
+    /// The key pressed while the prompt showed; true to quit, anything but
+    /// y or q cancels
+    pub fn answer(&mut self, key: char) -> bool {
+        self.pending = false;
+        matches!(key, 'y' | 'Y' | 'q')
+    }
+
+    /// A key that is not a character cancels
+    pub fn cancel(&mut self) {
+        self.pending = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quit_asks_once_unless_repeated_quickly() {
+        let start = Instant::now();
+        let later = |secs: u64| start + Duration::from_secs(secs);
+
+        let mut confirm = QuitConfirm::default();
+        assert!(confirm.request(start, false));
+        assert!(!confirm.is_pending());
+
+        let mut confirm = QuitConfirm::default();
+        assert!(!confirm.request(start, true));
+        assert!(confirm.is_pending());
+        assert!(!confirm.answer('n'));
+        assert!(!confirm.is_pending());
+
+        // qq within a second quits without asking
+        assert!(confirm.request(start + Duration::from_millis(400), true));
+
+        assert!(!confirm.request(later(5), true));
+        assert!(confirm.answer('y'));
+
+        assert!(!confirm.request(later(10), true));
+        confirm.cancel();
+        assert!(!confirm.is_pending());
+        assert!(!confirm.request(later(20), true));
+        assert!(confirm.answer('q'));
+    }
+}
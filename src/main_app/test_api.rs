@@ -0,0 +1,189 @@
+//! Scripting surface for integration tests and tools that drive the app
+//! without a terminal: bookmarks, comments and the reading position.
+
+use super::App;
+use crate::bookmarks::Bookmark;
+use crate::comments::{Comment, CommentTarget};
+use crate::ratatui_image::picker::Picker;
+use anyhow::{Result, anyhow, bail};
+use chrono::Utc;
+use std::time::{Duration, Instant};
+
+/// Where the reader is in the open book
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadingPosition {
+    pub book_path: String,
+    pub chapter_index: usize,
+    pub chapter_href: String,
+    /// First document node at the top of the view
+    pub node_index: usize,
+}
+
+/// Borrow of the app returned by [`App::test_api`]
+pub struct TestApi<'a> {
+    app: &'a mut App,
+}
+
+impl App {
+    pub fn test_api(&mut self) -> TestApi<'_> {
+        TestApi { app: self }
+    }
+}
+
+impl TestApi<'_> {
+    /// Position in the open book, None when no book is open
+    pub fn position(&self) -> Option<ReadingPosition> {
+        let book = self.app.current_book.as_ref()?;
+        let chapter_index = book.current_chapter();
+        Some(ReadingPosition {
+            book_path: book.file.clone(),
+            chapter_index,
+            chapter_href: App::get_chapter_href(&book.epub, chapter_index)?,
+            node_index: self.app.text_reader.get_current_node_index(),
+        })
+    }
+
+    pub fn bookmark(&self, book_path: &str) -> Option<Bookmark> {
+        self.app.bookmarks.get_bookmark(book_path).cloned()
+    }
+
+    /// Point a book's main bookmark at a node of a chapter
+    pub fn set_bookmark(
+        &mut self,
+        book_path: &str,
+        chapter_href: &str,
+        chapter_index: usize,
+        node_index: usize,
+    ) {
+        self.app.bookmarks.update_bookmark(
+            book_path,
+            chapter_href.to_string(),
+            Some(node_index),
+            Some(chapter_index),
+            None,
+        );
+    }
+
+    /// Save the current position the way scrolling does, skipping the save throttle
+    pub fn bookmark_position(&mut self) -> Result<()> {
+        if self.app.current_book.is_none() {
+            bail!("No book is open");
+        }
+        self.app.save_bookmark_with_throttle(true);
+        Ok(())
+    }
+
+    /// Forget a book's bookmark, threads included; returns false if it had none.
+    /// Reading on in the open book saves a fresh one.
+    pub fn remove_bookmark(&mut self, book_path: &str) -> bool {
+        self.app.bookmarks.remove_bookmark(book_path)
+    }
+
+    /// Draw images with half blocks in cells of `font_size` pixels, as on a
+    /// terminal without a graphics protocol
+    pub fn use_halfblock_images(&mut self, font_size: (u16, u16)) {
+        self.app
+            .text_reader
+            .set_image_picker(Some(Picker::from_fontsize(font_size)));
+    }
+
+    /// Wait (up to a few seconds) for the chapter's images to finish loading
+    /// in the background, as the event loop would
+    pub fn wait_for_images(&mut self) {
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while self.app.text_reader.has_images_loading() && Instant::now() < deadline {
+            self.app.text_reader.check_for_loaded_images();
+            std::thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// File of the chapter shown in the reader, as comments refer to it
+    pub fn current_chapter_file(&self) -> Option<String> {
+        self.app.text_reader.get_current_chapter_file().clone()
+    }
+
+    /// Comments of the open book in storage order
+    pub fn comments(&self) -> Result<Vec<Comment>> {
+        self.with_comments(|comments| Ok(comments.get_all_comments().to_vec()))
+    }
+
+    pub fn add_comment(&mut self, comment: Comment) -> Result<()> {
+        self.with_comments(|comments| comments.add_comment(comment))?;
+        self.app.text_reader.rebuild_chapter_comments();
+        Ok(())
+    }
+
+    pub fn remove_comment(&mut self, chapter_href: &str, target: &CommentTarget) -> Result<()> {
+        self.with_comments(|comments| comments.delete_comment(chapter_href, target))?;
+        self.app.text_reader.rebuild_chapter_comments();
+        Ok(())
+    }
+
+    /// Highlights are comments on a word range of a paragraph
+    pub fn highlights(&self) -> Result<Vec<Comment>> {
+        Ok(self
+            .comments()?
+            .into_iter()
+            .filter(|comment| comment.target.word_range().is_some())
+            .collect())
+    }
+
+    /// Highlight words `start..=end` of a paragraph with a note
+    pub fn add_highlight(
+        &mut self,
+        chapter_href: &str,
+        paragraph_index: usize,
+        word_range: (usize, usize),
+        note: &str,
+    ) -> Result<()> {
+        self.add_comment(Comment {
+            chapter_href: chapter_href.to_string(),
+            target: CommentTarget::Paragraph {
+                paragraph_index,
+                word_range: Some(word_range),
+            },
+            content: note.to_string(),
+            updated_at: Utc::now(),
+            fingerprint: None,
+        })
+    }
+
+    fn with_comments<T>(
+        &self,
+        f: impl FnOnce(&mut crate::comments::BookComments) -> Result<T>,
+    ) -> Result<T> {
+        if self.app.current_book.is_none() {
+            bail!("No book is open");
+        }
+        let comments = self.app.text_reader.get_comments();
+        let mut guard = comments
+            .lock()
+            .map_err(|_| anyhow!("Comments lock is poisoned"))?;
+        f(&mut guard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_round_trip_without_an_open_book() {
+        let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+        let mut api = app.test_api();
+
+        assert_eq!(api.position(), None);
+        assert!(api.comments().is_err());
+        assert!(api.bookmark_position().is_err());
+
+        api.set_bookmark("/books/dune.epub", "ch3.xhtml", 2, 14);
+        let bookmark = api.bookmark("/books/dune.epub").unwrap();
+        assert_eq!(bookmark.chapter_href, "ch3.xhtml");
+        assert_eq!(bookmark.chapter_index, Some(2));
+        assert_eq!(bookmark.node_index, Some(14));
+
+        assert!(api.remove_bookmark("/books/dune.epub"));
+        assert!(api.bookmark("/books/dune.epub").is_none());
+        assert!(!api.remove_bookmark("/books/dune.epub"));
+    }
+}
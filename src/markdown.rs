@@ -9,7 +9,14 @@ pub struct Document {
 pub struct Node {
     pub block: Block,
     pub source_range: Range<usize>,
-    pub id: Option<String>, // HTML id attribute for anchor resolution
+    pub id: Option<String>,         // HTML id attribute for anchor resolution
+    pub dir: Option<TextDirection>, // Base direction from HTML dir/lang attributes
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TextDirection {
+    Ltr,
+    Rtl,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -27,6 +34,10 @@ pub enum Block {
     },
     Quote {
         content: Vec<Node>,
+        /// Attribution from a trailing `<footer>`/`<cite>`, without the leading dash
+        attribution: Option<Text>,
+        /// Quotes this one is nested in, 0 for a top-level quote
+        depth: usize,
     },
     List {
         kind: ListKind,
@@ -45,6 +56,12 @@ pub enum Block {
         element_name: String,
         content: Vec<Node>,
     },
+    /// `<details>` or an accordion: the summary is always shown, the content
+    /// only once expanded
+    Collapsible {
+        summary: Text,
+        content: Vec<Node>,
+    },
     ThematicBreak,
 }
 
@@ -92,10 +109,17 @@ pub enum Inline {
         alt_text: String,
         url: String,
         title: Option<String>,
+        /// `<figcaption>` of the `<figure>` holding the image
+        caption: Option<String>,
     },
     Anchor {
         id: String,
     },
+    /// `<ruby>` annotation: base text with its reading (furigana)
+    Ruby {
+        base: String,
+        reading: String,
+    },
     LineBreak,
     SoftBreak,
 }
@@ -127,6 +151,9 @@ pub enum TaskStatus {
 pub struct ListItem {
     pub content: Vec<Node>,
     pub task_status: Option<TaskStatus>,
+    /// Number of the item in an ordered list, counted from the list's
+    /// `start` and any `value` of the items before it
+    pub number: Option<u32>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -204,6 +231,55 @@ impl Node {
             block,
             source_range,
             id,
+            dir: None,
+        }
+    }
+
+    /// Plain text of the node, used for previews and comment fingerprints
+    pub fn plain_text(&self) -> String {
+        match &self.block {
+            Block::Paragraph { content } | Block::Heading { content, .. } => content.plain_text(),
+            Block::CodeBlock { content, .. } => content.clone(),
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => content
+                .iter()
+                .map(Node::plain_text)
+                .chain(attribution.iter().map(Text::plain_text))
+                .collect::<Vec<_>>()
+                .join(" "),
+            Block::Collapsible { summary, content } => std::iter::once(summary.plain_text())
+                .chain(content.iter().map(Node::plain_text))
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        }
+    }
+
+    /// Alt text of the image `src` in the node, for quoting a note on it;
+    /// None when the image isn't there
+    pub fn image_alt_text(&self, src: &str) -> Option<&str> {
+        match &self.block {
+            Block::Paragraph { content } | Block::Heading { content, .. } => {
+                content.iter().find_map(|item| match item {
+                    TextOrInline::Inline(Inline::Image { url, alt_text, .. }) if url == src => {
+                        Some(alt_text.as_str())
+                    }
+                    _ => None,
+                })
+            }
+            Block::Quote { content, .. }
+            | Block::EpubBlock { content, .. }
+            | Block::Collapsible { content, .. } => {
+                content.iter().find_map(|node| node.image_alt_text(src))
+            }
+            Block::List { items, .. } => items
+                .iter()
+                .flat_map(|item| &item.content)
+                .find_map(|node| node.image_alt_text(src)),
+            _ => None,
         }
     }
 }
@@ -231,6 +307,18 @@ impl ListItem {
         ListItem {
             content,
             task_status: None,
+            number: None,
+        }
+    }
+
+    /// The number shown before the `index`th item of a `kind` list; items
+    /// built without one count on from the start
+    pub fn number_in(&self, kind: &ListKind, index: usize) -> Option<u32> {
+        match kind {
+            ListKind::Ordered { start } => {
+                Some(self.number.unwrap_or(start.saturating_add(index as u32)))
+            }
+            ListKind::Unordered => None,
         }
     }
 
@@ -239,6 +327,7 @@ impl ListItem {
         ListItem {
             content,
             task_status: Some(status),
+            number: None,
         }
     }
 }
@@ -387,6 +476,23 @@ impl Text {
     pub fn insert_front(&mut self, item: TextOrInline) {
         self.0.insert(0, item);
     }
+
+    pub fn plain_text(&self) -> String {
+        let mut result = String::new();
+        for item in self.iter() {
+            match item {
+                TextOrInline::Text(txt) => result.push_str(&txt.content),
+                TextOrInline::Inline(inline) => match inline {
+                    Inline::Link { text, .. } => result.push_str(&text.plain_text()),
+                    Inline::Image { alt_text, .. } => result.push_str(alt_text),
+                    Inline::Ruby { base, .. } => result.push_str(base),
+                    Inline::LineBreak | Inline::SoftBreak => result.push(' '),
+                    Inline::Anchor { .. } => {}
+                },
+            }
+        }
+        result
+    }
 }
 
 impl IntoIterator for Text {
@@ -401,11 +507,17 @@ impl IntoIterator for Text {
 /// Utility function to classify link href and extract target information
 /// This is used by both the HTML parser and table rendering code
 pub fn classify_link_href(href: &str) -> (LinkType, Option<String>, Option<String>) {
+    let href = href.trim();
+    let path = href
+        .split('#')
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
     if href.starts_with("http://") || href.starts_with("https://") {
         (LinkType::External, None, None)
     } else if let Some(stripped) = href.strip_prefix('#') {
         (LinkType::InternalAnchor, None, Some(stripped.to_string()))
-    } else if href.contains(".html") {
+    } else if path.contains(".html") || path.ends_with(".xhtml") || path.ends_with(".htm") {
         // Extract chapter and anchor for chapter links
         if let Some(hash_pos) = href.find('#') {
             let chapter = href[..hash_pos].to_string();
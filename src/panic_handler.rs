@@ -1,8 +1,4 @@
-use crossterm::{
-    event::DisableMouseCapture,
-    execute,
-    terminal::{LeaveAlternateScreen, disable_raw_mode},
-};
+use crate::terminal_state::release_terminal;
 use std::io::{self, Write};
 use std::panic;
 
@@ -28,9 +24,7 @@ pub fn initialize_panic_handler() {
 /// - Disabling keyboard enhancement flags
 /// - Showing the cursor
 fn restore_terminal() {
-    let _ = disable_raw_mode();
-    let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
-    let _ = execute!(io::stderr(), crossterm::cursor::Show);
+    let _ = release_terminal();
     let _ = writeln!(io::stderr());
 }
 
@@ -0,0 +1,201 @@
+//! Rejoins words split across line breaks in OCR'd books.
+//!
+//! Scanned books often keep the printed hyphenation: "informa- tion" or
+//! "informa-<br/>tion". The pass only joins when the continuation starts
+//! lowercase and neither part contains digits, so "New- York", "COVID- 19"
+//! and "pre- and post-war" are left alone.
+
+use crate::markdown::{Block, Document, Inline, Node, Text, TextOrInline};
+
+/// Continuations that follow a suspended hyphen ("pre- and post-war")
+const CONJUNCTIONS: &[&str] = &["and", "or", "nor", "to"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Join {
+    /// Drop the hyphen: "informa- tion" -> "information"
+    Merge,
+    /// The fragment is part of a compound: "state-of-the- art" -> "state-of-the-art"
+    KeepHyphen,
+}
+
+pub fn dehyphenate_document(document: &mut Document) {
+    dehyphenate_nodes(&mut document.blocks);
+}
+
+fn dehyphenate_nodes(nodes: &mut [Node]) {
+    for node in nodes {
+        dehyphenate_block(&mut node.block);
+    }
+}
+
+fn dehyphenate_block(block: &mut Block) {
+    match block {
+        Block::Heading { content, .. } | Block::Paragraph { content } => dehyphenate_text(content),
+        Block::Quote {
+            content,
+            attribution,
+            ..
+        } => {
+            dehyphenate_nodes(content);
+            if let Some(attribution) = attribution {
+                dehyphenate_text(attribution);
+            }
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                dehyphenate_nodes(&mut item.content);
+            }
+        }
+        Block::Table { header, rows, .. } => {
+            for row in header.iter_mut().chain(rows.iter_mut()) {
+                for cell in &mut row.cells {
+                    dehyphenate_text(&mut cell.content);
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                dehyphenate_text(&mut item.term);
+                for definition in &mut item.definitions {
+                    dehyphenate_nodes(definition);
+                }
+            }
+        }
+        Block::EpubBlock { content, .. } => dehyphenate_nodes(content),
+        Block::Collapsible { summary, content } => {
+            dehyphenate_text(summary);
+            dehyphenate_nodes(content);
+        }
+        Block::CodeBlock { .. } | Block::ThematicBreak => {}
+    }
+}
+
+fn dehyphenate_text(text: &mut Text) {
+    let mut items: Vec<TextOrInline> = std::mem::take(text).into_iter().collect();
+
+    for item in &mut items {
+        match item {
+            TextOrInline::Text(node) => node.content = join_within(&node.content),
+            TextOrInline::Inline(Inline::Link { text, .. }) => dehyphenate_text(text),
+            TextOrInline::Inline(_) => {}
+        }
+    }
+    join_across(&mut items);
+
+    *text = Text::from(items);
+}
+
+/// Join fragments inside one text node, where the converter has already
+/// turned source line breaks into spaces
+fn join_within(content: &str) -> String {
+    let mut result = String::with_capacity(content.len());
+    let mut rest = content;
+
+    while let Some(pos) = rest.find(is_hyphen) {
+        let hyphen_len = rest[pos..].chars().next().map_or(1, char::len_utf8);
+        result.push_str(&rest[..pos]);
+        let after_hyphen = &rest[pos + hyphen_len..];
+        let continuation = after_hyphen.trim_start();
+        let has_gap = continuation.len() < after_hyphen.len();
+
+        match join_kind(&result, continuation).filter(|_| has_gap) {
+            Some(Join::Merge) => rest = continuation,
+            Some(Join::KeepHyphen) => {
+                result.push_str(&rest[pos..pos + hyphen_len]);
+                rest = continuation;
+            }
+            None => {
+                result.push_str(&rest[pos..pos + hyphen_len]);
+                rest = after_hyphen;
+            }
+        }
+    }
+
+    result.push_str(rest);
+    result
+}
+
+/// Join a node ending in a hyphen with a following node, across `<br/>`s
+/// and whitespace
+fn join_across(items: &mut Vec<TextOrInline>) {
+    let mut i = 0;
+    while i < items.len() {
+        if let Some((next, join)) = find_continuation(items, i) {
+            if let TextOrInline::Text(node) = &mut items[i] {
+                let trimmed = node.content.trim_end();
+                let hyphen = trimmed.chars().next_back().unwrap_or('-');
+                let mut joined = trimmed[..trimmed.len() - hyphen.len_utf8()].to_string();
+                if join == Join::KeepHyphen {
+                    joined.push(hyphen);
+                }
+                node.content = joined;
+            }
+            if let TextOrInline::Text(node) = &mut items[next] {
+                node.content = node.content.trim_start().to_string();
+            }
+            items.drain(i + 1..next);
+        }
+        i += 1;
+    }
+}
+
+/// Index of the node continuing the word that `items[index]` breaks off,
+/// if the two should be joined
+fn find_continuation(items: &[TextOrInline], index: usize) -> Option<(usize, Join)> {
+    let TextOrInline::Text(node) = &items[index] else {
+        return None;
+    };
+    let trimmed = node.content.trim_end();
+    let prefix = trimmed.strip_suffix(is_hyphen)?;
+    let mut has_gap = trimmed.len() < node.content.len();
+
+    for (offset, item) in items[index + 1..].iter().enumerate() {
+        match item {
+            TextOrInline::Inline(Inline::LineBreak | Inline::SoftBreak) => has_gap = true,
+            TextOrInline::Text(next) if next.content.trim().is_empty() => has_gap = true,
+            TextOrInline::Text(next) => {
+                let continuation = next.content.trim_start();
+                has_gap |= continuation.len() < next.content.len();
+                if !has_gap {
+                    return None;
+                }
+                return join_kind(prefix, continuation).map(|join| (index + 1 + offset, join));
+            }
+            TextOrInline::Inline(_) => return None,
+        }
+    }
+    None
+}
+
+/// How `prefix` (text up to a line-end hyphen) joins with `continuation`
+/// (text after the break), or None to keep them apart
+fn join_kind(prefix: &str, continuation: &str) -> Option<Join> {
+    let fragment_start = prefix
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphanumeric())
+        .last()
+        .map(|(i, _)| i)?;
+    let fragment = &prefix[fragment_start..];
+    let word: String = continuation
+        .chars()
+        .take_while(|c| c.is_alphanumeric())
+        .collect();
+
+    if !word.chars().next().is_some_and(char::is_lowercase)
+        || fragment.chars().chain(word.chars()).any(|c| c.is_numeric())
+        || CONJUNCTIONS.contains(&word.as_str())
+    {
+        return None;
+    }
+
+    if prefix[..fragment_start].ends_with(is_hyphen) {
+        Some(Join::KeepHyphen)
+    } else {
+        Some(Join::Merge)
+    }
+}
+
+fn is_hyphen(c: char) -> bool {
+    matches!(c, '-' | '\u{2010}' | '\u{00AD}')
+}
@@ -0,0 +1,309 @@
+use crate::markdown::{Block, Document, Node};
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+use crate::parsing::toc_parser::{LandmarkKind, TocParser};
+use epub::doc::{EpubDoc, NavPoint};
+use std::io::{Read, Seek};
+
+/// Pages with fewer words than this are treated as front matter
+pub const MIN_BODY_WORDS: usize = 50;
+
+/// How many spine items are scanned for body text before giving up
+const MAX_SCANNED_SPINE_ITEMS: usize = 8;
+
+/// TOC labels that name front matter rather than the start of the book
+const FRONT_MATTER_LABELS: &[&str] = &[
+    "cover",
+    "title",
+    "half title",
+    "half-title",
+    "halftitle",
+    "copyright",
+    "dedication",
+    "contents",
+    "table of contents",
+    "epigraph",
+    "also by",
+    "praise for",
+    "imprint",
+    "colophon",
+    "frontispiece",
+];
+
+/// Why a chapter was chosen as the start of the book
+#[derive(Debug, Clone, PartialEq)]
+pub enum StartReason {
+    /// `bodymatter` landmark (EPUB3 nav) or `text` guide reference (EPUB2 OPF)
+    Landmark,
+    /// First TOC entry that is not front matter
+    TocEntry(String),
+    /// First spine item with enough body text
+    BodyText { words: usize },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookStart {
+    pub chapter: usize,
+    pub reason: StartReason,
+}
+
+/// Pick the chapter a book should open at when it has no bookmark yet.
+/// Returns None when nothing beyond the first spine item looks like the start.
+pub fn find_start_chapter<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Option<BookStart> {
+    let spine: Vec<String> = doc
+        .spine
+        .iter()
+        .map(|item| {
+            doc.resources
+                .get(&item.idref)
+                .map(|resource| resource.path.to_string_lossy().to_string())
+                .unwrap_or_default()
+        })
+        .collect();
+    let idrefs: Vec<String> = doc.spine.iter().map(|item| item.idref.clone()).collect();
+
+    let landmark = find_landmark(doc);
+    let toc = doc.toc.clone();
+
+    choose_start_chapter(&spine, landmark.as_deref(), &toc, |index| {
+        doc.get_resource_str(&idrefs[index])
+            .map(|(content, _mime)| content)
+    })
+}
+
+/// Core of [`find_start_chapter`], independent of the EPUB container.
+/// `spine` holds the resource path of every spine item and `chapter_html`
+/// loads the content of a spine item on demand.
+pub fn choose_start_chapter(
+    spine: &[String],
+    landmark: Option<&str>,
+    toc: &[NavPoint],
+    mut chapter_html: impl FnMut(usize) -> Option<String>,
+) -> Option<BookStart> {
+    if spine.len() <= 1 {
+        return None;
+    }
+
+    if let Some(chapter) = landmark.and_then(|href| spine_index_for_href(spine, href)) {
+        return Some(BookStart {
+            chapter,
+            reason: StartReason::Landmark,
+        });
+    }
+
+    if let Some((chapter, label)) = first_body_toc_entry(spine, toc) {
+        return Some(BookStart {
+            chapter,
+            reason: StartReason::TocEntry(label),
+        });
+    }
+
+    for chapter in 0..spine.len().min(MAX_SCANNED_SPINE_ITEMS) {
+        let Some(html) = chapter_html(chapter) else {
+            continue;
+        };
+        let document = HtmlToMarkdownConverter::new().convert(&html);
+        let text = document_text(&document);
+        let words = text.split_whitespace().count();
+        if words >= MIN_BODY_WORDS && !looks_like_copyright_page(&text) {
+            return Some(BookStart {
+                chapter,
+                reason: StartReason::BodyText { words },
+            });
+        }
+    }
+
+    None
+}
+
+fn first_body_toc_entry(spine: &[String], toc: &[NavPoint]) -> Option<(usize, String)> {
+    for navpoint in toc {
+        if !is_front_matter_label(&navpoint.label) {
+            let href = navpoint.content.to_string_lossy();
+            if let Some(index) = spine_index_for_href(spine, &href) {
+                return Some((index, navpoint.label.clone()));
+            }
+        }
+        if let Some(found) = first_body_toc_entry(spine, &navpoint.children) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+fn is_front_matter_label(label: &str) -> bool {
+    let label = label.trim().to_lowercase();
+    FRONT_MATTER_LABELS
+        .iter()
+        .any(|front| label == *front || label.starts_with(&format!("{front} ")))
+}
+
+fn looks_like_copyright_page(text: &str) -> bool {
+    let text = text.to_lowercase();
+    text.contains("all rights reserved") || text.contains("isbn")
+}
+
+fn spine_index_for_href(spine: &[String], href: &str) -> Option<usize> {
+    let path = href.split('#').next().unwrap_or(href);
+    if path.is_empty() {
+        return None;
+    }
+    spine.iter().position(|spine_path| {
+        !spine_path.is_empty()
+            && (ends_with_path(spine_path, path) || ends_with_path(path, spine_path))
+    })
+}
+
+/// Whether `path` ends with the whole path components of `suffix`, so
+/// "1.xhtml" matches "Text/1.xhtml" but not "11.xhtml"
+fn ends_with_path(path: &str, suffix: &str) -> bool {
+    path.strip_suffix(suffix)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with('/'))
+}
+
+fn document_text(document: &Document) -> String {
+    let mut text = String::new();
+    collect_text(&document.blocks, &mut text);
+    text
+}
+
+fn collect_text(nodes: &[Node], out: &mut String) {
+    for node in nodes {
+        match &node.block {
+            Block::List { items, .. } => {
+                for item in items {
+                    collect_text(&item.content, out);
+                }
+            }
+            Block::EpubBlock { content, .. } => collect_text(content, out),
+            Block::DefinitionList { items } => {
+                for item in items {
+                    out.push_str(&item.term.plain_text());
+                    out.push(' ');
+                    for definition in &item.definitions {
+                        collect_text(definition, out);
+                    }
+                }
+            }
+            _ => {
+                out.push_str(&node.plain_text());
+                out.push(' ');
+            }
+        }
+    }
+}
+
+/// Href of the start of the body from the EPUB3 landmarks nav or the EPUB2
+/// guide, resolved to a path inside the container
+fn find_landmark<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Option<String> {
+    TocParser::parse_landmarks(doc)
+        .into_iter()
+        .find(|landmark| landmark.kind == LandmarkKind::BodyMatter)
+        .map(|landmark| landmark.href)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn navpoint(label: &str, content: &str) -> NavPoint {
+        NavPoint {
+            label: label.to_string(),
+            content: PathBuf::from(content),
+            children: vec![],
+            play_order: None,
+        }
+    }
+
+    fn words(count: usize) -> String {
+        vec!["word"; count].join(" ")
+    }
+
+    /// A book shaped like cover → title page → copyright → chapter 1 → chapter 2
+    fn fake_book() -> (Vec<String>, Vec<String>) {
+        let spine = ["cover", "title", "copyright", "chapter1", "chapter2"]
+            .iter()
+            .map(|name| format!("OEBPS/Text/{name}.xhtml"))
+            .collect();
+        let html = vec![
+            r#"<html><body><img src="../Images/cover.jpg" alt=""/></body></html>"#.to_string(),
+            "<html><body><h1>The Book</h1><p>A Novel</p></body></html>".to_string(),
+            format!(
+                "<html><body><p>Copyright 2024. All rights reserved. ISBN 978-0-00-000000-0. {}</p></body></html>",
+                words(60)
+            ),
+            format!(
+                "<html><body><h1>Chapter 1</h1><p>{}</p></body></html>",
+                words(300)
+            ),
+            format!(
+                "<html><body><h1>Chapter 2</h1><p>{}</p></body></html>",
+                words(300)
+            ),
+        ];
+        (spine, html)
+    }
+
+    #[test]
+    fn test_toc_skips_front_matter_entries() {
+        let (spine, html) = fake_book();
+        let toc = vec![
+            navpoint("Cover", "OEBPS/Text/cover.xhtml"),
+            navpoint("Title Page", "OEBPS/Text/title.xhtml"),
+            navpoint("Copyright", "OEBPS/Text/copyright.xhtml"),
+            navpoint("Chapter 1", "OEBPS/Text/chapter1.xhtml#start"),
+            navpoint("Chapter 2", "OEBPS/Text/chapter2.xhtml"),
+        ];
+
+        let start = choose_start_chapter(&spine, None, &toc, |i| Some(html[i].clone())).unwrap();
+        assert_eq!(start.chapter, 3);
+        assert_eq!(start.reason, StartReason::TocEntry("Chapter 1".to_string()));
+    }
+
+    #[test]
+    fn test_body_text_scan_without_usable_toc() {
+        let (spine, html) = fake_book();
+        let toc = vec![navpoint("Cover", "OEBPS/Text/cover.xhtml")];
+
+        let start = choose_start_chapter(&spine, None, &toc, |i| Some(html[i].clone())).unwrap();
+        assert_eq!(start.chapter, 3);
+        assert_eq!(start.reason, StartReason::BodyText { words: 302 });
+    }
+
+    #[test]
+    fn test_landmark_wins_over_toc() {
+        let (spine, html) = fake_book();
+        let toc = vec![navpoint("Copyright", "OEBPS/Text/copyright.xhtml")];
+
+        let start = choose_start_chapter(&spine, Some("OEBPS/Text/chapter1.xhtml"), &toc, |i| {
+            Some(html[i].clone())
+        })
+        .unwrap();
+        assert_eq!(start.chapter, 3);
+        assert_eq!(start.reason, StartReason::Landmark);
+    }
+
+    #[test]
+    fn test_single_spine_book_is_not_skipped() {
+        let spine = vec!["OEBPS/book.xhtml".to_string()];
+        let html = format!("<p>{}</p>", words(10));
+
+        assert_eq!(
+            choose_start_chapter(&spine, None, &[], |_| Some(html.clone())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_spine_href_matches_whole_path_components() {
+        let spine: Vec<String> = ["OEBPS/11.xhtml", "OEBPS/1.xhtml"]
+            .iter()
+            .map(|path| path.to_string())
+            .collect();
+
+        assert_eq!(spine_index_for_href(&spine, "1.xhtml#start"), Some(1));
+        assert_eq!(spine_index_for_href(&spine, "11.xhtml"), Some(0));
+        assert_eq!(spine_index_for_href(&spine, "../OEBPS/1.xhtml"), Some(1));
+        assert_eq!(spine_index_for_href(&spine, "x1.xhtml"), None);
+    }
+}
@@ -1,12 +1,17 @@
 use crate::markdown::{
-    Block, DefinitionListItem, Document, HeadingLevel, Inline, Node, Style, Text, TextNode,
-    TextOrInline,
+    Block, DefinitionListItem, Document, HeadingLevel, Inline, Node, Style, Text, TextDirection,
+    TextNode, TextOrInline,
 };
 use crate::mathml_renderer::{MathMLParser, mathml_to_ascii};
+use crate::parsing::dehyphenate::dehyphenate_document;
+use crate::parsing::invisible::strip_invisible_document;
+use crate::parsing::private_use::GlyphMap;
+use crate::parsing::xhtml::prepare_xhtml;
 use html5ever::parse_document;
 use html5ever::tendril::TendrilSink;
 use markup5ever_rcdom::{NodeData, RcDom};
 use std::rc::Rc;
+use std::sync::Arc;
 
 /// Strategy for content collection mode
 #[derive(Debug, Clone)]
@@ -113,11 +118,48 @@ struct ProcessingContext {
 /// let markdown_doc = converter.convert(html_content);
 /// # }
 /// ```
-pub struct HtmlToMarkdownConverter {}
+pub struct HtmlToMarkdownConverter {
+    /// Rejoin words hyphenated across line breaks (OCR'd books)
+    dehyphenate: bool,
+    /// Drop soft hyphens and zero-width spaces from the text
+    strip_invisible: bool,
+    /// Substitutions for private-use characters from embedded fonts
+    glyphs: Arc<GlyphMap>,
+    /// Private-use characters of the last conversion without a known equivalent
+    unknown_glyphs: usize,
+    /// Blockquotes around the one being converted
+    quote_depth: usize,
+}
 
 impl HtmlToMarkdownConverter {
     pub fn new() -> Self {
-        HtmlToMarkdownConverter {}
+        HtmlToMarkdownConverter {
+            dehyphenate: false,
+            strip_invisible: false,
+            glyphs: Arc::default(),
+            unknown_glyphs: 0,
+            quote_depth: 0,
+        }
+    }
+
+    pub fn with_dehyphenation(mut self, dehyphenate: bool) -> Self {
+        self.dehyphenate = dehyphenate;
+        self
+    }
+
+    pub fn with_invisible_stripping(mut self, strip_invisible: bool) -> Self {
+        self.strip_invisible = strip_invisible;
+        self
+    }
+
+    pub fn with_glyph_map(mut self, glyphs: Arc<GlyphMap>) -> Self {
+        self.glyphs = glyphs;
+        self
+    }
+
+    /// How many private-use characters the last `convert` replaced with a placeholder
+    pub fn unknown_glyphs(&self) -> usize {
+        self.unknown_glyphs
     }
 
     fn collect_content(
@@ -169,6 +211,24 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    /// Make `id` an anchor on `block`, the first block of a container whose
+    /// id would otherwise be lost
+    fn anchor_block(block: Option<&mut Node>, id: String) {
+        let Some(block) = block else {
+            return;
+        };
+        if block.id.is_none() {
+            block.id = Some(id);
+            return;
+        }
+        match &mut block.block {
+            Block::Paragraph { content } | Block::Heading { content, .. } => {
+                content.insert_front(TextOrInline::Inline(Inline::Anchor { id }));
+            }
+            _ => {}
+        }
+    }
+
     fn handle_link_element(
         &self,
         node: &Rc<markup5ever_rcdom::Node>,
@@ -240,6 +300,7 @@ impl HtmlToMarkdownConverter {
     }
 
     pub fn convert(&mut self, html: &str) -> Document {
+        let html = prepare_xhtml(html);
         let dom = parse_document(RcDom::default(), Default::default())
             .from_utf8()
             .read_from(&mut html.as_bytes())
@@ -249,6 +310,15 @@ impl HtmlToMarkdownConverter {
         self.visit_node(&dom.document, &mut document);
 
         self.group_dialog_paragraphs(&mut document);
+        self.unknown_glyphs = self.glyphs.substitute_document(&mut document);
+
+        if self.strip_invisible {
+            strip_invisible_document(&mut document);
+        }
+
+        if self.dehyphenate {
+            dehyphenate_document(&mut document);
+        }
 
         document
     }
@@ -265,7 +335,17 @@ impl HtmlToMarkdownConverter {
                 ref attrs,
                 ..
             } => {
+                let first_new_block = document.blocks.len();
                 self.visit_element(name, attrs, node, document);
+
+                // Blocks inherit the direction of their closest ancestor that declares one
+                if let Some(dir) = self.get_direction_attr(attrs) {
+                    for block in &mut document.blocks[first_new_block..] {
+                        if block.dir.is_none() {
+                            block.dir = Some(dir);
+                        }
+                    }
+                }
             }
             NodeData::Text { contents: _ } => {
                 // For now, we'll handle text within element contexts
@@ -294,6 +374,18 @@ impl HtmlToMarkdownConverter {
             return;
         }
 
+        if tag_name != "details" && self.is_accordion(attrs) {
+            // The first element is the header that toggles the rest
+            let header = node
+                .children
+                .borrow()
+                .iter()
+                .find(|child| matches!(child.data, NodeData::Element { .. }))
+                .cloned();
+            self.handle_collapsible(attrs, node, header, document);
+            return;
+        }
+
         match tag_name {
             "html" | "body" => {
                 for child in node.children.borrow().iter() {
@@ -355,9 +447,13 @@ impl HtmlToMarkdownConverter {
                         }
                     }
                 } else {
+                    let first_block = document.blocks.len();
                     for child in node.children.borrow().iter() {
                         self.visit_node(child, document);
                     }
+                    if let Some(id) = div_id {
+                        Self::anchor_block(document.blocks.get_mut(first_block), id);
+                    }
                 }
             }
             "p" => {
@@ -394,9 +490,37 @@ impl HtmlToMarkdownConverter {
             "blockquote" => {
                 self.handle_blockquote(attrs, node, document);
             }
+            "figure" => {
+                self.handle_figure(node, document);
+            }
+            "epub:switch" => {
+                if let Some(default) = Self::switch_default_branch(node) {
+                    for child in default.children.borrow().iter() {
+                        self.visit_node(child, document);
+                    }
+                }
+            }
+            "svg" => {
+                let mut content = Text::default();
+                self.push_svg(node, &mut content);
+                document
+                    .blocks
+                    .push(Node::new(Block::Paragraph { content }, 0..0));
+            }
             "hr" => {
                 document.blocks.push(Node::new(Block::ThematicBreak, 0..0));
             }
+            "details" => {
+                let summary = node
+                    .children
+                    .borrow()
+                    .iter()
+                    .find(|child| {
+                        matches!(&child.data, NodeData::Element { name, .. } if name.local.as_ref() == "summary")
+                    })
+                    .cloned();
+                self.handle_collapsible(attrs, node, summary, document);
+            }
             // Skip li, dt, dd at this level - they're handled within their containers
             "li" | "dt" | "dd" => {}
             _ => {
@@ -496,6 +620,7 @@ impl HtmlToMarkdownConverter {
                 alt_text,
                 url: src,
                 title,
+                caption: None,
             };
 
             let mut content = Text::default();
@@ -520,6 +645,47 @@ impl HtmlToMarkdownConverter {
         Node::new_with_id(Block::CodeBlock { language, content }, 0..0, id)
     }
 
+    /// Visit a `<figure>` as usual, then attach the text of its
+    /// `<figcaption>` to the images it produced
+    fn handle_figure(&mut self, node: &Rc<markup5ever_rcdom::Node>, document: &mut Document) {
+        let first_new_block = document.blocks.len();
+        for child in node.children.borrow().iter() {
+            self.visit_node(child, document);
+        }
+
+        let mut caption = String::new();
+        for child in node.children.borrow().iter() {
+            if matches!(&child.data, NodeData::Element { name, .. } if name.local.as_ref() == "figcaption")
+            {
+                Self::collect_text_from_node(child, &mut caption);
+            }
+        }
+        let caption = caption.split_whitespace().collect::<Vec<_>>().join(" ");
+        if caption.is_empty() {
+            return;
+        }
+        for block_node in &mut document.blocks[first_new_block..] {
+            if let Block::Paragraph { content } = &mut block_node.block {
+                Self::set_image_captions(content, &caption);
+            }
+        }
+    }
+
+    fn set_image_captions(text: &mut Text, caption: &str) {
+        for item in text.iter_mut() {
+            match item {
+                TextOrInline::Inline(Inline::Image {
+                    caption: image_caption,
+                    ..
+                }) => *image_caption = Some(caption.to_string()),
+                TextOrInline::Inline(Inline::Link { text, .. }) => {
+                    Self::set_image_captions(text, caption)
+                }
+                _ => {}
+            }
+        }
+    }
+
     fn handle_pre(
         &mut self,
         attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
@@ -839,7 +1005,7 @@ impl HtmlToMarkdownConverter {
     ) {
         let kind = self.get_list_kind(tag_name, attrs);
 
-        let items = self.extract_list_items(node);
+        let items = self.extract_list_items(node, &kind);
 
         if !items.is_empty() {
             let id = self.get_attr_value(attrs, "id");
@@ -850,17 +1016,32 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    /// The `<li>` items of a list, numbered from its start in an ordered
+    /// one; an item's `value` sets its number and the count goes on from it
     fn extract_list_items(
         &mut self,
         list_node: &Rc<markup5ever_rcdom::Node>,
+        kind: &crate::markdown::ListKind,
     ) -> Vec<crate::markdown::ListItem> {
         let mut items = Vec::new();
+        let mut next_number = match kind {
+            crate::markdown::ListKind::Ordered { start } => Some(*start),
+            crate::markdown::ListKind::Unordered => None,
+        };
 
         for child in list_node.children.borrow().iter() {
             match &child.data {
-                NodeData::Element { name, .. } => {
+                NodeData::Element { name, attrs, .. } => {
                     if name.local.as_ref() == "li" {
-                        let item = self.extract_list_item(child);
+                        let mut item = self.extract_list_item(child);
+                        if let Some(number) = next_number {
+                            let number = self
+                                .get_attr_value(attrs, "value")
+                                .and_then(|value| value.trim().parse().ok())
+                                .unwrap_or(number);
+                            item.number = Some(number);
+                            next_number = Some(number.saturating_add(1));
+                        }
                         items.push(item);
                     }
                 }
@@ -882,11 +1063,23 @@ impl HtmlToMarkdownConverter {
     fn extract_container_blocks(
         &mut self,
         container_node: &Rc<markup5ever_rcdom::Node>,
+    ) -> Vec<Node> {
+        self.extract_container_blocks_except(container_node, None)
+    }
+
+    /// Like `extract_container_blocks`, leaving out one child (e.g. a quote attribution)
+    fn extract_container_blocks_except(
+        &mut self,
+        container_node: &Rc<markup5ever_rcdom::Node>,
+        skip_child: Option<&Rc<markup5ever_rcdom::Node>>,
     ) -> Vec<Node> {
         let mut content = Vec::new();
         let mut current_text = Text::default();
 
         for child in container_node.children.borrow().iter() {
+            if skip_child.is_some_and(|skip| Rc::ptr_eq(skip, child)) {
+                continue;
+            }
             match &child.data {
                 NodeData::Element { name, attrs, .. } => {
                     let tag_name = name.local.as_ref();
@@ -896,7 +1089,7 @@ impl HtmlToMarkdownConverter {
                             self.flush_text_as_paragraph(&mut current_text, &mut content);
 
                             let kind = self.get_list_kind(tag_name, attrs);
-                            let nested_items = self.extract_list_items(child);
+                            let nested_items = self.extract_list_items(child, &kind);
                             if !nested_items.is_empty() {
                                 let nested_list = Block::List {
                                     kind,
@@ -922,6 +1115,7 @@ impl HtmlToMarkdownConverter {
                                     alt_text,
                                     url: src,
                                     title,
+                                    caption: None,
                                 };
                                 current_text.push_inline(image_inline);
                             }
@@ -947,6 +1141,13 @@ impl HtmlToMarkdownConverter {
                                 content.push(Node::new(table, 0..0));
                             }
                         }
+                        "blockquote" => {
+                            self.flush_text_as_paragraph(&mut current_text, &mut content);
+
+                            if let Some(quote) = self.extract_quote_block(child) {
+                                content.push(Node::new(quote, 0..0));
+                            }
+                        }
                         "math" => {
                             let mathml_html = self.serialize_node_to_html(child);
                             match mathml_to_ascii(&mathml_html, true) {
@@ -1215,14 +1416,107 @@ impl HtmlToMarkdownConverter {
         node: &Rc<markup5ever_rcdom::Node>,
         document: &mut Document,
     ) {
-        let content = self.extract_container_blocks(node);
-
-        if !content.is_empty() {
-            let quote_block = Block::Quote { content };
+        if let Some(quote_block) = self.extract_quote_block(node) {
             document.blocks.push(Node::new(quote_block, 0..0));
         }
     }
 
+    /// A `<blockquote>` as a quote block one level deeper than the quotes
+    /// around it, None when it is empty
+    fn extract_quote_block(&mut self, node: &Rc<markup5ever_rcdom::Node>) -> Option<Block> {
+        let depth = self.quote_depth;
+        self.quote_depth += 1;
+        let attribution_node = Self::find_quote_attribution(node);
+        let attribution = attribution_node
+            .as_ref()
+            .map(|child| Self::strip_attribution_dash(self.extract_formatted_content(child)))
+            .filter(|text| !text.plain_text().trim().is_empty());
+        let content = if attribution.is_some() {
+            self.extract_container_blocks_except(node, attribution_node.as_ref())
+        } else {
+            self.extract_container_blocks(node)
+        };
+        self.quote_depth = depth;
+
+        (!content.is_empty()).then_some(Block::Quote {
+            content,
+            attribution,
+            depth,
+        })
+    }
+
+    /// `<details>` or an accordion as a collapsible block; without a summary
+    /// element the summary reads "Details", as in browsers
+    fn handle_collapsible(
+        &mut self,
+        attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
+        node: &Rc<markup5ever_rcdom::Node>,
+        summary_node: Option<Rc<markup5ever_rcdom::Node>>,
+        document: &mut Document,
+    ) {
+        let summary = summary_node
+            .as_ref()
+            .map(|child| self.extract_formatted_content(child))
+            .filter(|text| !text.plain_text().trim().is_empty())
+            .unwrap_or_else(|| Text::from("Details"));
+        let content = self.extract_container_blocks_except(node, summary_node.as_ref());
+
+        let id = self.get_attr_value(attrs, "id");
+        let collapsible = Block::Collapsible { summary, content };
+        document
+            .blocks
+            .push(Node::new_with_id(collapsible, 0..0, id));
+    }
+
+    /// Class-based accordions (`class="accordion"`, `class="collapsible"`)
+    fn is_accordion(&self, attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>) -> bool {
+        self.get_attr_value(attrs, "class").is_some_and(|class| {
+            class
+                .split_whitespace()
+                .any(|token| matches!(token, "accordion" | "collapsible"))
+        })
+    }
+
+    /// The last element child of a blockquote, if it is a `<footer>` or `<cite>`
+    fn find_quote_attribution(
+        node: &Rc<markup5ever_rcdom::Node>,
+    ) -> Option<Rc<markup5ever_rcdom::Node>> {
+        let children = node.children.borrow();
+        for child in children.iter().rev() {
+            match &child.data {
+                NodeData::Element { name, .. } => {
+                    return matches!(name.local.as_ref(), "footer" | "cite")
+                        .then(|| Rc::clone(child));
+                }
+                NodeData::Text { contents } if contents.borrow().trim().is_empty() => {}
+                NodeData::Comment { .. } => {}
+                _ => return None,
+            }
+        }
+        None
+    }
+
+    /// Drop a leading dash ("—", "–", "-") and whitespace; the renderer adds its own
+    fn strip_attribution_dash(mut text: Text) -> Text {
+        for item in text.iter_mut() {
+            match item {
+                TextOrInline::Text(node) => {
+                    let stripped = node
+                        .content
+                        .trim_start_matches(|c: char| c.is_whitespace() || "—–-".contains(c));
+                    if stripped.is_empty() {
+                        node.content.clear();
+                        continue;
+                    }
+                    node.content = stripped.to_string();
+                }
+                TextOrInline::Inline(_) => {}
+            }
+            break;
+        }
+        text
+    }
+
     fn handle_epub_block(
         &mut self,
         element_name: &str,
@@ -1266,7 +1560,7 @@ impl HtmlToMarkdownConverter {
                         "ul" | "ol" => {
                             let kind = self.get_list_kind(tag_name, attrs);
 
-                            let items = self.extract_list_items(child);
+                            let items = self.extract_list_items(child, &kind);
                             if !items.is_empty() {
                                 let list_block = Block::List { kind, items };
                                 content.push(Node::new(list_block, 0..0));
@@ -1547,6 +1841,19 @@ impl HtmlToMarkdownConverter {
                     self.collect_as_text(child, text, sup_context.clone());
                 }
             }
+            "ruby" => {
+                self.push_ruby_element(node, text, context);
+            }
+            "epub:switch" => {
+                if let Some(default) = Self::switch_default_branch(node) {
+                    for child in default.children.borrow().iter() {
+                        self.collect_as_text(child, text, context.clone());
+                    }
+                }
+            }
+            "svg" => {
+                self.push_svg(node, text);
+            }
             "img" => {
                 if let Some(src) = self.get_attr_value(attrs, "src") {
                     let alt_text = self.get_attr_value(attrs, "alt").unwrap_or_default();
@@ -1556,6 +1863,7 @@ impl HtmlToMarkdownConverter {
                         alt_text,
                         url: src,
                         title,
+                        caption: None,
                     };
                     text.push_inline(image_inline);
                 }
@@ -1656,6 +1964,19 @@ impl HtmlToMarkdownConverter {
                     self.collect_as_blocks(child, blocks, current_text, sup_context.clone());
                 }
             }
+            "ruby" => {
+                self.push_ruby_element(node, current_text, context);
+            }
+            "epub:switch" => {
+                if let Some(default) = Self::switch_default_branch(node) {
+                    for child in default.children.borrow().iter() {
+                        self.collect_as_blocks(child, blocks, current_text, context.clone());
+                    }
+                }
+            }
+            "svg" => {
+                self.push_svg(node, current_text);
+            }
             "img" => {
                 if let Some(src) = self.get_attr_value(attrs, "src") {
                     let alt_text = self.get_attr_value(attrs, "alt").unwrap_or_default();
@@ -1665,6 +1986,7 @@ impl HtmlToMarkdownConverter {
                         alt_text,
                         url: src,
                         title,
+                        caption: None,
                     };
                     current_text.push_inline(image_inline);
                 }
@@ -1695,6 +2017,109 @@ impl HtmlToMarkdownConverter {
         }
     }
 
+    /// The `<epub:default>` of an `<epub:switch>`: the fallback for reading
+    /// systems that support none of its `<epub:case>`s, which is us
+    fn switch_default_branch(
+        node: &Rc<markup5ever_rcdom::Node>,
+    ) -> Option<Rc<markup5ever_rcdom::Node>> {
+        node.children
+            .borrow()
+            .iter()
+            .find(|child| {
+                matches!(&child.data, NodeData::Element { name, .. } if name.local.as_ref() == "epub:default")
+            })
+            .cloned()
+    }
+
+    /// Push an inline `<svg>`: the raster image it wraps, as cover pages do,
+    /// else a label named after its `<title>`. Its text is not book text.
+    fn push_svg(&self, node: &Rc<markup5ever_rcdom::Node>, text: &mut Text) {
+        fn find_element(
+            node: &Rc<markup5ever_rcdom::Node>,
+            tag: &str,
+        ) -> Option<Rc<markup5ever_rcdom::Node>> {
+            node.children
+                .borrow()
+                .iter()
+                .find_map(|child| match &child.data {
+                    NodeData::Element { name, .. } if name.local.as_ref() == tag => {
+                        Some(child.clone())
+                    }
+                    NodeData::Element { .. } => find_element(child, tag),
+                    _ => None,
+                })
+        }
+
+        let mut title = String::new();
+        if let Some(title_node) = find_element(node, "title") {
+            Self::collect_text_from_node(&title_node, &mut title);
+        }
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let href = find_element(node, "image").and_then(|image| match &image.data {
+            // xlink:href and href both have the local name href
+            NodeData::Element { attrs, .. } => self.get_attr_value(attrs, "href"),
+            _ => None,
+        });
+        match href {
+            Some(url) => text.push_inline(Inline::Image {
+                alt_text: title,
+                url,
+                title: None,
+                caption: None,
+            }),
+            None if title.is_empty() => text.push_text(TextNode::new("[image]".to_string(), None)),
+            None => text.push_text(TextNode::new(format!("[image: {title}]"), None)),
+        }
+    }
+
+    /// Push the base/reading pairs of a `<ruby>` element. `<rp>` fallback
+    /// parentheses are dropped; base text without a reading stays plain text.
+    fn push_ruby_element(
+        &self,
+        node: &Rc<markup5ever_rcdom::Node>,
+        text: &mut Text,
+        context: ProcessingContext,
+    ) {
+        let mut base = String::new();
+        for child in node.children.borrow().iter() {
+            let tag = match &child.data {
+                NodeData::Element { name, .. } => Some(name.local.as_ref().to_ascii_lowercase()),
+                _ => None,
+            };
+            match tag.as_deref() {
+                Some("rp") => {}
+                Some("rt") | Some("rtc") => {
+                    let mut reading = String::new();
+                    Self::collect_text_from_node(child, &mut reading);
+                    let base_text = base.trim();
+                    let reading = reading.trim();
+                    if !base_text.is_empty() && !reading.is_empty() {
+                        text.push_inline(Inline::Ruby {
+                            base: base_text.to_string(),
+                            reading: reading.to_string(),
+                        });
+                    } else if !base_text.is_empty() {
+                        text.push_text(TextNode::new(
+                            base_text.to_string(),
+                            context.current_style.clone(),
+                        ));
+                    }
+                    base.clear();
+                }
+                _ => Self::collect_text_from_node(child, &mut base),
+            }
+        }
+
+        let trailing = base.trim();
+        if !trailing.is_empty() {
+            text.push_text(TextNode::new(
+                trailing.to_string(),
+                context.current_style.clone(),
+            ));
+        }
+    }
+
     fn collect_heading_nodes(
         &self,
         level: HeadingLevel,
@@ -1950,6 +2375,35 @@ impl HtmlToMarkdownConverter {
             .map(|attr| attr.value.to_string())
     }
 
+    /// Base text direction declared by `dir`, falling back to the script implied by `lang`
+    fn get_direction_attr(
+        &self,
+        attrs: &std::cell::RefCell<Vec<html5ever::Attribute>>,
+    ) -> Option<TextDirection> {
+        match self
+            .get_attr_value(attrs, "dir")
+            .map(|dir| dir.trim().to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("rtl") => return Some(TextDirection::Rtl),
+            Some("ltr") => return Some(TextDirection::Ltr),
+            _ => {}
+        }
+
+        let lang = self
+            .get_attr_value(attrs, "lang")
+            .or_else(|| self.get_attr_value(attrs, "xml:lang"))?;
+        let primary = lang.split(['-', '_']).next()?.to_ascii_lowercase();
+        if matches!(
+            primary.as_str(),
+            "ar" | "he" | "iw" | "fa" | "ur" | "yi" | "ps" | "sd" | "ug" | "dv" | "ckb"
+        ) {
+            Some(TextDirection::Rtl)
+        } else {
+            Some(TextDirection::Ltr)
+        }
+    }
+
     fn serialize_node_to_html(&self, node: &Rc<markup5ever_rcdom::Node>) -> String {
         fn serialize_node_recursive(node: &Rc<markup5ever_rcdom::Node>, html: &mut String) {
             match node.data {
@@ -3996,4 +4450,353 @@ The protocol operates on multiple layers:
             panic!("oops 2");
         }
     }
+
+    #[test]
+    fn test_dir_attribute_is_inherited_by_blocks() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<body dir="rtl"><p>שלום עולם</p><p dir="ltr">Hello</p></body>"#;
+        let doc = converter.convert(html);
+
+        assert_eq!(doc.blocks.len(), 2);
+        assert_eq!(doc.blocks[0].dir, Some(TextDirection::Rtl));
+        assert_eq!(doc.blocks[1].dir, Some(TextDirection::Ltr));
+    }
+
+    #[test]
+    fn test_lang_attribute_implies_direction() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<div lang="ar-EG"><p>مرحبا</p></div><p>Plain</p>"#;
+        let doc = converter.convert(html);
+
+        assert_eq!(doc.blocks.len(), 2);
+        assert_eq!(doc.blocks[0].dir, Some(TextDirection::Rtl));
+        assert_eq!(doc.blocks[1].dir, None);
+    }
+
+    #[test]
+    fn test_blockquote_footer_becomes_attribution() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<blockquote><p>All happy families are alike.</p>
+            <footer>— Leo Tolstoy</footer></blockquote>"#;
+        let doc = converter.convert(html);
+
+        assert_eq!(doc.blocks.len(), 1);
+        match &doc.blocks[0].block {
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => {
+                assert_eq!(content.len(), 1);
+                assert_eq!(content[0].plain_text(), "All happy families are alike.");
+                assert_eq!(
+                    attribution.as_ref().map(|text| text.plain_text()),
+                    Some("Leo Tolstoy".to_string())
+                );
+            }
+            other => panic!("Expected quote, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_blockquote_without_attribution_is_unchanged() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<blockquote><p>First</p><p>Second <cite>Book</cite> here</p></blockquote>"#;
+        let doc = converter.convert(html);
+
+        match &doc.blocks[0].block {
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => {
+                assert_eq!(content.len(), 2);
+                assert!(attribution.is_none());
+            }
+            other => panic!("Expected quote, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_nested_blockquotes_keep_their_depth() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<blockquote><p>He wrote:</p><blockquote><p>She said:</p>
+            <blockquote>Nothing.</blockquote></blockquote></blockquote>
+            <blockquote><p>Again</p></blockquote>"#;
+        let doc = converter.convert(html);
+
+        let mut depths = Vec::new();
+        let mut block = &doc.blocks[0].block;
+        while let Block::Quote { content, depth, .. } = block {
+            depths.push(*depth);
+            block = &content.last().unwrap().block;
+        }
+        assert_eq!(depths, [0, 1, 2]);
+        match block {
+            Block::Paragraph { content } => assert_eq!(content.plain_text(), "Nothing."),
+            other => panic!("Expected paragraph, got {other:?}"),
+        }
+        assert!(matches!(doc.blocks[1].block, Block::Quote { depth: 0, .. }));
+    }
+
+    fn paragraph_items(doc: &Document) -> Vec<TextOrInline> {
+        match &doc.blocks[0].block {
+            Block::Paragraph { content } => content.iter().cloned().collect(),
+            other => panic!("Expected paragraph, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ruby_pairs_become_ruby_inlines() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html =
+            r#"<p>今日は<ruby>漢<rp>(</rp><rt>かん</rt><rp>)</rp>字<rt>じ</rt></ruby>を読む</p>"#;
+        let doc = converter.convert(html);
+        let items = paragraph_items(&doc);
+
+        let rubies: Vec<(&str, &str)> = items
+            .iter()
+            .filter_map(|item| match item {
+                TextOrInline::Inline(Inline::Ruby { base, reading }) => {
+                    Some((base.as_str(), reading.as_str()))
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(rubies, vec![("漢", "かん"), ("字", "じ")]);
+
+        match &doc.blocks[0].block {
+            Block::Paragraph { content } => assert_eq!(content.plain_text(), "今日は漢字を読む"),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_ruby_with_rb_and_missing_reading() {
+        let mut converter = HtmlToMarkdownConverter::new();
+
+        let html = r#"<p><ruby><rb>東京</rb><rt>とうきょう</rt>駅</ruby></p>"#;
+        let doc = converter.convert(html);
+        let items = paragraph_items(&doc);
+
+        assert!(matches!(
+            &items[0],
+            TextOrInline::Inline(Inline::Ruby { base, reading })
+                if base == "東京" && reading == "とうきょう"
+        ));
+        assert!(matches!(&items[1], TextOrInline::Text(node) if node.content == "駅"));
+    }
+
+    fn dehyphenated_text(html: &str) -> String {
+        let doc = HtmlToMarkdownConverter::new()
+            .with_dehyphenation(true)
+            .convert(html);
+        match &doc.blocks[0].block {
+            Block::Paragraph { content } => content.plain_text(),
+            other => panic!("Expected paragraph, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_dehyphenation_joins_ocr_fragments() {
+        assert_eq!(
+            dehyphenated_text("<p>the informa-\ntion age</p>"),
+            "the information age"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>a compre- hensive survey</p>"),
+            "a comprehensive survey"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>a long <em>exam-</em><br/>ple here</p>"),
+            "a long example here"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>Revolu-<br/>\n tionary ideas</p>"),
+            "Revolutionary ideas"
+        );
+    }
+
+    #[test]
+    fn test_dehyphenation_leaves_real_hyphens_alone() {
+        assert_eq!(
+            dehyphenated_text("<p>state-of-the-art methods</p>"),
+            "state-of-the-art methods"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>truly state-of-the-\nart methods</p>"),
+            "truly state-of-the-art methods"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>in New- York today</p>"),
+            "in New- York today"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>the COVID-\n19 years</p>"),
+            "the COVID- 19 years"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>the B2- b relay</p>"),
+            "the B2- b relay"
+        );
+        assert_eq!(
+            dehyphenated_text("<p>pre- and post-war</p>"),
+            "pre- and post-war"
+        );
+        assert_eq!(dehyphenated_text("<p>yes - no</p>"), "yes - no");
+    }
+
+    #[test]
+    fn test_private_use_glyphs_are_substituted() {
+        let mut converter = HtmlToMarkdownConverter::new();
+        let doc = converter
+            .convert("<p>\u{F0B7} first <a href=\"#n\">\u{F0E0} note</a></p><p>a \u{E042} b</p>");
+
+        assert_eq!(doc.blocks[0].plain_text(), "• first → note");
+        assert_eq!(doc.blocks[1].plain_text(), "a □ b");
+        assert_eq!(converter.unknown_glyphs(), 1);
+    }
+
+    #[test]
+    fn test_details_and_accordions_become_collapsible_blocks() {
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<details><summary>Answer <em>key</em></summary><p>Forty-two.</p><p>Really.</p></details>\
+             <div class=\"ui accordion\"><h4>More</h4><p>Hidden text.</p></div>",
+        );
+        assert_eq!(doc.blocks.len(), 2);
+        match &doc.blocks[0].block {
+            Block::Collapsible { summary, content } => {
+                assert_eq!(summary.plain_text(), "Answer key");
+                assert_eq!(content.len(), 2);
+                assert_eq!(content[0].plain_text(), "Forty-two.");
+            }
+            other => panic!("Expected collapsible, got {other:?}"),
+        }
+        match &doc.blocks[1].block {
+            Block::Collapsible { summary, content } => {
+                assert_eq!(summary.plain_text(), "More");
+                assert_eq!(content.len(), 1);
+                assert_eq!(content[0].plain_text(), "Hidden text.");
+            }
+            other => panic!("Expected collapsible, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_figcaption_is_attached_to_the_figure_image() {
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<figure><img src=\"whale.png\" alt=\"Whale\"/>\
+             <figcaption>Figure 1. The <em>white</em>\n   whale</figcaption></figure>\
+             <p><img src=\"plain.png\"/></p>",
+        );
+        let captions: Vec<Option<String>> = doc
+            .blocks
+            .iter()
+            .filter_map(|node| match &node.block {
+                Block::Paragraph { content } => content.iter().find_map(|item| match item {
+                    TextOrInline::Inline(Inline::Image { caption, .. }) => Some(caption.clone()),
+                    _ => None,
+                }),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            captions,
+            vec![Some("Figure 1. The white whale".to_string()), None]
+        );
+    }
+
+    #[test]
+    fn test_container_ids_anchor_their_first_block() {
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<div id=\"sec-4\"><p>Section four.</p></div>\
+             <section id=\"sec-5\"><p id=\"own\">Section five.</p></section>",
+        );
+        assert_eq!(doc.blocks[0].id.as_deref(), Some("sec-4"));
+        assert_eq!(doc.blocks[1].id.as_deref(), Some("own"));
+        let Block::Paragraph { content } = &doc.blocks[1].block else {
+            panic!("expected a paragraph");
+        };
+        assert!(matches!(
+            content.iter().next(),
+            Some(TextOrInline::Inline(Inline::Anchor { id })) if id == "sec-5"
+        ));
+    }
+
+    #[test]
+    fn test_dehyphenation_is_off_by_default() {
+        let doc = HtmlToMarkdownConverter::new().convert("<p>informa- tion</p>");
+        match &doc.blocks[0].block {
+            Block::Paragraph { content } => assert_eq!(content.plain_text(), "informa- tion"),
+            other => panic!("Expected paragraph, got {other:?}"),
+        }
+    }
+
+    fn paragraph_texts(doc: &Document) -> Vec<String> {
+        doc.blocks
+            .iter()
+            .filter_map(|node| match &node.block {
+                Block::Paragraph { content } => Some(content.plain_text()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_indesign_xhtml_keeps_text_around_odd_constructs() {
+        // Entities declared in the DOCTYPE and a self-closed <title/>,
+        // which used to swallow the whole chapter
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+             <!DOCTYPE html [\n<!ENTITY mdash \"&#8212;\">\n<!ENTITY publisher \"Ink Press\">\n]>\n\
+             <html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\">\
+             <head><title/><link href=\"css/idGeneratedStyles.css\" rel=\"stylesheet\" type=\"text/css\"/>\
+             </head><body id=\"ch01\"><div class=\"Basic-Text-Frame\">\
+             <p class=\"Body\">Printed by &publisher;&nbsp;in 1998&mdash;twice.</p>\
+             <a id=\"_idTextAnchor003\"/><p class=\"Body\">The rest of the chapter.</p>\
+             </div></body></html>",
+        );
+        assert_eq!(
+            paragraph_texts(&doc),
+            [
+                "Printed by Ink Press in 1998—twice.",
+                "The rest of the chapter."
+            ]
+        );
+
+        // Only the default branch of an epub:switch, inline SVG as an image
+        let doc = HtmlToMarkdownConverter::new().convert(
+            "<html><body><p>Before the formula.</p>\
+             <epub:switch id=\"mathml-1\"><epub:case required-namespace=\"http://www.w3.org/1998/Math/MathML\">\
+             <p>MathML branch</p></epub:case><epub:default><p>x = 2</p></epub:default></epub:switch>\
+             <p>Inline <svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 10 10\">\
+             <title>Sales chart</title><rect width=\"10\" height=\"10\"/><text x=\"1\">42</text></svg> figure.</p>\
+             <div><svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\">\
+             <image width=\"600\" height=\"800\" xlink:href=\"../image/cover.jpg\"/></svg></div>\
+             <p>After the cover.</p></body></html>",
+        );
+        assert_eq!(
+            paragraph_texts(&doc),
+            [
+                "Before the formula.",
+                "x = 2",
+                "Inline [image: Sales chart] figure.",
+                "",
+                "After the cover."
+            ]
+        );
+        let Block::Paragraph { content } = &doc.blocks[3].block else {
+            panic!("expected the cover image");
+        };
+        assert!(matches!(
+            content.iter().next(),
+            Some(TextOrInline::Inline(Inline::Image { url, .. })) if url == "../image/cover.jpg"
+        ));
+    }
 }
@@ -0,0 +1,118 @@
+//! Strips soft hyphens and zero-width characters.
+//!
+//! Many EPUBs scatter U+00AD (soft hyphen) and zero-width spaces through
+//! their text as line-breaking hints. A terminal shows them as stray marks
+//! or nothing at all, and they split words for search: "co\u{00AD}operate"
+//! is not "cooperate". Search always ignores them; display keeps them unless
+//! `strip_invisible_chars` is set. Zero-width joiners and non-joiners are
+//! left alone since they shape emoji and scripts such as Persian.
+
+use crate::markdown::{Block, Document, Inline, Node, Text, TextOrInline};
+use std::borrow::Cow;
+
+/// Soft hyphen, zero-width space, word joiner and zero-width no-break space (BOM)
+pub fn is_invisible(c: char) -> bool {
+    matches!(c, '\u{00AD}' | '\u{200B}' | '\u{2060}' | '\u{FEFF}')
+}
+
+/// The text without invisible characters, borrowed when it has none
+pub fn strip_invisible(text: &str) -> Cow<'_, str> {
+    if text.chars().any(is_invisible) {
+        Cow::Owned(text.chars().filter(|c| !is_invisible(*c)).collect())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+pub fn strip_invisible_document(document: &mut Document) {
+    strip_nodes(&mut document.blocks);
+}
+
+fn strip_nodes(nodes: &mut [Node]) {
+    for node in nodes {
+        strip_block(&mut node.block);
+    }
+}
+
+fn strip_block(block: &mut Block) {
+    match block {
+        Block::Heading { content, .. } | Block::Paragraph { content } => strip_text(content),
+        Block::CodeBlock { content, .. } => strip_string(content),
+        Block::Quote {
+            content,
+            attribution,
+            ..
+        } => {
+            strip_nodes(content);
+            if let Some(attribution) = attribution {
+                strip_text(attribution);
+            }
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                strip_nodes(&mut item.content);
+            }
+        }
+        Block::Table { header, rows, .. } => {
+            for row in header.iter_mut().chain(rows.iter_mut()) {
+                for cell in &mut row.cells {
+                    strip_text(&mut cell.content);
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                strip_text(&mut item.term);
+                for definition in &mut item.definitions {
+                    strip_nodes(definition);
+                }
+            }
+        }
+        Block::EpubBlock { content, .. } => strip_nodes(content),
+        Block::Collapsible { summary, content } => {
+            strip_text(summary);
+            strip_nodes(content);
+        }
+        Block::ThematicBreak => {}
+    }
+}
+
+fn strip_text(text: &mut Text) {
+    for item in text.iter_mut() {
+        match item {
+            TextOrInline::Text(node) => strip_string(&mut node.content),
+            TextOrInline::Inline(Inline::Link { text, .. }) => strip_text(text),
+            TextOrInline::Inline(Inline::Image { alt_text, .. }) => strip_string(alt_text),
+            TextOrInline::Inline(Inline::Ruby { base, reading }) => {
+                strip_string(base);
+                strip_string(reading);
+            }
+            TextOrInline::Inline(_) => {}
+        }
+    }
+}
+
+fn strip_string(content: &mut String) {
+    if let Cow::Owned(stripped) = strip_invisible(content) {
+        *content = stripped;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_soft_hyphens_and_zero_width_spaces_are_stripped() {
+        assert!(matches!(strip_invisible("plain"), Cow::Borrowed("plain")));
+        assert_eq!(
+            strip_invisible("co\u{00AD}operate\u{200B}/\u{2060}\u{FEFF}x"),
+            "cooperate/x"
+        );
+        // Joiners shape emoji and some scripts, so they stay
+        assert_eq!(
+            strip_invisible("a\u{200D}b\u{200C}c"),
+            "a\u{200D}b\u{200C}c"
+        );
+    }
+}
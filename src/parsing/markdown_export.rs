@@ -0,0 +1,334 @@
+//! Markdown for chapters exported to notes.
+//!
+//! Unlike [`MarkdownRenderer`](super::markdown_renderer::MarkdownRenderer),
+//! which keeps the debug notation of the HTML→Markdown→Text pipeline, this
+//! writes CommonMark with GFM tables that other tools read back: headings
+//! keep their case, tables get a header row, links into the book drop to
+//! their text, and image URLs go through a callback so they can point at
+//! files extracted next to the export.
+
+use crate::markdown::{
+    Block, Inline, LinkType, ListItem, ListKind, Node, Style, TableAlignment, TableRow, TaskStatus,
+    Text, TextNode, TextOrInline,
+};
+
+/// Markdown for `nodes`, with each image URL replaced by `image_url`'s
+pub fn export_markdown(nodes: &[Node], image_url: &dyn Fn(&str) -> String) -> String {
+    let exporter = MarkdownExporter { image_url };
+    let mut output = exporter.blocks(nodes);
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    output
+}
+
+struct MarkdownExporter<'a> {
+    image_url: &'a dyn Fn(&str) -> String,
+}
+
+impl MarkdownExporter<'_> {
+    /// Blocks separated by blank lines, without a trailing newline
+    fn blocks(&self, nodes: &[Node]) -> String {
+        nodes
+            .iter()
+            .map(|node| self.block(&node.block))
+            .filter(|block| !block.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
+
+    fn block(&self, block: &Block) -> String {
+        match block {
+            Block::Heading { level, content } => {
+                let hashes = "#".repeat(level.as_u8() as usize);
+                format!("{hashes} {}", single_line(&self.text(content)))
+            }
+            Block::Paragraph { content } => self.text(content).trim().to_string(),
+            Block::CodeBlock { language, content } => {
+                let fence = "`".repeat(longest_run(content, '`').max(2) + 1);
+                let language = language.as_deref().unwrap_or("");
+                format!(
+                    "{fence}{language}\n{}\n{fence}",
+                    content.trim_end_matches('\n')
+                )
+            }
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => {
+                let mut inner = self.blocks(content);
+                if let Some(attribution) = attribution {
+                    inner.push_str(&format!("\n\n— {}", self.text(attribution).trim()));
+                }
+                prefix_lines(&inner, "> ", ">")
+            }
+            Block::List { kind, items } => self.list(kind, items),
+            Block::Table {
+                header,
+                rows,
+                alignment,
+            } => self.table(header.as_ref(), rows, alignment),
+            Block::DefinitionList { items } => items
+                .iter()
+                .map(|item| {
+                    let mut entry = format!("**{}**", single_line(&self.text(&item.term)));
+                    for definition in &item.definitions {
+                        entry.push('\n');
+                        entry.push_str(&indent_item(&self.blocks(definition), ": "));
+                    }
+                    entry
+                })
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            // The epub:type (asides, footnotes) has no Markdown equivalent
+            Block::EpubBlock { content, .. } => self.blocks(content),
+            Block::Collapsible { summary, content } => format!(
+                "<details>\n<summary>{}</summary>\n\n{}\n\n</details>",
+                single_line(&self.text(summary)),
+                self.blocks(content)
+            ),
+            Block::ThematicBreak => "---".to_string(),
+        }
+    }
+
+    fn list(&self, kind: &ListKind, items: &[ListItem]) -> String {
+        // Items of a single paragraph each make a tight list
+        let tight = items.iter().all(|item| item.content.len() <= 1);
+        items
+            .iter()
+            .enumerate()
+            .map(|(index, item)| {
+                let mut marker = match item.number_in(kind, index) {
+                    Some(number) => format!("{number}. "),
+                    None => "- ".to_string(),
+                };
+                match item.task_status {
+                    Some(TaskStatus::Checked) => marker.push_str("[x] "),
+                    Some(TaskStatus::Unchecked) => marker.push_str("[ ] "),
+                    None => {}
+                }
+                indent_item(&self.blocks(&item.content), &marker)
+            })
+            .collect::<Vec<_>>()
+            .join(if tight { "\n" } else { "\n\n" })
+    }
+
+    fn table(
+        &self,
+        header: Option<&TableRow>,
+        rows: &[TableRow],
+        alignment: &[TableAlignment],
+    ) -> String {
+        let columns = header
+            .into_iter()
+            .chain(rows)
+            .map(|row| row.cells.len())
+            .max()
+            .unwrap_or(0);
+        if columns == 0 {
+            return String::new();
+        }
+        let row_line = |row: Option<&TableRow>| {
+            let cells: Vec<String> = (0..columns)
+                .map(|column| {
+                    row.and_then(|row| row.cells.get(column))
+                        .map(|cell| {
+                            let content = self.text(&cell.content);
+                            single_line(&content).replace('|', "\\|")
+                        })
+                        .unwrap_or_default()
+                })
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+
+        // GFM needs a header row, so headerless tables get an empty one
+        let mut lines = vec![row_line(header)];
+        let separator: Vec<&str> = (0..columns)
+            .map(|column| match alignment.get(column) {
+                Some(TableAlignment::Left) => ":---",
+                Some(TableAlignment::Center) => ":---:",
+                Some(TableAlignment::Right) => "---:",
+                Some(TableAlignment::None) | None => "---",
+            })
+            .collect();
+        lines.push(format!("| {} |", separator.join(" | ")));
+        lines.extend(rows.iter().map(|row| row_line(Some(row))));
+        lines.join("\n")
+    }
+
+    fn text(&self, text: &Text) -> String {
+        let mut output = String::new();
+        for item in text.iter() {
+            match item {
+                TextOrInline::Text(node) => output.push_str(&text_node(node)),
+                TextOrInline::Inline(inline) => self.inline(inline, &mut output),
+            }
+        }
+        output
+    }
+
+    fn inline(&self, inline: &Inline, output: &mut String) {
+        match inline {
+            Inline::Link {
+                text,
+                url,
+                title,
+                link_type,
+                ..
+            } => {
+                let text = self.text(text);
+                // Links into the book lead nowhere outside it
+                if *link_type != LinkType::External {
+                    output.push_str(&text);
+                    return;
+                }
+                output.push_str(&format!("[{text}]({url}"));
+                if let Some(title) = title {
+                    output.push_str(&format!(" \"{}\"", title.replace('"', "\\\"")));
+                }
+                output.push(')');
+            }
+            Inline::Image { alt_text, url, .. } => {
+                output.push_str(&format!(
+                    "![{}]({})",
+                    escape(alt_text),
+                    (self.image_url)(url).replace(' ', "%20")
+                ));
+            }
+            Inline::Ruby { base, reading } => {
+                output.push_str(&format!("<ruby>{base}<rt>{reading}</rt></ruby>"));
+            }
+            Inline::LineBreak => output.push_str("\\\n"),
+            Inline::SoftBreak => output.push('\n'),
+            Inline::Anchor { .. } => {}
+        }
+    }
+}
+
+fn text_node(node: &TextNode) -> String {
+    let Some(style) = &node.style else {
+        return escape(&node.content);
+    };
+    // Emphasis markers can't sit next to the whitespace they enclose
+    let content = node.content.trim();
+    if content.is_empty() {
+        return node.content.clone();
+    }
+    let leading = &node.content[..node.content.len() - node.content.trim_start().len()];
+    let trailing = &node.content[node.content.trim_end().len()..];
+    let styled = match style {
+        Style::Code => {
+            let ticks = "`".repeat(longest_run(content, '`') + 1);
+            let pad = if content.starts_with('`') || content.ends_with('`') {
+                " "
+            } else {
+                ""
+            };
+            format!("{ticks}{pad}{content}{pad}{ticks}")
+        }
+        Style::Emphasis => format!("*{}*", escape(content)),
+        Style::Strong => format!("**{}**", escape(content)),
+        Style::Strikethrough => format!("~~{}~~", escape(content)),
+    };
+    format!("{leading}{styled}{trailing}")
+}
+
+/// Backslash the characters that would otherwise start Markdown syntax
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn longest_run(text: &str, c: char) -> usize {
+    text.split(|other| other != c)
+        .map(str::len)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Headings and table cells hold a single line
+fn single_line(text: &str) -> String {
+    text.replace("\\\n", " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn prefix_lines(text: &str, prefix: &str, blank_prefix: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.is_empty() {
+                blank_prefix.to_string()
+            } else {
+                format!("{prefix}{line}")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// `marker` before the first line and the rest indented to match
+fn indent_item(text: &str, marker: &str) -> String {
+    let indent = " ".repeat(marker.chars().count());
+    let body = prefix_lines(text, &indent, "");
+    format!("{marker}{}", &body[indent.len().min(body.len())..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    fn export(html: &str) -> String {
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        export_markdown(&doc.blocks, &|url| format!("images/{url}"))
+    }
+
+    #[test]
+    fn test_structure_survives_export() {
+        let markdown = export(
+            r#"<h1>Call Me Ishmael</h1>
+            <p>Some <em>years</em> ago, <strong>never mind</strong> how <code>long</code>
+            precisely, see <a href="https://example.com/moby">the text</a> or
+            <a href="ch02.html#loomings">Loomings</a>.</p>
+            <ul><li>Whales</li><li>Ships</li></ul>
+            <ol start="3"><li><p>Third</p><p>More about it</p></li><li><p>Fourth</p></li></ol>
+            <blockquote><p>Call me Ishmael.</p></blockquote>
+            <pre><code>fn main() {}</code></pre>
+            <table><thead><tr><th>Ship</th><th>Captain</th></tr></thead>
+            <tbody><tr><td>Pequod</td><td>Ahab | the captain</td></tr></tbody></table>
+            <p><img src="whale.png" alt="A whale"/></p>
+            <hr/>"#,
+        );
+
+        assert!(markdown.starts_with("# Call Me Ishmael\n\n"), "{markdown}");
+        assert!(markdown.contains("Some *years* ago, **never mind** how `long`"));
+        assert!(markdown.contains("[the text](https://example.com/moby)"));
+        assert!(markdown.contains(" or Loomings."), "{markdown}");
+        assert!(markdown.contains("- Whales\n- Ships"), "{markdown}");
+        assert!(
+            markdown.contains("3. Third\n\n   More about it\n\n4. Fourth"),
+            "{markdown}"
+        );
+        assert!(markdown.contains("> Call me Ishmael."));
+        assert!(markdown.contains("```\nfn main() {}\n```"), "{markdown}");
+        assert!(markdown.contains("| Ship | Captain |\n| --- | --- |\n"));
+        assert!(markdown.contains("| Pequod | Ahab \\| the captain |"));
+        assert!(markdown.contains("![A whale](images/whale.png)"));
+        assert!(markdown.trim_end().ends_with("---"));
+    }
+
+    #[test]
+    fn test_markdown_characters_in_text_are_escaped() {
+        let markdown = export("<p>2 * 3 = [six] and snake_case <code>a`b</code></p>");
+        assert_eq!(markdown, "2 \\* 3 = \\[six\\] and snake\\_case ``a`b``\n");
+    }
+}
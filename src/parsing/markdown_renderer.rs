@@ -72,8 +72,12 @@ impl MarkdownRenderer {
             Block::CodeBlock { language, content } => {
                 self.render_code_block(content, language, output);
             }
-            Block::Quote { content } => {
-                self.render_quote(content, output);
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => {
+                self.render_quote(content, attribution.as_ref(), output);
             }
             Block::List { kind, items } => {
                 self.render_list(kind, items, output, 0);
@@ -95,6 +99,15 @@ impl MarkdownRenderer {
             } => {
                 self.render_epub_block(epub_type, element_name, content, output);
             }
+            Block::Collapsible { summary, content } => {
+                output.push_str("<details>\n<summary>");
+                output.push_str(&self.render_text(summary));
+                output.push_str("</summary>\n\n");
+                for node in content {
+                    self.render_node(node, output);
+                }
+                output.push_str("</details>\n\n");
+            }
             Block::ThematicBreak => {
                 output.push_str("---\n\n");
             }
@@ -145,11 +158,16 @@ impl MarkdownRenderer {
         output.push_str("\n```\n\n");
     }
 
-    fn render_quote(&self, content: &[Node], output: &mut String) {
+    fn render_quote(&self, content: &[Node], attribution: Option<&Text>, output: &mut String) {
         for node in content {
             output.push_str("> ");
             self.render_node(node, output);
         }
+        if let Some(attribution) = attribution {
+            output.push_str("> — ");
+            output.push_str(&self.render_text(attribution));
+            output.push('\n');
+        }
         output.push('\n');
     }
 
@@ -356,12 +374,11 @@ impl MarkdownRenderer {
 
         // Render the list marker
         output.push_str(&indent);
-        match kind {
-            crate::markdown::ListKind::Ordered { start } => {
-                let number = start + index as u32;
+        match item.number_in(kind, index) {
+            Some(number) => {
                 output.push_str(&format!("{number}. "));
             }
-            crate::markdown::ListKind::Unordered => {
+            None => {
                 // Use different bullets for different nesting levels
                 let bullet = match depth % 3 {
                     0 => '-',
@@ -508,6 +525,7 @@ impl MarkdownRenderer {
                 alt_text: _,
                 url,
                 title: _,
+                caption: _,
             } => {
                 // Add spacing around image placeholders
                 if !output.is_empty() && !output.ends_with(' ') && !output.ends_with('\n') {
@@ -528,6 +546,9 @@ impl MarkdownRenderer {
                 output.push_str(url);
                 output.push(')');
             }
+            Inline::Ruby { base, reading } => {
+                output.push_str(&format!("<ruby>{base}<rt>{reading}</rt></ruby>"));
+            }
             Inline::LineBreak => {
                 output.push_str("  \n");
             }
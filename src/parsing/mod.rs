@@ -1,4 +1,12 @@
+pub mod dehyphenate;
+pub mod front_matter;
 pub mod html_to_markdown;
+pub mod invisible;
+pub mod markdown_export;
 pub mod markdown_renderer;
+pub mod plain_text_fallback;
+pub mod private_use;
 pub mod text_generator;
 pub mod toc_parser;
+pub mod typography;
+pub mod xhtml;
@@ -0,0 +1,142 @@
+//! Plain text for chapters the converter makes nothing of.
+//!
+//! Some malformed EPUBs keep their text outside of any paragraph or in
+//! markup the converter doesn't follow, and convert to an empty document.
+//! When the HTML clearly has text, it is shown with the tags stripped, one
+//! paragraph per block element, rather than as a blank chapter.
+
+use crate::markdown::{Block, Document, Inline, Node, Text, TextOrInline};
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+/// Markup whose content is never shown
+static HIDDEN: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?is)<!--.*?-->|<head[\s>].*?</head>|<script[\s>].*?</script>|<style[\s>].*?</style>",
+    )
+    .unwrap()
+});
+
+/// Tags that end a paragraph
+static BREAKS: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)</?(p|div|section|article|h[1-6]|li|tr|blockquote|pre|dt|dd|figure|figcaption|table|ul|ol|body)\b[^>]*>|<br\s*/?>",
+    )
+    .unwrap()
+});
+
+static TAGS: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^>]*>").unwrap());
+
+static ENTITIES: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"&(#[0-9]+|#[xX][0-9a-fA-F]+|[a-zA-Z]+);").unwrap());
+
+/// Whether the document shows anything: text, or an image
+pub fn has_visible_blocks(document: &Document) -> bool {
+    document.blocks.iter().any(|node| {
+        !node.plain_text().trim().is_empty()
+            || matches!(&node.block, Block::Paragraph { content }
+                if content.iter().any(|item| matches!(item, TextOrInline::Inline(Inline::Image { .. }))))
+    })
+}
+
+/// The text of `html` as paragraphs, None when it has no text either
+pub fn plain_text_document(html: &str) -> Option<Document> {
+    let visible = HIDDEN.replace_all(html, "");
+    let broken = BREAKS.replace_all(&visible, "\n\n");
+    let text = TAGS.replace_all(&broken, "");
+
+    let mut document = Document::new();
+    for paragraph in text.split("\n\n") {
+        let paragraph = decode_entities(paragraph)
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ");
+        if !paragraph.is_empty() {
+            let content = Text::from(paragraph);
+            document
+                .blocks
+                .push(Node::new(Block::Paragraph { content }, 0..0));
+        }
+    }
+    if document
+        .blocks
+        .iter()
+        .any(|node| node.plain_text().chars().any(|c| c.is_alphanumeric()))
+    {
+        Some(document)
+    } else {
+        None
+    }
+}
+
+fn decode_entities(text: &str) -> String {
+    ENTITIES
+        .replace_all(text, |caps: &regex::Captures| {
+            let entity = &caps[1];
+            let decoded = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                "mdash" => Some('—'),
+                "ndash" => Some('–'),
+                "hellip" => Some('…'),
+                "lsquo" => Some('‘'),
+                "rsquo" => Some('’'),
+                "ldquo" => Some('“'),
+                "rdquo" => Some('”'),
+                _ if entity.starts_with("#x") || entity.starts_with("#X") => {
+                    u32::from_str_radix(&entity[2..], 16)
+                        .ok()
+                        .and_then(char::from_u32)
+                }
+                _ if entity.starts_with('#') => entity[1..].parse().ok().and_then(char::from_u32),
+                _ => None,
+            };
+            decoded.map_or_else(|| caps[0].to_string(), String::from)
+        })
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    fn paragraphs(document: &Document) -> Vec<String> {
+        document.blocks.iter().map(Node::plain_text).collect()
+    }
+
+    #[test]
+    fn test_loose_text_falls_back_to_paragraphs() {
+        let html = "<html><head><title>Chapter 1</title><style>p { }</style></head>\
+                    <body>Call me <b>Ishmael</b>.<br/>Some years ago &mdash; never\n  mind \
+                    how long &amp; precisely.<!-- note --><div>Having little money</div></body></html>";
+        // The converter drops text outside of paragraphs
+        let converted = HtmlToMarkdownConverter::new().convert(html);
+        assert!(!has_visible_blocks(&converted));
+
+        let fallback = plain_text_document(html).unwrap();
+        assert_eq!(
+            paragraphs(&fallback),
+            vec![
+                "Call me Ishmael.",
+                "Some years ago — never mind how long & precisely.",
+                "Having little money",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chapters_without_text_have_no_fallback() {
+        let cover = r#"<html><head><title>Cover</title></head>
+                       <body><div><img src="cover.jpg"/></div></body></html>"#;
+        assert!(has_visible_blocks(
+            &HtmlToMarkdownConverter::new().convert(cover)
+        ));
+        assert!(plain_text_document(cover).is_none());
+        assert!(plain_text_document("<body> &nbsp; <p> – </p></body>").is_none());
+    }
+}
@@ -0,0 +1,260 @@
+//! Replaces private-use-area characters, which only draw something with the
+//! book's embedded fonts and show up as tofu boxes in a terminal.
+//!
+//! Word and most EPUB toolchains put Symbol and Wingdings glyphs at
+//! U+F020-U+F0FF, and Adobe expert fonts keep old-style figures, small caps
+//! and ligatures at U+F6xx-U+F7xx and U+F001-U+F002. Those get their Unicode
+//! equivalents; anything else in the private use area becomes `□`.
+
+use crate::markdown::{Block, Document, Inline, Node, Text, TextOrInline};
+use log::warn;
+use std::collections::{BTreeMap, HashMap};
+
+/// Stands in for private-use characters without a known equivalent
+pub const UNKNOWN_GLYPH: &str = "□";
+
+const DIGITS: [&str; 10] = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+const LETTERS: [&str; 26] = [
+    "a", "b", "c", "d", "e", "f", "g", "h", "i", "j", "k", "l", "m", "n", "o", "p", "q", "r", "s",
+    "t", "u", "v", "w", "x", "y", "z",
+];
+
+/// Built-in and user substitutions for private-use characters
+#[derive(Debug, Clone, Default)]
+pub struct GlyphMap {
+    custom: HashMap<char, String>,
+}
+
+impl GlyphMap {
+    /// User entries map a hex codepoint ("F0B7", "U+F0B7" or "0xF0B7") to a
+    /// replacement and take precedence over the built-in table. Invalid
+    /// codepoints are logged and skipped.
+    pub fn new(custom: &BTreeMap<String, String>) -> Self {
+        let custom = custom
+            .iter()
+            .filter_map(
+                |(codepoint, replacement)| match parse_codepoint(codepoint) {
+                    Some(c) => Some((c, replacement.clone())),
+                    None => {
+                        warn!("Ignoring glyph substitution for invalid codepoint '{codepoint}'");
+                        None
+                    }
+                },
+            )
+            .collect();
+        GlyphMap { custom }
+    }
+
+    /// The text with private-use characters substituted, or None if it has
+    /// none. `unknown` counts the ones replaced by the placeholder.
+    pub fn substitute(&self, text: &str, unknown: &mut usize) -> Option<String> {
+        if !text.chars().any(is_private_use) {
+            return None;
+        }
+
+        let mut result = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !is_private_use(c) {
+                result.push(c);
+            } else if let Some(replacement) = self.custom.get(&c) {
+                result.push_str(replacement);
+            } else if let Some(replacement) = builtin_substitution(c) {
+                result.push_str(replacement);
+            } else {
+                result.push_str(UNKNOWN_GLYPH);
+                *unknown += 1;
+            }
+        }
+        Some(result)
+    }
+
+    /// Substitute throughout a converted chapter; returns how many characters
+    /// had no known equivalent
+    pub fn substitute_document(&self, document: &mut Document) -> usize {
+        let mut unknown = 0;
+        self.substitute_nodes(&mut document.blocks, &mut unknown);
+        unknown
+    }
+
+    fn substitute_nodes(&self, nodes: &mut [Node], unknown: &mut usize) {
+        for node in nodes {
+            self.substitute_block(&mut node.block, unknown);
+        }
+    }
+
+    fn substitute_block(&self, block: &mut Block, unknown: &mut usize) {
+        match block {
+            Block::Heading { content, .. } | Block::Paragraph { content } => {
+                self.substitute_text(content, unknown)
+            }
+            Block::CodeBlock { content, .. } => self.substitute_string(content, unknown),
+            Block::Quote {
+                content,
+                attribution,
+                ..
+            } => {
+                self.substitute_nodes(content, unknown);
+                if let Some(attribution) = attribution {
+                    self.substitute_text(attribution, unknown);
+                }
+            }
+            Block::List { items, .. } => {
+                for item in items {
+                    self.substitute_nodes(&mut item.content, unknown);
+                }
+            }
+            Block::Table { header, rows, .. } => {
+                for row in header.iter_mut().chain(rows.iter_mut()) {
+                    for cell in &mut row.cells {
+                        self.substitute_text(&mut cell.content, unknown);
+                    }
+                }
+            }
+            Block::DefinitionList { items } => {
+                for item in items {
+                    self.substitute_text(&mut item.term, unknown);
+                    for definition in &mut item.definitions {
+                        self.substitute_nodes(definition, unknown);
+                    }
+                }
+            }
+            Block::EpubBlock { content, .. } => self.substitute_nodes(content, unknown),
+            Block::Collapsible { summary, content } => {
+                self.substitute_text(summary, unknown);
+                self.substitute_nodes(content, unknown);
+            }
+            Block::ThematicBreak => {}
+        }
+    }
+
+    fn substitute_text(&self, text: &mut Text, unknown: &mut usize) {
+        for item in text.iter_mut() {
+            match item {
+                TextOrInline::Text(node) => self.substitute_string(&mut node.content, unknown),
+                TextOrInline::Inline(Inline::Link { text, .. }) => {
+                    self.substitute_text(text, unknown)
+                }
+                TextOrInline::Inline(Inline::Image { alt_text, .. }) => {
+                    self.substitute_string(alt_text, unknown)
+                }
+                TextOrInline::Inline(Inline::Ruby { base, reading }) => {
+                    self.substitute_string(base, unknown);
+                    self.substitute_string(reading, unknown);
+                }
+                TextOrInline::Inline(_) => {}
+            }
+        }
+    }
+
+    fn substitute_string(&self, content: &mut String, unknown: &mut usize) {
+        if let Some(substituted) = self.substitute(content, unknown) {
+            *content = substituted;
+        }
+    }
+}
+
+fn is_private_use(c: char) -> bool {
+    matches!(
+        c,
+        '\u{E000}'..='\u{F8FF}' | '\u{F0000}'..='\u{FFFFD}' | '\u{100000}'..='\u{10FFFD}'
+    )
+}
+
+fn parse_codepoint(value: &str) -> Option<char> {
+    let value = value.trim();
+    let hex = value
+        .strip_prefix("U+")
+        .or_else(|| value.strip_prefix("u+"))
+        .or_else(|| value.strip_prefix("0x"))
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+}
+
+fn builtin_substitution(c: char) -> Option<&'static str> {
+    let replacement = match c {
+        // Adobe ligatures and expert-font old-style figures and small caps
+        '\u{F001}' => "fi",
+        '\u{F002}' => "fl",
+        '\u{F730}'..='\u{F739}' => DIGITS[(c as u32 - 0xF730) as usize],
+        '\u{F761}'..='\u{F77A}' => LETTERS[(c as u32 - 0xF761) as usize],
+        // Wingdings bullets and arrows as Word exports them
+        '\u{F06E}' => "■",
+        '\u{F076}' => "❖",
+        '\u{F0A7}' => "▪",
+        '\u{F0D8}' => "➢",
+        '\u{F0E0}' => "→",
+        '\u{F0FC}' => "✓",
+        // Symbol font
+        '\u{F0A3}' => "≤",
+        '\u{F0A5}' => "∞",
+        '\u{F0AC}' => "←",
+        '\u{F0AD}' => "↑",
+        '\u{F0AE}' => "→",
+        '\u{F0AF}' => "↓",
+        '\u{F0B0}' => "°",
+        '\u{F0B1}' => "±",
+        '\u{F0B3}' => "≥",
+        '\u{F0B4}' => "×",
+        '\u{F0B6}' => "∂",
+        '\u{F0B7}' => "•",
+        '\u{F0B8}' => "÷",
+        '\u{F0B9}' => "≠",
+        '\u{F0BB}' => "≈",
+        '\u{F0BC}' => "…",
+        '\u{F0D6}' => "√",
+        '\u{F0DE}' => "⇒",
+        '\u{F0E5}' => "∑",
+        _ => return None,
+    };
+    Some(replacement)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_glyphs_are_substituted_and_unknown_ones_counted() {
+        let map = GlyphMap::default();
+        let mut unknown = 0;
+
+        assert_eq!(map.substitute("plain text", &mut unknown), None);
+        assert_eq!(
+            map.substitute("\u{F0B7} x \u{F0A3} 2 \u{F0AE} \u{F001}ne", &mut unknown)
+                .as_deref(),
+            Some("• x ≤ 2 → fine")
+        );
+        assert_eq!(
+            map.substitute("\u{F731}\u{F739}\u{F739}\u{F734}", &mut unknown)
+                .as_deref(),
+            Some("1994")
+        );
+        assert_eq!(unknown, 0);
+
+        assert_eq!(
+            map.substitute("a\u{E123}b\u{F0000}", &mut unknown)
+                .as_deref(),
+            Some("a□b□")
+        );
+        assert_eq!(unknown, 2);
+    }
+
+    #[test]
+    fn test_custom_entries_override_the_builtin_table() {
+        let custom = BTreeMap::from([
+            ("U+F0B7".to_string(), "-".to_string()),
+            ("e001".to_string(), "ⓐ".to_string()),
+            ("not hex".to_string(), "?".to_string()),
+        ]);
+        let map = GlyphMap::new(&custom);
+        let mut unknown = 0;
+
+        assert_eq!(
+            map.substitute("\u{F0B7} \u{E001} \u{F0B4}", &mut unknown)
+                .as_deref(),
+            Some("- ⓐ ×")
+        );
+        assert_eq!(unknown, 0);
+    }
+}
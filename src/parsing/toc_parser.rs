@@ -1,6 +1,47 @@
 use crate::table_of_contents::TocItem;
 use epub::doc::{EpubDoc, NavPoint};
 use std::io::{Read, Seek};
+use std::path::{Component, Path, PathBuf};
+
+const EPUB_OPS_NAMESPACE: &str = "http://www.idpf.org/2007/ops";
+
+/// The landmarks of a book this reader goes to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LandmarkKind {
+    Cover,
+    Toc,
+    /// Where the text proper starts, past the front matter
+    BodyMatter,
+}
+
+impl LandmarkKind {
+    /// From an EPUB3 landmark's `epub:type` or an EPUB2 guide reference's `type`
+    fn from_type(kind: &str) -> Option<Self> {
+        match kind.to_ascii_lowercase().as_str() {
+            "cover" => Some(LandmarkKind::Cover),
+            "toc" => Some(LandmarkKind::Toc),
+            "bodymatter" | "text" => Some(LandmarkKind::BodyMatter),
+            _ => None,
+        }
+    }
+
+    /// How the TOC marks the entry the landmark points at
+    pub fn label(self) -> &'static str {
+        match self {
+            LandmarkKind::Cover => "cover",
+            LandmarkKind::Toc => "contents",
+            LandmarkKind::BodyMatter => "start",
+        }
+    }
+}
+
+/// A landmark with its href resolved to a path inside the container
+#[derive(Debug, Clone, PartialEq)]
+pub struct Landmark {
+    pub kind: LandmarkKind,
+    pub href: String,
+    pub anchor: Option<String>,
+}
 
 pub struct TocParser;
 
@@ -21,6 +62,93 @@ impl TocParser {
         Self::convert_navpoints_to_toc_items(&doc.toc)
     }
 
+    /// Landmarks of the EPUB3 nav document, or of the EPUB2 guide when the
+    /// book has no nav landmarks
+    pub fn parse_landmarks<R: Read + Seek>(doc: &mut EpubDoc<R>) -> Vec<Landmark> {
+        let nav_path = doc
+            .resources
+            .values()
+            .find(|resource| {
+                resource
+                    .properties
+                    .as_deref()
+                    .is_some_and(|props| props.split_whitespace().any(|p| p == "nav"))
+            })
+            .map(|resource| resource.path.clone());
+
+        if let Some(nav_path) = nav_path
+            && let Some(nav_html) = doc.get_resource_str_by_path(&nav_path)
+        {
+            let landmarks =
+                Self::resolve_landmarks(&nav_path, Self::parse_nav_landmarks(&nav_html));
+            if !landmarks.is_empty() {
+                return landmarks;
+            }
+        }
+
+        let root_file = doc.root_file.clone();
+        doc.get_resource_str_by_path(&root_file)
+            .map(|opf| Self::resolve_landmarks(&root_file, Self::parse_guide_references(&opf)))
+            .unwrap_or_default()
+    }
+
+    fn resolve_landmarks(base_file: &Path, entries: Vec<(LandmarkKind, String)>) -> Vec<Landmark> {
+        entries
+            .into_iter()
+            .map(|(kind, href)| {
+                let (href, anchor) = Self::split_href_and_anchor(&resolve_href(base_file, &href));
+                Landmark { kind, href, anchor }
+            })
+            .collect()
+    }
+
+    /// Kind and href of the entries of an EPUB3 `<nav epub:type="landmarks">`
+    /// of a kind this reader knows, in document order
+    pub fn parse_nav_landmarks(nav_html: &str) -> Vec<(LandmarkKind, String)> {
+        let Some(document) = parse_xml(nav_html) else {
+            return Vec::new();
+        };
+        let Some(landmarks) = document
+            .descendants()
+            .find(|node| node.has_tag_name("nav") && has_epub_type(node, "landmarks"))
+        else {
+            return Vec::new();
+        };
+        landmarks
+            .descendants()
+            .filter(|node| node.has_tag_name("a"))
+            .filter_map(|node| {
+                let kind = node
+                    .attribute((EPUB_OPS_NAMESPACE, "type"))?
+                    .split_whitespace()
+                    .find_map(LandmarkKind::from_type)?;
+                Some((kind, node.attribute("href")?.to_string()))
+            })
+            .collect()
+    }
+
+    /// Kind and href of the references of an EPUB2 OPF `<guide>`, `text`
+    /// standing for the body matter
+    pub fn parse_guide_references(opf: &str) -> Vec<(LandmarkKind, String)> {
+        let Some(document) = parse_xml(opf) else {
+            return Vec::new();
+        };
+        let Some(guide) = document
+            .descendants()
+            .find(|node| node.has_tag_name("guide"))
+        else {
+            return Vec::new();
+        };
+        guide
+            .children()
+            .filter(|node| node.has_tag_name("reference"))
+            .filter_map(|node| {
+                let kind = LandmarkKind::from_type(node.attribute("type")?)?;
+                Some((kind, node.attribute("href")?.to_string()))
+            })
+            .collect()
+    }
+
     /// Convert NavPoint structure to TocItem structure
     fn convert_navpoints_to_toc_items(navpoints: &[NavPoint]) -> Vec<TocItem> {
         navpoints
@@ -55,10 +183,41 @@ impl TocParser {
     }
 }
 
+fn parse_xml(content: &str) -> Option<roxmltree::Document<'_>> {
+    let options = roxmltree::ParsingOptions {
+        allow_dtd: true,
+        ..Default::default()
+    };
+    roxmltree::Document::parse_with_options(content, options).ok()
+}
+
+fn has_epub_type(node: &roxmltree::Node, wanted: &str) -> bool {
+    node.attribute((EPUB_OPS_NAMESPACE, "type"))
+        .is_some_and(|types| types.split_whitespace().any(|t| t == wanted))
+}
+
+/// Resolve `href` against the directory of `base_file`, dropping `.` and `..`
+fn resolve_href(base_file: &Path, href: &str) -> String {
+    let joined = base_file
+        .parent()
+        .map_or_else(|| PathBuf::from(href), |dir| dir.join(href));
+
+    let mut resolved = PathBuf::new();
+    for component in joined.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            other => resolved.push(other),
+        }
+    }
+    resolved.to_string_lossy().to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::path::PathBuf;
 
     fn create_test_navpoint(label: &str, content: &str, children: Vec<NavPoint>) -> NavPoint {
         NavPoint {
@@ -174,4 +333,59 @@ mod tests {
         assert_eq!(href, "chapter.xhtml");
         assert_eq!(anchor, None);
     }
+
+    #[test]
+    fn test_parse_landmarks_and_guide() {
+        let nav = r##"<?xml version="1.0" encoding="UTF-8"?>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<body>
+  <nav epub:type="toc"><ol><li><a href="cover.xhtml">Cover</a></li></ol></nav>
+  <nav epub:type="landmarks">
+    <ol>
+      <li><a epub:type="cover" href="cover.xhtml">Cover</a></li>
+      <li><a epub:type="loi" href="images.xhtml">Illustrations</a></li>
+      <li><a epub:type="bodymatter" href="../Text/chapter1.xhtml#start">Start</a></li>
+    </ol>
+  </nav>
+</body>
+</html>"##;
+        let landmarks = TocParser::parse_nav_landmarks(nav);
+        assert_eq!(
+            landmarks,
+            vec![
+                (LandmarkKind::Cover, "cover.xhtml".to_string()),
+                (
+                    LandmarkKind::BodyMatter,
+                    "../Text/chapter1.xhtml#start".to_string()
+                ),
+            ]
+        );
+        let resolved = TocParser::resolve_landmarks(Path::new("OEBPS/Nav/nav.xhtml"), landmarks);
+        assert_eq!(
+            resolved[1],
+            Landmark {
+                kind: LandmarkKind::BodyMatter,
+                href: "OEBPS/Text/chapter1.xhtml".to_string(),
+                anchor: Some("start".to_string()),
+            }
+        );
+        assert_eq!(resolved[0].href, "OEBPS/Nav/cover.xhtml");
+
+        let opf = r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="2.0">
+  <guide>
+    <reference type="cover" href="Text/cover.xhtml"/>
+    <reference type="toc" href="Text/contents.xhtml"/>
+    <reference type="text" href="Text/chapter1.xhtml"/>
+  </guide>
+</package>"#;
+        assert_eq!(
+            TocParser::parse_guide_references(opf),
+            vec![
+                (LandmarkKind::Cover, "Text/cover.xhtml".to_string()),
+                (LandmarkKind::Toc, "Text/contents.xhtml".to_string()),
+                (LandmarkKind::BodyMatter, "Text/chapter1.xhtml".to_string()),
+            ]
+        );
+    }
 }
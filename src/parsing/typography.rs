@@ -0,0 +1,237 @@
+//! Typographic punctuation for books typeset with a typewriter's.
+//!
+//! Straight quotes become curly ones, opening or closing by what precedes
+//! them, `--` becomes an em dash, `...` an ellipsis, and runs of spaces
+//! collapse to one. The pass runs on a copy of the chapter when it is laid
+//! out, so the stored document, book-wide search and exports keep the text
+//! as the book has it. Inline code and code blocks are left alone.
+
+use crate::markdown::{Block, Document, Inline, Node, Style, Text, TextOrInline};
+
+/// Characters after which a quote opens rather than closes
+const OPENERS: &[char] = &['(', '[', '{', '—', '–', '-', '/', '“', '‘', '"', '\'', '«'];
+
+pub fn typeset_document(document: &mut Document) {
+    typeset_nodes(&mut document.blocks);
+}
+
+fn typeset_nodes(nodes: &mut [Node]) {
+    for node in nodes {
+        typeset_block(&mut node.block);
+    }
+}
+
+fn typeset_block(block: &mut Block) {
+    match block {
+        Block::Heading { content, .. } | Block::Paragraph { content } => typeset_text(content),
+        Block::Quote {
+            content,
+            attribution,
+            ..
+        } => {
+            typeset_nodes(content);
+            if let Some(attribution) = attribution {
+                typeset_text(attribution);
+            }
+        }
+        Block::List { items, .. } => {
+            for item in items {
+                typeset_nodes(&mut item.content);
+            }
+        }
+        Block::Table { header, rows, .. } => {
+            for row in header.iter_mut().chain(rows.iter_mut()) {
+                for cell in &mut row.cells {
+                    typeset_text(&mut cell.content);
+                }
+            }
+        }
+        Block::DefinitionList { items } => {
+            for item in items {
+                typeset_text(&mut item.term);
+                for definition in &mut item.definitions {
+                    typeset_nodes(definition);
+                }
+            }
+        }
+        Block::EpubBlock { content, .. } => typeset_nodes(content),
+        Block::Collapsible { summary, content } => {
+            typeset_text(summary);
+            typeset_nodes(content);
+        }
+        Block::CodeBlock { .. } | Block::ThematicBreak => {}
+    }
+}
+
+/// Typeset a run of text; quotes look back across its styled pieces
+fn typeset_text(text: &mut Text) {
+    let mut typesetter = Typesetter::default();
+    typesetter.text(text);
+}
+
+#[derive(Default)]
+struct Typesetter {
+    /// The last character written, None at the start of the text
+    previous: Option<char>,
+}
+
+impl Typesetter {
+    fn text(&mut self, text: &mut Text) {
+        let mut items: Vec<TextOrInline> = std::mem::take(text).into_iter().collect();
+        for item in &mut items {
+            match item {
+                TextOrInline::Text(node) if node.style == Some(Style::Code) => {
+                    if let Some(last) = node.content.chars().last() {
+                        self.previous = Some(last);
+                    }
+                }
+                TextOrInline::Text(node) => node.content = self.typeset(&node.content),
+                TextOrInline::Inline(Inline::Link { text, .. }) => self.text(text),
+                TextOrInline::Inline(Inline::LineBreak | Inline::SoftBreak) => {
+                    self.previous = Some(' ');
+                }
+                TextOrInline::Inline(Inline::Ruby { base, .. }) => {
+                    self.previous = base.chars().last().or(self.previous);
+                }
+                TextOrInline::Inline(Inline::Image { .. } | Inline::Anchor { .. }) => {}
+            }
+        }
+        *text = Text::from(items);
+    }
+
+    fn typeset(&mut self, content: &str) -> String {
+        let chars: Vec<char> = content.chars().collect();
+        let mut result = String::with_capacity(content.len());
+        let mut i = 0;
+
+        while i < chars.len() {
+            let c = chars[i];
+            let run = chars[i..].iter().take_while(|&&other| other == c).count();
+            let typeset = match c {
+                ' ' if self.previous == Some(' ') => None,
+                '"' if self.opens() => Some('“'),
+                '"' => Some('”'),
+                // Years like '90s take an apostrophe
+                '\'' if self.opens() && !chars.get(i + 1).is_some_and(char::is_ascii_digit) => {
+                    Some('‘')
+                }
+                // Apostrophes (don't, dogs') and closing quotes
+                '\'' => Some('’'),
+                '-' if run == 2 || run == 3 => {
+                    i += run - 1;
+                    Some('—')
+                }
+                '.' if run == 3 => {
+                    i += run - 1;
+                    Some('…')
+                }
+                // Longer runs are rules or leaders, kept as printed
+                '-' | '.' => {
+                    result.extend(&chars[i..i + run]);
+                    self.previous = Some(c);
+                    i += run;
+                    continue;
+                }
+                c => Some(c),
+            };
+            if let Some(typeset) = typeset {
+                result.push(typeset);
+                self.previous = Some(typeset);
+            }
+            i += 1;
+        }
+        result
+    }
+
+    /// Whether a quote here opens: at the start, after whitespace or after
+    /// an opening bracket, dash or quote
+    fn opens(&self) -> bool {
+        self.previous
+            .is_none_or(|previous| previous.is_whitespace() || OPENERS.contains(&previous))
+    }
+}
+
+/// `text` with typographic punctuation turned back into the typewriter's,
+/// for copying text as plain ASCII
+pub fn plain_punctuation(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '“' | '”' => plain.push('"'),
+            '‘' | '’' => plain.push('\''),
+            '—' => plain.push_str("--"),
+            '…' => plain.push_str("..."),
+            c => plain.push(c),
+        }
+    }
+    plain
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+
+    fn typeset(html: &str) -> String {
+        let mut doc = HtmlToMarkdownConverter::new().convert(html);
+        typeset_document(&mut doc);
+        doc.blocks
+            .iter()
+            .map(|node| match &node.block {
+                Block::Paragraph { content } | Block::Heading { content, .. } => {
+                    MarkdownTextReader::text_to_string(content)
+                }
+                Block::CodeBlock { content, .. } => content.clone(),
+                _ => String::new(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_quotes_open_and_close_by_context() {
+        assert_eq!(
+            typeset(r#"<p>"Don't," she said, "call me 'Ishmael.'"</p>"#),
+            "“Don’t,” she said, “call me ‘Ishmael.’”"
+        );
+        assert_eq!(
+            typeset(r#"<p>The dogs' bowls ("empty") in the '90s.</p>"#),
+            "The dogs’ bowls (“empty”) in the ’90s."
+        );
+        assert_eq!(
+            typeset(r#"<p>He said "<em>never</em>"--and left.</p>"#),
+            "He said “never”—and left."
+        );
+        assert_eq!(typeset("<p>Say 'yes'! ['no']</p>"), "Say ‘yes’! [‘no’]");
+    }
+
+    #[test]
+    fn test_dashes_ellipses_and_spaces() {
+        assert_eq!(
+            typeset("<p>Wait... what --- now? Well....</p>"),
+            "Wait… what — now? Well...."
+        );
+        assert_eq!(
+            Typesetter::default().typeset("Two  spaces,   three."),
+            "Two spaces, three."
+        );
+        assert_eq!(typeset("<p>a rule: ----------</p>"), "a rule: ----------");
+        assert_eq!(
+            typeset(r#"<p>Run <code>ls -- "x"...</code> "now"</p>"#),
+            "Run ls -- \"x\"... “now”"
+        );
+        assert_eq!(
+            typeset("<pre><code>let s = \"it's\"; // --</code></pre>"),
+            "let s = \"it's\"; // --"
+        );
+    }
+
+    #[test]
+    fn test_plain_punctuation_undoes_typesetting() {
+        assert_eq!(
+            plain_punctuation("“Don’t”—they said…"),
+            "\"Don't\"--they said..."
+        );
+    }
+}
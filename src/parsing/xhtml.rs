@@ -0,0 +1,181 @@
+//! Makes a chapter's XHTML safe to hand to the HTML parser.
+//!
+//! html5ever parses chapters as HTML, which differs from XML in two ways
+//! that blank out books exported by older InDesign versions. A non-void
+//! element closed XML-style, `<title/>` or `<script/>`, is an open tag to
+//! an HTML parser, so everything after it becomes the title or the
+//! script; such tags are written out as an empty element instead. And
+//! entities declared in the DOCTYPE (`<!ENTITY ndash "&#8211;">`) mean
+//! nothing to HTML, so their declarations are dropped and their uses
+//! replaced with the declared text.
+
+use std::borrow::Cow;
+
+/// Elements HTML already treats as empty, where `<br/>` is fine as it is
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+pub fn prepare_xhtml(html: &str) -> Cow<'_, str> {
+    let mut prepared = Cow::Borrowed(html);
+    if let Some(expanded) = expand_declared_entities(&prepared) {
+        prepared = Cow::Owned(expanded);
+    }
+    if let Some(expanded) = expand_self_closing_tags(&prepared) {
+        prepared = Cow::Owned(expanded);
+    }
+    prepared
+}
+
+/// `html` with the DOCTYPE's internal subset removed and the general
+/// entities it declares substituted, None when it declares none
+fn expand_declared_entities(html: &str) -> Option<String> {
+    let doctype_start = find_ignore_case(html, "<!doctype")?;
+    let subset_start = doctype_start + html[doctype_start..].find(['[', '>'])?;
+    if !html[subset_start..].starts_with('[') {
+        return None;
+    }
+    let subset_end = subset_start + html[subset_start..].find(']')?;
+    let doctype_end = subset_end + html[subset_end..].find('>')? + 1;
+
+    let entities = parse_entity_declarations(&html[subset_start + 1..subset_end]);
+    let mut expanded = String::with_capacity(html.len());
+    expanded.push_str(&html[..doctype_start]);
+    expanded.push_str("<!DOCTYPE html>");
+    let mut rest = &html[doctype_end..];
+    while let Some(amp) = rest.find('&') {
+        expanded.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let declared = rest[1..].find(';').and_then(|semicolon| {
+            let name = &rest[1..=semicolon];
+            entities
+                .iter()
+                .find(|(declared, _)| *declared == name)
+                .map(|(_, value)| (semicolon + 2, *value))
+        });
+        match declared {
+            Some((reference_len, value)) => {
+                expanded.push_str(value);
+                rest = &rest[reference_len..];
+            }
+            None => {
+                expanded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    expanded.push_str(rest);
+    Some(expanded)
+}
+
+/// (name, value) of each `<!ENTITY name "value">` in a DTD subset;
+/// parameter entities (`<!ENTITY % name ...>`) are skipped
+fn parse_entity_declarations(subset: &str) -> Vec<(&str, &str)> {
+    subset
+        .split("<!ENTITY")
+        .skip(1)
+        .filter_map(|declaration| {
+            let declaration = declaration.trim_start();
+            if declaration.starts_with('%') {
+                return None;
+            }
+            let name_end = declaration.find(char::is_whitespace)?;
+            let value = declaration[name_end..].trim_start();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            let value_end = value[1..].find(quote)?;
+            Some((&declaration[..name_end], &value[1..=value_end]))
+        })
+        .collect()
+}
+
+/// `html` with each self-closed non-void tag (`<title/>`) written as an
+/// open and a close tag, None when there are none
+fn expand_self_closing_tags(html: &str) -> Option<String> {
+    let mut expanded: Option<String> = None;
+    // How much of `html` is already in `expanded`
+    let mut copied = 0;
+    let mut pos = 0;
+    while let Some(offset) = html[pos..].find('<') {
+        let start = pos + offset;
+        let rest = &html[start + 1..];
+        if rest.starts_with("!--") {
+            pos = start + rest.find("-->").map_or(html.len() - start, |end| end + 4);
+            continue;
+        }
+        if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            pos = start + 1;
+            continue;
+        }
+        let name_len = rest
+            .find(|c: char| c.is_whitespace() || c == '/' || c == '>')
+            .unwrap_or(rest.len());
+        let Some(tag_len) = tag_length(rest) else {
+            break;
+        };
+        let end = start + 1 + tag_len;
+        let name = &rest[..name_len];
+        let self_closed = html[..end - 1].trim_end().ends_with('/');
+        if self_closed && !VOID_ELEMENTS.contains(&name.to_ascii_lowercase().as_str()) {
+            let output = expanded.get_or_insert_with(|| String::with_capacity(html.len() + 64));
+            let slash = html[..end - 1].trim_end().len() - 1;
+            output.push_str(html[copied..slash].trim_end());
+            output.push('>');
+            output.push_str("</");
+            output.push_str(name);
+            output.push('>');
+            copied = end;
+        }
+        pos = end;
+    }
+    let mut expanded = expanded?;
+    expanded.push_str(&html[copied..]);
+    Some(expanded)
+}
+
+/// Length of the tag starting at `tag` (just after its `<`), through its
+/// `>`, skipping any `>` inside quoted attribute values
+fn tag_length(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in tag.char_indices() {
+        match (quote, c) {
+            (Some(open), c) if c == open => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => return Some(index + 1),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn find_ignore_case(haystack: &str, needle: &str) -> Option<usize> {
+    haystack
+        .as_bytes()
+        .windows(needle.len())
+        .position(|window| window.eq_ignore_ascii_case(needle.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_self_closed_tags_are_expanded_and_entities_substituted() {
+        let html = "<!DOCTYPE html [\n<!ENTITY ndash \"&#8211;\">\n<!ENTITY % p \"x\">\n\
+                    <!ENTITY press 'Ink &amp; Co'>\n]>\n<html><head><title/>\
+                    <script src=\"a.js\" /></head><body><a id=\"p1\"/><br/>\
+                    <img alt=\"a/b>\" src=\"c.png\"/><!-- <x/> -->\
+                    <p>1&ndash;2 &press; &amp; &nbsp;</p></body></html>";
+        assert_eq!(
+            prepare_xhtml(html),
+            "<!DOCTYPE html>\n<html><head><title></title>\
+             <script src=\"a.js\"></script></head><body><a id=\"p1\"></a><br/>\
+             <img alt=\"a/b>\" src=\"c.png\"/><!-- <x/> -->\
+             <p>1&#8211;2 Ink &amp; Co &amp; &nbsp;</p></body></html>"
+        );
+
+        let plain = "<p>Nothing to do here.</p>";
+        assert!(matches!(prepare_xhtml(plain), Cow::Borrowed(_)));
+    }
+}
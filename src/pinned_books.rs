@@ -0,0 +1,90 @@
+//! Books pinned to the top of the book list, with `p` on the selected book.
+//!
+//! Pins are kept in `pinned_books.json` next to the bookmarks file, as the
+//! books' paths, and outlast rescans of the library.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+
+pub const PINNED_BOOKS_FILENAME: &str = "pinned_books.json";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Pins {
+    #[serde(default)]
+    pinned: BTreeSet<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct PinnedBooks {
+    pins: Pins,
+    file_path: Option<String>,
+}
+
+impl PinnedBooks {
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        let Some(path) = file_path else {
+            return Self::default();
+        };
+        let pins = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Failed to parse pinned books {path}: {e}");
+                Pins::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Pins::default(),
+            Err(e) => {
+                log::error!("Failed to load pinned books from {path}: {e}");
+                Pins::default()
+            }
+        };
+        Self {
+            pins,
+            file_path: Some(path.to_string()),
+        }
+    }
+
+    pub fn is_pinned(&self, path: &str) -> bool {
+        self.pins.pinned.contains(path)
+    }
+
+    /// Pin the book, or unpin it if it was; returns whether it is pinned now
+    pub fn toggle(&mut self, path: &str) -> bool {
+        if self.pins.pinned.remove(path) {
+            return false;
+        }
+        self.pins.pinned.insert(path.to_string());
+        true
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            fs::write(path, serde_json::to_string_pretty(&self.pins)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggle_and_persist_pins() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join(PINNED_BOOKS_FILENAME);
+        let file = file.to_str().unwrap();
+
+        let mut pins = PinnedBooks::load_or_ephemeral(Some(file));
+        assert!(pins.toggle("/books/a.epub"));
+        assert!(pins.toggle("/books/b.epub"));
+        assert!(!pins.toggle("/books/a.epub"));
+        pins.save().unwrap();
+
+        let pins = PinnedBooks::load_or_ephemeral(Some(file));
+        assert!(!pins.is_pinned("/books/a.epub"));
+        assert!(pins.is_pinned("/books/b.epub"));
+    }
+}
@@ -0,0 +1,373 @@
+use crate::i18n::tr;
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+pub const READING_GOALS_FILENAME: &str = "reading_goals.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum GoalUnit {
+    #[default]
+    Minutes,
+    Lines,
+    Words,
+}
+
+impl GoalUnit {
+    pub fn label(&self) -> &'static str {
+        match self {
+            GoalUnit::Minutes => tr("goal.unit.minutes"),
+            GoalUnit::Lines => tr("goal.unit.lines"),
+            GoalUnit::Words => tr("goal.unit.words"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyGoal {
+    pub target: u32,
+    pub unit: GoalUnit,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BookDayTotals {
+    #[serde(default)]
+    pub read_ms: u64,
+    #[serde(default)]
+    pub lines: u64,
+    /// Words on the lines scrolled past going forward
+    #[serde(default)]
+    pub words: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DayLog {
+    #[serde(default)]
+    pub books: HashMap<String, BookDayTotals>,
+
+    /// Set once the goal was hit and the congratulation was shown
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub goal_celebrated: bool,
+}
+
+impl DayLog {
+    pub fn total_read_ms(&self) -> u64 {
+        self.books.values().map(|totals| totals.read_ms).sum()
+    }
+
+    pub fn total_lines(&self) -> u64 {
+        self.books.values().map(|totals| totals.lines).sum()
+    }
+
+    pub fn total_words(&self) -> u64 {
+        self.books.values().map(|totals| totals.words).sum()
+    }
+
+    pub fn progress(&self, unit: GoalUnit) -> u64 {
+        match unit {
+            GoalUnit::Minutes => self.total_read_ms() / 60_000,
+            GoalUnit::Lines => self.total_lines(),
+            GoalUnit::Words => self.total_words(),
+        }
+    }
+}
+
+/// Per-day reading totals, persisted next to the bookmarks file
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReadingGoals {
+    days: BTreeMap<NaiveDate, DayLog>,
+
+    #[serde(skip)]
+    file_path: Option<String>,
+
+    /// Something was recorded since the last load or save
+    #[serde(skip)]
+    unsaved: bool,
+}
+
+impl ReadingGoals {
+    pub fn ephemeral() -> Self {
+        Self {
+            days: BTreeMap::new(),
+            file_path: None,
+            unsaved: false,
+        }
+    }
+
+    pub fn with_file(file_path: &str) -> Self {
+        Self {
+            days: BTreeMap::new(),
+            file_path: Some(file_path.to_string()),
+            unsaved: false,
+        }
+    }
+
+    pub fn load_or_ephemeral(file_path: Option<&str>) -> Self {
+        match file_path {
+            Some(path) => Self::load_from_file(path).unwrap_or_else(|e| {
+                log::error!("Failed to load reading goals from {path}: {e}");
+                Self::with_file(path)
+            }),
+            None => Self::ephemeral(),
+        }
+    }
+
+    pub fn load_from_file(file_path: &str) -> anyhow::Result<Self> {
+        let path = Path::new(file_path);
+        if path.exists() {
+            let content = fs::read_to_string(path)?;
+            let mut goals = serde_json::from_str::<Self>(&content)
+                .map_err(|e| anyhow::anyhow!("Failed to parse reading goals: {}", e))?;
+            goals.file_path = Some(file_path.to_string());
+            Ok(goals)
+        } else {
+            Ok(Self::with_file(file_path))
+        }
+    }
+
+    /// Write the totals out if anything was recorded since they were last
+    /// loaded or saved
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() || !self.unsaved {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            let content = serde_json::to_string_pretty(self)?;
+            fs::write(path, content)?;
+        }
+        self.unsaved = false;
+        Ok(())
+    }
+
+    pub fn day(&self, date: NaiveDate) -> Option<&DayLog> {
+        self.days.get(&date)
+    }
+
+    /// Record reading time that ended at `end`. Time spanning local midnight
+    /// is split between the two days.
+    pub fn record_time(&mut self, book: &str, end: DateTime<Local>, elapsed: Duration) {
+        let Ok(elapsed) = chrono::Duration::from_std(elapsed) else {
+            return;
+        };
+        let mut start = end - elapsed;
+
+        while start < end {
+            let day = start.date_naive();
+            let chunk_end = day
+                .succ_opt()
+                .and_then(start_of_day)
+                .map_or(end, |next_midnight| next_midnight.min(end));
+            let chunk_ms = (chunk_end - start).num_milliseconds().max(0) as u64;
+
+            self.book_totals(day, book).read_ms += chunk_ms;
+            start = chunk_end;
+        }
+    }
+
+    pub fn record_lines(&mut self, book: &str, at: DateTime<Local>, lines: u64) {
+        self.book_totals(at.date_naive(), book).lines += lines;
+    }
+
+    pub fn record_words(&mut self, book: &str, at: DateTime<Local>, words: u64) {
+        self.book_totals(at.date_naive(), book).words += words;
+    }
+
+    pub fn progress(&self, goal: &DailyGoal, date: NaiveDate) -> u64 {
+        self.days
+            .get(&date)
+            .map(|day| day.progress(goal.unit))
+            .unwrap_or(0)
+    }
+
+    pub fn is_goal_met(&self, goal: &DailyGoal, date: NaiveDate) -> bool {
+        goal.target > 0 && self.progress(goal, date) >= goal.target as u64
+    }
+
+    /// Consecutive days up to `today` on which the goal was met. Today only
+    /// extends the streak once met, so an unfinished day does not break it.
+    pub fn streak(&self, goal: &DailyGoal, today: NaiveDate) -> u32 {
+        let mut date = if self.is_goal_met(goal, today) {
+            today
+        } else {
+            match today.pred_opt() {
+                Some(yesterday) => yesterday,
+                None => return 0,
+            }
+        };
+
+        let mut streak = 0;
+        while self.is_goal_met(goal, date) {
+            streak += 1;
+            match date.pred_opt() {
+                Some(previous) => date = previous,
+                None => break,
+            }
+        }
+        streak
+    }
+
+    /// Returns true exactly once per day: the first time the goal is found met
+    pub fn take_goal_celebration(&mut self, goal: &DailyGoal, date: NaiveDate) -> bool {
+        if !self.is_goal_met(goal, date) {
+            return false;
+        }
+
+        let day = self.days.entry(date).or_default();
+        if day.goal_celebrated {
+            return false;
+        }
+        day.goal_celebrated = true;
+        self.unsaved = true;
+        true
+    }
+
+    fn book_totals(&mut self, date: NaiveDate, book: &str) -> &mut BookDayTotals {
+        self.unsaved = true;
+        self.days
+            .entry(date)
+            .or_default()
+            .books
+            .entry(book.to_string())
+            .or_default()
+    }
+}
+
+fn start_of_day(date: NaiveDate) -> Option<DateTime<Local>> {
+    Local
+        .from_local_datetime(&date.and_hms_opt(0, 0, 0)?)
+        .earliest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn local(y: i32, m: u32, d: u32, h: u32, min: u32, s: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, h, min, s).unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    const MINUTES_GOAL: DailyGoal = DailyGoal {
+        target: 30,
+        unit: GoalUnit::Minutes,
+    };
+
+    #[test]
+    fn test_record_time_splits_at_midnight() {
+        let mut goals = ReadingGoals::ephemeral();
+        goals.record_time(
+            "book.epub",
+            local(2024, 3, 10, 0, 10, 0),
+            Duration::from_secs(20 * 60),
+        );
+
+        let before = goals.day(date(2024, 3, 9)).unwrap();
+        let after = goals.day(date(2024, 3, 10)).unwrap();
+        assert_eq!(before.total_read_ms(), 10 * 60_000);
+        assert_eq!(after.total_read_ms(), 10 * 60_000);
+    }
+
+    #[test]
+    fn test_record_time_ending_at_midnight_stays_on_previous_day() {
+        let mut goals = ReadingGoals::ephemeral();
+        goals.record_time(
+            "book.epub",
+            local(2024, 3, 10, 0, 0, 0),
+            Duration::from_secs(60),
+        );
+
+        assert_eq!(goals.day(date(2024, 3, 9)).unwrap().total_read_ms(), 60_000);
+        assert!(goals.day(date(2024, 3, 10)).is_none());
+    }
+
+    #[test]
+    fn test_multi_book_day_sums_progress() {
+        let mut goals = ReadingGoals::ephemeral();
+        let now = local(2024, 5, 1, 20, 0, 0);
+        goals.record_time("a.epub", now, Duration::from_secs(20 * 60));
+        goals.record_time("b.epub", now, Duration::from_secs(15 * 60));
+        goals.record_lines("a.epub", now, 40);
+        goals.record_lines("b.epub", now, 2);
+
+        let day = goals.day(date(2024, 5, 1)).unwrap();
+        assert_eq!(day.books.len(), 2);
+        assert_eq!(goals.progress(&MINUTES_GOAL, date(2024, 5, 1)), 35);
+        assert!(goals.is_goal_met(&MINUTES_GOAL, date(2024, 5, 1)));
+
+        let lines_goal = DailyGoal {
+            target: 50,
+            unit: GoalUnit::Lines,
+        };
+        assert_eq!(goals.progress(&lines_goal, date(2024, 5, 1)), 42);
+        assert!(!goals.is_goal_met(&lines_goal, date(2024, 5, 1)));
+
+        let words_goal = DailyGoal {
+            target: 500,
+            unit: GoalUnit::Words,
+        };
+        goals.record_words("a.epub", now, 320);
+        assert!(!goals.is_goal_met(&words_goal, date(2024, 5, 1)));
+        goals.record_words("b.epub", now, 180);
+        assert!(goals.take_goal_celebration(&words_goal, date(2024, 5, 1)));
+        // The count starts over the next day
+        assert_eq!(goals.progress(&words_goal, date(2024, 5, 2)), 0);
+    }
+
+    #[test]
+    fn test_streak_counts_consecutive_days() {
+        let mut goals = ReadingGoals::ephemeral();
+        for day in [1, 2, 4, 5, 6] {
+            goals.record_time(
+                "book.epub",
+                local(2024, 5, day, 12, 0, 0),
+                Duration::from_secs(30 * 60),
+            );
+        }
+
+        assert_eq!(goals.streak(&MINUTES_GOAL, date(2024, 5, 6)), 3);
+        // The current day is still in progress, so yesterday's streak holds
+        assert_eq!(goals.streak(&MINUTES_GOAL, date(2024, 5, 7)), 3);
+        assert_eq!(goals.streak(&MINUTES_GOAL, date(2024, 5, 8)), 0);
+    }
+
+    #[test]
+    fn test_goal_celebration_fires_once_per_day() {
+        let mut goals = ReadingGoals::ephemeral();
+        let today = date(2024, 5, 1);
+        assert!(!goals.take_goal_celebration(&MINUTES_GOAL, today));
+
+        goals.record_time(
+            "book.epub",
+            local(2024, 5, 1, 9, 0, 0),
+            Duration::from_secs(31 * 60),
+        );
+        assert!(goals.take_goal_celebration(&MINUTES_GOAL, today));
+        assert!(!goals.take_goal_celebration(&MINUTES_GOAL, today));
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(READING_GOALS_FILENAME);
+        let path = path.to_str().unwrap();
+
+        // Nothing recorded, nothing written
+        let mut goals = ReadingGoals::with_file(path);
+        goals.save().unwrap();
+        assert!(!Path::new(path).exists());
+
+        goals.record_lines("book.epub", local(2024, 5, 1, 9, 0, 0), 12);
+        goals.save().unwrap();
+
+        let loaded = ReadingGoals::load_from_file(path).unwrap();
+        assert_eq!(loaded.day(date(2024, 5, 1)).unwrap().total_lines(), 12);
+    }
+}
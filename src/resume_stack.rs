@@ -0,0 +1,98 @@
+/// Recently read books to cycle through, like alt-tabbing between windows.
+///
+/// The order is captured when cycling starts: reopening a book bumps its
+/// `last_read`, which would otherwise reshuffle the books mid-cycle.
+pub struct ResumeStack {
+    /// Book paths, most recently read first
+    books: Vec<String>,
+    /// Book currently resumed, None until the first step of a cycle
+    position: Option<usize>,
+}
+
+impl ResumeStack {
+    /// Returns None when there is nothing to resume
+    pub fn new(books: Vec<String>, current: Option<&str>) -> Option<Self> {
+        if books.is_empty() {
+            return None;
+        }
+        let position = current.and_then(|path| books.iter().position(|book| book == path));
+        Some(Self { books, position })
+    }
+
+    pub fn len(&self) -> usize {
+        self.books.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.books.is_empty()
+    }
+
+    /// One-based position of the current book in the stack
+    pub fn position(&self) -> Option<usize> {
+        self.position.map(|pos| pos + 1)
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.position.map(|pos| self.books[pos].as_str())
+    }
+
+    /// Step to the book read before the current one, wrapping to the newest
+    pub fn older(&mut self) -> &str {
+        let pos = match self.position {
+            Some(pos) => (pos + 1) % self.books.len(),
+            None => 0,
+        };
+        self.position = Some(pos);
+        &self.books[pos]
+    }
+
+    /// Step to the book read after the current one, wrapping to the oldest
+    pub fn newer(&mut self) -> &str {
+        let pos = match self.position {
+            Some(0) | None => self.books.len() - 1,
+            Some(pos) => pos - 1,
+        };
+        self.position = Some(pos);
+        &self.books[pos]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn books() -> Vec<String> {
+        vec![
+            "c.epub".to_string(),
+            "b.epub".to_string(),
+            "a.epub".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_older_walks_back_from_current_book_and_wraps() {
+        let mut stack = ResumeStack::new(books(), Some("c.epub")).unwrap();
+        assert_eq!(stack.older(), "b.epub");
+        assert_eq!(stack.older(), "a.epub");
+        assert_eq!(stack.older(), "c.epub");
+        assert_eq!(stack.position(), Some(1));
+    }
+
+    #[test]
+    fn test_newer_returns_towards_newest() {
+        let mut stack = ResumeStack::new(books(), Some("c.epub")).unwrap();
+        stack.older();
+        stack.older();
+        assert_eq!(stack.newer(), "b.epub");
+        assert_eq!(stack.newer(), "c.epub");
+        assert_eq!(stack.newer(), "a.epub");
+    }
+
+    #[test]
+    fn test_without_open_book_older_resumes_most_recent() {
+        let mut stack = ResumeStack::new(books(), None).unwrap();
+        assert_eq!(stack.current(), None);
+        assert_eq!(stack.older(), "c.epub");
+        assert!(ResumeStack::new(Vec::new(), None).is_none());
+    }
+}
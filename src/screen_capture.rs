@@ -0,0 +1,174 @@
+//! Saving what is on screen for bug reports (Space+?).
+//!
+//! The frame just drawn is written to the export directory twice: as ANSI
+//! text, which `cat` in a terminal shows with its colors, and as plain text
+//! for pasting into an issue. Builds with the `devtools` feature write an
+//! SVG of it as well, the way the snapshot tests render screens.
+
+use anyhow::{Context, Result};
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Each row of the buffer with the ANSI codes for its colors and styles
+pub fn buffer_to_ansi(buffer: &Buffer) -> String {
+    let mut ansi_output = String::new();
+
+    for y in 0..buffer.area.height {
+        for x in 0..buffer.area.width {
+            let cell = &buffer[(buffer.area.x + x, buffer.area.y + y)];
+
+            // Reset first
+            ansi_output.push_str("\u{1b}[0m");
+
+            if cell.fg != Color::Reset {
+                ansi_output.push_str(&format_color(cell.fg, true));
+            }
+            if cell.bg != Color::Reset {
+                ansi_output.push_str(&format_color(cell.bg, false));
+            }
+
+            if cell.modifier.contains(Modifier::BOLD) {
+                ansi_output.push_str("\u{1b}[1m");
+            }
+            if cell.modifier.contains(Modifier::ITALIC) {
+                ansi_output.push_str("\u{1b}[3m");
+            }
+            if cell.modifier.contains(Modifier::UNDERLINED) {
+                ansi_output.push_str("\u{1b}[4m");
+            }
+
+            ansi_output.push_str(cell.symbol());
+        }
+
+        // Add newline and reset at end of line
+        if y < buffer.area.height - 1 {
+            ansi_output.push_str("\u{1b}[0m\n");
+        }
+    }
+
+    // Final reset
+    ansi_output.push_str("\u{1b}[0m");
+    ansi_output
+}
+
+/// The buffer's text without styles, trailing blanks trimmed from each row
+pub fn buffer_to_text(buffer: &Buffer) -> String {
+    let mut lines = Vec::new();
+    for y in 0..buffer.area.height {
+        let mut line = String::new();
+        // Cells a wide character covers hold a blank the terminal never shows
+        let mut covered = 0;
+        for x in 0..buffer.area.width {
+            if covered > 0 {
+                covered -= 1;
+                continue;
+            }
+            let symbol = buffer[(buffer.area.x + x, buffer.area.y + y)].symbol();
+            covered = textwrap::core::display_width(symbol).saturating_sub(1);
+            line.push_str(symbol);
+        }
+        lines.push(line.trim_end().to_string());
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+pub fn format_color(color: Color, is_foreground: bool) -> String {
+    let base = if is_foreground { 30 } else { 40 };
+
+    match color {
+        Color::Reset => "\u{1b}[0m".to_string(),
+        Color::Black => format!("\u{1b}[{base}m"),
+        Color::Red => format!("\u{1b}[{}m", base + 1),
+        Color::Green => format!("\u{1b}[{}m", base + 2),
+        Color::Yellow => format!("\u{1b}[{}m", base + 3),
+        Color::Blue => format!("\u{1b}[{}m", base + 4),
+        Color::Magenta => format!("\u{1b}[{}m", base + 5),
+        Color::Cyan => format!("\u{1b}[{}m", base + 6),
+        Color::Gray => format!("\u{1b}[{}m", base + 7),
+        Color::DarkGray => format!("\u{1b}[{}m", base + 60),
+        Color::LightRed => format!("\u{1b}[{}m", base + 61),
+        Color::LightGreen => format!("\u{1b}[{}m", base + 62),
+        Color::LightYellow => format!("\u{1b}[{}m", base + 63),
+        Color::LightBlue => format!("\u{1b}[{}m", base + 64),
+        Color::LightMagenta => format!("\u{1b}[{}m", base + 65),
+        Color::LightCyan => format!("\u{1b}[{}m", base + 66),
+        Color::White => format!("\u{1b}[{}m", base + 67),
+        Color::Rgb(r, g, b) => {
+            if is_foreground {
+                format!("\u{1b}[38;2;{r};{g};{b}m")
+            } else {
+                format!("\u{1b}[48;2;{r};{g};{b}m")
+            }
+        }
+        Color::Indexed(idx) => {
+            if is_foreground {
+                format!("\u{1b}[38;5;{idx}m")
+            } else {
+                format!("\u{1b}[48;5;{idx}m")
+            }
+        }
+    }
+}
+
+/// Write the screen into `directory` as `bookokrat-screen-<time>.ansi` and
+/// `.txt` (and `.svg` with devtools); returns the paths written
+pub fn write_capture(directory: &Path, buffer: &Buffer) -> Result<Vec<PathBuf>> {
+    fs::create_dir_all(directory)
+        .with_context(|| format!("cannot create {}", directory.display()))?;
+    let stem = format!(
+        "bookokrat-screen-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    );
+
+    let ansi = buffer_to_ansi(buffer);
+    #[cfg_attr(not(feature = "devtools"), allow(unused_mut))]
+    let mut files = vec![("ansi", ansi.clone()), ("txt", buffer_to_text(buffer))];
+    #[cfg(feature = "devtools")]
+    files.push(("svg", anstyle_svg::Term::new().render_svg(&ansi)));
+
+    let mut paths = Vec::new();
+    for (extension, content) in files {
+        let path = directory.join(format!("{stem}.{extension}"));
+        fs::write(&path, content).with_context(|| format!("cannot write {}", path.display()))?;
+        paths.push(path);
+    }
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    #[test]
+    fn test_capture_keeps_text_and_styles() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 12, 3));
+        buffer.set_string(0, 0, "Moby", Style::default().fg(Color::Red));
+        buffer.set_string(5, 0, "Dick", Style::default().add_modifier(Modifier::BOLD));
+        buffer.set_string(0, 1, "鯨 whale", Style::default().bg(Color::Rgb(1, 2, 3)));
+
+        assert_eq!(buffer_to_text(&buffer), "Moby Dick\n鯨 whale\n");
+
+        let ansi = buffer_to_ansi(&buffer);
+        assert!(ansi.starts_with("\u{1b}[0m\u{1b}[31mM"));
+        assert!(ansi.contains("\u{1b}[0m\u{1b}[1mD"));
+        assert!(ansi.contains("\u{1b}[48;2;1;2;3m鯨"));
+        assert_eq!(ansi.lines().count(), 3);
+
+        let dir = tempfile::tempdir().unwrap();
+        let paths = write_capture(&dir.path().join("captures"), &buffer).unwrap();
+        assert!(paths[0].extension().is_some_and(|ext| ext == "ansi"));
+        assert_eq!(
+            fs::read_to_string(&paths[1]).unwrap(),
+            "Moby Dick\n鯨 whale\n"
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use crate::parsing::invisible::is_invisible;
+use crate::search_history::HistoryRecall;
+
 /// Search functionality for BookRat
 /// Provides vim-like search with "/" input and "n"/"N" navigation
 
@@ -9,6 +12,8 @@ pub struct SearchState {
     pub matches: Vec<SearchMatch>,
     pub current_match_index: Option<usize>,
     pub original_position: usize, // Position to restore on cancel
+    /// Up/Down stepping through past queries while typing
+    pub history_recall: HistoryRecall,
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
@@ -38,6 +43,7 @@ impl SearchState {
         self.matches.clear();
         self.current_match_index = None;
         self.original_position = current_position;
+        self.history_recall.reset();
     }
 
     pub fn cancel_search(&mut self) -> usize {
@@ -166,27 +172,65 @@ pub trait SearchablePanel {
     fn get_searchable_content(&self) -> Vec<String>; // Extract searchable text
 }
 
+/// Lowercased, with curly quotes matching straight ones so queries typed
+/// with either find both
+fn fold_char(c: char) -> char {
+    match c.to_lowercase().next().unwrap_or(c) {
+        '‘' | '’' | '‚' | '‛' => '\'',
+        '“' | '”' | '„' | '‟' => '"',
+        c => c,
+    }
+}
+
+/// `text` as searches compare it: lowercased with straight quotes, one
+/// character for each of `text`'s
+pub fn fold_for_search(text: &str) -> String {
+    text.chars().map(fold_char).collect()
+}
+
+/// Byte ranges in `text` of every occurrence of `query`, overlapping ones
+/// included, ignoring case, the difference between straight and curly
+/// quotes, and soft hyphens or zero-width spaces inside words
+pub fn match_ranges(text: &str, query: &str) -> Vec<(usize, usize)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !is_invisible(*c))
+        .map(fold_char)
+        .collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    let chars: Vec<(usize, char)> = text
+        .char_indices()
+        .filter(|(_, c)| !is_invisible(*c))
+        .map(|(byte, c)| (byte, fold_char(c)))
+        .collect();
+    if chars.len() < query.len() {
+        return Vec::new();
+    }
+    chars
+        .windows(query.len())
+        .enumerate()
+        .filter(|(_, window)| window.iter().map(|(_, c)| *c).eq(query.iter().copied()))
+        .map(|(start, window)| {
+            let end = chars
+                .get(start + query.len())
+                .map_or(text.len(), |(byte, _)| *byte);
+            (window[0].0, end)
+        })
+        .collect()
+}
+
 /// Helper function to find matches in text (case-insensitive)
 pub fn find_matches_in_text(query: &str, items: &[String]) -> Vec<SearchMatch> {
     if query.is_empty() {
         return Vec::new();
     }
 
-    let query_lower = query.to_lowercase();
     let mut matches = Vec::new();
 
     for (index, item) in items.iter().enumerate() {
-        let item_lower = item.to_lowercase();
-
-        // Find all occurrences of the query in this item
-        let mut highlight_ranges = Vec::new();
-        let mut search_start = 0;
-
-        while let Some(pos) = item_lower[search_start..].find(&query_lower) {
-            let actual_pos = search_start + pos;
-            highlight_ranges.push((actual_pos, actual_pos + query.len()));
-            search_start = actual_pos + 1; // Allow overlapping matches
-        }
+        let highlight_ranges = match_ranges(item, query);
 
         if !highlight_ranges.is_empty() {
             matches.push(SearchMatch {
@@ -225,6 +269,28 @@ mod tests {
         assert_eq!(matches[0].index, 2);
     }
 
+    #[test]
+    fn test_matches_skip_soft_hyphens() {
+        let items = vec!["We co\u{00AD}operate".to_string()];
+
+        let matches = find_matches_in_text("cooperate", &items);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].highlight_ranges, vec![(3, items[0].len())]);
+    }
+
+    #[test]
+    fn test_quotes_match_straight_or_curly() {
+        let items = vec!["“Don’t,” she said.".to_string(), "don't".to_string()];
+
+        let matches = find_matches_in_text("\"don't,\"", &items);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].highlight_ranges, vec![(0, "“Don’t,”".len())]);
+
+        let matches = find_matches_in_text("don’t", &items);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[1].highlight_ranges, vec![(0, 5)]);
+    }
+
     #[test]
     fn test_search_state_navigation() {
         let mut state = SearchState::new();
@@ -1,10 +1,77 @@
+use crate::i18n::tr;
+use crate::parsing::invisible::strip_invisible;
+use crate::search::{fold_for_search, match_ranges};
 use log::debug;
 
+/// What a search result is in, shown as a label and used to filter results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    Text,
+    Figure,
+    Table,
+}
+
+impl MatchKind {
+    pub const ALL: [MatchKind; 3] = [MatchKind::Text, MatchKind::Figure, MatchKind::Table];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MatchKind::Text => tr("search.kind.text"),
+            MatchKind::Figure => tr("search.kind.figure"),
+            MatchKind::Table => tr("search.kind.table"),
+        }
+    }
+}
+
+/// Where a searchable unit sits in its chapter, to scroll to a match in it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatchTarget {
+    /// A line of the chapter's text
+    Text,
+    /// Alt text of the image with this source
+    Figure { src: String },
+    /// A cell of the chapter's `index`-th table, counted in reading order
+    Table { index: usize },
+    /// Anything inside the chapter's `index`-th collapsible block, counted
+    /// in reading order; the block is expanded on the jump
+    Collapsible { index: usize, kind: MatchKind },
+}
+
+impl MatchTarget {
+    pub fn kind(&self) -> MatchKind {
+        match self {
+            MatchTarget::Text => MatchKind::Text,
+            MatchTarget::Figure { .. } => MatchKind::Figure,
+            MatchTarget::Table { .. } => MatchKind::Table,
+            MatchTarget::Collapsible { kind, .. } => *kind,
+        }
+    }
+}
+
+/// One searchable piece of a chapter: a paragraph, an image's alt text or a
+/// table cell
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchUnit {
+    pub text: String,
+    pub target: MatchTarget,
+}
+
+impl SearchUnit {
+    pub fn text(text: impl Into<String>) -> Self {
+        SearchUnit {
+            text: text.into(),
+            target: MatchTarget::Text,
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct BookSearchResult {
     pub chapter_index: usize,
     pub chapter_title: String,
+    /// Index of the matching unit among the chapter's units
     pub line_number: usize,
+    pub target: MatchTarget,
     pub snippet: String,
     pub context_before: String,
     pub context_after: String,
@@ -12,13 +79,151 @@ pub struct BookSearchResult {
     pub match_positions: Vec<usize>,
 }
 
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChapterMatchCount {
+    pub chapter_index: usize,
+    pub chapter_title: String,
+    pub count: usize,
+}
+
+/// A quoted query matches as one exact phrase, anything else word by word
+enum SearchQuery {
+    Words(Vec<String>),
+    Phrase(String),
+}
+
+impl SearchQuery {
+    fn parse(query: &str) -> Option<Self> {
+        let query = strip_invisible(query);
+        let query = query.as_ref();
+        if query.is_empty() {
+            return None;
+        }
+
+        let trimmed = query.trim();
+        if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() > 2 {
+            let phrase = &trimmed[1..trimmed.len() - 1];
+            return Some(SearchQuery::Phrase(fold_for_search(phrase)));
+        }
+
+        let words: Vec<String> = query
+            .split_whitespace()
+            .map(fold_for_search)
+            .filter(|w| !w.is_empty())
+            .collect();
+        (!words.is_empty()).then_some(SearchQuery::Words(words))
+    }
+
+    fn is_match(&self, line: &str) -> bool {
+        match self {
+            SearchQuery::Phrase(phrase) => !match_ranges(line, phrase).is_empty(),
+            SearchQuery::Words(_) => self.match_line(line).is_some(),
+        }
+    }
+
+    /// Score and matched char positions when `line` is a hit
+    fn match_line(&self, line: &str) -> Option<(f64, Vec<usize>)> {
+        match self {
+            SearchQuery::Words(words) => Self::match_words(words, line),
+            SearchQuery::Phrase(phrase) => Self::match_phrase(phrase, line),
+        }
+    }
+
+    fn match_words(query_words: &[String], line: &str) -> Option<(f64, Vec<usize>)> {
+        let line_lower = fold_for_search(line);
+
+        let line_words: Vec<&str> = line_lower.split_whitespace().collect();
+
+        let mut matched_words = 0;
+        let mut all_match_positions = Vec::new();
+
+        for query_word in query_words {
+            let mut word_found = false;
+
+            for line_word in &line_words {
+                // Match if:
+                // 1. Exact word match
+                // 2. Line word starts with query word (prefix match)
+                // 3. Line word contains query word (substring match for compound words)
+                if line_word == query_word
+                    || line_word.starts_with(query_word.as_str())
+                    || (query_word.len() >= 4 && line_word.contains(query_word.as_str()))
+                {
+                    word_found = true;
+
+                    if let Some(&(start, end)) = match_ranges(line, query_word).first() {
+                        for (char_pos, (byte_idx, _ch)) in line.char_indices().enumerate() {
+                            if byte_idx >= start && byte_idx < end {
+                                all_match_positions.push(char_pos);
+                            }
+                        }
+                    }
+                    break;
+                }
+            }
+
+            if word_found {
+                matched_words += 1;
+            }
+        }
+
+        let match_ratio = matched_words as f64 / query_words.len() as f64;
+
+        // Include results where:
+        // - All words match (perfect match)
+        // - At least half the words match for multi-word queries
+        // - Single word queries must match
+        let include_result = if query_words.len() == 1 {
+            matched_words > 0
+        } else {
+            match_ratio >= 0.5
+        };
+
+        include_result.then_some((match_ratio, all_match_positions))
+    }
+
+    fn match_phrase(phrase_lower: &str, line: &str) -> Option<(f64, Vec<usize>)> {
+        let mut search_start = 0;
+        let mut match_positions_in_line = Vec::new();
+
+        for (start, end) in match_ranges(line, phrase_lower) {
+            // Matches don't overlap
+            if start < search_start {
+                continue;
+            }
+            for (char_pos, (byte_idx, _ch)) in line.char_indices().enumerate() {
+                if byte_idx >= start && byte_idx < end {
+                    match_positions_in_line.push(char_pos);
+                }
+            }
+            search_start = end;
+        }
+
+        // Exact match gets highest score
+        (!match_positions_in_line.is_empty()).then_some((1.0, match_positions_in_line))
+    }
+}
+
 #[derive(Debug)]
 struct ProcessedChapter {
     index: usize,
     title: String,
     lines: Vec<String>,
-    #[allow(dead_code)]
-    raw_text: String,
+    /// Where each line comes from, in step with `lines`
+    targets: Vec<MatchTarget>,
+}
+
+impl ProcessedChapter {
+    /// Lines matching the kind filter, with their index
+    fn lines_of_kind(
+        &self,
+        kind: Option<MatchKind>,
+    ) -> impl Iterator<Item = (usize, &String)> + '_ {
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(move |(idx, _)| kind.is_none_or(|kind| self.targets[*idx].kind() == kind))
+    }
 }
 
 pub struct SearchEngine {
@@ -38,202 +243,165 @@ impl SearchEngine {
         }
     }
 
+    /// Index chapters of plain text, one searchable unit per line
     pub fn process_chapters(&mut self, chapters: Vec<(usize, String, String)>) {
-        self.chapters = chapters
-            .into_iter()
-            .map(|(index, title, content)| {
-                let lines: Vec<String> = content.lines().map(|l| l.to_string()).collect();
-                ProcessedChapter {
-                    index,
-                    title,
-                    lines,
-                    raw_text: content,
-                }
-            })
-            .collect();
+        self.process_chapter_units(
+            chapters
+                .into_iter()
+                .map(|(index, title, content)| {
+                    (
+                        index,
+                        title,
+                        content.lines().map(SearchUnit::text).collect(),
+                    )
+                })
+                .collect(),
+        );
     }
 
-    pub fn search_fuzzy(&self, query: &str) -> Vec<BookSearchResult> {
-        if query.is_empty() {
-            return Vec::new();
-        }
+    /// Index chapters of searchable units. Soft hyphens and zero-width
+    /// spaces are dropped so they never split a word apart.
+    pub fn process_chapter_units(&mut self, chapters: Vec<(usize, String, Vec<SearchUnit>)>) {
+        self.chapters.clear();
+        self.add_chapter_units(chapters);
+    }
 
-        let trimmed = query.trim();
-        if trimmed.starts_with('"') && trimmed.ends_with('"') && trimmed.len() > 2 {
-            let phrase = &trimmed[1..trimmed.len() - 1];
-            return self.search_exact_phrase(phrase);
+    /// Index more chapters, replacing any indexed before under the same
+    /// index; results keep following the reading order
+    pub fn add_chapter_units(&mut self, chapters: Vec<(usize, String, Vec<SearchUnit>)>) {
+        for (index, title, units) in chapters {
+            let (lines, targets) = units
+                .into_iter()
+                .map(|unit| (strip_invisible(&unit.text).into_owned(), unit.target))
+                .unzip();
+            let chapter = ProcessedChapter {
+                index,
+                title,
+                lines,
+                targets,
+            };
+            match self.chapters.binary_search_by_key(&index, |c| c.index) {
+                Ok(position) => self.chapters[position] = chapter,
+                Err(position) => self.chapters.insert(position, chapter),
+            }
         }
-
-        self.search_word_based(query)
     }
 
-    fn search_word_based(&self, query: &str) -> Vec<BookSearchResult> {
-        let mut results = Vec::new();
-
-        let query_words: Vec<String> = query
-            .split_whitespace()
-            .map(|w| w.to_lowercase())
-            .filter(|w| !w.is_empty())
-            .collect();
+    pub fn is_indexed(&self, chapter_index: usize) -> bool {
+        self.chapters
+            .binary_search_by_key(&chapter_index, |c| c.index)
+            .is_ok()
+    }
 
-        if query_words.is_empty() {
+    /// Best matches across the book, only of `kind` when given
+    pub fn search_fuzzy(&self, query: &str, kind: Option<MatchKind>) -> Vec<BookSearchResult> {
+        let Some(parsed) = SearchQuery::parse(query) else {
             return Vec::new();
-        }
-
-        for chapter in &self.chapters {
-            for (line_idx, line) in chapter.lines.iter().enumerate() {
-                let line_lower = line.to_lowercase();
-
-                let line_words: Vec<&str> = line_lower.split_whitespace().collect();
-
-                let mut matched_words = 0;
-                let mut all_match_positions = Vec::new();
-
-                for query_word in &query_words {
-                    let mut word_found = false;
-
-                    for line_word in &line_words {
-                        // Match if:
-                        // 1. Exact word match
-                        // 2. Line word starts with query word (prefix match)
-                        // 3. Line word contains query word (substring match for compound words)
-                        if line_word == query_word
-                            || line_word.starts_with(query_word.as_str())
-                            || (query_word.len() >= 4 && line_word.contains(query_word.as_str()))
-                        {
-                            word_found = true;
-
-                            if let Some(pos) = line_lower.find(query_word.as_str()) {
-                                for (char_pos, (byte_idx, _ch)) in line.char_indices().enumerate() {
-                                    if byte_idx >= pos && byte_idx < pos + query_word.len() {
-                                        all_match_positions.push(char_pos);
-                                    }
-                                }
-                            }
-                            break;
-                        }
-                    }
+        };
 
-                    if word_found {
-                        matched_words += 1;
-                    }
-                }
+        let mut results: Vec<BookSearchResult> = self
+            .chapters
+            .iter()
+            .flat_map(|chapter| self.chapter_results(chapter, &parsed, kind))
+            .collect();
 
-                let match_ratio = matched_words as f64 / query_words.len() as f64;
-
-                // Include results where:
-                // - All words match (perfect match)
-                // - At least half the words match for multi-word queries
-                // - Single word queries must match
-                let include_result = if query_words.len() == 1 {
-                    matched_words > 0
-                } else {
-                    match_ratio >= 0.5
-                };
-
-                if include_result {
-                    let (context_before, context_after) = self.extract_context(chapter, line_idx);
-
-                    // Truncate very long snippet lines to keep results readable
-                    let max_snippet_chars = 300;
-                    let snippet = if line.chars().count() > max_snippet_chars {
-                        let truncated: String = line.chars().take(max_snippet_chars).collect();
-                        format!("{truncated}...")
-                    } else {
-                        line.clone()
-                    };
-
-                    results.push(BookSearchResult {
-                        chapter_index: chapter.index,
-                        chapter_title: chapter.title.clone(),
-                        line_number: line_idx,
-                        snippet,
-                        context_before,
-                        context_after,
-                        match_score: match_ratio,
-                        match_positions: all_match_positions,
-                    });
-                }
-            }
+        if matches!(parsed, SearchQuery::Words(_)) {
+            results.sort_by(|a, b| {
+                b.match_score
+                    .partial_cmp(&a.match_score)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            });
         }
 
-        results.sort_by(|a, b| {
-            b.match_score
-                .partial_cmp(&a.match_score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
         results.truncate(50);
 
-        debug!(
-            "Word-based search for '{}' found {} results",
-            query,
-            results.len()
-        );
+        debug!("Search for '{}' found {} results", query, results.len());
         results
     }
 
-    fn search_exact_phrase(&self, phrase: &str) -> Vec<BookSearchResult> {
-        if phrase.is_empty() {
+    /// Every match in one chapter, in reading order. Unlike `search_fuzzy`
+    /// this is not capped, so a chapter's matches can all be browsed.
+    pub fn search_chapter(
+        &self,
+        query: &str,
+        chapter_index: usize,
+        kind: Option<MatchKind>,
+    ) -> Vec<BookSearchResult> {
+        let Some(parsed) = SearchQuery::parse(query) else {
             return Vec::new();
-        }
-
-        let mut results = Vec::new();
-        let phrase_lower = phrase.to_lowercase();
-
-        for chapter in &self.chapters {
-            for (line_idx, line) in chapter.lines.iter().enumerate() {
-                let line_lower = line.to_lowercase();
-
-                let mut search_start = 0;
-                let mut match_positions_in_line = Vec::new();
+        };
 
-                while let Some(match_start) = line_lower[search_start..].find(&phrase_lower) {
-                    let absolute_start = search_start + match_start;
+        self.chapters
+            .iter()
+            .filter(|chapter| chapter.index == chapter_index)
+            .flat_map(|chapter| self.chapter_results(chapter, &parsed, kind))
+            .collect()
+    }
 
-                    let mut positions = Vec::new();
-                    for (char_pos, (byte_idx, _ch)) in line.char_indices().enumerate() {
-                        if byte_idx >= absolute_start && byte_idx < absolute_start + phrase.len() {
-                            positions.push(char_pos);
-                        }
-                    }
+    /// Number of matching lines per chapter, in book order, leaving out
+    /// chapters without matches. Counts every match, not just the 50 results.
+    pub fn count_matches_by_chapter(
+        &self,
+        query: &str,
+        kind: Option<MatchKind>,
+    ) -> Vec<ChapterMatchCount> {
+        let Some(parsed) = SearchQuery::parse(query) else {
+            return Vec::new();
+        };
 
-                    match_positions_in_line.extend(positions);
-                    search_start = absolute_start + phrase.len();
-                }
+        self.chapters
+            .iter()
+            .filter_map(|chapter| {
+                let count = chapter
+                    .lines_of_kind(kind)
+                    .filter(|(_, line)| parsed.is_match(line))
+                    .count();
+                (count > 0).then(|| ChapterMatchCount {
+                    chapter_index: chapter.index,
+                    chapter_title: chapter.title.clone(),
+                    count,
+                })
+            })
+            .collect()
+    }
 
-                if !match_positions_in_line.is_empty() {
-                    let (context_before, context_after) = self.extract_context(chapter, line_idx);
+    fn chapter_results(
+        &self,
+        chapter: &ProcessedChapter,
+        query: &SearchQuery,
+        kind: Option<MatchKind>,
+    ) -> Vec<BookSearchResult> {
+        let mut results = Vec::new();
 
-                    let max_snippet_chars = 300;
-                    let snippet = if line.chars().count() > max_snippet_chars {
-                        let truncated: String = line.chars().take(max_snippet_chars).collect();
-                        format!("{truncated}...")
-                    } else {
-                        line.clone()
-                    };
-
-                    results.push(BookSearchResult {
-                        chapter_index: chapter.index,
-                        chapter_title: chapter.title.clone(),
-                        line_number: line_idx,
-                        snippet,
-                        context_before,
-                        context_after,
-                        match_score: 1.0, // Exact match gets highest score
-                        match_positions: match_positions_in_line,
-                    });
-                }
-            }
+        for (line_idx, line) in chapter.lines_of_kind(kind) {
+            let Some((match_score, match_positions)) = query.match_line(line) else {
+                continue;
+            };
+
+            let (context_before, context_after) = self.extract_context(chapter, line_idx);
+
+            // Truncate very long snippet lines to keep results readable
+            let max_snippet_chars = 300;
+            let snippet = if line.chars().count() > max_snippet_chars {
+                let truncated: String = line.chars().take(max_snippet_chars).collect();
+                format!("{truncated}...")
+            } else {
+                line.clone()
+            };
+
+            results.push(BookSearchResult {
+                chapter_index: chapter.index,
+                chapter_title: chapter.title.clone(),
+                line_number: line_idx,
+                target: chapter.targets[line_idx].clone(),
+                snippet,
+                context_before,
+                context_after,
+                match_score,
+                match_positions,
+            });
         }
 
-        results.truncate(50);
-
-        debug!(
-            "Phrase search for '{}' found {} results",
-            phrase,
-            results.len()
-        );
         results
     }
 
@@ -291,3 +459,170 @@ impl SearchEngine {
         self.chapters.clear();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn engine() -> SearchEngine {
+        let mut engine = SearchEngine::new();
+        let busy = "the whale surfaced\n".repeat(60);
+        engine.process_chapters(vec![
+            (
+                0,
+                "Loomings".to_string(),
+                "Call me Ishmael.\nA whale!".to_string(),
+            ),
+            (
+                1,
+                "The Carpet-Bag".to_string(),
+                "No match here.".to_string(),
+            ),
+            (2, "The Chase".to_string(), busy),
+        ]);
+        engine
+    }
+
+    #[test]
+    fn test_counts_cover_every_match_grouped_by_chapter() {
+        let counts = engine().count_matches_by_chapter("whale", None);
+        assert_eq!(
+            counts
+                .iter()
+                .map(|c| (c.chapter_index, c.count))
+                .collect::<Vec<_>>(),
+            vec![(0, 1), (2, 60)]
+        );
+        // The flat result list stays capped
+        assert_eq!(engine().search_fuzzy("whale", None).len(), 50);
+    }
+
+    #[test]
+    fn test_soft_hyphens_and_zero_width_spaces_do_not_split_words() {
+        let mut engine = SearchEngine::new();
+        engine.process_chapters(vec![(
+            0,
+            "Mutual Aid".to_string(),
+            "They co\u{00AD}operate.\nNo\u{200B}where else.".to_string(),
+        )]);
+
+        let results = engine.search_fuzzy("cooperate", None);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].snippet, "They cooperate.");
+        assert_eq!(results[0].match_positions, (5..14).collect::<Vec<_>>());
+        assert_eq!(engine.search_fuzzy("\"nowhere\"", None).len(), 1);
+        assert_eq!(engine.search_fuzzy("co\u{00AD}op", None).len(), 1);
+    }
+
+    #[test]
+    fn test_phrase_counts_match_phrase_results() {
+        let engine = engine();
+        let counts = engine.count_matches_by_chapter("\"whale surfaced\"", None);
+        assert_eq!(counts.len(), 1);
+        assert_eq!(counts[0].count, 60);
+        assert!(
+            engine
+                .count_matches_by_chapter("\"ishmael whale\"", None)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_search_chapter_returns_all_matches_in_reading_order() {
+        let results = engine().search_chapter("whale", 2, None);
+        assert_eq!(results.len(), 60);
+        assert!(
+            results
+                .windows(2)
+                .all(|w| w[0].line_number < w[1].line_number)
+        );
+        assert!(results.iter().all(|r| r.chapter_index == 2));
+    }
+
+    #[test]
+    fn test_figures_and_table_cells_are_separate_units() {
+        let mut engine = SearchEngine::new();
+        engine.process_chapter_units(vec![(
+            0,
+            "Results".to_string(),
+            vec![
+                SearchUnit::text("The harbour seal population grew."),
+                SearchUnit {
+                    text: "Map of the seal colonies".to_string(),
+                    target: MatchTarget::Figure {
+                        src: "images/map.png".to_string(),
+                    },
+                },
+                SearchUnit {
+                    text: "Seal".to_string(),
+                    target: MatchTarget::Table { index: 0 },
+                },
+                SearchUnit {
+                    text: "1,240".to_string(),
+                    target: MatchTarget::Table { index: 0 },
+                },
+            ],
+        )]);
+
+        let kinds: Vec<MatchKind> = engine
+            .search_chapter("seal", 0, None)
+            .iter()
+            .map(|result| result.target.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![MatchKind::Text, MatchKind::Figure, MatchKind::Table]
+        );
+
+        let figures = engine.search_fuzzy("seal", Some(MatchKind::Figure));
+        assert_eq!(figures.len(), 1);
+        assert_eq!(figures[0].snippet, "Map of the seal colonies");
+        assert_eq!(
+            figures[0].target,
+            MatchTarget::Figure {
+                src: "images/map.png".to_string()
+            }
+        );
+
+        let cells = engine.search_fuzzy("1,240", Some(MatchKind::Table));
+        assert_eq!(cells[0].target, MatchTarget::Table { index: 0 });
+        assert_eq!(
+            engine.count_matches_by_chapter("seal", Some(MatchKind::Table))[0].count,
+            1
+        );
+    }
+
+    #[test]
+    fn test_chapters_indexed_later_keep_reading_order() {
+        let mut engine = SearchEngine::new();
+        engine.add_chapter_units(vec![(
+            2,
+            "The Chase".to_string(),
+            vec![SearchUnit::text("a whale")],
+        )]);
+        assert!(engine.is_indexed(2));
+        assert!(!engine.is_indexed(0));
+
+        engine.add_chapter_units(vec![
+            (
+                0,
+                "Loomings".to_string(),
+                vec![SearchUnit::text("whale ho")],
+            ),
+            (
+                2,
+                "The Chase".to_string(),
+                vec![SearchUnit::text("the whale again")],
+            ),
+        ]);
+        let counts = engine.count_matches_by_chapter("whale", None);
+        assert_eq!(
+            counts.iter().map(|c| c.chapter_index).collect::<Vec<_>>(),
+            vec![0, 2]
+        );
+        assert_eq!(
+            engine.search_chapter("whale", 2, None)[0].snippet,
+            "the whale again"
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Past search queries, recalled with Up/Down in the search inputs.
+//!
+//! Queries are kept next to the bookmarks file, one per line prefixed with
+//! the search they were made in (`chapter` or `book`) and a tab, oldest
+//! first. With `merge_search_history` both searches recall the same list.
+
+use std::fs;
+
+pub const SEARCH_HISTORY_FILENAME: &str = "search_history.txt";
+
+/// Oldest queries are dropped past this many
+const MAX_QUERIES: usize = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchScope {
+    /// `/` in the reader
+    Chapter,
+    /// The book search popup
+    Book,
+}
+
+impl SearchScope {
+    fn tag(self) -> &'static str {
+        match self {
+            SearchScope::Chapter => "chapter",
+            SearchScope::Book => "book",
+        }
+    }
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "chapter" => Some(SearchScope::Chapter),
+            "book" => Some(SearchScope::Book),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct SearchHistory {
+    /// Oldest first; a query appears once per scope, or once overall when merged
+    entries: Vec<(SearchScope, String)>,
+    merged: bool,
+    file_path: Option<String>,
+}
+
+impl SearchHistory {
+    pub fn ephemeral(merged: bool) -> Self {
+        Self {
+            merged,
+            ..Self::default()
+        }
+    }
+
+    pub fn load_or_ephemeral(file_path: Option<&str>, merged: bool) -> Self {
+        let Some(path) = file_path else {
+            return Self::ephemeral(merged);
+        };
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                log::error!("Failed to load search history from {path}: {e}");
+                String::new()
+            }
+        };
+        let mut history = Self {
+            entries: Vec::new(),
+            merged,
+            file_path: Some(path.to_string()),
+        };
+        for line in content.lines() {
+            if let Some((tag, query)) = line.split_once('\t')
+                && let Some(scope) = SearchScope::from_tag(tag)
+            {
+                history.insert(scope, query);
+            }
+        }
+        history
+    }
+
+    /// Remember a query as the newest of its scope and save the history
+    pub fn add(&mut self, scope: SearchScope, query: &str) {
+        if query.trim().is_empty() {
+            return;
+        }
+        self.insert(scope, query);
+        if let Err(e) = self.save() {
+            log::error!("Failed to save search history: {e}");
+        }
+    }
+
+    fn insert(&mut self, scope: SearchScope, query: &str) {
+        let merged = self.merged;
+        self.entries
+            .retain(|(s, q)| q != query || (!merged && *s != scope));
+        self.entries.push((scope, query.to_string()));
+        if self.entries.len() > MAX_QUERIES {
+            self.entries.drain(..self.entries.len() - MAX_QUERIES);
+        }
+    }
+
+    /// Queries recalled in a search, oldest first
+    pub fn queries(&self, scope: SearchScope) -> Vec<String> {
+        self.entries
+            .iter()
+            .filter(|(s, _)| self.merged || *s == scope)
+            .map(|(_, query)| query.clone())
+            .collect()
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        if crate::instance_lock::is_read_only() {
+            return Ok(());
+        }
+        if let Some(path) = &self.file_path {
+            let mut content = String::new();
+            for (scope, query) in &self.entries {
+                content.push_str(scope.tag());
+                content.push('\t');
+                content.push_str(query);
+                content.push('\n');
+            }
+            fs::write(path, content)?;
+        }
+        Ok(())
+    }
+}
+
+/// Position while stepping through past queries in a search input, like a
+/// shell's history
+#[derive(Debug, Clone, Default)]
+pub struct HistoryRecall {
+    /// Index into the queries of the one shown; None while typing
+    position: Option<usize>,
+    /// What was typed before recalling began, restored past the newest query
+    draft: String,
+}
+
+impl HistoryRecall {
+    /// The query before the shown one, None when there is none
+    pub fn older(&mut self, queries: &[String], current: &str) -> Option<String> {
+        let position = match self.position {
+            None => {
+                self.draft = current.to_string();
+                queries.len().checked_sub(1)?
+            }
+            Some(position) => position.checked_sub(1)?,
+        };
+        self.position = Some(position);
+        queries.get(position).cloned()
+    }
+
+    /// The query after the shown one, or the draft past the newest; None
+    /// when not recalling
+    pub fn newer(&mut self, queries: &[String]) -> Option<String> {
+        let position = self.position? + 1;
+        if position < queries.len() {
+            self.position = Some(position);
+            queries.get(position).cloned()
+        } else {
+            self.position = None;
+            Some(std::mem::take(&mut self.draft))
+        }
+    }
+
+    pub fn is_recalling(&self) -> bool {
+        self.position.is_some()
+    }
+
+    pub fn reset(&mut self) {
+        self.position = None;
+        self.draft.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_history_is_deduplicated_per_scope_and_persisted() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(SEARCH_HISTORY_FILENAME);
+        let path = path.to_str().unwrap();
+
+        let mut history = SearchHistory::load_or_ephemeral(Some(path), false);
+        history.add(SearchScope::Chapter, "whale");
+        history.add(SearchScope::Book, "whale");
+        history.add(SearchScope::Chapter, "ishmael");
+        history.add(SearchScope::Chapter, "whale");
+        history.add(SearchScope::Chapter, "  ");
+        assert_eq!(
+            history.queries(SearchScope::Chapter),
+            vec!["ishmael", "whale"]
+        );
+        assert_eq!(history.queries(SearchScope::Book), vec!["whale"]);
+
+        let merged = SearchHistory::load_or_ephemeral(Some(path), true);
+        assert_eq!(merged.queries(SearchScope::Book), vec!["ishmael", "whale"]);
+        assert_eq!(
+            merged.queries(SearchScope::Chapter),
+            merged.queries(SearchScope::Book)
+        );
+    }
+
+    #[test]
+    fn test_recall_steps_through_queries_and_back_to_the_draft() {
+        let queries: Vec<String> = ["first", "second"].map(String::from).to_vec();
+        let mut recall = HistoryRecall::default();
+
+        assert_eq!(recall.newer(&queries), None);
+        assert_eq!(recall.older(&queries, "dra").as_deref(), Some("second"));
+        assert_eq!(recall.older(&queries, "second").as_deref(), Some("first"));
+        assert_eq!(recall.older(&queries, "first"), None);
+        assert_eq!(recall.newer(&queries).as_deref(), Some("second"));
+        assert_eq!(recall.newer(&queries).as_deref(), Some("dra"));
+        assert!(!recall.is_recalling());
+
+        assert_eq!(recall.older(&[], "x"), None);
+    }
+}
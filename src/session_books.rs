@@ -0,0 +1,112 @@
+/// Where a book was left, exactly as it was on screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionPosition {
+    pub chapter_index: usize,
+    /// First node on screen
+    pub node_index: usize,
+    /// Lines the view was scrolled past the start of that node
+    pub line_offset: isize,
+    /// Reading thread being read, None for the primary bookmark
+    pub thread: Option<String>,
+}
+
+/// Books opened this session, for switching back and forth between them
+/// (Ctrl+6, like vim's alternate file, and Space+l).
+///
+/// Positions live in memory only: the bookmark saved to disk keeps the node
+/// being read, this keeps the view as it was. A restart goes back to the
+/// bookmarks.
+#[derive(Default)]
+pub struct SessionBooks {
+    /// Book paths, most recently opened first, with where each was left;
+    /// None until the book is left for another
+    books: Vec<(String, Option<SessionPosition>)>,
+}
+
+impl SessionBooks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `path` first, keeping the position it was left at
+    pub fn opened(&mut self, path: &str) {
+        let entry = match self.books.iter().position(|(book, _)| book == path) {
+            Some(index) => self.books.remove(index),
+            None => (path.to_string(), None),
+        };
+        self.books.insert(0, entry);
+    }
+
+    /// Note where `path` is being left
+    pub fn left(&mut self, path: &str, position: SessionPosition) {
+        match self.books.iter_mut().find(|(book, _)| book == path) {
+            Some((_, left_at)) => *left_at = Some(position),
+            None => self.books.push((path.to_string(), Some(position))),
+        }
+    }
+
+    pub fn position(&self, path: &str) -> Option<&SessionPosition> {
+        self.books
+            .iter()
+            .find(|(book, _)| book == path)
+            .and_then(|(_, position)| position.as_ref())
+    }
+
+    /// The book opened before `current`
+    pub fn alternate(&self, current: Option<&str>) -> Option<&str> {
+        self.books
+            .iter()
+            .map(|(book, _)| book.as_str())
+            .find(|book| Some(*book) != current)
+    }
+
+    /// Book paths, most recently opened first
+    pub fn paths(&self) -> impl Iterator<Item = &str> {
+        self.books.iter().map(|(book, _)| book.as_str())
+    }
+
+    /// Forget a book that could not be opened again
+    pub fn remove(&mut self, path: &str) {
+        self.books.retain(|(book, _)| book != path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(node_index: usize) -> SessionPosition {
+        SessionPosition {
+            chapter_index: 2,
+            node_index,
+            line_offset: 3,
+            thread: None,
+        }
+    }
+
+    #[test]
+    fn test_alternate_toggles_between_the_last_two_books() {
+        let mut session = SessionBooks::new();
+        assert_eq!(session.alternate(None), None);
+
+        session.opened("a.epub");
+        assert_eq!(session.alternate(Some("a.epub")), None);
+        session.left("a.epub", at(7));
+        session.opened("b.epub");
+        assert_eq!(session.alternate(Some("b.epub")), Some("a.epub"));
+
+        session.left("b.epub", at(9));
+        session.opened("a.epub");
+        assert_eq!(session.alternate(Some("a.epub")), Some("b.epub"));
+        assert_eq!(session.position("a.epub"), Some(&at(7)));
+        assert_eq!(session.position("b.epub"), Some(&at(9)));
+
+        session.opened("c.epub");
+        assert_eq!(
+            session.paths().collect::<Vec<_>>(),
+            ["c.epub", "a.epub", "b.epub"]
+        );
+        session.remove("a.epub");
+        assert_eq!(session.alternate(Some("c.epub")), Some("b.epub"));
+    }
+}
@@ -1,5 +1,9 @@
+use crate::color_mode::ColorMode;
+use crate::i18n::Language;
+use crate::reading_goal::{DailyGoal, GoalUnit};
 use log::{debug, error, info, warn};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::path::PathBuf;
 use std::sync::RwLock;
@@ -9,6 +13,130 @@ use once_cell::sync::Lazy;
 pub const CURRENT_VERSION: u32 = 1;
 const SETTINGS_FILENAME: &str = ".bookokrat_settings.yaml";
 
+/// The leader as sequences are written: `" h"` is Leader+h, whichever key
+/// the leader is
+pub const LEADER: char = ' ';
+
+/// What a lone Space does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpaceKey {
+    /// Space starts the Space+ sequences
+    #[default]
+    Leader,
+    /// Space pages down and the `leader_key` setting starts the sequences
+    PageDown,
+}
+
+/// When the navigation panel is shown next to the reader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NavPanelMode {
+    #[default]
+    Always,
+    /// Collapse to a thin strip while the reader has focus
+    Auto,
+    /// Start in zen mode when a book is opened on launch
+    Never,
+}
+
+/// What `h`/`l` do when there is no previous/next chapter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookBoundary {
+    /// Show "End of book" / "Start of book"
+    #[default]
+    Notify,
+    /// `l` on the last chapter opens the next book in the library
+    NextBook,
+    Nothing,
+}
+
+/// What Ctrl+z does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CtrlZ {
+    /// Suspend to the shell, like other terminal programs
+    #[default]
+    Suspend,
+    /// Toggle zen mode, as Ctrl+z did before it suspended
+    ZenMode,
+}
+
+/// How ruby readings (furigana) are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RubyMode {
+    /// Reading in parentheses after the base text: `漢字(かんじ)`
+    #[default]
+    Compact,
+    /// Dimmed reading line above each line of text
+    TwoLine,
+}
+
+/// How thematic breaks (`<hr>`, scene breaks) are drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleStyle {
+    /// Short centered line in the muted theme color
+    #[default]
+    Line,
+    /// Centered `* * *`
+    Dinkus,
+    /// Dimmed `───── ❧ ─────` across the content width, the glyph set by
+    /// `rule_glyph`
+    Ornament,
+}
+
+/// What a click without a drag does in the text
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClickAction {
+    /// Anchor a selection that a drag extends; nothing stays selected
+    /// without one
+    #[default]
+    Select,
+    /// Place a caret at the clicked character, used by `*`; a selection
+    /// only starts once the mouse is dragged
+    Position,
+}
+
+/// How the line a jump lands on is flashed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JumpHighlightStyle {
+    /// Behind the line's text
+    #[default]
+    Text,
+    /// Across the whole width of the reader
+    Line,
+}
+
+/// How long a jump target stays highlighted by default
+pub const DEFAULT_JUMP_HIGHLIGHT_MS: u64 = 2000;
+
+/// Glyph in the middle of `RuleStyle::Ornament` rules by default
+pub const DEFAULT_RULE_GLYPH: &str = "❧";
+
+/// Runs of blank lines longer than this are squeezed by default
+pub const DEFAULT_MAX_BLANK_LINES: usize = 2;
+
+/// Tables laid out narrower than this are stacked by default
+pub const DEFAULT_TABLE_STACK_WIDTH: usize = 50;
+
+/// Columns a nested list steps in by default
+pub const DEFAULT_LIST_INDENT: usize = 2;
+
+/// Lines of context kept around jump targets by default
+pub const DEFAULT_SCROLL_OFF: usize = 3;
+
+/// Size of a popup in percent of the screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PopupSize {
+    pub width: u16,
+    pub height: u16,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct YamlTheme {
     pub scheme: String,
@@ -46,9 +174,278 @@ pub struct Settings {
     #[serde(default = "default_theme")]
     pub theme: String,
 
+    /// Themes `theme: auto` and `theme: system` pick in light and dark mode
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub light_theme: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dark_theme: Option<String>,
+
+    /// Directory scanned for books, chosen during onboarding
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub books_directory: Option<String>,
+
     #[serde(default)]
     pub margin: u16,
 
+    /// Navigation panel width as a percentage of the terminal width
+    #[serde(default = "default_nav_panel_width")]
+    pub nav_panel_width: u16,
+
+    /// Whether the navigation panel stays open, collapses while reading, or starts hidden
+    #[serde(default)]
+    pub nav_panel_mode: NavPanelMode,
+
+    /// Show each book's series and place in it after its name in the book list
+    #[serde(default)]
+    pub book_list_series: bool,
+
+    #[serde(default = "default_render_images")]
+    pub render_images: bool,
+
+    /// Color support override; `none` renders with text attributes only
+    #[serde(default)]
+    pub color_mode: ColorMode,
+
+    /// Interface language; `auto` follows $LANG
+    #[serde(default)]
+    pub language: Language,
+
+    /// Daily reading goal in `daily_goal_unit`; 0 disables goal tracking
+    #[serde(default)]
+    pub daily_goal: u32,
+
+    #[serde(default)]
+    pub daily_goal_unit: GoalUnit,
+
+    /// How ruby annotations (furigana) are displayed
+    #[serde(default)]
+    pub ruby_mode: RubyMode,
+
+    /// Include ruby readings when copying text
+    #[serde(default)]
+    pub copy_ruby_readings: bool,
+
+    /// Keep the bars down the left of quotes in copied text
+    #[serde(default)]
+    pub copy_quote_bars: bool,
+
+    /// Show straight quotes, `--` and `...` as curly quotes, dashes and
+    /// ellipses; books can override it with Space+'
+    #[serde(default)]
+    pub typography: bool,
+
+    /// Copy typeset text as shown rather than with the typewriter's punctuation
+    #[serde(default = "default_copy_typography")]
+    pub copy_typography: bool,
+
+    /// Copy an image inside a selection as `[image: alt text]`; false
+    /// leaves images out of copied text
+    #[serde(default = "default_copy_image_alt_text")]
+    pub copy_image_alt_text: bool,
+
+    /// Ask for confirmation before `q` quits, unless `q` is pressed twice
+    /// within a second
+    #[serde(default)]
+    pub confirm_quit: bool,
+
+    /// What Ctrl+z does; `zen_mode` keeps the zen toggle it had before it
+    /// suspended
+    #[serde(default)]
+    pub ctrl_z: CtrlZ,
+
+    /// Minutes without input after which the bookmark is saved and the
+    /// session counts as idle; 0 never goes idle
+    #[serde(default = "default_idle_timeout_minutes")]
+    pub idle_timeout_minutes: u64,
+
+    /// Dim the screen while idle, until the next key or mouse event
+    #[serde(default = "default_idle_dim")]
+    pub idle_dim: bool,
+
+    /// What `h`/`l` do past the first/last chapter
+    #[serde(default)]
+    pub book_boundary: BookBoundary,
+
+    /// Let `h`/`l` pass over chapters with almost no text (covers, ad pages)
+    #[serde(default)]
+    pub skip_empty_chapters: bool,
+
+    /// Scrolling on from a chapter's end or start opens the next or previous
+    /// chapter instead of stopping. The chapters are not joined into one view.
+    #[serde(default, alias = "continuous_scroll")]
+    pub auto_advance_chapters: bool,
+
+    /// In-chapter and book search recall one query history instead of two
+    #[serde(default)]
+    pub merge_search_history: bool,
+
+    /// Drop soft hyphens and zero-width spaces from the displayed text;
+    /// search ignores them either way
+    #[serde(default)]
+    pub strip_invisible_chars: bool,
+
+    /// Chapters with fewer words than this count as empty
+    #[serde(default = "default_empty_chapter_words")]
+    pub empty_chapter_words: usize,
+
+    /// Percent of a chapter scrolled past at which it is marked read
+    #[serde(default = "default_chapter_read_percent")]
+    pub chapter_read_percent: u8,
+
+    /// Reading history progress bars count chapters read instead of the
+    /// bookmark's position
+    #[serde(default)]
+    pub progress_by_chapters_read: bool,
+
+    /// Chapters on either side of the current one converted and indexed
+    /// ahead of time; the whole book when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefetch_radius: Option<usize>,
+
+    /// Longest run of blank lines kept in the reader; 0 keeps them all
+    #[serde(default = "default_max_blank_lines")]
+    pub max_blank_lines: usize,
+
+    /// Tables narrower than this many columns are shown as `header: value`
+    /// lists, row by row; 0 always draws the grid
+    #[serde(default = "default_table_stack_width")]
+    pub table_stack_width: usize,
+
+    /// Columns each nested list steps in by, at least to the text of the
+    /// item it is in
+    #[serde(default = "default_list_indent")]
+    pub list_indent: usize,
+
+    /// Lines of context kept around links, search results and other jump targets
+    #[serde(default = "default_scroll_off")]
+    pub scroll_off: usize,
+
+    /// How long the line a link, anchor or book search result jumps to is
+    /// highlighted, in milliseconds; 0 turns the highlight off
+    #[serde(default = "default_jump_highlight_ms")]
+    pub jump_highlight_ms: u64,
+
+    #[serde(default)]
+    pub jump_highlight_style: JumpHighlightStyle,
+
+    /// Background of the jump highlight: a palette slot or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub jump_highlight_color: Option<String>,
+
+    /// What a click without a drag does in the reader
+    #[serde(default)]
+    pub click_action: ClickAction,
+
+    /// h/l in the image popup go from the last image of the chapter back to
+    /// the first, and the other way round
+    #[serde(default = "default_image_popup_wrap")]
+    pub image_popup_wrap: bool,
+
+    /// Whether Space starts key sequences or pages down
+    #[serde(default)]
+    pub space_key: SpaceKey,
+
+    /// Key that starts the sequences when Space pages down
+    #[serde(default = "default_leader_key")]
+    pub leader_key: char,
+
+    /// Below this size only a "terminal too small" message is shown
+    #[serde(default = "default_min_terminal_width")]
+    pub min_terminal_width: u16,
+
+    #[serde(default = "default_min_terminal_height")]
+    pub min_terminal_height: u16,
+
+    /// Below this size the help bar is hidden and one panel fills the screen
+    #[serde(default = "default_compact_terminal_width")]
+    pub compact_terminal_width: u16,
+
+    #[serde(default = "default_compact_terminal_height")]
+    pub compact_terminal_height: u16,
+
+    /// Show the position within the current TOC section in the reader footer
+    #[serde(default = "default_show_section_progress")]
+    pub show_section_progress: bool,
+
+    /// Draw the chapter progress along the reader's bottom border (true color only)
+    #[serde(default)]
+    pub progress_bar: bool,
+
+    /// Tint one row of the reader as a reading guide (Space+r)
+    #[serde(default)]
+    pub reading_ruler: bool,
+
+    /// Row of the reader the ruler is on, the middle one when unset (Shift+J/K)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reading_ruler_row: Option<u16>,
+
+    /// How horizontal rules and scene breaks are drawn
+    #[serde(default)]
+    pub rule_style: RuleStyle,
+
+    /// Glyph in the middle of `ornament` rules
+    #[serde(default = "default_rule_glyph")]
+    pub rule_glyph: String,
+
+    /// Mark words ranked past `gloss_rank` in `word_frequency_list`
+    #[serde(default)]
+    pub gloss_rare_words: bool,
+
+    #[serde(default = "default_gloss_rank")]
+    pub gloss_rank: usize,
+
+    /// Word list file, one word per line, most frequent first
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub word_frequency_list: Option<String>,
+
+    /// Directory Space+e saves chapters to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub export_directory: Option<String>,
+
+    /// Directory comments are kept in (Space+A moves them)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comments_dir: Option<String>,
+
+    /// Show a random comment from the library on startup (Space+q any time)
+    #[serde(default)]
+    pub resurface_comments: bool,
+
+    /// Least severe log messages written: error, warn, info, debug or trace
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+
+    /// File the log is written to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+
+    /// Copy a chapter's images next to its Markdown export
+    #[serde(default = "default_export_images")]
+    pub export_images: bool,
+
+    /// Replacements for private-use characters of embedded fonts, keyed by hex codepoint
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub glyph_substitutions: BTreeMap<String, String>,
+
+    /// Sizes picked for popups with Ctrl+arrows or +/-, keyed by popup
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub popup_sizes: BTreeMap<String, PopupSize>,
+
+    /// Selection background override: a palette slot (`base00`-`base0F`) or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_bg: Option<String>,
+
+    /// Selection foreground override: a palette slot (`base00`-`base0F`) or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selection_fg: Option<String>,
+
+    /// Table of contents entry of the section being read: a palette slot or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toc_active_color: Option<String>,
+
+    /// Other table of contents entries of the current chapter: a palette slot or a hex color
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub toc_chapter_color: Option<String>,
+
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub custom_themes: Vec<YamlTheme>,
 }
@@ -57,8 +454,105 @@ fn default_version() -> u32 {
     CURRENT_VERSION
 }
 
+/// Theme setting that follows the terminal's background
+pub const AUTO_THEME: &str = "auto";
+/// Theme setting that follows the operating system's dark/light mode
+pub const SYSTEM_THEME: &str = "system";
+
 fn default_theme() -> String {
-    "Oceanic Next".to_string()
+    AUTO_THEME.to_string()
+}
+
+fn default_nav_panel_width() -> u16 {
+    30
+}
+
+fn default_render_images() -> bool {
+    true
+}
+
+fn default_show_section_progress() -> bool {
+    true
+}
+
+fn default_min_terminal_width() -> u16 {
+    40
+}
+
+fn default_min_terminal_height() -> u16 {
+    12
+}
+
+fn default_compact_terminal_width() -> u16 {
+    60
+}
+
+fn default_compact_terminal_height() -> u16 {
+    16
+}
+
+fn default_idle_timeout_minutes() -> u64 {
+    10
+}
+
+fn default_idle_dim() -> bool {
+    true
+}
+
+fn default_empty_chapter_words() -> usize {
+    20
+}
+
+fn default_chapter_read_percent() -> u8 {
+    95
+}
+
+fn default_gloss_rank() -> usize {
+    10_000
+}
+
+fn default_copy_typography() -> bool {
+    true
+}
+
+fn default_copy_image_alt_text() -> bool {
+    true
+}
+
+fn default_export_images() -> bool {
+    true
+}
+
+fn default_max_blank_lines() -> usize {
+    DEFAULT_MAX_BLANK_LINES
+}
+
+fn default_table_stack_width() -> usize {
+    DEFAULT_TABLE_STACK_WIDTH
+}
+
+fn default_list_indent() -> usize {
+    DEFAULT_LIST_INDENT
+}
+
+fn default_image_popup_wrap() -> bool {
+    true
+}
+
+fn default_leader_key() -> char {
+    ','
+}
+
+fn default_rule_glyph() -> String {
+    DEFAULT_RULE_GLYPH.to_string()
+}
+
+fn default_scroll_off() -> usize {
+    DEFAULT_SCROLL_OFF
+}
+
+fn default_jump_highlight_ms() -> u64 {
+    DEFAULT_JUMP_HIGHLIGHT_MS
 }
 
 impl Default for Settings {
@@ -66,7 +560,73 @@ impl Default for Settings {
         Self {
             version: CURRENT_VERSION,
             theme: default_theme(),
+            light_theme: None,
+            dark_theme: None,
+            books_directory: None,
             margin: 0,
+            nav_panel_width: default_nav_panel_width(),
+            nav_panel_mode: NavPanelMode::default(),
+            book_list_series: false,
+            show_section_progress: default_show_section_progress(),
+            progress_bar: false,
+            reading_ruler: false,
+            reading_ruler_row: None,
+            min_terminal_width: default_min_terminal_width(),
+            min_terminal_height: default_min_terminal_height(),
+            compact_terminal_width: default_compact_terminal_width(),
+            compact_terminal_height: default_compact_terminal_height(),
+            render_images: default_render_images(),
+            color_mode: ColorMode::default(),
+            language: Language::default(),
+            daily_goal: 0,
+            daily_goal_unit: GoalUnit::default(),
+            ruby_mode: RubyMode::default(),
+            copy_ruby_readings: false,
+            copy_quote_bars: false,
+            typography: false,
+            copy_typography: default_copy_typography(),
+            copy_image_alt_text: default_copy_image_alt_text(),
+            confirm_quit: false,
+            ctrl_z: CtrlZ::default(),
+            idle_timeout_minutes: default_idle_timeout_minutes(),
+            idle_dim: default_idle_dim(),
+            book_boundary: BookBoundary::default(),
+            skip_empty_chapters: false,
+            auto_advance_chapters: false,
+            merge_search_history: false,
+            strip_invisible_chars: false,
+            empty_chapter_words: default_empty_chapter_words(),
+            chapter_read_percent: default_chapter_read_percent(),
+            progress_by_chapters_read: false,
+            prefetch_radius: None,
+            max_blank_lines: default_max_blank_lines(),
+            table_stack_width: default_table_stack_width(),
+            list_indent: default_list_indent(),
+            scroll_off: default_scroll_off(),
+            jump_highlight_ms: default_jump_highlight_ms(),
+            jump_highlight_style: JumpHighlightStyle::default(),
+            jump_highlight_color: None,
+            click_action: ClickAction::default(),
+            image_popup_wrap: default_image_popup_wrap(),
+            space_key: SpaceKey::default(),
+            leader_key: default_leader_key(),
+            rule_style: RuleStyle::default(),
+            rule_glyph: default_rule_glyph(),
+            gloss_rare_words: false,
+            gloss_rank: default_gloss_rank(),
+            word_frequency_list: None,
+            export_directory: None,
+            comments_dir: None,
+            resurface_comments: false,
+            log_level: None,
+            log_file: None,
+            export_images: default_export_images(),
+            glyph_substitutions: BTreeMap::new(),
+            popup_sizes: BTreeMap::new(),
+            selection_bg: None,
+            selection_fg: None,
+            toc_active_color: None,
+            toc_chapter_color: None,
             custom_themes: Vec::new(),
         }
     }
@@ -158,8 +718,291 @@ fn generate_settings_yaml(settings: &Settings) -> String {
     let mut content = String::new();
 
     content.push_str(&format!("version: {}\n", settings.version));
+    content.push_str(THEME_COMMENT);
     content.push_str(&format!("theme: \"{}\"\n", settings.theme));
+    match &settings.light_theme {
+        Some(name) => content.push_str(&format!("light_theme: {:?}\n", name)),
+        None => content.push_str(LIGHT_THEME_TEMPLATE),
+    }
+    match &settings.dark_theme {
+        Some(name) => content.push_str(&format!("dark_theme: {:?}\n", name)),
+        None => content.push_str(DARK_THEME_TEMPLATE),
+    }
+    match &settings.books_directory {
+        Some(dir) => content.push_str(&format!("books_directory: {:?}\n", dir)),
+        None => content.push_str(BOOKS_DIRECTORY_TEMPLATE),
+    }
+    content.push_str(MARGIN_COMMENT);
     content.push_str(&format!("margin: {}\n", settings.margin));
+    content.push_str(&format!("nav_panel_width: {}\n", settings.nav_panel_width));
+    content.push_str(NAV_PANEL_MODE_COMMENT);
+    let nav_panel_mode = match settings.nav_panel_mode {
+        NavPanelMode::Always => "always",
+        NavPanelMode::Auto => "auto",
+        NavPanelMode::Never => "never",
+    };
+    content.push_str(&format!("nav_panel_mode: {}\n", nav_panel_mode));
+    content.push_str(BOOK_LIST_SERIES_COMMENT);
+    content.push_str(&format!(
+        "book_list_series: {}\n",
+        settings.book_list_series
+    ));
+    content.push_str(TERMINAL_SIZE_COMMENT);
+    content.push_str(&format!(
+        "min_terminal_width: {}\n",
+        settings.min_terminal_width
+    ));
+    content.push_str(&format!(
+        "min_terminal_height: {}\n",
+        settings.min_terminal_height
+    ));
+    content.push_str(&format!(
+        "compact_terminal_width: {}\n",
+        settings.compact_terminal_width
+    ));
+    content.push_str(&format!(
+        "compact_terminal_height: {}\n",
+        settings.compact_terminal_height
+    ));
+    content.push_str(&format!("render_images: {}\n", settings.render_images));
+    content.push_str(COLOR_MODE_COMMENT);
+    let color_mode = match settings.color_mode {
+        ColorMode::Auto => "auto",
+        ColorMode::Truecolor => "truecolor",
+        ColorMode::Ansi256 => "ansi256",
+        ColorMode::Ansi16 => "ansi16",
+        ColorMode::Ansi8 => "ansi8",
+        ColorMode::None => "none",
+    };
+    content.push_str(&format!("color_mode: {}\n", color_mode));
+    content.push_str(LANGUAGE_COMMENT);
+    let language = match settings.language {
+        Language::Auto => "auto",
+        Language::En => "en",
+        Language::Ru => "ru",
+    };
+    content.push_str(&format!("language: {}\n", language));
+    content.push_str(DAILY_GOAL_COMMENT);
+    content.push_str(&format!("daily_goal: {}\n", settings.daily_goal));
+    let unit = match settings.daily_goal_unit {
+        GoalUnit::Minutes => "minutes",
+        GoalUnit::Lines => "lines",
+        GoalUnit::Words => "words",
+    };
+    content.push_str(&format!("daily_goal_unit: {}\n", unit));
+    content.push_str(RUBY_MODE_COMMENT);
+    let ruby_mode = match settings.ruby_mode {
+        RubyMode::Compact => "compact",
+        RubyMode::TwoLine => "two_line",
+    };
+    content.push_str(&format!("ruby_mode: {}\n", ruby_mode));
+    content.push_str(&format!(
+        "copy_ruby_readings: {}\n",
+        settings.copy_ruby_readings
+    ));
+    content.push_str(COPY_QUOTE_BARS_COMMENT);
+    content.push_str(&format!("copy_quote_bars: {}\n", settings.copy_quote_bars));
+    content.push_str(&format!("typography: {}\n", settings.typography));
+    content.push_str(&format!("copy_typography: {}\n", settings.copy_typography));
+    content.push_str(&format!(
+        "copy_image_alt_text: {}\n",
+        settings.copy_image_alt_text
+    ));
+    content.push_str(&format!("confirm_quit: {}\n", settings.confirm_quit));
+    content.push_str(CTRL_Z_COMMENT);
+    let ctrl_z = match settings.ctrl_z {
+        CtrlZ::Suspend => "suspend",
+        CtrlZ::ZenMode => "zen_mode",
+    };
+    content.push_str(&format!("ctrl_z: {}\n", ctrl_z));
+    content.push_str(IDLE_COMMENT);
+    content.push_str(&format!(
+        "idle_timeout_minutes: {}\n",
+        settings.idle_timeout_minutes
+    ));
+    content.push_str(&format!("idle_dim: {}\n", settings.idle_dim));
+    content.push_str(BOOK_BOUNDARY_COMMENT);
+    let book_boundary = match settings.book_boundary {
+        BookBoundary::Notify => "notify",
+        BookBoundary::NextBook => "next_book",
+        BookBoundary::Nothing => "nothing",
+    };
+    content.push_str(&format!("book_boundary: {}\n", book_boundary));
+    content.push_str(EMPTY_CHAPTERS_COMMENT);
+    content.push_str(&format!(
+        "skip_empty_chapters: {}\n",
+        settings.skip_empty_chapters
+    ));
+    content.push_str(&format!(
+        "empty_chapter_words: {}\n",
+        settings.empty_chapter_words
+    ));
+    content.push_str(CHAPTER_READ_COMMENT);
+    content.push_str(&format!(
+        "chapter_read_percent: {}\n",
+        settings.chapter_read_percent
+    ));
+    content.push_str(&format!(
+        "progress_by_chapters_read: {}\n",
+        settings.progress_by_chapters_read
+    ));
+    match settings.prefetch_radius {
+        Some(radius) => {
+            content.push_str(PREFETCH_RADIUS_COMMENT);
+            content.push_str(&format!("prefetch_radius: {radius}\n"));
+        }
+        None => content.push_str(PREFETCH_RADIUS_TEMPLATE),
+    }
+    content.push_str(AUTO_ADVANCE_COMMENT);
+    content.push_str(&format!(
+        "auto_advance_chapters: {}\n",
+        settings.auto_advance_chapters
+    ));
+    content.push_str(SEARCH_HISTORY_COMMENT);
+    content.push_str(&format!(
+        "merge_search_history: {}\n",
+        settings.merge_search_history
+    ));
+    content.push_str(INVISIBLE_CHARS_COMMENT);
+    content.push_str(&format!(
+        "strip_invisible_chars: {}\n",
+        settings.strip_invisible_chars
+    ));
+    content.push_str(BLANK_LINES_COMMENT);
+    content.push_str(&format!("max_blank_lines: {}\n", settings.max_blank_lines));
+    content.push_str(TABLE_STACK_WIDTH_COMMENT);
+    content.push_str(&format!(
+        "table_stack_width: {}\n",
+        settings.table_stack_width
+    ));
+    content.push_str(LIST_INDENT_COMMENT);
+    content.push_str(&format!("list_indent: {}\n", settings.list_indent));
+    let rule_style = match settings.rule_style {
+        RuleStyle::Line => "line",
+        RuleStyle::Dinkus => "dinkus",
+        RuleStyle::Ornament => "ornament",
+    };
+    content.push_str(&format!("rule_style: {}\n", rule_style));
+    content.push_str(&format!("rule_glyph: \"{}\"\n", settings.rule_glyph));
+    content.push_str(SECTION_PROGRESS_COMMENT);
+    content.push_str(&format!(
+        "show_section_progress: {}\n",
+        settings.show_section_progress
+    ));
+    content.push_str(PROGRESS_BAR_COMMENT);
+    content.push_str(&format!("progress_bar: {}\n", settings.progress_bar));
+    content.push_str(READING_RULER_COMMENT);
+    content.push_str(&format!("reading_ruler: {}\n", settings.reading_ruler));
+    match settings.reading_ruler_row {
+        Some(row) => content.push_str(&format!("reading_ruler_row: {}\n", row)),
+        None => content.push_str(READING_RULER_ROW_TEMPLATE),
+    }
+    content.push_str(SCROLL_OFF_COMMENT);
+    content.push_str(&format!("scroll_off: {}\n", settings.scroll_off));
+    content.push_str(JUMP_HIGHLIGHT_COMMENT);
+    content.push_str(&format!(
+        "jump_highlight_ms: {}\n",
+        settings.jump_highlight_ms
+    ));
+    let jump_highlight_style = match settings.jump_highlight_style {
+        JumpHighlightStyle::Text => "text",
+        JumpHighlightStyle::Line => "line",
+    };
+    content.push_str(&format!("jump_highlight_style: {}\n", jump_highlight_style));
+    match &settings.jump_highlight_color {
+        Some(color) => content.push_str(&format!("jump_highlight_color: \"{}\"\n", color)),
+        None => content.push_str(JUMP_HIGHLIGHT_COLOR_TEMPLATE),
+    }
+    content.push_str(CLICK_ACTION_COMMENT);
+    let click_action = match settings.click_action {
+        ClickAction::Select => "select",
+        ClickAction::Position => "position",
+    };
+    content.push_str(&format!("click_action: {}\n", click_action));
+    content.push_str(IMAGE_POPUP_WRAP_COMMENT);
+    content.push_str(&format!(
+        "image_popup_wrap: {}\n",
+        settings.image_popup_wrap
+    ));
+    content.push_str(SPACE_KEY_COMMENT);
+    let space_key = match settings.space_key {
+        SpaceKey::Leader => "leader",
+        SpaceKey::PageDown => "page_down",
+    };
+    content.push_str(&format!("space_key: {}\n", space_key));
+    content.push_str(&format!(
+        "leader_key: {:?}\n",
+        settings.leader_key.to_string()
+    ));
+    content.push_str(GLOSS_COMMENT);
+    content.push_str(&format!(
+        "gloss_rare_words: {}\n",
+        settings.gloss_rare_words
+    ));
+    content.push_str(&format!("gloss_rank: {}\n", settings.gloss_rank));
+    match &settings.word_frequency_list {
+        Some(path) => content.push_str(&format!("word_frequency_list: {:?}\n", path)),
+        None => content.push_str(WORD_FREQUENCY_LIST_TEMPLATE),
+    }
+    match &settings.export_directory {
+        Some(dir) => content.push_str(&format!("export_directory: {:?}\n", dir)),
+        None => content.push_str(EXPORT_DIRECTORY_TEMPLATE),
+    }
+    match &settings.comments_dir {
+        Some(dir) => content.push_str(&format!("comments_dir: {:?}\n", dir)),
+        None => content.push_str(COMMENTS_DIR_TEMPLATE),
+    }
+    content.push_str(RESURFACE_COMMENTS_COMMENT);
+    content.push_str(&format!(
+        "resurface_comments: {}\n",
+        settings.resurface_comments
+    ));
+    content.push_str(&format!("export_images: {}\n", settings.export_images));
+    match &settings.log_level {
+        Some(level) => content.push_str(&format!("log_level: {}\n", level)),
+        None => content.push_str(LOG_LEVEL_TEMPLATE),
+    }
+    match &settings.log_file {
+        Some(file) => content.push_str(&format!("log_file: {:?}\n", file)),
+        None => content.push_str(LOG_FILE_TEMPLATE),
+    }
+    if settings.glyph_substitutions.is_empty() {
+        content.push_str(GLYPH_SUBSTITUTIONS_TEMPLATE);
+    } else {
+        content.push_str(GLYPH_SUBSTITUTIONS_COMMENT);
+        content.push_str("glyph_substitutions:\n");
+        for (codepoint, replacement) in &settings.glyph_substitutions {
+            content.push_str(&format!("  {:?}: {:?}\n", codepoint, replacement));
+        }
+    }
+    if settings.popup_sizes.is_empty() {
+        content.push_str(POPUP_SIZES_TEMPLATE);
+    } else {
+        content.push_str(POPUP_SIZES_COMMENT);
+        content.push_str("popup_sizes:\n");
+        for (popup, size) in &settings.popup_sizes {
+            content.push_str(&format!(
+                "  {}: {{ width: {}, height: {} }}\n",
+                popup, size.width, size.height
+            ));
+        }
+    }
+    match &settings.selection_bg {
+        Some(bg) => content.push_str(&format!("selection_bg: \"{}\"\n", bg)),
+        None => content.push_str(SELECTION_BG_TEMPLATE),
+    }
+    match &settings.selection_fg {
+        Some(fg) => content.push_str(&format!("selection_fg: \"{}\"\n", fg)),
+        None => content.push_str(SELECTION_FG_TEMPLATE),
+    }
+    match &settings.toc_active_color {
+        Some(color) => content.push_str(&format!("toc_active_color: \"{}\"\n", color)),
+        None => content.push_str(TOC_ACTIVE_COLOR_TEMPLATE),
+    }
+    match &settings.toc_chapter_color {
+        Some(color) => content.push_str(&format!("toc_chapter_color: \"{}\"\n", color)),
+        None => content.push_str(TOC_CHAPTER_COLOR_TEMPLATE),
+    }
     content.push('\n');
 
     content.push_str(CUSTOM_THEMES_TEMPLATE);
@@ -196,6 +1039,149 @@ fn generate_settings_yaml(settings: &Settings) -> String {
     content
 }
 
+const BOOKS_DIRECTORY_TEMPLATE: &str =
+    "# books_directory: \"~/Books\"    # Directory scanned for EPUB files\n";
+
+const SELECTION_BG_TEMPLATE: &str =
+    "# selection_bg: \"base03\"    # Selection background: palette slot (base00-base0F) or hex\n";
+const SELECTION_FG_TEMPLATE: &str =
+    "# selection_fg: \"base07\"    # Selection foreground: palette slot (base00-base0F) or hex\n";
+const TOC_ACTIVE_COLOR_TEMPLATE: &str =
+    "# toc_active_color: \"base08\"    # Contents entry of the section being read\n";
+const TOC_CHAPTER_COLOR_TEMPLATE: &str =
+    "# toc_chapter_color: \"base0E\"    # Other contents entries of the current chapter\n";
+
+const DAILY_GOAL_COMMENT: &str =
+    "# Daily reading goal (0 disables); daily_goal_unit is \"minutes\", \"lines\" or \"words\"\n";
+
+const THEME_COMMENT: &str = "# Theme name, \"auto\" to follow the terminal's background or \"system\" to follow the\n\
+     # OS dark mode: light_theme (Catppuccin Latte) when light, dark_theme (Oceanic Next) when dark\n";
+const LIGHT_THEME_TEMPLATE: &str = "# light_theme: \"Catppuccin Latte\"\n";
+const DARK_THEME_TEMPLATE: &str = "# dark_theme: \"Oceanic Next\"\n";
+
+const COLOR_MODE_COMMENT: &str =
+    "# Colors: auto, truecolor, ansi256, ansi16, ansi8 or none (bold/underline/reverse only)\n";
+
+const LANGUAGE_COMMENT: &str =
+    "# Interface language: auto (from $LANG), en or ru; missing strings show in English\n";
+
+const RUBY_MODE_COMMENT: &str =
+    "# Ruby (furigana): \"compact\" shows base(reading), \"two_line\" puts readings above\n";
+
+const COPY_QUOTE_BARS_COMMENT: &str = "# Copy quotes with the │ bars shown down their left\n";
+
+const MARGIN_COMMENT: &str =
+    "# Content margin of books without their own (+/- while reading set a book's margin)\n";
+
+const NAV_PANEL_MODE_COMMENT: &str =
+    "# Navigation panel: \"always\", \"auto\" (collapse while reading) or \"never\" (zen)\n";
+
+const BOOK_LIST_SERIES_COMMENT: &str =
+    "# Show the series of each book after its name in the book list, e.g. · Earthsea #2\n";
+
+const TERMINAL_SIZE_COMMENT: &str =
+    "# Smaller terminals get a size warning (min_*) or a single-panel layout (compact_*)\n";
+
+const CTRL_Z_COMMENT: &str =
+    "# Ctrl+z: \"suspend\" to the shell, or \"zen_mode\" to toggle zen mode as it used to\n";
+
+const IDLE_COMMENT: &str =
+    "# Idle after idle_timeout_minutes without input (0 = never): place saved, idle_dim dims\n";
+
+const BOOK_BOUNDARY_COMMENT: &str =
+    "# What l does after the last chapter: \"notify\", \"next_book\" or \"nothing\"\n";
+
+const CHAPTER_READ_COMMENT: &str = "# Chapters count as read once scrolled this far (percent)\n\
+     # progress_by_chapters_read: reading history bars count chapters read\n";
+
+const PREFETCH_RADIUS_COMMENT: &str =
+    "# Chapters around the current one prepared ahead; the rest load when visited or searched\n";
+
+const PREFETCH_RADIUS_TEMPLATE: &str =
+    "# prefetch_radius: 2    # Chapters around the current one prepared ahead (default: all)\n";
+
+const EMPTY_CHAPTERS_COMMENT: &str =
+    "# h/l skip chapters under empty_chapter_words words (covers, image-only pages)\n";
+
+const AUTO_ADVANCE_COMMENT: &str =
+    "# Scrolling on from the end of a chapter opens the next one instead of stopping\n";
+
+const SEARCH_HISTORY_COMMENT: &str =
+    "# Up/Down recall past searches; true shares one history between / and Space+f\n";
+
+const INVISIBLE_CHARS_COMMENT: &str =
+    "# Hide soft hyphens and zero-width spaces in the text (search ignores them anyway)\n";
+
+const GLOSS_COMMENT: &str =
+    "# Mark words ranked past gloss_rank in the frequency list (Space+w lists them)\n";
+const RESURFACE_COMMENTS_COMMENT: &str =
+    "# Show a random comment from the library on startup (Space+q shows one any time)\n";
+const WORD_FREQUENCY_LIST_TEMPLATE: &str =
+    "# word_frequency_list: \"~/words.txt\"    # One word per line, most frequent first\n";
+
+const GLYPH_SUBSTITUTIONS_COMMENT: &str =
+    "# Replacements for embedded-font glyphs (hex codepoint: text); others show as □\n";
+const EXPORT_DIRECTORY_TEMPLATE: &str = "# export_directory: \"~/notes\"    # Where Space+e saves chapters and Space+? screens (~/bookokrat-exports)\n";
+const COMMENTS_DIR_TEMPLATE: &str = "# comments_dir: \"~/notes/comments\"    # Where comments are kept (./.bookokrat_comments); Space+A moves them\n";
+const LOG_LEVEL_TEMPLATE: &str =
+    "# log_level: debug    # Log messages kept: error, warn, info (default), debug, trace or off\n";
+const LOG_FILE_TEMPLATE: &str =
+    "# log_file: \"~/bookokrat.log\"    # Where the log goes (~/.bookokrat_logs/bookokrat.log)\n";
+
+const GLYPH_SUBSTITUTIONS_TEMPLATE: &str =
+    "# Replacements for embedded-font glyphs (hex codepoint: text); others show as □
+# glyph_substitutions:
+#   \"F0D8\": \"➢\"
+";
+
+const POPUP_SIZES_COMMENT: &str =
+    "# Popup sizes in percent of the screen, set with Ctrl+arrows or +/- in the popup\n";
+const POPUP_SIZES_TEMPLATE: &str =
+    "# Popup sizes in percent of the screen, set with Ctrl+arrows or +/- in the popup
+# popup_sizes:
+#   comments_viewer: { width: 85, height: 90 }
+";
+
+const BLANK_LINES_COMMENT: &str =
+    "# Squeeze blank runs to max_blank_lines (0 = off); rule_style: \"line\", \"dinkus\"
+# or \"ornament\" (a dimmed ─── ❧ ─── line with rule_glyph in the middle)
+";
+
+const TABLE_STACK_WIDTH_COMMENT: &str =
+    "# Tables narrower than this are shown as header: value lists (0 = always a grid)\n";
+
+const LIST_INDENT_COMMENT: &str =
+    "# Columns a nested list steps in by, at least to its parent item's text\n";
+
+const SECTION_PROGRESS_COMMENT: &str =
+    "# Footer shows \"Section 3/5 40%\" when the chapter has several TOC sections\n";
+
+const PROGRESS_BAR_COMMENT: &str =
+    "# Shade the reader's bottom border up to the chapter progress (true color terminals)\n";
+
+const READING_RULER_COMMENT: &str =
+    "# Tint one row of the reader to read along (Space+r toggles, Shift+J/K move it)\n";
+const READING_RULER_ROW_TEMPLATE: &str =
+    "# reading_ruler_row: 10    # Row of the reader the ruler is on (default: the middle)\n";
+
+const SCROLL_OFF_COMMENT: &str =
+    "# Lines of context kept above and below links, anchors and search results jumped to\n";
+
+const JUMP_HIGHLIGHT_COMMENT: &str =
+    "# The line jumped to flashes for jump_highlight_ms (0 = never); jump_highlight_style:
+# \"text\" colors behind the text, \"line\" the whole width of the reader\n";
+const JUMP_HIGHLIGHT_COLOR_TEMPLATE: &str =
+    "# jump_highlight_color: \"base02\"    # Jump highlight: palette slot (base00-base0F) or hex\n";
+
+const CLICK_ACTION_COMMENT: &str =
+    "# A click without a drag: \"select\" anchors a selection, \"position\" places a caret\n";
+
+const IMAGE_POPUP_WRAP_COMMENT: &str =
+    "# h/l in the image popup wrap from the chapter's last image to its first\n";
+const SPACE_KEY_COMMENT: &str =
+    "# Space: \"leader\" starts Space+ key sequences, \"page_down\" pages down
+# and leader_key starts them instead\n";
+
 const CUSTOM_THEMES_TEMPLATE: &str = r#"# ============================================================================
 # Custom Themes
 # ============================================================================
@@ -233,6 +1219,14 @@ pub fn get_theme_name() -> String {
         .unwrap_or_else(|_| default_theme())
 }
 
+pub fn get_light_theme() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.light_theme.clone())
+}
+
+pub fn get_dark_theme() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.dark_theme.clone())
+}
+
 pub fn set_theme_name(name: &str) {
     if let Ok(mut settings) = SETTINGS.write() {
         settings.theme = name.to_string();
@@ -240,17 +1234,426 @@ pub fn set_theme_name(name: &str) {
     save_settings();
 }
 
+pub fn get_books_directory() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.books_directory.clone())
+}
+
+pub fn set_books_directory(directory: &str) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.books_directory = Some(directory.to_string());
+    }
+    save_settings();
+}
+
 pub fn get_margin() -> u16 {
     SETTINGS.read().map(|s| s.margin).unwrap_or(0)
 }
 
-pub fn set_margin(margin: u16) {
+pub fn get_nav_panel_width() -> u16 {
+    SETTINGS
+        .read()
+        .map(|s| s.nav_panel_width)
+        .unwrap_or_else(|_| default_nav_panel_width())
+}
+
+pub fn set_nav_panel_width(width: u16) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.nav_panel_width = width;
+    }
+    save_settings();
+}
+
+pub fn get_nav_panel_mode() -> NavPanelMode {
+    SETTINGS
+        .read()
+        .map(|s| s.nav_panel_mode)
+        .unwrap_or_default()
+}
+
+pub fn get_book_list_series() -> bool {
+    SETTINGS.read().map(|s| s.book_list_series).unwrap_or(false)
+}
+
+pub fn get_render_images() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.render_images)
+        .unwrap_or_else(|_| default_render_images())
+}
+
+pub fn set_render_images(render_images: bool) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.render_images = render_images;
+    }
+    save_settings();
+}
+
+pub fn get_daily_goal() -> Option<DailyGoal> {
+    SETTINGS
+        .read()
+        .ok()
+        .filter(|s| s.daily_goal > 0)
+        .map(|s| DailyGoal {
+            target: s.daily_goal,
+            unit: s.daily_goal_unit,
+        })
+}
+
+pub fn get_color_mode() -> ColorMode {
+    SETTINGS.read().map(|s| s.color_mode).unwrap_or_default()
+}
+
+pub fn get_language() -> Language {
+    SETTINGS.read().map(|s| s.language).unwrap_or_default()
+}
+
+pub fn get_ruby_mode() -> RubyMode {
+    SETTINGS.read().map(|s| s.ruby_mode).unwrap_or_default()
+}
+
+pub fn get_copy_ruby_readings() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.copy_ruby_readings)
+        .unwrap_or(false)
+}
+
+pub fn get_copy_quote_bars() -> bool {
+    SETTINGS.read().map(|s| s.copy_quote_bars).unwrap_or(false)
+}
+
+pub fn get_typography() -> bool {
+    SETTINGS.read().map(|s| s.typography).unwrap_or(false)
+}
+
+pub fn get_copy_typography() -> bool {
+    SETTINGS.read().map(|s| s.copy_typography).unwrap_or(true)
+}
+
+pub fn get_copy_image_alt_text() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.copy_image_alt_text)
+        .unwrap_or(true)
+}
+
+pub fn get_confirm_quit() -> bool {
+    SETTINGS.read().map(|s| s.confirm_quit).unwrap_or(false)
+}
+
+pub fn get_idle_timeout_minutes() -> u64 {
+    SETTINGS
+        .read()
+        .map(|s| s.idle_timeout_minutes)
+        .unwrap_or_else(|_| default_idle_timeout_minutes())
+}
+
+pub fn get_idle_dim() -> bool {
+    SETTINGS.read().map(|s| s.idle_dim).unwrap_or(true)
+}
+
+/// Width and height below which nothing but a size warning is drawn
+pub fn get_min_terminal_size() -> (u16, u16) {
+    SETTINGS
+        .read()
+        .map(|s| (s.min_terminal_width, s.min_terminal_height))
+        .unwrap_or((default_min_terminal_width(), default_min_terminal_height()))
+}
+
+/// Width and height below which the help bar and side panel are dropped
+pub fn get_compact_terminal_size() -> (u16, u16) {
+    SETTINGS
+        .read()
+        .map(|s| (s.compact_terminal_width, s.compact_terminal_height))
+        .unwrap_or((
+            default_compact_terminal_width(),
+            default_compact_terminal_height(),
+        ))
+}
+
+pub fn get_ctrl_z() -> CtrlZ {
+    SETTINGS.read().map(|s| s.ctrl_z).unwrap_or_default()
+}
+
+pub fn get_book_boundary() -> BookBoundary {
+    SETTINGS.read().map(|s| s.book_boundary).unwrap_or_default()
+}
+
+pub fn get_skip_empty_chapters() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.skip_empty_chapters)
+        .unwrap_or(false)
+}
+
+pub fn get_auto_advance_chapters() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.auto_advance_chapters)
+        .unwrap_or(false)
+}
+
+pub fn get_merge_search_history() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.merge_search_history)
+        .unwrap_or(false)
+}
+
+pub fn get_strip_invisible_chars() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.strip_invisible_chars)
+        .unwrap_or(false)
+}
+
+pub fn get_empty_chapter_words() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.empty_chapter_words)
+        .unwrap_or_else(|_| default_empty_chapter_words())
+}
+
+pub fn get_chapter_read_percent() -> u8 {
+    SETTINGS
+        .read()
+        .map(|s| s.chapter_read_percent.min(100))
+        .unwrap_or_else(|_| default_chapter_read_percent())
+}
+
+pub fn get_progress_by_chapters_read() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.progress_by_chapters_read)
+        .unwrap_or(false)
+}
+
+pub fn get_prefetch_radius() -> Option<usize> {
+    SETTINGS.read().ok().and_then(|s| s.prefetch_radius)
+}
+
+pub fn get_gloss_rare_words() -> bool {
+    SETTINGS.read().map(|s| s.gloss_rare_words).unwrap_or(false)
+}
+
+pub fn get_resurface_comments() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.resurface_comments)
+        .unwrap_or(false)
+}
+
+pub fn get_gloss_rank() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.gloss_rank)
+        .unwrap_or_else(|_| default_gloss_rank())
+}
+
+pub fn get_word_frequency_list() -> Option<String> {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.word_frequency_list.clone())
+}
+
+pub fn get_export_directory() -> String {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.export_directory.clone())
+        .unwrap_or_else(|| crate::chapter_export::DEFAULT_EXPORT_DIRECTORY.to_string())
+}
+
+/// The `log_level` and `log_file` settings on their own
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct LogSettings {
+    pub log_level: Option<String>,
+    pub log_file: Option<String>,
+}
+
+/// The logging settings, read quietly from the file so the log can start
+/// before [`load_settings`] and catch its warnings; defaults when the file
+/// is missing or broken
+pub fn read_log_settings() -> LogSettings {
+    settings_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_yaml::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+pub fn get_comments_dir() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.comments_dir.clone())
+}
+
+pub fn set_comments_dir(directory: &str) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.comments_dir = Some(directory.to_string());
+    }
+    save_settings();
+}
+
+pub fn get_export_images() -> bool {
+    SETTINGS.read().map(|s| s.export_images).unwrap_or(true)
+}
+
+pub fn get_max_blank_lines() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.max_blank_lines)
+        .unwrap_or(DEFAULT_MAX_BLANK_LINES)
+}
+
+pub fn get_table_stack_width() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.table_stack_width)
+        .unwrap_or(DEFAULT_TABLE_STACK_WIDTH)
+}
+
+pub fn get_list_indent() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.list_indent)
+        .unwrap_or(DEFAULT_LIST_INDENT)
+}
+
+pub fn get_reading_ruler() -> bool {
+    SETTINGS.read().map(|s| s.reading_ruler).unwrap_or(false)
+}
+
+pub fn set_reading_ruler(reading_ruler: bool) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.reading_ruler = reading_ruler;
+    }
+    save_settings();
+}
+
+pub fn get_reading_ruler_row() -> Option<u16> {
+    SETTINGS.read().ok().and_then(|s| s.reading_ruler_row)
+}
+
+pub fn set_reading_ruler_row(row: u16) {
+    if let Ok(mut settings) = SETTINGS.write() {
+        settings.reading_ruler_row = Some(row);
+    }
+    save_settings();
+}
+
+pub fn get_progress_bar() -> bool {
+    SETTINGS.read().map(|s| s.progress_bar).unwrap_or(false)
+}
+
+pub fn get_show_section_progress() -> bool {
+    SETTINGS
+        .read()
+        .map(|s| s.show_section_progress)
+        .unwrap_or(true)
+}
+
+pub fn get_scroll_off() -> usize {
+    SETTINGS
+        .read()
+        .map(|s| s.scroll_off)
+        .unwrap_or(DEFAULT_SCROLL_OFF)
+}
+
+pub fn get_jump_highlight_ms() -> u64 {
+    SETTINGS
+        .read()
+        .map(|s| s.jump_highlight_ms)
+        .unwrap_or(DEFAULT_JUMP_HIGHLIGHT_MS)
+}
+
+pub fn get_jump_highlight_style() -> JumpHighlightStyle {
+    SETTINGS
+        .read()
+        .map(|s| s.jump_highlight_style)
+        .unwrap_or_default()
+}
+
+pub fn get_jump_highlight_color() -> Option<String> {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.jump_highlight_color.clone())
+}
+
+pub fn get_click_action() -> ClickAction {
+    SETTINGS.read().map(|s| s.click_action).unwrap_or_default()
+}
+
+pub fn get_image_popup_wrap() -> bool {
+    SETTINGS.read().map(|s| s.image_popup_wrap).unwrap_or(true)
+}
+
+/// Key that starts the Space+ sequences: Space, or `leader_key` when
+/// Space pages down
+pub fn get_leader_key() -> char {
+    SETTINGS
+        .read()
+        .map(|s| match s.space_key {
+            SpaceKey::Leader => LEADER,
+            SpaceKey::PageDown => s.leader_key,
+        })
+        .unwrap_or(LEADER)
+}
+
+pub fn get_rule_glyph() -> String {
+    SETTINGS
+        .read()
+        .map(|s| s.rule_glyph.clone())
+        .unwrap_or_else(|_| default_rule_glyph())
+}
+
+pub fn get_rule_style() -> RuleStyle {
+    SETTINGS.read().map(|s| s.rule_style).unwrap_or_default()
+}
+
+pub fn get_selection_bg() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.selection_bg.clone())
+}
+
+pub fn get_selection_fg() -> Option<String> {
+    SETTINGS.read().ok().and_then(|s| s.selection_fg.clone())
+}
+
+pub fn get_glyph_substitutions() -> BTreeMap<String, String> {
+    SETTINGS
+        .read()
+        .map(|s| s.glyph_substitutions.clone())
+        .unwrap_or_default()
+}
+
+pub fn get_popup_size(popup: &str) -> Option<PopupSize> {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.popup_sizes.get(popup).copied())
+}
+
+pub fn set_popup_size(popup: &str, size: PopupSize) {
     if let Ok(mut settings) = SETTINGS.write() {
-        settings.margin = margin;
+        settings.popup_sizes.insert(popup.to_string(), size);
     }
     save_settings();
 }
 
+pub fn get_toc_active_color() -> Option<String> {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.toc_active_color.clone())
+}
+
+pub fn get_toc_chapter_color() -> Option<String> {
+    SETTINGS
+        .read()
+        .ok()
+        .and_then(|s| s.toc_chapter_color.clone())
+}
+
 pub fn get_custom_themes() -> Vec<YamlTheme> {
     SETTINGS
         .read()
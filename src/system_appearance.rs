@@ -0,0 +1,159 @@
+//! Following the operating system's dark/light mode with `theme: system`.
+//!
+//! macOS keeps the mode in the `AppleInterfaceStyle` default, which is only
+//! set in dark mode; Linux desktops publish the freedesktop color scheme
+//! that `gsettings` reports. Either is asked every thirty seconds from the
+//! run loop's tick. Where neither answers, the first failure turns the
+//! probing off for the session.
+
+use crate::color_mode::TerminalBackground;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+/// How often the system is asked for its mode
+pub const APPEARANCE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Asks the system whether it is in dark or light mode (mockable for testing)
+pub trait AppearanceProbe {
+    /// The current mode, None when it can't be found out
+    fn probe(&mut self) -> Option<TerminalBackground>;
+}
+
+pub struct RealAppearanceProbe;
+
+impl AppearanceProbe for RealAppearanceProbe {
+    fn probe(&mut self) -> Option<TerminalBackground> {
+        if cfg!(target_os = "macos") {
+            let output = Command::new("defaults")
+                .args(["read", "-g", "AppleInterfaceStyle"])
+                .output()
+                .ok()?;
+            // The key is missing, and the command fails, in light mode
+            Some(parse_macos_style(
+                output.status.success(),
+                &String::from_utf8_lossy(&output.stdout),
+            ))
+        } else if cfg!(unix) {
+            let output = Command::new("gsettings")
+                .args(["get", "org.gnome.desktop.interface", "color-scheme"])
+                .output()
+                .ok()?;
+            if !output.status.success() {
+                return None;
+            }
+            Some(parse_color_scheme(&String::from_utf8_lossy(&output.stdout)))
+        } else {
+            None
+        }
+    }
+}
+
+fn parse_macos_style(found: bool, style: &str) -> TerminalBackground {
+    if found && style.trim().eq_ignore_ascii_case("dark") {
+        TerminalBackground::Dark
+    } else {
+        TerminalBackground::Light
+    }
+}
+
+/// `'prefer-dark'` is dark; `'default'` and `'prefer-light'` are light
+fn parse_color_scheme(scheme: &str) -> TerminalBackground {
+    if scheme.trim().trim_matches('\'') == "prefer-dark" {
+        TerminalBackground::Dark
+    } else {
+        TerminalBackground::Light
+    }
+}
+
+/// The system's mode as last probed
+pub struct SystemAppearance {
+    probe: Box<dyn AppearanceProbe>,
+    current: Option<TerminalBackground>,
+    last_probe: Option<Instant>,
+    disabled: bool,
+}
+
+impl SystemAppearance {
+    pub fn new(probe: Box<dyn AppearanceProbe>) -> Self {
+        Self {
+            probe,
+            current: None,
+            last_probe: None,
+            disabled: false,
+        }
+    }
+
+    /// Probe when the interval is up; returns the mode when it differs from
+    /// the last one seen, including the first time it is known
+    pub fn poll(&mut self, now: Instant) -> Option<TerminalBackground> {
+        if self.disabled
+            || self
+                .last_probe
+                .is_some_and(|last| now.duration_since(last) < APPEARANCE_POLL_INTERVAL)
+        {
+            return None;
+        }
+        self.last_probe = Some(now);
+
+        let Some(mode) = self.probe.probe() else {
+            log::debug!("System dark/light mode unavailable, no longer following it");
+            self.disabled = true;
+            return None;
+        };
+        if self.current == Some(mode) {
+            return None;
+        }
+        self.current = Some(mode);
+        Some(mode)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    struct ScriptedProbe(Rc<RefCell<Vec<Option<TerminalBackground>>>>);
+
+    impl AppearanceProbe for ScriptedProbe {
+        fn probe(&mut self) -> Option<TerminalBackground> {
+            self.0.borrow_mut().remove(0)
+        }
+    }
+
+    #[test]
+    fn test_changes_are_reported_once_per_interval() {
+        use TerminalBackground::{Dark, Light};
+        let answers = Rc::new(RefCell::new(vec![Some(Light), Some(Light), Some(Dark)]));
+        let mut appearance = SystemAppearance::new(Box::new(ScriptedProbe(answers.clone())));
+        let start = Instant::now();
+
+        assert_eq!(appearance.poll(start), Some(Light));
+        // Not asked again until the interval is up
+        assert_eq!(appearance.poll(start + Duration::from_secs(1)), None);
+        assert_eq!(appearance.poll(start + APPEARANCE_POLL_INTERVAL), None);
+        assert_eq!(
+            appearance.poll(start + 2 * APPEARANCE_POLL_INTERVAL),
+            Some(Dark)
+        );
+        assert!(answers.borrow().is_empty());
+
+        // A failed probe turns it off for good
+        *answers.borrow_mut() = vec![None, Some(Light)];
+        assert_eq!(appearance.poll(start + 3 * APPEARANCE_POLL_INTERVAL), None);
+        assert_eq!(appearance.poll(start + 4 * APPEARANCE_POLL_INTERVAL), None);
+        assert_eq!(answers.borrow().len(), 1);
+    }
+
+    #[test]
+    fn test_system_answers_are_parsed() {
+        assert_eq!(parse_macos_style(true, "Dark\n"), TerminalBackground::Dark);
+        assert_eq!(parse_macos_style(false, ""), TerminalBackground::Light);
+        assert_eq!(
+            parse_color_scheme("'prefer-dark'\n"),
+            TerminalBackground::Dark
+        );
+        assert_eq!(parse_color_scheme("'default'\n"), TerminalBackground::Light);
+    }
+}
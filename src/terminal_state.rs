@@ -0,0 +1,96 @@
+//! Handing the terminal back and taking it again.
+//!
+//! Bookokrat draws on the alternate screen in raw mode, with the mouse
+//! captured unless Space+m left it to the terminal. The panic handler gives
+//! the terminal back before printing the panic, suspending (Ctrl+Z) gives it
+//! back to the shell until the job is resumed with `fg`, and closing the
+//! terminal (SIGHUP) or SIGTERM end the app as a quit would.
+
+use crossterm::{
+    cursor::Show,
+    event::{DisableMouseCapture, EnableMouseCapture},
+    execute,
+    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+};
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static QUIT_SIGNALLED: AtomicBool = AtomicBool::new(false);
+
+/// Raw mode, the alternate screen and, with `mouse_capture`, the mouse.
+/// Keeps going past errors, so a failed step leaves the rest claimed.
+pub fn claim_terminal(mouse_capture: bool) -> io::Result<()> {
+    let raw_mode = enable_raw_mode();
+    let screen = execute!(io::stdout(), EnterAlternateScreen);
+    let mouse = if mouse_capture {
+        execute!(io::stdout(), EnableMouseCapture)
+    } else {
+        Ok(())
+    };
+    raw_mode.and(screen).and(mouse)
+}
+
+/// The terminal as the shell had it: cooked mode, the main screen, the
+/// mouse released and the cursor shown. Keeps going past errors, since it
+/// also runs while panicking.
+pub fn release_terminal() -> io::Result<()> {
+    let raw_mode = disable_raw_mode();
+    let screen = execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        Show
+    );
+    raw_mode.and(screen)
+}
+
+/// Stop the process as Ctrl+Z in a shell would, with the terminal given
+/// back; returns once the job is continued, with the terminal claimed again
+#[cfg(unix)]
+pub fn suspend(mouse_capture: bool) -> io::Result<()> {
+    let stopped = release_terminal().and_then(|()| {
+        // Raw mode kept the terminal from sending SIGTSTP for Ctrl+Z; the
+        // default action stops the process until SIGCONT
+        if unsafe { libc::raise(libc::SIGTSTP) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    });
+    // Take the terminal back however far giving it away got
+    let claimed = claim_terminal(mouse_capture);
+    stopped.and(claimed)
+}
+
+#[cfg(not(unix))]
+pub fn suspend(_mouse_capture: bool) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "suspending needs job control",
+    ))
+}
+
+/// Catch SIGHUP and SIGTERM instead of dying on them, so the main loop can
+/// end and the session be saved and the terminal given back
+#[cfg(unix)]
+pub fn install_quit_signal_handlers() {
+    extern "C" fn on_quit_signal(_signal: libc::c_int) {
+        QUIT_SIGNALLED.store(true, Ordering::SeqCst);
+    }
+    for signal in [libc::SIGHUP, libc::SIGTERM] {
+        let handler = on_quit_signal as extern "C" fn(libc::c_int) as libc::sighandler_t;
+        if unsafe { libc::signal(signal, handler) } == libc::SIG_ERR {
+            log::warn!(
+                "Failed to catch signal {signal}: {}",
+                io::Error::last_os_error()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+pub fn install_quit_signal_handlers() {}
+
+/// A SIGHUP or SIGTERM came in; the app should save and exit
+pub fn quit_signalled() -> bool {
+    QUIT_SIGNALLED.load(Ordering::SeqCst)
+}
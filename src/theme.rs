@@ -1,8 +1,12 @@
-use crate::color_mode::smart_color;
+use crate::color_mode::{
+    ColorLevel, TerminalBackground, color_level, downsample, is_monochrome, smart_color,
+    terminal_background,
+};
 use crate::settings::{self, YamlTheme};
 use log::{debug, warn};
 use once_cell::sync::Lazy;
-use ratatui::style::Color;
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
 use std::sync::RwLock;
 use std::sync::atomic::{AtomicUsize, Ordering};
 
@@ -41,6 +45,7 @@ pub struct Theme {
 pub enum BuiltinTheme {
     OceanicNext,
     CatppuccinMocha,
+    CatppuccinLatte,
     Kanagawa,
     KanagawaDragon,
 }
@@ -50,6 +55,7 @@ impl BuiltinTheme {
         match self {
             BuiltinTheme::OceanicNext => "Oceanic Next",
             BuiltinTheme::CatppuccinMocha => "Catppuccin Mocha",
+            BuiltinTheme::CatppuccinLatte => "Catppuccin Latte",
             BuiltinTheme::Kanagawa => "Kanagawa",
             BuiltinTheme::KanagawaDragon => "Kanagawa Dragon",
         }
@@ -59,6 +65,7 @@ impl BuiltinTheme {
         match self {
             BuiltinTheme::OceanicNext => &OCEANIC_NEXT_PALETTE,
             BuiltinTheme::CatppuccinMocha => &CATPPUCCIN_MOCHA_PALETTE,
+            BuiltinTheme::CatppuccinLatte => &CATPPUCCIN_LATTE_PALETTE,
             BuiltinTheme::Kanagawa => &KANAGAWA_PALETTE,
             BuiltinTheme::KanagawaDragon => &KANAGAWA_DRAGON_PALETTE,
         }
@@ -68,6 +75,7 @@ impl BuiltinTheme {
         &[
             BuiltinTheme::OceanicNext,
             BuiltinTheme::CatppuccinMocha,
+            BuiltinTheme::CatppuccinLatte,
             BuiltinTheme::Kanagawa,
             BuiltinTheme::KanagawaDragon,
         ]
@@ -104,13 +112,96 @@ pub fn load_custom_themes() {
     }
 
     // Apply saved theme from settings
-    let saved_theme = settings::get_theme_name();
+    let mut saved_theme = settings::get_theme_name();
+    // Until the system is asked, `system` starts like `auto`
+    if saved_theme == settings::AUTO_THEME || saved_theme == settings::SYSTEM_THEME {
+        saved_theme = auto_theme_name(terminal_background());
+    }
     if let Some(index) = get_theme_index_by_name(&saved_theme) {
         CURRENT_THEME_INDEX.store(index, Ordering::Relaxed);
         debug!("Applied saved theme: {}", saved_theme);
     } else {
         warn!("Saved theme '{}' not found, using default", saved_theme);
     }
+
+    load_color_overrides();
+}
+
+/// The theme `theme: auto` and `theme: system` stand for: `light_theme` on a
+/// light background, otherwise `dark_theme`
+pub fn auto_theme_name(background: Option<TerminalBackground>) -> String {
+    match background {
+        Some(TerminalBackground::Light) => settings::get_light_theme()
+            .unwrap_or_else(|| BuiltinTheme::CatppuccinLatte.name().to_string()),
+        Some(TerminalBackground::Dark) | None => settings::get_dark_theme()
+            .unwrap_or_else(|| BuiltinTheme::OceanicNext.name().to_string()),
+    }
+}
+
+/// Switch to the theme for a light or dark background without saving it;
+/// returns its name when the theme changed
+pub fn apply_auto_theme(background: TerminalBackground) -> Option<String> {
+    let name = auto_theme_name(Some(background));
+    let Some(index) = get_theme_index_by_name(&name) else {
+        warn!("Theme '{}' not found, keeping the current one", name);
+        return None;
+    };
+    if index == current_theme_index() {
+        return None;
+    }
+    CURRENT_THEME_INDEX.store(index, Ordering::Relaxed);
+    Some(name)
+}
+
+/// Parse the color overrides once at startup, reporting invalid ones here
+/// instead of on every frame
+fn load_color_overrides() {
+    let parse = |key: &str, value: Option<String>| {
+        let value = value?;
+        parse_color_override(&value)
+            .map_err(|e| warn!("Ignoring {key} override '{value}': {e}"))
+            .ok()
+    };
+    let selection = (
+        parse("selection_bg", settings::get_selection_bg()),
+        parse("selection_fg", settings::get_selection_fg()),
+    );
+    if let Ok(mut overrides) = SELECTION_OVERRIDES.write() {
+        *overrides = selection;
+    }
+    parse("toc_active_color", settings::get_toc_active_color());
+    parse("toc_chapter_color", settings::get_toc_chapter_color());
+    parse("jump_highlight_color", settings::get_jump_highlight_color());
+}
+
+/// A color setting: a palette slot, looked up in whichever theme is current,
+/// or a fixed color
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ColorOverride {
+    Slot(u8),
+    Fixed(Color),
+}
+
+/// `selection_bg`/`selection_fg`, parsed when the settings load so rendering
+/// never goes back to the settings
+static SELECTION_OVERRIDES: RwLock<(Option<ColorOverride>, Option<ColorOverride>)> =
+    RwLock::new((None, None));
+
+/// Parse a color setting: either a palette slot name (`base00`-`base0F`) or a hex color
+fn parse_color_override(value: &str) -> Result<ColorOverride, String> {
+    let value = value.trim();
+    let name = value.to_ascii_lowercase().replace('_', "");
+    if let Some(slot) = name.strip_prefix("base").filter(|slot| slot.len() == 2) {
+        if let Ok(slot @ 0..=0x0f) = u8::from_str_radix(slot, 16) {
+            return Ok(ColorOverride::Slot(slot));
+        }
+    }
+    if value.trim_start_matches('#').len() == 6 {
+        return parse_hex_color(value).map(ColorOverride::Fixed);
+    }
+    Err(format!(
+        "expected a palette slot (base00-base0F) or a 6-digit hex color, got '{value}'"
+    ))
 }
 
 fn theme_from_yaml(yaml: &YamlTheme) -> Result<Theme, String> {
@@ -135,7 +226,7 @@ fn theme_from_yaml(yaml: &YamlTheme) -> Result<Theme, String> {
 
     Ok(Theme {
         name: yaml.scheme.clone(),
-        palette,
+        palette: palette.with_safe_grays(color_level()),
         is_builtin: false,
     })
 }
@@ -291,83 +382,119 @@ pub fn current_theme() -> &'static Base16Palette {
 // ============================================================================
 
 // Oceanic Next theme
-static OCEANIC_NEXT_PALETTE: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
-    base_00: smart_color(0x1B2B34),
-    base_01: smart_color(0x343D46),
-    base_02: smart_color(0x4F5B66),
-    base_03: smart_color(0x65737E),
-    base_04: smart_color(0xA7ADBA),
-    base_05: smart_color(0xC0C5CE),
-    base_06: smart_color(0xCDD3DE),
-    base_07: smart_color(0xF0F4F8),
-    base_08: smart_color(0xEC5F67),
-    base_09: smart_color(0xF99157),
-    base_0a: smart_color(0xFAC863),
-    base_0b: smart_color(0x99C794),
-    base_0c: smart_color(0x5FB3B3),
-    base_0d: smart_color(0x6699CC),
-    base_0e: smart_color(0xC594C5),
-    base_0f: smart_color(0xAB7967),
+static OCEANIC_NEXT_PALETTE: Lazy<Base16Palette> = Lazy::new(|| {
+    Base16Palette {
+        base_00: smart_color(0x1B2B34),
+        base_01: smart_color(0x343D46),
+        base_02: smart_color(0x4F5B66),
+        base_03: smart_color(0x65737E),
+        base_04: smart_color(0xA7ADBA),
+        base_05: smart_color(0xC0C5CE),
+        base_06: smart_color(0xCDD3DE),
+        base_07: smart_color(0xF0F4F8),
+        base_08: smart_color(0xEC5F67),
+        base_09: smart_color(0xF99157),
+        base_0a: smart_color(0xFAC863),
+        base_0b: smart_color(0x99C794),
+        base_0c: smart_color(0x5FB3B3),
+        base_0d: smart_color(0x6699CC),
+        base_0e: smart_color(0xC594C5),
+        base_0f: smart_color(0xAB7967),
+    }
+    .with_safe_grays(color_level())
 });
 
 // Catppuccin Mocha theme
-static CATPPUCCIN_MOCHA_PALETTE: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
-    base_00: smart_color(0x1E1E2E),
-    base_01: smart_color(0x313244),
-    base_02: smart_color(0x45475A),
-    base_03: smart_color(0x6C7086),
-    base_04: smart_color(0x7F849C),
-    base_05: smart_color(0xA6ADC8),
-    base_06: smart_color(0xCDD6F4),
-    base_07: smart_color(0xF5E0DC),
-    base_08: smart_color(0xF38BA8),
-    base_09: smart_color(0xFAB387),
-    base_0a: smart_color(0xF9E2AF),
-    base_0b: smart_color(0xA6E3A1),
-    base_0c: smart_color(0x94E2D5),
-    base_0d: smart_color(0x89B4FA),
-    base_0e: smart_color(0xCBA6F7),
-    base_0f: smart_color(0xEBA0AC),
+static CATPPUCCIN_MOCHA_PALETTE: Lazy<Base16Palette> = Lazy::new(|| {
+    Base16Palette {
+        base_00: smart_color(0x1E1E2E),
+        base_01: smart_color(0x313244),
+        base_02: smart_color(0x45475A),
+        base_03: smart_color(0x6C7086),
+        base_04: smart_color(0x7F849C),
+        base_05: smart_color(0xA6ADC8),
+        base_06: smart_color(0xCDD6F4),
+        base_07: smart_color(0xF5E0DC),
+        base_08: smart_color(0xF38BA8),
+        base_09: smart_color(0xFAB387),
+        base_0a: smart_color(0xF9E2AF),
+        base_0b: smart_color(0xA6E3A1),
+        base_0c: smart_color(0x94E2D5),
+        base_0d: smart_color(0x89B4FA),
+        base_0e: smart_color(0xCBA6F7),
+        base_0f: smart_color(0xEBA0AC),
+    }
+    .with_safe_grays(color_level())
+});
+
+// Catppuccin Latte theme - the light flavor, picked by `theme: auto` on
+// light terminals
+static CATPPUCCIN_LATTE_PALETTE: Lazy<Base16Palette> = Lazy::new(|| {
+    Base16Palette {
+        base_00: smart_color(0xEFF1F5),
+        base_01: smart_color(0xE6E9EF),
+        base_02: smart_color(0xCCD0DA),
+        base_03: smart_color(0x9CA0B0),
+        base_04: smart_color(0x8C8FA1),
+        base_05: smart_color(0x6C6F85),
+        base_06: smart_color(0x4C4F69),
+        base_07: smart_color(0xDC8A78),
+        base_08: smart_color(0xD20F39),
+        base_09: smart_color(0xFE640B),
+        base_0a: smart_color(0xDF8E1D),
+        base_0b: smart_color(0x40A02B),
+        base_0c: smart_color(0x179299),
+        base_0d: smart_color(0x1E66F5),
+        base_0e: smart_color(0x8839EF),
+        base_0f: smart_color(0xE64553),
+    }
+    .with_safe_grays(color_level())
 });
 
 // Kanagawa theme - Japanese-inspired warm tones
-static KANAGAWA_PALETTE: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
-    base_00: smart_color(0x1F1F28),
-    base_01: smart_color(0x2A2A37),
-    base_02: smart_color(0x223249),
-    base_03: smart_color(0x727169),
-    base_04: smart_color(0xC8C093),
-    base_05: smart_color(0xDCD7BA),
-    base_06: smart_color(0xDCD7BA),
-    base_07: smart_color(0xE6E0C2),
-    base_08: smart_color(0xC34043),
-    base_09: smart_color(0xFFA066),
-    base_0a: smart_color(0xDCA561),
-    base_0b: smart_color(0x98BB6C),
-    base_0c: smart_color(0x7FB4CA),
-    base_0d: smart_color(0x7E9CD8),
-    base_0e: smart_color(0x957FB8),
-    base_0f: smart_color(0xD27E99),
+static KANAGAWA_PALETTE: Lazy<Base16Palette> = Lazy::new(|| {
+    Base16Palette {
+        base_00: smart_color(0x1F1F28),
+        base_01: smart_color(0x2A2A37),
+        base_02: smart_color(0x223249),
+        base_03: smart_color(0x727169),
+        base_04: smart_color(0xC8C093),
+        base_05: smart_color(0xDCD7BA),
+        base_06: smart_color(0xDCD7BA),
+        base_07: smart_color(0xE6E0C2),
+        base_08: smart_color(0xC34043),
+        base_09: smart_color(0xFFA066),
+        base_0a: smart_color(0xDCA561),
+        base_0b: smart_color(0x98BB6C),
+        base_0c: smart_color(0x7FB4CA),
+        base_0d: smart_color(0x7E9CD8),
+        base_0e: smart_color(0x957FB8),
+        base_0f: smart_color(0xD27E99),
+    }
+    .with_safe_grays(color_level())
 });
 
 // Kanagawa Dragon theme - darker variant with cooler tones
-static KANAGAWA_DRAGON_PALETTE: Lazy<Base16Palette> = Lazy::new(|| Base16Palette {
-    base_00: smart_color(0x181616),
-    base_01: smart_color(0x0d0c0c),
-    base_02: smart_color(0x2d4f67),
-    base_03: smart_color(0xa6a69c),
-    base_04: smart_color(0x7fb4ca),
-    base_05: smart_color(0xc5c9c5),
-    base_06: smart_color(0xc5c9c5),
-    base_07: smart_color(0xc5c9c5),
-    base_08: smart_color(0xc4746e),
-    base_09: smart_color(0xe46876),
-    base_0a: smart_color(0xc4b28a),
-    base_0b: smart_color(0x8a9a7b),
-    base_0c: smart_color(0x8ea4a2),
-    base_0d: smart_color(0x8ba4b0),
-    base_0e: smart_color(0xa292a3),
-    base_0f: smart_color(0x7aa89f),
+static KANAGAWA_DRAGON_PALETTE: Lazy<Base16Palette> = Lazy::new(|| {
+    Base16Palette {
+        base_00: smart_color(0x181616),
+        base_01: smart_color(0x0d0c0c),
+        base_02: smart_color(0x2d4f67),
+        base_03: smart_color(0xa6a69c),
+        base_04: smart_color(0x7fb4ca),
+        base_05: smart_color(0xc5c9c5),
+        base_06: smart_color(0xc5c9c5),
+        base_07: smart_color(0xc5c9c5),
+        base_08: smart_color(0xc4746e),
+        base_09: smart_color(0xe46876),
+        base_0a: smart_color(0xc4b28a),
+        base_0b: smart_color(0x8a9a7b),
+        base_0c: smart_color(0x8ea4a2),
+        base_0d: smart_color(0x8ba4b0),
+        base_0e: smart_color(0xa292a3),
+        base_0f: smart_color(0x7aa89f),
+    }
+    .with_safe_grays(color_level())
 });
 
 // Backward compatibility alias
@@ -410,11 +537,348 @@ impl Base16Palette {
         }
     }
 
+    /// Selection colors for list rows: (background, foreground)
     pub fn get_selection_colors(&self, is_focused: bool) -> (Color, Color) {
-        if is_focused {
-            (self.base_02, self.base_06)
+        let (bg, fg_override) = self.get_text_selection_colors(is_focused);
+        let fg = fg_override.unwrap_or(if is_focused {
+            self.base_06
+        } else {
+            self.base_03
+        });
+        (bg, fg)
+    }
+
+    /// Table of contents colors: (section being read, other entries of the current chapter).
+    ///
+    /// The `toc_active_color`/`toc_chapter_color` settings take precedence.
+    pub fn get_toc_state_colors(&self) -> (Color, Color) {
+        let resolve = |value: Option<String>, default: Color| {
+            value
+                .and_then(|value| self.resolve_color(&value).ok())
+                .unwrap_or(default)
+        };
+        (
+            resolve(settings::get_toc_active_color(), self.base_08),
+            resolve(settings::get_toc_chapter_color(), self.base_0e),
+        )
+    }
+
+    /// Background of the line a jump lands on; the `jump_highlight_color`
+    /// setting takes precedence over `base_02`
+    pub fn jump_highlight_bg(&self) -> Color {
+        settings::get_jump_highlight_color()
+            .and_then(|value| self.resolve_color(&value).ok())
+            .unwrap_or(self.base_02)
+    }
+
+    /// Selection colors for highlighted text: (background, optional foreground).
+    ///
+    /// The `selection_bg`/`selection_fg` settings take precedence. Without an override the
+    /// background is the first candidate slot that stands out from `base_00`, so themes with
+    /// a near-background `base_02` still get a visible selection.
+    pub fn get_text_selection_colors(&self, is_focused: bool) -> (Color, Option<Color>) {
+        let (bg, fg) = SELECTION_OVERRIDES
+            .read()
+            .map(|overrides| *overrides)
+            .unwrap_or_default();
+        let bg = bg.map_or_else(
+            || self.default_selection_bg(is_focused),
+            |bg| self.override_color(bg),
+        );
+        (bg, fg.map(|fg| self.override_color(fg)))
+    }
+
+    fn default_selection_bg(&self, is_focused: bool) -> Color {
+        let candidates = if is_focused {
+            [self.base_02, self.base_03]
         } else {
-            (self.base_02, self.base_03)
+            [self.base_01, self.base_02]
+        };
+
+        candidates
+            .iter()
+            .copied()
+            .find(|&color| {
+                contrast_ratio(color, self.base_00).is_none_or(|c| c >= MIN_SELECTION_CONTRAST)
+            })
+            .unwrap_or(candidates[candidates.len() - 1])
+    }
+
+    /// Hand-tuned grays for 8/16-color terminals, where the nearest ANSI color
+    /// would turn muted text into dark gray on black and hide the selection
+    pub fn with_safe_grays(mut self, level: ColorLevel) -> Self {
+        match level {
+            ColorLevel::Ansi16 => {
+                self.base_00 = Color::Black;
+                self.base_01 = Color::DarkGray;
+                self.base_02 = Color::DarkGray;
+                self.base_03 = Color::Gray;
+                self.base_04 = Color::Gray;
+                self.base_05 = Color::Gray;
+                self.base_06 = Color::White;
+                self.base_07 = Color::White;
+            }
+            ColorLevel::Ansi8 => {
+                self.base_00 = Color::Black;
+                self.base_01 = Color::Blue;
+                self.base_02 = Color::Blue;
+                self.base_03 = Color::Gray;
+                self.base_04 = Color::Gray;
+                self.base_05 = Color::Gray;
+                self.base_06 = Color::Gray;
+                self.base_07 = Color::Gray;
+            }
+            ColorLevel::Ansi256 | ColorLevel::TrueColor => {}
         }
+        self
+    }
+
+    /// Resolve a color setting: either a palette slot name (`base00`-`base0F`) or a hex color
+    pub fn resolve_color(&self, value: &str) -> Result<Color, String> {
+        parse_color_override(value).map(|color| self.override_color(color))
+    }
+
+    fn override_color(&self, color: ColorOverride) -> Color {
+        let slot = match color {
+            ColorOverride::Fixed(color) => return color,
+            ColorOverride::Slot(slot) => slot,
+        };
+        match slot {
+            0x00 => self.base_00,
+            0x01 => self.base_01,
+            0x02 => self.base_02,
+            0x03 => self.base_03,
+            0x04 => self.base_04,
+            0x05 => self.base_05,
+            0x06 => self.base_06,
+            0x07 => self.base_07,
+            0x08 => self.base_08,
+            0x09 => self.base_09,
+            0x0a => self.base_0a,
+            0x0b => self.base_0b,
+            0x0c => self.base_0c,
+            0x0d => self.base_0d,
+            0x0e => self.base_0e,
+            _ => self.base_0f,
+        }
+    }
+}
+
+/// Adapt a rendered frame to the terminal: strip colors in monochrome mode,
+/// otherwise downsample hard-coded colors on 8/16-color terminals
+pub fn adapt_buffer(buf: &mut Buffer) {
+    if is_monochrome() {
+        apply_monochrome(buf, current_theme());
+        return;
+    }
+
+    let level = color_level();
+    if level >= ColorLevel::Ansi256 {
+        return;
+    }
+    for cell in buf.content.iter_mut() {
+        cell.fg = downsample(cell.fg, level);
+        cell.bg = downsample(cell.bg, level);
+    }
+}
+
+/// Replace colors with attributes: highlighted backgrounds (selection, search
+/// matches, status bars) become reverse video, accents bold and muted text dim
+fn apply_monochrome(buf: &mut Buffer, palette: &Base16Palette) {
+    let accents = [
+        palette.base_08,
+        palette.base_09,
+        palette.base_0a,
+        palette.base_0b,
+        palette.base_0c,
+        palette.base_0d,
+        palette.base_0e,
+        palette.base_0f,
+    ];
+    let muted = [palette.base_01, palette.base_02, palette.base_03];
+
+    for cell in buf.content.iter_mut() {
+        if is_highlight_bg(cell.bg, palette) {
+            cell.modifier.insert(Modifier::REVERSED);
+        } else if accents.contains(&cell.fg) {
+            cell.modifier.insert(Modifier::BOLD);
+        } else if cell.fg != palette.base_05 && muted.contains(&cell.fg) {
+            cell.modifier.insert(Modifier::DIM);
+        }
+        cell.fg = Color::Reset;
+        cell.bg = Color::Reset;
+    }
+}
+
+fn is_highlight_bg(bg: Color, palette: &Base16Palette) -> bool {
+    match bg {
+        Color::Reset | Color::Black => false,
+        bg if bg == palette.base_00 => false,
+        bg => contrast_ratio(bg, palette.base_00).is_none_or(|c| c >= MIN_SELECTION_CONTRAST),
+    }
+}
+
+/// Minimum contrast ratio between a default selection background and the panel background
+const MIN_SELECTION_CONTRAST: f64 = 1.3;
+
+/// WCAG contrast ratio between two colors, if both can be mapped to RGB
+fn contrast_ratio(a: Color, b: Color) -> Option<f64> {
+    let la = relative_luminance(a)?;
+    let lb = relative_luminance(b)?;
+    let (lighter, darker) = if la > lb { (la, lb) } else { (lb, la) };
+    Some((lighter + 0.05) / (darker + 0.05))
+}
+
+fn relative_luminance(color: Color) -> Option<f64> {
+    let (r, g, b) = color_to_rgb(color)?;
+    let channel = |c: u8| {
+        let c = c as f64 / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    Some(0.2126 * channel(r) + 0.7152 * channel(g) + 0.0722 * channel(b))
+}
+
+/// Map a color to RGB; 256-color indices use the standard xterm cube and grayscale ramp
+fn color_to_rgb(color: Color) -> Option<(u8, u8, u8)> {
+    match color {
+        Color::Rgb(r, g, b) => Some((r, g, b)),
+        Color::Indexed(index @ 16..=231) => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = index - 16;
+            Some((level(i / 36), level((i / 6) % 6), level(i % 6)))
+        }
+        Color::Indexed(index @ 232..=255) => {
+            let v = 8 + (index - 232) * 10;
+            Some((v, v, v))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_color_accepts_slots_and_hex() {
+        let palette = &*KANAGAWA_PALETTE;
+        assert_eq!(palette.resolve_color("base0D"), Ok(palette.base_0d));
+        assert_eq!(palette.resolve_color("base_03"), Ok(palette.base_03));
+        assert!(palette.resolve_color("#7E9CD8").is_ok());
+        assert!(palette.resolve_color("base10").is_err());
+        assert!(palette.resolve_color("blue").is_err());
+    }
+
+    #[test]
+    fn test_safe_grays_keep_text_readable() {
+        let palette = OCEANIC_NEXT_PALETTE
+            .clone()
+            .with_safe_grays(ColorLevel::Ansi16);
+        assert_eq!(palette.base_00, Color::Black);
+        assert_eq!(palette.base_03, Color::Gray);
+        assert_eq!(palette.get_panel_colors(true).0, Color::White);
+
+        let palette = OCEANIC_NEXT_PALETTE
+            .clone()
+            .with_safe_grays(ColorLevel::Ansi8);
+        let (selection_bg, selection_fg) = palette.get_selection_colors(true);
+        assert_eq!(selection_bg, Color::Blue);
+        assert_eq!(selection_fg, Color::Gray);
+    }
+
+    #[test]
+    fn test_monochrome_uses_attributes_only() {
+        use ratatui::layout::Rect;
+        use ratatui::style::Style;
+
+        let palette = Base16Palette {
+            base_00: Color::Rgb(0x1B, 0x2B, 0x34),
+            base_02: Color::Rgb(0x4F, 0x5B, 0x66),
+            base_03: Color::Rgb(0x65, 0x73, 0x7E),
+            base_05: Color::Rgb(0xC0, 0xC5, 0xCE),
+            base_0d: Color::Rgb(0x66, 0x99, 0xCC),
+            ..OCEANIC_NEXT_PALETTE.clone()
+        };
+        let mut buf = Buffer::empty(Rect::new(0, 0, 4, 1));
+        let bg = Style::default().bg(palette.base_00);
+        buf.set_string(0, 0, "t", bg.fg(palette.base_05));
+        buf.set_string(1, 0, "s", Style::default().bg(palette.base_02));
+        buf.set_string(2, 0, "l", bg.fg(palette.base_0d));
+        buf.set_string(3, 0, "m", bg.fg(palette.base_03));
+
+        apply_monochrome(&mut buf, &palette);
+
+        let modifiers: Vec<Modifier> = buf.content.iter().map(|cell| cell.modifier).collect();
+        assert_eq!(
+            modifiers,
+            vec![
+                Modifier::empty(),
+                Modifier::REVERSED,
+                Modifier::BOLD,
+                Modifier::DIM
+            ]
+        );
+        assert!(
+            buf.content
+                .iter()
+                .all(|cell| cell.fg == Color::Reset && cell.bg == Color::Reset)
+        );
+    }
+
+    #[test]
+    fn test_color_overrides_name_slots_or_fixed_colors() {
+        assert_eq!(
+            parse_color_override("base0D"),
+            Ok(ColorOverride::Slot(0x0d))
+        );
+        assert_eq!(
+            parse_color_override(" base_03 "),
+            Ok(ColorOverride::Slot(0x03))
+        );
+        assert!(matches!(
+            parse_color_override("#7E9CD8"),
+            Ok(ColorOverride::Fixed(_))
+        ));
+        assert!(parse_color_override("base10").is_err());
+
+        let palette = &*KANAGAWA_PALETTE;
+        assert_eq!(
+            palette.override_color(ColorOverride::Slot(0x0f)),
+            palette.base_0f
+        );
+    }
+
+    #[test]
+    fn test_list_selection_follows_focus() {
+        let palette = &*OCEANIC_NEXT_PALETTE;
+        let (focused_bg, focused_fg) = palette.get_selection_colors(true);
+        let (unfocused_bg, unfocused_fg) = palette.get_selection_colors(false);
+        assert_eq!((focused_bg, focused_fg), (palette.base_02, palette.base_06));
+        assert_eq!(unfocused_bg, palette.default_selection_bg(false));
+        assert_eq!(unfocused_fg, palette.base_03);
+    }
+
+    #[test]
+    fn test_default_selection_bg_is_visible() {
+        let palette = Base16Palette {
+            base_00: Color::Rgb(0x1F, 0x1F, 0x28),
+            base_01: Color::Rgb(0x2A, 0x2A, 0x37),
+            base_02: Color::Rgb(0x22, 0x32, 0x49),
+            base_03: Color::Rgb(0x72, 0x71, 0x69),
+            ..KANAGAWA_PALETTE.clone()
+        };
+        // base_02 barely differs from the background, so base_03 is used instead
+        assert_eq!(palette.default_selection_bg(true), palette.base_03);
+
+        let oceanic = Base16Palette {
+            base_00: Color::Rgb(0x1B, 0x2B, 0x34),
+            base_02: Color::Rgb(0x4F, 0x5B, 0x66),
+            ..OCEANIC_NEXT_PALETTE.clone()
+        };
+        assert_eq!(oceanic.default_selection_bg(true), oceanic.base_02);
     }
 }
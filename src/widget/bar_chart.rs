@@ -0,0 +1,50 @@
+/// Bar tops in eighths of a cell, from empty to full
+const BAR_LEVELS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Rows of a vertical bar chart, top row first. Each value is one column,
+/// bars are separated by a space and scaled so the largest fills `height`
+/// rows; any non-zero value shows at least a sliver.
+pub fn bar_chart_rows(values: &[u64], height: usize) -> Vec<String> {
+    let max = values.iter().copied().max().unwrap_or(0);
+    let eighths: Vec<u64> = values
+        .iter()
+        .map(|&value| {
+            if value == 0 || max == 0 {
+                0
+            } else {
+                (value * height as u64 * 8).div_ceil(max)
+            }
+        })
+        .collect();
+
+    (0..height)
+        .map(|row| {
+            let floor = (height - 1 - row) as u64 * 8;
+            let cells: Vec<String> = eighths
+                .iter()
+                .map(|&bar| {
+                    let level = bar.saturating_sub(floor).min(8) as usize;
+                    BAR_LEVELS[level].to_string()
+                })
+                .collect();
+            cells.join(" ")
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bars_scale_to_the_largest_value() {
+        let rows = bar_chart_rows(&[0, 1, 8, 16, 12], 2);
+        assert_eq!(rows, vec!["      █ ▄", "  ▁ █ █ █"]);
+    }
+
+    #[test]
+    fn test_empty_chart_is_blank() {
+        assert_eq!(bar_chart_rows(&[0, 0], 1), vec!["   "]);
+        assert!(bar_chart_rows(&[], 3).iter().all(String::is_empty));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::book_metadata::BookMetadata;
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph, Wrap},
+};
+
+/// Widest the popup gets
+const MAX_WIDTH: u16 = 64;
+
+pub enum BookInfoAction {
+    Close,
+}
+
+/// Popup with what the book's metadata says of it (Space+n): the book
+/// open in the reader, or the one selected in the book list
+pub struct BookInfoPopup {
+    title: String,
+    /// Label and value of each field the book has, in order
+    fields: Vec<(&'static str, String)>,
+    last_popup_area: Option<Rect>,
+}
+
+impl BookInfoPopup {
+    /// `title` is shown when the metadata has none, e.g. the file name
+    pub fn new(title: String, metadata: &BookMetadata, path: &str) -> Self {
+        let fields = [
+            ("book_info.author", metadata.author.clone()),
+            ("book_info.publisher", metadata.publisher.clone()),
+            ("book_info.language", metadata.language.clone()),
+            ("book_info.series", metadata.series_label()),
+            ("book_info.file", Some(path.to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(label, value)| Some((label, value?)))
+        .collect();
+
+        BookInfoPopup {
+            title: metadata.title.clone().unwrap_or(title),
+            fields,
+            last_popup_area: None,
+        }
+    }
+
+    pub fn handle_command(&self, command: PopupCommand) -> Option<BookInfoAction> {
+        (command == PopupCommand::Close).then_some(BookInfoAction::Close)
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let palette = current_theme();
+        let width = MAX_WIDTH.min(area.width.saturating_sub(4)).max(20);
+        let text_width = width.saturating_sub(4) as usize;
+
+        let mut lines = vec![
+            Line::from(Span::styled(
+                self.title.clone(),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+        ];
+        let label_width = self
+            .fields
+            .iter()
+            .map(|(label, _)| tr(label).chars().count())
+            .max()
+            .unwrap_or(0);
+        for (label, value) in &self.fields {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<label_width$}  ", tr(label)),
+                    Style::default().fg(palette.base_03),
+                ),
+                Span::styled(value.clone(), Style::default().fg(palette.base_05)),
+            ]));
+        }
+
+        // Long titles and paths wrap onto more rows
+        let height = lines
+            .iter()
+            .map(|line| line.width().div_ceil(text_width.max(1)).max(1) as u16)
+            .sum::<u16>()
+            .saturating_add(2)
+            .min(area.height);
+        let popup_area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width: width.min(area.width),
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(tr("title.book_info"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .padding(Padding::horizontal(1))
+            .style(Style::default().bg(palette.base_00));
+        f.render_widget(
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: false }),
+            popup_area,
+        );
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_fields_are_left_out() {
+        let metadata = BookMetadata {
+            author: Some("Ursula K. Le Guin".to_string()),
+            series: Some("Earthsea".to_string()),
+            series_index: Some("2".to_string()),
+            ..Default::default()
+        };
+        let popup = BookInfoPopup::new("tombs".to_string(), &metadata, "/books/tombs.epub");
+        assert_eq!(popup.title, "tombs");
+        assert_eq!(
+            popup.fields,
+            [
+                ("book_info.author", "Ursula K. Le Guin".to_string()),
+                ("book_info.series", "Earthsea #2".to_string()),
+                ("book_info.file", "/books/tombs.epub".to_string()),
+            ]
+        );
+    }
+}
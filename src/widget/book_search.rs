@@ -1,7 +1,13 @@
+use crate::i18n::{tr, tr_args};
 use crate::main_app::VimNavMotions;
-use crate::search_engine::{BookSearchResult, SearchEngine};
+use crate::main_app::commands::PopupCommand;
+use crate::search_engine::{
+    BookSearchResult, ChapterMatchCount, MatchKind, MatchTarget, SearchEngine,
+};
+use crate::search_history::HistoryRecall;
 use crate::theme::Base16Palette;
-use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEvent};
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
+use crossterm::event::MouseEvent;
 use log::debug;
 use ratatui::{
     Frame,
@@ -10,13 +16,17 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
+use std::collections::HashMap;
 use std::time::{Duration, Instant};
 
 pub enum BookSearchAction {
     JumpToChapter {
         chapter_index: usize,
         line_number: usize,
+        target: MatchTarget,
     },
+    /// A query was searched with Enter, for the search history
+    Searched(String),
     Close,
 }
 
@@ -25,6 +35,26 @@ enum FocusMode {
     Results,
 }
 
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ResultsView {
+    /// Best matches across the book
+    Flat,
+    /// One row per chapter with its match count; expanded chapters list their matches
+    ByChapter,
+}
+
+/// A row of the per-chapter summary
+enum SummaryRow<'a> {
+    Chapter {
+        counts: &'a ChapterMatchCount,
+        expanded: bool,
+    },
+    Match(&'a BookSearchResult),
+}
+
+/// Width of the per-chapter histogram bar at the largest count
+const HISTOGRAM_WIDTH: usize = 20;
+
 pub struct BookSearch {
     active: bool,
     search_input: String,
@@ -43,6 +73,19 @@ pub struct BookSearch {
 
     focus_mode: FocusMode,
     cached_results: Option<Vec<BookSearchResult>>,
+
+    view: ResultsView,
+    chapter_counts: Vec<ChapterMatchCount>,
+    /// Matches of the chapters expanded in the summary, by chapter index
+    expanded_chapters: HashMap<usize, Vec<BookSearchResult>>,
+    /// Rows that fit in the summary at the last render
+    summary_height: usize,
+    /// Only results of this kind, cycled with `f`
+    kind_filter: Option<MatchKind>,
+    /// Past queries, oldest first, recalled with Up/Down in the input
+    history: Vec<String>,
+    history_recall: HistoryRecall,
+    last_popup_area: Option<Rect>,
 }
 
 impl BookSearch {
@@ -61,6 +104,14 @@ impl BookSearch {
             pending_search: None,
             focus_mode: FocusMode::Input,
             cached_results: None,
+            view: ResultsView::Flat,
+            chapter_counts: Vec::new(),
+            expanded_chapters: HashMap::new(),
+            summary_height: 10,
+            kind_filter: None,
+            history: Vec::new(),
+            history_recall: HistoryRecall::default(),
+            last_popup_area: None,
         }
     }
 
@@ -73,10 +124,28 @@ impl BookSearch {
             self.selected_result = 0;
             self.scroll_offset = 0;
             self.last_search_query.clear();
+            self.chapter_counts.clear();
+            self.expanded_chapters.clear();
         } else if let Some(cached) = &self.cached_results {
             self.results = cached.clone();
         }
         self.focus_mode = FocusMode::Input;
+        self.history_recall.reset();
+    }
+
+    /// Fill in a query and search for it, e.g. one carried over from the
+    /// chapter search
+    pub fn search_for(&mut self, query: &str) {
+        self.set_input(query.to_string());
+    }
+
+    pub fn set_history(&mut self, queries: Vec<String>) {
+        self.history = queries;
+    }
+
+    /// For chapters indexed after the book was opened
+    pub fn search_engine_mut(&mut self) -> &mut SearchEngine {
+        &mut self.search_engine
     }
 
     pub fn close(&mut self) {
@@ -87,6 +156,22 @@ impl BookSearch {
         self.active
     }
 
+    /// Whether keys go to the query field
+    pub fn is_typing(&self) -> bool {
+        matches!(self.focus_mode, FocusMode::Input)
+    }
+
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            false
+        }
+    }
+
     pub fn update(&mut self) -> Option<BookSearchAction> {
         if let Some(ref query) = self.pending_search {
             if self.last_input_time.elapsed() > Duration::from_millis(200) {
@@ -101,74 +186,90 @@ impl BookSearch {
         if query == self.last_search_query {
             return;
         }
-        self.results = self.search_engine.search_fuzzy(&query);
+        self.results = self.search_engine.search_fuzzy(&query, self.kind_filter);
         self.cached_results = Some(self.results.clone());
+        self.chapter_counts = self
+            .search_engine
+            .count_matches_by_chapter(&query, self.kind_filter);
+        self.expanded_chapters.clear();
         self.last_search_query = query;
         self.selected_result = 0;
         self.scroll_offset = 0;
     }
 
-    pub fn handle_key_event(&mut self, key: KeyEvent) -> Option<BookSearchAction> {
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<BookSearchAction> {
         match self.focus_mode {
             FocusMode::Input => {
-                let action = self.handle_input_key(key);
+                let action = self.handle_input_command(command);
                 self.update();
                 action
             }
-            FocusMode::Results => self.handle_results_key(key),
+            FocusMode::Results => self.handle_results_command(command),
         }
     }
 
-    fn handle_input_key(&mut self, key: KeyEvent) -> Option<BookSearchAction> {
-        match key.code {
-            KeyCode::Esc => {
+    fn handle_input_command(&mut self, command: PopupCommand) -> Option<BookSearchAction> {
+        match command {
+            PopupCommand::Close => {
                 self.active = false;
                 return Some(BookSearchAction::Close);
             }
-            KeyCode::Enter => {
+            PopupCommand::Submit => {
                 if self.search_input.is_empty() {
                     return None;
                 }
                 // Execute search and switch to results
                 self.execute_search(self.search_input.clone());
+                self.history_recall.reset();
                 if !self.results.is_empty() {
                     self.focus_mode = FocusMode::Results;
                 }
+                return Some(BookSearchAction::Searched(self.search_input.clone()));
+            }
+            // Up/Down step through past queries; Down past them moves to the results
+            PopupCommand::Up if !self.history.is_empty() => {
+                if let Some(query) = self.history_recall.older(&self.history, &self.search_input) {
+                    self.set_input(query);
+                }
             }
-            KeyCode::Down => {
+            PopupCommand::Down if self.history_recall.is_recalling() => {
+                if let Some(query) = self.history_recall.newer(&self.history) {
+                    self.set_input(query);
+                }
+            }
+            PopupCommand::Down => {
                 if !self.results.is_empty() {
                     self.focus_mode = FocusMode::Results;
                     self.move_selection_down();
                 }
             }
-            KeyCode::Up => {
+            PopupCommand::Up => {
                 if !self.results.is_empty() {
                     self.focus_mode = FocusMode::Results;
                     self.move_selection_up();
                 }
             }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            PopupCommand::ClearInput => {
                 self.search_input.clear();
                 self.cursor_position = 0;
                 self.schedule_search();
             }
-            KeyCode::Char(c) => {
-                // In input mode, all characters including 'j' and 'k' should be typed
+            PopupCommand::Input(c) => {
                 self.search_input.insert(self.cursor_position, c);
                 self.cursor_position += 1;
                 self.schedule_search();
             }
-            KeyCode::Backspace => {
+            PopupCommand::Backspace => {
                 if self.cursor_position > 0 {
                     self.cursor_position -= 1;
                     self.search_input.remove(self.cursor_position);
                     self.schedule_search();
                 }
             }
-            KeyCode::Left => {
+            PopupCommand::Left => {
                 self.cursor_position = self.cursor_position.saturating_sub(1);
             }
-            KeyCode::Right => {
+            PopupCommand::Right => {
                 self.cursor_position = (self.cursor_position + 1).min(self.search_input.len());
             }
             _ => {}
@@ -176,39 +277,71 @@ impl BookSearch {
         None
     }
 
-    fn handle_results_key(&mut self, key: KeyEvent) -> Option<BookSearchAction> {
-        match key.code {
-            KeyCode::Esc => {
+    fn handle_results_command(&mut self, command: PopupCommand) -> Option<BookSearchAction> {
+        match command {
+            PopupCommand::Close => {
                 self.active = false;
                 return Some(BookSearchAction::Close);
             }
-            KeyCode::Enter => {
-                if !self.results.is_empty() {
-                    let result = &self.results[self.selected_result];
+            PopupCommand::Select => {
+                let jump = match self.view {
+                    ResultsView::Flat => self.results.get(self.selected_result),
+                    ResultsView::ByChapter => match self.summary_rows().get(self.selected_result) {
+                        Some(SummaryRow::Chapter { counts, .. }) => {
+                            let chapter_index = counts.chapter_index;
+                            self.toggle_chapter(chapter_index);
+                            return None;
+                        }
+                        Some(SummaryRow::Match(result)) => Some(*result),
+                        None => None,
+                    },
+                }
+                .map(|result| BookSearchAction::JumpToChapter {
+                    chapter_index: result.chapter_index,
+                    line_number: result.line_number,
+                    target: result.target.clone(),
+                });
+                if jump.is_some() {
                     self.active = false;
-                    return Some(BookSearchAction::JumpToChapter {
-                        chapter_index: result.chapter_index,
-                        line_number: result.line_number,
-                    });
                 }
+                return jump;
             }
-            KeyCode::Char(' ') if key.modifiers.is_empty() => {
+            PopupCommand::SwitchView => {
+                self.toggle_view();
+            }
+            PopupCommand::CycleFilter => {
+                self.cycle_kind_filter();
+            }
+            PopupCommand::Left if self.view == ResultsView::ByChapter => {
+                self.collapse_selected_chapter();
+            }
+            PopupCommand::Right if self.view == ResultsView::ByChapter => {
+                if let Some(SummaryRow::Chapter {
+                    counts,
+                    expanded: false,
+                }) = self.summary_rows().get(self.selected_result)
+                {
+                    let chapter_index = counts.chapter_index;
+                    self.toggle_chapter(chapter_index);
+                }
+            }
+            PopupCommand::EditQuery => {
                 // Space+f behavior - go back to input mode
                 self.focus_mode = FocusMode::Input;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
+            PopupCommand::Down => {
                 self.move_selection_down();
             }
-            KeyCode::Char('k') | KeyCode::Up => {
+            PopupCommand::Up => {
                 self.move_selection_up();
             }
-            KeyCode::Char('g') => {
+            PopupCommand::Top => {
                 self.selected_result = 0;
                 self.scroll_offset = 0;
             }
-            KeyCode::Char('G') => {
-                if !self.results.is_empty() {
-                    self.selected_result = self.results.len() - 1;
+            PopupCommand::Bottom => {
+                if self.item_count() > 0 {
+                    self.selected_result = self.item_count() - 1;
                     self.update_scroll();
                 }
             }
@@ -217,13 +350,124 @@ impl BookSearch {
         None
     }
 
+    /// Total matches across the book, including those past the result cap
+    pub fn total_matches(&self) -> usize {
+        self.chapter_counts.iter().map(|c| c.count).sum()
+    }
+
+    /// Number of selectable rows in the current view
+    fn item_count(&self) -> usize {
+        match self.view {
+            ResultsView::Flat => self.results.len(),
+            ResultsView::ByChapter => {
+                self.chapter_counts.len()
+                    + self
+                        .expanded_chapters
+                        .values()
+                        .map(|matches| matches.len())
+                        .sum::<usize>()
+            }
+        }
+    }
+
+    fn summary_rows(&self) -> Vec<SummaryRow<'_>> {
+        let mut rows = Vec::new();
+        for counts in &self.chapter_counts {
+            let matches = self.expanded_chapters.get(&counts.chapter_index);
+            rows.push(SummaryRow::Chapter {
+                counts,
+                expanded: matches.is_some(),
+            });
+            rows.extend(matches.into_iter().flatten().map(SummaryRow::Match));
+        }
+        rows
+    }
+
+    /// Switch between the flat results and the per-chapter summary, keeping
+    /// the selected result's chapter selected
+    fn toggle_view(&mut self) {
+        match self.view {
+            ResultsView::Flat => {
+                let chapter = self
+                    .results
+                    .get(self.selected_result)
+                    .map(|result| result.chapter_index);
+                self.view = ResultsView::ByChapter;
+                self.selected_result = chapter
+                    .and_then(|chapter| self.chapter_row(chapter))
+                    .unwrap_or(0);
+            }
+            ResultsView::ByChapter => {
+                self.view = ResultsView::Flat;
+                self.selected_result = 0;
+            }
+        }
+        self.scroll_offset = 0;
+        self.update_scroll();
+    }
+
+    fn chapter_row(&self, chapter_index: usize) -> Option<usize> {
+        self.summary_rows().iter().position(|row| {
+            matches!(row, SummaryRow::Chapter { counts, .. } if counts.chapter_index == chapter_index)
+        })
+    }
+
+    fn toggle_chapter(&mut self, chapter_index: usize) {
+        if self.expanded_chapters.remove(&chapter_index).is_none() {
+            let matches = self.search_engine.search_chapter(
+                &self.last_search_query,
+                chapter_index,
+                self.kind_filter,
+            );
+            self.expanded_chapters.insert(chapter_index, matches);
+        }
+        if let Some(row) = self.chapter_row(chapter_index) {
+            self.selected_result = row;
+        }
+        self.update_scroll();
+    }
+
+    /// Collapse the chapter the selection is in and select its row
+    fn collapse_selected_chapter(&mut self) {
+        let chapter_index = match self.summary_rows().get(self.selected_result) {
+            Some(SummaryRow::Chapter {
+                counts,
+                expanded: true,
+            }) => counts.chapter_index,
+            Some(SummaryRow::Match(result)) => result.chapter_index,
+            _ => return,
+        };
+        self.toggle_chapter(chapter_index);
+    }
+
+    /// Step through all results, then text, figure and table matches only,
+    /// and search again with the new filter
+    fn cycle_kind_filter(&mut self) {
+        self.kind_filter = match self.kind_filter {
+            None => Some(MatchKind::ALL[0]),
+            Some(kind) => MatchKind::ALL
+                .iter()
+                .position(|k| *k == kind)
+                .and_then(|i| MatchKind::ALL.get(i + 1))
+                .copied(),
+        };
+        let query = std::mem::take(&mut self.last_search_query);
+        self.execute_search(query);
+    }
+
+    fn set_input(&mut self, query: String) {
+        self.cursor_position = query.len();
+        self.search_input = query;
+        self.schedule_search();
+    }
+
     fn schedule_search(&mut self) {
         self.last_input_time = Instant::now();
         self.pending_search = Some(self.search_input.clone());
     }
 
     fn move_selection_down(&mut self) {
-        if self.selected_result < self.results.len().saturating_sub(1) {
+        if self.selected_result < self.item_count().saturating_sub(1) {
             self.selected_result += 1;
             self.update_scroll();
         }
@@ -238,13 +482,13 @@ impl BookSearch {
 
     /// Scroll the view down while keeping cursor at same screen position if possible
     pub fn scroll_down(&mut self, area_height: u16) {
-        if self.results.is_empty() {
+        if self.item_count() == 0 {
             return;
         }
 
         // Calculate visible height (accounting for borders and search input area)
         let visible_height = area_height.saturating_sub(5) as usize; // Account for borders and input
-        let total_items = self.results.len();
+        let total_items = self.item_count();
 
         // Calculate cursor position relative to viewport
         let cursor_viewport_pos = self.selected_result.saturating_sub(self.scroll_offset);
@@ -264,7 +508,7 @@ impl BookSearch {
 
     /// Scroll the view up while keeping cursor at same screen position if possible
     pub fn scroll_up(&mut self, area_height: u16) {
-        if self.results.is_empty() {
+        if self.item_count() == 0 {
             return;
         }
 
@@ -286,12 +530,22 @@ impl BookSearch {
                 .scroll_offset
                 .saturating_add(visible_height.saturating_sub(1));
             if self.selected_result > max_visible_index {
-                self.selected_result = max_visible_index.min(self.results.len().saturating_sub(1));
+                self.selected_result = max_visible_index.min(self.item_count().saturating_sub(1));
             }
         }
     }
 
     fn update_scroll(&mut self) {
+        if self.view == ResultsView::ByChapter {
+            // Summary rows are one line each, so the exact fit is known
+            if self.selected_result < self.scroll_offset {
+                self.scroll_offset = self.selected_result;
+            } else if self.selected_result >= self.scroll_offset + self.summary_height {
+                self.scroll_offset = self.selected_result + 1 - self.summary_height.max(1);
+            }
+            return;
+        }
+
         // Scroll to keep selected result visible
         // If selected is before current scroll, scroll up to it
         if self.selected_result < self.scroll_offset {
@@ -315,21 +569,17 @@ impl BookSearch {
             return;
         }
 
-        // Make the popup use most of the screen (90% width, 80% height)
-        let popup_width = ((area.width as f32 * 0.9) as u16).max(80);
-        let popup_height = ((area.height as f32 * 0.8) as u16).max(20);
-
-        let popup_area = Rect {
-            x: (area.width - popup_width) / 2,
-            y: (area.height - popup_height) / 2,
-            width: popup_width,
-            height: popup_height,
-        };
+        let popup_area = popup_rect(PopupKind::BookSearch, area);
+        self.last_popup_area = Some(popup_area);
 
         f.render_widget(Clear, popup_area);
 
+        let title = match self.kind_filter {
+            Some(kind) => tr_args("search.title_filtered", &[("kind", &kind.label())]),
+            None => tr("search.title").to_string(),
+        };
         let block = Block::default()
-            .title(" Search Book ")
+            .title(title)
             .borders(Borders::ALL)
             .style(Style::default().bg(palette.base_00).fg(palette.base_05));
 
@@ -346,8 +596,11 @@ impl BookSearch {
             ])
             .split(inner);
 
-        // Calculate visible results based on the actual results area height
-        let _visible_count = chunks[1].height as usize;
+        // The first line of the results area holds the match count
+        self.summary_height = chunks[1].height.saturating_sub(1).max(1) as usize;
+        if self.view == ResultsView::ByChapter {
+            self.update_scroll();
+        }
 
         self.render_search_input(f, chunks[0], palette);
         self.render_results(f, chunks[1], palette);
@@ -362,10 +615,9 @@ impl BookSearch {
             FocusMode::Results => Style::default().fg(palette.base_03),
         };
 
-        let input_text = vec![
-            Span::raw("🔍 Search: "),
-            Span::styled(&self.search_input, input_style),
-        ];
+        let prompt = Span::raw(tr("search.prompt"));
+        let prompt_width = prompt.width() as u16;
+        let input_text = vec![prompt, Span::styled(&self.search_input, input_style)];
 
         let input = Paragraph::new(Line::from(input_text))
             .style(Style::default().bg(palette.base_00))
@@ -378,7 +630,7 @@ impl BookSearch {
         f.render_widget(input, area);
 
         if matches!(self.focus_mode, FocusMode::Input) {
-            let cursor_x = area.x + 11 + self.cursor_position as u16;
+            let cursor_x = area.x + prompt_width + self.cursor_position as u16;
             let cursor_y = area.y; // Cursor should be on the same line as the text
             f.set_cursor_position(ratatui::layout::Position {
                 x: cursor_x,
@@ -388,6 +640,132 @@ impl BookSearch {
     }
 
     fn render_results(&self, f: &mut Frame, area: Rect, palette: &Base16Palette) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Min(1)])
+            .split(area);
+
+        self.render_match_count(f, chunks[0], palette);
+        match self.view {
+            ResultsView::Flat => self.render_flat_results(f, chunks[1], palette),
+            ResultsView::ByChapter => self.render_chapter_summary(f, chunks[1], palette),
+        }
+    }
+
+    fn render_match_count(&self, f: &mut Frame, area: Rect, palette: &Base16Palette) {
+        let total = self.total_matches();
+        if total == 0 {
+            return;
+        }
+
+        let chapters = self.chapter_counts.len();
+        let key = match (total == 1, chapters == 1) {
+            (true, _) => "search.match_count.one",
+            (false, true) => "search.match_count.other_one_chapter",
+            (false, false) => "search.match_count.other",
+        };
+        let mut text = tr_args(key, &[("count", &total), ("chapters", &chapters)]);
+        if self.view == ResultsView::Flat && total > self.results.len() {
+            text.push_str(&tr_args(
+                "search.best_shown",
+                &[("count", &self.results.len())],
+            ));
+        }
+
+        let badge = Paragraph::new(Line::from(Span::styled(
+            text,
+            Style::default()
+                .fg(palette.base_0b)
+                .add_modifier(Modifier::BOLD),
+        )))
+        .style(Style::default().bg(palette.base_00));
+        f.render_widget(badge, area);
+    }
+
+    fn render_chapter_summary(&self, f: &mut Frame, area: Rect, palette: &Base16Palette) {
+        if self.chapter_counts.is_empty() {
+            let no_results = Paragraph::new(tr("search.no_results"))
+                .style(Style::default().fg(palette.base_03).bg(palette.base_00))
+                .alignment(Alignment::Center);
+            f.render_widget(no_results, area);
+            return;
+        }
+
+        let (selection_bg, _) = palette.get_text_selection_colors(true);
+        let max_count = self
+            .chapter_counts
+            .iter()
+            .map(|c| c.count)
+            .max()
+            .unwrap_or(1);
+        // Marker, count column and histogram take the rest of the row
+        let title_width = (area.width as usize)
+            .saturating_sub(HISTOGRAM_WIDTH + 12)
+            .max(10);
+
+        let lines: Vec<Line> = self
+            .summary_rows()
+            .iter()
+            .enumerate()
+            .skip(self.scroll_offset)
+            .take(area.height as usize)
+            .map(|(idx, row)| {
+                let line = match row {
+                    SummaryRow::Chapter { counts, expanded } => {
+                        let title: String =
+                            counts.chapter_title.chars().take(title_width).collect();
+                        let bar_width = (counts.count * HISTOGRAM_WIDTH).div_ceil(max_count).max(1);
+                        Line::from(vec![
+                            Span::styled(
+                                if *expanded { "▾ " } else { "▸ " },
+                                Style::default().fg(palette.base_0d),
+                            ),
+                            Span::styled(
+                                format!("{title:<title_width$}"),
+                                Style::default()
+                                    .fg(palette.base_0d)
+                                    .add_modifier(Modifier::BOLD),
+                            ),
+                            Span::styled(
+                                format!(" {:>6} ", counts.count),
+                                Style::default().fg(palette.base_05),
+                            ),
+                            Span::styled(
+                                "█".repeat(bar_width),
+                                Style::default().fg(palette.base_0b),
+                            ),
+                        ])
+                    }
+                    SummaryRow::Match(result) => {
+                        let mut spans = vec![Span::styled(
+                            format!(
+                                "    {:>5} {:<8} ",
+                                result.line_number + 1,
+                                result.target.kind().label()
+                            ),
+                            Style::default().fg(palette.base_03),
+                        )];
+                        spans.extend(self.highlight_match(
+                            &result.snippet,
+                            &result.match_positions,
+                            palette,
+                        ));
+                        Line::from(spans)
+                    }
+                };
+                if idx == self.selected_result {
+                    line.style(Style::default().bg(selection_bg))
+                } else {
+                    line
+                }
+            })
+            .collect();
+
+        let paragraph = Paragraph::new(lines).style(Style::default().bg(palette.base_00));
+        f.render_widget(paragraph, area);
+    }
+
+    fn render_flat_results(&self, f: &mut Frame, area: Rect, palette: &Base16Palette) {
         debug!(
             "Rendering {} results in area {:?}",
             self.results.len(),
@@ -395,7 +773,7 @@ impl BookSearch {
         );
 
         if self.results.is_empty() {
-            let no_results = Paragraph::new("No results found")
+            let no_results = Paragraph::new(tr("search.no_results"))
                 .style(Style::default().fg(palette.base_03).bg(palette.base_00))
                 .alignment(Alignment::Center);
             f.render_widget(no_results, area);
@@ -463,6 +841,7 @@ impl BookSearch {
 
         // Build all lines for display
         let mut all_lines = Vec::new();
+        let (selection_bg, _) = palette.get_text_selection_colors(true);
 
         for (idx, result) in visible_results.iter().enumerate() {
             let is_selected = idx + self.scroll_offset == self.selected_result;
@@ -488,9 +867,13 @@ impl BookSearch {
                         .add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(
-                    format!("(line {}) ", result.line_number + 1),
+                    tr_args("search.line", &[("line", &(result.line_number + 1))]),
                     Style::default().fg(palette.base_03),
                 ),
+                Span::styled(
+                    format!("{} ", result.target.kind().label()),
+                    Style::default().fg(palette.base_0e),
+                ),
                 Span::styled(
                     format!("[{:.2}]", result.match_score),
                     Style::default().fg(score_color),
@@ -498,8 +881,7 @@ impl BookSearch {
             ];
 
             if is_selected {
-                all_lines
-                    .push(Line::from(header_spans).style(Style::default().bg(palette.base_02)));
+                all_lines.push(Line::from(header_spans).style(Style::default().bg(selection_bg)));
             } else {
                 all_lines.push(Line::from(header_spans));
             }
@@ -510,7 +892,7 @@ impl BookSearch {
                     if is_selected {
                         all_lines.push(Line::from(Span::styled(
                             prefixed_line,
-                            Style::default().fg(palette.base_03).bg(palette.base_02),
+                            Style::default().fg(palette.base_03).bg(selection_bg),
                         )));
                     } else {
                         all_lines.push(Line::from(Span::styled(
@@ -528,13 +910,13 @@ impl BookSearch {
                         self.highlight_match(&result.snippet, &result.match_positions, palette);
                     let mut styled_spans = vec![Span::styled(
                         "  → ",
-                        Style::default().fg(palette.base_0d).bg(palette.base_02),
+                        Style::default().fg(palette.base_0d).bg(selection_bg),
                     )];
                     for span in highlighted {
                         // Apply selection background to each span
                         styled_spans.push(Span::styled(
                             span.content.to_string(),
-                            span.style.bg(palette.base_02),
+                            span.style.bg(selection_bg),
                         ));
                     }
                     all_lines.push(Line::from(styled_spans));
@@ -554,7 +936,7 @@ impl BookSearch {
                     if is_selected {
                         all_lines.push(Line::from(Span::styled(
                             prefixed_line,
-                            Style::default().fg(palette.base_03).bg(palette.base_02),
+                            Style::default().fg(palette.base_03).bg(selection_bg),
                         )));
                     } else {
                         all_lines.push(Line::from(Span::styled(
@@ -617,15 +999,16 @@ impl BookSearch {
 
     fn render_status_bar(&self, f: &mut Frame, area: Rect, palette: &Base16Palette) {
         let help_text = match self.focus_mode {
-            FocusMode::Input => "Enter:Search  \"phrase\":Exact  Esc:Cancel",
-            FocusMode::Results => {
-                "j/k:Navigate  Enter:Jump  g/G:Top/Bottom  Space+f:Edit Query  Esc:Cancel"
-            }
+            FocusMode::Input => tr("search.help.input"),
+            FocusMode::Results => match self.view {
+                ResultsView::Flat => tr("search.help.flat"),
+                ResultsView::ByChapter => tr("search.help.by_chapter"),
+            },
         };
 
         let status = vec![
             Span::styled(
-                format!("{} results  ", self.results.len()),
+                tr_args("search.result_count", &[("count", &self.results.len())]),
                 Style::default().fg(palette.base_0b),
             ),
             Span::styled(help_text, Style::default().fg(palette.base_03)),
@@ -678,9 +1061,130 @@ impl VimNavMotions for BookSearch {
     }
 
     fn handle_upper_g(&mut self) {
-        if !self.results.is_empty() {
-            self.selected_result = self.results.len() - 1;
+        if self.item_count() > 0 {
+            self.selected_result = self.item_count() - 1;
             self.update_scroll();
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Search results for "whale" across three chapters, focused on the results
+    fn searched() -> BookSearch {
+        let mut engine = SearchEngine::new();
+        engine.process_chapters(vec![
+            (
+                0,
+                "Loomings".to_string(),
+                "A whale!\nAnother whale.".to_string(),
+            ),
+            (
+                1,
+                "The Carpet-Bag".to_string(),
+                "No match here.".to_string(),
+            ),
+            (
+                2,
+                "The Chase".to_string(),
+                "the whale surfaced\n".repeat(60),
+            ),
+        ]);
+        let mut search = BookSearch::new(engine);
+        search.open(true);
+        for c in "whale".chars() {
+            search.handle_command(PopupCommand::Input(c));
+        }
+        search.handle_command(PopupCommand::Submit);
+        search
+    }
+
+    #[test]
+    fn test_total_match_count_includes_capped_results() {
+        let search = searched();
+        assert_eq!(search.results.len(), 50);
+        assert_eq!(search.total_matches(), 62);
+    }
+
+    #[test]
+    fn test_chapter_summary_expands_to_matches_and_jumps() {
+        let mut search = searched();
+        search.handle_command(PopupCommand::SwitchView);
+        assert_eq!(search.view, ResultsView::ByChapter);
+        assert_eq!(search.item_count(), 2);
+
+        search.handle_command(PopupCommand::Top);
+        assert!(search.handle_command(PopupCommand::Select).is_none());
+        assert_eq!(search.item_count(), 4);
+
+        search.handle_command(PopupCommand::Down);
+        search.handle_command(PopupCommand::Down);
+        match search.handle_command(PopupCommand::Select) {
+            Some(BookSearchAction::JumpToChapter {
+                chapter_index,
+                line_number,
+                ..
+            }) => assert_eq!((chapter_index, line_number), (0, 1)),
+            _ => panic!("expected a jump to the second match"),
+        }
+    }
+
+    #[test]
+    fn test_collapse_from_match_selects_its_chapter() {
+        let mut search = searched();
+        search.handle_command(PopupCommand::SwitchView);
+        search.handle_command(PopupCommand::Bottom);
+        search.handle_command(PopupCommand::Right);
+        assert_eq!(search.item_count(), 62);
+
+        search.handle_command(PopupCommand::Bottom);
+        assert_eq!(search.selected_result, 61);
+        assert!(search.scroll_offset > 0);
+
+        search.handle_command(PopupCommand::Left);
+        assert_eq!(search.item_count(), 2);
+        assert_eq!(search.selected_result, 1);
+        assert_eq!(search.scroll_offset, 1);
+    }
+
+    #[test]
+    fn test_kind_filter_cycles_and_narrows_results() {
+        let mut search = searched();
+        let cycle = |search: &mut BookSearch| {
+            search.handle_command(PopupCommand::CycleFilter);
+            (search.kind_filter, search.total_matches())
+        };
+
+        assert_eq!(cycle(&mut search), (Some(MatchKind::Text), 62));
+        assert_eq!(cycle(&mut search), (Some(MatchKind::Figure), 0));
+        assert!(search.results.is_empty());
+        assert_eq!(cycle(&mut search), (Some(MatchKind::Table), 0));
+        assert_eq!(cycle(&mut search), (None, 62));
+        assert_eq!(search.results.len(), 50);
+    }
+
+    #[test]
+    fn test_up_and_down_recall_past_queries_in_the_input() {
+        let mut search = searched();
+        search.set_history(vec!["ahab".to_string(), "whale".to_string()]);
+        search.open(true);
+        search.search_input = "sea".to_string();
+
+        search.handle_command(PopupCommand::Up);
+        search.handle_command(PopupCommand::Up);
+        assert_eq!(search.search_input, "ahab");
+        assert_eq!(search.cursor_position, 4);
+        assert!(matches!(search.focus_mode, FocusMode::Input));
+
+        search.handle_command(PopupCommand::Down);
+        search.handle_command(PopupCommand::Down);
+        assert_eq!(search.search_input, "sea");
+
+        match search.handle_command(PopupCommand::Submit) {
+            Some(BookSearchAction::Searched(query)) => assert_eq!(query, "sea"),
+            _ => panic!("expected the searched query"),
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use crate::inputs::KeySeq;
+use crate::i18n::{tr, tr_args};
 use crate::main_app::VimNavMotions;
+use crate::main_app::commands::PopupCommand;
 use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
 use crate::parsing::markdown_renderer::MarkdownRenderer;
 use crate::parsing::toc_parser::TocParser;
 use crate::theme::current_theme;
 use anyhow::Result;
-use crossterm::event::KeyModifiers;
 use epub::doc::EpubDoc;
 use log::{debug, error};
 use ratatui::Frame;
@@ -21,6 +21,10 @@ pub struct BookStat {
     visible: bool,
     terminal_size: (u16, u16),
     last_popup_area: Option<Rect>,
+    /// Spine chapters with (almost) no text, flagged in the list
+    empty_chapters: Vec<bool>,
+    /// Chapters marked read and the spine length
+    chapters_read: Option<(usize, usize)>,
 }
 
 #[derive(Clone, Debug)]
@@ -29,6 +33,7 @@ struct ChapterStat {
     screens: usize,
     chapter_index: usize, // The actual chapter index in the EPUB
     is_top_level: bool,   // Whether this is a top-level chapter or nested section
+    is_empty: bool,       // No text here nor in nested sections
 }
 
 pub enum BookStatAction {
@@ -50,16 +55,24 @@ impl BookStat {
             visible: false,
             terminal_size: (80, 24),
             last_popup_area: None,
+            empty_chapters: Vec::new(),
+            chapters_read: None,
         }
     }
 
+    pub fn set_chapters_read(&mut self, read: usize, total: usize) {
+        self.chapters_read = Some((read, total));
+    }
+
     pub fn calculate_stats<R: Read + Seek>(
         &mut self,
         epub: &mut EpubDoc<R>,
         terminal_size: (u16, u16),
+        empty_chapters: &[bool],
     ) -> Result<()> {
         self.terminal_size = terminal_size;
         self.chapter_stats.clear();
+        self.empty_chapters = empty_chapters.to_vec();
 
         let toc = TocParser::parse_toc_structure(epub);
 
@@ -262,6 +275,11 @@ impl BookStat {
 
         // Calculate screens based on rendered text
         let screens = self.calculate_screens(&rendered_text, text_width, lines_per_screen);
+        let is_empty = self
+            .empty_chapters
+            .get(chapter_index)
+            .copied()
+            .unwrap_or(false);
 
         if is_top_level {
             // Only add top-level chapters to the visible stats list
@@ -270,6 +288,7 @@ impl BookStat {
                 screens,
                 chapter_index,
                 is_top_level,
+                is_empty,
             });
         } else {
             // For nested sections, contribute screens to the parent top-level chapter
@@ -281,6 +300,7 @@ impl BookStat {
                 .find(|stat| stat.is_top_level)
             {
                 last_top_level.screens += screens;
+                last_top_level.is_empty &= is_empty;
             }
         }
     }
@@ -355,7 +375,7 @@ impl BookStat {
         let items: Vec<ListItem> = if self.chapter_stats.is_empty() {
             // Show a message if no chapters found
             vec![ListItem::new(vec![Line::from(vec![Span::styled(
-                "No chapters found. Processing...",
+                tr("book_stat.no_chapters"),
                 Style::default().fg(current_theme().base_0a),
             )])])]
         } else {
@@ -373,12 +393,12 @@ impl BookStat {
                     cumulative_screens += stat.screens;
 
                     let screens_text = if stat.screens == 1 {
-                        "1 screen".to_string()
+                        tr("book_stat.screens.one").to_string()
                     } else {
-                        format!("{} screens", stat.screens)
+                        tr_args("book_stat.screens.other", &[("count", &stat.screens)])
                     };
 
-                    let content = vec![Line::from(vec![
+                    let mut spans = vec![
                         Span::styled(
                             format!("{percentage:3}% "),
                             Style::default().fg(current_theme().base_03),
@@ -389,18 +409,40 @@ impl BookStat {
                             format!("[{screens_text}]"),
                             Style::default().fg(current_theme().base_0c),
                         ),
-                    ])];
+                    ];
+                    if stat.is_empty {
+                        spans.push(Span::styled(
+                            tr("book_stat.empty_chapter"),
+                            Style::default().fg(current_theme().base_08),
+                        ));
+                    }
 
-                    ListItem::new(content)
+                    ListItem::new(vec![Line::from(spans)])
                 })
                 .collect()
         };
 
+        // Empty spine items are often missing from the TOC, so count them all
+        let empty_count = self.empty_chapters.iter().filter(|&&empty| empty).count();
+        let mut title_parts = vec![tr("book_stat.title").to_string()];
+        if let Some((read, total)) = self.chapters_read {
+            title_parts.push(tr_args(
+                "book_stat.chapters_read",
+                &[("read", &read), ("total", &total)],
+            ));
+        }
+        match empty_count {
+            0 => {}
+            1 => title_parts.push(tr("book_stat.empty_count.one").to_string()),
+            n => title_parts.push(tr_args("book_stat.empty_count.other", &[("count", &n)])),
+        }
+        let title = format!(" {} ", title_parts.join(" · "));
+
         // Create the list widget
         let list = List::new(items)
             .block(
                 Block::default()
-                    .title(" Chapter Statistics ")
+                    .title(title)
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(current_theme().base_0c))
                     .style(Style::default().bg(current_theme().base_00)),
@@ -416,8 +458,7 @@ impl BookStat {
         frame.render_stateful_widget(list, popup_area, &mut self.list_state);
 
         // Add help text at the bottom
-        let help_text =
-            "j/k/Scroll: Navigate | Enter/DblClick: Jump | G/gg: Bottom/Top | Esc: Close";
+        let help_text = tr("book_stat.help");
         let help = Paragraph::new(help_text)
             .style(Style::default().fg(current_theme().base_03))
             .alignment(Alignment::Center);
@@ -432,44 +473,23 @@ impl BookStat {
         frame.render_widget(help, help_area);
     }
 
-    pub fn handle_key(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        key_seq: &mut KeySeq,
-    ) -> Option<BookStatAction> {
-        use crossterm::event::KeyCode;
-
-        match key.code {
-            KeyCode::Char('j') => {
-                self.handle_j();
-                None
-            }
-            KeyCode::Char('k') => {
-                self.handle_k();
-                None
-            }
-            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => {
-                self.handle_gg();
-                None
-            }
-            KeyCode::Char('G') => {
-                self.handle_upper_g();
-                None
-            }
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_d();
-                None
-            }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_u();
-                None
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<BookStatAction> {
+        match command {
+            PopupCommand::Down => self.handle_j(),
+            PopupCommand::Up => self.handle_k(),
+            PopupCommand::Top => self.handle_gg(),
+            PopupCommand::Bottom => self.handle_upper_g(),
+            PopupCommand::HalfPageDown => self.handle_ctrl_d(),
+            PopupCommand::HalfPageUp => self.handle_ctrl_u(),
+            PopupCommand::Close => return Some(BookStatAction::Close),
+            PopupCommand::Select => {
+                return Some(BookStatAction::JumpToChapter {
+                    chapter_index: self.get_selected_chapter_index().unwrap_or(0),
+                });
             }
-            KeyCode::Esc => Some(BookStatAction::Close),
-            KeyCode::Enter => Some(BookStatAction::JumpToChapter {
-                chapter_index: self.get_selected_chapter_index().unwrap_or(0),
-            }),
-            _ => None,
+            _ => {}
         }
+        None
     }
 
     /// Handle mouse click at the given position
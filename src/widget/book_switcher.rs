@@ -0,0 +1,226 @@
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+pub enum BookSwitcherAction {
+    Switch { path: String },
+    Close,
+}
+
+/// A row of the popup: a book opened this session
+pub struct SwitcherEntry {
+    pub path: String,
+    pub name: String,
+    /// Chapter the book was left in, None for the open book
+    pub chapter_index: Option<usize>,
+}
+
+/// Popup listing the books opened this session, most recent first (Space+l)
+pub struct BookSwitcher {
+    entries: Vec<SwitcherEntry>,
+    current: Option<String>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+    last_list_area: Option<Rect>,
+}
+
+impl BookSwitcher {
+    /// Opens on the book before `current`, the one Ctrl+6 would switch to
+    pub fn new(entries: Vec<SwitcherEntry>, current: Option<String>) -> Self {
+        let selected = entries
+            .iter()
+            .position(|entry| Some(&entry.path) != current.as_ref());
+        let mut state = ListState::default();
+        state.select(selected.or(if entries.is_empty() { None } else { Some(0) }));
+
+        BookSwitcher {
+            entries,
+            current,
+            state,
+            last_popup_area: None,
+            last_list_area: None,
+        }
+    }
+
+    fn step_selection(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let i = match (self.state.selected(), forward) {
+            (Some(i), true) if i < last => i + 1,
+            (Some(_), true) | (None, _) => 0,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn switch_action(&self) -> Option<BookSwitcherAction> {
+        let entry = self.state.selected().and_then(|i| self.entries.get(i))?;
+        Some(BookSwitcherAction::Switch {
+            path: entry.path.clone(),
+        })
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<BookSwitcherAction> {
+        match command {
+            PopupCommand::Close => Some(BookSwitcherAction::Close),
+            PopupCommand::Select => self.switch_action(),
+            PopupCommand::Down => {
+                self.step_selection(true);
+                None
+            }
+            PopupCommand::Up => {
+                self.step_selection(false);
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(50, 40, area);
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let marker = if Some(&entry.path) == self.current.as_ref() {
+                    "● "
+                } else {
+                    "  "
+                };
+                let mut spans = vec![
+                    Span::styled(marker, Style::default().fg(palette.base_0b)),
+                    Span::styled(
+                        entry.name.as_str(),
+                        Style::default()
+                            .fg(palette.base_05)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ];
+                if let Some(chapter_index) = entry.chapter_index {
+                    spans.push(Span::styled(
+                        format!(" · chapter {}", chapter_index + 1),
+                        Style::default().fg(palette.base_03),
+                    ));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let block = Block::default()
+            .title(tr("title.book_switcher"))
+            .title_bottom(Line::from(Span::styled(
+                " Enter: Switch | Ctrl+6: Alternate book ",
+                Style::default().fg(palette.base_03),
+            )))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let list_area = block.inner(popup_area);
+        self.last_list_area = Some(list_area);
+        f.render_widget(block, popup_area);
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    /// Select the clicked book; returns the switch on a click on the
+    /// selected one
+    pub fn handle_mouse_click(&mut self, x: u16, y: u16) -> Option<BookSwitcherAction> {
+        let list_area = self.last_list_area?;
+        if x < list_area.x || x >= list_area.right() || y < list_area.y || y >= list_area.bottom() {
+            return None;
+        }
+        let index = self.state.offset() + (y - list_area.y) as usize;
+        if index >= self.entries.len() {
+            return None;
+        }
+        if self.state.selected() == Some(index) {
+            return self.switch_action();
+        }
+        self.state.select(Some(index));
+        None
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str) -> SwitcherEntry {
+        SwitcherEntry {
+            path: path.to_string(),
+            name: path.trim_end_matches(".epub").to_string(),
+            chapter_index: Some(0),
+        }
+    }
+
+    #[test]
+    fn test_opens_on_the_alternate_book() {
+        let mut popup = BookSwitcher::new(
+            vec![entry("a.epub"), entry("b.epub"), entry("c.epub")],
+            Some("a.epub".to_string()),
+        );
+        match popup.handle_command(PopupCommand::Select) {
+            Some(BookSwitcherAction::Switch { path }) => assert_eq!(path, "b.epub"),
+            _ => panic!("expected a switch"),
+        }
+
+        popup.handle_command(PopupCommand::Down);
+        match popup.handle_command(PopupCommand::Select) {
+            Some(BookSwitcherAction::Switch { path }) => assert_eq!(path, "c.epub"),
+            _ => panic!("expected a switch"),
+        }
+        assert!(matches!(
+            popup.handle_command(PopupCommand::Close),
+            Some(BookSwitcherAction::Close)
+        ));
+    }
+}
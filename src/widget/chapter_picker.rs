@@ -0,0 +1,544 @@
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
+use crate::table_of_contents::TocItem;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+const BREADCRUMB_SEPARATOR: &str = " › ";
+
+pub enum ChapterPickerAction {
+    Close,
+    NavigateToChapter {
+        href: String,
+        anchor: Option<String>,
+    },
+}
+
+/// A TOC entry flattened for the picker, remembering where it sat in the tree
+#[derive(Debug, Clone)]
+struct PickerEntry {
+    title: String,
+    href: String,
+    anchor: Option<String>,
+    depth: usize,
+    ancestors: Vec<String>,
+    recent_rank: Option<usize>,
+}
+
+struct PickerMatch {
+    entry_index: usize,
+    /// Char positions in the title that matched the query
+    positions: Vec<usize>,
+}
+
+/// Quick-switcher popup: a fuzzy-searchable flat list of the book's TOC
+pub struct ChapterPicker {
+    entries: Vec<PickerEntry>,
+    matches: Vec<PickerMatch>,
+    query: String,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+    last_list_area: Option<Rect>,
+}
+
+impl ChapterPicker {
+    /// `recent_rank` maps a chapter href to its recency (0 = most recent);
+    /// recent chapters are listed first while the query is empty.
+    pub fn new(toc_items: &[TocItem], recent_rank: impl Fn(&str) -> Option<usize>) -> Self {
+        let mut entries = Vec::new();
+        flatten_toc(toc_items, &mut Vec::new(), &mut entries);
+        for entry in &mut entries {
+            entry.recent_rank = recent_rank(&entry.href);
+        }
+
+        let mut picker = ChapterPicker {
+            entries,
+            matches: Vec::new(),
+            query: String::new(),
+            state: ListState::default(),
+            last_popup_area: None,
+            last_list_area: None,
+        };
+        picker.update_matches();
+        picker
+    }
+
+    fn update_matches(&mut self) {
+        if self.query.is_empty() {
+            let mut indices: Vec<usize> = (0..self.entries.len()).collect();
+            // Stable sort keeps TOC order within the recent and the rest groups
+            indices.sort_by_key(|&idx| self.entries[idx].recent_rank.unwrap_or(usize::MAX));
+            self.matches = indices
+                .into_iter()
+                .map(|entry_index| PickerMatch {
+                    entry_index,
+                    positions: Vec::new(),
+                })
+                .collect();
+        } else {
+            let mut scored: Vec<(i64, PickerMatch)> = self
+                .entries
+                .iter()
+                .enumerate()
+                .filter_map(|(entry_index, entry)| {
+                    fuzzy_match(&self.query, &entry.title).map(|(score, positions)| {
+                        (
+                            score,
+                            PickerMatch {
+                                entry_index,
+                                positions,
+                            },
+                        )
+                    })
+                })
+                .collect();
+            scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+            self.matches = scored.into_iter().map(|(_, m)| m).collect();
+        }
+
+        self.state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        *self.state.offset_mut() = 0;
+    }
+
+    fn selected_entry(&self) -> Option<&PickerEntry> {
+        let selected = self.state.selected()?;
+        let picker_match = self.matches.get(selected)?;
+        self.entries.get(picker_match.entry_index)
+    }
+
+    fn next(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.matches.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.matches.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn navigate_action(&self) -> ChapterPickerAction {
+        match self.selected_entry() {
+            Some(entry) => ChapterPickerAction::NavigateToChapter {
+                href: entry.href.clone(),
+                anchor: entry.anchor.clone(),
+            },
+            None => ChapterPickerAction::Close,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 60, area);
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+
+        let block = Block::default()
+            .title(tr("title.chapter_picker"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(1)])
+            .split(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled("> ", Style::default().fg(palette.base_0d)),
+            Span::styled(
+                self.query.as_str(),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {}/{}", self.matches.len(), self.entries.len()),
+                Style::default().fg(palette.base_03),
+            ),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(Style::default().fg(palette.base_02)),
+        );
+        f.render_widget(input, chunks[0]);
+
+        let list_area = chunks[1];
+        self.last_list_area = Some(list_area);
+
+        let filtering = !self.query.is_empty();
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|picker_match| {
+                let entry = &self.entries[picker_match.entry_index];
+                let mut spans = Vec::new();
+
+                if filtering {
+                    if !entry.ancestors.is_empty() {
+                        spans.push(Span::styled(
+                            format!(
+                                "{}{}",
+                                entry.ancestors.join(BREADCRUMB_SEPARATOR),
+                                BREADCRUMB_SEPARATOR
+                            ),
+                            Style::default().fg(palette.base_03),
+                        ));
+                    }
+                } else {
+                    spans.push(Span::raw("  ".repeat(entry.depth)));
+                }
+
+                let normal = Style::default().fg(palette.base_06);
+                let highlighted = Style::default()
+                    .fg(palette.base_0a)
+                    .add_modifier(Modifier::BOLD);
+                let mut current = String::new();
+                let mut current_is_match = false;
+                for (idx, ch) in entry.title.chars().enumerate() {
+                    let is_match = picker_match.positions.contains(&idx);
+                    if is_match != current_is_match && !current.is_empty() {
+                        let style = if current_is_match {
+                            highlighted
+                        } else {
+                            normal
+                        };
+                        spans.push(Span::styled(std::mem::take(&mut current), style));
+                    }
+                    current_is_match = is_match;
+                    current.push(ch);
+                }
+                if !current.is_empty() {
+                    let style = if current_is_match {
+                        highlighted
+                    } else {
+                        normal
+                    };
+                    spans.push(Span::styled(current, style));
+                }
+
+                if !filtering && entry.recent_rank.is_some() {
+                    spans.push(Span::styled(
+                        " (recent)",
+                        Style::default().fg(palette.base_03),
+                    ));
+                }
+
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    pub fn handle_mouse_click(&mut self, x: u16, y: u16) -> bool {
+        if let Some(list_area) = self.last_list_area {
+            if x >= list_area.x
+                && x < list_area.x + list_area.width
+                && y >= list_area.y
+                && y < list_area.y + list_area.height
+            {
+                let relative_y = y.saturating_sub(list_area.y);
+                let new_index = self.state.offset() + relative_y as usize;
+
+                if new_index < self.matches.len() {
+                    self.state.select(Some(new_index));
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<ChapterPickerAction> {
+        match command {
+            PopupCommand::Close => Some(ChapterPickerAction::Close),
+            PopupCommand::Select => Some(self.navigate_action()),
+            PopupCommand::Down => {
+                self.next();
+                None
+            }
+            PopupCommand::Up => {
+                self.previous();
+                None
+            }
+            PopupCommand::ClearInput => {
+                self.query.clear();
+                self.update_matches();
+                None
+            }
+            PopupCommand::Backspace => {
+                if self.query.pop().is_some() {
+                    self.update_matches();
+                }
+                None
+            }
+            PopupCommand::Input(c) => {
+                self.query.push(c);
+                self.update_matches();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn flatten_toc(items: &[TocItem], ancestors: &mut Vec<String>, entries: &mut Vec<PickerEntry>) {
+    for item in items {
+        if let Some(href) = item.href() {
+            entries.push(PickerEntry {
+                title: item.title().to_string(),
+                href: href.to_string(),
+                anchor: item.anchor().cloned(),
+                depth: ancestors.len(),
+                ancestors: ancestors.clone(),
+                recent_rank: None,
+            });
+        }
+
+        if let TocItem::Section {
+            title, children, ..
+        } = item
+        {
+            ancestors.push(title.clone());
+            flatten_toc(children, ancestors, entries);
+            ancestors.pop();
+        }
+    }
+}
+
+/// Case-insensitive subsequence match. Returns a score (higher is better) and
+/// the char positions in `candidate` that matched. Consecutive matches and
+/// matches at word starts score higher; gaps cost a little.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+
+    let candidate: Vec<char> = candidate.chars().collect();
+    let mut positions = Vec::with_capacity(query.len());
+    let mut score = 0i64;
+    let mut query_idx = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (idx, ch) in candidate.iter().enumerate() {
+        if query_idx == query.len() {
+            break;
+        }
+        if !ch.to_lowercase().eq(std::iter::once(query[query_idx])) {
+            continue;
+        }
+
+        score += 1;
+        let word_start = idx == 0 || !candidate[idx - 1].is_alphanumeric();
+        if word_start {
+            score += 8;
+        }
+        match last_match {
+            Some(last) if last + 1 == idx => score += 5,
+            Some(last) => score -= (idx - last - 1).min(5) as i64,
+            None => score -= idx.min(10) as i64,
+        }
+
+        positions.push(idx);
+        last_match = Some(idx);
+        query_idx += 1;
+    }
+
+    if query_idx < query.len() {
+        return None;
+    }
+    // Prefer shorter titles among otherwise equal matches
+    score -= (candidate.len() / 10) as i64;
+    Some((score, positions))
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chapter(title: &str, href: &str) -> TocItem {
+        TocItem::Chapter {
+            title: title.to_string(),
+            href: href.to_string(),
+            anchor: None,
+        }
+    }
+
+    fn sample_toc() -> Vec<TocItem> {
+        vec![
+            chapter("Preface", "preface.xhtml"),
+            TocItem::Section {
+                title: "Part I".to_string(),
+                href: None,
+                anchor: None,
+                children: vec![
+                    chapter("Getting Started", "ch01.xhtml"),
+                    TocItem::Chapter {
+                        title: "Ownership".to_string(),
+                        href: "ch02.xhtml".to_string(),
+                        anchor: Some("ownership".to_string()),
+                    },
+                ],
+                is_expanded: false,
+            },
+            chapter("Appendix", "appendix.xhtml"),
+        ]
+    }
+
+    fn type_query(picker: &mut ChapterPicker, query: &str) {
+        for c in query.chars() {
+            picker.handle_command(PopupCommand::Input(c));
+        }
+    }
+
+    fn listed_titles(picker: &ChapterPicker) -> Vec<&str> {
+        picker
+            .matches
+            .iter()
+            .map(|m| picker.entries[m.entry_index].title.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn test_flatten_keeps_nesting_as_ancestors() {
+        let picker = ChapterPicker::new(&sample_toc(), |_| None);
+
+        assert_eq!(
+            listed_titles(&picker),
+            vec!["Preface", "Getting Started", "Ownership", "Appendix"]
+        );
+        let ownership = &picker.entries[2];
+        assert_eq!(ownership.depth, 1);
+        assert_eq!(ownership.ancestors, vec!["Part I".to_string()]);
+    }
+
+    #[test]
+    fn test_fuzzy_match_prefers_word_starts_and_rejects_non_subsequences() {
+        let (start_score, positions) = fuzzy_match("gs", "Getting Started").unwrap();
+        assert_eq!(positions, vec![0, 8]);
+        let (middle_score, _) = fuzzy_match("gs", "Hedgehogs").unwrap();
+        assert!(start_score > middle_score);
+
+        assert!(fuzzy_match("xyz", "Getting Started").is_none());
+        assert!(fuzzy_match("OWN", "Ownership").is_some());
+    }
+
+    #[test]
+    fn test_typing_filters_and_enter_navigates_with_anchor() {
+        let mut picker = ChapterPicker::new(&sample_toc(), |_| None);
+        type_query(&mut picker, "own");
+
+        assert_eq!(listed_titles(&picker), vec!["Ownership"]);
+        match picker.handle_command(PopupCommand::Select) {
+            Some(ChapterPickerAction::NavigateToChapter { href, anchor }) => {
+                assert_eq!(href, "ch02.xhtml");
+                assert_eq!(anchor.as_deref(), Some("ownership"));
+            }
+            _ => panic!("expected navigation"),
+        }
+    }
+
+    #[test]
+    fn test_backspace_restores_full_list_and_esc_closes() {
+        let mut picker = ChapterPicker::new(&sample_toc(), |_| None);
+        type_query(&mut picker, "zz");
+        assert!(picker.matches.is_empty());
+        assert!(matches!(
+            picker.handle_command(PopupCommand::Select),
+            Some(ChapterPickerAction::Close)
+        ));
+
+        picker.handle_command(PopupCommand::Backspace);
+        picker.handle_command(PopupCommand::Backspace);
+        assert_eq!(picker.matches.len(), 4);
+        assert!(matches!(
+            picker.handle_command(PopupCommand::Close),
+            Some(ChapterPickerAction::Close)
+        ));
+    }
+
+    #[test]
+    fn test_recent_chapters_sort_first_with_empty_query() {
+        let picker = ChapterPicker::new(&sample_toc(), |href| match href {
+            "appendix.xhtml" => Some(0),
+            "ch01.xhtml" => Some(1),
+            _ => None,
+        });
+
+        assert_eq!(
+            listed_titles(&picker),
+            vec!["Appendix", "Getting Started", "Preface", "Ownership"]
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use crate::i18n::tr;
+use crate::main_app::commands::{
+    Command, PALETTE, PopupCommand, keys_with_ctrl_z, keys_with_leader,
+};
+use crate::settings::{CtrlZ, LEADER};
+use crate::theme::current_theme;
+use crate::widget::chapter_picker::fuzzy_match;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+pub enum CommandPaletteAction {
+    Close,
+    Run {
+        /// Translation key of the command's name
+        key: &'static str,
+        command: Command,
+    },
+}
+
+struct PaletteMatch {
+    /// Index into `PALETTE`
+    entry_index: usize,
+    /// Char positions in the name that matched the query
+    positions: Vec<usize>,
+}
+
+/// Popup listing every command by name with its keys (Space+:), filtered
+/// by fuzzy search the way the chapter picker filters chapters
+pub struct CommandPalette {
+    /// Recency of each `PALETTE` entry (0 = most recent)
+    recent_ranks: Vec<Option<usize>>,
+    matches: Vec<PaletteMatch>,
+    query: String,
+    /// Key the Space+ sequences start with, shown in their keys
+    leader_key: char,
+    /// What Ctrl+z runs, which decides the entries it is listed for
+    ctrl_z: CtrlZ,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+    last_list_area: Option<Rect>,
+}
+
+impl CommandPalette {
+    /// `recent` holds the name keys of commands run from the palette, most
+    /// recent first; they are listed first, and win ties while filtering
+    pub fn new(recent: &[&str]) -> Self {
+        let recent_ranks = PALETTE
+            .iter()
+            .map(|(key, _, _)| recent.iter().position(|recent| recent == key))
+            .collect();
+        let mut palette = CommandPalette {
+            recent_ranks,
+            matches: Vec::new(),
+            query: String::new(),
+            leader_key: LEADER,
+            ctrl_z: CtrlZ::default(),
+            state: ListState::default(),
+            last_popup_area: None,
+            last_list_area: None,
+        };
+        palette.update_matches();
+        palette
+    }
+
+    /// Show the keys of sequences as started with `leader_key`
+    pub fn with_leader_key(mut self, leader_key: char) -> Self {
+        self.leader_key = leader_key;
+        self
+    }
+
+    /// List Ctrl+z for the command the `ctrl_z` setting gives it
+    pub fn with_ctrl_z(mut self, ctrl_z: CtrlZ) -> Self {
+        self.ctrl_z = ctrl_z;
+        self
+    }
+
+    fn update_matches(&mut self) {
+        let mut scored: Vec<(i64, usize, PaletteMatch)> = PALETTE
+            .iter()
+            .enumerate()
+            .filter_map(|(entry_index, (key, _, _))| {
+                let (score, positions) = fuzzy_match(&self.query, tr(key))?;
+                let recent_rank = self.recent_ranks[entry_index].unwrap_or(usize::MAX);
+                Some((
+                    score,
+                    recent_rank,
+                    PaletteMatch {
+                        entry_index,
+                        positions,
+                    },
+                ))
+            })
+            .collect();
+        // Stable sort keeps the palette's order among equals
+        scored.sort_by_key(|(score, recent_rank, _)| (std::cmp::Reverse(*score), *recent_rank));
+        self.matches = scored.into_iter().map(|(_, _, m)| m).collect();
+
+        self.state.select(if self.matches.is_empty() {
+            None
+        } else {
+            Some(0)
+        });
+        *self.state.offset_mut() = 0;
+    }
+
+    fn next(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(i) if i + 1 < self.matches.len() => i + 1,
+            _ => 0,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn previous(&mut self) {
+        if self.matches.is_empty() {
+            return;
+        }
+        let i = match self.state.selected() {
+            Some(0) | None => self.matches.len() - 1,
+            Some(i) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn run_action(&self) -> CommandPaletteAction {
+        let selected = self
+            .state
+            .selected()
+            .and_then(|selected| self.matches.get(selected));
+        match selected {
+            Some(palette_match) => {
+                let (key, _, command) = PALETTE[palette_match.entry_index];
+                CommandPaletteAction::Run { key, command }
+            }
+            None => CommandPaletteAction::Close,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 60, area);
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+
+        let block = Block::default()
+            .title(tr("title.command_palette"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(1)])
+            .split(inner);
+
+        let input = Paragraph::new(Line::from(vec![
+            Span::styled(": ", Style::default().fg(palette.base_0d)),
+            Span::styled(
+                self.query.as_str(),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!("  {}/{}", self.matches.len(), PALETTE.len()),
+                Style::default().fg(palette.base_03),
+            ),
+        ]))
+        .block(
+            Block::default()
+                .borders(Borders::BOTTOM)
+                .border_style(Style::default().fg(palette.base_02)),
+        );
+        f.render_widget(input, chunks[0]);
+
+        let list_area = chunks[1];
+        self.last_list_area = Some(list_area);
+
+        // Room for a row after the highlight symbol
+        let row_width = list_area.width.saturating_sub(2) as usize;
+        let filtering = !self.query.is_empty();
+        let items: Vec<ListItem> = self
+            .matches
+            .iter()
+            .map(|palette_match| {
+                let (key, keys, command) = PALETTE[palette_match.entry_index];
+                let name = tr(key);
+                let keys = keys_with_leader(
+                    &keys_with_ctrl_z(keys, command, self.ctrl_z),
+                    self.leader_key,
+                );
+                let normal = Style::default().fg(palette.base_06);
+                let highlighted = Style::default()
+                    .fg(palette.base_0a)
+                    .add_modifier(Modifier::BOLD);
+                let mut spans: Vec<Span> = name
+                    .chars()
+                    .enumerate()
+                    .map(|(idx, ch)| {
+                        let style = if palette_match.positions.contains(&idx) {
+                            highlighted
+                        } else {
+                            normal
+                        };
+                        Span::styled(ch.to_string(), style)
+                    })
+                    .collect();
+
+                let mut used = name.chars().count();
+                if !filtering && self.recent_ranks[palette_match.entry_index].is_some() {
+                    let recent = tr("palette.recent");
+                    spans.push(Span::styled(recent, Style::default().fg(palette.base_03)));
+                    used += recent.chars().count();
+                }
+                // The keys against the right edge
+                let gap = row_width.saturating_sub(used + keys.chars().count()).max(2);
+                spans.push(Span::raw(" ".repeat(gap)));
+                spans.push(Span::styled(keys, Style::default().fg(palette.base_0d)));
+
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    pub fn handle_mouse_click(&mut self, x: u16, y: u16) -> bool {
+        if let Some(list_area) = self.last_list_area {
+            if x >= list_area.x
+                && x < list_area.x + list_area.width
+                && y >= list_area.y
+                && y < list_area.y + list_area.height
+            {
+                let relative_y = y.saturating_sub(list_area.y);
+                let new_index = self.state.offset() + relative_y as usize;
+
+                if new_index < self.matches.len() {
+                    self.state.select(Some(new_index));
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<CommandPaletteAction> {
+        match command {
+            PopupCommand::Close => Some(CommandPaletteAction::Close),
+            PopupCommand::Select => Some(self.run_action()),
+            PopupCommand::Down => {
+                self.next();
+                None
+            }
+            PopupCommand::Up => {
+                self.previous();
+                None
+            }
+            PopupCommand::ClearInput => {
+                self.query.clear();
+                self.update_matches();
+                None
+            }
+            PopupCommand::Backspace => {
+                if self.query.pop().is_some() {
+                    self.update_matches();
+                }
+                None
+            }
+            PopupCommand::Input(c) => {
+                self.query.push(c);
+                self.update_matches();
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{Locale, with_locale};
+    use crate::main_app::PopupWindow;
+
+    fn type_query(palette: &mut CommandPalette, query: &str) {
+        for c in query.chars() {
+            palette.handle_command(PopupCommand::Input(c));
+        }
+    }
+
+    fn listed_names(palette: &CommandPalette) -> Vec<&'static str> {
+        palette
+            .matches
+            .iter()
+            .map(|m| tr(PALETTE[m.entry_index].0))
+            .collect()
+    }
+
+    #[test]
+    fn test_typing_filters_and_enter_runs_the_command() {
+        let mut palette = CommandPalette::new(&[]);
+        assert_eq!(palette.matches.len(), PALETTE.len());
+
+        type_query(&mut palette, "theme");
+        assert_eq!(listed_names(&palette)[0], "Change theme");
+        match palette.handle_command(PopupCommand::Select) {
+            Some(CommandPaletteAction::Run { key, command }) => {
+                assert_eq!(key, "palette.change_theme");
+                assert_eq!(command, Command::TogglePopup(PopupWindow::ThemeSelector));
+            }
+            _ => panic!("expected a command"),
+        }
+
+        type_query(&mut palette, "zz");
+        assert!(matches!(
+            palette.handle_command(PopupCommand::Select),
+            Some(CommandPaletteAction::Close)
+        ));
+        assert!(matches!(
+            palette.handle_command(PopupCommand::Close),
+            Some(CommandPaletteAction::Close)
+        ));
+    }
+
+    #[test]
+    fn test_recent_commands_sort_first() {
+        let mut palette = CommandPalette::new(&["palette.quit", "palette.next_recent_book"]);
+        assert_eq!(listed_names(&palette)[..2], ["Quit", "Next recent book"]);
+
+        // And win among equal matches
+        type_query(&mut palette, "next");
+        assert_eq!(listed_names(&palette)[0], "Next recent book");
+        let mut palette = CommandPalette::new(&[]);
+        type_query(&mut palette, "next");
+        assert_eq!(listed_names(&palette)[0], "Next chapter");
+    }
+
+    #[test]
+    fn test_names_are_translated_and_filtered_as_shown() {
+        for (key, _, _) in PALETTE {
+            assert_ne!(tr(key), *key, "{key} has no translation");
+        }
+        with_locale(Locale::Ru, || {
+            let mut palette = CommandPalette::new(&[]);
+            type_query(&mut palette, "тему");
+            assert_eq!(listed_names(&palette)[0], "Сменить тему");
+        });
+    }
+}
@@ -0,0 +1,454 @@
+use crate::comments::CommentFileInfo;
+use crate::i18n::{tr, tr_args};
+use crate::main_app::commands::{PopupCommand, PopupMode};
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+use std::path::PathBuf;
+
+pub enum CommentStorageAction {
+    /// Move the selected file to the trash
+    Delete {
+        path: PathBuf,
+    },
+    /// Merge the comments file at `source` into `target`
+    Merge {
+        target: PathBuf,
+        source: String,
+    },
+    /// Copy every comments file to `directory` and keep comments there
+    Migrate {
+        directory: String,
+    },
+    Close,
+}
+
+/// A row of the popup: a comments file and the library book it belongs to
+pub struct CommentFileEntry {
+    pub info: CommentFileInfo,
+    /// None when no book in the library uses the file
+    pub book_name: Option<String>,
+}
+
+enum Mode {
+    Browse,
+    /// Deletion of the selected file waiting for a y/n answer
+    ConfirmDelete,
+    /// Typing the path of a comments file to merge into the selected one
+    Merging(String),
+    /// Typing the directory to move comments to
+    Migrating(String),
+}
+
+/// Popup listing the comments files in the comments directory
+pub struct CommentStorage {
+    directory: PathBuf,
+    entries: Vec<CommentFileEntry>,
+    mode: Mode,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl CommentStorage {
+    pub fn new(directory: PathBuf, entries: Vec<CommentFileEntry>) -> Self {
+        let mut state = ListState::default();
+        state.select(if entries.is_empty() { None } else { Some(0) });
+
+        CommentStorage {
+            directory,
+            entries,
+            mode: Mode::Browse,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    /// Replace the listed files after a change, keeping the selection near
+    /// where it was
+    pub fn refresh(&mut self, directory: PathBuf, entries: Vec<CommentFileEntry>) {
+        let selected = self
+            .state
+            .selected()
+            .map(|i| i.min(entries.len().saturating_sub(1)));
+        self.state
+            .select(if entries.is_empty() { None } else { selected });
+        self.directory = directory;
+        self.entries = entries;
+        self.mode = Mode::Browse;
+    }
+
+    fn selected_entry(&self) -> Option<&CommentFileEntry> {
+        self.state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    fn step_selection(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let i = match (self.state.selected(), forward) {
+            (Some(i), true) if i < last => i + 1,
+            (Some(_), true) | (None, _) => 0,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn popup_mode(&self) -> PopupMode {
+        match self.mode {
+            Mode::Browse => PopupMode::Browse,
+            Mode::Merging(_) | Mode::Migrating(_) => PopupMode::Typing,
+            Mode::ConfirmDelete => PopupMode::Confirming,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<CommentStorageAction> {
+        match &mut self.mode {
+            Mode::Merging(input) | Mode::Migrating(input) => {
+                match command {
+                    PopupCommand::Cancel => self.mode = Mode::Browse,
+                    PopupCommand::Complete => {
+                        let (completed, _) = crate::widget::onboarding::complete_path(input);
+                        *input = completed;
+                    }
+                    PopupCommand::Backspace => {
+                        input.pop();
+                    }
+                    PopupCommand::Input(c) => input.push(c),
+                    PopupCommand::Submit => {
+                        let input = input.trim().to_string();
+                        if input.is_empty() {
+                            return None;
+                        }
+                        return match self.mode {
+                            Mode::Merging(_) => {
+                                let target = self.selected_entry()?.info.path.clone();
+                                Some(CommentStorageAction::Merge {
+                                    target,
+                                    source: input,
+                                })
+                            }
+                            _ => Some(CommentStorageAction::Migrate { directory: input }),
+                        };
+                    }
+                    _ => {}
+                }
+                None
+            }
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Browse;
+                if command == PopupCommand::Confirm {
+                    let path = self.selected_entry()?.info.path.clone();
+                    return Some(CommentStorageAction::Delete { path });
+                }
+                // Anything else cancels the deletion
+                None
+            }
+            Mode::Browse => {
+                match command {
+                    PopupCommand::Close => return Some(CommentStorageAction::Close),
+                    PopupCommand::Down => self.step_selection(true),
+                    PopupCommand::Up => self.step_selection(false),
+                    PopupCommand::Remove if self.selected_entry().is_some() => {
+                        self.mode = Mode::ConfirmDelete;
+                    }
+                    PopupCommand::Merge if self.selected_entry().is_some() => {
+                        self.mode = Mode::Merging(String::new());
+                    }
+                    PopupCommand::Migrate => {
+                        self.mode = Mode::Migrating(String::new());
+                    }
+                    _ => {}
+                }
+                None
+            }
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(70, 60, area);
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = match &entry.book_name {
+                    Some(name) => Span::styled(
+                        name.clone(),
+                        Style::default()
+                            .fg(palette.base_05)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    None => Span::styled(
+                        tr("comment_storage.not_in_library"),
+                        Style::default()
+                            .fg(palette.base_03)
+                            .add_modifier(Modifier::ITALIC),
+                    ),
+                };
+                let count = match entry.info.comment_count {
+                    Some(1) => tr("comment_storage.comments.one").to_string(),
+                    Some(count) => tr_args("comment_storage.comments.other", &[("count", &count)]),
+                    None => tr("comment_storage.unreadable").to_string(),
+                };
+                ListItem::new(Line::from(vec![
+                    name,
+                    Span::styled(" · ", Style::default().fg(palette.base_03)),
+                    Span::styled(count, Style::default().fg(palette.base_0b)),
+                    Span::styled(
+                        format!(
+                            "  {}  {}",
+                            format_size(entry.info.size),
+                            entry.info.file_name()
+                        ),
+                        Style::default().fg(palette.base_03),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let footer = match &self.mode {
+            Mode::Browse => Line::from(Span::styled(
+                tr("comment_storage.footer"),
+                Style::default().fg(palette.base_03),
+            )),
+            Mode::Merging(_) | Mode::Migrating(_) => Line::from(Span::styled(
+                tr("comment_storage.footer_input"),
+                Style::default().fg(palette.base_03),
+            )),
+            Mode::ConfirmDelete => Line::from(Span::styled(
+                tr_args(
+                    "comment_storage.confirm_delete",
+                    &[(
+                        "file",
+                        &self
+                            .selected_entry()
+                            .map(|entry| entry.info.file_name())
+                            .unwrap_or_default(),
+                    )],
+                ),
+                Style::default()
+                    .fg(palette.base_08)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        };
+
+        let block = Block::default()
+            .title(tr("title.comment_storage"))
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(2), Constraint::Min(1)])
+            .split(inner);
+
+        let header = match &self.mode {
+            Mode::Merging(input) => prompt_line(tr("comment_storage.merge_from"), input),
+            Mode::Migrating(input) => prompt_line(tr("comment_storage.move_to"), input),
+            _ => Line::from(vec![
+                Span::styled(
+                    tr("comment_storage.directory"),
+                    Style::default().fg(palette.base_0d),
+                ),
+                Span::styled(
+                    self.directory.display().to_string(),
+                    Style::default().fg(palette.base_05),
+                ),
+            ]),
+        };
+        f.render_widget(
+            Paragraph::new(header).block(
+                Block::default()
+                    .borders(Borders::BOTTOM)
+                    .border_style(Style::default().fg(palette.base_02)),
+            ),
+            chunks[0],
+        );
+
+        if self.entries.is_empty() {
+            f.render_widget(
+                Paragraph::new(Span::styled(
+                    tr("comment_storage.empty"),
+                    Style::default().fg(palette.base_03),
+                )),
+                chunks[1],
+            );
+            return;
+        }
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, chunks[1], &mut self.state);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+fn prompt_line<'a>(label: &'a str, input: &'a str) -> Line<'a> {
+    let palette = current_theme();
+    Line::from(vec![
+        Span::styled(label, Style::default().fg(palette.base_0d)),
+        Span::styled(
+            input,
+            Style::default()
+                .fg(palette.base_05)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::styled("█", Style::default().fg(palette.base_05)),
+    ])
+}
+
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..1024 => format!("{bytes} B"),
+        1024..1_048_576 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MB", bytes as f64 / 1_048_576.0),
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::main_app::commands::{Command, KeyContext, command_for_key};
+    use crate::main_app::{FocusedPanel, PopupWindow};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn entry(file: &str, book_name: Option<&str>) -> CommentFileEntry {
+        CommentFileEntry {
+            info: CommentFileInfo {
+                path: PathBuf::from("/comments").join(file),
+                size: 2048,
+                comment_count: Some(3),
+            },
+            book_name: book_name.map(str::to_string),
+        }
+    }
+
+    fn storage() -> CommentStorage {
+        CommentStorage::new(
+            PathBuf::from("/comments"),
+            vec![
+                entry("book_a.yaml", Some("Moby Dick")),
+                entry("book_b.yaml", None),
+            ],
+        )
+    }
+
+    /// Presses a key the way the app does, in the popup's current mode
+    fn press(popup: &mut CommentStorage, code: KeyCode) -> Option<CommentStorageAction> {
+        let mut context = KeyContext::new(FocusedPanel::Popup(PopupWindow::CommentStorage));
+        context.popup_mode = popup.popup_mode();
+        match command_for_key(&context, KeyEvent::new(code, KeyModifiers::NONE))? {
+            Command::Popup(command) => popup.handle_command(command),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_delete_requires_confirmation() {
+        let mut popup = storage();
+        assert!(press(&mut popup, KeyCode::Char('x')).is_none());
+        assert!(press(&mut popup, KeyCode::Char('n')).is_none());
+        // Browsing again after the cancelled deletion
+        assert!(press(&mut popup, KeyCode::Char('j')).is_none());
+
+        press(&mut popup, KeyCode::Char('x'));
+        match press(&mut popup, KeyCode::Char('y')) {
+            Some(CommentStorageAction::Delete { path }) => {
+                assert_eq!(path, PathBuf::from("/comments/book_b.yaml"))
+            }
+            _ => panic!("expected a deletion"),
+        }
+
+        let mut empty = CommentStorage::new(PathBuf::from("/comments"), Vec::new());
+        press(&mut empty, KeyCode::Char('x'));
+        assert!(press(&mut empty, KeyCode::Char('y')).is_none());
+    }
+
+    #[test]
+    fn test_merge_and_migrate_prompts() {
+        let mut popup = storage();
+        press(&mut popup, KeyCode::Char('m'));
+        assert!(press(&mut popup, KeyCode::Enter).is_none());
+        // Browsing keys are part of the path while typing
+        for c in "~/sync/book_a.yaml".chars() {
+            press(&mut popup, KeyCode::Char(c));
+        }
+        match press(&mut popup, KeyCode::Enter) {
+            Some(CommentStorageAction::Merge { target, source }) => {
+                assert_eq!(target, PathBuf::from("/comments/book_a.yaml"));
+                assert_eq!(source, "~/sync/book_a.yaml");
+            }
+            _ => panic!("expected a merge"),
+        }
+
+        let mut popup = storage();
+        press(&mut popup, KeyCode::Char('M'));
+        for c in "/new/dirx".chars() {
+            press(&mut popup, KeyCode::Char(c));
+        }
+        press(&mut popup, KeyCode::Backspace);
+        match press(&mut popup, KeyCode::Enter) {
+            Some(CommentStorageAction::Migrate { directory }) => assert_eq!(directory, "/new/dir"),
+            _ => panic!("expected a migration"),
+        }
+
+        press(&mut popup, KeyCode::Esc);
+        assert!(matches!(
+            press(&mut popup, KeyCode::Esc),
+            Some(CommentStorageAction::Close)
+        ));
+    }
+
+    #[test]
+    fn test_sizes_are_readable() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(2048), "2.0 KB");
+        assert_eq!(format_size(3 * 1_048_576), "3.0 MB");
+    }
+}
@@ -1,11 +1,15 @@
-use crate::comments::{BookComments, Comment, CommentTarget};
-use crate::inputs::KeySeq;
+use crate::comments::{AnchorStatus, BookComments, Comment, CommentTarget};
+use crate::comments_viewer_state::ViewerPosition;
+use crate::i18n::{self, tr, tr_args};
 use crate::main_app::VimNavMotions;
-use crate::markdown::Inline;
+use crate::main_app::commands::PopupCommand;
+use crate::markdown::{Document, Node};
 use crate::search::{find_matches_in_text, SearchMode, SearchState, SearchablePanel};
 use crate::table_of_contents::TocItem;
 use crate::theme::current_theme;
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
 use epub::doc::EpubDoc;
+use log::warn;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     prelude::Stylize,
@@ -49,6 +53,9 @@ pub struct CommentsViewer {
     global_position: Option<(usize, usize)>,
 }
 
+/// Chapter key of the pseudo-chapter that lists comments whose paragraph is gone.
+const ORPHANED_CHAPTER_KEY: &str = "\u{0}orphaned";
+
 #[derive(Clone)]
 pub struct ChapterDisplay {
     pub title: String,
@@ -69,6 +76,7 @@ pub struct CommentEntry {
     pub chapter_href: String,
     pub quoted_text: String,
     pub comments: Vec<Comment>,
+    pub anchor_status: AnchorStatus,
     pub render_start_line: usize,
     pub render_end_line: usize,
 }
@@ -94,6 +102,15 @@ impl CommentEntry {
     pub fn comments(&self) -> &[Comment] {
         &self.comments
     }
+
+    /// Key used to group the entry in the chapter list
+    fn chapter_key(&self) -> &str {
+        if self.anchor_status == AnchorStatus::Orphaned {
+            ORPHANED_CHAPTER_KEY
+        } else {
+            &self.chapter_href
+        }
+    }
 }
 
 impl CommentsViewer {
@@ -136,27 +153,75 @@ impl CommentsViewer {
 
     pub fn restore_position(&mut self) {
         if self.global_search_mode {
-            if let Some((scroll, selection)) = self.global_position {
-                self.scroll_offset = scroll.min(self.total_rendered_lines.saturating_sub(1));
-                self.selected_index = selection.min(self.rendered_entries.len().saturating_sub(1));
+            if let Some(position) = self.global_position {
+                self.apply_position(position);
                 return;
             }
         } else if let Some(key) = self.current_chapter_key() {
-            if let Some((scroll, selection)) = self.chapter_positions.get(&key).copied() {
-                self.scroll_offset = scroll.min(self.total_rendered_lines.saturating_sub(1));
-                self.selected_index = selection.min(self.rendered_entries.len().saturating_sub(1));
+            if let Some(position) = self.chapter_positions.get(&key).copied() {
+                self.apply_position(position);
                 return;
             }
         }
 
-        if let Some((scroll, selection)) = if self.global_search_mode {
+        if let Some(position) = if self.global_search_mode {
             self.global_position
         } else {
             self.last_position
         } {
-            self.scroll_offset = scroll.min(self.total_rendered_lines.saturating_sub(1));
-            self.selected_index = selection.min(self.rendered_entries.len().saturating_sub(1));
+            self.apply_position(position);
+        }
+    }
+
+    /// Scroll and select as saved; before the entries are first laid out
+    /// the scroll is left for the layout to clamp
+    fn apply_position(&mut self, (scroll, selection): (usize, usize)) {
+        self.scroll_offset = if self.total_rendered_lines == 0 {
+            scroll
+        } else {
+            scroll.min(self.total_rendered_lines - 1)
+        };
+        self.selected_index = selection.min(self.rendered_entries.len().saturating_sub(1));
+    }
+
+    /// Where the viewer is, to reopen it there later; the current list's
+    /// position is included once `save_position` recorded it
+    pub fn saved_position(&self) -> ViewerPosition {
+        let chapter_index = if self.global_search_mode {
+            self.saved_chapter_index
+        } else {
+            self.selected_chapter_index
+        };
+        ViewerPosition {
+            all_comments: self.global_search_mode,
+            chapter_href: self
+                .chapters
+                .get(chapter_index)
+                .and_then(|chapter| chapter.href.clone()),
+            chapter_positions: self.chapter_positions.clone(),
+            all_comments_position: self.global_position,
+        }
+    }
+
+    /// Reopen where `saved_position` left off; a chapter no longer listed
+    /// keeps the one the viewer opened on
+    pub fn restore_saved_position(&mut self, saved: ViewerPosition) {
+        self.chapter_positions = saved.chapter_positions;
+        self.global_position = saved.all_comments_position;
+        if let Some(index) = saved.chapter_href.and_then(|href| {
+            self.chapters
+                .iter()
+                .position(|chapter| chapter.href.as_deref() == Some(href.as_str()))
+        }) {
+            self.selected_chapter_index = index;
+            self.saved_chapter_index = index;
         }
+        self.global_search_mode = saved.all_comments;
+        if self.global_search_mode {
+            self.focus = ViewerFocus::Comments;
+        }
+        self.update_visible_entries();
+        self.restore_position();
     }
 
     pub fn save_position(&mut self) {
@@ -187,7 +252,7 @@ impl CommentsViewer {
             self.rendered_entries = self
                 .all_entries
                 .iter()
-                .filter(|entry| entry.chapter_href == href)
+                .filter(|entry| entry.chapter_key() == href)
                 .cloned()
                 .collect();
         } else {
@@ -319,13 +384,30 @@ impl CommentsViewer {
         epub: &mut EpubDoc<BufReader<std::fs::File>>,
         toc_items: &[TocItem],
     ) -> Vec<CommentEntry> {
-        let comments_guard = comments.lock().unwrap();
-        let all_comments = comments_guard.get_all_comments();
-
-        if all_comments.is_empty() {
+        let mut comments_guard = comments.lock().unwrap();
+        if comments_guard.get_all_comments().is_empty() {
             return Vec::new();
         }
 
+        let mut chapter_docs: HashMap<String, Option<Document>> = HashMap::new();
+        for comment in comments_guard.get_all_comments() {
+            if !chapter_docs.contains_key(&comment.chapter_href) {
+                let doc = Self::load_chapter_document(epub, &comment.chapter_href);
+                chapter_docs.insert(comment.chapter_href.clone(), doc);
+            }
+        }
+
+        for (chapter_href, doc) in &chapter_docs {
+            if let Some(doc) = doc {
+                let node_texts: Vec<String> = doc.blocks.iter().map(Node::plain_text).collect();
+                if let Err(e) = comments_guard.relocate_chapter_comments(chapter_href, &node_texts)
+                {
+                    warn!("Failed to relocate comments for {chapter_href}: {e}");
+                }
+            }
+        }
+
+        let all_comments = comments_guard.get_all_comments();
         let mut entries: Vec<CommentEntry> = Vec::new();
         let mut last_chapter_href: Option<String> = None;
         let mut current_line = 0;
@@ -333,8 +415,13 @@ impl CommentsViewer {
 
         for comment in all_comments {
             let chapter_title = Self::find_chapter_title(&comment.chapter_href, toc_items, epub);
-            let quoted_text =
-                Self::extract_quoted_text(epub, &comment.chapter_href, comment.node_index());
+            let chapter_doc = chapter_docs
+                .get(&comment.chapter_href)
+                .and_then(|doc| doc.as_ref());
+            let quoted_text = match comment.target.image_src() {
+                Some(src) => Self::figure_quote(chapter_doc, comment.node_index(), src),
+                None => Self::extract_quoted_text(chapter_doc, comment.node_index()),
+            };
 
             let entry_index = if matches!(comment.target, CommentTarget::CodeBlock { .. }) {
                 let key = (comment.chapter_href.clone(), comment.node_index());
@@ -365,6 +452,7 @@ impl CommentsViewer {
                     chapter_href: comment.chapter_href.clone(),
                     quoted_text,
                     comments: vec![comment.clone()],
+                    anchor_status: comments_guard.anchor_status(comment),
                     render_start_line,
                     render_end_line,
                 });
@@ -389,8 +477,11 @@ impl CommentsViewer {
         );
 
         let mut unmatched_counts: HashMap<String, usize> = HashMap::new();
+        let mut orphaned_count = 0;
         for entry in entries {
-            if let Some(idx) = href_to_index.get(&entry.chapter_href) {
+            if entry.anchor_status == AnchorStatus::Orphaned {
+                orphaned_count += 1;
+            } else if let Some(idx) = href_to_index.get(&entry.chapter_href) {
                 if let Some(chapter) = chapters.get_mut(*idx) {
                     chapter.comment_count += 1;
                 }
@@ -413,9 +504,18 @@ impl CommentsViewer {
             });
         }
 
+        if orphaned_count > 0 {
+            chapters.push(ChapterDisplay {
+                title: tr("comments.orphaned").to_string(),
+                href: Some(ORPHANED_CHAPTER_KEY.to_string()),
+                depth: 0,
+                comment_count: orphaned_count,
+            });
+        }
+
         if chapters.is_empty() {
             chapters.push(ChapterDisplay {
-                title: "Chapters".to_string(),
+                title: tr("comments.chapters").to_string(),
                 href: None,
                 depth: 0,
                 comment_count: 0,
@@ -508,7 +608,7 @@ impl CommentsViewer {
             .replace(['-', '_'], " ")
     }
 
-    fn find_chapter_title(
+    pub fn find_chapter_title(
         chapter_href: &str,
         toc_items: &[TocItem],
         _epub: &mut EpubDoc<BufReader<std::fs::File>>,
@@ -558,77 +658,60 @@ impl CommentsViewer {
             .to_string()
     }
 
-    fn extract_quoted_text(
+    pub fn load_chapter_document(
         epub: &mut EpubDoc<BufReader<std::fs::File>>,
         chapter_href: &str,
-        paragraph_index: usize,
-    ) -> String {
+    ) -> Option<Document> {
         use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
 
         let original_chapter = epub.get_current_chapter();
 
         let chapter_path = std::path::PathBuf::from(chapter_href);
+        let mut doc = None;
         if let Some(chapter_id) = epub.resource_uri_to_chapter(&chapter_path) {
             if epub.set_current_chapter(chapter_id) {
                 if let Some((content, _)) = epub.get_current_str() {
                     let mut converter = HtmlToMarkdownConverter::new();
-                    let doc = converter.convert(&content);
-                    if let Some(node) = doc.blocks.get(paragraph_index) {
-                        let text = Self::extract_text_from_node(node);
-
-                        let _ = epub.set_current_chapter(original_chapter);
-
-                        let max_chars = 80;
-                        if text.chars().count() > max_chars {
-                            let truncated: String = text.chars().take(max_chars).collect();
-                            return format!("{truncated}...");
-                        }
-                        return text;
-                    }
+                    doc = Some(converter.convert(&content));
                 }
             }
         }
 
         let _ = epub.set_current_chapter(original_chapter);
 
-        "[Unable to retrieve text]".to_string()
+        doc
     }
 
-    fn extract_text_from_node(node: &crate::markdown::Node) -> String {
-        use crate::markdown::Block;
+    fn extract_quoted_text(doc: Option<&Document>, paragraph_index: usize) -> String {
+        if let Some(node) = doc.and_then(|doc| doc.blocks.get(paragraph_index)) {
+            let text = node.plain_text();
 
-        match &node.block {
-            Block::Paragraph { content } | Block::Heading { content, .. } => {
-                Self::extract_text_from_text(content)
+            let max_chars = 80;
+            if text.chars().count() > max_chars {
+                let truncated: String = text.chars().take(max_chars).collect();
+                return format!("{truncated}...");
             }
-            Block::CodeBlock { content, .. } => content.clone(),
-            Block::Quote { content } => content
-                .iter()
-                .map(Self::extract_text_from_node)
-                .collect::<Vec<_>>()
-                .join(" "),
-            _ => String::new(),
+            return text;
         }
+
+        tr("comments.unavailable_text").to_string()
     }
 
-    fn extract_text_from_text(text: &crate::markdown::Text) -> String {
-        let mut result = String::new();
-        for item in text.iter() {
-            match item {
-                crate::markdown::TextOrInline::Text(txt) => result.push_str(&txt.content),
-                crate::markdown::TextOrInline::Inline(inline) => match inline {
-                    Inline::Link { text, .. } => {
-                        result.push_str(&Self::extract_text_from_text(text));
-                    }
-                    Inline::Image { alt_text, .. } => {
-                        result.push_str(alt_text);
-                    }
-                    Inline::LineBreak | Inline::SoftBreak => result.push(' '),
-                    _ => {}
-                },
-            }
+    /// "[figure]" and the alt text of the image a note is on, or its file
+    /// name when it has none
+    fn figure_quote(doc: Option<&Document>, paragraph_index: usize, src: &str) -> String {
+        let alt_text = doc
+            .and_then(|doc| doc.blocks.get(paragraph_index))
+            .and_then(|node| node.image_alt_text(src))
+            .map(str::trim)
+            .filter(|alt| !alt.is_empty());
+        match alt_text {
+            Some(alt) => tr_args("comments.figure_quote", &[("alt", &alt)]),
+            None => tr_args(
+                "comments.figure_quote",
+                &[("alt", &src.rsplit('/').next().unwrap_or(src))],
+            ),
         }
-        result
     }
 
     fn estimate_entry_height(
@@ -679,27 +762,40 @@ impl CommentsViewer {
     }
 
     fn comment_header_text(entry: &CommentEntry, idx: usize, comment: &Comment) -> String {
-        let timestamp = comment.updated_at.format("%m-%d-%y %H:%M").to_string();
+        let timestamp = i18n::format_comment_time(comment.updated_at.naive_utc());
         let mut header = match comment.target {
             CommentTarget::CodeBlock { line_range, .. } => {
                 if line_range.0 == line_range.1 {
-                    format!("Line {} // {}", line_range.0 + 1, timestamp)
+                    tr_args(
+                        "comments.line_header",
+                        &[("line", &(line_range.0 + 1)), ("time", &timestamp)],
+                    )
                 } else {
-                    format!(
-                        "Lines {}-{} // {}",
-                        line_range.0 + 1,
-                        line_range.1 + 1,
-                        timestamp
+                    tr_args(
+                        "comments.lines_header",
+                        &[
+                            ("first", &(line_range.0 + 1)),
+                            ("last", &(line_range.1 + 1)),
+                            ("time", &timestamp),
+                        ],
                     )
                 }
             }
-            CommentTarget::Paragraph { .. } => format!("Note // {timestamp}"),
+            CommentTarget::Paragraph { .. } | CommentTarget::Image { .. } => {
+                tr_args("comments.note_header", &[("time", &timestamp)])
+            }
         };
 
         if entry.comment_count() > 1 {
             header = format!("[{}] {header}", idx + 1);
         }
 
+        match entry.anchor_status {
+            AnchorStatus::Relocated => header.push_str(" (relocated)"),
+            AnchorStatus::Orphaned => header.push_str(" (orphaned)"),
+            AnchorStatus::Anchored => {}
+        }
+
         header
     }
 
@@ -730,7 +826,7 @@ impl CommentsViewer {
     }
 
     pub fn render(&mut self, f: &mut Frame, area: Rect) {
-        let popup_area = centered_rect(85, 90, area);
+        let popup_area = popup_rect(PopupKind::CommentsViewer, area);
         self.last_popup_area = Some(popup_area);
         f.render_widget(Clear, popup_area);
 
@@ -739,7 +835,7 @@ impl CommentsViewer {
             .iter()
             .map(|chapter| chapter.comment_count)
             .sum();
-        let title = format!(" All Comments ({total_comments}) ");
+        let title = tr_args("comments.all_title", &[("count", &total_comments)]);
         let outer_block = self.build_outer_block(title);
         let inner_area = outer_block.inner(popup_area);
         f.render_widget(outer_block, popup_area);
@@ -822,21 +918,23 @@ impl CommentsViewer {
             let mut background = Style::default().bg(current_theme().base_00);
 
             if self.focus == ViewerFocus::Chapters {
+                let (selection_bg, selection_fg) = current_theme().get_text_selection_colors(true);
+                let selection_fg = selection_fg.unwrap_or(current_theme().base_00);
                 style = Style::default()
-                    .fg(current_theme().base_00)
-                    .bg(current_theme().base_02)
+                    .fg(selection_fg)
+                    .bg(selection_bg)
                     .add_modifier(Modifier::BOLD);
                 count_style = Style::default()
-                    .fg(current_theme().base_00)
-                    .bg(current_theme().base_02)
+                    .fg(selection_fg)
+                    .bg(selection_bg)
                     .add_modifier(Modifier::BOLD);
-                background = Style::default().bg(current_theme().base_02);
+                background = Style::default().bg(selection_bg);
             }
 
             let line = Line::from(vec![
                 Span::styled(format!("({total})"), count_style),
                 Span::raw(" "),
-                Span::styled("Comments Search", style),
+                Span::styled(tr("comments.search"), style),
             ])
             .bg(background.bg.unwrap_or(current_theme().base_00));
 
@@ -894,14 +992,15 @@ impl CommentsViewer {
             let mut background_style = Style::default().bg(current_theme().base_00);
             if self.focus == ViewerFocus::Chapters {
                 if is_selected {
+                    let (selection_bg, selection_fg) =
+                        current_theme().get_text_selection_colors(true);
+                    let selection_fg = selection_fg.unwrap_or(current_theme().base_00);
                     title_style = Style::default()
-                        .fg(current_theme().base_00)
-                        .bg(current_theme().base_02)
+                        .fg(selection_fg)
+                        .bg(selection_bg)
                         .add_modifier(Modifier::BOLD);
-                    count_style = count_style
-                        .fg(current_theme().base_00)
-                        .bg(current_theme().base_02);
-                    background_style = Style::default().bg(current_theme().base_02);
+                    count_style = count_style.fg(selection_fg).bg(selection_bg);
+                    background_style = Style::default().bg(selection_bg);
                 }
             } else if is_selected {
                 title_style = Style::default()
@@ -1016,14 +1115,14 @@ impl CommentsViewer {
         let message = Paragraph::new(vec![
             Line::from(""),
             Line::from(Span::styled(
-                "No comments in this chapter",
+                tr("comments.empty"),
                 Style::default()
                     .fg(current_theme().base_03)
                     .add_modifier(Modifier::ITALIC),
             )),
             Line::from(""),
             Line::from(Span::styled(
-                "Select text and press 'a' to add a note",
+                tr("comments.empty_hint"),
                 Style::default().fg(current_theme().base_04),
             )),
         ])
@@ -1045,11 +1144,13 @@ impl CommentsViewer {
             .fg(current_theme().base_0c)
             .add_modifier(Modifier::BOLD);
 
+        let (selection_bg, selection_fg) = current_theme().get_text_selection_colors(false);
+
         let quote_style = if is_selected {
             Style::default()
-                .fg(current_theme().base_04)
+                .fg(selection_fg.unwrap_or(current_theme().base_04))
                 .add_modifier(Modifier::ITALIC)
-                .bg(current_theme().base_01)
+                .bg(selection_bg)
         } else {
             Style::default()
                 .fg(current_theme().base_03)
@@ -1058,8 +1159,8 @@ impl CommentsViewer {
 
         let comment_style = if is_selected {
             Style::default()
-                .fg(current_theme().base_05)
-                .bg(current_theme().base_01)
+                .fg(selection_fg.unwrap_or(current_theme().base_05))
+                .bg(selection_bg)
         } else {
             Style::default().fg(current_theme().base_05)
         };
@@ -1242,6 +1343,11 @@ impl CommentsViewer {
         false
     }
 
+    /// Whether keys go to the search field
+    pub fn is_typing(&self) -> bool {
+        self.search_state.active && self.search_state.mode == SearchMode::InputMode
+    }
+
     pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
         if let Some(popup_area) = self.last_popup_area {
             x < popup_area.x
@@ -1350,26 +1456,6 @@ impl CommentsViewer {
     }
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
-
 impl VimNavMotions for CommentsViewer {
     fn handle_h(&mut self) {
         self.move_chapter_up();
@@ -1533,122 +1619,59 @@ impl SearchablePanel for CommentsViewer {
 }
 
 impl CommentsViewer {
-    pub fn handle_key(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        key_seq: &mut KeySeq,
-    ) -> Option<CommentsViewerAction> {
-        use crossterm::event::{KeyCode, KeyModifiers};
-
-        if self.search_state.active && self.search_state.mode == SearchMode::InputMode {
-            match key.code {
-                KeyCode::Esc => {
-                    self.cancel_search();
-                    None
-                }
-                KeyCode::Enter => {
-                    self.confirm_search();
-                    None
-                }
-                KeyCode::Char(c) => {
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<CommentsViewerAction> {
+        if self.is_typing() {
+            match command {
+                PopupCommand::Cancel => self.cancel_search(),
+                PopupCommand::Submit => self.confirm_search(),
+                PopupCommand::Input(c) => {
                     let mut new_query = self.search_state.query.clone();
                     new_query.push(c);
                     self.update_search_query(&new_query);
-                    None
                 }
-                KeyCode::Backspace => {
+                PopupCommand::Backspace => {
                     let mut new_query = self.search_state.query.clone();
                     new_query.pop();
                     self.update_search_query(&new_query);
-                    None
                 }
-                _ => None,
+                _ => {}
             }
-        } else {
-            match key.code {
-                KeyCode::Char('j') => {
-                    self.handle_j();
-                    None
-                }
-                KeyCode::Char('k') => {
-                    self.handle_k();
-                    None
+            return None;
+        }
+
+        let navigating_matches =
+            self.search_state.active && self.search_state.mode == SearchMode::NavigationMode;
+        match command {
+            PopupCommand::Down => self.handle_j(),
+            PopupCommand::Up => self.handle_k(),
+            PopupCommand::SwitchView => self.toggle_focus(),
+            PopupCommand::Left => self.handle_h(),
+            PopupCommand::Right => self.handle_l(),
+            PopupCommand::Top => self.handle_gg(),
+            PopupCommand::Bottom => self.handle_upper_g(),
+            PopupCommand::HalfPageDown => self.handle_ctrl_d(),
+            PopupCommand::HalfPageUp => self.handle_ctrl_u(),
+            PopupCommand::StartSearch => self.start_search(),
+            PopupCommand::ToggleSearchScope => self.toggle_global_search_mode(),
+            PopupCommand::NextMatch if navigating_matches => self.next_match(),
+            PopupCommand::PreviousMatch if navigating_matches => self.previous_match(),
+            PopupCommand::Remove => return Some(CommentsViewerAction::DeleteSelectedComment),
+            PopupCommand::Close => {
+                if !self.search_state.active {
+                    return Some(CommentsViewerAction::Close);
                 }
-                KeyCode::Tab => {
-                    self.toggle_focus();
-                    None
-                }
-                KeyCode::Char('h') => {
-                    self.handle_h();
-                    None
-                }
-                KeyCode::Char('l') => {
-                    self.handle_l();
-                    None
-                }
-                KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => {
-                    self.handle_gg();
-                    None
-                }
-                KeyCode::Char('G') => {
-                    self.handle_upper_g();
-                    None
-                }
-                KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    self.handle_ctrl_d();
-                    None
-                }
-                KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                    self.handle_ctrl_u();
-                    None
-                }
-                KeyCode::Char('/') => {
-                    self.start_search();
-                    None
-                }
-                KeyCode::Char('?') => {
-                    self.toggle_global_search_mode();
-                    None
-                }
-                KeyCode::Char('n')
-                    if self.search_state.active
-                        && self.search_state.mode == SearchMode::NavigationMode =>
-                {
-                    self.next_match();
-                    None
-                }
-                KeyCode::Char('N')
-                    if self.search_state.active
-                        && self.search_state.mode == SearchMode::NavigationMode =>
-                {
-                    self.previous_match();
-                    None
-                }
-                KeyCode::Char('d') => {
-                    if key_seq.handle_key('d') == "dd" {
-                        key_seq.clear();
-                        Some(CommentsViewerAction::DeleteSelectedComment)
-                    } else {
-                        None
-                    }
-                }
-                KeyCode::Esc => {
-                    if self.search_state.active {
-                        self.exit_search();
-                        None
-                    } else {
-                        Some(CommentsViewerAction::Close)
-                    }
-                }
-                KeyCode::Enter => {
-                    self.selected_comment()
-                        .map(|entry| CommentsViewerAction::JumpToComment {
-                            chapter_href: entry.chapter_href.clone(),
-                            target: entry.primary_comment().target.clone(),
-                        })
-                }
-                _ => None,
+                self.exit_search();
+            }
+            PopupCommand::Select => {
+                return self
+                    .selected_comment()
+                    .map(|entry| CommentsViewerAction::JumpToComment {
+                        chapter_href: entry.chapter_href.clone(),
+                        target: entry.primary_comment().target.clone(),
+                    });
             }
+            _ => {}
         }
+        None
     }
 }
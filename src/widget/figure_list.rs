@@ -0,0 +1,262 @@
+use crate::i18n::{tr, tr_args};
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+pub enum FigureListAction {
+    /// Go to the chapter and scroll the image into view
+    Show {
+        chapter_index: usize,
+        src: String,
+    },
+    Close,
+}
+
+/// A row of the popup: an image and the chapter it is in
+pub struct FigureEntry {
+    pub chapter_index: usize,
+    pub chapter_title: String,
+    pub src: String,
+    pub alt_text: String,
+    pub caption: Option<String>,
+}
+
+impl FigureEntry {
+    /// The caption, else the alt text, else the image's file name
+    fn label(&self) -> &str {
+        self.caption
+            .as_deref()
+            .filter(|caption| !caption.trim().is_empty())
+            .or(Some(self.alt_text.as_str()).filter(|alt| !alt.is_empty()))
+            .unwrap_or_else(|| self.src.rsplit('/').next().unwrap_or(&self.src))
+    }
+}
+
+/// Popup listing every image of the book with its chapter (Space+I)
+pub struct FigureList {
+    entries: Vec<FigureEntry>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+    last_list_area: Option<Rect>,
+}
+
+impl FigureList {
+    /// Opens on the first figure of `current_chapter` or after it
+    pub fn new(entries: Vec<FigureEntry>, current_chapter: usize) -> Self {
+        let selected = entries
+            .iter()
+            .position(|entry| entry.chapter_index >= current_chapter)
+            .or(entries.len().checked_sub(1));
+        let mut state = ListState::default();
+        state.select(selected);
+
+        FigureList {
+            entries,
+            state,
+            last_popup_area: None,
+            last_list_area: None,
+        }
+    }
+
+    fn step_selection(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let i = match (self.state.selected(), forward) {
+            (Some(i), true) if i < last => i + 1,
+            (Some(_), true) | (None, _) => 0,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    fn show_action(&self) -> Option<FigureListAction> {
+        let entry = self.state.selected().and_then(|i| self.entries.get(i))?;
+        Some(FigureListAction::Show {
+            chapter_index: entry.chapter_index,
+            src: entry.src.clone(),
+        })
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<FigureListAction> {
+        match command {
+            PopupCommand::Close => Some(FigureListAction::Close),
+            PopupCommand::Select => self.show_action(),
+            PopupCommand::Down => {
+                self.step_selection(true);
+                None
+            }
+            PopupCommand::Up => {
+                self.step_selection(false);
+                None
+            }
+            PopupCommand::Top => {
+                self.state.select((!self.entries.is_empty()).then_some(0));
+                None
+            }
+            PopupCommand::Bottom => {
+                self.state.select(self.entries.len().checked_sub(1));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 60, area);
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{:>3}  ", i + 1),
+                            Style::default().fg(palette.base_0c),
+                        ),
+                        Span::styled(
+                            entry.label().to_string(),
+                            Style::default()
+                                .fg(palette.base_05)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ]),
+                    Line::from(Span::styled(
+                        format!("     {}", entry.chapter_title),
+                        Style::default().fg(palette.base_03),
+                    )),
+                ])
+            })
+            .collect();
+
+        let block = Block::default()
+            .title(tr_args(
+                "title.figure_list",
+                &[("count", &self.entries.len())],
+            ))
+            .title_bottom(Line::from(Span::styled(
+                tr("figure_list.footer"),
+                Style::default().fg(palette.base_03),
+            )))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let list_area = block.inner(popup_area);
+        self.last_list_area = Some(list_area);
+        f.render_widget(block, popup_area);
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    /// Select the clicked image; returns the jump on a click on the
+    /// selected one
+    pub fn handle_mouse_click(&mut self, x: u16, y: u16) -> Option<FigureListAction> {
+        let list_area = self.last_list_area?;
+        if x < list_area.x || x >= list_area.right() || y < list_area.y || y >= list_area.bottom() {
+            return None;
+        }
+        // Each image takes two rows
+        let index = self.state.offset() + (y - list_area.y) as usize / 2;
+        if index >= self.entries.len() {
+            return None;
+        }
+        if self.state.selected() == Some(index) {
+            return self.show_action();
+        }
+        self.state.select(Some(index));
+        None
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(chapter_index: usize, src: &str, alt_text: &str) -> FigureEntry {
+        FigureEntry {
+            chapter_index,
+            chapter_title: format!("Chapter {}", chapter_index + 1),
+            src: src.to_string(),
+            alt_text: alt_text.to_string(),
+            caption: None,
+        }
+    }
+
+    #[test]
+    fn test_opens_at_the_current_chapter_and_shows_the_selected_image() {
+        let mut popup = FigureList::new(
+            vec![
+                entry(0, "images/a.png", "Map"),
+                entry(2, "images/b.png", ""),
+                entry(5, "images/c.png", "Chart"),
+            ],
+            1,
+        );
+        assert_eq!(popup.entries[1].label(), "b.png");
+        match popup.handle_command(PopupCommand::Select) {
+            Some(FigureListAction::Show { chapter_index, src }) => {
+                assert_eq!((chapter_index, src.as_str()), (2, "images/b.png"));
+            }
+            _ => panic!("expected a jump"),
+        }
+
+        popup.handle_command(PopupCommand::Bottom);
+        popup.handle_command(PopupCommand::Down);
+        match popup.handle_command(PopupCommand::Select) {
+            Some(FigureListAction::Show { chapter_index, .. }) => assert_eq!(chapter_index, 0),
+            _ => panic!("expected a jump"),
+        }
+        assert!(matches!(
+            popup.handle_command(PopupCommand::Close),
+            Some(FigureListAction::Close)
+        ));
+    }
+}
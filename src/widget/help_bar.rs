@@ -0,0 +1,390 @@
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
+use crate::main_app::{FocusedPanel, MainPanel, PopupWindow};
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+/// Popups that can be opened from the right side of the help bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpBarAction {
+    Comments,
+    History,
+    Stats,
+    Theme,
+    Help,
+}
+
+impl HelpBarAction {
+    pub const ALL: [HelpBarAction; 5] = [
+        HelpBarAction::Comments,
+        HelpBarAction::History,
+        HelpBarAction::Stats,
+        HelpBarAction::Theme,
+        HelpBarAction::Help,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            HelpBarAction::Comments => "Space+a",
+            HelpBarAction::History => "Space+h",
+            HelpBarAction::Stats => "Space+d",
+            HelpBarAction::Theme => "Space+t",
+            HelpBarAction::Help => "?",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        tr(match self {
+            HelpBarAction::Comments => "help.action.comments",
+            HelpBarAction::History => "help.action.history",
+            HelpBarAction::Stats => "help.action.stats",
+            HelpBarAction::Theme => "help.action.theme",
+            HelpBarAction::Help => "help.action.help",
+        })
+    }
+}
+
+/// A clickable button of the help bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelpBarItem {
+    Action(HelpBarAction),
+    /// Stands in for all actions when they don't fit
+    Menu,
+}
+
+impl HelpBarItem {
+    fn label(self) -> String {
+        match self {
+            HelpBarItem::Action(action) => format!("{}: {}", action.key(), action.name()),
+            HelpBarItem::Menu => tr("help.menu").to_string(),
+        }
+    }
+}
+
+/// Keys of the focused panel or popup, shown on the left of the help bar
+pub fn panel_hint(panel: FocusedPanel) -> &'static str {
+    tr(match panel {
+        FocusedPanel::Main(MainPanel::NavigationList) => "help.panel.navigation_list",
+        FocusedPanel::Main(MainPanel::Content) => "help.panel.content",
+        FocusedPanel::Popup(PopupWindow::ReadingHistory) => "help.panel.reading_history",
+        FocusedPanel::Popup(PopupWindow::BookStats) => "help.panel.book_stats",
+        FocusedPanel::Popup(PopupWindow::ImagePopup) => "help.panel.image_popup",
+        FocusedPanel::Popup(PopupWindow::BookSearch) => "help.panel.book_search",
+        FocusedPanel::Popup(PopupWindow::Help) => "help.panel.help",
+        FocusedPanel::Popup(PopupWindow::CommentsViewer) => "help.panel.comments_viewer",
+        FocusedPanel::Popup(PopupWindow::ThemeSelector) => "help.panel.theme_selector",
+        FocusedPanel::Popup(PopupWindow::Onboarding) => "help.panel.onboarding",
+        FocusedPanel::Popup(PopupWindow::ChapterPicker) => "help.panel.chapter_picker",
+        FocusedPanel::Popup(PopupWindow::CommandPalette) => "help.panel.command_palette",
+        FocusedPanel::Popup(PopupWindow::BookSwitcher) => "help.panel.book_switcher",
+        FocusedPanel::Popup(PopupWindow::FigureList) => "help.panel.figure_list",
+        FocusedPanel::Popup(PopupWindow::InstanceLock) => "help.panel.instance_lock",
+        FocusedPanel::Popup(PopupWindow::ReadingThreads) => "help.panel.reading_threads",
+        FocusedPanel::Popup(PopupWindow::CommentStorage) => "help.panel.comment_storage",
+        FocusedPanel::Popup(PopupWindow::RareWords) => "help.panel.rare_words",
+        FocusedPanel::Popup(PopupWindow::ReadingStats) => "help.panel.reading_stats",
+        FocusedPanel::Popup(PopupWindow::ResurfacedComment) => "help.panel.resurfaced_comment",
+        FocusedPanel::Popup(PopupWindow::HelpBarMenu) => "help.panel.help_bar_menu",
+        FocusedPanel::Popup(PopupWindow::BookInfo) => "help.panel.book_info",
+    })
+}
+
+/// Columns left to the help text before the buttons collapse into the menu
+const MIN_HELP_TEXT_WIDTH: u16 = 40;
+
+/// Right-align the buttons in `area`, each drawn as `[label]` with a space
+/// between them. Returns every button with the cells it covers, brackets
+/// included; when they leave less than `MIN_HELP_TEXT_WIDTH` columns for the
+/// help text, the only button is the menu.
+pub fn layout_items(area: Rect) -> Vec<(HelpBarItem, Rect)> {
+    let full: Vec<HelpBarItem> = HelpBarAction::ALL
+        .into_iter()
+        .map(HelpBarItem::Action)
+        .collect();
+    let full_width: u16 = full.iter().map(|item| button_width(*item) + 1).sum::<u16>() - 1;
+    let items = if full_width + MIN_HELP_TEXT_WIDTH <= area.width {
+        full
+    } else {
+        vec![HelpBarItem::Menu]
+    };
+
+    let mut right = area.right();
+    let mut placed: Vec<(HelpBarItem, Rect)> = items
+        .into_iter()
+        .rev()
+        .map(|item| {
+            let width = button_width(item).min(right - area.x);
+            right -= width;
+            let rect = Rect {
+                x: right,
+                y: area.y,
+                width,
+                height: area.height.min(1),
+            };
+            right = right.saturating_sub(1).max(area.x);
+            (item, rect)
+        })
+        .collect();
+    placed.reverse();
+    placed
+}
+
+fn button_width(item: HelpBarItem) -> u16 {
+    item.label().chars().count() as u16 + 2
+}
+
+/// Draw the buttons where `layout_items` placed them
+pub fn render_items(f: &mut Frame, items: &[(HelpBarItem, Rect)]) {
+    let palette = current_theme();
+    for (item, rect) in items {
+        let button = Line::from(vec![
+            Span::raw("["),
+            Span::styled(
+                item.label(),
+                Style::default()
+                    .fg(palette.base_03)
+                    .add_modifier(Modifier::UNDERLINED),
+            ),
+            Span::raw("]"),
+        ]);
+        f.render_widget(
+            Paragraph::new(button).style(Style::default().bg(palette.base_00)),
+            *rect,
+        );
+    }
+}
+
+/// The button under the given cell
+pub fn item_at(items: &[(HelpBarItem, Rect)], x: u16, y: u16) -> Option<HelpBarItem> {
+    items
+        .iter()
+        .find(|(_, rect)| x >= rect.x && x < rect.right() && y >= rect.y && y < rect.bottom())
+        .map(|(item, _)| *item)
+}
+
+pub enum HelpBarMenuAction {
+    Run(HelpBarAction),
+    Close,
+}
+
+/// Drop-up list of the help bar actions, opened from the menu button
+pub struct HelpBarMenu {
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl Default for HelpBarMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HelpBarMenu {
+    pub fn new() -> Self {
+        let mut state = ListState::default();
+        state.select(Some(0));
+        HelpBarMenu {
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<HelpBarMenuAction> {
+        let last = HelpBarAction::ALL.len() - 1;
+        let selected = self.state.selected().unwrap_or(0);
+        match command {
+            PopupCommand::Close => Some(HelpBarMenuAction::Close),
+            PopupCommand::Select => Some(HelpBarMenuAction::Run(HelpBarAction::ALL[selected])),
+            PopupCommand::Down => {
+                self.state
+                    .select(Some(if selected < last { selected + 1 } else { 0 }));
+                None
+            }
+            PopupCommand::Up => {
+                self.state
+                    .select(Some(if selected > 0 { selected - 1 } else { last }));
+                None
+            }
+            _ => None,
+        }
+    }
+
+    /// A click on an entry runs it, a click anywhere outside closes the menu
+    pub fn handle_click(&mut self, x: u16, y: u16) -> Option<HelpBarMenuAction> {
+        let area = self.last_popup_area?;
+        if x < area.x || x >= area.right() || y < area.y || y >= area.bottom() {
+            return Some(HelpBarMenuAction::Close);
+        }
+        let row = y.checked_sub(area.y + 1)? as usize;
+        HelpBarAction::ALL
+            .get(row)
+            .map(|action| HelpBarMenuAction::Run(*action))
+    }
+
+    /// Draw the menu above `anchor`, its right edge lined up with the
+    /// anchor's; without an anchor it sits in the bottom right corner
+    pub fn render(&mut self, f: &mut Frame, area: Rect, anchor: Option<Rect>) {
+        let key_width = HelpBarAction::ALL
+            .iter()
+            .map(|action| action.key().len())
+            .max()
+            .unwrap_or(0);
+        let items: Vec<ListItem> = HelpBarAction::ALL
+            .iter()
+            .map(|action| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<key_width$}  ", action.key()),
+                        Style::default().fg(current_theme().base_0d),
+                    ),
+                    Span::styled(action.name(), Style::default().fg(current_theme().base_05)),
+                ]))
+            })
+            .collect();
+
+        let name_width = HelpBarAction::ALL
+            .iter()
+            .map(|action| action.name().chars().count())
+            .max()
+            .unwrap_or(0);
+        let width = ((key_width + name_width + 6) as u16).min(area.width);
+        let height = (HelpBarAction::ALL.len() as u16 + 2).min(area.height);
+        let anchor = anchor.unwrap_or(Rect {
+            x: area.right().saturating_sub(1),
+            y: area.bottom(),
+            width: 1,
+            height: 0,
+        });
+        let popup_area = Rect {
+            x: anchor.right().saturating_sub(width).max(area.x),
+            y: anchor.y.saturating_sub(height).max(area.y),
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let list = List::new(items).block(block).highlight_style(
+            Style::default()
+                .bg(palette.base_02)
+                .add_modifier(Modifier::BOLD),
+        );
+        f.render_stateful_widget(list, popup_area, &mut self.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar(width: u16) -> Rect {
+        Rect {
+            x: 1,
+            y: 20,
+            width,
+            height: 1,
+        }
+    }
+
+    #[test]
+    fn test_wide_bar_places_every_action() {
+        let area = bar(140);
+        let items = layout_items(area);
+
+        assert_eq!(items.len(), HelpBarAction::ALL.len());
+        let (last, help_rect) = items.last().unwrap();
+        assert_eq!(*last, HelpBarItem::Action(HelpBarAction::Help));
+        assert_eq!(help_rect.right(), area.right());
+        assert_eq!(help_rect.width, "[?: Help]".len() as u16);
+
+        // Each button starts after the previous one and a space
+        for pair in items.windows(2) {
+            assert_eq!(pair[0].1.right() + 1, pair[1].1.x);
+        }
+
+        let (_, comments_rect) = items[0];
+        assert_eq!(
+            item_at(&items, comments_rect.x + 3, 20),
+            Some(HelpBarItem::Action(HelpBarAction::Comments))
+        );
+        assert_eq!(item_at(&items, comments_rect.x - 1, 20), None);
+        assert_eq!(item_at(&items, comments_rect.x + 3, 21), None);
+    }
+
+    #[test]
+    fn test_hint_and_buttons_follow_the_locale() {
+        use crate::i18n::{Locale, with_locale};
+        use crate::test_utils::test_helpers::{capture_terminal_state, create_test_terminal};
+
+        let area = Rect {
+            x: 0,
+            y: 0,
+            width: 200,
+            height: 1,
+        };
+        let (hint, bar) = with_locale(Locale::Ru, || {
+            let mut terminal = create_test_terminal(area.width, 1);
+            terminal
+                .draw(|f| {
+                    let hint = panel_hint(FocusedPanel::Main(MainPanel::Content));
+                    f.render_widget(Paragraph::new(hint), area);
+                    render_items(f, &layout_items(area));
+                })
+                .unwrap();
+            (
+                panel_hint(FocusedPanel::Popup(PopupWindow::ReadingHistory)),
+                capture_terminal_state(&terminal),
+            )
+        });
+        assert_eq!(
+            hint,
+            "j/k/Колесо: Навигация | Enter/Двойной щелчок: Открыть | ESC: Закрыть"
+        );
+        assert_eq!(
+            bar,
+            "j/k: Прокрутка | h/l: Глава | Ctrl+d/u: Полэкрана | Tab: Переключить | \
+             Space+o: Открыть | q: Выход          [Space+a: Комментарии] [Space+h: История] \
+             [Space+d: Статистика] [Space+t: Тема] [?: Справка]"
+        );
+    }
+
+    #[test]
+    fn test_narrow_bar_collapses_into_menu() {
+        let area = bar(58);
+        let items = layout_items(area);
+
+        assert_eq!(items.len(), 1);
+        let (item, rect) = items[0];
+        assert_eq!(item, HelpBarItem::Menu);
+        assert_eq!(rect.right(), area.right());
+        assert_eq!(
+            item_at(&items, area.right() - 1, 20),
+            Some(HelpBarItem::Menu)
+        );
+    }
+
+    #[test]
+    fn test_menu_runs_selected_action() {
+        let mut menu = HelpBarMenu::new();
+        menu.handle_command(PopupCommand::Up);
+        match menu.handle_command(PopupCommand::Select) {
+            Some(HelpBarMenuAction::Run(action)) => assert_eq!(action, HelpBarAction::Help),
+            _ => panic!("expected the last action"),
+        }
+        assert!(matches!(
+            menu.handle_command(PopupCommand::Close),
+            Some(HelpBarMenuAction::Close)
+        ));
+    }
+}
@@ -1,10 +1,12 @@
-use crate::inputs::KeySeq;
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
 use crate::theme::current_theme;
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
 use codepage_437::{BorrowFromCp437, CP437_CONTROL};
 use once_cell::sync::Lazy;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::Rect,
     style::{Color, Modifier, Style},
     text::{Line, Span, Text},
     widgets::{
@@ -112,7 +114,7 @@ impl HelpPopup {
     }
 
     pub fn render(&mut self, f: &mut Frame, area: Rect) {
-        let popup_area = content_sized_rect(94, 90, area);
+        let popup_area = popup_rect(PopupKind::Help, area);
         self.last_popup_area = Some(popup_area);
 
         f.render_widget(Clear, popup_area);
@@ -128,7 +130,7 @@ impl HelpPopup {
         let paragraph = Paragraph::new(lines)
             .block(
                 Block::default()
-                    .title(" Help - Press ? or ESC to close ")
+                    .title(tr("title.help"))
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(current_theme().base_0c))
                     .style(Style::default().bg(current_theme().base_00)),
@@ -185,52 +187,23 @@ impl HelpPopup {
         self.scroll_offset = self.total_lines.saturating_sub(1);
     }
 
-    pub fn handle_key(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        key_seq: &mut KeySeq,
-    ) -> Option<HelpPopupAction> {
-        use crossterm::event::{KeyCode, KeyModifiers};
-
-        match key.code {
-            KeyCode::Char('j') => {
-                self.scroll_down();
-                None
-            }
-            KeyCode::Char('k') => {
-                self.scroll_up();
-                None
-            }
-            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => {
-                self.scroll_to_top();
-                key_seq.clear();
-                None
-            }
-            KeyCode::Char('G') => {
-                self.scroll_to_bottom();
-                None
-            }
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                let page_size = if let Some(area) = self.last_popup_area {
-                    (area.height as usize / 2).max(1)
-                } else {
-                    10
-                };
-                self.scroll_page_down(page_size);
-                None
-            }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                let page_size = if let Some(area) = self.last_popup_area {
-                    (area.height as usize / 2).max(1)
-                } else {
-                    10
-                };
-                self.scroll_page_up(page_size);
-                None
-            }
-            KeyCode::Esc | KeyCode::Char('?') => Some(HelpPopupAction::Close),
-            _ => None,
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<HelpPopupAction> {
+        let page_size = if let Some(area) = self.last_popup_area {
+            (area.height as usize / 2).max(1)
+        } else {
+            10
+        };
+        match command {
+            PopupCommand::Down => self.scroll_down(),
+            PopupCommand::Up => self.scroll_up(),
+            PopupCommand::Top => self.scroll_to_top(),
+            PopupCommand::Bottom => self.scroll_to_bottom(),
+            PopupCommand::HalfPageDown => self.scroll_page_down(page_size),
+            PopupCommand::HalfPageUp => self.scroll_page_up(page_size),
+            PopupCommand::Close => return Some(HelpPopupAction::Close),
+            _ => {}
         }
+        None
     }
 
     /// Check if the given coordinates are outside the popup area
@@ -294,31 +267,6 @@ fn preprocess_custom_ansi(input: &str) -> String {
     .into_owned()
 }
 
-fn content_sized_rect(width: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    // Calculate centering based on fixed width
-    let available_width = r.width;
-    let width = width.min(available_width);
-    let margin = (available_width.saturating_sub(width)) / 2;
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Length(margin),
-            Constraint::Length(width),
-            Constraint::Length(margin),
-        ])
-        .split(popup_layout[1])[1]
-}
-
 fn to_color(c: vt100::Color) -> Color {
     match c {
         vt100::Color::Default => Color::Reset,
@@ -0,0 +1,96 @@
+use crate::i18n::{tr, tr_args};
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+pub enum LockWarningAction {
+    ReadOnly,
+    TakeOver,
+}
+
+/// Startup popup shown when another running instance holds the lock file
+pub struct LockWarningPopup {
+    pid: u32,
+}
+
+impl LockWarningPopup {
+    pub fn new(pid: u32) -> Self {
+        LockWarningPopup { pid }
+    }
+
+    pub fn handle_command(&self, command: PopupCommand) -> Option<LockWarningAction> {
+        match command {
+            PopupCommand::Close => Some(LockWarningAction::ReadOnly),
+            PopupCommand::TakeOver => Some(LockWarningAction::TakeOver),
+            _ => None,
+        }
+    }
+
+    pub fn render(&self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 40, area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+
+        let block = Block::default()
+            .title(tr("title.instance_lock"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_08))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(4), Constraint::Length(1)])
+            .split(inner);
+
+        let text = Paragraph::new(vec![
+            Line::from(Span::styled(
+                tr_args("instance_lock.message", &[("pid", &self.pid)]),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                tr("instance_lock.explanation"),
+                Style::default().fg(palette.base_05),
+            )),
+        ])
+        .wrap(Wrap { trim: true });
+        f.render_widget(text, chunks[0]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            tr("help.panel.instance_lock"),
+            Style::default().fg(palette.base_0c),
+        )));
+        f.render_widget(hints, chunks[1]);
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
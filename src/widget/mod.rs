@@ -1,8 +1,23 @@
+pub mod bar_chart;
+pub mod book_info;
 pub mod book_search;
 pub mod book_stat;
+pub mod book_switcher;
+pub mod chapter_picker;
+pub mod command_palette;
+pub mod comment_storage;
 pub mod comments_viewer;
+pub mod figure_list;
+pub mod help_bar;
 pub mod help_popup;
+pub mod lock_warning;
 pub mod navigation_panel;
+pub mod onboarding;
+pub mod popup_geometry;
+pub mod rare_words;
 pub mod reading_history;
+pub mod reading_stats;
+pub mod reading_threads;
+pub mod resurfaced_comment;
 pub mod text_reader;
 pub mod theme_selector;
@@ -33,6 +33,11 @@ impl BookList {
         }
     }
 
+    /// Pick up renamed books; the library's order is unchanged
+    pub fn update_names(&mut self, book_manager: &BookManager) {
+        self.book_infos = book_manager.books.clone();
+    }
+
     pub fn move_selection_down(&mut self) {
         if self.selected < self.book_infos.len().saturating_sub(1) {
             self.selected += 1;
@@ -174,6 +179,7 @@ impl BookList {
 
         // Create list items
         let mut items: Vec<ListItem> = Vec::new();
+        let show_series = crate::settings::get_book_list_series();
 
         for (idx, book_info) in self.book_infos.iter().enumerate() {
             // Determine base style for this book
@@ -188,7 +194,7 @@ impl BookList {
             let is_current_search_match = self.search_state.is_current_match(idx);
 
             // Build the line with potential search highlights
-            let content = if self.search_state.active && is_search_match {
+            let mut content = if self.search_state.active && is_search_match {
                 // Find the highlight ranges for this match
                 let empty_vec = vec![];
                 let highlight_ranges = self
@@ -203,10 +209,15 @@ impl BookList {
                 let text = &book_info.display_name;
                 let mut last_end = 0;
 
-                for (start, end) in highlight_ranges {
+                // Matches in the file name appended for searching aren't shown
+                let visible_ranges = highlight_ranges
+                    .iter()
+                    .filter(|(start, _)| *start < text.len())
+                    .map(|&(start, end)| (start, end.min(text.len())));
+                for (start, end) in visible_ranges {
                     // Add non-highlighted text before this match
-                    if *start > last_end {
-                        spans.push(Span::styled(text[last_end..*start].to_string(), base_style));
+                    if start > last_end {
+                        spans.push(Span::styled(text[last_end..start].to_string(), base_style));
                     }
 
                     // Add highlighted match text
@@ -218,12 +229,9 @@ impl BookList {
                         Style::default().bg(Color::Rgb(100, 100, 0)).fg(text_color)
                     };
 
-                    spans.push(Span::styled(
-                        text[*start..*end].to_string(),
-                        highlight_style,
-                    ));
+                    spans.push(Span::styled(text[start..end].to_string(), highlight_style));
 
-                    last_end = *end;
+                    last_end = end;
                 }
 
                 // Add remaining non-highlighted text
@@ -239,6 +247,23 @@ impl BookList {
                     base_style,
                 )])
             };
+            if show_series && let Some(series) = book_info.metadata.series_label() {
+                content.spans.push(Span::styled(
+                    format!(" · {series}"),
+                    Style::default().fg(palette.base_03),
+                ));
+            }
+            // Books added since the last scan are listed first with a mark
+            if book_info.is_new {
+                content
+                    .spans
+                    .insert(0, Span::styled("✦ ", Style::default().fg(palette.base_0a)));
+            }
+            if book_info.is_pinned {
+                content
+                    .spans
+                    .insert(0, Span::styled("★ ", Style::default().fg(palette.base_09)));
+            }
 
             items.push(ListItem::new(content));
         }
@@ -252,11 +277,17 @@ impl BookList {
             Style::default().bg(selection_bg).fg(selection_fg)
         };
 
+        let new_count = self.book_infos.iter().filter(|book| book.is_new).count();
+        let title = if new_count > 0 {
+            format!("Books · {new_count} new")
+        } else {
+            "Books".to_string()
+        };
         let files = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title("Books")
+                    .title(title)
                     .border_style(Style::default().fg(border_color))
                     .style(Style::default().bg(palette.base_00)),
             )
@@ -401,10 +432,18 @@ impl SearchablePanel for BookList {
         }
     }
 
+    /// Titles, followed by the file name when it differs, so either matches
     fn get_searchable_content(&self) -> Vec<String> {
         self.book_infos
             .iter()
-            .map(|book| book.display_name.clone())
+            .map(|book| {
+                let file_name = book.file_name();
+                if file_name == book.display_name {
+                    file_name
+                } else {
+                    format!("{} {file_name}", book.display_name)
+                }
+            })
             .collect()
     }
 }
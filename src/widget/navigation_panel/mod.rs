@@ -21,8 +21,13 @@ pub enum NavigationPanelAction {
         anchor: Option<String>,
     },
     ToggleSection,
+    ToggleChapterRead {
+        href: String,
+    },
+    TogglePinned {
+        book_index: usize,
+    },
     SwitchToBookList,
-    Bypass, // when the component assumes the upper layer should handle the action
 }
 
 pub enum SelectedActionOwned {
@@ -127,6 +132,31 @@ impl NavigationPanel {
         // Keep current_book_index so we can highlight the open book
     }
 
+    /// Rebuild the book list after the library was rescanned
+    pub fn refresh_book_list(&mut self, book_manager: &BookManager) {
+        self.book_list = BookList::new(book_manager);
+        self.current_book_index = None;
+        self.mode = NavigationMode::BookSelection;
+    }
+
+    /// Show new book names without resetting the selection or mode
+    pub fn update_book_names(&mut self, book_manager: &BookManager) {
+        self.book_list.update_names(book_manager);
+    }
+
+    /// Show the books in their new order, selecting `selected` and
+    /// highlighting `current` as the open book
+    pub fn reorder_book_list(
+        &mut self,
+        book_manager: &BookManager,
+        selected: usize,
+        current: Option<usize>,
+    ) {
+        self.book_list.update_names(book_manager);
+        self.book_list.set_selection_to_index(selected);
+        self.current_book_index = current;
+    }
+
     pub fn is_in_book_mode(&self) -> bool {
         matches!(self.mode, NavigationMode::BookSelection)
     }
@@ -219,7 +249,6 @@ impl NavigationPanel {
         use crossterm::event::{KeyCode, KeyModifiers};
 
         match key.code {
-            KeyCode::Tab => Some(NavigationPanelAction::Bypass),
             KeyCode::Char('/') => {
                 self.start_search();
                 None
@@ -278,6 +307,23 @@ impl NavigationPanel {
                 }
                 None
             }
+            KeyCode::Char('m') if self.mode == NavigationMode::TableOfContents => {
+                match self.table_of_contents.get_selected_item() {
+                    Some(SelectedTocItem::TocItem(toc_item)) => {
+                        toc_item
+                            .href()
+                            .map(|href| NavigationPanelAction::ToggleChapterRead {
+                                href: href.to_string(),
+                            })
+                    }
+                    _ => None,
+                }
+            }
+            KeyCode::Char('p') if self.mode == NavigationMode::BookSelection => {
+                (!self.book_list.is_empty()).then(|| NavigationPanelAction::TogglePinned {
+                    book_index: self.get_selected_book_index(),
+                })
+            }
             KeyCode::Enter => {
                 // Handle Enter key based on current mode
                 match self.mode {
@@ -1,14 +1,16 @@
 use super::CurrentBookInfo;
 use crate::markdown_text_reader::ActiveSection;
+use crate::parsing::toc_parser::LandmarkKind;
 use crate::search::{SearchMode, SearchState, SearchablePanel, find_matches_in_text};
 use crate::theme::Base16Palette;
 use ratatui::{
     Frame,
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, List, ListItem, ListState},
 };
+use std::collections::{HashMap, HashSet};
 
 /// New ADT-based model for TOC items
 #[derive(Clone, Debug)]
@@ -85,6 +87,12 @@ pub struct TableOfContents {
     manual_navigation: bool,          // True when user is manually navigating TOC
     manual_navigation_cooldown: u8,   // Grace period counter after manual navigation
     search_state: SearchState,
+    /// Hrefs of the chapters marked read, checked off in the list
+    read_chapters: HashSet<String>,
+    /// Comment counts of the annotated chapters by href
+    annotation_counts: HashMap<String, usize>,
+    /// Landmarks of the book (start, cover, contents) by chapter href
+    landmarks: HashMap<String, LandmarkKind>,
 }
 
 impl Default for TableOfContents {
@@ -107,9 +115,24 @@ impl TableOfContents {
             manual_navigation: false,
             manual_navigation_cooldown: 0,
             search_state: SearchState::new(),
+            read_chapters: HashSet::new(),
+            annotation_counts: HashMap::new(),
+            landmarks: HashMap::new(),
         }
     }
 
+    pub fn set_read_chapters(&mut self, read_chapters: HashSet<String>) {
+        self.read_chapters = read_chapters;
+    }
+
+    pub fn set_annotation_counts(&mut self, annotation_counts: HashMap<String, usize>) {
+        self.annotation_counts = annotation_counts;
+    }
+
+    pub fn set_landmarks(&mut self, landmarks: HashMap<String, LandmarkKind>) {
+        self.landmarks = landmarks;
+    }
+
     pub fn set_current_book_info(&mut self, book_info: CurrentBookInfo) {
         self.current_book_info = Some(book_info);
     }
@@ -803,7 +826,7 @@ impl TableOfContents {
         };
         // Get focus-aware colors
         let (_text_color, border_color, _bg_color) = palette.get_panel_colors(is_focused);
-        let (selection_bg, selection_fg) = palette.get_selection_colors(is_focused);
+        let (selection_bg, _) = palette.get_selection_colors(is_focused);
 
         let mut items: Vec<ListItem> = Vec::new();
 
@@ -841,8 +864,9 @@ impl TableOfContents {
             )
             .style(Style::default().bg(palette.base_00));
 
+        // Only the background: the selected entry keeps the color of its reading state
         if is_focused {
-            toc_list = toc_list.highlight_style(Style::default().bg(selection_bg).fg(selection_fg))
+            toc_list = toc_list.highlight_style(Style::default().bg(selection_bg))
         }
 
         f.render_stateful_widget(toc_list, area, &mut self.list_state);
@@ -865,19 +889,20 @@ impl TableOfContents {
             match item {
                 TocItem::Chapter { title, .. } => {
                     // Render a simple chapter
-                    let should_highlight =
-                        self.should_highlight_item(item, &current_book.active_section);
-                    let base_color = if should_highlight {
-                        palette.base_08
-                    } else {
-                        text_color // Dimmer for other chapters
-                    };
+                    let (base_color, modifier) = self.item_look(
+                        item,
+                        &current_book.active_section,
+                        text_color, // Dimmer for other chapters
+                        *toc_item_index,
+                        palette,
+                        is_focused,
+                    );
 
                     let indent = "  ".repeat(indent_level + 1);
                     let full_text = format!("{indent}{title}");
 
                     // Check if this item matches search
-                    let chapter_content = if self.search_state.active
+                    let mut chapter_content = if self.search_state.active
                         && self.search_state.is_match(*toc_item_index)
                     {
                         self.create_highlighted_line_with_indent(
@@ -893,7 +918,23 @@ impl TableOfContents {
                             Style::default().fg(base_color),
                         )])
                     };
-                    items.push(ListItem::new(chapter_content));
+                    chapter_content.spans.extend(self.section_progress_span(
+                        item,
+                        &current_book.active_section,
+                        palette,
+                    ));
+                    chapter_content
+                        .spans
+                        .extend(self.read_mark_span(item, palette));
+                    chapter_content
+                        .spans
+                        .extend(self.annotation_mark_span(item, palette));
+                    chapter_content
+                        .spans
+                        .extend(self.landmark_mark_span(item, palette));
+                    items.push(ListItem::new(
+                        chapter_content.patch_style(Style::default().add_modifier(modifier)),
+                    ));
                 }
                 TocItem::Section {
                     title,
@@ -904,19 +945,20 @@ impl TableOfContents {
                 } => {
                     let section_icon = if *is_expanded { "⌄" } else { "›" };
 
-                    let should_highlight =
-                        self.should_highlight_item(item, &current_book.active_section);
-                    let base_color = if should_highlight {
-                        palette.base_08
-                    } else {
-                        palette.base_0d // Blue for sections
-                    };
+                    let (base_color, modifier) = self.item_look(
+                        item,
+                        &current_book.active_section,
+                        palette.base_0d, // Blue for sections
+                        *toc_item_index,
+                        palette,
+                        is_focused,
+                    );
 
                     let indent = "  ".repeat(indent_level + 1);
                     let full_text = format!("{indent}{section_icon} {title}");
 
                     // Check if this item matches search
-                    let section_content = if self.search_state.active
+                    let mut section_content = if self.search_state.active
                         && self.search_state.is_match(*toc_item_index)
                     {
                         self.create_highlighted_line_with_indent(
@@ -932,7 +974,23 @@ impl TableOfContents {
                             Style::default().fg(base_color),
                         )])
                     };
-                    items.push(ListItem::new(section_content));
+                    section_content.spans.extend(self.section_progress_span(
+                        item,
+                        &current_book.active_section,
+                        palette,
+                    ));
+                    section_content
+                        .spans
+                        .extend(self.read_mark_span(item, palette));
+                    section_content
+                        .spans
+                        .extend(self.annotation_mark_span(item, palette));
+                    section_content
+                        .spans
+                        .extend(self.landmark_mark_span(item, palette));
+                    items.push(ListItem::new(
+                        section_content.patch_style(Style::default().add_modifier(modifier)),
+                    ));
 
                     *toc_item_index += 1; // Increment for the section itself
 
@@ -957,6 +1015,100 @@ impl TableOfContents {
         }
     }
 
+    /// Percentage read of the section being read, after its entry
+    fn section_progress_span(
+        &self,
+        item: &TocItem,
+        active_section: &ActiveSection,
+        palette: &Base16Palette,
+    ) -> Option<Span<'static>> {
+        let progress = active_section.progress.as_ref()?;
+        if item.anchor().is_none() || !self.is_item_active(item, active_section) {
+            return None;
+        }
+        Some(Span::styled(
+            format!(" {}%", progress.percent),
+            Style::default().fg(palette.base_03),
+        ))
+    }
+
+    /// Check mark after the entry of a chapter marked read; entries pointing
+    /// at an anchor within a chapter go without
+    fn read_mark_span(&self, item: &TocItem, palette: &Base16Palette) -> Option<Span<'static>> {
+        let href = item.href()?;
+        if item.anchor().is_some() || !self.read_chapters.contains(&ActiveSection::base_href(href))
+        {
+            return None;
+        }
+        Some(Span::styled(" ✓", Style::default().fg(palette.base_03)))
+    }
+
+    /// Pencil with the comment count after the entry of an annotated chapter
+    fn annotation_mark_span(
+        &self,
+        item: &TocItem,
+        palette: &Base16Palette,
+    ) -> Option<Span<'static>> {
+        let href = item.href()?;
+        if item.anchor().is_some() {
+            return None;
+        }
+        let count = self
+            .annotation_counts
+            .get(&ActiveSection::base_href(href))?;
+        Some(Span::styled(
+            format!(" ✎{count}"),
+            Style::default().fg(palette.base_0a),
+        ))
+    }
+
+    /// Which landmark the entry's chapter is, e.g. `[start]` after the
+    /// chapter the text starts with (`gs` goes there)
+    fn landmark_mark_span(&self, item: &TocItem, palette: &Base16Palette) -> Option<Span<'static>> {
+        let href = item.href()?;
+        if item.anchor().is_some() {
+            return None;
+        }
+        let kind = self.landmarks.get(&ActiveSection::base_href(href))?;
+        Some(Span::styled(
+            format!(" [{}]", kind.label()),
+            Style::default().fg(palette.base_03),
+        ))
+    }
+
+    /// Foreground and emphasis of a TOC entry. The section being read and the other entries
+    /// of its chapter get their own colors; the keyboard selection only adds a background,
+    /// so it composes with either, and plain entries take the selection foreground.
+    fn item_look(
+        &self,
+        item: &TocItem,
+        active_section: &ActiveSection,
+        default_color: Color,
+        index: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) -> (Color, Modifier) {
+        let (active_color, chapter_color) = palette.get_toc_state_colors();
+        if self.should_highlight_item(item, active_section) {
+            (active_color, Modifier::BOLD)
+        } else if Self::is_in_chapter(item, active_section) {
+            (chapter_color, Modifier::empty())
+        } else if is_focused && index == self.selected_index {
+            (
+                palette.get_selection_colors(is_focused).1,
+                Modifier::empty(),
+            )
+        } else {
+            (default_color, Modifier::empty())
+        }
+    }
+
+    /// Check if a TOC item points into the chapter of the active section
+    fn is_in_chapter(item: &TocItem, active_section: &ActiveSection) -> bool {
+        item.href()
+            .is_some_and(|href| ActiveSection::base_href(href) == active_section.chapter_base_href)
+    }
+
     /// Check if this item or any of its collapsed descendants contains the active section
     /// This ensures that collapsed sections containing the active item get highlighted
     fn should_highlight_item(&self, item: &TocItem, active_section: &ActiveSection) -> bool {
@@ -1287,3 +1439,136 @@ pub enum SelectedTocItem<'a> {
     BackToBooks,
     TocItem(&'a TocItem),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::create_test_terminal;
+
+    fn chapter(title: &str, href: &str) -> TocItem {
+        TocItem::Chapter {
+            title: title.to_string(),
+            href: href.to_string(),
+            anchor: href.split_once('#').map(|(_, anchor)| anchor.to_string()),
+        }
+    }
+
+    fn toc_reading_arrival() -> TableOfContents {
+        let mut toc = TableOfContents::new();
+        toc.set_current_book_info(CurrentBookInfo {
+            path: "book.epub".to_string(),
+            toc_items: vec![
+                chapter("Prologue", "prologue.xhtml"),
+                TocItem::Section {
+                    title: "Part One".to_string(),
+                    href: None,
+                    anchor: None,
+                    children: vec![
+                        chapter("Arrival", "part1.xhtml#arrival"),
+                        chapter("Departure", "part1.xhtml#departure"),
+                    ],
+                    is_expanded: true,
+                },
+                chapter("Epilogue", "epilogue.xhtml"),
+            ],
+            current_chapter: 1,
+            current_chapter_href: Some("part1.xhtml".to_string()),
+            active_section: ActiveSection::new(
+                1,
+                "part1.xhtml".to_string(),
+                Some("arrival".to_string()),
+            ),
+        });
+        toc
+    }
+
+    /// A gray ramp, so every slot is a distinct color whatever the terminal supports
+    fn ramp_palette() -> Base16Palette {
+        let slot = |i: u8| Color::Rgb(i * 16, i * 16, i * 16);
+        Base16Palette {
+            base_00: slot(0),
+            base_01: slot(1),
+            base_02: slot(2),
+            base_03: slot(3),
+            base_04: slot(4),
+            base_05: slot(5),
+            base_06: slot(6),
+            base_07: slot(7),
+            base_08: slot(8),
+            base_09: slot(9),
+            base_0a: slot(10),
+            base_0b: slot(11),
+            base_0c: slot(12),
+            base_0d: slot(13),
+            base_0e: slot(14),
+            base_0f: slot(15),
+        }
+    }
+
+    /// Each row's text followed by the look of its first letter
+    fn styled_snapshot(toc: &mut TableOfContents) -> Vec<String> {
+        let palette = &ramp_palette();
+        let (active, chapter) = palette.get_toc_state_colors();
+        let (selection_bg, selection_fg) = palette.get_selection_colors(true);
+        let (text_color, _, _) = palette.get_panel_colors(true);
+
+        let mut terminal = create_test_terminal(24, 8);
+        terminal
+            .draw(|f| toc.render(f, f.area(), true, palette, "Book"))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+
+        (1..7)
+            .map(|y| {
+                let row: String = (1..23)
+                    .map(|x| buffer.cell((x, y)).unwrap().symbol().to_string())
+                    .collect();
+                let first = (1..23)
+                    .map(|x| buffer.cell((x, y)).unwrap())
+                    .find(|cell| cell.symbol().chars().any(char::is_alphabetic))
+                    .unwrap();
+                let fg = match first.fg {
+                    color if color == active => "active",
+                    color if color == chapter => "chapter",
+                    color if color == selection_fg => "selection",
+                    color if color == text_color => "text",
+                    color if color == palette.base_0d => "section",
+                    _ => "other",
+                };
+                let mut look = fg.to_string();
+                if first.modifier.contains(Modifier::BOLD) {
+                    look.push_str(" bold");
+                }
+                if first.bg == selection_bg {
+                    look.push_str(" selected");
+                }
+                format!("{} {look}", row.trim_end())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_selected_active_and_current_chapter_look_different() {
+        let mut toc = toc_reading_arrival();
+        toc.selected_index = 5;
+        toc.list_state.select(Some(5));
+        assert_eq!(
+            styled_snapshot(&mut toc),
+            vec![
+                "← Books List other",
+                "  Prologue text",
+                "  ⌄ Part One section",
+                "    Arrival active bold",
+                "    Departure chapter",
+                "  Epilogue selection selected",
+            ]
+        );
+
+        // The selection adds its background without hiding the reading state
+        toc.selected_index = 4;
+        toc.list_state.select(Some(4));
+        let snapshot = styled_snapshot(&mut toc);
+        assert_eq!(snapshot[4], "    Departure chapter selected");
+        assert_eq!(snapshot[5], "  Epilogue text");
+    }
+}
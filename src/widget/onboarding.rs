@@ -0,0 +1,360 @@
+use crate::book_manager::BookManager;
+use crate::i18n::{tr, tr_args};
+use crate::inputs::text_area_utils::map_keys_to_input;
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+use std::path::{MAIN_SEPARATOR, PathBuf};
+use tui_textarea::TextArea;
+
+pub enum OnboardingAction {
+    Close,
+    UseDirectory(String),
+}
+
+/// First-launch popup shown when the library is empty: asks for a books directory
+pub struct OnboardingPopup {
+    textarea: TextArea<'static>,
+    scanned_directory: String,
+    error: Option<String>,
+    completions: Vec<String>,
+    last_popup_area: Option<Rect>,
+}
+
+impl OnboardingPopup {
+    pub fn new(scanned_directory: &str) -> Self {
+        let mut textarea = TextArea::default();
+        textarea.set_placeholder_text("~/Books");
+
+        OnboardingPopup {
+            textarea,
+            scanned_directory: scanned_directory.to_string(),
+            error: None,
+            completions: Vec::new(),
+            last_popup_area: None,
+        }
+    }
+
+    fn input(&self) -> String {
+        self.textarea.lines().join("")
+    }
+
+    fn set_input(&mut self, value: &str) {
+        self.textarea = TextArea::from([value.to_string()]);
+        self.textarea.set_placeholder_text("~/Books");
+        self.textarea.move_cursor(tui_textarea::CursorMove::End);
+    }
+
+    fn submit(&mut self, input: &str) -> Option<OnboardingAction> {
+        match validate_books_directory(input) {
+            Ok(directory) => Some(OnboardingAction::UseDirectory(directory)),
+            Err(e) => {
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        if let Some(popup_area) = self.last_popup_area {
+            x < popup_area.x
+                || x >= popup_area.x + popup_area.width
+                || y < popup_area.y
+                || y >= popup_area.y + popup_area.height
+        } else {
+            true
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<OnboardingAction> {
+        match command {
+            PopupCommand::Close => Some(OnboardingAction::Close),
+            PopupCommand::Submit => {
+                let input = self.input();
+                self.submit(&input)
+            }
+            PopupCommand::Complete => {
+                let (completed, candidates) = complete_path(&self.input());
+                self.set_input(&completed);
+                self.completions = candidates;
+                self.error = None;
+                None
+            }
+            PopupCommand::UseCurrentDirectory => match std::env::current_dir() {
+                Ok(dir) => {
+                    let dir = dir.to_string_lossy().into_owned();
+                    self.set_input(&dir);
+                    self.submit(&dir)
+                }
+                Err(e) => {
+                    self.error = Some(tr_args("onboarding.no_current_dir", &[("error", &e)]));
+                    None
+                }
+            },
+            PopupCommand::EditText(key) => {
+                if let Some(input) = map_keys_to_input(key) {
+                    self.textarea.input(input);
+                    self.error = None;
+                    self.completions.clear();
+                }
+                None
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(70, 60, area);
+        self.last_popup_area = Some(popup_area);
+
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+
+        let block = Block::default()
+            .title(tr("title.onboarding"))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Min(4),
+                Constraint::Length(3),
+                Constraint::Length(2),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        let intro = Paragraph::new(vec![
+            Line::from(Span::styled(
+                tr_args(
+                    "onboarding.no_books",
+                    &[("directory", &self.scanned_directory)],
+                ),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                tr("onboarding.intro"),
+                Style::default().fg(palette.base_05),
+            )),
+        ])
+        .wrap(Wrap { trim: true });
+        f.render_widget(intro, chunks[0]);
+
+        self.textarea
+            .set_style(Style::default().fg(palette.base_05).bg(palette.base_00));
+        self.textarea
+            .set_cursor_style(Style::default().fg(palette.base_00).bg(palette.base_05));
+        self.textarea.set_cursor_line_style(Style::default());
+        self.textarea.set_block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(tr("title.books_directory"))
+                .border_style(Style::default().fg(palette.base_04)),
+        );
+        f.render_widget(&self.textarea, chunks[1]);
+
+        let status = if let Some(ref error) = self.error {
+            Line::from(Span::styled(
+                error.as_str(),
+                Style::default().fg(palette.base_08),
+            ))
+        } else if !self.completions.is_empty() {
+            Line::from(Span::styled(
+                self.completions.join("  "),
+                Style::default().fg(palette.base_03),
+            ))
+        } else {
+            Line::from("")
+        };
+        f.render_widget(Paragraph::new(status).wrap(Wrap { trim: true }), chunks[2]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            tr("onboarding.hints"),
+            Style::default().fg(palette.base_03),
+        )));
+        f.render_widget(hints, chunks[3]);
+    }
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(input: &str) -> PathBuf {
+    if input == "~" {
+        if let Some(home) = home::home_dir() {
+            return home;
+        }
+    } else if let Some(rest) = input
+        .strip_prefix("~/")
+        .or_else(|| input.strip_prefix(&format!("~{MAIN_SEPARATOR}")))
+    {
+        if let Some(home) = home::home_dir() {
+            return home.join(rest);
+        }
+    }
+    PathBuf::from(input)
+}
+
+/// Check that `input` names an existing directory with at least one EPUB.
+/// Returns the expanded directory path.
+pub fn validate_books_directory(input: &str) -> Result<String, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err(tr("onboarding.empty_path").to_string());
+    }
+
+    let path = expand_tilde(input);
+    if !path.is_dir() {
+        return Err(tr_args(
+            "onboarding.not_a_directory",
+            &[("path", &path.display())],
+        ));
+    }
+
+    let directory = path.to_string_lossy().into_owned();
+    if BookManager::count_epubs_in_dir(&directory) == 0 {
+        return Err(tr_args("onboarding.no_epubs", &[("directory", &directory)]));
+    }
+    Ok(directory)
+}
+
+/// Complete the last path component of `input` against the subdirectories on
+/// disk. Returns the (possibly extended) input and the matching names.
+pub fn complete_path(input: &str) -> (String, Vec<String>) {
+    let split_at = input.rfind(['/', MAIN_SEPARATOR]).map_or(0, |idx| idx + 1);
+    let (dir_part, prefix) = input.split_at(split_at);
+
+    let search_dir = if dir_part.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_tilde(dir_part)
+    };
+
+    let mut candidates: Vec<String> = match std::fs::read_dir(&search_dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().to_str().map(str::to_string))
+            .filter(|name| name.starts_with(prefix))
+            .filter(|name| prefix.starts_with('.') || !name.starts_with('.'))
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    candidates.sort();
+
+    match candidates.as_slice() {
+        [] => (input.to_string(), Vec::new()),
+        [only] => (format!("{dir_part}{only}/"), Vec::new()),
+        _ => {
+            let common = longest_common_prefix(&candidates);
+            (format!("{dir_part}{common}"), candidates)
+        }
+    }
+}
+
+fn longest_common_prefix(names: &[String]) -> String {
+    let Some(first) = names.first() else {
+        return String::new();
+    };
+
+    let mut prefix_len = first.len();
+    for name in &names[1..] {
+        prefix_len = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((idx, c), _)| idx + c.len_utf8())
+            .min(prefix_len);
+    }
+    first[..prefix_len].to_string()
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn path_str(path: &Path) -> String {
+        path.to_string_lossy().into_owned()
+    }
+
+    #[test]
+    fn test_expand_tilde_uses_home_directory() {
+        let home = home::home_dir().unwrap();
+        assert_eq!(expand_tilde("~"), home);
+        assert_eq!(expand_tilde("~/Books"), home.join("Books"));
+        assert_eq!(expand_tilde("/tmp/~/x"), PathBuf::from("/tmp/~/x"));
+    }
+
+    #[test]
+    fn test_validate_requires_directory_with_epub() {
+        let temp_dir = TempDir::new().unwrap();
+        let dir = path_str(temp_dir.path());
+
+        assert!(validate_books_directory("").is_err());
+        assert!(validate_books_directory(&format!("{dir}/missing")).is_err());
+
+        std::fs::write(temp_dir.path().join("notes.txt"), "").unwrap();
+        let err = validate_books_directory(&dir).unwrap_err();
+        assert!(err.contains("No EPUB files"));
+
+        std::fs::write(temp_dir.path().join("book.epub"), "").unwrap();
+        assert_eq!(validate_books_directory(&dir), Ok(dir));
+    }
+
+    #[test]
+    fn test_complete_path_extends_unique_and_common_prefixes() {
+        let temp_dir = TempDir::new().unwrap();
+        let base = path_str(temp_dir.path());
+        for name in ["books", "bookmarks", "comics", ".hidden"] {
+            std::fs::create_dir(temp_dir.path().join(name)).unwrap();
+        }
+        std::fs::write(temp_dir.path().join("cover.jpg"), "").unwrap();
+
+        let (completed, candidates) = complete_path(&format!("{base}/c"));
+        assert_eq!(completed, format!("{base}/comics/"));
+        assert!(candidates.is_empty());
+
+        let (completed, candidates) = complete_path(&format!("{base}/b"));
+        assert_eq!(completed, format!("{base}/book"));
+        assert_eq!(candidates, vec!["bookmarks", "books"]);
+
+        let (completed, candidates) = complete_path(&format!("{base}/x"));
+        assert_eq!(completed, format!("{base}/x"));
+        assert!(candidates.is_empty());
+    }
+}
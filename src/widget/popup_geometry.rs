@@ -0,0 +1,219 @@
+//! Where resizable popups sit and how big they are.
+//!
+//! Each popup has a preferred size in percent of the screen and a minimum
+//! in cells. Ctrl+arrows (or `+`/`-`) while it is open change its size in
+//! steps of `RESIZE_STEP` percent, and the size is kept per popup under
+//! `popup_sizes` in the settings file. Popups remember the rect they were
+//! last drawn in and hit-test clicks against that same rect.
+
+use crate::settings::{self, PopupSize};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+
+/// Percent of the screen one resize key adds or takes away
+pub const RESIZE_STEP: u16 = 5;
+/// Smallest share of the screen a popup can be shrunk to
+const MIN_PERCENT: u16 = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PopupKind {
+    ReadingHistory,
+    CommentsViewer,
+    Help,
+    BookSearch,
+    ThemeSelector,
+    /// The largest the image popup gets; the image's shape decides the rest
+    Image,
+}
+
+impl PopupKind {
+    /// Name of the popup under `popup_sizes` in the settings file
+    pub fn key(self) -> &'static str {
+        match self {
+            PopupKind::ReadingHistory => "reading_history",
+            PopupKind::CommentsViewer => "comments_viewer",
+            PopupKind::Help => "help",
+            PopupKind::BookSearch => "book_search",
+            PopupKind::ThemeSelector => "theme_selector",
+            PopupKind::Image => "image",
+        }
+    }
+
+    /// Size used until one is picked
+    fn preferred(self) -> PopupSize {
+        let (width, height) = match self {
+            PopupKind::ReadingHistory => (60, 80),
+            PopupKind::CommentsViewer => (85, 90),
+            PopupKind::Help => (100, 90),
+            PopupKind::BookSearch => (90, 80),
+            PopupKind::ThemeSelector => (40, 50),
+            PopupKind::Image => (100, 100),
+        };
+        PopupSize { width, height }
+    }
+
+    /// Smallest width and height in cells, as far as the screen allows
+    fn minimum(self) -> (u16, u16) {
+        match self {
+            PopupKind::ReadingHistory => (40, 10),
+            PopupKind::CommentsViewer => (60, 15),
+            PopupKind::Help => (40, 10),
+            PopupKind::BookSearch => (80, 20),
+            PopupKind::ThemeSelector => (30, 10),
+            PopupKind::Image => (20, 8),
+        }
+    }
+
+    /// Widest the popup gets, for content laid out at a fixed width
+    fn max_width(self) -> Option<u16> {
+        match self {
+            PopupKind::Help => Some(94),
+            _ => None,
+        }
+    }
+}
+
+/// The popup's size: the one picked last, or its preferred one
+pub fn popup_size(kind: PopupKind) -> PopupSize {
+    settings::get_popup_size(kind.key()).unwrap_or_else(|| kind.preferred())
+}
+
+/// The rect the popup takes in `area`, centered
+pub fn popup_rect(kind: PopupKind, area: Rect) -> Rect {
+    sized_rect(kind, popup_size(kind), area)
+}
+
+fn sized_rect(kind: PopupKind, size: PopupSize, area: Rect) -> Rect {
+    let mut rect = centered_rect(size.width, size.height, area);
+    let (min_width, min_height) = kind.minimum();
+    let max_width = kind.max_width().unwrap_or(u16::MAX);
+
+    if rect.width < min_width || rect.width > max_width {
+        rect.width = rect.width.clamp(min_width, max_width).min(area.width);
+        rect.x = area.x + (area.width - rect.width) / 2;
+    }
+    if rect.height < min_height {
+        rect.height = min_height.min(area.height);
+        rect.y = area.y + (area.height - rect.height) / 2;
+    }
+    rect
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+/// Steps of width and height a key asks for: Ctrl+Left/Right narrow and
+/// widen, Ctrl+Up/Down shrink and grow, `+`/`-` do both unless the popup
+/// is taking typed text
+pub fn resize_steps(key: &KeyEvent, typing: bool) -> Option<(i16, i16)> {
+    if key.modifiers.contains(KeyModifiers::CONTROL) {
+        return match key.code {
+            KeyCode::Left => Some((-1, 0)),
+            KeyCode::Right => Some((1, 0)),
+            KeyCode::Up => Some((0, -1)),
+            KeyCode::Down => Some((0, 1)),
+            _ => None,
+        };
+    }
+    match key.code {
+        KeyCode::Char('+') | KeyCode::Char('=') if !typing => Some((1, 1)),
+        KeyCode::Char('-') if !typing => Some((-1, -1)),
+        _ => None,
+    }
+}
+
+/// Resize the popup by `steps` and remember the new size
+pub fn resize(kind: PopupKind, steps: (i16, i16)) -> PopupSize {
+    let size = resized(popup_size(kind), steps);
+    settings::set_popup_size(kind.key(), size);
+    size
+}
+
+fn resized(size: PopupSize, (width_steps, height_steps): (i16, i16)) -> PopupSize {
+    let step = |percent: u16, steps: i16| {
+        (percent as i16 + steps * RESIZE_STEP as i16).clamp(MIN_PERCENT as i16, 100) as u16
+    };
+    PopupSize {
+        width: step(size.width, width_steps),
+        height: step(size.height, height_steps),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rect_keeps_to_minimum_and_screen() {
+        let screen = Rect::new(0, 0, 200, 50);
+        let size = PopupSize {
+            width: 50,
+            height: 50,
+        };
+        assert_eq!(
+            sized_rect(PopupKind::CommentsViewer, size, screen),
+            Rect::new(50, 13, 100, 25)
+        );
+        // Help never gets wider than its text
+        assert_eq!(sized_rect(PopupKind::Help, size, screen).width, 94);
+
+        // The minimum gives way to a small screen, centered all the same
+        let small = Rect::new(0, 0, 70, 12);
+        assert_eq!(
+            sized_rect(PopupKind::BookSearch, size, small),
+            Rect::new(0, 0, 70, 12)
+        );
+        assert_eq!(
+            sized_rect(PopupKind::ThemeSelector, size, small),
+            Rect::new(18, 1, 35, 10)
+        );
+    }
+
+    #[test]
+    fn test_resize_keys_and_limits() {
+        let ctrl = |code| KeyEvent::new(code, KeyModifiers::CONTROL);
+        let plain = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        assert_eq!(resize_steps(&ctrl(KeyCode::Right), true), Some((1, 0)));
+        assert_eq!(resize_steps(&ctrl(KeyCode::Up), false), Some((0, -1)));
+        assert_eq!(resize_steps(&plain('+'), false), Some((1, 1)));
+        // Typed into a search field instead
+        assert_eq!(resize_steps(&plain('-'), true), None);
+        assert_eq!(resize_steps(&plain('j'), false), None);
+
+        let size = PopupSize {
+            width: 85,
+            height: 25,
+        };
+        assert_eq!(
+            resized(size, (1, -1)),
+            PopupSize {
+                width: 90,
+                height: 20
+            }
+        );
+        assert_eq!(
+            resized(size, (4, -2)),
+            PopupSize {
+                width: 100,
+                height: 20
+            }
+        );
+    }
+}
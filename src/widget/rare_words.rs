@@ -0,0 +1,165 @@
+use crate::glossing::RareWord;
+use crate::i18n::{tr, tr_args};
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState},
+};
+
+pub enum RareWordsAction {
+    /// Scroll the reader to the first occurrence
+    Show {
+        block: usize,
+    },
+    /// Never gloss the word again
+    MarkKnown {
+        word: String,
+    },
+    Close,
+}
+
+/// Popup listing the rare words glossed in the current chapter
+pub struct RareWords {
+    words: Vec<RareWord>,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl RareWords {
+    pub fn new(words: Vec<RareWord>) -> Self {
+        let mut state = ListState::default();
+        state.select(if words.is_empty() { None } else { Some(0) });
+        RareWords {
+            words,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    fn step_selection(&mut self, forward: bool) {
+        if self.words.is_empty() {
+            return;
+        }
+        let last = self.words.len() - 1;
+        let i = match (self.state.selected(), forward) {
+            (Some(i), true) if i < last => i + 1,
+            (Some(_), true) | (None, _) => 0,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<RareWordsAction> {
+        match command {
+            PopupCommand::Close => Some(RareWordsAction::Close),
+            PopupCommand::Select => {
+                let word = self.words.get(self.state.selected()?)?;
+                Some(RareWordsAction::Show { block: word.block })
+            }
+            PopupCommand::Down => {
+                self.step_selection(true);
+                None
+            }
+            PopupCommand::Up => {
+                self.step_selection(false);
+                None
+            }
+            PopupCommand::Remove => {
+                let selected = self.state.selected()?;
+                let word = self.words.remove(selected).word;
+                if selected >= self.words.len() {
+                    self.state.select(self.words.len().checked_sub(1));
+                }
+                Some(RareWordsAction::MarkKnown { word })
+            }
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 60, area);
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let items: Vec<ListItem> = self
+            .words
+            .iter()
+            .map(|rare| {
+                let rank = match rare.rank {
+                    Some(rank) => tr_args("rare_words.rank", &[("rank", &rank)]),
+                    None => tr("rare_words.unranked").to_string(),
+                };
+                ListItem::new(vec![
+                    Line::from(vec![
+                        Span::styled(
+                            rare.word.clone(),
+                            Style::default()
+                                .fg(palette.base_05)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                        Span::styled(rank, Style::default().fg(palette.base_0c)),
+                    ]),
+                    Line::from(Span::styled(
+                        format!("  {}", rare.context),
+                        Style::default().fg(palette.base_03),
+                    )),
+                ])
+            })
+            .collect();
+
+        let title = tr_args("title.rare_words", &[("count", &self.words.len())]);
+        let block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(Span::styled(
+                tr("rare_words.footer"),
+                Style::default().fg(palette.base_03),
+            )))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, popup_area, &mut self.state);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
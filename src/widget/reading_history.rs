@@ -1,27 +1,83 @@
 use crate::bookmarks::Bookmarks;
-use crate::inputs::KeySeq;
+use crate::i18n::{self, tr, tr_args};
 use crate::main_app::VimNavMotions;
+use crate::main_app::commands::{PopupCommand, PopupMode};
 use crate::theme::current_theme;
-use chrono::{DateTime, Local, TimeZone};
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
+use chrono::{DateTime, Local, NaiveDate, TimeZone};
 use log::debug;
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState},
 };
 use std::collections::HashMap;
+use std::path::Path;
 
 pub enum ReadingHistoryAction {
-    OpenBook { path: String },
+    OpenBook {
+        path: String,
+    },
+    /// Delete the bookmarks of these books; the popup already dropped them
+    RemoveBooks {
+        paths: Vec<String>,
+    },
     Close,
 }
 
+/// Day buckets the history is grouped under, newest first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HistoryGroup {
+    Today,
+    Yesterday,
+    ThisWeek,
+    Earlier,
+}
+
+impl HistoryGroup {
+    fn for_date(date: NaiveDate, today: NaiveDate) -> Self {
+        match (today - date).num_days() {
+            days if days <= 0 => HistoryGroup::Today,
+            1 => HistoryGroup::Yesterday,
+            2..=6 => HistoryGroup::ThisWeek,
+            _ => HistoryGroup::Earlier,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        tr(match self {
+            HistoryGroup::Today => "history.today",
+            HistoryGroup::Yesterday => "history.yesterday",
+            HistoryGroup::ThisWeek => "history.this_week",
+            HistoryGroup::Earlier => "history.earlier",
+        })
+    }
+}
+
+enum HistoryRow {
+    Header(HistoryGroup),
+    Entry(usize),
+}
+
+/// Removal waiting for a y/n answer
+enum PendingRemoval {
+    Entry(usize),
+    AllMissing,
+}
+
+const PROGRESS_BAR_WIDTH: usize = 10;
+
 pub struct ReadingHistory {
     items: Vec<HistoryItem>,
+    rows: Vec<HistoryRow>,
     state: ListState,
     last_popup_area: Option<Rect>,
+    streak: Option<u32>,
+    today: NaiveDate,
+    missing_only: bool,
+    pending_removal: Option<PendingRemoval>,
 }
 
 #[derive(Clone)]
@@ -31,6 +87,11 @@ struct HistoryItem {
     path: String,
     chapter: usize,
     total_chapters: usize,
+    /// Chapters marked read, when progress is counted that way
+    chapters_read: Option<usize>,
+    missing: bool,
+    /// Reading threads besides the primary bookmark
+    threads: usize,
 }
 
 impl ReadingHistory {
@@ -49,8 +110,9 @@ impl ReadingHistory {
 
             let local_time = Local.from_utc_datetime(&bookmark_entry.last_read.naive_utc());
 
-            // Get chapter info from bookmark
-            let chapter = bookmark_entry.chapter_index.unwrap_or(0);
+            // Progress goes as far as the book has been read, not to where
+            // the reader is now
+            let chapter = bookmark_entry.furthest().map_or(0, |(chapter, _)| chapter);
             let total_chapters = bookmark_entry.total_chapters.unwrap_or(0);
 
             latest_access
@@ -63,6 +125,8 @@ impl ReadingHistory {
                 .or_insert((local_time, title, chapter, total_chapters));
         }
 
+        let by_chapters_read = crate::settings::get_progress_by_chapters_read();
+
         // Convert to sorted list
         let mut items: Vec<HistoryItem> = latest_access
             .into_iter()
@@ -70,6 +134,11 @@ impl ReadingHistory {
                 |(path, (date, title, chapter, total_chapters))| HistoryItem {
                     date,
                     title,
+                    missing: !Path::new(&path).exists(),
+                    threads: bookmarks.threads(&path).len(),
+                    chapters_read: by_chapters_read
+                        .then(|| bookmarks.chapters_read(&path))
+                        .flatten(),
                     path,
                     chapter,
                     total_chapters,
@@ -79,58 +148,202 @@ impl ReadingHistory {
 
         items.sort_by(|a, b| b.date.cmp(&a.date));
 
-        let mut state = ListState::default();
-        if !items.is_empty() {
-            state.select(Some(0));
-        }
-
-        ReadingHistory {
+        let mut history = ReadingHistory {
             items,
-            state,
+            rows: Vec::new(),
+            state: ListState::default(),
             last_popup_area: None,
+            streak: None,
+            today: Local::now().date_naive(),
+            missing_only: false,
+            pending_removal: None,
+        };
+        history.rebuild_rows();
+        history
+    }
+
+    /// Group the entries by their distance from `today` rather than the
+    /// current date
+    pub fn with_today(mut self, today: NaiveDate) -> Self {
+        self.today = today;
+        self.rebuild_rows();
+        self
+    }
+
+    /// Show the daily reading goal streak in the popup title
+    pub fn with_streak(mut self, streak: Option<u32>) -> Self {
+        self.streak = streak;
+        self
+    }
+
+    /// Show books by their metadata titles where known instead of file names
+    pub fn with_titles(mut self, title_of: impl Fn(&str) -> Option<String>) -> Self {
+        for item in &mut self.items {
+            if let Some(title) = title_of(&item.path) {
+                item.title = title;
+            }
+        }
+        self
+    }
+
+    /// Group the (filtered) items under day headers and keep the selection on an entry
+    fn rebuild_rows(&mut self) {
+        let selected_path = self.selected_path().map(str::to_string);
+
+        self.rows.clear();
+        let mut current_group = None;
+        for (index, item) in self.items.iter().enumerate() {
+            if self.missing_only && !item.missing {
+                continue;
+            }
+            let group = HistoryGroup::for_date(item.date.date_naive(), self.today);
+            if current_group != Some(group) {
+                self.rows.push(HistoryRow::Header(group));
+                current_group = Some(group);
+            }
+            self.rows.push(HistoryRow::Entry(index));
+        }
+
+        let selected_row = selected_path
+            .and_then(|path| {
+                self.rows.iter().position(
+                    |row| matches!(row, HistoryRow::Entry(i) if self.items[*i].path == path),
+                )
+            })
+            .or_else(|| self.first_entry_row());
+        self.state.select(selected_row);
+    }
+
+    fn first_entry_row(&self) -> Option<usize> {
+        self.rows
+            .iter()
+            .position(|row| matches!(row, HistoryRow::Entry(_)))
+    }
+
+    fn selected_item(&self) -> Option<usize> {
+        match self.state.selected().and_then(|row| self.rows.get(row)) {
+            Some(HistoryRow::Entry(index)) => Some(*index),
+            _ => None,
         }
     }
 
     pub fn render(&mut self, f: &mut Frame, area: Rect) {
         // Create centered popup area first
-        let popup_area = centered_rect(60, 80, area);
+        let popup_area = popup_rect(PopupKind::ReadingHistory, area);
         self.last_popup_area = Some(popup_area);
 
         // Clear the background for the popup area
         f.render_widget(Clear, popup_area);
 
-        // Create list items with formatted dates
+        let palette = current_theme();
         let items: Vec<ListItem> = self
-            .items
+            .rows
             .iter()
-            .map(|item| {
-                let date_str = item.date.format("%Y-%m-%d").to_string();
-                let progress_str = if item.total_chapters > 0 {
-                    format!(" [ {} / {} ]", item.chapter + 1, item.total_chapters)
-                } else {
-                    String::new()
-                };
-
-                ListItem::new(Line::from(vec![
-                    Span::styled(date_str, Style::default().fg(current_theme().base_03)),
-                    Span::raw(" : "),
-                    Span::styled(&item.title, Style::default().fg(current_theme().base_05)),
-                    Span::styled(progress_str, Style::default().fg(current_theme().base_03)),
-                ]))
+            .map(|row| match row {
+                HistoryRow::Header(group) => ListItem::new(Line::from(Span::styled(
+                    group.label(),
+                    Style::default()
+                        .fg(palette.base_0d)
+                        .add_modifier(Modifier::BOLD),
+                ))),
+                HistoryRow::Entry(index) => {
+                    let item = &self.items[*index];
+                    let (title_style, detail_style) = if item.missing {
+                        (
+                            Style::default()
+                                .fg(palette.base_03)
+                                .add_modifier(Modifier::DIM),
+                            Style::default()
+                                .fg(palette.base_03)
+                                .add_modifier(Modifier::DIM),
+                        )
+                    } else {
+                        (
+                            Style::default().fg(palette.base_05),
+                            Style::default().fg(palette.base_03),
+                        )
+                    };
+
+                    let mut spans = vec![
+                        Span::styled(i18n::format_date(item.date.date_naive()), detail_style),
+                        Span::raw(" : "),
+                        Span::styled(item.title.as_str(), title_style),
+                    ];
+                    let percent = match item.chapters_read {
+                        Some(read) => read_percent(read, item.total_chapters),
+                        None => progress_percent(item.chapter, item.total_chapters),
+                    };
+                    if let Some(percent) = percent {
+                        spans.push(Span::styled(
+                            format!(
+                                " {} {percent:>3}%",
+                                progress_bar(percent, PROGRESS_BAR_WIDTH)
+                            ),
+                            detail_style,
+                        ));
+                    }
+                    if item.threads > 0 {
+                        spans.push(Span::styled(
+                            tr_args("history.threads", &[("count", &(item.threads + 1))]),
+                            Style::default().fg(palette.base_0e),
+                        ));
+                    }
+                    if item.missing {
+                        spans.push(Span::styled(
+                            tr("history.missing"),
+                            Style::default().fg(palette.base_08),
+                        ));
+                    }
+                    ListItem::new(Line::from(spans))
+                }
             })
             .collect();
 
+        let mut title = match self.streak {
+            Some(streak) => tr_args("history.title_streak", &[("streak", &streak)]),
+            None => tr("history.title").to_string(),
+        };
+        if self.missing_only {
+            title.push_str(tr("history.missing_only"));
+        }
+
+        let footer = match self.pending_removal {
+            Some(PendingRemoval::Entry(index)) => Line::from(Span::styled(
+                tr_args(
+                    "history.remove_entry",
+                    &[("title", &self.items[index].title)],
+                ),
+                Style::default()
+                    .fg(palette.base_08)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            Some(PendingRemoval::AllMissing) => Line::from(Span::styled(
+                tr_args(
+                    "history.remove_missing",
+                    &[("count", &self.missing_count())],
+                ),
+                Style::default()
+                    .fg(palette.base_08)
+                    .add_modifier(Modifier::BOLD),
+            )),
+            None => Line::from(Span::styled(
+                tr("history.footer"),
+                Style::default().fg(palette.base_03),
+            )),
+        };
+
         let list = List::new(items)
             .block(
                 Block::default()
-                    .title(" Reading History ")
+                    .title(title)
+                    .title_bottom(footer)
                     .borders(Borders::ALL)
-                    .border_style(Style::default().fg(current_theme().base_0c))
-                    .style(Style::default().bg(current_theme().base_00)), // Use theme background
+                    .border_style(Style::default().fg(palette.base_0c))
+                    .style(Style::default().bg(palette.base_00)), // Use theme background
             )
             .highlight_style(
                 Style::default()
-                    .bg(current_theme().base_02)
+                    .bg(palette.base_02)
                     .add_modifier(Modifier::BOLD),
             )
             .highlight_symbol("» ");
@@ -138,41 +351,97 @@ impl ReadingHistory {
         f.render_stateful_widget(list, popup_area, &mut self.state);
     }
 
-    pub fn next(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i >= self.items.len() - 1 {
-                    0
-                } else {
-                    i + 1
-                }
-            }
-            None => 0,
+    /// Move the selection by one entry, skipping group headers and wrapping around
+    fn step_selection(&mut self, forward: bool) {
+        let entries: Vec<usize> = self
+            .rows
+            .iter()
+            .enumerate()
+            .filter(|(_, row)| matches!(row, HistoryRow::Entry(_)))
+            .map(|(row, _)| row)
+            .collect();
+        if entries.is_empty() {
+            return;
+        }
+
+        let current = self
+            .state
+            .selected()
+            .and_then(|row| entries.iter().position(|&entry| entry == row));
+        let next = match (current, forward) {
+            (Some(i), true) => (i + 1) % entries.len(),
+            (Some(0), false) => entries.len() - 1,
+            (Some(i), false) => i - 1,
+            (None, _) => 0,
         };
-        self.state.select(Some(i));
+        self.state.select(Some(entries[next]));
+    }
+
+    pub fn next(&mut self) {
+        self.step_selection(true);
     }
 
     pub fn previous(&mut self) {
-        let i = match self.state.selected() {
-            Some(i) => {
-                if i == 0 {
-                    self.items.len().saturating_sub(1)
-                } else {
-                    i - 1
-                }
-            }
-            None => 0,
-        };
-        self.state.select(Some(i));
+        self.step_selection(false);
     }
 
     pub fn selected_path(&self) -> Option<&str> {
-        self.state
-            .selected()
+        self.selected_item()
             .and_then(|i| self.items.get(i))
             .map(|item| item.path.as_str())
     }
 
+    fn missing_count(&self) -> usize {
+        self.items.iter().filter(|item| item.missing).count()
+    }
+
+    /// Drop the given items from the popup and return their paths
+    fn remove_items(&mut self, remove: impl Fn(&HistoryItem) -> bool) -> Vec<String> {
+        let (removed, kept): (Vec<HistoryItem>, Vec<HistoryItem>) = std::mem::take(&mut self.items)
+            .into_iter()
+            .partition(remove);
+        self.items = kept;
+
+        // Keep the selection near where it was instead of jumping to the top
+        let selected_row = self.state.selected();
+        self.rebuild_rows();
+        if let Some(row) = selected_row {
+            let entries_from = self
+                .rows
+                .iter()
+                .enumerate()
+                .skip(row.min(self.rows.len().saturating_sub(1)))
+                .find(|(_, row)| matches!(row, HistoryRow::Entry(_)))
+                .map(|(row, _)| row);
+            if let Some(row) = entries_from.or_else(|| self.last_entry_row()) {
+                self.state.select(Some(row));
+            }
+        }
+
+        removed.into_iter().map(|item| item.path).collect()
+    }
+
+    fn last_entry_row(&self) -> Option<usize> {
+        self.rows
+            .iter()
+            .rposition(|row| matches!(row, HistoryRow::Entry(_)))
+    }
+
+    fn confirm_removal(&mut self) -> Option<ReadingHistoryAction> {
+        let paths = match self.pending_removal.take()? {
+            PendingRemoval::Entry(index) => {
+                let path = self.items.get(index)?.path.clone();
+                self.remove_items(|item| item.path == path)
+            }
+            PendingRemoval::AllMissing => self.remove_items(|item| item.missing),
+        };
+        if paths.is_empty() {
+            None
+        } else {
+            Some(ReadingHistoryAction::RemoveBooks { paths })
+        }
+    }
+
     /// Handle mouse click at the given position
     /// Returns true if an item was clicked (for double-click detection)
     pub fn handle_mouse_click(&mut self, x: u16, y: u16) -> bool {
@@ -201,14 +470,14 @@ impl ReadingHistory {
                 let new_index = offset + relative_y as usize;
 
                 debug!(
-                    "ReadingHistory: relative_y={}, offset={}, new_index={}, items_len={}",
+                    "ReadingHistory: relative_y={}, offset={}, new_index={}, rows_len={}",
                     relative_y,
                     offset,
                     new_index,
-                    self.items.len()
+                    self.rows.len()
                 );
 
-                if new_index < self.items.len() {
+                if matches!(self.rows.get(new_index), Some(HistoryRow::Entry(_))) {
                     self.state.select(Some(new_index));
                     debug!("ReadingHistory: Selected item at index {new_index}");
                     return true;
@@ -235,24 +504,25 @@ impl ReadingHistory {
     }
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
+/// Percentage of the book reached, counting the current chapter as read
+fn progress_percent(chapter: usize, total_chapters: usize) -> Option<usize> {
+    if total_chapters == 0 {
+        return None;
+    }
+    Some(((chapter + 1).min(total_chapters) * 100) / total_chapters)
+}
+
+/// Percentage of the book's chapters marked read
+fn read_percent(read: usize, total_chapters: usize) -> Option<usize> {
+    if total_chapters == 0 {
+        return None;
+    }
+    Some((read.min(total_chapters) * 100) / total_chapters)
+}
+
+fn progress_bar(percent: usize, width: usize) -> String {
+    let filled = (percent.min(100) * width + 50) / 100;
+    format!("{}{}", "█".repeat(filled), "░".repeat(width - filled))
 }
 
 impl VimNavMotions for ReadingHistory {
@@ -280,8 +550,7 @@ impl VimNavMotions for ReadingHistory {
         // Page down - move selection down by half page
         let page_size = 10; // Approximate half-page
         for _ in 0..page_size {
-            let current = self.state.selected().unwrap_or(0);
-            if current < self.items.len() - 1 {
+            if self.state.selected() < self.last_entry_row() {
                 self.next();
             } else {
                 break;
@@ -293,8 +562,7 @@ impl VimNavMotions for ReadingHistory {
         // Page up - move selection up by half page
         let page_size = 10; // Approximate half-page
         for _ in 0..page_size {
-            let current = self.state.selected().unwrap_or(0);
-            if current > 0 {
+            if self.state.selected() > self.first_entry_row() {
                 self.previous();
             } else {
                 break;
@@ -304,68 +572,239 @@ impl VimNavMotions for ReadingHistory {
 
     fn handle_gg(&mut self) {
         // Go to top - select first item
-        if !self.items.is_empty() {
-            self.state.select(Some(0));
+        if let Some(row) = self.first_entry_row() {
+            self.state.select(Some(row));
         }
     }
 
     fn handle_upper_g(&mut self) {
         // Go to bottom - select last item
-        if !self.items.is_empty() {
-            let last_index = self.items.len() - 1;
-            self.state.select(Some(last_index));
+        if let Some(row) = self.last_entry_row() {
+            self.state.select(Some(row));
         }
     }
 }
 
 impl ReadingHistory {
-    pub fn handle_key(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        key_seq: &mut KeySeq,
-    ) -> Option<ReadingHistoryAction> {
-        use crossterm::event::{KeyCode, KeyModifiers};
-
-        match key.code {
-            KeyCode::Char('j') => {
-                self.handle_j();
-                None
-            }
-            KeyCode::Char('k') => {
-                self.handle_k();
-                None
-            }
-            KeyCode::Char('h') => {
-                self.handle_h();
-                None
+    pub fn popup_mode(&self) -> PopupMode {
+        if self.pending_removal.is_some() {
+            PopupMode::Confirming
+        } else {
+            PopupMode::Browse
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<ReadingHistoryAction> {
+        if self.pending_removal.is_some() {
+            if command == PopupCommand::Confirm {
+                return self.confirm_removal();
             }
-            KeyCode::Char('l') => {
-                self.handle_l();
-                None
+            // Anything else cancels the removal
+            self.pending_removal = None;
+            return None;
+        }
+
+        match command {
+            PopupCommand::Remove => {
+                self.pending_removal = self.selected_item().map(PendingRemoval::Entry);
             }
-            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => {
-                self.handle_gg();
-                None
+            PopupCommand::RemoveMissing if self.missing_count() > 0 => {
+                self.pending_removal = Some(PendingRemoval::AllMissing);
             }
-            KeyCode::Char('G') => {
-                self.handle_upper_g();
-                None
+            PopupCommand::CycleFilter => {
+                self.missing_only = !self.missing_only;
+                self.rebuild_rows();
             }
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_d();
-                None
+            PopupCommand::Down => self.handle_j(),
+            PopupCommand::Up => self.handle_k(),
+            PopupCommand::Left => self.handle_h(),
+            PopupCommand::Right => self.handle_l(),
+            PopupCommand::Top => self.handle_gg(),
+            PopupCommand::Bottom => self.handle_upper_g(),
+            PopupCommand::HalfPageDown => self.handle_ctrl_d(),
+            PopupCommand::HalfPageUp => self.handle_ctrl_u(),
+            PopupCommand::Close => return Some(ReadingHistoryAction::Close),
+            PopupCommand::Select => {
+                return self
+                    .selected_path()
+                    .map(|path| ReadingHistoryAction::OpenBook {
+                        path: path.to_string(),
+                    });
             }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_u();
-                None
+            _ => {}
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn item(path: &str, date: DateTime<Local>, missing: bool) -> HistoryItem {
+        HistoryItem {
+            date,
+            title: path.trim_end_matches(".epub").to_string(),
+            path: path.to_string(),
+            chapter: 0,
+            total_chapters: 4,
+            chapters_read: None,
+            missing,
+            threads: 0,
+        }
+    }
+
+    fn history(items: Vec<HistoryItem>, today: NaiveDate) -> ReadingHistory {
+        let mut history = ReadingHistory::new(&Bookmarks::ephemeral());
+        history.items = items;
+        history.today = today;
+        history.rebuild_rows();
+        history
+    }
+
+    fn local(y: i32, m: u32, d: u32) -> DateTime<Local> {
+        Local.with_ymd_and_hms(y, m, d, 12, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_history_group_for_date() {
+        let today = date(2024, 5, 10);
+        assert_eq!(HistoryGroup::for_date(today, today), HistoryGroup::Today);
+        assert_eq!(
+            HistoryGroup::for_date(date(2024, 5, 9), today),
+            HistoryGroup::Yesterday
+        );
+        assert_eq!(
+            HistoryGroup::for_date(date(2024, 5, 4), today),
+            HistoryGroup::ThisWeek
+        );
+        assert_eq!(
+            HistoryGroup::for_date(date(2024, 5, 3), today),
+            HistoryGroup::Earlier
+        );
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert_eq!(progress_percent(0, 4), Some(25));
+        assert_eq!(progress_percent(9, 4), Some(100));
+        assert_eq!(progress_percent(0, 0), None);
+        assert_eq!(progress_bar(25, 8), "██░░░░░░");
+        assert_eq!(progress_bar(100, 4), "████");
+    }
+
+    #[test]
+    fn test_rows_are_grouped_and_selection_skips_headers() {
+        let mut history = history(
+            vec![
+                item("a.epub", local(2024, 5, 10), false),
+                item("b.epub", local(2024, 5, 9), false),
+                item("c.epub", local(2024, 1, 1), false),
+            ],
+            date(2024, 5, 10),
+        );
+
+        assert_eq!(history.rows.len(), 6);
+        assert_eq!(history.selected_path(), Some("a.epub"));
+        history.next();
+        assert_eq!(history.selected_path(), Some("b.epub"));
+        history.handle_upper_g();
+        assert_eq!(history.selected_path(), Some("c.epub"));
+        history.next();
+        assert_eq!(history.selected_path(), Some("a.epub"));
+    }
+
+    #[test]
+    fn test_headers_and_dates_follow_the_locale() {
+        use crate::i18n::{Locale, with_locale};
+        use crate::test_utils::test_helpers::create_test_terminal;
+
+        let mut history = history(
+            vec![
+                item("a.epub", local(2024, 5, 10), false),
+                item("b.epub", local(2024, 5, 9), true),
+                item("c.epub", local(2024, 5, 6), false),
+            ],
+            date(2024, 5, 10),
+        )
+        .with_streak(Some(3));
+        let rows = with_locale(Locale::Ru, || {
+            let mut terminal = create_test_terminal(100, 14);
+            terminal.draw(|f| history.render(f, f.area())).unwrap();
+            let buffer = terminal.backend().buffer();
+            (0..buffer.area.height)
+                .map(|y| {
+                    let row: String = (0..buffer.area.width)
+                        .map(|x| buffer[(x, y)].symbol())
+                        .collect();
+                    row.trim_matches(|c: char| c == ' ' || c == '│').to_string()
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            rows[1..8],
+            [
+                "┌ История чтения · дней подряд: 3 ─────────────────────────┐",
+                "Сегодня",
+                "» 10.05.2024 : a ███░░░░░░░  25%",
+                "Вчера",
+                "09.05.2024 : b ███░░░░░░░  25% [нет файла]",
+                "На этой неделе",
+                "06.05.2024 : c ███░░░░░░░  25%",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remove_entry_requires_confirmation() {
+        let mut history = history(
+            vec![
+                item("a.epub", local(2024, 5, 10), false),
+                item("b.epub", local(2024, 5, 10), false),
+            ],
+            date(2024, 5, 10),
+        );
+
+        assert!(history.handle_command(PopupCommand::Remove).is_none());
+        assert!(history.handle_command(PopupCommand::Cancel).is_none());
+        assert_eq!(history.items.len(), 2);
+
+        history.handle_command(PopupCommand::Remove);
+        match history.handle_command(PopupCommand::Confirm) {
+            Some(ReadingHistoryAction::RemoveBooks { paths }) => assert_eq!(paths, ["a.epub"]),
+            _ => panic!("expected a removal"),
+        }
+        assert_eq!(history.selected_path(), Some("b.epub"));
+    }
+
+    #[test]
+    fn test_missing_filter_and_purge() {
+        let mut history = history(
+            vec![
+                item("a.epub", local(2024, 5, 10), false),
+                item("gone.epub", local(2024, 5, 9), true),
+                item("lost.epub", local(2024, 1, 1), true),
+            ],
+            date(2024, 5, 10),
+        );
+
+        history.handle_command(PopupCommand::CycleFilter);
+        assert_eq!(history.selected_path(), Some("gone.epub"));
+        assert_eq!(history.rows.len(), 4);
+
+        history.handle_command(PopupCommand::RemoveMissing);
+        match history.handle_command(PopupCommand::Confirm) {
+            Some(ReadingHistoryAction::RemoveBooks { paths }) => {
+                assert_eq!(paths, ["gone.epub", "lost.epub"])
             }
-            KeyCode::Esc => Some(ReadingHistoryAction::Close),
-            KeyCode::Enter => self
-                .selected_path()
-                .map(|path| ReadingHistoryAction::OpenBook {
-                    path: path.to_string(),
-                }),
-            _ => None,
+            _ => panic!("expected a purge"),
         }
+        history.handle_command(PopupCommand::CycleFilter);
+        assert_eq!(history.items.len(), 1);
+        assert_eq!(history.selected_path(), Some("a.epub"));
     }
 }
@@ -0,0 +1,361 @@
+use crate::i18n::{self, tr, tr_args};
+use crate::main_app::commands::PopupCommand;
+use crate::reading_goal::{BookDayTotals, GoalUnit, ReadingGoals};
+use crate::theme::current_theme;
+use crate::widget::bar_chart::bar_chart_rows;
+use chrono::{Days, NaiveDate};
+use ratatui::{
+    Frame,
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+};
+use std::collections::HashMap;
+
+/// Days covered by the chart, ending today
+pub const STATS_DAYS: usize = 30;
+const CHART_HEIGHT: usize = 8;
+/// Width of the value labels left of the chart
+const AXIS_WIDTH: usize = 7;
+
+pub enum ReadingStatsAction {
+    Close,
+}
+
+/// Popup charting how much the reader read per day over the last weeks
+pub struct ReadingStats {
+    /// Oldest first, ending today
+    days: Vec<(NaiveDate, BookDayTotals)>,
+    /// Totals per book over the period, most read first
+    books: Vec<(String, BookDayTotals)>,
+    unit: GoalUnit,
+    last_popup_area: Option<Rect>,
+}
+
+impl ReadingStats {
+    /// `book_name` turns the book paths stored in the daily records into titles
+    pub fn new(
+        goals: &ReadingGoals,
+        today: NaiveDate,
+        unit: GoalUnit,
+        book_name: impl Fn(&str) -> String,
+    ) -> Self {
+        let first_day = today
+            .checked_sub_days(Days::new(STATS_DAYS as u64 - 1))
+            .unwrap_or(today);
+        let mut per_book: HashMap<&str, BookDayTotals> = HashMap::new();
+
+        let days = first_day
+            .iter_days()
+            .take_while(|date| *date <= today)
+            .map(|date| {
+                let mut totals = BookDayTotals::default();
+                for (book, book_totals) in goals.day(date).iter().flat_map(|day| &day.books) {
+                    totals.read_ms += book_totals.read_ms;
+                    totals.lines += book_totals.lines;
+                    totals.words += book_totals.words;
+                    let entry = per_book.entry(book.as_str()).or_default();
+                    entry.read_ms += book_totals.read_ms;
+                    entry.lines += book_totals.lines;
+                    entry.words += book_totals.words;
+                }
+                (date, totals)
+            })
+            .collect();
+
+        let mut books: Vec<(String, BookDayTotals)> = per_book
+            .into_iter()
+            .map(|(book, totals)| (book_name(book), totals))
+            .collect();
+        books.sort_by(|a, b| (b.1.read_ms, b.1.lines, &a.0).cmp(&(a.1.read_ms, a.1.lines, &b.0)));
+
+        ReadingStats {
+            days,
+            books,
+            unit,
+            last_popup_area: None,
+        }
+    }
+
+    fn value(&self, totals: &BookDayTotals) -> u64 {
+        match self.unit {
+            GoalUnit::Minutes => totals.read_ms / 60_000,
+            GoalUnit::Lines => totals.lines,
+            GoalUnit::Words => totals.words,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<ReadingStatsAction> {
+        match command {
+            PopupCommand::Close => Some(ReadingStatsAction::Close),
+            PopupCommand::SwitchView => {
+                self.unit = match self.unit {
+                    GoalUnit::Minutes => GoalUnit::Lines,
+                    GoalUnit::Lines => GoalUnit::Words,
+                    GoalUnit::Words => GoalUnit::Minutes,
+                };
+                None
+            }
+            _ => None,
+        }
+    }
+
+    fn content_lines(&self) -> Vec<Line<'static>> {
+        let palette = current_theme();
+        let label = self.unit.label();
+        let values: Vec<u64> = self
+            .days
+            .iter()
+            .map(|(_, totals)| self.value(totals))
+            .collect();
+        let total: u64 = values.iter().sum();
+        let active_days = values.iter().filter(|&&value| value > 0).count();
+        let best = self
+            .days
+            .iter()
+            .zip(&values)
+            .filter(|(_, value)| **value > 0)
+            .max_by_key(|(_, value)| **value);
+
+        let mut lines = vec![Line::from(vec![
+            Span::styled(
+                tr_args("stats.total", &[("total", &total), ("unit", &label)]),
+                Style::default()
+                    .fg(palette.base_05)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                tr_args(
+                    "stats.summary",
+                    &[
+                        ("days", &STATS_DAYS),
+                        ("active", &active_days),
+                        ("average", &(total / STATS_DAYS as u64)),
+                        ("unit", &label),
+                    ],
+                ),
+                Style::default().fg(palette.base_04),
+            ),
+        ])];
+        if let Some(((date, _), best)) = best {
+            lines.push(Line::from(Span::styled(
+                tr_args(
+                    "stats.best_day",
+                    &[
+                        ("best", &best),
+                        ("unit", &label),
+                        ("date", &i18n::format_month_day(*date)),
+                    ],
+                ),
+                Style::default().fg(palette.base_04),
+            )));
+        }
+        lines.push(Line::from(""));
+
+        let max = values.iter().copied().max().unwrap_or(0);
+        let rows = bar_chart_rows(&values, CHART_HEIGHT);
+        let last_row = rows.len().saturating_sub(1);
+        for (i, row) in rows.into_iter().enumerate() {
+            let axis = match i {
+                0 => format!("{max:>5} ┤"),
+                i if i == last_row => format!("{:>5} ┤", 0),
+                _ => format!("{:>5} │", ""),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(axis, Style::default().fg(palette.base_03)),
+                Span::styled(row, Style::default().fg(palette.base_0d)),
+            ]));
+        }
+
+        // Dates under the first, middle and last bars
+        if let (Some((first, _)), Some((last, _))) = (self.days.first(), self.days.last()) {
+            let chart_width = (self.days.len() * 2).saturating_sub(1);
+            let middle = self.days[self.days.len() / 2].0;
+            let mut axis = vec![' '; chart_width];
+            for (column, date) in [(0, *first), (self.days.len() / 2 * 2, middle)] {
+                for (offset, c) in i18n::format_month_day(date).chars().enumerate() {
+                    if let Some(cell) = axis.get_mut(column + offset) {
+                        *cell = c;
+                    }
+                }
+            }
+            let last_label = i18n::format_month_day(*last);
+            let start = chart_width.saturating_sub(last_label.chars().count());
+            axis.truncate(start);
+            let axis: String = axis.into_iter().collect::<String>() + &last_label;
+            lines.push(Line::from(Span::styled(
+                format!("{}{axis}", " ".repeat(AXIS_WIDTH)),
+                Style::default().fg(palette.base_03),
+            )));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            tr("stats.books"),
+            Style::default()
+                .fg(palette.base_0c)
+                .add_modifier(Modifier::BOLD),
+        )));
+        if self.books.is_empty() {
+            lines.push(Line::from(Span::styled(
+                tr("stats.nothing_read"),
+                Style::default().fg(palette.base_03),
+            )));
+        }
+        for (name, totals) in &self.books {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!(
+                        "{:>7} {} {:>7} {}  ",
+                        totals.read_ms / 60_000,
+                        GoalUnit::Minutes.label(),
+                        totals.lines,
+                        GoalUnit::Lines.label()
+                    ),
+                    Style::default().fg(palette.base_04),
+                ),
+                Span::styled(name.clone(), Style::default().fg(palette.base_05)),
+            ]));
+        }
+
+        lines
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let width = (AXIS_WIDTH as u16 + STATS_DAYS as u16 * 2 + 3).min(area.width);
+        let height = area.height.saturating_sub(4).min(30);
+        let popup_area = Rect {
+            x: area.x + (area.width - width) / 2,
+            y: area.y + (area.height - height) / 2,
+            width,
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let title = match self.unit {
+            GoalUnit::Minutes => tr("stats.title.minutes"),
+            GoalUnit::Lines => tr("stats.title.lines"),
+            GoalUnit::Words => tr("stats.title.words"),
+        };
+        let block = Block::default()
+            .title(title)
+            .title_bottom(Line::from(Span::styled(
+                tr("stats.footer"),
+                Style::default().fg(palette.base_03),
+            )))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+
+        f.render_widget(
+            Paragraph::new(self.content_lines()).block(block),
+            popup_area,
+        );
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::test_helpers::{capture_terminal_state, create_test_terminal};
+    use chrono::{Local, TimeZone};
+    use std::time::Duration;
+
+    fn month_of_reading() -> ReadingGoals {
+        let mut goals = ReadingGoals::ephemeral();
+        for day in 1..=31u32 {
+            let at = Local.with_ymd_and_hms(2024, 5, day, 21, 0, 0).unwrap();
+            if day % 7 != 0 {
+                goals.record_time(
+                    "/books/dune.epub",
+                    at,
+                    Duration::from_secs(u64::from(day) * 60),
+                );
+                goals.record_lines("/books/dune.epub", at, u64::from(day) * 10);
+            }
+            if day % 10 == 0 {
+                goals.record_time("/books/emma.epub", at, Duration::from_secs(45 * 60));
+            }
+        }
+        // Outside the 30-day window
+        let before = Local.with_ymd_and_hms(2024, 4, 1, 21, 0, 0).unwrap();
+        goals.record_time("/books/old.epub", before, Duration::from_secs(600 * 60));
+        goals
+    }
+
+    fn book_name(path: &str) -> String {
+        std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn render_snapshot(stats: &mut ReadingStats) -> String {
+        let mut terminal = create_test_terminal(80, 30);
+        terminal.draw(|f| stats.render(f, f.area())).unwrap();
+        capture_terminal_state(&terminal)
+    }
+
+    #[test]
+    fn test_month_of_minutes_snapshot() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+        let mut stats = ReadingStats::new(&month_of_reading(), today, GoalUnit::Minutes, book_name);
+
+        let expected = [
+            "",
+            "",
+            "     ┌ My Reading · Minutes per Day ──────────────────────────────────────┐",
+            "     │560 min in the last 30 days · read on 26 · 18 min/day               │",
+            "     │Best day: 75 min on May 30                                          │",
+            "     │                                                                    │",
+            "     │   75 ┤                                                        █    │",
+            "     │      │                                    █                   █    │",
+            "     │      │                ▇                   █                   █    │",
+            "     │      │                █                   █                   █    │",
+            "     │      │                █                   █                 ▁ █ ▃  │",
+            "     │      │                █                 ▁ █   ▃ ▄ ▅ ▆ ▇ █   █ █ █  │",
+            "     │      │                █ ▂ ▃ ▄   ▅ ▆ ▇ █ █ █   █ █ █ █ █ █   █ █ █  │",
+            "     │    0 ┤▂ ▃ ▄ ▅ ▆   ▇ █ █ █ █ █   █ █ █ █ █ █   █ █ █ █ █ █   █ █ █  │",
+            "     │       May 2                         May 17                 May 31  │",
+            "     │                                                                    │",
+            "     │Books                                                               │",
+            "     │    425 min    4250 lines  dune                                     │",
+            "     │    135 min       0 lines  emma                                     │",
+        ];
+        let snapshot = render_snapshot(&mut stats);
+        let lines: Vec<&str> = snapshot.lines().collect();
+        assert_eq!(&lines[..expected.len()], &expected[..], "\n{snapshot}");
+    }
+
+    #[test]
+    fn test_book_totals_and_unit_switch() {
+        let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+        let mut stats = ReadingStats::new(&month_of_reading(), today, GoalUnit::Minutes, book_name);
+
+        let names: Vec<&str> = stats.books.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["dune", "emma"]);
+        assert_eq!(stats.books[1].1.read_ms, 3 * 45 * 60_000);
+
+        stats.handle_command(PopupCommand::SwitchView);
+        let snapshot = render_snapshot(&mut stats);
+        assert!(
+            snapshot.contains("My Reading · Lines per Day"),
+            "{snapshot}"
+        );
+        assert!(snapshot.contains("lines  dune"), "{snapshot}");
+
+        stats.handle_command(PopupCommand::SwitchView);
+        assert!(render_snapshot(&mut stats).contains("My Reading · Words per Day"));
+    }
+}
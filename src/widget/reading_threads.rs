@@ -0,0 +1,398 @@
+use crate::i18n::{self, tr, tr_args};
+use crate::main_app::commands::{PopupCommand, PopupMode};
+use crate::theme::current_theme;
+use chrono::{DateTime, Local, Utc};
+use ratatui::{
+    Frame,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+};
+
+pub enum ReadingThreadsAction {
+    /// Continue reading at a thread; None is the primary bookmark
+    Switch {
+        name: Option<String>,
+    },
+    /// Start a new thread at the current position
+    Create {
+        name: String,
+    },
+    Delete {
+        name: String,
+    },
+    Close,
+}
+
+/// A row of the popup: the primary bookmark or one of the book's threads
+pub struct ThreadEntry {
+    /// None for the primary bookmark
+    pub name: Option<String>,
+    pub chapter_title: String,
+    pub last_read: DateTime<Utc>,
+}
+
+enum Mode {
+    Browse,
+    /// Typing the name of a new thread
+    Naming(String),
+    /// Deletion of the selected thread waiting for a y/n answer
+    ConfirmDelete,
+}
+
+/// Popup listing the reading threads of the open book
+pub struct ReadingThreads {
+    entries: Vec<ThreadEntry>,
+    active: Option<String>,
+    mode: Mode,
+    state: ListState,
+    last_popup_area: Option<Rect>,
+}
+
+impl ReadingThreads {
+    /// `entries` starts with the primary bookmark; `active` is the thread
+    /// being read, None for the primary one
+    pub fn new(entries: Vec<ThreadEntry>, active: Option<String>) -> Self {
+        let selected = entries
+            .iter()
+            .position(|entry| entry.name == active)
+            .or(if entries.is_empty() { None } else { Some(0) });
+        let mut state = ListState::default();
+        state.select(selected);
+
+        ReadingThreads {
+            entries,
+            active,
+            mode: Mode::Browse,
+            state,
+            last_popup_area: None,
+        }
+    }
+
+    /// Open straight into the name prompt for a new thread
+    pub fn naming(mut self) -> Self {
+        self.mode = Mode::Naming(String::new());
+        self
+    }
+
+    fn selected_entry(&self) -> Option<&ThreadEntry> {
+        self.state.selected().and_then(|i| self.entries.get(i))
+    }
+
+    fn step_selection(&mut self, forward: bool) {
+        if self.entries.is_empty() {
+            return;
+        }
+        let last = self.entries.len() - 1;
+        let i = match (self.state.selected(), forward) {
+            (Some(i), true) if i < last => i + 1,
+            (Some(_), true) | (None, _) => 0,
+            (Some(0), false) => last,
+            (Some(i), false) => i - 1,
+        };
+        self.state.select(Some(i));
+    }
+
+    pub fn popup_mode(&self) -> PopupMode {
+        match self.mode {
+            Mode::Browse => PopupMode::Browse,
+            Mode::Naming(_) => PopupMode::Typing,
+            Mode::ConfirmDelete => PopupMode::Confirming,
+        }
+    }
+
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<ReadingThreadsAction> {
+        match &mut self.mode {
+            Mode::Naming(name) => {
+                match command {
+                    PopupCommand::Cancel => self.mode = Mode::Browse,
+                    PopupCommand::Submit => {
+                        let name = name.trim().to_string();
+                        if !name.is_empty() {
+                            return Some(ReadingThreadsAction::Create { name });
+                        }
+                    }
+                    PopupCommand::Backspace => {
+                        name.pop();
+                    }
+                    PopupCommand::Input(c) => name.push(c),
+                    _ => {}
+                }
+                None
+            }
+            Mode::ConfirmDelete => {
+                self.mode = Mode::Browse;
+                if command == PopupCommand::Confirm {
+                    let name = self.selected_entry()?.name.clone()?;
+                    return Some(ReadingThreadsAction::Delete { name });
+                }
+                // Anything else cancels the deletion
+                None
+            }
+            Mode::Browse => match command {
+                PopupCommand::Close => Some(ReadingThreadsAction::Close),
+                PopupCommand::Select => {
+                    self.selected_entry()
+                        .map(|entry| ReadingThreadsAction::Switch {
+                            name: entry.name.clone(),
+                        })
+                }
+                PopupCommand::Down => {
+                    self.step_selection(true);
+                    None
+                }
+                PopupCommand::Up => {
+                    self.step_selection(false);
+                    None
+                }
+                PopupCommand::New => {
+                    self.mode = Mode::Naming(String::new());
+                    None
+                }
+                PopupCommand::Remove => {
+                    // The primary bookmark cannot be deleted
+                    if self
+                        .selected_entry()
+                        .is_some_and(|entry| entry.name.is_some())
+                    {
+                        self.mode = Mode::ConfirmDelete;
+                    }
+                    None
+                }
+                _ => None,
+            },
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let popup_area = centered_rect(60, 50, area);
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let palette = current_theme();
+        let items: Vec<ListItem> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                let name = entry.name.as_deref().unwrap_or(tr("threads.main"));
+                let marker = if entry.name == self.active {
+                    "● "
+                } else {
+                    "  "
+                };
+                let last_read = DateTime::<Local>::from(entry.last_read);
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, Style::default().fg(palette.base_0b)),
+                    Span::styled(
+                        name.to_string(),
+                        Style::default()
+                            .fg(palette.base_05)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(" · ", Style::default().fg(palette.base_03)),
+                    Span::styled(
+                        entry.chapter_title.as_str(),
+                        Style::default().fg(palette.base_05),
+                    ),
+                    Span::styled(
+                        format!("  {}", i18n::format_date_time(last_read.naive_local())),
+                        Style::default().fg(palette.base_03),
+                    ),
+                ]))
+            })
+            .collect();
+
+        let footer = match &self.mode {
+            Mode::Browse => Line::from(Span::styled(
+                tr("threads.footer"),
+                Style::default().fg(palette.base_03),
+            )),
+            Mode::Naming(_) => Line::from(Span::styled(
+                tr("threads.footer_naming"),
+                Style::default().fg(palette.base_03),
+            )),
+            Mode::ConfirmDelete => Line::from(Span::styled(
+                tr_args(
+                    "threads.confirm_delete",
+                    &[(
+                        "name",
+                        &self
+                            .selected_entry()
+                            .and_then(|entry| entry.name.as_deref())
+                            .unwrap_or_default(),
+                    )],
+                ),
+                Style::default()
+                    .fg(palette.base_08)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        };
+
+        let block = Block::default()
+            .title(tr("title.reading_threads"))
+            .title_bottom(footer)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .style(Style::default().bg(palette.base_00));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
+
+        let (prompt_area, list_area) = if let Mode::Naming(name) = &self.mode {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(2), Constraint::Min(1)])
+                .split(inner);
+            let prompt = Paragraph::new(Line::from(vec![
+                Span::styled(
+                    tr("threads.new_prompt"),
+                    Style::default().fg(palette.base_0d),
+                ),
+                Span::styled(
+                    name.as_str(),
+                    Style::default()
+                        .fg(palette.base_05)
+                        .add_modifier(Modifier::BOLD),
+                ),
+                Span::styled("█", Style::default().fg(palette.base_05)),
+            ]))
+            .block(
+                Block::default()
+                    .borders(Borders::BOTTOM)
+                    .border_style(Style::default().fg(palette.base_02)),
+            );
+            (Some((prompt, chunks[0])), chunks[1])
+        } else {
+            (None, inner)
+        };
+
+        if let Some((prompt, area)) = prompt_area {
+            f.render_widget(prompt, area);
+        }
+
+        let list = List::new(items)
+            .highlight_style(
+                Style::default()
+                    .bg(palette.base_02)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol("» ");
+        f.render_stateful_widget(list, list_area, &mut self.state);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::main_app::commands::{Command, KeyContext, command_for_key};
+    use crate::main_app::{FocusedPanel, PopupWindow};
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+    fn entry(name: Option<&str>) -> ThreadEntry {
+        ThreadEntry {
+            name: name.map(str::to_string),
+            chapter_title: "Chapter".to_string(),
+            last_read: Utc::now(),
+        }
+    }
+
+    fn threads() -> ReadingThreads {
+        ReadingThreads::new(
+            vec![
+                entry(None),
+                entry(Some("appendix")),
+                entry(Some("tutorial")),
+            ],
+            Some("appendix".to_string()),
+        )
+    }
+
+    /// Presses a key the way the app does, in the popup's current mode
+    fn press(popup: &mut ReadingThreads, code: KeyCode) -> Option<ReadingThreadsAction> {
+        let mut context = KeyContext::new(FocusedPanel::Popup(PopupWindow::ReadingThreads));
+        context.popup_mode = popup.popup_mode();
+        match command_for_key(&context, KeyEvent::new(code, KeyModifiers::NONE))? {
+            Command::Popup(command) => popup.handle_command(command),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_opens_on_active_thread_and_switches() {
+        let mut popup = threads();
+        match press(&mut popup, KeyCode::Enter) {
+            Some(ReadingThreadsAction::Switch { name }) => {
+                assert_eq!(name.as_deref(), Some("appendix"))
+            }
+            _ => panic!("expected a switch"),
+        }
+
+        press(&mut popup, KeyCode::Char('k'));
+        match press(&mut popup, KeyCode::Enter) {
+            Some(ReadingThreadsAction::Switch { name }) => assert_eq!(name, None),
+            _ => panic!("expected a switch to the primary bookmark"),
+        }
+    }
+
+    #[test]
+    fn test_naming_prompt_creates_thread() {
+        let mut popup = threads().naming();
+        press(&mut popup, KeyCode::Char(' '));
+        assert!(press(&mut popup, KeyCode::Enter).is_none());
+
+        // Keys that browse the list are part of the name while typing
+        for c in "j k".chars() {
+            press(&mut popup, KeyCode::Char(c));
+        }
+        match press(&mut popup, KeyCode::Enter) {
+            Some(ReadingThreadsAction::Create { name }) => assert_eq!(name, "j k"),
+            _ => panic!("expected a new thread"),
+        }
+    }
+
+    #[test]
+    fn test_delete_requires_confirmation_and_skips_primary() {
+        let mut popup = threads();
+        assert!(press(&mut popup, KeyCode::Char('x')).is_none());
+        assert!(press(&mut popup, KeyCode::Char('n')).is_none());
+
+        press(&mut popup, KeyCode::Char('x'));
+        match press(&mut popup, KeyCode::Char('y')) {
+            Some(ReadingThreadsAction::Delete { name }) => assert_eq!(name, "appendix"),
+            _ => panic!("expected a deletion"),
+        }
+
+        press(&mut popup, KeyCode::Char('g'));
+        press(&mut popup, KeyCode::Char('k'));
+        press(&mut popup, KeyCode::Char('x'));
+        assert!(press(&mut popup, KeyCode::Char('y')).is_none());
+    }
+}
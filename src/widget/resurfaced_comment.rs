@@ -0,0 +1,156 @@
+use crate::comments::Comment;
+use crate::i18n::tr;
+use crate::main_app::commands::PopupCommand;
+use crate::theme::current_theme;
+use ratatui::{
+    Frame,
+    layout::{Alignment, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Padding, Paragraph},
+};
+
+/// Widest the popup gets, so the quote reads like one
+const MAX_WIDTH: u16 = 72;
+const MAX_QUOTE_LINES: usize = 8;
+const MAX_NOTE_LINES: usize = 4;
+
+pub enum ResurfacedCommentAction {
+    /// Open the book at the commented passage
+    Jump,
+    Close,
+}
+
+/// Popup bringing back a random comment from the library (Space+q, or on
+/// startup with `resurface_comments: true`)
+pub struct ResurfacedComment {
+    pub book_path: String,
+    pub chapter_href: String,
+    pub node_index: usize,
+    book_title: String,
+    chapter_title: String,
+    /// Text of the commented passage, None when the book no longer has it
+    quote: Option<String>,
+    note: String,
+    last_popup_area: Option<Rect>,
+}
+
+impl ResurfacedComment {
+    pub fn new(
+        book_path: String,
+        book_title: String,
+        chapter_title: String,
+        comment: &Comment,
+        quote: Option<String>,
+    ) -> Self {
+        ResurfacedComment {
+            book_path,
+            chapter_href: comment.chapter_href.clone(),
+            node_index: comment.node_index(),
+            book_title,
+            chapter_title,
+            quote,
+            note: comment.content.trim().to_string(),
+            last_popup_area: None,
+        }
+    }
+
+    pub fn handle_command(&self, command: PopupCommand) -> Option<ResurfacedCommentAction> {
+        match command {
+            PopupCommand::Select => Some(ResurfacedCommentAction::Jump),
+            PopupCommand::Close => Some(ResurfacedCommentAction::Close),
+            _ => None,
+        }
+    }
+
+    pub fn render(&mut self, f: &mut Frame, area: Rect) {
+        let palette = current_theme();
+        let width = MAX_WIDTH.min(area.width.saturating_sub(4)).max(20);
+        let text_width = width.saturating_sub(4) as usize;
+
+        let mut lines = Vec::new();
+        match &self.quote {
+            Some(quote) => {
+                let quote = format!("“{quote}”");
+                for line in wrap_lines(&quote, text_width, MAX_QUOTE_LINES) {
+                    lines.push(Line::from(Span::styled(
+                        line,
+                        Style::default()
+                            .fg(palette.base_05)
+                            .add_modifier(Modifier::ITALIC),
+                    )));
+                }
+            }
+            None => lines.push(Line::from(Span::styled(
+                tr("comments.unavailable_text"),
+                Style::default().fg(palette.base_03),
+            ))),
+        }
+        if !self.note.is_empty() {
+            lines.push(Line::from(""));
+            for line in wrap_lines(&self.note, text_width, MAX_NOTE_LINES) {
+                lines.push(Line::from(Span::styled(
+                    line,
+                    Style::default().fg(palette.base_0b),
+                )));
+            }
+        }
+        lines.push(Line::from(""));
+        let source = format!("— {} · {}", self.book_title, self.chapter_title);
+        for line in wrap_lines(&source, text_width, 2) {
+            lines.push(
+                Line::from(Span::styled(line, Style::default().fg(palette.base_03)))
+                    .alignment(Alignment::Right),
+            );
+        }
+
+        let height = (lines.len() as u16 + 2).min(area.height);
+        let popup_area = Rect {
+            x: area.x + area.width.saturating_sub(width) / 2,
+            y: area.y + area.height.saturating_sub(height) / 2,
+            width: width.min(area.width),
+            height,
+        };
+        self.last_popup_area = Some(popup_area);
+        f.render_widget(Clear, popup_area);
+
+        let block = Block::default()
+            .title(tr("title.resurfaced_comment"))
+            .title_bottom(Line::from(Span::styled(
+                " Enter: Go to passage | Esc: Close ",
+                Style::default().fg(palette.base_03),
+            )))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(palette.base_0c))
+            .padding(Padding::horizontal(1))
+            .style(Style::default().bg(palette.base_00));
+        f.render_widget(Paragraph::new(lines).block(block), popup_area);
+    }
+
+    /// Check if the given coordinates are outside the popup area
+    pub fn is_outside_popup_area(&self, x: u16, y: u16) -> bool {
+        match self.last_popup_area {
+            Some(area) => x < area.x || x >= area.right() || y < area.y || y >= area.bottom(),
+            None => true,
+        }
+    }
+}
+
+/// `text` wrapped to `width`, at most `max_lines` of it with an ellipsis
+/// when cut
+fn wrap_lines(text: &str, width: usize, max_lines: usize) -> Vec<String> {
+    let mut lines: Vec<String> = textwrap::wrap(text, width.max(1))
+        .into_iter()
+        .map(|line| line.into_owned())
+        .collect();
+    if lines.len() > max_lines {
+        lines.truncate(max_lines);
+        if let Some(last) = lines.last_mut() {
+            if last.chars().count() >= width {
+                last.pop();
+            }
+            last.push('…');
+        }
+    }
+    lines
+}
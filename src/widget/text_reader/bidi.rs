@@ -0,0 +1,191 @@
+use super::types::*;
+use ratatui::style::Style as RatatuiStyle;
+use ratatui::text::Span;
+use unicode_bidi::{BidiInfo, Level};
+
+/// Where the chars of a right-to-left line appear on screen. The line keeps
+/// its logical order for copy, search and selection; only drawing reorders it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RtlLayout {
+    /// Blank columns that right-align the line
+    pub padding: usize,
+    /// Logical char index shown at each column after the padding
+    pub order: Vec<usize>,
+}
+
+impl RtlLayout {
+    /// Logical column of the char drawn at screen `column`
+    pub fn logical_column(&self, column: usize) -> usize {
+        match column.checked_sub(self.padding) {
+            // Left of the text is past the end of an RTL line
+            None => self.order.len(),
+            Some(offset) => self.order.get(offset).copied().unwrap_or(0),
+        }
+    }
+
+    /// Reorder logically ordered spans, highlights included, into display order
+    pub fn visual_spans<'a>(&self, spans: Vec<Span<'a>>) -> Vec<Span<'a>> {
+        let styled_chars: Vec<(char, RatatuiStyle)> = spans
+            .iter()
+            .flat_map(|span| span.content.chars().map(move |c| (c, span.style)))
+            .collect();
+
+        let mut visual = Vec::new();
+        if self.padding > 0 {
+            visual.push(Span::raw(" ".repeat(self.padding)));
+        }
+        let mut current = String::new();
+        let mut current_style: Option<RatatuiStyle> = None;
+        for &(c, style) in self.order.iter().filter_map(|&idx| styled_chars.get(idx)) {
+            if current_style != Some(style) && !current.is_empty() {
+                visual.push(Span::styled(
+                    std::mem::take(&mut current),
+                    current_style.unwrap_or_default(),
+                ));
+            }
+            current_style = Some(style);
+            current.push(c);
+        }
+        if !current.is_empty() {
+            visual.push(Span::styled(current, current_style.unwrap_or_default()));
+        }
+        visual
+    }
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Lay out the lines of a right-to-left block starting at `first_line`:
+    /// each line is reordered into visual order with the Unicode bidi
+    /// algorithm and right-aligned when drawn.
+    /// Code, images and comments keep their left-to-right layout.
+    pub fn apply_rtl_layout(&mut self, lines: &[RenderedLine], first_line: usize, width: usize) {
+        for (offset, line) in lines.iter().enumerate() {
+            if !matches!(
+                line.line_type,
+                LineType::Text | LineType::Heading { .. } | LineType::ListItem { .. }
+            ) {
+                continue;
+            }
+
+            let logical: String = line
+                .spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect();
+            let logical = logical.trim_end();
+            if logical.is_empty() {
+                continue;
+            }
+
+            let order = Self::visual_char_order(logical);
+            let chars: Vec<char> = logical.chars().collect();
+            let visual_text: String = order.iter().map(|&i| chars[i]).collect();
+            let padding = width.saturating_sub(textwrap::core::display_width(&visual_text));
+            self.rtl_layouts
+                .insert(first_line + offset, RtlLayout { padding, order });
+        }
+    }
+
+    /// Char indices of `text` in visual (left-to-right display) order for an RTL paragraph
+    fn visual_char_order(text: &str) -> Vec<usize> {
+        let char_starts: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        let char_at_byte = |byte: usize| char_starts.partition_point(|&start| start < byte);
+
+        let bidi_info = BidiInfo::new(text, Some(Level::rtl()));
+        let Some(paragraph) = bidi_info.paragraphs.first() else {
+            return (0..char_starts.len()).rev().collect();
+        };
+
+        let (levels, runs) = bidi_info.visual_runs(paragraph, paragraph.range.clone());
+        let mut order = Vec::with_capacity(char_starts.len());
+        for run in runs {
+            let run_chars = char_at_byte(run.start)..char_at_byte(run.end);
+            if levels[run.start].is_rtl() {
+                order.extend(run_chars.rev());
+            } else {
+                order.extend(run_chars);
+            }
+        }
+        order
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+
+    fn text_line(text: &str) -> RenderedLine {
+        RenderedLine {
+            spans: vec![Span::raw(text.to_string())],
+            raw_text: text.to_string(),
+            line_type: LineType::Text,
+            link_nodes: vec![],
+            node_anchor: None,
+            node_index: Some(0),
+            code_line: None,
+            inline_code_comments: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_rtl_line_is_right_aligned_but_keeps_logical_text() {
+        let mut reader = MarkdownTextReader::new();
+        let lines = vec![text_line("שלום abc")];
+        reader.apply_rtl_layout(&lines, 3, 12);
+
+        assert_eq!(lines[0].raw_text, "שלום abc");
+        let layout = &reader.rtl_layouts[&3];
+        let rendered: String = layout
+            .visual_spans(lines[0].spans.clone())
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(rendered, "    abc םולש");
+    }
+
+    #[test]
+    fn test_rtl_columns_map_between_screen_and_text() {
+        let mut reader = MarkdownTextReader::new();
+        reader.apply_rtl_layout(&[text_line("שלום abc")], 0, 12);
+        let layout = &reader.rtl_layouts[&0];
+
+        // The rightmost cell is the first logical char
+        assert_eq!(layout.logical_column(11), 0);
+        // "abc" keeps its own order on the left
+        assert_eq!(layout.logical_column(4), 5);
+        // The padding is past the end of the line
+        assert_eq!(layout.logical_column(0), 8);
+        // Every logical char is drawn exactly once
+        let mut logical: Vec<usize> = (4..12).map(|x| layout.logical_column(x)).collect();
+        logical.sort_unstable();
+        assert_eq!(logical, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_highlights_on_logical_columns_follow_the_reordering() {
+        let mut reader = MarkdownTextReader::new();
+        reader.apply_rtl_layout(&[text_line("שלום abc")], 0, 12);
+        let highlight = RatatuiStyle::default().bg(ratatui::style::Color::Yellow);
+        // Highlight the first logical word
+        let spans = vec![Span::styled("שלום", highlight), Span::raw(" abc")];
+
+        let visual = reader.rtl_layouts[&0].visual_spans(spans);
+        let highlighted: Vec<&str> = visual
+            .iter()
+            .filter(|span| span.style == highlight)
+            .map(|span| span.content.as_ref())
+            .collect();
+        assert_eq!(highlighted, vec!["םולש"]);
+    }
+
+    #[test]
+    fn test_rtl_layout_skips_code_lines() {
+        let mut reader = MarkdownTextReader::new();
+        let mut line = text_line("let x = 1;");
+        line.line_type = LineType::CodeBlock { language: None };
+        reader.apply_rtl_layout(&[line], 0, 20);
+
+        assert!(reader.rtl_layouts.is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+use super::conversion::{self, ConversionNotes};
+use crate::markdown::Document;
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// Converted chapters, kept so going back and chapters prefetched around
+/// the current one open without converting again
+#[derive(Default)]
+pub(super) struct ChapterCache {
+    /// Chapters kept, the least recently used dropped first; unbounded
+    /// when None
+    capacity: Option<usize>,
+    /// Chapter index, document and what converting it ran into; most
+    /// recently used last
+    entries: VecDeque<(usize, Arc<Document>, ConversionNotes)>,
+}
+
+impl ChapterCache {
+    pub(super) fn get(&mut self, chapter: usize) -> Option<(Arc<Document>, ConversionNotes)> {
+        let position = self
+            .entries
+            .iter()
+            .position(|(index, ..)| *index == chapter)?;
+        let entry = self.entries.remove(position)?;
+        let found = (entry.1.clone(), entry.2);
+        self.entries.push_back(entry);
+        Some(found)
+    }
+
+    pub(super) fn insert(&mut self, chapter: usize, doc: Arc<Document>, notes: ConversionNotes) {
+        self.entries.retain(|(index, ..)| *index != chapter);
+        self.entries.push_back((chapter, doc, notes));
+        self.evict();
+    }
+
+    fn evict(&mut self) {
+        if let Some(capacity) = self.capacity {
+            while self.entries.len() > capacity {
+                self.entries.pop_front();
+            }
+        }
+    }
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Bound the number of converted chapters kept, None keeping them all
+    pub fn set_chapter_cache_capacity(&mut self, capacity: Option<usize>) {
+        self.chapter_cache.capacity = capacity;
+        self.chapter_cache.evict();
+    }
+
+    /// Drop converted chapters, e.g. when another book is opened
+    pub fn clear_chapter_cache(&mut self) {
+        self.chapter_cache.entries.clear();
+    }
+
+    pub fn is_chapter_cached(&self, chapter: usize) -> bool {
+        self.chapter_cache
+            .entries
+            .iter()
+            .any(|(index, ..)| *index == chapter)
+    }
+
+    /// The chapter's document converted as the reader shows it, from the
+    /// cache when there; a fresh conversion is cached when `keep` is set
+    pub fn chapter_document(
+        &mut self,
+        chapter: usize,
+        raw_html: &str,
+        keep: bool,
+    ) -> Arc<Document> {
+        if let Some((doc, _)) = self.chapter_cache.get(chapter) {
+            return doc;
+        }
+        let (doc, notes) = conversion::convert_chapter(
+            raw_html,
+            self.dehyphenate,
+            self.strip_invisible,
+            self.glyph_map.clone(),
+        );
+        let doc = Arc::new(doc);
+        if keep {
+            self.chapter_cache.insert(chapter, doc.clone(), notes);
+        }
+        doc
+    }
+
+    /// Show a chapter of the book, reusing its cached conversion if any
+    pub fn set_chapter_content(
+        &mut self,
+        chapter: usize,
+        content_raw_html: &str,
+        chapter_title: Option<String>,
+    ) {
+        let Some((doc, notes)) = self.chapter_cache.get(chapter) else {
+            self.load_content(Some(chapter), content_raw_html, chapter_title);
+            return;
+        };
+        self.clear_content();
+        self.chapter_title = chapter_title;
+        self.conversion_notes = notes;
+        self.markdown_document = Some(doc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+
+    #[test]
+    fn test_cache_keeps_the_most_recently_used_chapters() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_chapter_cache_capacity(Some(2));
+
+        reader.set_chapter_content(0, "<p>Zero</p>", None);
+        reader.chapter_document(1, "<p>One</p>", true);
+        reader.chapter_document(5, "<p>Five</p>", false);
+        assert!(reader.is_chapter_cached(0));
+        assert!(reader.is_chapter_cached(1));
+        assert!(!reader.is_chapter_cached(5));
+
+        // Showing chapter 0 again uses it last, so chapter 1 goes first
+        reader.set_chapter_content(0, "<p>Changed</p>", None);
+        let doc = reader.markdown_document.clone().unwrap();
+        assert_eq!(doc.blocks[0].plain_text(), "Zero");
+        reader.chapter_document(2, "<p>Two</p>", true);
+        assert!(reader.is_chapter_cached(0));
+        assert!(!reader.is_chapter_cached(1));
+        assert!(reader.is_chapter_cached(2));
+
+        reader.set_chapter_cache_capacity(Some(1));
+        assert!(!reader.is_chapter_cached(0));
+        reader.set_dehyphenate(true);
+        assert!(!reader.is_chapter_cached(2));
+    }
+}
@@ -0,0 +1,184 @@
+use super::types::LineType;
+use crate::markdown::Block;
+use crate::search::match_ranges;
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Expand a collapsed top-level block or collapse an expanded one
+    pub fn toggle_collapsible(&mut self, node_index: usize) {
+        if !self.expanded_blocks.remove(&node_index) {
+            self.expanded_blocks.insert(node_index);
+        }
+        self.cache_generation += 1;
+    }
+
+    fn expand_collapsible(&mut self, node_index: usize) {
+        if self.expanded_blocks.insert(node_index) {
+            self.cache_generation += 1;
+        }
+    }
+
+    /// Toggle the topmost block whose summary is in view; false when none is
+    pub fn toggle_visible_collapsible(&mut self) -> bool {
+        let end = (self.scroll_offset + self.visible_height).min(self.rendered_content.lines.len());
+        let node_index = self.rendered_content.lines[self.scroll_offset.min(end)..end]
+            .iter()
+            .find_map(|line| match line.line_type {
+                LineType::Summary { node_index, .. } => Some(node_index),
+                _ => None,
+            });
+        match node_index {
+            Some(node_index) => {
+                self.toggle_collapsible(node_index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// The block whose summary line is under the given cell
+    pub fn check_summary_click(&self, x: u16, y: u16) -> Option<usize> {
+        let text_area = self.last_inner_text_area?;
+        if x < text_area.x
+            || x >= text_area.x + text_area.width
+            || y < text_area.y
+            || y >= text_area.y + text_area.height
+        {
+            return None;
+        }
+        let clicked_line = self.scroll_offset + (y - text_area.y) as usize;
+        match self.rendered_content.lines.get(clicked_line)?.line_type {
+            LineType::Summary { node_index, .. } => Some(node_index),
+            _ => None,
+        }
+    }
+
+    /// First summary line of each collapsed block whose hidden content
+    /// contains the lowercased query
+    pub(super) fn collapsed_match_lines(&self, query: &str) -> Vec<usize> {
+        let mut seen = Vec::new();
+        let mut lines = Vec::new();
+        for (line_idx, line) in self.rendered_content.lines.iter().enumerate() {
+            if let LineType::Summary {
+                node_index,
+                expanded: false,
+            } = line.line_type
+                && !seen.contains(&node_index)
+            {
+                seen.push(node_index);
+                if self
+                    .collapsed_texts
+                    .get(&node_index)
+                    .is_some_and(|text| !match_ranges(text, query).is_empty())
+                {
+                    lines.push(line_idx);
+                }
+            }
+        }
+        lines
+    }
+
+    /// Expand the collapsed block whose summary is on `line`, if any
+    pub(super) fn expand_collapsible_at(&mut self, line: usize) -> bool {
+        match self.rendered_content.lines.get(line).map(|l| &l.line_type) {
+            Some(LineType::Summary {
+                node_index,
+                expanded: false,
+            }) => {
+                self.expand_collapsible(*node_index);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Expand the `index`-th top-level collapsible block of the chapter
+    pub(super) fn expand_nth_collapsible(&mut self, index: usize) {
+        let node_index = self.markdown_document.as_ref().and_then(|doc| {
+            doc.blocks
+                .iter()
+                .enumerate()
+                .filter(|(_, node)| matches!(node.block, Block::Collapsible { .. }))
+                .nth(index)
+                .map(|(node_index, _)| node_index)
+        });
+        if let Some(node_index) = node_index {
+            self.expand_collapsible(node_index);
+        }
+    }
+
+    /// Summary line of the `index`-th top-level collapsible block
+    pub(super) fn nth_collapsible_line(&self, index: usize) -> Option<usize> {
+        let mut seen = Vec::new();
+        for (line_idx, line) in self.rendered_content.lines.iter().enumerate() {
+            if let LineType::Summary { node_index, .. } = line.line_type
+                && !seen.contains(&node_index)
+            {
+                if seen.len() == index {
+                    return Some(line_idx);
+                }
+                seen.push(node_index);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::search::SearchablePanel;
+    use crate::test_utils::test_helpers::create_test_terminal;
+    use crate::theme::current_theme;
+
+    fn draw(reader: &mut MarkdownTextReader) {
+        let mut terminal = create_test_terminal(60, 20);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+    }
+
+    fn shows(reader: &MarkdownTextReader, text: &str) -> bool {
+        reader
+            .rendered_content
+            .lines
+            .iter()
+            .any(|line| line.raw_text.contains(text))
+    }
+
+    #[test]
+    fn test_collapsed_details_expand_on_toggle_and_on_search() {
+        let html = "<p>Intro.</p>\
+                    <details><summary>Solution</summary><p>The butler did it.</p></details>\
+                    <p>Outro.</p>";
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(html, None);
+        draw(&mut reader);
+        assert!(shows(&reader, "▸ Solution"));
+        assert!(!shows(&reader, "butler"));
+
+        assert!(reader.toggle_visible_collapsible());
+        draw(&mut reader);
+        assert!(shows(&reader, "▾ Solution"));
+        assert!(shows(&reader, "  The butler did it."));
+
+        reader.toggle_visible_collapsible();
+        draw(&mut reader);
+        assert!(!shows(&reader, "butler"));
+
+        // Searching finds the hidden text at the summary, and jumping to it
+        // expands the block
+        reader.start_search();
+        reader.update_search_query("butler");
+        assert_eq!(reader.search_state.matches.len(), 1);
+        reader.confirm_search();
+        reader.next_match();
+        draw(&mut reader);
+        assert!(shows(&reader, "The butler did it."));
+        let line = reader.current_search_match_line().unwrap();
+        assert!(
+            reader.rendered_content.lines[line]
+                .raw_text
+                .contains("butler")
+        );
+    }
+}
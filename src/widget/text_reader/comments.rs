@@ -1,5 +1,6 @@
 use super::types::*;
-use crate::comments::{BookComments, Comment, CommentTarget};
+use crate::comments::{AnchorStatus, BookComments, Comment, CommentTarget, text_fingerprint};
+use crate::i18n;
 use crate::markdown_text_reader::text_selection::SelectionPoint;
 use crate::theme::Base16Palette;
 use log::{debug, warn};
@@ -16,14 +17,28 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.rebuild_chapter_comments();
     }
 
-    /// Rebuild the comment lookup for the current chapter
+    /// Rebuild the comment lookup for the current chapter, re-anchoring
+    /// comments whose paragraph moved and hiding the ones that are orphaned
     pub fn rebuild_chapter_comments(&mut self) {
         self.current_chapter_comments.clear();
 
         if let Some(chapter_file) = &self.current_chapter_file {
             if let Some(comments_arc) = &self.book_comments {
-                if let Ok(comments) = comments_arc.lock() {
+                if let Ok(mut comments) = comments_arc.lock() {
+                    if let Some(doc) = &self.markdown_document {
+                        let node_texts: Vec<String> =
+                            doc.blocks.iter().map(|node| node.plain_text()).collect();
+                        if let Err(e) =
+                            comments.relocate_chapter_comments(chapter_file, &node_texts)
+                        {
+                            warn!("Failed to relocate comments: {e}");
+                        }
+                    }
+
                     for comment in comments.get_chapter_comments(chapter_file) {
+                        if comments.anchor_status(comment) == AnchorStatus::Orphaned {
+                            continue;
+                        }
                         self.current_chapter_comments
                             .entry(comment.node_index())
                             .or_default()
@@ -36,46 +51,129 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
     /// Start editing an existing comment
     pub fn start_editing_comment(&mut self, chapter_href: String, target: CommentTarget) -> bool {
-        if let Some(comments_arc) = &self.book_comments {
-            if let Ok(comments) = comments_arc.lock() {
-                let existing_content = comments
+        // The lock is let go before the view is scrolled to the textarea
+        let existing_content = self
+            .book_comments
+            .as_ref()
+            .and_then(|comments_arc| comments_arc.lock().ok())
+            .and_then(|comments| {
+                comments
                     .get_node_comments(&chapter_href, target.node_index())
                     .iter()
                     .find(|c| c.target == target)
-                    .map(|c| c.content.clone());
-
-                if let Some(content) = existing_content {
-                    let comment_start_line = self.find_comment_visual_line(&chapter_href, &target);
-
-                    if let Some(start_line) = comment_start_line {
-                        let mut textarea = TextArea::default();
-                        let lines: Vec<&str> = content.split('\n').collect();
-                        for (idx, line) in lines.iter().enumerate() {
-                            textarea.insert_str(line);
-                            if idx < lines.len().saturating_sub(1) {
-                                textarea.insert_newline();
-                            }
-                        }
+                    .map(|c| c.content.clone())
+            });
+        let Some(content) = existing_content else {
+            return false;
+        };
+        let Some(start_line) = self.find_comment_visual_line(&chapter_href, &target) else {
+            return false;
+        };
 
-                        self.comment_input.textarea = Some(textarea);
-                        self.comment_input.target_node_index = Some(target.node_index());
-                        self.comment_input.target_line = Some(start_line);
-                        self.comment_input.target = Some(target.clone());
-                        self.comment_input.edit_mode = Some(CommentEditMode::Editing {
-                            chapter_href,
-                            target,
-                        });
+        let mut textarea = TextArea::default();
+        let lines: Vec<&str> = content.split('\n').collect();
+        for (idx, line) in lines.iter().enumerate() {
+            textarea.insert_str(line);
+            if idx < lines.len().saturating_sub(1) {
+                textarea.insert_newline();
+            }
+        }
 
-                        self.cache_generation += 1;
+        self.comment_input.textarea = Some(textarea);
+        self.comment_input.target_node_index = Some(target.node_index());
+        self.comment_input.target_line = Some(start_line);
+        self.comment_input.target = Some(target.clone());
+        self.comment_input.edit_mode = Some(CommentEditMode::Editing {
+            chapter_href,
+            target,
+        });
 
-                        self.text_selection.clear_selection();
-                        return true;
-                    }
-                }
-            }
+        self.cache_generation += 1;
+
+        self.text_selection.clear_selection();
+        self.keep_comment_input_in_view();
+        true
+    }
+
+    /// Start a note on the image `src`, or edit the one it has
+    pub fn start_image_comment(&mut self, src: &str) -> bool {
+        let (Some(chapter_href), Some(&block)) = (
+            self.current_chapter_file.clone(),
+            self.figure_blocks.get(src),
+        ) else {
+            return false;
+        };
+        let target = CommentTarget::Image {
+            paragraph_index: block,
+            src: src.to_string(),
+        };
+        if self.start_editing_comment(chapter_href, target.clone()) {
+            return true;
         }
 
-        false
+        let Some(image_end) = self
+            .embedded_images
+            .borrow()
+            .get(src)
+            .map(|image| image.lines_before_image + image.height_cells as usize)
+        else {
+            return false;
+        };
+        let mut textarea = TextArea::default();
+        textarea.set_placeholder_text(i18n::tr("comments.placeholder_figure"));
+
+        self.comment_input.textarea = Some(textarea);
+        self.comment_input.target = Some(target);
+        self.comment_input.target_node_index = Some(block);
+        self.comment_input.target_line = Some(image_end);
+        self.comment_input.edit_mode = Some(CommentEditMode::Creating);
+        self.text_selection.clear_selection();
+        self.keep_comment_input_in_view();
+        self.cache_generation += 1;
+        true
+    }
+
+    /// Source of the first image in view, for `a` without a selection
+    pub fn visible_image_src(&self) -> Option<String> {
+        self.visible_lines().find_map(|line| match &line.line_type {
+            LineType::ImagePlaceholder { src } => Some(src.clone()),
+            _ => None,
+        })
+    }
+
+    /// The first image note marker in view, opened by Enter
+    pub fn visible_image_note(&self) -> Option<CommentSelection> {
+        self.visible_lines().find_map(Self::image_note_of)
+    }
+
+    /// The image note whose marker is under the given cell
+    pub fn check_image_note_click(&self, x: u16, y: u16) -> Option<CommentSelection> {
+        let text_area = self.last_inner_text_area?;
+        if x < text_area.x
+            || x >= text_area.x + text_area.width
+            || y < text_area.y
+            || y >= text_area.y + text_area.height
+        {
+            return None;
+        }
+        let clicked_line = self.scroll_offset + (y - text_area.y) as usize;
+        Self::image_note_of(self.rendered_content.lines.get(clicked_line)?)
+    }
+
+    fn visible_lines(&self) -> impl Iterator<Item = &RenderedLine> {
+        let lines = &self.rendered_content.lines;
+        let end = (self.scroll_offset + self.visible_height).min(lines.len());
+        lines[self.scroll_offset.min(end)..end].iter()
+    }
+
+    fn image_note_of(line: &RenderedLine) -> Option<CommentSelection> {
+        match &line.line_type {
+            LineType::Comment {
+                chapter_href,
+                target: target @ CommentTarget::Image { .. },
+            } => Some((chapter_href.clone(), target.clone())),
+            _ => None,
+        }
     }
 
     pub fn start_comment_input(&mut self) -> bool {
@@ -91,7 +189,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             let (norm_start, norm_end) = self.normalize_selection_points(&start, &end);
             if let Some(target) = self.compute_selection_target(&norm_start, &norm_end) {
                 let mut textarea = TextArea::default();
-                textarea.set_placeholder_text("Type your comment here...");
+                textarea.set_placeholder_text(i18n::tr("comments.placeholder"));
 
                 self.comment_input.textarea = Some(textarea);
                 self.comment_input.target = Some(target.clone());
@@ -102,6 +200,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 self.comment_input.edit_mode = Some(CommentEditMode::Creating);
 
                 self.text_selection.clear_selection();
+                self.keep_comment_input_in_view();
 
                 return true;
             }
@@ -185,6 +284,8 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 }
                 _ => {
                     textarea.input(input);
+                    // A new line grows the textarea, which may now run off the view
+                    self.keep_comment_input_in_view();
                     return true;
                 }
             }
@@ -215,11 +316,17 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                                     debug!("Updated comment: {comment_text}");
                                 }
                             } else if let Some(chapter_file) = &self.current_chapter_file {
+                                let fingerprint = self
+                                    .markdown_document
+                                    .as_ref()
+                                    .and_then(|doc| doc.blocks.get(target.node_index()))
+                                    .and_then(|node| text_fingerprint(&node.plain_text()));
                                 let comment = Comment {
                                     chapter_href: chapter_file.clone(),
                                     target,
                                     content: comment_text.clone(),
                                     updated_at: Utc::now(),
+                                    fingerprint,
                                 };
 
                                 if let Err(e) = comments.add_comment(comment) {
@@ -236,12 +343,39 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
         self.rebuild_chapter_comments();
 
+        // Back to where the reader was before the textarea needed room
+        if let Some(offset) = self.comment_input.restore_scroll_offset {
+            self.scroll_offset = offset;
+        }
+
         // Clear comment input state AFTER rebuilding so the re-render doesn't try to show textarea
         self.comment_input.clear();
 
         self.cache_generation += 1;
     }
 
+    /// Scroll as little as needed to show the whole textarea under its
+    /// target line, with the scroll margin below it. The offset from before
+    /// the first such scroll is kept to go back to after saving.
+    fn keep_comment_input_in_view(&mut self) {
+        let Some(target_line) = self.comment_input.target_line else {
+            return;
+        };
+        let bottom = target_line + self.comment_input.height() + self.scroll_margin();
+        if target_line >= self.scroll_offset && bottom <= self.scroll_offset + self.visible_height {
+            return;
+        }
+        // Without room for all of it, the textarea goes to the top
+        let offset = bottom
+            .saturating_sub(self.visible_height)
+            .min(target_line)
+            .min(self.get_max_scroll_offset());
+        self.comment_input
+            .restore_scroll_offset
+            .get_or_insert(self.scroll_offset);
+        self.scroll_offset = offset;
+    }
+
     /// Check if we're currently in comment input mode
     pub fn is_comment_input_active(&self) -> bool {
         self.comment_input.is_active()
@@ -385,7 +519,8 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             return;
         }
 
-        let comment_header = format!("Note // {}", comment.updated_at.format("%m-%d-%y %H:%M"));
+        let time = i18n::format_comment_time(comment.updated_at.naive_utc());
+        let comment_header = i18n::tr_args("comments.note_header", &[("time", &time)]);
 
         lines.push(RenderedLine {
             spans: vec![Span::styled(
@@ -451,6 +586,54 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 
+    /// "◆ 1 note" under an image with notes; selecting it and pressing `d`
+    /// deletes the note, Enter or a click opens it
+    pub(super) fn render_image_note_marker(
+        &mut self,
+        src: &str,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        palette: &Base16Palette,
+    ) {
+        let Some(block) = self.figure_blocks.get(src) else {
+            return;
+        };
+        let notes: Vec<&Comment> = self
+            .current_chapter_comments
+            .get(block)
+            .into_iter()
+            .flatten()
+            .filter(|comment| comment.target.image_src() == Some(src))
+            .filter(|comment| !self.is_editing_this_comment(comment))
+            .collect();
+        let Some(first) = notes.first() else {
+            return;
+        };
+
+        let marker = match notes.len() {
+            1 => i18n::tr("comments.figure_notes.one").to_string(),
+            count => i18n::tr_args("comments.figure_notes.other", &[("count", &count)]),
+        };
+        lines.push(RenderedLine {
+            spans: vec![Span::styled(
+                marker.clone(),
+                RatatuiStyle::default().fg(palette.base_0e),
+            )],
+            raw_text: marker.clone(),
+            line_type: LineType::Comment {
+                chapter_href: first.chapter_href.clone(),
+                target: first.target.clone(),
+            },
+            link_nodes: vec![],
+            node_anchor: None,
+            node_index: None,
+            code_line: None,
+            inline_code_comments: Vec::new(),
+        });
+        self.raw_text_lines.push(marker);
+        *total_height += 1;
+    }
+
     fn normalize_selection_points(
         &self,
         start: &SelectionPoint,
@@ -539,3 +722,73 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         Some((start_offset, end_offset.min(total_len)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::test_utils::test_helpers::{capture_terminal_state, create_test_terminal};
+    use crate::theme::current_theme;
+    use tui_textarea::{Input, Key};
+
+    #[test]
+    fn test_comment_input_near_the_bottom_scrolls_into_view() {
+        let html: String = (1..=20).map(|i| format!("<p>Paragraph {i}</p>")).collect();
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(&html, None);
+        let mut terminal = create_test_terminal(40, 14);
+        let mut draw = |reader: &mut MarkdownTextReader| {
+            terminal
+                .draw(|f| reader.render(f, f.area(), 0, 1, current_theme(), true))
+                .unwrap();
+            capture_terminal_state(&terminal)
+        };
+        draw(&mut reader);
+
+        // The paragraph on the second-to-last visible line
+        let line = reader
+            .rendered_content
+            .lines
+            .iter()
+            .position(|line| line.raw_text == "Paragraph 9")
+            .unwrap();
+        let original_offset = line + 2 - reader.visible_height;
+        reader.scroll_offset = original_offset;
+        reader.text_selection.start_selection(line, 0);
+        reader.text_selection.update_selection(line, 8);
+        reader.text_selection.end_selection();
+        assert!(reader.start_comment_input());
+        // The whole textarea shows, with the scroll margin under it
+        assert_eq!(
+            draw(&mut reader),
+            [
+                "┌Chapter 0/1───────────────────────────┐",
+                "│                                      │",
+                "│ Paragraph 8                          │",
+                "│                                      │",
+                "│ Paragraph 9                          │",
+                "│ ┌ Add Note ────────────────────────┐ │",
+                "│ │ Type your comment here...        │ │",
+                "│ │                                  │ │",
+                "│ │                                  │ │",
+                "│ └──────────────────────────────────┘ │",
+                "│                                      │",
+                "│ Paragraph 10                         │",
+                "│                                      │",
+                "└───────────────────────────────── 62% ┘",
+            ]
+            .join("\n")
+        );
+
+        // Typing more lines scrolls further; saving goes back
+        let scrolled = reader.scroll_offset;
+        for _ in 0..3 {
+            reader.handle_comment_input(Input {
+                key: Key::Enter,
+                ..Default::default()
+            });
+        }
+        assert_eq!(reader.scroll_offset, scrolled + 1);
+        reader.save_comment();
+        assert_eq!(reader.scroll_offset, original_offset);
+    }
+}
@@ -0,0 +1,283 @@
+use super::types::*;
+use crate::i18n;
+use crate::markdown::Document;
+use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+use crate::parsing::plain_text_fallback::{has_visible_blocks, plain_text_document};
+use crate::parsing::private_use::GlyphMap;
+use crate::theme::Base16Palette;
+use log::{error, warn};
+use ratatui::style::{Modifier, Style as RatatuiStyle};
+use ratatui::text::Span;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::time::Instant;
+
+/// Chapters whose raw HTML is larger than this are converted off the UI
+/// thread, so opening one doesn't freeze the app
+pub const LARGE_CHAPTER_BYTES: usize = 1024 * 1024;
+
+const SPINNER: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// What converting a chapter ran into, told once the chapter is shown
+#[derive(Debug, Clone, Copy, Default)]
+pub(super) struct ConversionNotes {
+    /// Private-use characters without a known equivalent
+    pub(super) unknown_glyphs: usize,
+    /// The HTML converted to nothing, so its text is shown stripped of tags
+    pub(super) plain_text: bool,
+}
+
+/// Convert a chapter's HTML. HTML that has text but converts to nothing
+/// is shown as plain text instead of a blank chapter.
+pub(super) fn convert_chapter(
+    raw_html: &str,
+    dehyphenate: bool,
+    strip_invisible: bool,
+    glyph_map: Arc<GlyphMap>,
+) -> (Document, ConversionNotes) {
+    let mut converter = HtmlToMarkdownConverter::new()
+        .with_dehyphenation(dehyphenate)
+        .with_invisible_stripping(strip_invisible)
+        .with_glyph_map(glyph_map);
+    let mut doc = converter.convert(raw_html);
+    let mut notes = ConversionNotes {
+        unknown_glyphs: converter.unknown_glyphs(),
+        plain_text: false,
+    };
+    if !has_visible_blocks(&doc)
+        && let Some(plain) = plain_text_document(raw_html)
+    {
+        warn!("Chapter HTML converted to nothing, showing its plain text");
+        doc = plain;
+        notes.plain_text = true;
+    }
+    (doc, notes)
+}
+
+/// A large chapter being converted in the background
+pub(super) struct ChapterConversion {
+    /// Cached under this chapter once converted
+    chapter: Option<usize>,
+    receiver: Receiver<(Document, ConversionNotes)>,
+    started: Instant,
+}
+
+impl ChapterConversion {
+    pub(super) fn start(
+        chapter: Option<usize>,
+        raw_html: String,
+        dehyphenate: bool,
+        strip_invisible: bool,
+        glyph_map: Arc<GlyphMap>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        std::thread::spawn(move || {
+            let converted = convert_chapter(&raw_html, dehyphenate, strip_invisible, glyph_map);
+            // Dropped unread when the reader moved to another chapter meanwhile
+            let _ = sender.send(converted);
+        });
+        Self {
+            chapter,
+            receiver,
+            started: Instant::now(),
+        }
+    }
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Whether a large chapter is still being converted
+    pub fn is_converting(&self) -> bool {
+        self.conversion.is_some()
+    }
+
+    /// Returns true when a chapter converted in the background has been
+    /// swapped in and needs a redraw
+    pub fn check_for_converted_chapter(&mut self) -> bool {
+        let Some(conversion) = &self.conversion else {
+            return false;
+        };
+        match conversion.receiver.try_recv() {
+            Ok((doc, notes)) => {
+                let doc = Arc::new(doc);
+                if let Some(chapter) = conversion.chapter {
+                    self.chapter_cache.insert(chapter, doc.clone(), notes);
+                }
+                self.conversion = None;
+                self.markdown_document = Some(doc);
+                self.conversion_notes = notes;
+                self.cache_generation += 1;
+                // The comments were looked up before there was a document
+                self.rebuild_chapter_comments();
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                error!("Chapter conversion stopped without a result");
+                self.conversion = None;
+                true
+            }
+        }
+    }
+
+    /// Chapter title and a spinner, shown until the conversion finishes
+    pub(super) fn render_conversion_placeholder(&mut self, palette: &Base16Palette) {
+        let Some(conversion) = &self.conversion else {
+            return;
+        };
+        let frame = (conversion.started.elapsed().as_millis() / 100) as usize % SPINNER.len();
+        let mut lines = Vec::new();
+        if let Some(title) = &self.chapter_title {
+            lines.push(RenderedLine {
+                spans: vec![Span::styled(
+                    title.clone(),
+                    RatatuiStyle::default()
+                        .fg(palette.base_0a)
+                        .add_modifier(Modifier::BOLD),
+                )],
+                raw_text: title.clone(),
+                ..RenderedLine::empty()
+            });
+            lines.push(RenderedLine::empty());
+        }
+        let status = i18n::tr_args("content.large_chapter", &[("spinner", &SPINNER[frame])]);
+        lines.push(RenderedLine {
+            spans: vec![Span::styled(
+                status.clone(),
+                RatatuiStyle::default().fg(palette.base_03),
+            )],
+            raw_text: status,
+            ..RenderedLine::empty()
+        });
+
+        self.rendered_content = RenderedContent {
+            total_height: lines.len(),
+            lines,
+            generation: self.cache_generation,
+        };
+        self.total_wrapped_lines = self.rendered_content.total_height;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::comments::{AnchorStatus, BookComments, Comment, CommentTarget, text_fingerprint};
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::test_utils::test_helpers::create_test_terminal;
+    use crate::theme::current_theme;
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    /// The app's tick, which no step on the UI thread may exceed
+    const TICK: Duration = Duration::from_millis(50);
+
+    #[test]
+    fn test_large_chapter_converts_without_blocking_the_ui_thread() {
+        let mut html = String::from("<h1>Appendix</h1>");
+        let mut paragraph = 0;
+        while html.len() <= 2 * LARGE_CHAPTER_BYTES {
+            html.push_str(&format!(
+                "<p>Paragraph {paragraph} of the appendix, with words to convert.</p>"
+            ));
+            paragraph += 1;
+        }
+
+        let mut reader = MarkdownTextReader::new();
+        let mut terminal = create_test_terminal(80, 24);
+        reader.set_content_from_string(&html, Some("Appendix".to_string()));
+        assert!(reader.is_converting());
+
+        // Navigating away drops the conversion; its result is never applied
+        reader.set_content_from_string("<p>Short chapter.</p>", None);
+        assert!(!reader.is_converting());
+        assert!(!reader.check_for_converted_chapter());
+
+        let started = Instant::now();
+        reader.set_content_from_string(&html, Some("Appendix".to_string()));
+        assert!(started.elapsed() < TICK);
+        reader.restore_to_node_index(paragraph / 2);
+
+        let deadline = Instant::now() + Duration::from_secs(120);
+        loop {
+            let tick = Instant::now();
+            let converted = reader.check_for_converted_chapter();
+            if converted {
+                break;
+            }
+            terminal
+                .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+                .unwrap();
+            assert!(tick.elapsed() < TICK, "a tick took {:?}", tick.elapsed());
+            assert!(
+                reader.rendered_content.lines[2]
+                    .raw_text
+                    .ends_with("Large chapter, rendering…")
+            );
+            assert!(Instant::now() < deadline, "conversion never finished");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!reader.is_converting());
+
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+        assert!(reader.rendered_content.lines.len() > paragraph);
+        // The node restore requested during the conversion still applies
+        assert!(reader.get_scroll_offset() > 0);
+    }
+
+    #[test]
+    fn test_comments_are_relocated_once_a_large_chapter_is_converted() {
+        let mut html = String::from("<h1>Appendix</h1>");
+        let mut paragraph = 0;
+        while html.len() <= 2 * LARGE_CHAPTER_BYTES {
+            html.push_str(&format!("<p>Paragraph {paragraph} of the appendix.</p>"));
+            paragraph += 1;
+        }
+
+        let temp_dir = TempDir::new().unwrap();
+        let book_path = temp_dir.path().join("book.epub");
+        std::fs::write(&book_path, "fake epub content").unwrap();
+        let mut book_comments =
+            BookComments::new_with_custom_dir(&book_path, &temp_dir.path().join("comments"))
+                .unwrap();
+        // Saved against an older edition, where the paragraph came first
+        book_comments
+            .add_comment(Comment {
+                chapter_href: "appendix.xhtml".to_string(),
+                target: CommentTarget::Paragraph {
+                    paragraph_index: 0,
+                    word_range: None,
+                },
+                content: "note".to_string(),
+                updated_at: Utc::now(),
+                fingerprint: text_fingerprint("Paragraph 7 of the appendix."),
+            })
+            .unwrap();
+        let book_comments = Arc::new(Mutex::new(book_comments));
+
+        let mut reader = MarkdownTextReader::new();
+        reader.set_book_comments(book_comments.clone());
+        reader.set_content_from_string(&html, Some("Appendix".to_string()));
+        reader.set_current_chapter_file(Some("appendix.xhtml".to_string()));
+        assert!(reader.is_converting());
+
+        let deadline = Instant::now() + Duration::from_secs(120);
+        while !reader.check_for_converted_chapter() {
+            assert!(Instant::now() < deadline, "conversion never finished");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // The heading is node 0, so the paragraph is node 8
+        assert!(!reader.current_chapter_comments.contains_key(&0));
+        assert_eq!(reader.current_chapter_comments[&8].len(), 1);
+        let comments = book_comments.lock().unwrap();
+        let relocated = comments.get_node_comments("appendix.xhtml", 8);
+        assert_eq!(
+            comments.anchor_status(relocated[0]),
+            AnchorStatus::Relocated
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::types::*;
-use crate::images::book_images::BookImages;
+use crate::images::book_images::{BookImages, MIN_FIGURE_SIZE};
 use crate::markdown::{Block as MarkdownBlock, Inline, Node, TextOrInline};
 use crate::ratatui_image::picker::Picker;
 use crate::types::LinkInfo;
@@ -16,7 +16,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         use MarkdownBlock::*;
         match &node.block {
             Paragraph { content } => self.extract_images_from_text(content, book_images),
-            Quote { content } => {
+            Quote { content, .. } => {
                 let mut vec = Vec::new();
                 for inner_node in content {
                     vec.append(&mut self.extract_images_from_node(inner_node, book_images));
@@ -66,7 +66,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
                 let chapter_path = self.current_chapter_file.as_deref();
                 match book_images.get_image_size_with_context(url, chapter_path) {
-                    Some((w, h)) if w >= 64 && h >= 64 => {
+                    Some((w, h)) if w >= MIN_FIGURE_SIZE && h >= MIN_FIGURE_SIZE => {
                         let height_cells = EmbeddedImage::height_in_cells(w, h);
                         self.embedded_images.borrow_mut().insert(
                             url.clone(),
@@ -99,6 +99,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     }
 
     pub fn preload_image_dimensions(&mut self, book_images: &BookImages) {
+        if !self.render_images {
+            return;
+        }
+
         if let Some(doc) = self.markdown_document.clone() {
             self.background_loader.cancel_loading();
 
@@ -163,6 +167,14 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         any_loaded
     }
 
+    /// Whether some of the chapter's images are still being loaded in the background
+    pub fn has_images_loading(&self) -> bool {
+        self.embedded_images
+            .borrow()
+            .values()
+            .any(|image| matches!(image.state, ImageLoadState::Loading))
+    }
+
     pub fn check_image_click(&self, x: u16, y: u16) -> Option<String> {
         // Use the inner text area if available
         let text_area = self.last_inner_text_area?;
@@ -192,10 +204,88 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         None
     }
 
+    /// Images in the chapter ordered by the line they start on, one entry
+    /// per occurrence so an image used twice is visited twice
+    pub fn image_positions(&self) -> Vec<(usize, String)> {
+        let mut positions = Vec::new();
+        let mut previous: Option<&str> = None;
+        for (idx, line) in self.rendered_content.lines.iter().enumerate() {
+            let src = match &line.line_type {
+                LineType::ImagePlaceholder { src } => Some(src.as_str()),
+                _ => None,
+            };
+            // A placeholder spans several lines; it starts where its src does
+            if let Some(src) = src
+                && previous != Some(src)
+            {
+                positions.push((idx, src.to_string()));
+            }
+            previous = src;
+        }
+        positions
+    }
+
+    pub fn scroll_to_next_image(&mut self) -> Option<ImageJump> {
+        self.complete_layout();
+        let positions = self.image_positions();
+        let max_offset = self.get_max_scroll_offset();
+        let index = positions
+            .iter()
+            .position(|(line, _)| (*line).min(max_offset) > self.scroll_offset)
+            .unwrap_or(0);
+        self.scroll_to_image(positions, index)
+    }
+
+    pub fn scroll_to_prev_image(&mut self) -> Option<ImageJump> {
+        self.complete_layout();
+        let positions = self.image_positions();
+        let max_offset = self.get_max_scroll_offset();
+        let index = positions
+            .iter()
+            .rposition(|(line, _)| (*line).min(max_offset) < self.scroll_offset)
+            .unwrap_or(positions.len().saturating_sub(1));
+        self.scroll_to_image(positions, index)
+    }
+
+    fn scroll_to_image(
+        &mut self,
+        mut positions: Vec<(usize, String)>,
+        index: usize,
+    ) -> Option<ImageJump> {
+        let total = positions.len();
+        if index >= total {
+            return None;
+        }
+        let (line, src) = positions.swap_remove(index);
+        self.scroll_offset = line.min(self.get_max_scroll_offset());
+        Some(ImageJump { src, index, total })
+    }
+
     pub fn get_image_picker(&self) -> Option<&Picker> {
         self.image_picker.as_ref()
     }
 
+    /// Ask the terminal for its image protocol again, after resuming in a
+    /// terminal that may not be the one suspended from, and rebuild the
+    /// loaded images for it
+    pub fn requery_image_picker(&mut self) {
+        self.set_image_picker(Self::query_image_picker());
+    }
+
+    /// Draw images with `picker`, rebuilding the loaded ones for it
+    pub fn set_image_picker(&mut self, picker: Option<Picker>) {
+        self.image_picker = picker;
+        for embedded_image in self.embedded_images.borrow_mut().values_mut() {
+            if let ImageLoadState::Loaded { image, protocol } = &mut embedded_image.state {
+                match self.image_picker {
+                    Some(ref picker) => *protocol = picker.new_resize_protocol((**image).clone()),
+                    None => embedded_image.state = ImageLoadState::Unsupported,
+                }
+            }
+        }
+        self.cache_generation += 1;
+    }
+
     pub fn get_loaded_image(&self, image_src: &str) -> Option<Arc<DynamicImage>> {
         self.embedded_images
             .borrow()
@@ -206,6 +296,11 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             })
     }
 
+    /// Caption of the figure holding the image, if it has one
+    pub fn image_caption(&self, image_src: &str) -> Option<String> {
+        self.figure_captions.get(image_src).cloned()
+    }
+
     //todo: there should be a better way
     pub fn get_link_at_position(&self, line: usize, column: usize) -> Option<&LinkInfo> {
         self.links
@@ -0,0 +1,205 @@
+//! Layout of chapters too long to lay out in one frame, such as whole books
+//! in a single spine item.
+//!
+//! Their blocks are laid out in document order only as far as the viewport
+//! needs plus a buffer, and the rest follows a slice per tick. Lines are only
+//! ever appended, so anchors, images, tables and links are recorded for each
+//! slice as it is added and stay valid; the line count is estimated from the
+//! blocks laid out so far until the last one is.
+
+use super::types::RenderedContent;
+use crate::markdown::Document;
+use crate::theme::Base16Palette;
+use std::sync::Arc;
+
+/// Chapters with more top-level blocks than this are laid out progressively
+pub(super) const PROGRESSIVE_LAYOUT_MIN_BLOCKS: usize = 2_000;
+/// Blocks laid out per tick once the viewport is covered
+const BLOCKS_PER_TICK: usize = 1_000;
+/// Blocks laid out at a time while catching up with a target
+const BLOCKS_PER_STEP: usize = 100;
+/// Lines laid out past the bottom of the viewport
+const BUFFER_LINES: usize = 200;
+/// Where images not laid out yet are kept, well below any line
+const UNPLACED_LINE: usize = usize::MAX / 2;
+
+/// What's needed to continue laying out a long chapter
+pub(super) struct PendingLayout {
+    doc: Arc<Document>,
+    next_block: usize,
+    width: usize,
+    palette: Base16Palette,
+    is_focused: bool,
+}
+
+impl crate::markdown_text_reader::MarkdownTextReader {
+    /// Lay the document out from the top: all of it when short, otherwise
+    /// as far as the viewport needs
+    pub(super) fn start_layout(
+        &mut self,
+        doc: Arc<Document>,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        self.pending_layout = None;
+        if doc.blocks.len() <= PROGRESSIVE_LAYOUT_MIN_BLOCKS {
+            self.rendered_content =
+                self.render_document_to_lines(doc.as_ref(), width, palette, is_focused);
+            self.total_wrapped_lines = self.rendered_content.total_height;
+            return;
+        }
+
+        self.clear_layout_state();
+        for image in self.embedded_images.borrow_mut().values_mut() {
+            image.lines_before_image = UNPLACED_LINE;
+        }
+        self.rendered_content = RenderedContent {
+            lines: Vec::new(),
+            total_height: 0,
+            generation: self.cache_generation,
+        };
+        self.pending_layout = Some(PendingLayout {
+            doc,
+            next_block: 0,
+            width,
+            palette: palette.clone(),
+            is_focused,
+        });
+        self.extend_layout_to_viewport();
+    }
+
+    /// Lay out blocks until the viewport and the buffer below it are covered
+    pub(super) fn extend_layout_to_viewport(&mut self) {
+        let needed = self.scroll_offset + self.visible_height + BUFFER_LINES;
+        self.extend_layout_while(|reader| reader.rendered_content.lines.len() < needed);
+    }
+
+    /// Lay out blocks up to and including `node_index`
+    pub(super) fn extend_layout_to_node(&mut self, node_index: usize) {
+        self.extend_layout_while(|reader| {
+            reader
+                .pending_layout
+                .as_ref()
+                .is_some_and(|layout| layout.next_block <= node_index)
+        });
+    }
+
+    /// Lay out blocks while `more` holds and some are left
+    pub(super) fn extend_layout_while(&mut self, more: impl Fn(&Self) -> bool) {
+        while self.pending_layout.is_some() && more(self) {
+            self.extend_layout(BLOCKS_PER_STEP);
+        }
+    }
+
+    /// Lay out the rest of the chapter, for what needs all of it such as
+    /// search or jumping to the end
+    pub fn complete_layout(&mut self) {
+        if self.pending_layout.is_some() {
+            self.extend_layout(usize::MAX);
+        }
+    }
+
+    /// Lay out the next slice of a long chapter; true when lines were added
+    pub fn continue_layout(&mut self) -> bool {
+        if self.pending_layout.is_none()
+            || self.rendered_content.generation != self.cache_generation
+        {
+            return false;
+        }
+        self.extend_layout(BLOCKS_PER_TICK);
+        true
+    }
+
+    pub fn is_layout_complete(&self) -> bool {
+        self.pending_layout.is_none()
+    }
+
+    fn extend_layout(&mut self, blocks: usize) {
+        let Some(layout) = self.pending_layout.take() else {
+            return;
+        };
+        let total_blocks = layout.doc.blocks.len();
+        let end = layout.next_block.saturating_add(blocks).min(total_blocks);
+
+        let mut lines = std::mem::take(&mut self.rendered_content.lines);
+        let mut total_height = self.rendered_content.total_height;
+        self.layout_blocks(
+            &layout.doc,
+            layout.next_block..end,
+            &mut lines,
+            &mut total_height,
+            layout.width,
+            &layout.palette,
+            layout.is_focused,
+        );
+
+        if end == total_blocks {
+            self.finish_layout(&mut lines, &mut total_height, layout.width, &layout.palette);
+            self.total_wrapped_lines = total_height;
+        } else {
+            // Estimate the rest from the blocks laid out so far
+            self.total_wrapped_lines = total_height + (total_blocks - end) * total_height / end;
+            self.pending_layout = Some(PendingLayout {
+                next_block: end,
+                ..layout
+            });
+        }
+        self.rendered_content.lines = lines;
+        self.rendered_content.total_height = total_height;
+        self.scroll_offset = self.scroll_offset.min(self.get_max_scroll_offset());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::conversion::convert_chapter;
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::theme::current_theme;
+
+    #[test]
+    fn test_long_chapter_is_laid_out_as_far_as_needed() {
+        let html: String = (0..50_000)
+            .map(|i| format!("<p id=\"p{i}\">Paragraph {i} of a book kept in one file.</p>"))
+            .collect();
+        let (doc, _) = convert_chapter(&html, false, false, Arc::default());
+        let doc = Arc::new(doc);
+
+        let mut reader = MarkdownTextReader::new();
+        reader.visible_height = 40;
+        reader.start_layout(doc.clone(), 80, current_theme(), true);
+
+        assert!(!reader.is_layout_complete());
+        assert!(reader.rendered_content.lines.len() < 1_000);
+        assert!(reader.total_wrapped_lines > 50_000);
+
+        // Anchors further down are found by laying out up to them
+        let anchor_line = reader.get_anchor_position("p20000").unwrap();
+        assert!(!reader.is_layout_complete());
+        assert_eq!(
+            reader.rendered_content.lines[anchor_line].raw_text.trim(),
+            "Paragraph 20000 of a book kept in one file."
+        );
+
+        while reader.continue_layout() {}
+        assert!(reader.is_layout_complete());
+
+        let full = MarkdownTextReader::new().render_document_to_lines(
+            doc.as_ref(),
+            80,
+            current_theme(),
+            true,
+        );
+        assert_eq!(reader.total_wrapped_lines, full.total_height);
+        assert!(
+            reader
+                .rendered_content
+                .lines
+                .iter()
+                .map(|line| &line.raw_text)
+                .eq(full.lines.iter().map(|line| &line.raw_text))
+        );
+        assert_eq!(reader.get_anchor_position("p20000"), Some(anchor_line));
+    }
+}
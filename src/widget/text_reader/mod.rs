@@ -1,20 +1,38 @@
+mod bidi;
+mod chapter_cache;
+mod collapsible;
 mod comments;
+mod conversion;
 mod images;
+mod layout;
 mod navigation;
+mod progress_bar;
+mod reading_ruler;
 mod rendering;
 mod search;
 mod selection;
 mod text_selection;
 mod types;
 
+pub use conversion::LARGE_CHAPTER_BYTES;
 pub use types::*;
 
 use crate::comments::{BookComments, Comment};
+use crate::glossing::{GlossAnalysis, GlossTargets, Glossary};
+use crate::i18n;
 use crate::images::background_image_loader::BackgroundImageLoader;
 use crate::markdown::Document;
 use crate::markdown_text_reader::text_selection::TextSelection;
+use crate::parsing::private_use::GlyphMap;
+use crate::parsing::typography::typeset_document;
 use crate::ratatui_image::{Resize, StatefulImage, ViewportOptions, picker::Picker};
 use crate::search::SearchState;
+use crate::search_engine::MatchTarget;
+use crate::settings::{
+    ClickAction, DEFAULT_JUMP_HIGHLIGHT_MS, DEFAULT_LIST_INDENT, DEFAULT_MAX_BLANK_LINES,
+    DEFAULT_RULE_GLYPH, DEFAULT_SCROLL_OFF, DEFAULT_TABLE_STACK_WIDTH, JumpHighlightStyle,
+    RubyMode, RuleStyle,
+};
 use crate::theme::Base16Palette;
 use crate::types::LinkInfo;
 use image::{DynamicImage, GenericImageView};
@@ -26,19 +44,27 @@ use ratatui::{
     text::{Line, Span},
     widgets::{Block, Borders, Paragraph},
 };
+use selection::apply_caret;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 pub struct MarkdownTextReader {
     markdown_document: Option<Arc<Document>>,
     rendered_content: RenderedContent,
+    /// Rest of a long chapter still to be laid out below `rendered_content`
+    pending_layout: Option<layout::PendingLayout>,
 
     // Scrolling state
     scroll_offset: usize,
     last_scroll_time: Instant,
     scroll_speed: usize,
+    /// Lines of context kept between a jump target and the viewport edges
+    scroll_off: usize,
+    /// How long a link, anchor or book search jump highlights its line
+    jump_highlight_duration: Duration,
+    jump_highlight_style: JumpHighlightStyle,
 
     // Visual highlighting
     highlight_visual_line: Option<usize>,
@@ -55,7 +81,20 @@ pub struct MarkdownTextReader {
 
     // Text selection
     text_selection: TextSelection,
+    /// Where the mouse last went down in the text, for `*` without a selection
+    last_click: Option<text_selection::SelectionPoint>,
+    /// Where the mouse went down when clicks only place the caret, until
+    /// a drag turns it into a selection
+    press_point: Option<text_selection::SelectionPoint>,
+    click_action: ClickAction,
     raw_text_lines: Vec<String>, // Still needed for clipboard
+    /// Char columns of ruby readings per raw text line, left out of copies
+    ruby_reading_ranges: HashMap<usize, Vec<std::ops::Range<usize>>>,
+    /// Char columns of quote bars per raw text line, left out of copies
+    /// unless `copy_quote_bars` is set
+    quote_bar_ranges: HashMap<usize, Vec<std::ops::Range<usize>>>,
+    /// Display order of right-to-left lines, keyed by line index
+    rtl_layouts: HashMap<usize, bidi::RtlLayout>,
     last_content_area: Option<Rect>,
 
     last_inner_text_area: Option<Rect>, // Track the actual text rendering area
@@ -66,9 +105,53 @@ pub struct MarkdownTextReader {
     image_picker: Option<Picker>,
     embedded_images: RefCell<HashMap<String, EmbeddedImage>>,
     background_loader: BackgroundImageLoader,
+    /// When false, images collapse to a one-line alt text label and are never loaded
+    render_images: bool,
+
+    // Ruby annotations (furigana)
+    ruby_mode: RubyMode,
+    copy_ruby_readings: bool,
+    copy_quote_bars: bool,
+
+    /// Longest run of blank lines kept after wrapping; 0 keeps them all
+    max_blank_lines: usize,
+    /// Below this width tables are stacked `header: value` lists; 0 keeps the grid
+    table_stack_width: usize,
+    /// Columns each nested list steps in, at least to its item's text
+    list_indent: usize,
+    rule_style: RuleStyle,
+    rule_glyph: String,
+    /// Rejoin words hyphenated across line breaks when converting chapters
+    dehyphenate: bool,
+    /// Drop soft hyphens and zero-width spaces when converting chapters
+    strip_invisible: bool,
+    /// Lay chapters out with curly quotes, dashes and ellipses
+    typography: bool,
+    /// Copy typeset text as shown rather than with the typewriter's punctuation
+    copy_typography: bool,
+    /// Copy an image in a selection as `[image: alt]` rather than leave it out
+    copy_image_alt_text: bool,
+    /// The chapter's document and its typeset copy, made once per chapter
+    typeset_document: Option<(Arc<Document>, Arc<Document>)>,
+    glyph_map: Arc<GlyphMap>,
+    /// Background conversion of a large chapter, replaced or dropped when
+    /// another chapter is opened so a stale result is never applied
+    conversion: Option<conversion::ChapterConversion>,
+    chapter_cache: chapter_cache::ChapterCache,
+    /// What converting the chapter shown ran into
+    conversion_notes: conversion::ConversionNotes,
+
+    // Rare word glossing
+    gloss_analysis: Option<GlossAnalysis>,
+    gloss_targets: Option<GlossTargets>,
+    /// Top-level block being rendered, to look up its rare words and
+    /// attach notes to its figures
+    rendering_block: Option<usize>,
 
     // Deferred node index to restore after rendering
     pending_node_restore: Option<usize>,
+    /// Lines past the start of that node to scroll to
+    pending_node_line_offset: isize,
 
     // Raw HTML mode
     show_raw_html: bool,
@@ -80,6 +163,18 @@ pub struct MarkdownTextReader {
     // Tables extracted from AST
     embedded_tables: RefCell<Vec<EmbeddedTable>>,
 
+    /// Top-level collapsible blocks expanded in this chapter, by node index
+    expanded_blocks: HashSet<usize>,
+    /// Lowercased hidden content of the collapsed blocks by node index, for
+    /// in-chapter search
+    collapsed_texts: HashMap<usize, String>,
+    /// Set while rendering a collapsible's content, whose tables are left
+    /// out of `embedded_tables` as the book search doesn't count them
+    in_collapsible_body: bool,
+    /// Jump to the current search match after the next render, once an
+    /// expanded block has shown it
+    pending_search_jump: bool,
+
     /// Map of anchor IDs to their line positions in rendered content
     anchor_positions: HashMap<String, usize>,
 
@@ -91,6 +186,17 @@ pub struct MarkdownTextReader {
 
     /// Pending anchor scroll after chapter navigation
     pending_anchor_scroll: Option<String>,
+    /// Pending anchor the chapter turned out not to have
+    missing_anchor: Option<String>,
+    /// Figure or table of a book search match to show after chapter navigation
+    pending_match_scroll: Option<MatchTarget>,
+    /// Alt text of the chapter's images by source, searched with the text
+    figure_alt_texts: HashMap<String, String>,
+    /// `<figcaption>` of the chapter's images by source, shown in the popup
+    figure_captions: HashMap<String, String>,
+    /// Top-level block holding each of the chapter's images, by source,
+    /// which notes on the image are attached to
+    figure_blocks: HashMap<String, usize>,
 
     /// Last active anchor for maintaining continuous highlighting
     last_active_anchor: Option<String>,
@@ -106,6 +212,25 @@ pub struct MarkdownTextReader {
 
     /// Content margin level (0-20), each level adds 2 columns on each side
     content_margin: u16,
+
+    /// Whole-book progress percent shown next to the chapter percent
+    book_progress: Option<u32>,
+    /// Progress in the TOC section being read, updated with the active section
+    section_progress: Option<SectionProgress>,
+    show_section_progress: bool,
+    /// Draw the chapter progress along the bottom border where true color allows
+    progress_bar: bool,
+    /// Tint a row of the viewport as a reading guide
+    reading_ruler: bool,
+    /// Row of the viewport the ruler is on, the middle one when None
+    reading_ruler_row: Option<u16>,
+
+    /// Title of the following chapter, drawn as a separator below the last
+    /// line when chapters auto-advance
+    next_chapter_title: Option<String>,
+    /// Scroll to the bottom once the new chapter is rendered, for advancing
+    /// upward into the previous chapter
+    pending_scroll_to_end: bool,
 }
 
 impl Default for MarkdownTextReader {
@@ -115,8 +240,10 @@ impl Default for MarkdownTextReader {
 }
 
 impl MarkdownTextReader {
-    pub fn new() -> Self {
-        let image_picker = match Picker::from_query_stdio() {
+    /// Ask the terminal which image protocol it speaks, None when images
+    /// can't be shown
+    fn query_image_picker() -> Option<Picker> {
+        match Picker::from_query_stdio() {
             Ok(mut picker) => {
                 info!(
                     "Image picker initial protocol type: {:?}",
@@ -170,7 +297,11 @@ impl MarkdownTextReader {
                 );
                 None
             }
-        };
+        }
+    }
+
+    pub fn new() -> Self {
+        let image_picker = Self::query_image_picker();
 
         Self {
             markdown_document: None,
@@ -179,9 +310,13 @@ impl MarkdownTextReader {
                 total_height: 0,
                 generation: 0,
             },
+            pending_layout: None,
             scroll_offset: 0,
             last_scroll_time: Instant::now(),
             scroll_speed: 1,
+            scroll_off: DEFAULT_SCROLL_OFF,
+            jump_highlight_duration: Duration::from_millis(DEFAULT_JUMP_HIGHLIGHT_MS),
+            jump_highlight_style: JumpHighlightStyle::default(),
             highlight_visual_line: None,
             highlight_end_time: Instant::now(),
             total_wrapped_lines: 0,
@@ -190,7 +325,13 @@ impl MarkdownTextReader {
             last_width: 0,
             last_focus_state: false,
             text_selection: TextSelection::new(),
+            last_click: None,
+            press_point: None,
+            click_action: ClickAction::default(),
             raw_text_lines: Vec::new(),
+            ruby_reading_ranges: HashMap::new(),
+            quote_bar_ranges: HashMap::new(),
+            rtl_layouts: HashMap::new(),
             last_content_area: None,
             last_inner_text_area: None,
             auto_scroll_active: false,
@@ -198,31 +339,84 @@ impl MarkdownTextReader {
             image_picker,
             embedded_images: RefCell::new(HashMap::new()),
             background_loader: BackgroundImageLoader::new(),
+            render_images: true,
+            ruby_mode: RubyMode::default(),
+            copy_ruby_readings: false,
+            copy_quote_bars: false,
+            max_blank_lines: DEFAULT_MAX_BLANK_LINES,
+            table_stack_width: DEFAULT_TABLE_STACK_WIDTH,
+            list_indent: DEFAULT_LIST_INDENT,
+            rule_style: RuleStyle::default(),
+            rule_glyph: DEFAULT_RULE_GLYPH.to_string(),
+            dehyphenate: false,
+            strip_invisible: false,
+            typography: false,
+            copy_typography: true,
+            copy_image_alt_text: true,
+            typeset_document: None,
+            glyph_map: Arc::default(),
+            conversion: None,
+            chapter_cache: chapter_cache::ChapterCache::default(),
+            conversion_notes: conversion::ConversionNotes::default(),
+            gloss_analysis: None,
+            gloss_targets: None,
+            rendering_block: None,
             pending_node_restore: None,
+            pending_node_line_offset: 0,
             raw_html_content: None,
             show_raw_html: false,
             links: Vec::new(),
             embedded_tables: RefCell::new(Vec::new()),
+            expanded_blocks: HashSet::new(),
+            collapsed_texts: HashMap::new(),
+            in_collapsible_body: false,
+            pending_search_jump: false,
             anchor_positions: HashMap::new(),
             current_chapter_file: None,
             search_state: SearchState::new(),
             pending_anchor_scroll: None,
+            missing_anchor: None,
+            pending_match_scroll: None,
+            figure_alt_texts: HashMap::new(),
+            figure_captions: HashMap::new(),
+            figure_blocks: HashMap::new(),
             last_active_anchor: None,
             book_comments: None,
             current_chapter_comments: HashMap::new(),
             comment_input: CommentInputState::default(),
             chapter_title: None,
             content_margin: 0,
+            book_progress: None,
+            section_progress: None,
+            show_section_progress: true,
+            progress_bar: false,
+            reading_ruler: false,
+            reading_ruler_row: None,
+            next_chapter_title: None,
+            pending_scroll_to_end: false,
         }
     }
 
     fn calculate_progress(&self, _content: &str, _width: usize, _height: usize) -> u32 {
+        (self.chapter_progress_fraction() * 100.0) as u32
+    }
+
+    /// Fraction (0.0-1.0) of the current chapter read up to the bottom of the viewport
+    pub fn chapter_progress_fraction(&self) -> f32 {
         if self.total_wrapped_lines == 0 {
-            return 0;
+            return 0.0;
         }
 
         let visible_end = (self.scroll_offset + self.visible_height).min(self.total_wrapped_lines);
-        ((visible_end as f32 / self.total_wrapped_lines as f32) * 100.0) as u32
+        visible_end as f32 / self.total_wrapped_lines as f32
+    }
+
+    /// Like `chapter_progress_fraction`, but None until the current content
+    /// has been rendered, so line counts of the previous chapter don't count
+    pub fn rendered_progress_fraction(&self) -> Option<f32> {
+        (self.markdown_document.is_some()
+            && self.rendered_content.generation == self.cache_generation)
+            .then(|| self.chapter_progress_fraction())
     }
 
     pub fn get_comments(&self) -> Arc<Mutex<BookComments>> {
@@ -235,55 +429,138 @@ impl MarkdownTextReader {
 }
 
 impl MarkdownTextReader {
-    pub fn render(
-        &mut self,
-        frame: &mut Frame,
-        area: Rect,
-        current_chapter: usize,
-        total_chapters: usize,
-        palette: &Base16Palette,
-        is_focused: bool,
-    ) {
-        // Store content area for hit-testing and mouse interactions
-        self.last_content_area = Some(area);
-        // Trim borders plus footer space to get actual viewport height
-        self.visible_height = area.height.saturating_sub(3) as usize;
-
-        if self.show_raw_html {
-            self.render_raw_html(frame, area, current_chapter, total_chapters, palette);
-            return;
-        }
-
-        // Account for borders, side padding, and content margin
+    /// Width of the text in a reader panel `panel_width` columns wide,
+    /// after borders, side padding and the content margin
+    fn text_width(&self, panel_width: u16) -> usize {
         let margin_width = (self.content_margin * 2) as usize;
-        let width = area.width.saturating_sub(4) as usize - margin_width * 2;
+        (panel_width.saturating_sub(4) as usize).saturating_sub(margin_width * 2)
+    }
 
+    /// Lay the document out again when the width, focus or cached content
+    /// changed, keeping the selection, search matches and pending jumps
+    fn update_layout(&mut self, width: usize, palette: &Base16Palette, is_focused: bool) {
         // Re-render when dimensions, focus, or cached content change
         if self.last_width != width
             || self.last_focus_state != is_focused
             || self.rendered_content.generation != self.cache_generation
         {
             if let Some(doc) = self.markdown_document.clone() {
-                self.rendered_content =
-                    self.render_document_to_lines(doc.as_ref(), width, palette, is_focused);
-                self.total_wrapped_lines = self.rendered_content.total_height;
+                // Selection and search matches refer to line indices that the
+                // re-render (e.g. re-wrapping after a resize) invalidates
+                self.text_selection
+                    .anchor_to_document(&self.rendered_content.lines);
+                let search_anchor = self.search_match_anchor();
+
+                let doc = self.layout_document(doc);
+                self.start_layout(doc, width, palette, is_focused);
+                if let Some(node_index) = self.pending_node_restore {
+                    self.extend_layout_to_node(node_index);
+                }
+                if search_anchor.is_some()
+                    || self.text_selection.has_selection()
+                    || self.pending_search_jump
+                    || self.pending_scroll_to_end
+                    || self.pending_match_scroll.is_some()
+                {
+                    self.complete_layout();
+                }
                 self.last_width = width;
                 self.last_focus_state = is_focused;
 
+                self.text_selection
+                    .restore_from_document(&self.rendered_content.lines);
+                if let Some(anchor) = search_anchor {
+                    self.reanchor_search_matches(&anchor);
+                }
+                if std::mem::take(&mut self.pending_search_jump)
+                    && let Some(line) = self.current_search_match_line()
+                {
+                    self.jump_to_line(line);
+                }
+
                 if let Some(node_index) = self.pending_node_restore.take() {
                     self.perform_node_restore(node_index);
                 }
+                if std::mem::take(&mut self.pending_scroll_to_end) {
+                    self.scroll_offset = self.get_max_scroll_offset();
+                }
 
                 if let Some(anchor_id) = self.pending_anchor_scroll.take() {
                     if let Some(target_line) = self.get_anchor_position(&anchor_id) {
                         self.scroll_to_line(target_line);
-                        self.highlight_line_temporarily(target_line, Duration::from_secs(2));
+                        self.highlight_jump_target(target_line);
                     } else {
                         warn!("Pending anchor '{anchor_id}' not found after re-render");
+                        self.missing_anchor = Some(anchor_id);
+                    }
+                }
+
+                if let Some(target) = self.pending_match_scroll.take() {
+                    if let Some(target_line) = self.match_target_line(&target) {
+                        self.scroll_to_line(target_line);
+                        self.highlight_jump_target(target_line);
+                    } else {
+                        warn!("Search match {target:?} not found after re-render");
                     }
                 }
             }
         }
+        self.extend_layout_to_viewport();
+    }
+
+    /// Size the reader panel, borders included, for `render_offscreen`
+    pub fn set_viewport(&mut self, width: u16, height: u16) {
+        self.last_content_area = Some(Rect::new(0, 0, width, height));
+        self.visible_height = height.saturating_sub(3) as usize;
+    }
+
+    /// Lay the content out for the viewport given to `set_viewport` exactly
+    /// as a focused `render` would, without a frame to draw on, e.g. to test
+    /// wrapping
+    pub fn render_offscreen(&mut self) {
+        let Some(area) = self.last_content_area else {
+            return;
+        };
+        let palette = crate::theme::current_theme();
+        let width = self.text_width(area.width);
+        self.render_conversion_placeholder(palette);
+        self.update_layout(width, palette, true);
+    }
+
+    pub fn rendered_content(&self) -> &RenderedContent {
+        &self.rendered_content
+    }
+
+    /// Plain text of each rendered line, as copied to the clipboard
+    pub fn raw_text_lines(&self) -> &[String] {
+        &self.raw_text_lines
+    }
+
+    pub fn render(
+        &mut self,
+        frame: &mut Frame,
+        area: Rect,
+        current_chapter: usize,
+        total_chapters: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        // Store content area for hit-testing and mouse interactions
+        self.last_content_area = Some(area);
+        // Trim borders plus footer space to get actual viewport height
+        self.visible_height = area.height.saturating_sub(3) as usize;
+
+        if self.show_raw_html {
+            self.render_raw_html(frame, area, current_chapter, total_chapters, palette);
+            return;
+        }
+
+        let width = self.text_width(area.width);
+
+        self.render_conversion_placeholder(palette);
+        self.update_layout(width, palette, is_focused);
+        self.extend_layout_to_viewport();
+
         let title_text = if let Some(ref title) = self.chapter_title {
             format!("[{current_chapter}/{total_chapters}] {title}")
         } else {
@@ -291,11 +568,24 @@ impl MarkdownTextReader {
         };
 
         let progress = self.calculate_progress("", width, self.visible_height);
+        let section_text = match &self.section_progress {
+            Some(section) if self.show_section_progress => format!(
+                " Section {}/{} {}% ·",
+                section.index, section.count, section.percent
+            ),
+            _ => String::new(),
+        };
+        let progress_text = match self.book_progress {
+            Some(book_progress) => {
+                format!("{section_text} {progress}% · Book {book_progress}% ")
+            }
+            None => format!("{section_text} {progress}% "),
+        };
 
         let block = Block::default()
             .borders(Borders::ALL)
             .title(title_text)
-            .title_bottom(Line::from(format!(" {progress}% ")).right_aligned());
+            .title_bottom(Line::from(progress_text).right_aligned());
 
         // Remove borders so the text sits inside the frame cleanly
         let mut inner_area = block.inner(area);
@@ -316,12 +606,11 @@ impl MarkdownTextReader {
         let end_offset =
             (self.scroll_offset + self.visible_height).min(self.rendered_content.lines.len());
 
-        // Selection background depends on focus state
-        let selection_bg = if is_focused {
-            palette.base_02
-        } else {
-            palette.base_01
-        };
+        // Selection colors depend on focus state and the user's selection overrides
+        let (selection_bg, selection_fg) = palette.get_text_selection_colors(is_focused);
+        let ruler_row = self
+            .reading_ruler
+            .then(|| reading_ruler::ruler_row(self.reading_ruler_row, self.visible_height));
 
         // Reserve empty lines where the comment textarea will be drawn
         let mut textarea_lines_to_insert = 0;
@@ -331,16 +620,7 @@ impl MarkdownTextReader {
             if let Some(target_line) = self.comment_input.target_line {
                 if target_line >= self.scroll_offset && target_line < end_offset {
                     textarea_insert_position = Some(target_line);
-
-                    let content_lines = if let Some(ref textarea) = self.comment_input.textarea {
-                        textarea.lines().len()
-                    } else {
-                        0
-                    };
-
-                    let min_lines = 3;
-                    let actual_content_lines = content_lines.max(min_lines);
-                    textarea_lines_to_insert = actual_content_lines + 2;
+                    textarea_lines_to_insert = self.comment_input.height();
                 }
             }
         }
@@ -374,27 +654,54 @@ impl MarkdownTextReader {
                 }
 
                 let mut line_spans = if self.highlight_visual_line == Some(visual_line_idx) {
-                    rendered_line
+                    let bg = palette.jump_highlight_bg();
+                    let mut spans: Vec<Span<'static>> = rendered_line
                         .spans
                         .iter()
-                        .map(|span| {
-                            Span::styled(span.content.clone(), span.style.bg(palette.base_02))
-                        })
-                        .collect()
+                        .map(|span| Span::styled(span.content.clone(), span.style.bg(bg)))
+                        .collect();
+                    let used: usize = spans.iter().map(Span::width).sum();
+                    let width = self.text_width(area.width);
+                    if self.jump_highlight_style == JumpHighlightStyle::Line && used < width {
+                        spans.push(Span::styled(
+                            " ".repeat(width - used),
+                            RatatuiStyle::default().bg(bg),
+                        ));
+                    }
+                    spans
                 } else {
                     rendered_line.spans.clone()
                 };
 
-                if self.text_selection.has_selection() {
+                // An image is copied whole or not at all, so the selection
+                // leaves its placeholder box unpainted
+                let image_row =
+                    matches!(rendered_line.line_type, LineType::ImagePlaceholder { .. });
+                if self.text_selection.has_selection() && !image_row {
                     let line_with_selection = self.text_selection.apply_selection_highlighting(
                         line_idx,
                         line_spans,
                         selection_bg,
+                        selection_fg,
                     );
                     line_spans = line_with_selection.spans;
+                } else if let Some(column) = self.caret_column(line_idx).filter(|_| !image_row) {
+                    line_spans = apply_caret(line_spans, column);
                 }
 
                 line_spans = self.apply_search_highlighting(line_idx, line_spans, palette);
+                // Highlights are placed on logical columns, so reorder last
+                if let Some(layout) = self.rtl_layouts.get(&line_idx) {
+                    line_spans = layout.visual_spans(line_spans);
+                }
+                // Lowest priority: only cells no highlight has colored take the tint
+                if ruler_row == Some(visible_lines.len()) {
+                    line_spans = reading_ruler::apply_ruler(
+                        line_spans,
+                        self.text_width(area.width),
+                        palette.base_01,
+                    );
+                }
 
                 visible_lines.push(Line::from(line_spans));
             }
@@ -405,6 +712,15 @@ impl MarkdownTextReader {
             .wrap(ratatui::widgets::Wrap { trim: false });
 
         frame.render_widget(paragraph, area);
+        if progress_bar::shows_progress_bar(self.progress_bar) {
+            progress_bar::draw_progress_bar(
+                frame.buffer_mut(),
+                area,
+                self.chapter_progress_fraction(),
+                palette,
+                is_focused,
+            );
+        }
 
         let inner_text_paragraph = Paragraph::new(visible_lines)
             .block(Block::default().borders(Borders::NONE))
@@ -525,6 +841,7 @@ impl MarkdownTextReader {
         }
 
         if self.comment_input.is_active() {
+            let desired_height = self.comment_input.height() as u16;
             if let Some(ref mut textarea) = self.comment_input.textarea {
                 if let Some(target_line) = self.comment_input.target_line {
                     if target_line >= self.scroll_offset
@@ -535,12 +852,6 @@ impl MarkdownTextReader {
                         let textarea_y = inner_area.y + visual_position as u16;
 
                         if textarea_y < inner_area.y + inner_area.height {
-                            // Compute minimum height so borders never collapse
-                            let content_lines = textarea.lines().len();
-                            let min_lines = 3;
-                            let actual_content_lines = content_lines.max(min_lines);
-                            let desired_height = (actual_content_lines + 2) as u16;
-
                             // Constrain height to the remaining view
                             let textarea_height =
                                 desired_height.min(inner_area.y + inner_area.height - textarea_y);
@@ -579,7 +890,7 @@ impl MarkdownTextReader {
 
                             let block = Block::default()
                                 .borders(Borders::ALL)
-                                .title(" Add Note ")
+                                .title(i18n::tr("comments.add_note"))
                                 .style(
                                     RatatuiStyle::default()
                                         .fg(palette.base_04)
@@ -632,15 +943,45 @@ impl MarkdownTextReader {
         &mut self,
         content_raw_html: &str,
         chapter_title: Option<String>,
+    ) {
+        self.load_content(None, content_raw_html, chapter_title);
+    }
+
+    /// Convert and show content, cached under `chapter` when given
+    fn load_content(
+        &mut self,
+        chapter: Option<usize>,
+        content_raw_html: &str,
+        chapter_title: Option<String>,
     ) {
         self.clear_content();
+        self.chapter_title = chapter_title;
 
-        use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
-        let mut converter = HtmlToMarkdownConverter::new();
-        let doc = Arc::new(converter.convert(content_raw_html));
+        if content_raw_html.len() > LARGE_CHAPTER_BYTES {
+            // Shown as a placeholder until `check_for_converted_chapter`
+            // swaps the document in
+            self.conversion = Some(conversion::ChapterConversion::start(
+                chapter,
+                content_raw_html.to_string(),
+                self.dehyphenate,
+                self.strip_invisible,
+                self.glyph_map.clone(),
+            ));
+            return;
+        }
 
+        let (doc, notes) = conversion::convert_chapter(
+            content_raw_html,
+            self.dehyphenate,
+            self.strip_invisible,
+            self.glyph_map.clone(),
+        );
+        let doc = Arc::new(doc);
+        if let Some(chapter) = chapter {
+            self.chapter_cache.insert(chapter, doc.clone(), notes);
+        }
+        self.conversion_notes = notes;
         self.markdown_document = Some(doc);
-        self.chapter_title = chapter_title;
 
         // Mark cached render as stale so next draw rebuilds it
         self.cache_generation += 1;
@@ -652,12 +993,24 @@ impl MarkdownTextReader {
 
         // IMPORTANT: Clear the markdown document so new content can be parsed
         self.markdown_document = None;
+        self.pending_layout = None;
+        self.last_click = None;
+        self.press_point = None;
+        self.conversion = None;
+        self.gloss_analysis = None;
+        self.gloss_targets = None;
+        self.conversion_notes = conversion::ConversionNotes::default();
+        self.section_progress = None;
 
         self.cache_generation += 1;
 
         self.links.clear();
         self.embedded_tables.borrow_mut().clear();
+        self.expanded_blocks.clear();
         self.raw_text_lines.clear();
+        self.ruby_reading_ranges.clear();
+        self.quote_bar_ranges.clear();
+        self.rtl_layouts.clear();
         self.rendered_content = RenderedContent {
             lines: Vec::new(),
             total_height: 0,
@@ -697,6 +1050,215 @@ impl MarkdownTextReader {
         self.content_margin
     }
 
+    pub fn set_render_images(&mut self, render_images: bool) {
+        self.render_images = render_images;
+        if !render_images {
+            self.background_loader.cancel_loading();
+            self.embedded_images.borrow_mut().clear();
+        }
+        self.cache_generation += 1;
+    }
+
+    pub fn toggle_render_images(&mut self) {
+        self.set_render_images(!self.render_images);
+    }
+
+    pub fn is_rendering_images(&self) -> bool {
+        self.render_images
+    }
+
+    pub fn set_ruby_mode(&mut self, ruby_mode: RubyMode) {
+        self.ruby_mode = ruby_mode;
+        self.cache_generation += 1;
+    }
+
+    pub fn set_copy_ruby_readings(&mut self, copy_ruby_readings: bool) {
+        self.copy_ruby_readings = copy_ruby_readings;
+    }
+
+    pub fn set_copy_quote_bars(&mut self, copy_quote_bars: bool) {
+        self.copy_quote_bars = copy_quote_bars;
+    }
+
+    pub fn set_max_blank_lines(&mut self, max_blank_lines: usize) {
+        self.max_blank_lines = max_blank_lines;
+        self.cache_generation += 1;
+    }
+
+    pub fn set_table_stack_width(&mut self, table_stack_width: usize) {
+        self.table_stack_width = table_stack_width;
+        self.cache_generation += 1;
+    }
+
+    pub fn set_list_indent(&mut self, list_indent: usize) {
+        self.list_indent = list_indent;
+        self.cache_generation += 1;
+    }
+
+    pub fn set_scroll_off(&mut self, scroll_off: usize) {
+        self.scroll_off = scroll_off;
+    }
+
+    pub fn set_jump_highlight(&mut self, duration: Duration, style: JumpHighlightStyle) {
+        self.jump_highlight_duration = duration;
+        self.jump_highlight_style = style;
+    }
+
+    pub fn set_click_action(&mut self, click_action: ClickAction) {
+        self.click_action = click_action;
+    }
+
+    pub fn set_rule_style(&mut self, rule_style: RuleStyle) {
+        self.rule_style = rule_style;
+        self.cache_generation += 1;
+    }
+
+    pub fn set_rule_glyph(&mut self, rule_glyph: String) {
+        self.rule_glyph = rule_glyph;
+        self.cache_generation += 1;
+    }
+
+    /// Takes effect when the next chapter content is set
+    pub fn set_dehyphenate(&mut self, dehyphenate: bool) {
+        if self.dehyphenate != dehyphenate {
+            self.clear_chapter_cache();
+        }
+        self.dehyphenate = dehyphenate;
+    }
+
+    pub fn is_dehyphenating(&self) -> bool {
+        self.dehyphenate
+    }
+
+    pub fn set_typography(&mut self, typography: bool) {
+        if self.typography != typography {
+            self.cache_generation += 1;
+        }
+        self.typography = typography;
+    }
+
+    pub fn is_typesetting(&self) -> bool {
+        self.typography
+    }
+
+    pub fn set_copy_typography(&mut self, copy_typography: bool) {
+        self.copy_typography = copy_typography;
+    }
+
+    pub fn set_copy_image_alt_text(&mut self, copy_image_alt_text: bool) {
+        self.copy_image_alt_text = copy_image_alt_text;
+    }
+
+    /// The document to lay out: a typeset copy when typography is on
+    fn layout_document(&mut self, doc: Arc<Document>) -> Arc<Document> {
+        if !self.typography {
+            return doc;
+        }
+        if let Some((source, typeset)) = &self.typeset_document
+            && Arc::ptr_eq(source, &doc)
+        {
+            return typeset.clone();
+        }
+        let mut typeset = doc.as_ref().clone();
+        typeset_document(&mut typeset);
+        let typeset = Arc::new(typeset);
+        self.typeset_document = Some((doc, typeset.clone()));
+        typeset
+    }
+
+    /// Takes effect when the next chapter content is set
+    pub fn set_strip_invisible(&mut self, strip_invisible: bool) {
+        self.strip_invisible = strip_invisible;
+    }
+
+    /// Takes effect when the next chapter content is set
+    pub fn set_glyph_map(&mut self, glyph_map: GlyphMap) {
+        self.glyph_map = Arc::new(glyph_map);
+        self.clear_chapter_cache();
+    }
+
+    /// Private-use characters of the current chapter without a known equivalent
+    pub fn unknown_glyphs(&self) -> usize {
+        self.conversion_notes.unknown_glyphs
+    }
+
+    /// Whether the current chapter converted to nothing and is shown as the
+    /// plain text of its HTML
+    pub fn is_plain_text_fallback(&self) -> bool {
+        self.conversion_notes.plain_text
+    }
+
+    /// Scan the current chapter for rare words in the background; the
+    /// markers appear once `check_for_gloss_targets` picks up the result
+    pub fn start_gloss_analysis(&mut self, glossary: &Glossary) {
+        if let Some(doc) = &self.markdown_document {
+            self.gloss_analysis = Some(glossary.analyze(doc.clone()));
+        }
+    }
+
+    /// Returns true when rare word markers arrived and need a redraw
+    pub fn check_for_gloss_targets(&mut self) -> bool {
+        let Some(targets) = self.gloss_analysis.as_ref().and_then(|a| a.poll()) else {
+            return false;
+        };
+        self.gloss_analysis = None;
+        self.gloss_targets = Some(targets);
+        self.cache_generation += 1;
+        true
+    }
+
+    pub fn gloss_targets(&self) -> Option<&GlossTargets> {
+        self.gloss_targets.as_ref()
+    }
+
+    /// Stop marking `word` in the current chapter
+    pub fn remove_gloss_word(&mut self, word: &str) {
+        if let Some(targets) = &mut self.gloss_targets {
+            targets.remove_word(word);
+            self.cache_generation += 1;
+        }
+    }
+
+    pub fn set_book_progress(&mut self, book_progress: Option<u32>) {
+        self.book_progress = book_progress;
+    }
+
+    pub fn set_show_section_progress(&mut self, show_section_progress: bool) {
+        self.show_section_progress = show_section_progress;
+    }
+
+    pub fn set_progress_bar(&mut self, progress_bar: bool) {
+        self.progress_bar = progress_bar;
+    }
+
+    pub fn set_reading_ruler(&mut self, reading_ruler: bool) {
+        self.reading_ruler = reading_ruler;
+    }
+
+    pub fn has_reading_ruler(&self) -> bool {
+        self.reading_ruler
+    }
+
+    pub fn set_reading_ruler_row(&mut self, row: Option<u16>) {
+        self.reading_ruler_row = row;
+    }
+
+    /// Move the ruler `rows` down (up when negative) within the viewport;
+    /// returns the row it is on now
+    pub fn move_reading_ruler(&mut self, rows: i32) -> u16 {
+        let current = reading_ruler::ruler_row(self.reading_ruler_row, self.visible_height);
+        let last = self.visible_height.saturating_sub(1) as i32;
+        let row = (current as i32 + rows).clamp(0, last) as u16;
+        self.reading_ruler_row = Some(row);
+        row
+    }
+
+    /// None drops the separator, e.g. in the last chapter
+    pub fn set_next_chapter_title(&mut self, title: Option<String>) {
+        self.next_chapter_title = title;
+        self.cache_generation += 1;
+    }
+
     pub fn invalidate_render_cache(&mut self) {
         self.cache_generation += 1;
     }
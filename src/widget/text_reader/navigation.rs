@@ -1,8 +1,36 @@
 use super::types::*;
 use crate::main_app::VimNavMotions;
 use crate::search::SearchMode;
+use crate::search_engine::MatchTarget;
 use std::time::Instant;
 
+/// `text` with %XX escapes decoded, as hrefs may encode characters ids have
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| text.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Lines of the previous screen that stay in view after a full-page scroll
+const PAGE_OVERLAP: usize = 2;
+
 impl crate::markdown_text_reader::MarkdownTextReader {
     pub fn scroll_up(&mut self) {
         if self.scroll_offset > 0 {
@@ -48,18 +76,86 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// Scroll up a screen, keeping the top `PAGE_OVERLAP` lines in view at
+    /// the bottom
+    pub fn scroll_full_screen_up(&mut self, screen_height: usize) {
+        let scroll_amount = screen_height.saturating_sub(PAGE_OVERLAP).max(1);
+        self.scroll_offset = self.scroll_offset.saturating_sub(scroll_amount);
+        // The line that was at the top, where reading carries on from
+        self.highlight_visual_line = Some(scroll_amount.min(screen_height.saturating_sub(1)));
+        self.highlight_end_time = Instant::now() + std::time::Duration::from_millis(150);
+        if self.search_state.active && self.search_state.mode == SearchMode::NavigationMode {
+            self.search_state.current_match_index = None;
+        }
+    }
+
+    /// Scroll down a screen, keeping the bottom `PAGE_OVERLAP` lines in view
+    /// at the top
+    pub fn scroll_full_screen_down(&mut self, screen_height: usize) {
+        let scroll_amount = screen_height.saturating_sub(PAGE_OVERLAP).max(1);
+        let max_offset = self.get_max_scroll_offset();
+        self.scroll_offset = (self.scroll_offset + scroll_amount).min(max_offset);
+        // The line that was at the bottom, where reading carries on from
+        self.highlight_visual_line = Some(PAGE_OVERLAP.min(screen_height).saturating_sub(1));
+        self.highlight_end_time = Instant::now() + std::time::Duration::from_millis(150);
+        if self.search_state.active && self.search_state.mode == SearchMode::NavigationMode {
+            self.search_state.current_match_index = None;
+        }
+    }
+
     pub fn get_scroll_offset(&self) -> usize {
         self.scroll_offset
     }
 
+    /// Words on the rendered lines `lines`, for the daily word goal
+    pub fn count_words_in_lines(&self, lines: std::ops::Range<usize>) -> usize {
+        let rendered = &self.rendered_content.lines;
+        let end = lines.end.min(rendered.len());
+        rendered
+            .get(lines.start.min(end)..end)
+            .unwrap_or_default()
+            .iter()
+            .map(|line| line.raw_text.split_whitespace().count())
+            .sum()
+    }
+
+    /// The comment textarea counts as rows of the chapter while it is open,
+    /// so one at the end can still be scrolled into full view
     pub fn get_max_scroll_offset(&self) -> usize {
-        self.total_wrapped_lines.saturating_sub(self.visible_height)
+        (self.total_wrapped_lines + self.comment_input.height()).saturating_sub(self.visible_height)
+    }
+
+    /// Whether the view can't scroll further in a direction. False until new
+    /// content has been rendered, since the line count is stale before that.
+    pub fn is_at_scroll_edge(&self, downward: bool) -> bool {
+        if self.markdown_document.is_none()
+            || self.rendered_content.generation != self.cache_generation
+        {
+            return false;
+        }
+        if downward {
+            self.is_layout_complete() && self.scroll_offset >= self.get_max_scroll_offset()
+        } else {
+            self.scroll_offset == 0
+        }
+    }
+
+    /// Show the end of the chapter once it is rendered
+    pub fn scroll_to_end_after_render(&mut self) {
+        self.pending_scroll_to_end = true;
+    }
+
+    /// Context kept above and below a target line, capped like vim's
+    /// scrolloff so a short viewport can still show the target
+    pub(super) fn scroll_margin(&self) -> usize {
+        self.scroll_off
+            .min(self.visible_height.saturating_sub(1) / 2)
     }
 
     pub fn scroll_to_line(&mut self, target_line: usize) {
-        // Center target line in viewport if possible
+        // Put the target near the top, below the scroll margin
         let desired_offset = if target_line > self.visible_height / 2 {
-            target_line // - self.visible_height  / 2
+            target_line.saturating_sub(self.scroll_margin())
         } else {
             0
         };
@@ -67,6 +163,20 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.scroll_offset = desired_offset.min(self.get_max_scroll_offset());
     }
 
+    /// Scroll as little as needed to show `line` with the scroll margin around it
+    pub fn scroll_line_into_view(&mut self, line: usize) {
+        if self.visible_height == 0 {
+            return;
+        }
+        let margin = self.scroll_margin();
+        if line < self.scroll_offset + margin {
+            self.scroll_offset = line.saturating_sub(margin);
+        } else if line + margin >= self.scroll_offset + self.visible_height {
+            self.scroll_offset = (line + margin + 1).saturating_sub(self.visible_height);
+        }
+        self.scroll_offset = self.scroll_offset.min(self.get_max_scroll_offset());
+    }
+
     pub fn jump_to_line(&mut self, line_idx: usize) {
         if line_idx < self.rendered_content.lines.len() {
             // Center the line in the viewport if possible
@@ -82,6 +192,43 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// Show a figure or table matched by the book search once the chapter
+    /// is rendered
+    pub fn store_pending_match_scroll(&mut self, target: MatchTarget) {
+        if let MatchTarget::Collapsible { index, .. } = target {
+            self.expand_nth_collapsible(index);
+        }
+        self.pending_match_scroll = Some(target);
+    }
+
+    /// First line of the image, table or collapsible block a search match
+    /// points at
+    pub fn match_target_line(&self, target: &MatchTarget) -> Option<usize> {
+        match target {
+            MatchTarget::Text => None,
+            MatchTarget::Figure { src } if self.render_images => self
+                .embedded_images
+                .borrow()
+                .get(src)
+                .map(|image| image.lines_before_image),
+            // Without images the alt text is an inline label
+            MatchTarget::Figure { src } => {
+                let alt = self.figure_alt_texts.get(src)?;
+                let label = format!("[image: {}]", alt.trim());
+                self.rendered_content
+                    .lines
+                    .iter()
+                    .position(|line| line.raw_text.contains(&label))
+            }
+            MatchTarget::Table { index } => self
+                .embedded_tables
+                .borrow()
+                .get(*index)
+                .map(|table| table.lines_before_table),
+            MatchTarget::Collapsible { index, .. } => self.nth_collapsible_line(*index),
+        }
+    }
+
     pub fn scroll_to_paragraph(&mut self, paragraph_index: usize) {
         for (line_idx, line) in self.rendered_content.lines.iter().enumerate() {
             if line.node_index == Some(paragraph_index) {
@@ -91,8 +238,20 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
-    pub fn get_anchor_position(&self, anchor_id: &str) -> Option<usize> {
-        self.anchor_positions.get(anchor_id).copied()
+    /// Line of the anchor, laying the chapter out as far as needed. Hrefs
+    /// differing from the id in case or percent-encoding still find it.
+    pub fn get_anchor_position(&mut self, anchor_id: &str) -> Option<usize> {
+        self.extend_layout_while(|reader| !reader.anchor_positions.contains_key(anchor_id));
+        if let Some(&line) = self.anchor_positions.get(anchor_id) {
+            return Some(line);
+        }
+
+        let wanted = percent_decode(anchor_id).to_lowercase();
+        self.anchor_positions
+            .iter()
+            .filter(|(id, _)| percent_decode(id).to_lowercase() == wanted)
+            .map(|(_, &line)| line)
+            .min()
     }
 
     pub fn store_pending_anchor_scroll(&mut self, pending_anchor: String) {
@@ -100,8 +259,25 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.pending_anchor_scroll = Some(pending_anchor);
     }
 
+    /// Anchor a link led to after chapter navigation that the chapter
+    /// doesn't have, once
+    pub fn take_missing_anchor(&mut self) -> Option<String> {
+        self.missing_anchor.take()
+    }
+
+    /// Bring the line a link, anchor or search jump landed on into view
+    /// and highlight it as long as `jump_highlight_ms` asks, if at all
+    pub fn highlight_jump_target(&mut self, line: usize) {
+        if self.jump_highlight_duration.is_zero() {
+            self.scroll_line_into_view(line);
+        } else {
+            self.highlight_line_temporarily(line, self.jump_highlight_duration);
+        }
+    }
+
     //todo: remove
     pub fn highlight_line_temporarily(&mut self, line: usize, duration: std::time::Duration) {
+        self.scroll_line_into_view(line);
         if line >= self.scroll_offset && line < self.scroll_offset + self.visible_height {
             let visible_line = line - self.scroll_offset;
             self.highlight_visual_line = Some(visible_line);
@@ -173,6 +349,21 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             format!("chapter_{current_chapter}")
         };
 
+        let mut active = self.find_active_section(current_chapter, chapter_href, available_anchors);
+        active.progress = active
+            .anchor
+            .as_deref()
+            .and_then(|anchor| self.section_progress(anchor, available_anchors));
+        self.section_progress = active.progress.clone();
+        active
+    }
+
+    fn find_active_section(
+        &mut self,
+        current_chapter: usize,
+        chapter_href: String,
+        available_anchors: &[String],
+    ) -> ActiveSection {
         let visible_start = self.scroll_offset;
         let total_lines = self.rendered_content.lines.len();
         if total_lines == 0 {
@@ -210,6 +401,37 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         ActiveSection::new(current_chapter, chapter_href, None)
     }
 
+    /// Progress through the section starting at `anchor`, which runs to the
+    /// next section's anchor or the end of the chapter
+    fn section_progress(
+        &self,
+        anchor: &str,
+        available_anchors: &[String],
+    ) -> Option<SectionProgress> {
+        let mut starts: Vec<(usize, &str)> = available_anchors
+            .iter()
+            .filter_map(|a| self.anchor_positions.get(a).map(|&line| (line, a.as_str())))
+            .collect();
+        starts.sort_by_key(|(line, _)| *line);
+
+        let index = starts
+            .iter()
+            .position(|(_, a)| a.eq_ignore_ascii_case(anchor))?;
+        let start = starts[index].0;
+        let end = starts
+            .get(index + 1)
+            .map_or(self.total_wrapped_lines, |(line, _)| *line)
+            .max(start + 1);
+        let visible_end = (self.scroll_offset + self.visible_height).min(self.total_wrapped_lines);
+        let read = visible_end.clamp(start, end) - start;
+
+        Some(SectionProgress {
+            index: index + 1,
+            count: starts.len(),
+            percent: (read * 100 / (end - start)) as u32,
+        })
+    }
+
     fn match_available_anchor(anchor: &str, available: &[String]) -> Option<String> {
         if available.iter().any(|a| a == anchor) {
             return Some(anchor.to_string());
@@ -238,14 +460,35 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
     /// Restore scroll position to show a specific node
     pub fn restore_to_node_index(&mut self, node_index: usize) {
+        self.restore_to_node_line(node_index, 0);
+    }
+
+    /// Restore the view to `line_offset` lines past the start of a node,
+    /// as `get_current_node_line_offset` reported it
+    pub fn restore_to_node_line(&mut self, node_index: usize, line_offset: isize) {
         self.pending_node_restore = Some(node_index);
+        self.pending_node_line_offset = line_offset;
+    }
+
+    /// Lines the view is scrolled past the start of the current node;
+    /// negative when it starts on the blank line before it
+    pub fn get_current_node_line_offset(&self) -> isize {
+        let node_index = self.get_current_node_index();
+        self.rendered_content
+            .lines
+            .iter()
+            .position(|line| line.node_index.is_some_and(|idx| idx >= node_index))
+            .map_or(0, |start| self.scroll_offset as isize - start as isize)
     }
 
     pub fn perform_node_restore(&mut self, node_index: usize) {
+        let line_offset = std::mem::take(&mut self.pending_node_line_offset);
         for (line_idx, line) in self.rendered_content.lines.iter().enumerate() {
             if let Some(node_idx) = line.node_index {
                 if node_idx >= node_index {
-                    self.scroll_offset = line_idx.min(self.get_max_scroll_offset());
+                    self.scroll_offset = line_idx
+                        .saturating_add_signed(line_offset)
+                        .min(self.get_max_scroll_offset());
                     return;
                 }
             }
@@ -298,7 +541,288 @@ impl VimNavMotions for crate::markdown_text_reader::MarkdownTextReader {
     }
 
     fn handle_upper_g(&mut self) {
+        self.complete_layout();
         let max_offset = self.get_max_scroll_offset();
         self.scroll_offset = max_offset;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::settings::JumpHighlightStyle;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    fn viewport(visible_height: usize, total_lines: usize) -> MarkdownTextReader {
+        let mut reader = MarkdownTextReader::new();
+        reader.visible_height = visible_height;
+        reader.total_wrapped_lines = total_lines;
+        reader
+    }
+
+    #[test]
+    fn test_jump_targets_keep_scroll_off_context() {
+        let mut reader = viewport(20, 200);
+        reader.scroll_to_line(50);
+        assert_eq!(reader.get_scroll_offset(), 47);
+
+        // Near the start and the end the viewport can't move further
+        reader.scroll_to_line(5);
+        assert_eq!(reader.get_scroll_offset(), 0);
+        reader.scroll_to_line(199);
+        assert_eq!(reader.get_scroll_offset(), 180);
+
+        reader.set_scroll_off(0);
+        reader.scroll_to_line(50);
+        assert_eq!(reader.get_scroll_offset(), 50);
+    }
+
+    #[test]
+    fn test_highlighted_line_is_scrolled_away_from_the_edges() {
+        let mut reader = viewport(20, 200);
+        reader.scroll_to_line(50);
+
+        // Already inside the margins: nothing moves
+        reader.highlight_line_temporarily(60, Duration::from_secs(1));
+        assert_eq!(reader.get_scroll_offset(), 47);
+
+        reader.highlight_line_temporarily(66, Duration::from_secs(1));
+        assert_eq!(reader.get_scroll_offset(), 50);
+        assert_eq!(reader.highlight_visual_line, Some(16));
+
+        reader.highlight_line_temporarily(51, Duration::from_secs(1));
+        assert_eq!(reader.get_scroll_offset(), 48);
+
+        // A short viewport caps the margin so the target still fits
+        let mut reader = viewport(4, 200);
+        reader.set_scroll_off(10);
+        reader.highlight_line_temporarily(30, Duration::from_secs(1));
+        assert_eq!(reader.get_scroll_offset(), 28);
+    }
+
+    #[test]
+    fn test_jump_highlight_duration_of_zero_only_scrolls() {
+        let mut reader = viewport(20, 200);
+        reader.highlight_jump_target(66);
+        assert_eq!(reader.get_scroll_offset(), 50);
+        assert_eq!(reader.highlight_visual_line, Some(16));
+
+        let mut reader = viewport(20, 200);
+        reader.set_jump_highlight(Duration::ZERO, JumpHighlightStyle::Line);
+        reader.highlight_jump_target(66);
+        assert_eq!(reader.get_scroll_offset(), 50);
+        assert_eq!(reader.highlight_visual_line, None);
+    }
+
+    #[test]
+    fn test_full_pages_overlap_and_stop_at_the_ends() {
+        let mut reader = viewport(20, 50);
+
+        // The last two lines of a page open the next one
+        reader.scroll_full_screen_down(20);
+        assert_eq!(reader.get_scroll_offset(), 18);
+        assert_eq!(reader.highlight_visual_line, Some(1));
+
+        reader.scroll_full_screen_down(20);
+        assert_eq!(reader.get_scroll_offset(), 30);
+        reader.scroll_full_screen_down(20);
+        assert_eq!(reader.get_scroll_offset(), 30);
+
+        // Back up, the first two lines stay at the bottom
+        reader.scroll_full_screen_up(20);
+        assert_eq!(reader.get_scroll_offset(), 12);
+        assert_eq!(reader.highlight_visual_line, Some(18));
+        reader.scroll_full_screen_up(20);
+        assert_eq!(reader.get_scroll_offset(), 0);
+
+        // A viewport shorter than the overlap still moves
+        let mut reader = viewport(2, 50);
+        reader.scroll_full_screen_down(2);
+        assert_eq!(reader.get_scroll_offset(), 1);
+    }
+
+    #[test]
+    fn test_section_progress_spans_to_the_next_anchor() {
+        let mut reader = viewport(10, 100);
+        reader.rendered_content.lines = (0..100).map(|_| RenderedLine::empty()).collect();
+        reader.anchor_positions = HashMap::from([
+            ("intro".to_string(), 0),
+            ("methods".to_string(), 40),
+            ("results".to_string(), 60),
+            ("unlisted".to_string(), 50),
+        ]);
+        let anchors = ["intro", "results", "methods"].map(String::from);
+        reader.set_active_anchor(Some("methods".to_string()));
+
+        // The viewport ends at line 45, 5 lines into the 20 of "methods"
+        reader.scroll_offset = 35;
+        let active = reader.get_active_section(0, Some("ch1.xhtml"), &anchors);
+        assert_eq!(active.anchor.as_deref(), Some("methods"));
+        assert_eq!(
+            active.progress,
+            Some(SectionProgress {
+                index: 2,
+                count: 3,
+                percent: 25
+            })
+        );
+        assert_eq!(reader.section_progress, active.progress);
+
+        // The last section runs to the end of the chapter
+        reader.set_active_anchor(Some("results".to_string()));
+        reader.scroll_offset = 70;
+        let active = reader.get_active_section(0, Some("ch1.xhtml"), &anchors);
+        assert_eq!(active.progress.map(|p| (p.index, p.percent)), Some((3, 50)));
+    }
+
+    #[test]
+    fn test_chapter_edges_for_auto_advance() {
+        use crate::test_utils::test_helpers::create_test_terminal;
+        use crate::theme::current_theme;
+
+        let html: String = (1..=30).map(|i| format!("<p>Paragraph {i}</p>")).collect();
+        let mut reader = MarkdownTextReader::new();
+        let draw = |reader: &mut MarkdownTextReader| {
+            let mut terminal = create_test_terminal(60, 20);
+            terminal
+                .draw(|f| reader.render(f, f.area(), 1, 2, current_theme(), true))
+                .unwrap();
+        };
+
+        // Nothing counts as an edge until the chapter is rendered
+        reader.set_content_from_string(&html, None);
+        reader.set_next_chapter_title(Some("Chapter Two".to_string()));
+        assert!(!reader.is_at_scroll_edge(false));
+        draw(&mut reader);
+        assert!(reader.is_at_scroll_edge(false));
+        assert!(!reader.is_at_scroll_edge(true));
+
+        let last = reader.rendered_content.lines.last().unwrap();
+        assert_eq!(last.raw_text.trim_matches('─'), " Next: Chapter Two ");
+
+        reader.handle_upper_g();
+        assert!(reader.is_at_scroll_edge(true));
+
+        // Advancing upward into a chapter starts at its end
+        reader.set_content_from_string(&html, None);
+        reader.scroll_to_end_after_render();
+        draw(&mut reader);
+        assert_eq!(reader.get_scroll_offset(), reader.get_max_scroll_offset());
+        assert!(reader.get_scroll_offset() > 0);
+    }
+
+    #[test]
+    fn test_figure_and_table_matches_point_at_their_first_line() {
+        use crate::search::SearchablePanel;
+        use crate::test_utils::test_helpers::create_test_terminal;
+        use crate::theme::current_theme;
+
+        let html = "<p>Seals were counted along the coast.</p>\
+                    <p><img src=\"map.png\" alt=\"Map of the seal colonies\"/></p>\
+                    <table><tr><th>Colony</th><th>Count</th></tr>\
+                    <tr><td>North</td><td>1,240</td></tr></table>";
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(html, None);
+        reader.store_pending_match_scroll(MatchTarget::Table { index: 0 });
+        let mut terminal = create_test_terminal(60, 12);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+
+        let figure_line = reader
+            .match_target_line(&MatchTarget::Figure {
+                src: "map.png".to_string(),
+            })
+            .unwrap();
+        assert!(matches!(
+            reader.rendered_content.lines[figure_line].line_type,
+            LineType::ImagePlaceholder { .. }
+        ));
+
+        let table_line = reader
+            .match_target_line(&MatchTarget::Table { index: 0 })
+            .unwrap();
+        assert!(table_line > figure_line);
+        assert!(
+            reader.rendered_content.lines[table_line..table_line + 5]
+                .iter()
+                .any(|line| line.raw_text.contains("1,240"))
+        );
+        // The pending jump scrolled the table into view
+        assert!(reader.get_scroll_offset() > 0);
+
+        // The in-chapter search finds the alt text on the image's first line
+        reader.update_search_query("colonies");
+        let lines: Vec<usize> = reader
+            .search_state
+            .matches
+            .iter()
+            .map(|m| m.index)
+            .collect();
+        assert_eq!(lines, vec![figure_line]);
+    }
+
+    #[test]
+    fn test_fragment_links_scroll_to_heading_and_container_ids() {
+        use crate::markdown::LinkType;
+        use crate::test_utils::test_helpers::create_test_terminal;
+        use crate::theme::current_theme;
+
+        let filler: String = (0..40).map(|i| format!("<p>Filler {i}.</p>")).collect();
+        let html = format!(
+            "<p><a href=\"#Caf%C3%A9-Notes\">notes</a> and <a href=\"#sec-4\">section</a></p>\
+             {filler}<h2 id=\"café-notes\">Notes</h2>{filler}\
+             <div id=\"sec-4\"><p>Section four.</p></div>{filler}"
+        );
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(&html, None);
+        let mut terminal = create_test_terminal(60, 12);
+        let mut draw = |reader: &mut MarkdownTextReader| {
+            terminal
+                .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+                .unwrap();
+        };
+        draw(&mut reader);
+
+        let links: Vec<_> = reader.rendered_content.lines[0]
+            .link_nodes
+            .iter()
+            .map(|link| (link.link_type.clone(), link.target_anchor.clone().unwrap()))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                (LinkType::InternalAnchor, "Caf%C3%A9-Notes".to_string()),
+                (LinkType::InternalAnchor, "sec-4".to_string()),
+            ]
+        );
+
+        // Found despite the percent-encoding and case of the href
+        let notes = reader.get_anchor_position(&links[0].1).unwrap();
+        assert_eq!(
+            reader.rendered_content.lines[notes].raw_text.trim(),
+            "Notes"
+        );
+        let section = reader.get_anchor_position(&links[1].1).unwrap();
+        assert_eq!(
+            reader.rendered_content.lines[section].raw_text.trim(),
+            "Section four."
+        );
+
+        // A link followed into the chapter scrolls there once it is laid out
+        reader.set_content_from_string(&html, None);
+        reader.store_pending_anchor_scroll(links[1].1.clone());
+        draw(&mut reader);
+        let offset = reader.get_scroll_offset();
+        assert!(offset <= section && section < offset + reader.visible_height);
+        assert_eq!(reader.take_missing_anchor(), None);
+
+        reader.set_content_from_string(&html, None);
+        reader.store_pending_anchor_scroll("nowhere".to_string());
+        draw(&mut reader);
+        assert_eq!(reader.get_scroll_offset(), 0);
+        assert_eq!(reader.take_missing_anchor(), Some("nowhere".to_string()));
+    }
+}
@@ -0,0 +1,121 @@
+//! Chapter progress drawn along the bottom border of the content block.
+//!
+//! With `progress_bar: true` on a true-color terminal, the border up to the
+//! point read is drawn heavy, shading from the accent color to green; the
+//! percentage label the block writes into the border stays on top. It is
+//! drawn over the finished block, so the block's `title_bottom` is untouched.
+//! Other terminals keep the percentage alone.
+
+use crate::theme::Base16Palette;
+use ratatui::{buffer::Buffer, layout::Rect, style::Color, symbols};
+
+/// Whether the bar can be drawn: enabled, and colors fine enough for the
+/// gradient
+pub(super) fn shows_progress_bar(enabled: bool) -> bool {
+    enabled && crate::color_mode::supports_true_color()
+}
+
+/// Draw `fraction` of the bottom border of `area` as the progress bar
+pub(super) fn draw_progress_bar(
+    buf: &mut Buffer,
+    area: Rect,
+    fraction: f32,
+    palette: &Base16Palette,
+    is_focused: bool,
+) {
+    if area.width < 3 || area.height < 2 {
+        return;
+    }
+    let track = area.width - 2;
+    let filled = (fraction.clamp(0.0, 1.0) * track as f32).round() as u16;
+    let (from, to) = if is_focused {
+        (palette.base_0d, palette.base_0b)
+    } else {
+        (palette.base_03, palette.base_03)
+    };
+
+    let y = area.bottom() - 1;
+    for i in 0..filled {
+        let cell = &mut buf[(area.x + 1 + i, y)];
+        // The label written into the border keeps its own look
+        if cell.symbol() != symbols::line::HORIZONTAL {
+            continue;
+        }
+        cell.set_symbol(symbols::line::THICK_HORIZONTAL);
+        cell.set_fg(blend(from, to, i as f32 / (track - 1).max(1) as f32));
+    }
+}
+
+/// `from` shaded `t` (0.0-1.0) of the way towards `to`
+fn blend(from: Color, to: Color, t: f32) -> Color {
+    match (from, to) {
+        (Color::Rgb(r1, g1, b1), Color::Rgb(r2, g2, b2)) => {
+            let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+            Color::Rgb(mix(r1, r2), mix(g1, g2), mix(b1, b2))
+        }
+        _ => from,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::text::Line;
+    use ratatui::widgets::{Block, Borders, Widget};
+
+    /// The bottom border after drawing the bar at `fraction`: its
+    /// characters, and which cells took the bar's colors
+    fn bottom_border(fraction: f32, is_focused: bool) -> (String, Vec<Option<Color>>) {
+        let area = Rect::new(0, 0, 24, 3);
+        let mut buf = Buffer::empty(area);
+        Block::default()
+            .borders(Borders::ALL)
+            .title_bottom(Line::from(" 50% ").right_aligned())
+            .render(area, &mut buf);
+        let palette = crate::theme::current_theme();
+        draw_progress_bar(&mut buf, area, fraction, palette, is_focused);
+
+        let cells = (0..area.width).map(|x| &buf[(x, area.bottom() - 1)]);
+        let symbols = cells.clone().map(|cell| cell.symbol()).collect();
+        let colors = cells
+            .map(|cell| (cell.symbol() == symbols::line::THICK_HORIZONTAL).then_some(cell.fg))
+            .collect();
+        (symbols, colors)
+    }
+
+    #[test]
+    fn test_bar_at_zero_half_and_full() {
+        assert_eq!(bottom_border(0.0, true).0, "└───────────────── 50% ┘");
+        assert_eq!(bottom_border(0.5, true).0, "└━━━━━━━━━━━────── 50% ┘");
+        // The label stays readable over a full bar
+        assert_eq!(bottom_border(1.0, true).0, "└━━━━━━━━━━━━━━━━━ 50% ┘");
+    }
+
+    #[test]
+    fn test_bar_shades_when_focused_only() {
+        let palette = crate::theme::current_theme();
+        let (_, focused) = bottom_border(1.0, true);
+        assert_eq!(focused[1], Some(palette.base_0d));
+        // The last cell before the label, 16 of the 21 steps along
+        assert_eq!(
+            focused[17],
+            Some(blend(palette.base_0d, palette.base_0b, 16.0 / 21.0))
+        );
+        assert_eq!(focused[18], None);
+
+        let (_, unfocused) = bottom_border(1.0, false);
+        assert!(
+            unfocused
+                .iter()
+                .flatten()
+                .all(|color| *color == palette.base_03)
+        );
+    }
+
+    #[test]
+    fn test_blend_mixes_rgb_and_keeps_others() {
+        let (from, to) = (Color::Rgb(0, 100, 200), Color::Rgb(100, 200, 0));
+        assert_eq!(blend(from, to, 0.5), Color::Rgb(50, 150, 100));
+        assert_eq!(blend(Color::Blue, to, 0.5), Color::Blue);
+    }
+}
@@ -0,0 +1,126 @@
+//! A tinted row across the reader to keep the eye on one line.
+//!
+//! With the ruler on (Space+r), one row of the viewport, the middle one
+//! unless moved with Shift+J/K, gets a light background and the text
+//! scrolls through it. Only cells without a background of their own take
+//! the tint, so selection, search and jump highlights stay on top.
+
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+
+/// Row of the viewport the ruler is on: `row` if set, else the middle one
+pub(super) fn ruler_row(row: Option<u16>, visible_height: usize) -> usize {
+    let last = visible_height.saturating_sub(1);
+    row.map_or(visible_height / 2, |row| (row as usize).min(last))
+}
+
+/// `spans` with the ruler's tint behind them, padded to `width`
+pub(super) fn apply_ruler(
+    spans: Vec<Span<'static>>,
+    width: usize,
+    tint: Color,
+) -> Vec<Span<'static>> {
+    let mut used = 0;
+    let mut ruled: Vec<Span<'static>> = spans
+        .into_iter()
+        .map(|span| {
+            used += span.width();
+            if span.style.bg.is_none() {
+                let style = span.style.bg(tint);
+                span.style(style)
+            } else {
+                span
+            }
+        })
+        .collect();
+    if used < width {
+        ruled.push(Span::styled(
+            " ".repeat(width - used),
+            Style::default().bg(tint),
+        ));
+    }
+    ruled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::search::SearchablePanel;
+    use crate::test_utils::test_helpers::{capture_terminal_state, create_test_terminal};
+    use crate::theme::current_theme;
+
+    /// The screen, each row followed by which cells have the ruler's tint
+    /// (`=`) or another background (`#`)
+    fn ruler_snapshot(reader: &mut MarkdownTextReader) -> String {
+        let mut terminal = create_test_terminal(40, 12);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+        let palette = current_theme();
+        let buffer = terminal.backend().buffer().clone();
+        capture_terminal_state(&terminal)
+            .lines()
+            .enumerate()
+            .map(|(y, text)| {
+                let tints: String = (0..buffer.area.width)
+                    .map(|x| match buffer[(x, y as u16)].bg {
+                        bg if bg == palette.base_01 => '=',
+                        bg if bg == palette.base_00 || bg == Color::Reset => ' ',
+                        _ => '#',
+                    })
+                    .collect();
+                format!("{text}\n{}", tints.trim_end())
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_ruler_at_the_center_row_snapshot() {
+        let html: String = (1..=12)
+            .map(|i| format!("<p>Line <b>{i}</b> of <em>styled</em> text</p>"))
+            .collect();
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(&html, None);
+        reader.set_reading_ruler(true);
+        ruler_snapshot(&mut reader);
+        reader.start_search();
+        reader.update_search_query("3 of");
+
+        // The search match keeps its own color over the ruler
+        let expected = [
+            "┌Chapter 1/1───────────────────────────┐",
+            "",
+            "│                                      │",
+            "",
+            "│ Line 1 of styled text                │",
+            "",
+            "│                                      │",
+            "",
+            "│ Line 2 of styled text                │",
+            "",
+            "│                                      │",
+            "",
+            "│ Line 3 of styled text                │",
+            "  =====####===========================",
+            "│                                      │",
+            "",
+            "│ Line 4 of styled text                │",
+            "",
+            "│                                      │",
+            "",
+            "│ Line 5 of styled text                │",
+            "",
+            "└───────────────────────────────── 37% ┘",
+            "",
+        ];
+        assert_eq!(ruler_snapshot(&mut reader), expected.join("\n"));
+
+        // Moved rows stay within the viewport
+        assert_eq!(reader.move_reading_ruler(-2), 2);
+        assert_eq!(reader.move_reading_ruler(100), 8);
+    }
+}
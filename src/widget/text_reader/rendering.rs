@@ -1,17 +1,43 @@
 use super::types::*;
 use crate::comments::{Comment, CommentTarget};
+use crate::glossing::word_ranges;
+use crate::i18n::tr_args;
 use crate::markdown::{
     Block as MarkdownBlock, Document, HeadingLevel, Inline, Node, Style, Text as MarkdownText,
-    TextOrInline,
+    TextDirection, TextOrInline,
 };
+use crate::settings::{RubyMode, RuleStyle};
 use crate::theme::Base16Palette;
 use crate::types::LinkInfo;
 use ratatui::{
     layout::Constraint,
-    style::{Color, Modifier, Style as RatatuiStyle},
+    style::{Modifier, Style as RatatuiStyle},
     text::{Line, Span},
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+
+/// Dimmed marker after a word ranked rare by the frequency list
+const GLOSS_MARKER: &str = "°";
+
+/// Bar down the left of a quote, one per level of nesting
+const QUOTE_BAR: &str = "│ ";
+
+/// Quote levels take the accent colors in turn, this many of them
+const QUOTE_BAR_COLORS: usize = 3;
+
+/// Quotes nested deeper than this stop moving right
+const MAX_QUOTE_INDENT_DEPTH: usize = 3;
+
+/// Bullets of unordered lists by nesting level, starting over after the last
+const LIST_BULLETS: [&str; 3] = ["•", "◦", "▪"];
+
+/// How deep a list is nested in other lists, and the columns its markers
+/// sit in from the indent
+#[derive(Clone, Copy)]
+struct ListLevel {
+    depth: usize,
+    lead: usize,
+}
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum RenderContext {
@@ -19,6 +45,22 @@ pub enum RenderContext {
     InsideContainer,
 }
 
+/// A wrapped line of text with the rich spans it covers
+struct WrappedLine {
+    text: String,
+    spans: Vec<RichSpan>,
+    rubies: Vec<RubyPlacement>,
+}
+
+/// A ruby group on a wrapped line, in char columns from the line start.
+/// `group_end` is past the base text and, in compact mode, its `(reading)`.
+struct RubyPlacement {
+    base: std::ops::Range<usize>,
+    group_end: usize,
+    reading: String,
+    style: RatatuiStyle,
+}
+
 #[allow(dead_code)]
 pub struct RenderingContext {
     pub raw_text_lines: Vec<String>,
@@ -55,17 +97,66 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         let mut lines = Vec::new();
         let mut total_height = 0;
 
+        self.clear_layout_state();
+        self.layout_blocks(
+            doc,
+            0..doc.blocks.len(),
+            &mut lines,
+            &mut total_height,
+            width,
+            palette,
+            is_focused,
+        );
+        self.finish_layout(&mut lines, &mut total_height, width, palette);
+
+        RenderedContent {
+            lines,
+            total_height,
+            generation: self.cache_generation,
+        }
+    }
+
+    /// Forget the line positions recorded by the previous layout
+    pub(super) fn clear_layout_state(&mut self) {
         self.raw_text_lines.clear();
+        self.ruby_reading_ranges.clear();
+        self.quote_bar_ranges.clear();
+        self.rtl_layouts.clear();
         self.anchor_positions.clear();
+        self.figure_alt_texts.clear();
+        self.figure_captions.clear();
+        self.figure_blocks.clear();
+        self.collapsed_texts.clear();
+        self.links.clear();
+        // Tables are recorded again on every render, in reading order
+        self.embedded_tables.borrow_mut().clear();
+    }
+
+    /// Lay out a run of the document's top-level blocks below `lines`
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn layout_blocks(
+        &mut self,
+        doc: &Document,
+        blocks: std::ops::Range<usize>,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        let first_line = lines.len();
+        let links_before = self.links.len();
 
-        // Iterate through all blocks in the document
-        for (node_idx, node) in doc.blocks.iter().enumerate() {
-            self.extract_and_track_anchors_from_node(node, total_height);
+        for node_idx in blocks {
+            let node = &doc.blocks[node_idx];
+            self.extract_and_track_anchors_from_node(node, *total_height);
+            let node_lines_start = lines.len();
+            self.rendering_block = Some(node_idx);
 
             self.render_node(
                 node,
-                &mut lines,
-                &mut total_height,
+                lines,
+                total_height,
                 width,
                 palette,
                 is_focused,
@@ -73,17 +164,192 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 Some(node_idx),
                 RenderContext::TopLevel,
             );
+
+            if node.dir == Some(TextDirection::Rtl) {
+                self.apply_rtl_layout(&lines[node_lines_start..], node_lines_start, width);
+            }
         }
+        self.rendering_block = None;
 
-        self.links.clear();
-        for rendered_line in &lines {
+        if self.max_blank_lines > 0 {
+            let blank_run = lines[..first_line]
+                .iter()
+                .rev()
+                .take_while(|line| Self::is_blank_line(line))
+                .take(self.max_blank_lines)
+                .count();
+            let mut added = lines.split_off(first_line);
+            let line_map = Self::squeeze_blank_lines(&mut added, self.max_blank_lines, blank_run);
+            if added.len() + 1 < line_map.len() {
+                self.remap_line_positions(first_line, &line_map, &mut added);
+                lines.append(&mut added);
+                *total_height = lines.len();
+            } else {
+                lines.append(&mut added);
+            }
+        }
+
+        self.links.truncate(links_before);
+        for rendered_line in &lines[first_line..] {
             self.links.extend(rendered_line.link_nodes.clone());
         }
+    }
 
-        RenderedContent {
-            lines,
-            total_height,
-            generation: self.cache_generation,
+    /// Close the layout once the last block is laid out
+    pub(super) fn finish_layout(
+        &mut self,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+    ) {
+        if let Some(title) = self.next_chapter_title.clone() {
+            self.render_chapter_separator(&title, lines, total_height, width, palette);
+        }
+    }
+
+    /// Dim `──── Next: Title ────` line marking where scrolling on advances
+    /// to the next chapter
+    fn render_chapter_separator(
+        &mut self,
+        title: &str,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+    ) {
+        let label = tr_args("content.next_chapter", &[("title", &title)]);
+        let label: String = label.chars().take(width.saturating_sub(8)).collect();
+        let rule = "─".repeat(width.saturating_sub(label.chars().count()) / 2);
+        let separator = format!("{rule}{label}{rule}");
+
+        if Self::last_line_has_content(lines) {
+            lines.push(RenderedLine::empty());
+            self.raw_text_lines.push(String::new());
+            *total_height += 1;
+        }
+        lines.push(RenderedLine {
+            spans: vec![Span::styled(
+                separator.clone(),
+                RatatuiStyle::default().fg(palette.base_03),
+            )],
+            raw_text: separator.clone(),
+            line_type: LineType::HorizontalRule,
+            ..RenderedLine::empty()
+        });
+        self.raw_text_lines.push(separator);
+        *total_height += 1;
+    }
+
+    fn is_blank_line(line: &RenderedLine) -> bool {
+        matches!(line.line_type, LineType::Text | LineType::Empty)
+            && line.raw_text.trim().is_empty()
+            && line.link_nodes.is_empty()
+            && line.inline_code_comments.is_empty()
+    }
+
+    /// Collapse runs of more than `max_blank` blank lines, which badly
+    /// converted books produce from stacks of empty paragraphs, counting
+    /// `blank_run` blank lines already above the first one. Returns the
+    /// new index of every old line plus one past the end; a removed line maps
+    /// to the line that followed it, which also inherits its node index and
+    /// anchor when it has none of its own.
+    pub(super) fn squeeze_blank_lines(
+        lines: &mut Vec<RenderedLine>,
+        max_blank: usize,
+        mut blank_run: usize,
+    ) -> Vec<usize> {
+        let mut line_map = Vec::with_capacity(lines.len() + 1);
+        let mut kept: Vec<RenderedLine> = Vec::with_capacity(lines.len());
+        let mut pending_anchor = None;
+        let mut pending_node_index = None;
+
+        for mut line in lines.drain(..) {
+            line_map.push(kept.len());
+
+            if Self::is_blank_line(&line) {
+                blank_run += 1;
+                if blank_run > max_blank {
+                    pending_anchor = pending_anchor.or(line.node_anchor.take());
+                    pending_node_index = pending_node_index.or(line.node_index.take());
+                    continue;
+                }
+            } else {
+                blank_run = 0;
+            }
+
+            if line.node_anchor.is_none() {
+                line.node_anchor = pending_anchor.take();
+            }
+            if line.node_index.is_none() {
+                line.node_index = pending_node_index.take();
+            }
+            pending_anchor = None;
+            pending_node_index = None;
+            kept.push(line);
+        }
+
+        line_map.push(kept.len());
+        *lines = kept;
+        line_map
+    }
+
+    /// Move everything that points into the lines laid out from
+    /// `first_line` on to where those lines ended up after
+    /// `squeeze_blank_lines`; positions outside them stay
+    fn remap_line_positions(
+        &mut self,
+        first_line: usize,
+        line_map: &[usize],
+        lines: &mut [RenderedLine],
+    ) {
+        let remap = |line: usize| match line.checked_sub(first_line) {
+            Some(offset) if offset < line_map.len() => first_line + line_map[offset],
+            _ => line,
+        };
+
+        let mut old_line: usize = 0;
+        self.raw_text_lines.retain(|_| {
+            let kept = match old_line.checked_sub(first_line) {
+                Some(offset) => match (line_map.get(offset), line_map.get(offset + 1)) {
+                    (Some(start), Some(next)) => next > start,
+                    _ => true,
+                },
+                None => true,
+            };
+            old_line += 1;
+            kept
+        });
+
+        for position in self.anchor_positions.values_mut() {
+            *position = remap(*position);
+        }
+
+        self.ruby_reading_ranges = std::mem::take(&mut self.ruby_reading_ranges)
+            .into_iter()
+            .map(|(line, ranges)| (remap(line), ranges))
+            .collect();
+        self.quote_bar_ranges = std::mem::take(&mut self.quote_bar_ranges)
+            .into_iter()
+            .map(|(line, ranges)| (remap(line), ranges))
+            .collect();
+        self.rtl_layouts = std::mem::take(&mut self.rtl_layouts)
+            .into_iter()
+            .map(|(line, layout)| (remap(line), layout))
+            .collect();
+
+        for image in self.embedded_images.borrow_mut().values_mut() {
+            image.lines_before_image = remap(image.lines_before_image);
+        }
+
+        for table in self.embedded_tables.borrow_mut().iter_mut() {
+            table.lines_before_table = remap(table.lines_before_table);
+        }
+
+        for line in lines.iter_mut() {
+            for link in &mut line.link_nodes {
+                link.line = remap(link.line);
+            }
         }
     }
 
@@ -230,9 +496,15 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 );
             }
 
-            Quote { content } => {
+            Quote {
+                content,
+                attribution,
+                depth,
+            } => {
                 self.render_quote(
                     content,
+                    attribution.as_ref(),
+                    *depth,
                     lines,
                     total_height,
                     width,
@@ -257,6 +529,23 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 );
             }
 
+            // Epigraphs read as the quotes they are
+            EpubBlock {
+                epub_type, content, ..
+            } if epub_type == "epigraph" => {
+                self.render_quote(
+                    content,
+                    None,
+                    0,
+                    lines,
+                    total_height,
+                    width,
+                    palette,
+                    is_focused,
+                    indent,
+                );
+            }
+
             EpubBlock {
                 epub_type,
                 element_name,
@@ -273,6 +562,20 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     is_focused,
                 );
             }
+
+            Collapsible { summary, content } => {
+                self.render_collapsible(
+                    summary,
+                    content,
+                    lines,
+                    total_height,
+                    width,
+                    palette,
+                    is_focused,
+                    indent,
+                    node_index,
+                );
+            }
         }
 
         if current_node_anchor.is_none() {
@@ -313,6 +616,9 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     Inline::Image { alt_text, .. } => {
                         result.push_str(alt_text);
                     }
+                    Inline::Ruby { base, .. } => {
+                        result.push_str(base);
+                    }
                     Inline::Anchor { .. } => {
                         // Anchors don't contribute to text content
                     }
@@ -431,6 +737,19 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 
+    /// The paragraph shows something: text, a link, ruby or an image
+    fn has_visible_content(content: &MarkdownText) -> bool {
+        content.iter().any(|item| match item {
+            TextOrInline::Text(t) => !t.content.trim().is_empty(),
+            TextOrInline::Inline(inline) => match inline {
+                Inline::Image { .. } => true,
+                Inline::Link { text, .. } => !Self::text_to_string(text).trim().is_empty(),
+                Inline::Ruby { base, .. } => !base.trim().is_empty(),
+                Inline::Anchor { .. } | Inline::LineBreak | Inline::SoftBreak => false,
+            },
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub fn render_paragraph(
         &mut self,
@@ -445,26 +764,46 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         context: RenderContext,
     ) {
         let _paragraph_lines_start = lines.len();
-        if context == RenderContext::InsideContainer {
-            let has_visible_content = content.iter().any(|item| match item {
-                TextOrInline::Text(t) => !t.content.trim().is_empty(),
-                TextOrInline::Inline(inline) => match inline {
-                    Inline::Image { .. } => true,
-                    Inline::Link { text, .. } => !Self::text_to_string(text).trim().is_empty(),
-                    Inline::Anchor { .. } | Inline::LineBreak | Inline::SoftBreak => false,
-                },
-            });
-
-            if !has_visible_content {
-                return;
-            }
+        if context == RenderContext::InsideContainer && !Self::has_visible_content(content) {
+            return;
         }
 
         let mut current_rich_spans = Vec::new();
         let mut has_content = false;
 
         for item in content.iter() {
+            if let TextOrInline::Inline(Inline::Image {
+                url,
+                alt_text,
+                caption,
+                ..
+            }) = item
+            {
+                if !alt_text.trim().is_empty() {
+                    self.figure_alt_texts.insert(url.clone(), alt_text.clone());
+                }
+                if let Some(caption) = caption {
+                    self.figure_captions.insert(url.clone(), caption.clone());
+                }
+                if let Some(block) = self.rendering_block {
+                    self.figure_blocks.entry(url.clone()).or_insert(block);
+                }
+            }
             match item {
+                TextOrInline::Inline(Inline::Image { alt_text, .. }) if !self.render_images => {
+                    // Images are switched off: keep a one-line label in the text flow
+                    let label = if alt_text.trim().is_empty() {
+                        "[image]".to_string()
+                    } else {
+                        format!("[image: {}]", alt_text.trim())
+                    };
+                    current_rich_spans.push(RichSpan::Text(Span::styled(
+                        label,
+                        RatatuiStyle::default()
+                            .fg(palette.base_03)
+                            .add_modifier(Modifier::ITALIC),
+                    )));
+                }
                 TextOrInline::Inline(Inline::Image { url, .. }) => {
                     // If we have accumulated text before the image, render it first
                     if !current_rich_spans.is_empty() {
@@ -553,7 +892,10 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         match item {
             TextOrInline::Text(text_node) => {
                 let styled_span = self.style_text_node(text_node, palette, is_focused);
-                rich_spans.push(RichSpan::Text(styled_span));
+                match self.gloss_words() {
+                    Some(words) => rich_spans.extend(Self::gloss_span(styled_span, words, palette)),
+                    None => rich_spans.push(RichSpan::Text(styled_span)),
+                }
             }
 
             TextOrInline::Inline(inline) => {
@@ -627,6 +969,17 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                         rich_spans.push(RichSpan::Text(Span::raw(format!("[image: {alt_text}]"))));
                     }
 
+                    Inline::Ruby { base, reading } => {
+                        let (normal_color, _, _) = palette.get_panel_colors(is_focused);
+                        rich_spans.push(RichSpan::Ruby {
+                            span: Span::styled(
+                                base.clone(),
+                                RatatuiStyle::default().fg(normal_color),
+                            ),
+                            reading: reading.clone(),
+                        });
+                    }
+
                     Inline::Anchor { .. } => {
                         // Anchors don't produce visible content - position tracking is handled elsewhere
                     }
@@ -645,6 +998,47 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         rich_spans
     }
 
+    fn gloss_words(&self) -> Option<&HashSet<String>> {
+        let block = self.rendering_block?;
+        self.gloss_targets.as_ref()?.words_in_block(block)
+    }
+
+    /// Split `span` after each rare word to insert a dimmed marker
+    fn gloss_span(
+        span: Span<'static>,
+        words: &HashSet<String>,
+        palette: &Base16Palette,
+    ) -> Vec<RichSpan> {
+        let text = span.content.to_string();
+        let mut pieces = Vec::new();
+        let mut last = 0;
+
+        for (range, word) in word_ranges(&text) {
+            if words.contains(&word) {
+                pieces.push(RichSpan::Text(Span::styled(
+                    text[last..range.end].to_string(),
+                    span.style,
+                )));
+                pieces.push(RichSpan::Text(Span::styled(
+                    GLOSS_MARKER,
+                    RatatuiStyle::default().fg(palette.base_03),
+                )));
+                last = range.end;
+            }
+        }
+
+        if pieces.is_empty() {
+            return vec![RichSpan::Text(span)];
+        }
+        if last < text.len() {
+            pieces.push(RichSpan::Text(Span::styled(
+                text[last..].to_string(),
+                span.style,
+            )));
+        }
+        pieces
+    }
+
     pub fn style_text_node(
         &self,
         node: &crate::markdown::TextNode,
@@ -666,8 +1060,14 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             }
             Some(Style::Emphasis) => base_style.add_modifier(Modifier::ITALIC),
             Some(Style::Code) => {
-                // Inline code with background
-                RatatuiStyle::default().fg(Color::Black).bg(Color::Gray)
+                // Shaded like a code block, in its color; wrapping drops the
+                // spaces at a break, so the shade ends with the text
+                let code_color = if is_focused {
+                    palette.base_0b
+                } else {
+                    palette.base_03
+                };
+                RatatuiStyle::default().fg(code_color).bg(palette.base_01)
             }
             Some(Style::Strikethrough) => base_style.add_modifier(Modifier::CROSSED_OUT),
             None => base_style,
@@ -957,96 +1357,18 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         indent: usize,
         node_index: Option<usize>,
     ) {
-        use crate::markdown::ListKind;
-
-        for (idx, item) in items.iter().enumerate() {
-            // Determine bullet/number for this item
-            let prefix = match kind {
-                ListKind::Unordered => "• ".to_string(),
-                ListKind::Ordered { start } => {
-                    let num = start + idx as u32;
-                    format!("{num}. ")
-                }
-            };
-
-            let mut first_block_line_count = 0;
-
-            // Render the list item content
-            // List items can contain multiple blocks (paragraphs, nested lists, etc.)
-            for (block_idx, block_node) in item.content.iter().enumerate() {
-                if block_idx == 0 {
-                    // First block gets the bullet/number prefix
-                    match &block_node.block {
-                        MarkdownBlock::Paragraph { content } => {
-                            let mut content_rich_spans = Vec::new();
-                            for item in content.iter() {
-                                content_rich_spans
-                                    .extend(self.render_text_or_inline(item, palette, is_focused));
-                            }
-
-                            let lines_before = lines.len();
-
-                            self.render_text_spans(
-                                &content_rich_spans,
-                                Some(&prefix),
-                                lines,
-                                total_height,
-                                width,
-                                indent,
-                                false,
-                                None,
-                            );
-
-                            first_block_line_count = lines.len() - lines_before;
-
-                            for (i, line) in lines[lines_before..].iter_mut().enumerate() {
-                                line.line_type = LineType::ListItem {
-                                    kind: kind.clone(),
-                                    indent,
-                                };
-                                if i == 0 && idx == 0 {
-                                    line.node_index = node_index;
-                                }
-                            }
-                        }
-                        _ => {
-                            let lines_before = lines.len();
-                            self.render_node(
-                                block_node,
-                                lines,
-                                total_height,
-                                width,
-                                palette,
-                                is_focused,
-                                indent + 1,
-                                None,
-                                RenderContext::InsideContainer,
-                            );
-                            first_block_line_count = lines.len() - lines_before;
-                        }
-                    }
-                } else {
-                    self.render_node(
-                        block_node,
-                        lines,
-                        total_height,
-                        width,
-                        palette,
-                        is_focused,
-                        indent + 1,
-                        None,
-                        RenderContext::InsideContainer,
-                    );
-                }
-            }
-
-            // Smart spacing: add empty line between items if first block is >2 lines
-            if idx + 1 < items.len() && first_block_line_count > 2 {
-                lines.push(RenderedLine::empty());
-                self.raw_text_lines.push(String::new());
-                *total_height += 1;
-            }
-        }
+        self.render_list_items(
+            kind,
+            items,
+            lines,
+            total_height,
+            width,
+            palette,
+            is_focused,
+            indent,
+            ListLevel { depth: 0, lead: 0 },
+            node_index,
+        );
 
         // Render comments for the list if it has a node_index
         if let Some(node_idx) = node_index {
@@ -1079,129 +1401,319 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 
+    /// The items of a list `level.lead` columns in from `indent`: each
+    /// first paragraph after its marker, wrapped lines and later paragraphs
+    /// under the item's text, and nested lists `list_indent` further in
     #[allow(clippy::too_many_arguments)]
-    pub fn render_table(
+    fn render_list_items(
         &mut self,
-        header: &Option<crate::markdown::TableRow>,
-        rows: &[crate::markdown::TableRow],
-        _alignment: &[crate::markdown::TableAlignment],
+        kind: &crate::markdown::ListKind,
+        items: &[crate::markdown::ListItem],
         lines: &mut Vec<RenderedLine>,
         total_height: &mut usize,
         width: usize,
         palette: &Base16Palette,
         is_focused: bool,
+        indent: usize,
+        level: ListLevel,
+        node_index: Option<usize>,
     ) {
-        // Convert markdown table to String format for Table widget
-        let mut table_rows = Vec::new();
-        let mut table_headers = Vec::new();
+        let lead = " ".repeat(level.lead);
 
-        // Process header
-        if let Some(header_row) = header {
-            table_headers = header_row
-                .cells
-                .iter()
-                .map(|cell| Self::text_to_string(&cell.content))
-                .collect();
-        }
+        for (idx, item) in items.iter().enumerate() {
+            // The number or the bullet of this level; copies get `-` for
+            // the bullet, as markdown writes it
+            let (marker, copied_marker) = match item.number_in(kind, idx) {
+                Some(number) => (format!("{number}. "), None),
+                None => (
+                    format!("{} ", LIST_BULLETS[level.depth % LIST_BULLETS.len()]),
+                    Some("- "),
+                ),
+            };
+            let marker_width = marker.chars().count();
+            let text_lead = " ".repeat(level.lead + marker_width);
+            // Blocks other than paragraphs and lists step in by whole indents
+            let block_indent = indent + (level.lead + marker_width).div_ceil(2);
 
-        // Process data rows
-        for row in rows {
-            let row_data: Vec<String> = row
-                .cells
-                .iter()
-                .map(|cell| Self::text_to_string(&cell.content))
-                .collect();
-            table_rows.push(row_data);
-        }
+            let mut first_block_line_count = 0;
+            let mut paragraphs = 0;
 
-        // Get dimensions for embedded table tracking
-        let num_cols = table_headers
-            .len()
-            .max(table_rows.iter().map(|r| r.len()).max().unwrap_or(0));
+            // List items can contain multiple blocks (paragraphs, nested lists, etc.)
+            for (block_idx, block_node) in item.content.iter().enumerate() {
+                let lines_before = lines.len();
+                match &block_node.block {
+                    MarkdownBlock::Paragraph { content }
+                        if block_idx == 0
+                            || (Self::has_visible_content(content)
+                                && !content.iter().any(|item| {
+                                    matches!(item, TextOrInline::Inline(Inline::Image { .. }))
+                                })) =>
+                    {
+                        // Later paragraphs are set apart and start under the text
+                        let prefix = if block_idx == 0 {
+                            format!("{lead}{marker}")
+                        } else {
+                            lines.push(RenderedLine::empty());
+                            self.raw_text_lines.push(String::new());
+                            *total_height += 1;
+                            text_lead.clone()
+                        };
+                        paragraphs += 1;
 
-        if num_cols == 0 {
-            return; // Empty table
-        }
+                        let mut content_rich_spans = Vec::new();
+                        for item in content.iter() {
+                            content_rich_spans
+                                .extend(self.render_text_or_inline(item, palette, is_focused));
+                        }
+                        let first_line = lines.len();
+                        self.render_text_spans(
+                            &content_rich_spans,
+                            Some(&prefix),
+                            lines,
+                            total_height,
+                            width,
+                            indent,
+                            false,
+                            None,
+                        );
+                        if block_idx == 0
+                            && let Some(copied_marker) = copied_marker
+                        {
+                            self.copy_list_marker(
+                                lines,
+                                first_line,
+                                2 * indent + level.lead,
+                                &marker,
+                                copied_marker,
+                            );
+                        }
 
-        // Store table position
-        let table_start_line = *total_height;
+                        for (i, line) in lines[first_line..].iter_mut().enumerate() {
+                            line.line_type = LineType::ListItem {
+                                kind: kind.clone(),
+                                indent,
+                            };
+                            if i == 0 && idx == 0 && block_idx == 0 {
+                                line.node_index = node_index;
+                            }
+                        }
+                    }
+                    MarkdownBlock::List {
+                        kind: nested_kind,
+                        items: nested_items,
+                    } => {
+                        self.render_list_items(
+                            nested_kind,
+                            nested_items,
+                            lines,
+                            total_height,
+                            width,
+                            palette,
+                            is_focused,
+                            indent,
+                            ListLevel {
+                                depth: level.depth + 1,
+                                lead: level.lead + self.list_indent.max(marker_width),
+                            },
+                            None,
+                        );
+                    }
+                    _ => {
+                        self.render_node(
+                            block_node,
+                            lines,
+                            total_height,
+                            width,
+                            palette,
+                            is_focused,
+                            block_indent,
+                            None,
+                            RenderContext::InsideContainer,
+                        );
+                    }
+                }
+                if block_idx == 0 {
+                    first_block_line_count = lines.len() - lines_before;
+                }
+            }
 
-        // Create balanced column constraints based on content
-        let constraints = self.calculate_balanced_column_widths(&table_headers, &table_rows, width);
+            // Smart spacing: add empty line between items if first block is
+            // >2 lines or the item has several paragraphs
+            if idx + 1 < items.len() && (first_block_line_count > 2 || paragraphs > 1) {
+                lines.push(RenderedLine::empty());
+                self.raw_text_lines.push(String::new());
+                *total_height += 1;
+            }
+        }
+    }
 
-        // Create table widget configuration
-        let table_config = crate::table::TableConfig {
-            border_color: palette.base_03,
-            header_color: if is_focused {
-                palette.base_0a
-            } else {
-                palette.base_03
-            },
-            text_color: if is_focused {
-                palette.base_05
-            } else {
-                palette.base_04
-            },
-            use_block: false,
-        };
+    /// Put `copied` in place of the bullet `marker` at column `at` of the
+    /// first line rendered from `first_line` on, in the text copies take
+    fn copy_list_marker(
+        &mut self,
+        lines: &mut [RenderedLine],
+        first_line: usize,
+        at: usize,
+        marker: &str,
+        copied: &str,
+    ) {
+        let raw_first = self.raw_text_lines.len() - (lines.len() - first_line);
+        // Skipping a ruby reading line above the text, if there is one
+        for (offset, line) in lines[first_line..].iter_mut().enumerate() {
+            let mut chars: Vec<char> = line.raw_text.chars().collect();
+            let width = marker.chars().count();
+            if chars
+                .get(at..at + width)
+                .is_some_and(|found| found.iter().copied().eq(marker.chars()))
+            {
+                chars.splice(at..at + width, copied.chars());
+                line.raw_text = chars.into_iter().collect();
+                self.raw_text_lines[raw_first + offset] = line.raw_text.clone();
+                return;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_table(
+        &mut self,
+        header: &Option<crate::markdown::TableRow>,
+        rows: &[crate::markdown::TableRow],
+        _alignment: &[crate::markdown::TableAlignment],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        // Convert markdown table to String format for Table widget
+        let mut table_rows = Vec::new();
+        let mut table_headers = Vec::new();
 
-        // Create the table widget
-        let mut custom_table = crate::table::Table::new(table_rows.clone())
-            .constraints(constraints)
-            .config(table_config);
+        // Process header
+        if let Some(header_row) = header {
+            table_headers = header_row
+                .cells
+                .iter()
+                .map(|cell| Self::text_to_string(&cell.content))
+                .collect();
+        }
 
-        if !table_headers.is_empty() {
-            custom_table = custom_table.header(table_headers.clone());
+        // Process data rows
+        for row in rows {
+            let row_data: Vec<String> = row
+                .cells
+                .iter()
+                .map(|cell| Self::text_to_string(&cell.content))
+                .collect();
+            table_rows.push(row_data);
         }
 
-        // Set base line for link tracking
-        custom_table = custom_table.base_line(table_start_line);
+        // Get dimensions for embedded table tracking
+        let num_cols = table_headers
+            .len()
+            .max(table_rows.iter().map(|r| r.len()).max().unwrap_or(0));
 
-        // Render the table to lines
-        let rendered_lines = custom_table.render_to_lines(width as u16);
+        if num_cols == 0 {
+            return; // Empty table
+        }
 
-        // Convert ratatui Lines to RenderedLines
-        for line in rendered_lines {
-            // Get raw text before moving spans
-            let raw_text = self.line_to_plain_text(&line);
+        // Store table position
+        let table_start_line = *total_height;
 
-            // Convert line spans to our format
-            let rendered_line = RenderedLine {
-                spans: line.spans,
-                raw_text: raw_text.clone(),
-                line_type: LineType::Text, // Table widget handles its own styling
-                link_nodes: vec![],
-                node_anchor: None,
-                node_index: None,
-                code_line: None,
-                inline_code_comments: Vec::new(),
+        // Narrow terminals squeeze a grid into unreadable slivers
+        if self.table_stack_width > 0 && width < self.table_stack_width && num_cols > 1 {
+            self.render_stacked_table(
+                header,
+                rows,
+                lines,
+                total_height,
+                width,
+                palette,
+                is_focused,
+            );
+        } else {
+            // Create balanced column constraints based on content
+            let constraints =
+                self.calculate_balanced_column_widths(&table_headers, &table_rows, width);
+
+            // Create table widget configuration
+            let table_config = crate::table::TableConfig {
+                border_color: palette.base_03,
+                header_color: if is_focused {
+                    palette.base_0a
+                } else {
+                    palette.base_03
+                },
+                text_color: if is_focused {
+                    palette.base_05
+                } else {
+                    palette.base_04
+                },
+                use_block: false,
             };
 
-            lines.push(rendered_line);
-            self.raw_text_lines.push(raw_text);
-            *total_height += 1;
-        }
+            // Create the table widget
+            let mut custom_table = crate::table::Table::new(table_rows.clone())
+                .constraints(constraints)
+                .config(table_config);
+
+            if !table_headers.is_empty() {
+                custom_table = custom_table.header(table_headers.clone());
+            }
 
-        // Extract and store links from the table
-        let table_links = custom_table.get_links();
-        self.links.extend(table_links.clone());
+            // Set base line for link tracking
+            custom_table = custom_table.base_line(table_start_line);
+
+            // Render the table to lines
+            let rendered_lines = custom_table.render_to_lines(width as u16);
+
+            // Convert ratatui Lines to RenderedLines
+            for line in rendered_lines {
+                // Get raw text before moving spans
+                let raw_text = self.line_to_plain_text(&line);
+
+                // Convert line spans to our format
+                let rendered_line = RenderedLine {
+                    spans: line.spans,
+                    raw_text: raw_text.clone(),
+                    line_type: LineType::Text, // Table widget handles its own styling
+                    link_nodes: vec![],
+                    node_anchor: None,
+                    node_index: None,
+                    code_line: None,
+                    inline_code_comments: Vec::new(),
+                };
 
-        // Store table info for click detection
+                lines.push(rendered_line);
+                self.raw_text_lines.push(raw_text);
+                *total_height += 1;
+            }
+
+            // Extract and store links from the table
+            let table_links = custom_table.get_links();
+            self.links.extend(table_links.clone());
+        }
+
+        // Store table info for click detection, except in collapsible
+        // blocks, which would shift the table order the book search uses
         let table_height = *total_height - table_start_line;
         let num_data_rows = table_rows.len();
-        self.embedded_tables.borrow_mut().push(EmbeddedTable {
-            lines_before_table: table_start_line,
-            num_rows: num_data_rows + if table_headers.is_empty() { 0 } else { 1 },
-            num_cols,
-            has_header: !table_headers.is_empty(),
-            header_row: if table_headers.is_empty() {
-                None
-            } else {
-                Some(table_headers)
-            },
-            data_rows: table_rows,
-            height_cells: table_height,
-        });
+        if !self.in_collapsible_body {
+            self.embedded_tables.borrow_mut().push(EmbeddedTable {
+                lines_before_table: table_start_line,
+                num_rows: num_data_rows + if table_headers.is_empty() { 0 } else { 1 },
+                num_cols,
+                has_header: !table_headers.is_empty(),
+                header_row: if table_headers.is_empty() {
+                    None
+                } else {
+                    Some(table_headers)
+                },
+                data_rows: table_rows,
+                height_cells: table_height,
+            });
+        }
 
         // Add empty line after table
         lines.push(RenderedLine {
@@ -1218,6 +1730,76 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         *total_height += 1;
     }
 
+    /// Each row as `header: value` lines, one per non-empty cell, with a
+    /// blank line between rows
+    #[allow(clippy::too_many_arguments)]
+    fn render_stacked_table(
+        &mut self,
+        header: &Option<crate::markdown::TableRow>,
+        rows: &[crate::markdown::TableRow],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+    ) {
+        let label_color = if is_focused {
+            palette.base_0a
+        } else {
+            palette.base_03
+        };
+        let labels: Vec<String> = header
+            .iter()
+            .flat_map(|row| &row.cells)
+            .map(|cell| Self::text_to_string(&cell.content).trim().to_string())
+            .collect();
+
+        let mut first_row = true;
+        for row in rows {
+            let mut cells = row
+                .cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| !Self::text_to_string(&cell.content).trim().is_empty())
+                .peekable();
+            if cells.peek().is_none() {
+                continue;
+            }
+            if !first_row {
+                lines.push(RenderedLine::empty());
+                self.raw_text_lines.push(String::new());
+                *total_height += 1;
+            }
+            first_row = false;
+
+            for (column, cell) in cells {
+                let mut rich_spans = Vec::new();
+                // Without a header row the values stand on their own
+                if let Some(label) = labels.get(column).filter(|label| !label.is_empty()) {
+                    rich_spans.push(RichSpan::Text(Span::styled(
+                        format!("{label}: "),
+                        RatatuiStyle::default()
+                            .fg(label_color)
+                            .add_modifier(Modifier::BOLD),
+                    )));
+                }
+                for item in cell.content.iter() {
+                    rich_spans.extend(self.render_text_or_inline(item, palette, is_focused));
+                }
+                self.render_text_spans(
+                    &rich_spans,
+                    None,
+                    lines,
+                    total_height,
+                    width,
+                    0,
+                    false,
+                    None,
+                );
+            }
+        }
+    }
+
     /// Calculate balanced column constraints for table rendering
     pub fn calculate_balanced_column_widths(
         &self,
@@ -1340,6 +1922,8 @@ impl crate::markdown_text_reader::MarkdownTextReader {
     pub fn render_quote(
         &mut self,
         content: &[Node],
+        attribution: Option<&MarkdownText>,
+        depth: usize,
         lines: &mut Vec<RenderedLine>,
         total_height: &mut usize,
         width: usize,
@@ -1347,13 +1931,25 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         is_focused: bool,
         indent: usize,
     ) {
-        // Render quote content with "> " prefix
+        // A closing paragraph that opens with a dash signs the quote, as a
+        // <footer> or <cite> would
+        let (content, attribution) = match (attribution, content.split_last()) {
+            (Some(attribution), _) => (content, Some(attribution.plain_text())),
+            (None, Some((last, rest))) => match Self::dash_attribution(last) {
+                Some(signature) if !rest.is_empty() => (rest, Some(signature)),
+                _ => (content, None),
+            },
+            (None, None) => (content, None),
+        };
+        let bar_column = 2 * indent;
+        let first_line = lines.len();
+
         for node in content {
             match &node.block {
                 MarkdownBlock::Paragraph {
                     content: para_content,
                 } => {
-                    // Render the rich text content with "> " prefix
+                    // Render the rich text content after the bar
                     let mut content_rich_spans = Vec::new();
                     for item in para_content.iter() {
                         content_rich_spans
@@ -1381,12 +1977,19 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                                 ),
                                 info,
                             },
+                            RichSpan::Ruby { span, reading } => RichSpan::Ruby {
+                                span: Span::styled(
+                                    span.content.clone(),
+                                    span.style.fg(quote_color).add_modifier(Modifier::ITALIC),
+                                ),
+                                reading,
+                            },
                         })
                         .collect();
 
                     self.render_text_spans(
                         &styled_rich_spans,
-                        Some("> "), // quote prefix
+                        Some(QUOTE_BAR),
                         lines,
                         total_height,
                         width,
@@ -1396,6 +1999,12 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     );
                 }
                 _ => {
+                    let nested_indent = match node.block {
+                        MarkdownBlock::Quote { depth, .. } if depth > MAX_QUOTE_INDENT_DEPTH => {
+                            indent
+                        }
+                        _ => indent + 1,
+                    };
                     self.render_node(
                         node,
                         lines,
@@ -1403,7 +2012,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                         width,
                         palette,
                         is_focused,
-                        indent + 1,
+                        nested_indent,
                         None,
                         RenderContext::InsideContainer,
                     );
@@ -1411,10 +2020,248 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             }
         }
 
-        // Add empty line after quote
+        if let Some(attribution) = attribution {
+            self.render_quote_attribution(
+                &attribution,
+                lines,
+                total_height,
+                width,
+                bar_column + QUOTE_BAR.chars().count(),
+                palette,
+            );
+        }
+        // The bar runs down everything in the quote, nested blocks included
+        self.paint_quote_bar(lines, first_line, bar_column, depth, palette);
+
+        // Add empty line after quote, one for a run of closing quotes
+        if lines.last().is_none_or(|line| !line.raw_text.is_empty()) {
+            lines.push(RenderedLine::empty());
+            self.raw_text_lines.push(String::new());
+            *total_height += 1;
+        }
+    }
+
+    /// The signature of a paragraph such as "— Seneca", without the dash
+    fn dash_attribution(node: &Node) -> Option<String> {
+        let MarkdownBlock::Paragraph { content } = &node.block else {
+            return None;
+        };
+        let text = content.plain_text();
+        let signature = text.trim().strip_prefix(['—', '―'])?.trim();
+        (!signature.is_empty()).then(|| signature.to_string())
+    }
+
+    /// Draw the bar of a quote `depth` deep at char column `column` of the
+    /// lines from `first_line` on, where the column was left blank for it
+    /// (or holds the bar the text was laid out with). Copies leave the bar
+    /// out unless `copy_quote_bars` is set.
+    fn paint_quote_bar(
+        &mut self,
+        lines: &mut [RenderedLine],
+        first_line: usize,
+        column: usize,
+        depth: usize,
+        palette: &Base16Palette,
+    ) {
+        let level = depth % QUOTE_BAR_COLORS;
+        let color = [palette.base_0d, palette.base_0e, palette.base_0c][level];
+        let bar_width = QUOTE_BAR.chars().count();
+        let bar_columns = column..column + bar_width;
+        let raw_first = self.raw_text_lines.len() - (lines.len() - first_line);
+        // The bar stops at the quote's last text, not at blank lines after it
+        let quoted = lines[first_line..]
+            .iter()
+            .rposition(|line| !line.raw_text.trim().is_empty())
+            .map_or(0, |last| last + 1);
+
+        for (offset, line) in lines[first_line..].iter_mut().take(quoted).enumerate() {
+            if matches!(line.line_type, LineType::ImagePlaceholder { .. }) {
+                continue;
+            }
+            let mut chars: Vec<char> = line.raw_text.chars().collect();
+            // Blank lines between the quote's blocks carry the bar on too
+            if chars.len() < bar_columns.end && chars.iter().all(|c| *c == ' ') {
+                let shown: usize = line.spans.iter().map(|s| s.content.chars().count()).sum();
+                line.spans
+                    .push(Span::raw(" ".repeat(bar_columns.end.saturating_sub(shown))));
+                chars.resize(bar_columns.end, ' ');
+            }
+            let Some(found) = chars.get(bar_columns.clone()) else {
+                continue;
+            };
+            let painted = self
+                .quote_bar_ranges
+                .get(&(raw_first + offset))
+                .is_some_and(|ranges| ranges.contains(&bar_columns));
+            if painted
+                || !found.iter().all(|c| *c == ' ') && !found.iter().copied().eq(QUOTE_BAR.chars())
+            {
+                continue;
+            }
+
+            // Cut the bar's columns out of the spans and put it in their place
+            let mut spans = Vec::with_capacity(line.spans.len() + 2);
+            let mut start = 0;
+            for span in line.spans.drain(..) {
+                let len = span.content.chars().count();
+                let end = start + len;
+                if end <= column || start >= bar_columns.end {
+                    spans.push(span);
+                } else {
+                    let before: String = span
+                        .content
+                        .chars()
+                        .take(column.saturating_sub(start))
+                        .collect();
+                    let after: String = span
+                        .content
+                        .chars()
+                        .skip(bar_columns.end.saturating_sub(start))
+                        .collect();
+                    if !before.is_empty() {
+                        spans.push(Span::styled(before, span.style));
+                    }
+                    if start <= column {
+                        spans.push(Span::styled(QUOTE_BAR, RatatuiStyle::default().fg(color)));
+                    }
+                    if !after.is_empty() {
+                        spans.push(Span::styled(after, span.style));
+                    }
+                }
+                start = end;
+            }
+            line.spans = spans;
+
+            chars.splice(bar_columns.clone(), QUOTE_BAR.chars());
+            line.raw_text = chars.into_iter().collect();
+            self.raw_text_lines[raw_first + offset] = line.raw_text.clone();
+            self.quote_bar_ranges
+                .entry(raw_first + offset)
+                .or_default()
+                .push(bar_columns.clone());
+        }
+    }
+
+    /// Render a quote attribution right-aligned and dimmed, prefixed with an
+    /// em dash, in the quote's column from `lead` on
+    fn render_quote_attribution(
+        &mut self,
+        attribution: &str,
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        lead: usize,
+        palette: &Base16Palette,
+    ) {
+        let text = format!("— {}", attribution.trim());
+        let style = RatatuiStyle::default()
+            .fg(palette.base_03)
+            .add_modifier(Modifier::DIM);
+
+        for wrapped in textwrap::wrap(&text, width.saturating_sub(lead).max(1)) {
+            let padding = width.saturating_sub(textwrap::core::display_width(&wrapped));
+            let raw_text = format!("{}{}", " ".repeat(padding), wrapped);
+
+            lines.push(RenderedLine {
+                spans: vec![
+                    Span::raw(" ".repeat(padding)),
+                    Span::styled(wrapped.to_string(), style),
+                ],
+                raw_text: raw_text.clone(),
+                line_type: LineType::Text,
+                link_nodes: vec![],
+                node_anchor: None,
+                node_index: None,
+                code_line: None,
+                inline_code_comments: Vec::new(),
+            });
+            self.raw_text_lines.push(raw_text);
+            *total_height += 1;
+        }
+    }
+
+    /// The summary behind a ▸ marker, or ▾ and the indented content once
+    /// expanded. Only top-level blocks can be toggled; others stay expanded.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_collapsible(
+        &mut self,
+        summary: &MarkdownText,
+        content: &[crate::markdown::Node],
+        lines: &mut Vec<RenderedLine>,
+        total_height: &mut usize,
+        width: usize,
+        palette: &Base16Palette,
+        is_focused: bool,
+        indent: usize,
+        node_index: Option<usize>,
+    ) {
+        let expanded = node_index.is_none_or(|index| self.expanded_blocks.contains(&index));
+        let marker = if expanded { "▾ " } else { "▸ " };
+        let summary_text = Self::text_to_string(summary);
+        let style = RatatuiStyle::default()
+            .fg(if is_focused {
+                palette.base_0d
+            } else {
+                palette.base_03
+            })
+            .add_modifier(Modifier::BOLD);
+        let line_type = match node_index {
+            Some(node_index) => LineType::Summary {
+                node_index,
+                expanded,
+            },
+            None => LineType::Text,
+        };
+
+        let text_width = width.saturating_sub(indent + 2).max(1);
+        for (i, wrapped) in textwrap::wrap(&summary_text, text_width).iter().enumerate() {
+            let prefix = format!(
+                "{}{}",
+                " ".repeat(indent),
+                if i == 0 { marker } else { "  " }
+            );
+            let raw_text = format!("{prefix}{wrapped}");
+            lines.push(RenderedLine {
+                spans: vec![Span::raw(prefix), Span::styled(wrapped.to_string(), style)],
+                raw_text: raw_text.clone(),
+                line_type: line_type.clone(),
+                link_nodes: vec![],
+                node_anchor: None,
+                node_index: None,
+                code_line: None,
+                inline_code_comments: Vec::new(),
+            });
+            self.raw_text_lines.push(raw_text);
+            *total_height += 1;
+        }
         lines.push(RenderedLine::empty());
         self.raw_text_lines.push(String::new());
         *total_height += 1;
+
+        if !expanded {
+            if let Some(node_index) = node_index {
+                let hidden: Vec<String> = content.iter().map(|node| node.plain_text()).collect();
+                self.collapsed_texts
+                    .insert(node_index, hidden.join("\n").to_lowercase());
+            }
+            return;
+        }
+
+        let was_in_body = std::mem::replace(&mut self.in_collapsible_body, true);
+        for node in content {
+            self.render_node(
+                node,
+                lines,
+                total_height,
+                width,
+                palette,
+                is_focused,
+                indent + 2,
+                None,
+                RenderContext::InsideContainer,
+            );
+        }
+        self.in_collapsible_body = was_in_body;
     }
 
     pub fn render_thematic_break(
@@ -1425,17 +2272,43 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         palette: &Base16Palette,
         is_focused: bool,
     ) {
-        let hr_line = "─".repeat(width);
+        let rule = match self.rule_style {
+            RuleStyle::Dinkus => "* * *".to_string(),
+            RuleStyle::Line => "─".repeat((width * 2 / 5).max(1)),
+            RuleStyle::Ornament => {
+                let glyph_width = Span::raw(self.rule_glyph.as_str()).width();
+                let side = "─".repeat(width.saturating_sub(glyph_width + 2) / 2);
+                format!("{side} {} {side}", self.rule_glyph)
+            }
+        };
+        let padding = width.saturating_sub(Span::raw(rule.as_str()).width()) / 2;
+        let hr_line = format!("{}{}", " ".repeat(padding), rule);
+
+        // Keep scene breaks apart from the text above them
+        if Self::last_line_has_content(lines) {
+            lines.push(RenderedLine::empty());
+            self.raw_text_lines.push(String::new());
+            *total_height += 1;
+        }
 
         lines.push(RenderedLine {
-            spans: vec![Span::styled(
-                hr_line.clone(),
-                RatatuiStyle::default().fg(if is_focused {
-                    palette.base_03
-                } else {
-                    palette.base_02
-                }),
-            )],
+            spans: vec![
+                Span::raw(" ".repeat(padding)),
+                Span::styled(
+                    rule,
+                    RatatuiStyle::default()
+                        .fg(if is_focused {
+                            palette.base_03
+                        } else {
+                            palette.base_02
+                        })
+                        .add_modifier(if self.rule_style == RuleStyle::Ornament {
+                            Modifier::DIM
+                        } else {
+                            Modifier::empty()
+                        }),
+                ),
+            ],
             raw_text: hr_line.clone(),
             line_type: LineType::HorizontalRule,
             link_nodes: vec![],
@@ -1496,6 +2369,13 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                         ),
                         info,
                     },
+                    RichSpan::Ruby { span, reading } => RichSpan::Ruby {
+                        span: Span::styled(
+                            span.content.clone(),
+                            span.style.fg(term_color).add_modifier(Modifier::BOLD),
+                        ),
+                        reading,
+                    },
                 })
                 .collect();
 
@@ -1686,15 +2566,6 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         };
         let wrappable_rich_spans: Vec<RichSpan> = rich_spans.to_vec();
 
-        // Convert rich spans to plain text for wrapping
-        let plain_text = wrappable_rich_spans
-            .iter()
-            .map(|rs| match rs {
-                RichSpan::Text(span) => span.content.as_ref(),
-                RichSpan::Link { span, .. } => span.content.as_ref(),
-            })
-            .collect::<String>();
-
         // Calculate available width after accounting for indentation
         let indent_str = "  ".repeat(indent);
         let indent_width_chars = indent_str.chars().count();
@@ -1704,28 +2575,99 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
         let available_width = available_width.max(1);
 
-        // Wrap the text
-        let wrapped = textwrap::wrap(&plain_text, available_width);
+        let has_ruby = wrappable_rich_spans
+            .iter()
+            .any(|rs| matches!(rs, RichSpan::Ruby { .. }));
+
+        let wrapped_lines: Vec<WrappedLine> = if has_ruby {
+            Self::wrap_ruby_spans(&wrappable_rich_spans, available_width, self.ruby_mode)
+        } else {
+            // Convert rich spans to plain text for wrapping
+            let plain_text = wrappable_rich_spans
+                .iter()
+                .map(|rs| match rs {
+                    RichSpan::Text(span) => span.content.as_ref(),
+                    RichSpan::Link { span, .. } => span.content.as_ref(),
+                    RichSpan::Ruby { span, .. } => span.content.as_ref(),
+                })
+                .collect::<String>();
+
+            // Wrap the text
+            let wrapped = textwrap::wrap(&plain_text, available_width);
+
+            wrapped
+                .iter()
+                .enumerate()
+                .map(|(line_idx, wrapped_line)| {
+                    // Map wrapped line back to rich spans
+                    let spans = if line_idx == 0 && wrapped.len() == 1 {
+                        // Single line - use all rich spans
+                        wrappable_rich_spans.clone()
+                    } else {
+                        // Multi-line content: map wrapped line back to rich spans
+                        self.map_wrapped_line_to_rich_spans(wrapped_line, &wrappable_rich_spans)
+                    };
+                    WrappedLine {
+                        text: wrapped_line.to_string(),
+                        spans,
+                        rubies: Vec::new(),
+                    }
+                })
+                .collect()
+        };
 
         // Create lines from wrapped text
-        for (line_idx, wrapped_line) in wrapped.iter().enumerate() {
+        for (line_idx, wrapped) in wrapped_lines.into_iter().enumerate() {
+            let WrappedLine {
+                text: wrapped_line,
+                spans: rich_spans_for_line,
+                rubies,
+            } = wrapped;
             let mut line_spans = Vec::new();
             let mut line_links = Vec::new();
-
-            // Map wrapped line back to rich spans
-            let rich_spans_for_line = if line_idx == 0 && wrapped.len() == 1 {
-                // Single line - use all rich spans
-                wrappable_rich_spans.clone()
-            } else {
-                // Multi-line content: map wrapped line back to rich spans
-                self.map_wrapped_line_to_rich_spans(wrapped_line, &wrappable_rich_spans)
-            };
+            let lead_width = indent_width_chars + prefix_width;
+
+            if !rubies.is_empty() {
+                match self.ruby_mode {
+                    RubyMode::TwoLine => {
+                        let annotation =
+                            Self::ruby_annotation_line(&wrapped_line, &rubies, lead_width);
+                        // The whole reading line is left out of copies
+                        let reading_cols = 0..annotation.chars().count();
+                        self.ruby_reading_ranges
+                            .insert(self.raw_text_lines.len(), vec![reading_cols]);
+                        lines.push(RenderedLine {
+                            spans: vec![Span::styled(
+                                annotation.clone(),
+                                rubies[0].style.add_modifier(Modifier::DIM),
+                            )],
+                            raw_text: annotation.clone(),
+                            line_type: LineType::Text,
+                            link_nodes: Vec::new(),
+                            node_anchor: None,
+                            node_index,
+                            code_line: None,
+                            inline_code_comments: Vec::new(),
+                        });
+                        self.raw_text_lines.push(annotation);
+                        *total_height += 1;
+                    }
+                    RubyMode::Compact => {
+                        let ranges = rubies
+                            .iter()
+                            .map(|ruby| lead_width + ruby.base.end..lead_width + ruby.group_end)
+                            .collect();
+                        self.ruby_reading_ranges
+                            .insert(self.raw_text_lines.len(), ranges);
+                    }
+                }
+            }
 
             // Extract spans and links, calculating positions
             let mut current_col = 0;
             for rich_span in rich_spans_for_line {
                 match rich_span {
-                    RichSpan::Text(span) => {
+                    RichSpan::Text(span) | RichSpan::Ruby { span, .. } => {
                         let len = span.content.chars().count();
                         line_spans.push(span);
                         current_col += len;
@@ -1778,7 +2720,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     final_raw_text.push_str(&prefix_padding);
                 }
             }
-            final_raw_text.push_str(wrapped_line.as_ref());
+            final_raw_text.push_str(&wrapped_line);
 
             lines.push(RenderedLine {
                 spans: line_spans,
@@ -1803,6 +2745,218 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         }
     }
 
+    /// Wrap rich spans containing ruby groups. Each group is a single
+    /// unbreakable fragment so the base text and its reading stay together.
+    fn wrap_ruby_spans(rich_spans: &[RichSpan], width: usize, mode: RubyMode) -> Vec<WrappedLine> {
+        use textwrap::core::Word;
+
+        struct Group {
+            start: usize,
+            base_end: usize,
+            end: usize,
+            reading: String,
+            style: RatatuiStyle,
+        }
+
+        // Lay out the display text: compact mode appends `(reading)` to each base
+        let mut flat = String::new();
+        let mut display_spans = Vec::new();
+        let mut groups = Vec::new();
+        for rich_span in rich_spans {
+            match rich_span {
+                RichSpan::Ruby { span, reading } => {
+                    let start = flat.len();
+                    flat.push_str(&span.content);
+                    let base_end = flat.len();
+                    display_spans.push(RichSpan::Text(span.clone()));
+                    if mode == RubyMode::Compact {
+                        let annotation = format!("({reading})");
+                        flat.push_str(&annotation);
+                        display_spans.push(RichSpan::Text(Span::styled(
+                            annotation,
+                            span.style.add_modifier(Modifier::DIM),
+                        )));
+                    }
+                    groups.push(Group {
+                        start,
+                        base_end,
+                        end: flat.len(),
+                        reading: reading.clone(),
+                        style: span.style,
+                    });
+                }
+                RichSpan::Text(span) | RichSpan::Link { span, .. } => {
+                    flat.push_str(&span.content);
+                    display_spans.push(rich_span.clone());
+                }
+            }
+        }
+
+        let char_col = |byte: usize| flat[..byte].chars().count();
+        let mut wrapped = Vec::new();
+        let mut paragraph_start = 0;
+        for paragraph in flat.split('\n') {
+            let paragraph_end = paragraph_start + paragraph.len();
+
+            // Build fragments with their byte offsets into `flat`
+            let mut words: Vec<Word> = Vec::new();
+            let mut offsets = Vec::new();
+            let mut cursor = paragraph_start;
+            for group in groups
+                .iter()
+                .filter(|g| g.start >= paragraph_start && g.end <= paragraph_end)
+            {
+                Self::push_wrap_fragments(
+                    &flat[cursor..group.start],
+                    cursor,
+                    width,
+                    &mut words,
+                    &mut offsets,
+                );
+                offsets.push(group.start);
+                words.push(Word::from(&flat[group.start..group.end]));
+                cursor = group.end;
+            }
+            Self::push_wrap_fragments(
+                &flat[cursor..paragraph_end],
+                cursor,
+                width,
+                &mut words,
+                &mut offsets,
+            );
+
+            if words.is_empty() {
+                wrapped.push(WrappedLine {
+                    text: String::new(),
+                    spans: Vec::new(),
+                    rubies: Vec::new(),
+                });
+            } else {
+                let mut word_idx = 0;
+                for (line_idx, line_words) in
+                    textwrap::wrap_algorithms::wrap_first_fit(&words, &[width as f64])
+                        .into_iter()
+                        .enumerate()
+                {
+                    let first = word_idx;
+                    word_idx += line_words.len();
+                    let last = word_idx - 1;
+
+                    let mut start = offsets[first];
+                    let end = offsets[last] + words[last].word.len();
+                    if line_idx > 0 {
+                        start += flat[start..end].len() - flat[start..end].trim_start().len();
+                    }
+                    let line_start_col = char_col(start);
+
+                    let rubies = groups
+                        .iter()
+                        .filter(|g| g.start >= start && g.end <= end)
+                        .map(|g| RubyPlacement {
+                            base: char_col(g.start) - line_start_col
+                                ..char_col(g.base_end) - line_start_col,
+                            group_end: char_col(g.end) - line_start_col,
+                            reading: g.reading.clone(),
+                            style: g.style,
+                        })
+                        .collect();
+
+                    wrapped.push(WrappedLine {
+                        text: flat[start..end].to_string(),
+                        spans: Self::slice_rich_spans(&display_spans, start..end),
+                        rubies,
+                    });
+                }
+            }
+
+            paragraph_start = paragraph_end + 1;
+        }
+
+        wrapped
+    }
+
+    fn push_wrap_fragments<'a>(
+        text: &'a str,
+        mut offset: usize,
+        width: usize,
+        words: &mut Vec<textwrap::core::Word<'a>>,
+        offsets: &mut Vec<usize>,
+    ) {
+        use textwrap::WordSeparator;
+
+        for word in WordSeparator::UnicodeBreakProperties.find_words(text) {
+            for piece in word.break_apart(width) {
+                offsets.push(offset);
+                offset += piece.word.len() + piece.whitespace.len();
+                words.push(piece);
+            }
+        }
+    }
+
+    /// The rich spans covering `range` (byte offsets into their concatenated text)
+    fn slice_rich_spans(rich_spans: &[RichSpan], range: std::ops::Range<usize>) -> Vec<RichSpan> {
+        let mut result = Vec::new();
+        let mut span_start = 0;
+        for rich_span in rich_spans {
+            let span = match rich_span {
+                RichSpan::Text(span)
+                | RichSpan::Link { span, .. }
+                | RichSpan::Ruby { span, .. } => span,
+            };
+            let span_end = span_start + span.content.len();
+            let from = range.start.max(span_start);
+            let to = range.end.min(span_end);
+            if from < to {
+                let piece = Span::styled(
+                    span.content[from - span_start..to - span_start].to_string(),
+                    span.style,
+                );
+                result.push(match rich_span {
+                    RichSpan::Text(_) => RichSpan::Text(piece),
+                    RichSpan::Link { info, .. } => RichSpan::Link {
+                        span: piece,
+                        info: info.clone(),
+                    },
+                    RichSpan::Ruby { reading, .. } => RichSpan::Ruby {
+                        span: piece,
+                        reading: reading.clone(),
+                    },
+                });
+            }
+            span_start = span_end;
+        }
+        result
+    }
+
+    /// The reading line drawn above a line of text in two-line ruby mode.
+    /// Each reading is centered over its base, shifted right to avoid overlap.
+    fn ruby_annotation_line(
+        line_text: &str,
+        rubies: &[RubyPlacement],
+        lead_width: usize,
+    ) -> String {
+        use textwrap::core::display_width;
+
+        let chars: Vec<char> = line_text.chars().collect();
+        let width_of =
+            |cols: std::ops::Range<usize>| display_width(&chars[cols].iter().collect::<String>());
+
+        let mut annotation = String::new();
+        let mut cursor = 0;
+        for ruby in rubies {
+            let base_start = lead_width + width_of(0..ruby.base.start);
+            let base_width = width_of(ruby.base.clone());
+            let reading_width = display_width(&ruby.reading);
+            let start = (base_start + base_width / 2)
+                .saturating_sub(reading_width / 2)
+                .max(cursor);
+            annotation.push_str(&" ".repeat(start - cursor));
+            annotation.push_str(&ruby.reading);
+            cursor = start + reading_width;
+        }
+        annotation
+    }
+
     /// Map a wrapped line back to its rich spans, preserving links
     pub fn map_wrapped_line_to_rich_spans(
         &self,
@@ -1823,6 +2977,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             let span_text = match rich_span {
                 RichSpan::Text(span) => &span.content,
                 RichSpan::Link { span, .. } => &span.content,
+                RichSpan::Ruby { span, .. } => &span.content,
             };
             for (char_idx, ch) in span_text.chars().enumerate() {
                 chars_with_rich.push(CharWithRichSpan {
@@ -1885,6 +3040,13 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                                     span: Span::styled(current_text.clone(), original_span.style),
                                     info: info.clone(),
                                 },
+                                RichSpan::Ruby {
+                                    span: original_span,
+                                    reading,
+                                } => RichSpan::Ruby {
+                                    span: Span::styled(current_text.clone(), original_span.style),
+                                    reading: reading.clone(),
+                                },
                             };
                             result_spans.push(new_rich_span);
                         }
@@ -1910,6 +3072,13 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                             span: Span::styled(current_text, original_span.style),
                             info: info.clone(),
                         },
+                        RichSpan::Ruby {
+                            span: original_span,
+                            reading,
+                        } => RichSpan::Ruby {
+                            span: Span::styled(current_text, original_span.style),
+                            reading: reading.clone(),
+                        },
                     };
                     result_spans.push(new_rich_span);
                 }
@@ -2024,9 +3193,615 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             *total_height += 1;
         }
 
+        self.render_image_note_marker(url, lines, total_height, palette);
+
         // Add empty line after image
         lines.push(RenderedLine::empty());
         self.raw_text_lines.push(String::new());
         *total_height += 1;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::parsing::html_to_markdown::HtmlToMarkdownConverter;
+    use crate::search::find_matches_in_text;
+    use crate::theme::current_theme;
+
+    fn render_raw_lines(html: &str, width: usize, ruby_mode: RubyMode) -> Vec<String> {
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        reader.set_ruby_mode(ruby_mode);
+        let rendered = reader.render_document_to_lines(&doc, width, current_theme(), true);
+        rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.clone())
+            .filter(|text| !text.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_inline_code_is_shaded_up_to_its_text() {
+        use super::super::text_selection::TextSelection;
+        use ratatui::style::Color;
+
+        let html = "<p>Call <code>read_line(&amp;mut buf)</code> and then <code>trim</code> it</p>";
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let mut reader = MarkdownTextReader::new();
+        let palette = current_theme();
+        let rendered = reader.render_document_to_lines(&doc, 20, palette, true);
+
+        // Each line with the shaded runs marked by brackets
+        let shaded: Vec<String> = rendered
+            .lines
+            .iter()
+            .filter(|line| !line.raw_text.is_empty())
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| match span.style.bg {
+                        Some(bg) if bg == palette.base_01 => {
+                            assert_eq!(span.style.fg, Some(palette.base_0b));
+                            format!("[{}]", span.content)
+                        }
+                        _ => span.content.to_string(),
+                    })
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            shaded,
+            ["Call [read_line(&mut]", "[buf)] and then [trim]", "it"]
+        );
+
+        // A selection over the code shows the selection color instead
+        let mut selection = TextSelection::new();
+        selection.start_selection(1, 0);
+        selection.update_selection(1, 2);
+        let line = selection.apply_selection_highlighting(
+            1,
+            rendered.lines[1].spans.clone(),
+            Color::Blue,
+            None,
+        );
+        assert_eq!(line.spans[0].content, "bu");
+        assert_eq!(line.spans[0].style.bg, Some(Color::Blue));
+        assert_eq!(line.spans[0].style.fg, Some(palette.base_0b));
+        assert_eq!(line.spans[1].style.bg, Some(palette.base_01));
+    }
+
+    const MIXED_RUBY: &str = "<p>今日は<ruby>漢字<rt>かんじ</rt></ruby>を読む</p>";
+
+    #[test]
+    fn test_ruby_compact_mode_inlines_reading() {
+        let lines = render_raw_lines(MIXED_RUBY, 40, RubyMode::Compact);
+        assert_eq!(lines, vec!["今日は漢字(かんじ)を読む"]);
+    }
+
+    #[test]
+    fn test_ruby_two_line_mode_puts_reading_above_base() {
+        let lines = render_raw_lines(MIXED_RUBY, 40, RubyMode::TwoLine);
+        assert_eq!(lines, vec!["     かんじ", "今日は漢字を読む"]);
+    }
+
+    #[test]
+    fn test_offscreen_render_wraps_to_the_viewport_and_margin() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(
+            "<p>Call me Ishmael. Some years ago never mind how long precisely</p>",
+            None,
+        );
+        reader.set_viewport(34, 20);
+        reader.render_offscreen();
+        // 34 columns less borders and padding leave 30 for the text
+        let lines: Vec<&str> = reader
+            .raw_text_lines()
+            .iter()
+            .map(|line| line.trim_end())
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Call me Ishmael. Some years",
+                "ago never mind how long",
+                "precisely"
+            ]
+        );
+        assert!(lines.iter().all(|line| line.chars().count() <= 30));
+
+        // A margin of 2 takes 4 columns from each side
+        reader.set_margin(2);
+        reader.render_offscreen();
+        let widest = reader
+            .rendered_content()
+            .lines
+            .iter()
+            .map(|line| line.raw_text.trim_end().chars().count())
+            .max();
+        assert!(widest.is_some_and(|width| width <= 22), "{widest:?}");
+    }
+
+    #[test]
+    fn test_typography_is_applied_when_laid_out_and_searchable_both_ways() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(r#"<p>"It's late..." -- he said</p>"#, None);
+        reader.set_viewport(80, 20);
+        reader.render_offscreen();
+        assert_eq!(
+            reader.raw_text_lines()[0].trim_end(),
+            r#""It's late..." -- he said"#
+        );
+
+        reader.set_typography(true);
+        reader.render_offscreen();
+        assert_eq!(
+            reader.raw_text_lines()[0].trim_end(),
+            "“It’s late…” — he said"
+        );
+        let lines = reader.raw_text_lines();
+        assert_eq!(find_matches_in_text("it's", lines).len(), 1);
+        assert_eq!(find_matches_in_text("\"it’s", lines).len(), 1);
+    }
+
+    /// Ten empty paragraphs between two paragraphs, then a scene break
+    fn blank_run_chapter() -> String {
+        format!(
+            "<p>One</p>{}<p id=\"two\">Two</p><hr/><p id=\"three\">Three</p>",
+            "<p><br/></p>".repeat(10)
+        )
+    }
+
+    fn render_with(reader: &mut MarkdownTextReader, html: &str, width: usize) -> RenderedContent {
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        reader.render_document_to_lines(&doc, width, current_theme(), true)
+    }
+
+    #[test]
+    fn test_blank_line_runs_are_squeezed_after_wrapping() {
+        let mut reader = MarkdownTextReader::new();
+        let rendered = render_with(&mut reader, &blank_run_chapter(), 40);
+
+        let raw: Vec<&str> = rendered.lines.iter().map(|l| l.raw_text.trim()).collect();
+        assert_eq!(
+            raw,
+            vec![
+                "One",
+                "",
+                "",
+                "Two",
+                "",
+                "────────────────",
+                "",
+                "Three",
+                ""
+            ]
+        );
+        assert_eq!(rendered.total_height, 9);
+        assert_eq!(reader.raw_text_lines.len(), 9);
+        assert_eq!(reader.get_anchor_position("two"), Some(3));
+        assert_eq!(reader.get_anchor_position("three"), Some(7));
+        assert_eq!(rendered.lines[5].line_type, LineType::HorizontalRule);
+        // Node indices are kept for restoring the reading position
+        assert_eq!(rendered.lines[3].node_index, Some(11));
+    }
+
+    #[test]
+    fn test_narrow_tables_are_stacked_under_their_headers() {
+        let html = "<table><thead><tr><th>Ship</th><th>Captain</th></tr></thead><tbody>\
+            <tr><td>Pequod</td><td>Ahab</td></tr>\
+            <tr><td>Rachel</td><td></td></tr></tbody></table>";
+        let mut reader = MarkdownTextReader::new();
+
+        let rendered = render_with(&mut reader, html, 40);
+        let raw: Vec<&str> = rendered
+            .lines
+            .iter()
+            .map(|l| l.raw_text.trim_end())
+            .collect();
+        assert_eq!(
+            raw,
+            vec!["Ship: Pequod", "Captain: Ahab", "", "Ship: Rachel", ""]
+        );
+        // Search results in the table still find where it starts
+        assert_eq!(reader.embedded_tables.borrow()[0].lines_before_table, 0);
+
+        // Wide enough for the grid, or stacking turned off
+        let rendered = render_with(&mut reader, html, 60);
+        assert!(
+            rendered
+                .lines
+                .iter()
+                .any(|l| l.raw_text.contains("Pequod") && l.raw_text.contains("Ahab"))
+        );
+        reader.set_table_stack_width(0);
+        let rendered = render_with(&mut reader, html, 40);
+        assert!(!rendered.lines.iter().any(|l| l.raw_text.contains("Ship:")));
+    }
+
+    #[test]
+    fn test_blank_line_squeezing_can_be_disabled() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_max_blank_lines(0);
+        let rendered = render_with(&mut reader, &blank_run_chapter(), 40);
+
+        assert_eq!(rendered.total_height, 38);
+        assert_eq!(reader.get_anchor_position("two"), Some(32));
+    }
+
+    #[test]
+    fn test_image_note_marker_shows_under_the_image() {
+        use crate::comments::{Comment, CommentTarget};
+
+        let mut reader = MarkdownTextReader::new();
+        let html = "<p>One</p><p><img src=\"fig.png\" alt=\"A chart\"/></p>";
+        let note = |content: &str| Comment {
+            chapter_href: "ch1.xhtml".to_string(),
+            target: CommentTarget::Image {
+                paragraph_index: 1,
+                src: "fig.png".to_string(),
+            },
+            content: content.to_string(),
+            updated_at: chrono::Utc::now(),
+            fingerprint: None,
+        };
+        reader
+            .current_chapter_comments
+            .insert(1, vec![note("Axis is log scale")]);
+        let rendered = render_with(&mut reader, html, 40);
+
+        let image_end = {
+            let images = reader.embedded_images.borrow();
+            images["fig.png"].lines_before_image + images["fig.png"].height_cells as usize
+        };
+        let below = &rendered.lines[image_end];
+        assert_eq!(below.raw_text, "◆ 1 note");
+        assert!(matches!(
+            &below.line_type,
+            LineType::Comment { target: CommentTarget::Image { src, .. }, .. } if src == "fig.png"
+        ));
+        // The note itself isn't quoted in the text
+        assert!(
+            !rendered
+                .lines
+                .iter()
+                .any(|l| l.raw_text.contains("log scale"))
+        );
+
+        reader
+            .current_chapter_comments
+            .insert(1, vec![note("One"), note("Two")]);
+        let rendered = render_with(&mut reader, html, 40);
+        assert!(rendered.lines.iter().any(|l| l.raw_text == "◆ 2 notes"));
+    }
+
+    #[test]
+    fn test_image_placeholder_moves_with_squeezed_lines() {
+        let mut reader = MarkdownTextReader::new();
+        let html = format!(
+            "<p>One</p>{}<p><img src=\"cover.png\"/></p>",
+            "<p><br/></p>".repeat(10)
+        );
+        let rendered = render_with(&mut reader, &html, 40);
+
+        let image_line = reader.embedded_images.borrow()["cover.png"].lines_before_image;
+        assert!(matches!(
+            rendered.lines[image_line].line_type,
+            LineType::ImagePlaceholder { .. }
+        ));
+        assert!(!matches!(
+            rendered.lines[image_line - 1].line_type,
+            LineType::ImagePlaceholder { .. }
+        ));
+    }
+
+    /// Render `html` into the reader and show it in a ten-line viewport
+    fn show_in_viewport(reader: &mut MarkdownTextReader, html: &str) {
+        let rendered = render_with(reader, html, 40);
+        reader.total_wrapped_lines = rendered.total_height;
+        reader.rendered_content = rendered;
+        reader.visible_height = 10;
+    }
+
+    #[test]
+    fn test_hidden_images_collapse_to_one_line_labels() {
+        let html = "<p>Before</p><p><img src=\"map.png\" alt=\" The map \"/></p>\
+                    <p><img src=\"plain.png\"/></p><p>After</p>";
+        let is_image =
+            |line: &RenderedLine| matches!(line.line_type, LineType::ImagePlaceholder { .. });
+
+        let mut reader = MarkdownTextReader::new();
+        let shown = render_with(&mut reader, html, 40);
+        assert!(shown.lines.iter().any(is_image));
+
+        reader.toggle_render_images();
+        assert!(!reader.is_rendering_images());
+        let hidden = render_with(&mut reader, html, 40);
+        assert!(!hidden.lines.iter().any(is_image));
+        let raw: Vec<&str> = hidden
+            .lines
+            .iter()
+            .map(|line| line.raw_text.trim())
+            .filter(|text| !text.is_empty())
+            .collect();
+        assert_eq!(raw, ["Before", "[image: The map]", "[image]", "After"]);
+        assert!(hidden.total_height < shown.total_height);
+
+        // Switched back on, the placeholders return
+        reader.toggle_render_images();
+        assert!(
+            render_with(&mut reader, html, 40)
+                .lines
+                .iter()
+                .any(is_image)
+        );
+    }
+
+    #[test]
+    fn test_image_jumps_visit_every_occurrence_and_wrap_around() {
+        let mut reader = MarkdownTextReader::new();
+        let filler = "<p>Filler paragraph.</p>".repeat(20);
+        let html = format!(
+            "<p>One</p><p><img src=\"a.png\"/></p>{filler}<p><img src=\"b.png\"/></p>{filler}<p><img src=\"a.png\"/></p>{filler}"
+        );
+        show_in_viewport(&mut reader, &html);
+
+        let visited: Vec<(String, usize, usize)> = (0..4)
+            .map(|_| {
+                let jump = reader.scroll_to_next_image().unwrap();
+                (jump.src, jump.index, jump.total)
+            })
+            .collect();
+        assert_eq!(
+            visited,
+            vec![
+                ("a.png".to_string(), 0, 3),
+                ("b.png".to_string(), 1, 3),
+                ("a.png".to_string(), 2, 3),
+                ("a.png".to_string(), 0, 3),
+            ]
+        );
+
+        // Backwards from the first image wraps to the last one
+        let jump = reader.scroll_to_prev_image().unwrap();
+        assert_eq!((jump.src.as_str(), jump.index), ("a.png", 2));
+    }
+
+    #[test]
+    fn test_image_jumps_without_images_stay_put() {
+        let mut reader = MarkdownTextReader::new();
+        show_in_viewport(&mut reader, &"<p>Filler paragraph.</p>".repeat(30));
+        reader.scroll_offset = 5;
+
+        assert!(reader.scroll_to_next_image().is_none());
+        assert!(reader.scroll_to_prev_image().is_none());
+        assert_eq!(reader.scroll_offset, 5);
+    }
+
+    #[test]
+    fn test_thematic_break_styles_are_centered() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_rule_style(RuleStyle::Dinkus);
+        let rendered = render_with(&mut reader, "<p>A</p><hr/><p>B</p>", 41);
+        assert_eq!(
+            rendered.lines[2].raw_text,
+            format!("{}* * *", " ".repeat(18))
+        );
+
+        reader.set_rule_style(RuleStyle::Line);
+        let rendered = render_with(&mut reader, "<p>A</p><hr/><p>B</p>", 40);
+        assert_eq!(
+            rendered.lines[2].raw_text,
+            format!("{}{}", " ".repeat(12), "─".repeat(16))
+        );
+    }
+
+    #[test]
+    fn test_ornament_rule_spans_the_width_as_one_line() {
+        let mut reader = MarkdownTextReader::new();
+        reader.set_rule_style(RuleStyle::Ornament);
+        let rendered = render_with(&mut reader, "<p>A</p><hr/><p>B</p>", 21);
+        let texts: Vec<&str> = rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.as_str())
+            .collect();
+        assert_eq!(texts[..5], ["A", "", "───────── ❧ ─────────", "", "B"]);
+        assert_eq!(rendered.lines[2].line_type, LineType::HorizontalRule);
+
+        reader.set_rule_glyph("§".to_string());
+        let rendered = render_with(&mut reader, "<p>A</p><hr/><p>B</p>", 10);
+        assert_eq!(rendered.lines[2].raw_text, "─── § ───");
+    }
+
+    #[test]
+    fn test_ruby_group_is_never_split_by_wrapping() {
+        let html = "<p>あいう<ruby>東<rt>ひがし</rt></ruby>えお</p>";
+        let lines = render_raw_lines(html, 10, RubyMode::Compact);
+        assert_eq!(lines, vec!["あいう", "東(ひがし)", "えお"]);
+    }
+
+    #[test]
+    fn test_rare_words_are_marked_until_removed() {
+        use crate::glossing::{WordFrequencies, find_rare_words};
+
+        let html = "<p>The moor.</p><p>Again, <em>the moor</em>.</p>";
+        let doc = HtmlToMarkdownConverter::new().convert(html);
+        let frequencies = WordFrequencies::parse("the\nagain\nmoor\n");
+        let mut reader = MarkdownTextReader::new();
+        reader.gloss_targets = Some(find_rare_words(&doc, &frequencies, 2, &HashSet::new()));
+
+        let rendered = reader.render_document_to_lines(&doc, 40, current_theme(), true);
+        let raw: Vec<&str> = rendered.lines.iter().map(|l| l.raw_text.trim()).collect();
+        assert_eq!(raw, vec!["The moor°.", "", "Again, the moor°.", ""]);
+
+        reader.remove_gloss_word("moor");
+        let rendered = reader.render_document_to_lines(&doc, 40, current_theme(), true);
+        assert_eq!(rendered.lines[0].raw_text, "The moor.");
+    }
+
+    #[test]
+    fn test_nested_quotes_step_in_with_a_bar_per_level() {
+        let html = "<blockquote><p>Outer</p><blockquote><p>Inner words that wrap</p>\
+                    <blockquote><p>Second</p><blockquote><blockquote><p>Deep</p>\
+                    </blockquote></blockquote></blockquote></blockquote><p>Back</p>\
+                    </blockquote>";
+        let mut reader = MarkdownTextReader::new();
+        let rendered = render_with(&mut reader, html, 20);
+        let raw: Vec<&str> = rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect();
+        // Past three levels quotes stop stepping in
+        assert_eq!(
+            raw,
+            [
+                "│ Outer",
+                "│ │ Inner words that",
+                "│ │ wrap",
+                "│ │ │ Second",
+                "│ │ │ │ Deep",
+                "│ ",
+                "│ Back",
+            ]
+        );
+        let raw_lines: Vec<&str> = rendered.lines.iter().map(|l| l.raw_text.as_str()).collect();
+        assert_eq!(reader.raw_text_lines, raw_lines);
+        let wrap = rendered.lines.iter().find(|l| l.raw_text.ends_with("wrap"));
+        let colors: Vec<_> = wrap.unwrap().spans[..2]
+            .iter()
+            .map(|s| s.style.fg)
+            .collect();
+        let palette = current_theme();
+        assert_eq!(colors, [Some(palette.base_0d), Some(palette.base_0e)]);
+    }
+
+    #[test]
+    fn test_quote_bar_runs_down_a_list_to_the_signature() {
+        let html = "<blockquote><p>House rules:</p><ul><li>Be kind to the others</li>\
+                    <li>Be brief</li></ul><p>— The Editor</p></blockquote><p>After</p>";
+        let mut reader = MarkdownTextReader::new();
+        let rendered = render_with(&mut reader, html, 24);
+        let shown: Vec<String> = rendered
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                "│ House rules:",
+                "│ • Be kind to the",
+                "│   others",
+                "│ • Be brief",
+                "│ ",
+                "│           — The Editor",
+                "",
+                "After",
+                "",
+            ]
+        );
+
+        // Copies leave the bars out unless asked to keep them
+        let copy = |reader: &mut MarkdownTextReader| {
+            reader.text_selection.start_selection(0, 0);
+            reader.text_selection.update_selection(5, 24);
+            reader.selected_copy_text().unwrap()
+        };
+        assert_eq!(
+            copy(&mut reader),
+            "House rules:\n- Be kind to the\n  others\n- Be brief\n\n          — The Editor"
+        );
+        reader.set_copy_quote_bars(true);
+        assert!(copy(&mut reader).starts_with("│ House rules:\n│ - Be kind"));
+    }
+
+    #[test]
+    fn test_nested_lists_step_in_with_their_own_bullets() {
+        let html = "<ul><li>Fruit that is ripe<ul><li>Apples<ul><li>Green</li></ul></li>\
+                    <li>Pears</li></ul></li><li>Bread</li></ul>";
+        let mut reader = MarkdownTextReader::new();
+        let rendered = render_with(&mut reader, html, 16);
+        let shown: Vec<String> = rendered
+            .lines
+            .iter()
+            .map(|line| {
+                line.spans
+                    .iter()
+                    .map(|span| span.content.as_ref())
+                    .collect()
+            })
+            .filter(|text: &String| !text.is_empty())
+            .collect();
+        assert_eq!(
+            shown,
+            [
+                "• Fruit that is",
+                "  ripe",
+                "  ◦ Apples",
+                "    ▪ Green",
+                "  ◦ Pears",
+                "• Bread",
+            ]
+        );
+        // Copies carry markdown's bullet in the same columns
+        let raw: Vec<&str> = rendered
+            .lines
+            .iter()
+            .map(|line| line.raw_text.as_str())
+            .filter(|text| !text.is_empty())
+            .collect();
+        assert_eq!(
+            raw,
+            [
+                "- Fruit that is",
+                "  ripe",
+                "  - Apples",
+                "    - Green",
+                "  - Pears",
+                "- Bread",
+            ]
+        );
+        let raw_lines: Vec<&str> = rendered.lines.iter().map(|l| l.raw_text.as_str()).collect();
+        assert_eq!(reader.raw_text_lines, raw_lines);
+
+        reader.set_list_indent(4);
+        let rendered = render_with(&mut reader, html, 16);
+        assert!(
+            rendered
+                .lines
+                .iter()
+                .any(|line| line.raw_text == "        - Green")
+        );
+    }
+
+    #[test]
+    fn test_ordered_list_keeps_its_start_and_aligns_item_paragraphs() {
+        let html = "<blockquote><ol start=\"14\"><li><p>First point made</p>\
+                    <p>More on it</p></li><li value=\"20\">Jumps</li><li>Then</li>\
+                    </ol></blockquote>";
+        let lines = render_raw_lines(html, 20, RubyMode::Compact);
+        assert_eq!(
+            lines,
+            [
+                "│ 14. First point",
+                "│     made",
+                "│ ",
+                "│     More on it",
+                "│ ",
+                "│ 20. Jumps",
+                "│ 21. Then",
+            ]
+        );
+    }
+}
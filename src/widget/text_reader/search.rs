@@ -1,4 +1,7 @@
-use crate::search::{SearchState, SearchablePanel, find_matches_in_text};
+use crate::markdown_text_reader::text_selection::{LogicalPoint, SelectionPoint};
+use crate::search::{
+    SearchMatch, SearchState, SearchablePanel, find_matches_in_text, match_ranges,
+};
 use crate::theme::Base16Palette;
 use ratatui::style::{Color, Style as RatatuiStyle};
 use ratatui::text::Span;
@@ -13,6 +16,134 @@ impl crate::markdown_text_reader::MarkdownTextReader {
             .collect()
     }
 
+    /// Replace the query being typed with an older (Up) or newer (Down) one
+    /// of `queries`
+    pub fn recall_search_query(&mut self, queries: &[String], older: bool) {
+        let recalled = if older {
+            let current = self.search_state.query.clone();
+            self.search_state.history_recall.older(queries, &current)
+        } else {
+            self.search_state.history_recall.newer(queries)
+        };
+        if let Some(query) = recalled {
+            self.update_search_query(&query);
+        }
+    }
+
+    /// Matches in the rendered lines plus images whose alt text matches, at
+    /// the image's first line, and collapsed blocks whose hidden content
+    /// matches, at their summary; those two have no highlighted range
+    fn find_search_matches(&self, query: &str) -> Vec<SearchMatch> {
+        let mut matches = find_matches_in_text(query, &self.get_visible_text());
+        if query.is_empty() {
+            return matches;
+        }
+
+        let query = query.to_lowercase();
+        let mut lines = self.collapsed_match_lines(&query);
+        if self.render_images {
+            for (line, src) in self.image_positions() {
+                if self
+                    .figure_alt_texts
+                    .get(&src)
+                    .is_some_and(|alt| !match_ranges(alt, &query).is_empty())
+                {
+                    lines.push(line);
+                }
+            }
+        }
+        for line in lines {
+            if !matches.iter().any(|m| m.index == line) {
+                matches.push(SearchMatch {
+                    index: line,
+                    score: 1.0,
+                    highlight_ranges: Vec::new(),
+                });
+            }
+        }
+        matches.sort_by_key(|m| m.index);
+        matches
+    }
+
+    /// Search the chapter for the selected text, or the word last clicked
+    /// without a selection, and go to its next occurrence (the previous one
+    /// unless `forward`), like vim's `*` and `#`. Returns the query, None
+    /// when there is nothing to search for.
+    pub fn search_for_selection(&mut self, forward: bool) -> Option<String> {
+        let (query, line) = self.selection_search_query()?;
+        self.text_selection.clear_selection();
+
+        self.start_search();
+        self.update_search_query(&query);
+        self.search_state.confirm_search();
+
+        let matches = &self.search_state.matches;
+        let target = if forward {
+            matches
+                .iter()
+                .position(|m| m.index > line)
+                .or((!matches.is_empty()).then_some(0))
+        } else {
+            matches
+                .iter()
+                .rposition(|m| m.index < line)
+                .or(matches.len().checked_sub(1))
+        };
+        if let Some(target) = target {
+            let target_line = matches[target].index;
+            self.search_state.current_match_index = Some(target);
+            self.jump_to_match(target_line);
+        }
+        Some(query)
+    }
+
+    /// Line of the current search match
+    pub fn current_search_match_line(&self) -> Option<usize> {
+        let idx = self.search_state.current_match_index?;
+        self.search_state.matches.get(idx).map(|m| m.index)
+    }
+
+    /// Where the current search match is in the document, to find it again
+    /// once the lines are re-rendered
+    pub fn search_match_anchor(&self) -> Option<LogicalPoint> {
+        if self.search_state.query.is_empty() || self.search_state.matches.is_empty() {
+            return None;
+        }
+        let point = match self
+            .search_state
+            .current_match_index
+            .and_then(|idx| self.search_state.matches.get(idx))
+        {
+            Some(search_match) => SelectionPoint {
+                line: search_match.index,
+                column: search_match.highlight_ranges.first().map_or(0, |r| r.0),
+            },
+            None => SelectionPoint {
+                line: self.scroll_offset,
+                column: 0,
+            },
+        };
+        Some(LogicalPoint::from_visual(
+            &point,
+            &self.rendered_content.lines,
+        ))
+    }
+
+    /// Search the re-rendered lines again, keeping the current match on the same text
+    pub fn reanchor_search_matches(&mut self, anchor: &LogicalPoint) {
+        let matches = self.find_search_matches(&self.search_state.query);
+        let line = anchor.to_visual(&self.rendered_content.lines, false).line;
+        if self.search_state.current_match_index.is_some() {
+            self.search_state.current_match_index = (!matches.is_empty()).then(|| {
+                matches
+                    .iter()
+                    .position(|search_match| search_match.index >= line)
+                    .unwrap_or(0)
+            });
+        }
+        self.search_state.matches = matches;
+    }
+
     /// Apply search highlighting to a line's spans
     pub fn apply_search_highlighting(
         &self,
@@ -140,10 +271,10 @@ impl SearchablePanel for crate::markdown_text_reader::MarkdownTextReader {
 
     fn update_search_query(&mut self, query: &str) {
         self.search_state.update_query(query.to_string());
+        self.complete_layout();
 
-        // Find matches in visible text
-        let searchable = self.get_searchable_content();
-        let matches = find_matches_in_text(query, &searchable);
+        // Find matches in visible text and image alt texts
+        let matches = self.find_search_matches(query);
         self.search_state.set_matches(matches);
 
         // Jump to match if found
@@ -235,9 +366,74 @@ impl SearchablePanel for crate::markdown_text_reader::MarkdownTextReader {
 
     fn jump_to_match(&mut self, match_index: usize) {
         self.jump_to_line(match_index);
+        // A match hidden in a collapsed block expands it; the jump to the
+        // match itself follows the re-render
+        if self.expand_collapsible_at(match_index) {
+            self.pending_search_jump = true;
+        }
     }
 
     fn get_searchable_content(&self) -> Vec<String> {
         self.get_visible_text()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::search::SearchMode;
+    use crate::test_utils::test_helpers::create_test_terminal;
+    use crate::theme::current_theme;
+
+    fn line_of(reader: &MarkdownTextReader, text: &str) -> usize {
+        reader
+            .rendered_content
+            .lines
+            .iter()
+            .position(|line| line.raw_text.contains(text))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_star_searches_the_clicked_word_or_selection() {
+        let mut reader = MarkdownTextReader::new();
+        let mut terminal = create_test_terminal(80, 24);
+        reader.set_content_from_string(
+            "<p>Call me whale.</p><p>A ship.</p><p>The white whale.</p><p>Whale ho.</p>",
+            None,
+        );
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+        assert_eq!(reader.search_for_selection(true), None);
+
+        let first = line_of(&reader, "Call me whale");
+        reader.last_click = Some(SelectionPoint {
+            line: first,
+            column: 9,
+        });
+        assert_eq!(reader.search_for_selection(true).as_deref(), Some("whale"));
+        assert_eq!(reader.search_state.mode, SearchMode::NavigationMode);
+        assert_eq!(reader.search_state.matches.len(), 3);
+        assert_eq!(
+            reader.current_search_match_line(),
+            Some(line_of(&reader, "The white whale"))
+        );
+
+        // Backwards from the first occurrence wraps to the last
+        assert_eq!(reader.search_for_selection(false).as_deref(), Some("whale"));
+        assert_eq!(
+            reader.current_search_match_line(),
+            Some(line_of(&reader, "Whale ho"))
+        );
+
+        let ship = line_of(&reader, "A ship");
+        reader.text_selection.start_selection(ship, 0);
+        reader.text_selection.update_selection(ship, 6);
+        reader.text_selection.end_selection();
+        assert_eq!(reader.search_for_selection(true).as_deref(), Some("A ship"));
+        assert!(!reader.has_text_selection());
+        assert_eq!(reader.current_search_match_line(), Some(ship));
+    }
+}
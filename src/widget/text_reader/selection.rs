@@ -1,5 +1,12 @@
+use super::text_selection::{SelectionPoint, TextSelection};
+use super::types::LineType;
+use crate::markdown::Node;
+use crate::parsing::typography::plain_punctuation;
+use crate::settings::ClickAction;
 use log::debug;
 use ratatui::layout::Rect;
+use ratatui::style::Modifier;
+use ratatui::text::Span;
 
 impl crate::markdown_text_reader::MarkdownTextReader {
     pub fn handle_mouse_down(&mut self, x: u16, y: u16) {
@@ -10,12 +17,23 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                     return;
                 }
 
-                self.text_selection.start_selection(line, column);
+                match self.click_action {
+                    ClickAction::Select => self.text_selection.start_selection(line, column),
+                    ClickAction::Position => {
+                        self.text_selection.clear_selection();
+                        self.press_point = Some(SelectionPoint { line, column });
+                    }
+                }
+                self.last_click = Some(SelectionPoint { line, column });
             }
         }
     }
 
     pub fn handle_mouse_drag(&mut self, x: u16, y: u16) {
+        if let Some(press) = self.press_point.take() {
+            self.text_selection
+                .start_selection(press.line, press.column);
+        }
         if self.text_selection.is_selecting && self.last_inner_text_area.is_some() {
             // Use the inner text area if available, otherwise fall back to the provided area
             let text_area = self.last_inner_text_area.unwrap();
@@ -48,6 +66,7 @@ impl crate::markdown_text_reader::MarkdownTextReader {
 
     pub fn handle_mouse_up(&mut self, x: u16, y: u16) -> Option<String> {
         self.auto_scroll_active = false;
+        self.press_point = None;
 
         let text_area = self.last_inner_text_area?;
 
@@ -92,15 +111,82 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         self.text_selection.clear_selection();
     }
 
+    /// Text for `*` to search for and the line it is on: the first line of
+    /// the selection, or without one the word last clicked
+    pub(super) fn selection_search_query(&self) -> Option<(String, usize)> {
+        let lines = self.copy_masked_text_lines();
+        let (text, line) = match self.text_selection.get_selection_range() {
+            Some((start, _)) if self.text_selection.has_selection() => (
+                self.text_selection.extract_selected_text(&lines)?,
+                start.line,
+            ),
+            _ => {
+                let click = self.last_click.as_ref()?;
+                let mut word = TextSelection::new();
+                word.select_word_at(click.line, click.column, &lines);
+                (word.extract_selected_text(&lines)?, click.line)
+            }
+        };
+        let query = strip_copy_mask(&text)
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())?
+            .to_string();
+        Some((query, line))
+    }
+
+    /// Column to draw the caret at on a line, when clicks place one and
+    /// nothing is selected
+    pub(super) fn caret_column(&self, line: usize) -> Option<usize> {
+        if self.click_action != ClickAction::Position || self.text_selection.has_selection() {
+            return None;
+        }
+        self.last_click
+            .as_ref()
+            .filter(|click| click.line == line)
+            .map(|click| click.column)
+    }
+
+    /// Top-level blocks to export: those the selection touches, or the
+    /// whole chapter without one
+    pub fn export_nodes(&self) -> Option<Vec<Node>> {
+        let doc = self.markdown_document.as_ref()?;
+        let last_block = doc.blocks.len().checked_sub(1)?;
+        let Some((start, end)) = self
+            .text_selection
+            .get_selection_range()
+            .filter(|_| self.text_selection.has_selection())
+        else {
+            return Some(doc.blocks.clone());
+        };
+        // Only the first line of a block records its index
+        let lines = &self.rendered_content.lines;
+        let block_at = |line: usize| {
+            lines[..(line + 1).min(lines.len())]
+                .iter()
+                .rev()
+                .find_map(|line| line.node_index)
+                .unwrap_or(0)
+                .min(last_block)
+        };
+        let first = block_at(start.line);
+        let last = block_at(end.line).max(first);
+        Some(doc.blocks[first..=last].to_vec())
+    }
+
+    /// The chapter's HTML when it is shown raw, to export as it is
+    pub fn raw_html_export(&self) -> Option<&str> {
+        self.raw_html_content
+            .as_deref()
+            .filter(|_| self.show_raw_html)
+    }
+
     pub fn has_text_selection(&self) -> bool {
         self.text_selection.has_selection()
     }
 
     pub fn copy_selection_to_clipboard(&self) -> Result<(), String> {
-        if let Some(selected_text) = self
-            .text_selection
-            .extract_selected_text(&self.raw_text_lines)
-        {
+        if let Some(selected_text) = self.selected_copy_text() {
             use arboard::Clipboard;
             let mut clipboard =
                 Clipboard::new().map_err(|e| format!("Failed to access clipboard: {e}"))?;
@@ -123,13 +209,128 @@ impl crate::markdown_text_reader::MarkdownTextReader {
                 .unwrap_or(&"<failed to get raw html>".to_string())
                 .to_string()
         } else {
-            self.raw_text_lines.join("\n")
+            let lines = self.copy_text_lines(0);
+            self.copied_text(strip_copy_mask(&lines.join("\n")))
         };
         clipboard
             .set_text(text)
             .map_err(|e| format!("Failed to copy to clipboard: {e}"))
     }
 
+    /// The selected text as it goes to the clipboard
+    pub(super) fn selected_copy_text(&self) -> Option<String> {
+        let (start, _) = self.text_selection.get_selection_range()?;
+        let lines = self.copy_text_lines(start.line);
+        let rendered = &self.rendered_content.lines;
+        // An image is copied whole or not at all, wherever the selection
+        // starts or ends on it
+        let is_image_row = |line: usize| {
+            rendered
+                .get(line)
+                .is_some_and(|line| matches!(line.line_type, LineType::ImagePlaceholder { .. }))
+        };
+        self.text_selection
+            .extract_selected_text_whole(&lines, is_image_row)
+            .map(|text| self.copied_text(strip_copy_mask(&text)))
+            .filter(|text| !text.is_empty())
+    }
+
+    /// Lines to copy from: the ruby-masked text lines with each image's
+    /// blank placeholder rows dropped, keeping one `[image: alt]` row, on
+    /// the image's first row or on `first_line` when the copy starts inside
+    /// it. The blank line the image adds above itself is dropped where the
+    /// text already ends in one; with `copy_image_alt_text` off the image
+    /// is dropped entirely, along with the blank line after it.
+    fn copy_text_lines(&self, first_line: usize) -> Vec<String> {
+        let mut lines = self.copy_masked_text_lines();
+        let rendered = &self.rendered_content.lines;
+        let image_src = |line: usize| match rendered.get(line).map(|line| &line.line_type) {
+            Some(LineType::ImagePlaceholder { src }) => Some(src),
+            _ => None,
+        };
+
+        let mut previous_src = None;
+        for idx in 0..lines.len() {
+            let src = image_src(idx);
+            let image_start = src.is_some() && previous_src != src;
+            if image_start && idx >= 2 && lines[idx - 1].is_empty() && lines[idx - 2].is_empty() {
+                lines[idx - 1] = COPY_MASK.to_string();
+            }
+            match src {
+                Some(src) if self.copy_image_alt_text && (image_start || idx == first_line) => {
+                    lines[idx] = match self.figure_alt_texts.get(src) {
+                        Some(alt) => format!("[image: {}]", alt.trim()),
+                        None => "[image]".to_string(),
+                    };
+                }
+                Some(_) => lines[idx] = COPY_MASK.to_string(),
+                None if previous_src.is_some()
+                    && !self.copy_image_alt_text
+                    && lines[idx].is_empty() =>
+                {
+                    lines[idx] = COPY_MASK.to_string();
+                }
+                None => {}
+            }
+            previous_src = src;
+        }
+        lines
+    }
+
+    /// Typeset text with the typewriter's punctuation back, unless the
+    /// settings ask for it to be copied as shown
+    fn copied_text(&self, text: String) -> String {
+        if self.typography && !self.copy_typography {
+            plain_punctuation(&text)
+        } else {
+            text
+        }
+    }
+
+    /// Raw text lines with ruby readings and quote bars masked out, unless
+    /// the settings ask for them to be copied along with the text
+    fn copy_masked_text_lines(&self) -> Vec<String> {
+        let mut lines = self.raw_text_lines.clone();
+        let masked = [
+            (&self.ruby_reading_ranges, self.copy_ruby_readings),
+            (&self.quote_bar_ranges, self.copy_quote_bars),
+        ];
+        let masked_ranges = masked
+            .into_iter()
+            .filter(|(_, copied)| !copied)
+            .flat_map(|(ranges, _)| ranges);
+
+        for (line_idx, ranges) in masked_ranges {
+            if let Some(line) = lines.get_mut(*line_idx) {
+                *line = line
+                    .chars()
+                    .enumerate()
+                    .map(|(col, c)| {
+                        if ranges.iter().any(|range| range.contains(&col)) {
+                            COPY_MASK
+                        } else {
+                            c
+                        }
+                    })
+                    .collect();
+            }
+        }
+        // A blank line of a quote stays in the copy, as an empty line
+        if !self.copy_quote_bars {
+            for line_idx in self.quote_bar_ranges.keys() {
+                if self.ruby_reading_ranges.contains_key(line_idx) {
+                    continue;
+                }
+                if let Some(line) = lines.get_mut(*line_idx) {
+                    if line.chars().all(|c| c == COPY_MASK || c == ' ') {
+                        line.clear();
+                    }
+                }
+            }
+        }
+        lines
+    }
+
     //for debuggin purposes
     pub fn copy_raw_text_lines_to_clipboard(&self) -> Result<(), String> {
         if self.raw_text_lines.is_empty() {
@@ -163,12 +364,136 @@ impl crate::markdown_text_reader::MarkdownTextReader {
         screen_y: u16,
         content_area: Rect,
     ) -> Option<(usize, usize)> {
-        self.text_selection.screen_to_text_coords(
+        let (line, column) = self.text_selection.screen_to_text_coords(
             screen_x,
             screen_y,
             self.scroll_offset,
             content_area.x,
             content_area.y,
-        )
+        )?;
+        // Right-to-left lines are drawn reordered; hit-test their logical text
+        let column = match self.rtl_layouts.get(&line) {
+            Some(layout) => layout.logical_column(column),
+            None => column,
+        };
+        Some((line, column))
+    }
+}
+
+/// Reverse the character at `column`, or a space past the end of the line
+pub(super) fn apply_caret(spans: Vec<Span<'_>>, column: usize) -> Vec<Span<'_>> {
+    let mut result = Vec::with_capacity(spans.len() + 2);
+    let mut start = 0;
+    let mut placed = false;
+    for span in spans {
+        let chars: Vec<char> = span.content.chars().collect();
+        let end = start + chars.len();
+        if placed || column < start || column >= end {
+            result.push(span);
+        } else {
+            let offset = column - start;
+            let before: String = chars[..offset].iter().collect();
+            let after: String = chars[offset + 1..].iter().collect();
+            if !before.is_empty() {
+                result.push(Span::styled(before, span.style));
+            }
+            result.push(Span::styled(
+                chars[offset].to_string(),
+                span.style.add_modifier(Modifier::REVERSED),
+            ));
+            if !after.is_empty() {
+                result.push(Span::styled(after, span.style));
+            }
+            placed = true;
+        }
+        start = end;
+    }
+    if !placed {
+        result.push(Span::raw(" ".repeat(column - start)));
+        result.push(Span::styled(" ", Modifier::REVERSED));
+    }
+    result
+}
+
+/// Marks text left out of copies: ruby readings and image rows
+const COPY_MASK: char = '\u{0}';
+
+/// Drop masked text, and lines that were masked entirely such as two-line
+/// mode reading lines
+fn strip_copy_mask(text: &str) -> String {
+    text.split('\n')
+        .filter(|line| line.is_empty() || line.chars().any(|c| c != COPY_MASK))
+        .map(|line| line.replace(COPY_MASK, ""))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::markdown_text_reader::MarkdownTextReader;
+    use crate::test_utils::test_helpers::create_test_terminal;
+    use crate::theme::current_theme;
+
+    /// A drawn reader whose first text row, "Line 1 of text", is on screen
+    /// row 2 starting at column 2
+    fn drawn_reader(click_action: ClickAction) -> MarkdownTextReader {
+        let html: String = (1..=6)
+            .map(|i| format!("<p>Line {i} of text</p>"))
+            .collect();
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(&html, None);
+        reader.set_click_action(click_action);
+        let mut terminal = create_test_terminal(40, 12);
+        terminal
+            .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+            .unwrap();
+        reader
+    }
+
+    #[test]
+    fn test_click_without_drag_selects_nothing_but_is_remembered() {
+        let mut reader = drawn_reader(ClickAction::Select);
+        reader.handle_mouse_down(3, 2);
+        reader.handle_mouse_up(3, 2);
+
+        // No selection and no anchor left for the help bar to report
+        assert!(!reader.has_text_selection());
+        assert!(!reader.text_selection.is_selecting);
+        assert_eq!(reader.text_selection.start, None);
+
+        // The click itself is kept for `*`
+        let (query, _) = reader.selection_search_query().unwrap();
+        assert_eq!(query, "Line");
+
+        // A later drag without a press does not pick up a stale anchor
+        reader.handle_mouse_drag(12, 4);
+        assert!(!reader.has_text_selection());
+    }
+
+    #[test]
+    fn test_position_clicks_place_a_caret_until_dragged() {
+        let mut reader = drawn_reader(ClickAction::Position);
+        reader.handle_mouse_down(3, 2);
+        assert!(!reader.text_selection.is_selecting);
+        let click = reader.last_click.clone().unwrap();
+        assert_eq!(reader.caret_column(click.line), Some(click.column));
+
+        // The drag selects from where the mouse went down
+        reader.handle_mouse_drag(12, 4);
+        reader.handle_mouse_up(12, 4);
+        assert!(reader.has_text_selection());
+        assert_eq!(reader.text_selection.start, Some(click));
+        assert_eq!(
+            reader.caret_column(reader.last_click.clone().unwrap().line),
+            None
+        );
+
+        // A plain click afterwards drops the selection and shows the caret
+        reader.handle_mouse_down(5, 6);
+        reader.handle_mouse_up(5, 6);
+        assert!(!reader.has_text_selection());
+        let click = reader.last_click.clone().unwrap();
+        assert_eq!(reader.caret_column(click.line), Some(click.column));
     }
 }
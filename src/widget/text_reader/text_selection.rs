@@ -1,3 +1,4 @@
+use super::types::RenderedLine;
 use ratatui::{
     style::Color,
     text::{Line, Span},
@@ -9,11 +10,109 @@ pub struct SelectionPoint {
     pub column: usize,
 }
 
+/// A position pinned to the document instead of the wrapped lines: the
+/// top-level node it falls in (None before the first one) and how many
+/// non-whitespace characters into that node it is. Re-wrapping only moves
+/// whitespace around, so the same point can be found again at any width.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogicalPoint {
+    pub node_index: Option<usize>,
+    pub offset: usize,
+}
+
+impl LogicalPoint {
+    pub fn from_visual(point: &SelectionPoint, lines: &[RenderedLine]) -> Self {
+        let Some(last) = lines.len().checked_sub(1) else {
+            return LogicalPoint {
+                node_index: None,
+                offset: 0,
+            };
+        };
+        let line = point.line.min(last);
+        let node_index = lines[..=line].iter().rev().find_map(|l| l.node_index);
+        let first = Self::first_line(node_index, lines).min(line);
+
+        let offset = lines[first..line]
+            .iter()
+            .map(|l| non_blank_count(&l.raw_text))
+            .sum::<usize>()
+            + lines[line]
+                .raw_text
+                .chars()
+                .take(point.column)
+                .filter(|c| !c.is_whitespace())
+                .count();
+        LogicalPoint { node_index, offset }
+    }
+
+    /// Visual position on (re-wrapped) lines. A point sitting between two
+    /// lines goes to the start of the next one, or with `is_end` to the end
+    /// of the previous one, so selections never grow an extra line.
+    pub fn to_visual(&self, lines: &[RenderedLine], is_end: bool) -> SelectionPoint {
+        let first = Self::first_line(self.node_index, lines);
+        let mut remaining = self.offset;
+
+        for (line_idx, line) in lines.iter().enumerate().skip(first) {
+            let count = non_blank_count(&line.raw_text);
+            let on_this_line = if is_end {
+                remaining <= count
+            } else {
+                remaining < count
+            };
+            if !on_this_line {
+                remaining -= count;
+                continue;
+            }
+
+            // Column of the `remaining`-th non-blank character, or just past
+            // the one before it for an end point
+            let mut seen = 0;
+            let mut column = 0;
+            for (col, c) in line.raw_text.chars().enumerate() {
+                if c.is_whitespace() {
+                    continue;
+                }
+                if is_end && seen + 1 == remaining {
+                    column = col + 1;
+                    break;
+                }
+                if !is_end && seen == remaining {
+                    column = col;
+                    break;
+                }
+                seen += 1;
+            }
+            return SelectionPoint {
+                line: line_idx,
+                column,
+            };
+        }
+
+        let line = lines.len().saturating_sub(1);
+        SelectionPoint {
+            line,
+            column: lines.get(line).map_or(0, |l| l.raw_text.chars().count()),
+        }
+    }
+
+    fn first_line(node_index: Option<usize>, lines: &[RenderedLine]) -> usize {
+        node_index
+            .and_then(|node| lines.iter().position(|l| l.node_index == Some(node)))
+            .unwrap_or(0)
+    }
+}
+
+fn non_blank_count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
 #[derive(Debug, Clone)]
 pub struct TextSelection {
     pub start: Option<SelectionPoint>,
     pub end: Option<SelectionPoint>,
     pub is_selecting: bool,
+    /// Start and end pinned to the document while the lines are re-rendered
+    anchors: Option<(LogicalPoint, LogicalPoint)>,
 }
 
 impl TextSelection {
@@ -22,9 +121,32 @@ impl TextSelection {
             start: None,
             end: None,
             is_selecting: false,
+            anchors: None,
         }
     }
 
+    /// Pin the selection to the document before `lines` are replaced by a re-render
+    pub fn anchor_to_document(&mut self, lines: &[RenderedLine]) {
+        self.anchors = match (&self.start, &self.end) {
+            (Some(start), Some(end)) => Some((
+                LogicalPoint::from_visual(start, lines),
+                LogicalPoint::from_visual(end, lines),
+            )),
+            _ => None,
+        };
+    }
+
+    /// Put a pinned selection back on the re-rendered lines
+    pub fn restore_from_document(&mut self, lines: &[RenderedLine]) {
+        let Some((start, end)) = self.anchors.take() else {
+            return;
+        };
+        // The earlier point starts the selection even when it was dragged backwards
+        let start_is_first = (start.node_index, start.offset) <= (end.node_index, end.offset);
+        self.start = Some(start.to_visual(lines, !start_is_first));
+        self.end = Some(end.to_visual(lines, start_is_first));
+    }
+
     pub fn start_selection(&mut self, line: usize, column: usize) {
         self.start = Some(SelectionPoint { line, column });
         self.end = Some(SelectionPoint { line, column });
@@ -37,14 +159,20 @@ impl TextSelection {
         }
     }
 
+    /// Finish a drag; a click that never moved leaves nothing selected
     pub fn end_selection(&mut self) {
         self.is_selecting = false;
+        if !self.has_selection() {
+            self.start = None;
+            self.end = None;
+        }
     }
 
     pub fn clear_selection(&mut self) {
         self.start = None;
         self.end = None;
         self.is_selecting = false;
+        self.anchors = None;
     }
 
     pub fn has_selection(&self) -> bool {
@@ -93,6 +221,16 @@ impl TextSelection {
     }
 
     pub fn extract_selected_text(&self, lines: &[String]) -> Option<String> {
+        self.extract_selected_text_whole(lines, |_| false)
+    }
+
+    /// Like `extract_selected_text`, but the lines `whole` picks out are
+    /// taken entirely as soon as the selection touches them
+    pub fn extract_selected_text_whole(
+        &self,
+        lines: &[String],
+        whole: impl Fn(usize) -> bool,
+    ) -> Option<String> {
         if let Some((start, end)) = self.get_selection_range() {
             let mut selected_text = String::new();
 
@@ -100,13 +238,13 @@ impl TextSelection {
                 if let Some(line) = lines.get(line_idx) {
                     let line_chars: Vec<char> = line.chars().collect();
 
-                    let start_col = if line_idx == start.line {
+                    let start_col = if line_idx == start.line && !whole(line_idx) {
                         start.column.min(line_chars.len())
                     } else {
                         0
                     };
 
-                    let end_col = if line_idx == end.line {
+                    let end_col = if line_idx == end.line && !whole(line_idx) {
                         end.column.min(line_chars.len())
                     } else {
                         line_chars.len()
@@ -139,6 +277,7 @@ impl TextSelection {
         line_idx: usize,
         spans: Vec<Span<'a>>,
         selection_bg_color: Color,
+        selection_fg_color: Option<Color>,
     ) -> Line<'a> {
         if !self.has_selection() {
             return Line::from(spans);
@@ -176,7 +315,10 @@ impl TextSelection {
 
                     let segment_text: String = span_chars[i..j].iter().collect();
                     let segment_style = if is_selected {
-                        span.style.bg(selection_bg_color)
+                        match selection_fg_color {
+                            Some(fg) => span.style.bg(selection_bg_color).fg(fg),
+                            None => span.style.bg(selection_bg_color),
+                        }
                     } else {
                         span.style
                     };
@@ -365,7 +507,14 @@ mod tests {
         assert!(!selection.is_selecting);
         assert!(selection.has_selection());
 
+        // A click without a drag leaves nothing behind
+        selection.start_selection(3, 4);
+        selection.end_selection();
+        assert!(selection.start.is_none() && selection.end.is_none());
+
         // Clear selection
+        selection.start_selection(1, 5);
+        selection.update_selection(2, 10);
         selection.clear_selection();
         assert!(!selection.has_selection());
     }
@@ -446,4 +595,187 @@ mod tests {
         let result = selection.screen_to_text_coords(0, 10, 5, 10, 5);
         assert_eq!(result, Some((10, 0))); // Still column 0
     }
+
+    fn words(text: &str) -> Vec<&str> {
+        text.split_whitespace().collect()
+    }
+
+    #[test]
+    fn test_selection_survives_terminal_resize() {
+        use crate::markdown_text_reader::MarkdownTextReader;
+        use crate::test_utils::test_helpers::create_test_terminal;
+        use crate::theme::current_theme;
+
+        let html = "<p>Intro paragraph that stays above the selection.</p>\
+                    <p>The quick brown fox jumps over the lazy dog while the \
+                    cat watches from a sunny windowsill nearby.</p>\
+                    <p>A second paragraph ends the selection somewhere here.</p>";
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(html, None);
+
+        let draw = |reader: &mut MarkdownTextReader, width: u16| {
+            let mut terminal = create_test_terminal(width, 30);
+            terminal
+                .draw(|f| reader.render(f, f.area(), 0, 1, current_theme(), true))
+                .unwrap();
+        };
+        let selected = |reader: &MarkdownTextReader| {
+            let lines: Vec<String> = reader
+                .rendered_content
+                .lines
+                .iter()
+                .map(|line| line.raw_text.clone())
+                .collect();
+            reader.text_selection.extract_selected_text(&lines).unwrap()
+        };
+
+        draw(&mut reader, 100);
+        let lines = &reader.rendered_content.lines;
+        let find = |word: &str| {
+            lines
+                .iter()
+                .enumerate()
+                .find_map(|(idx, line)| {
+                    line.raw_text
+                        .find(word)
+                        .map(|byte| (idx, line.raw_text[..byte].chars().count()))
+                })
+                .unwrap()
+        };
+        let (start_line, start_col) = find("brown");
+        let (end_line, end_col) = find("somewhere");
+        // Dragged backwards, from "some" to "brown"
+        reader.text_selection.start_selection(end_line, end_col + 4);
+        reader
+            .text_selection
+            .update_selection(start_line, start_col);
+        reader.text_selection.end_selection();
+        let before = selected(&reader);
+        assert!(before.starts_with("brown fox"), "{before}");
+        assert!(before.ends_with("selection some"), "{before}");
+
+        reader.handle_terminal_resize();
+        draw(&mut reader, 30);
+        let after = selected(&reader);
+        assert!(after.lines().count() > before.lines().count(), "{after}");
+        assert_eq!(words(&after), words(&before));
+
+        reader.handle_terminal_resize();
+        draw(&mut reader, 100);
+        assert_eq!(selected(&reader), before);
+    }
+
+    #[test]
+    fn test_selection_across_an_image() {
+        use super::super::types::LineType;
+        use crate::markdown_text_reader::MarkdownTextReader;
+        use crate::test_utils::test_helpers::create_test_terminal;
+        use crate::theme::current_theme;
+
+        let html = "<p>Before the figure.</p>\
+                    <p><img src=\"fig.png\" alt=\"A chart\"/></p>\
+                    <p>After the figure.</p>";
+        let mut reader = MarkdownTextReader::new();
+        reader.set_content_from_string(html, None);
+        let mut terminal = create_test_terminal(60, 40);
+        let mut draw = |reader: &mut MarkdownTextReader| {
+            terminal
+                .draw(|f| reader.render(f, f.area(), 0, 1, current_theme(), true))
+                .unwrap();
+            terminal.backend().buffer().clone()
+        };
+        draw(&mut reader);
+
+        let lines = reader.rendered_content.lines.clone();
+        let line_of = |text: &str| {
+            lines
+                .iter()
+                .position(|line| line.raw_text.starts_with(text))
+                .unwrap()
+        };
+        let (before, after) = (line_of("Before"), line_of("After"));
+        let image_rows: Vec<usize> = (0..lines.len())
+            .filter(|&idx| matches!(lines[idx].line_type, LineType::ImagePlaceholder { .. }))
+            .collect();
+        assert!(image_rows.len() > 1);
+
+        reader.text_selection.start_selection(before, 7);
+        reader.text_selection.update_selection(after, 5);
+        reader.text_selection.end_selection();
+        assert_eq!(
+            reader.selected_copy_text().unwrap(),
+            "the figure.\n\n[image: A chart]\n\nAfter"
+        );
+
+        // Only the text rows are painted, each on its own row
+        let buffer = draw(&mut reader);
+        let (selection_bg, _) = current_theme().get_text_selection_colors(true);
+        let text_area = reader.last_inner_text_area.unwrap();
+        let painted_rows: Vec<usize> = (0..lines.len())
+            .filter(|&line| {
+                let y = text_area.y + line as u16;
+                (text_area.x..text_area.right()).any(|x| buffer[(x, y)].bg == selection_bg)
+            })
+            .collect();
+        assert_eq!(painted_rows, vec![before, after]);
+
+        // Starting inside the image still copies it once
+        reader.text_selection.start_selection(image_rows[2], 3);
+        reader.text_selection.update_selection(after, 5);
+        assert_eq!(
+            reader.selected_copy_text().unwrap(),
+            "[image: A chart]\n\nAfter"
+        );
+
+        reader.set_copy_image_alt_text(false);
+        reader.text_selection.start_selection(before, 7);
+        reader.text_selection.update_selection(after, 5);
+        assert_eq!(reader.selected_copy_text().unwrap(), "the figure.\n\nAfter");
+    }
+
+    #[test]
+    fn test_logical_point_round_trip() {
+        let line = |text: &str, node_index: Option<usize>| RenderedLine {
+            raw_text: text.to_string(),
+            node_index,
+            ..RenderedLine::empty()
+        };
+        let lines = vec![
+            line("Heading", None),
+            line("alpha beta", Some(0)),
+            line("gamma", Some(0)),
+            line("", None),
+            line("delta", Some(1)),
+        ];
+
+        let point = LogicalPoint::from_visual(&SelectionPoint { line: 2, column: 2 }, &lines);
+        assert_eq!(
+            point,
+            LogicalPoint {
+                node_index: Some(0),
+                offset: 11
+            }
+        );
+        assert_eq!(
+            point.to_visual(&lines, false),
+            SelectionPoint { line: 2, column: 2 }
+        );
+
+        // The boundary between two lines
+        let boundary = LogicalPoint {
+            node_index: Some(0),
+            offset: 9,
+        };
+        assert_eq!(
+            boundary.to_visual(&lines, false),
+            SelectionPoint { line: 2, column: 0 }
+        );
+        assert_eq!(
+            boundary.to_visual(&lines, true),
+            SelectionPoint {
+                line: 1,
+                column: 10
+            }
+        );
+    }
 }
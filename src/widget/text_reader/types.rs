@@ -81,6 +81,11 @@ pub enum LineType {
         src: String,
     },
     HorizontalRule,
+    /// Summary of a top-level collapsible block, toggled by Enter or a click
+    Summary {
+        node_index: usize,
+        expanded: bool,
+    },
     Empty,
     Comment {
         chapter_href: String,
@@ -92,7 +97,15 @@ pub enum LineType {
 #[derive(Clone)]
 pub enum RichSpan {
     Text(Span<'static>),
-    Link { span: Span<'static>, info: LinkInfo },
+    Link {
+        span: Span<'static>,
+        info: LinkInfo,
+    },
+    /// Ruby base text; the reading is laid out according to the `RubyMode`
+    Ruby {
+        span: Span<'static>,
+        reading: String,
+    },
 }
 
 impl RichSpan {
@@ -101,13 +114,14 @@ impl RichSpan {
         match self {
             RichSpan::Text(span) => span,
             RichSpan::Link { span, .. } => span,
+            RichSpan::Ruby { span, .. } => span,
         }
     }
 
     /// Get link info if this is a link
     pub fn link_info(&self) -> Option<&LinkInfo> {
         match self {
-            RichSpan::Text(_) => None,
+            RichSpan::Text(_) | RichSpan::Ruby { .. } => None,
             RichSpan::Link { info, .. } => Some(info),
         }
     }
@@ -136,6 +150,13 @@ pub struct EmbeddedImage {
     pub state: ImageLoadState,
 }
 
+/// Result of jumping to an image with `]i`/`[i`
+pub struct ImageJump {
+    pub src: String,
+    pub index: usize,
+    pub total: usize,
+}
+
 impl EmbeddedImage {
     pub fn height_in_cells(width: u32, height: u32) -> u16 {
         let aspect_ratio = width as f32 / height as f32;
@@ -180,6 +201,18 @@ pub struct ActiveSection {
     pub chapter_href: String,
     pub chapter_base_href: String,
     pub anchor: Option<String>,
+    /// How far into the section of `anchor` the reader is
+    pub progress: Option<SectionProgress>,
+}
+
+/// Position within the TOC sections of a chapter
+#[derive(Clone, Debug, PartialEq)]
+pub struct SectionProgress {
+    /// 1-based number of the section among the chapter's sections
+    pub index: usize,
+    pub count: usize,
+    /// Percent of the section read up to the bottom of the viewport
+    pub percent: u32,
 }
 
 impl ActiveSection {
@@ -191,6 +224,7 @@ impl ActiveSection {
             chapter_href,
             chapter_base_href,
             anchor: normalized_anchor,
+            progress: None,
         }
     }
 
@@ -217,6 +251,10 @@ pub enum CommentEditMode {
     },
 }
 
+/// Text lines the comment textarea keeps room for even when shorter, so
+/// its borders never collapse
+const MIN_COMMENT_INPUT_LINES: usize = 3;
+
 #[derive(Default)]
 pub struct CommentInputState {
     pub textarea: Option<TextArea<'static>>,
@@ -224,6 +262,9 @@ pub struct CommentInputState {
     pub target_line: Option<usize>,
     pub edit_mode: Option<CommentEditMode>,
     pub target: Option<CommentTarget>,
+    /// Scroll offset from before the view moved to fit the textarea, gone
+    /// back to once the comment is saved
+    pub restore_scroll_offset: Option<usize>,
 }
 
 impl CommentInputState {
@@ -233,9 +274,17 @@ impl CommentInputState {
         self.target_line = None;
         self.edit_mode = None;
         self.target = None;
+        self.restore_scroll_offset = None;
     }
 
     pub fn is_active(&self) -> bool {
         self.textarea.is_some()
     }
+
+    /// Rows the textarea takes, borders included; 0 when inactive
+    pub fn height(&self) -> usize {
+        self.textarea.as_ref().map_or(0, |textarea| {
+            textarea.lines().len().max(MIN_COMMENT_INPUT_LINES) + 2
+        })
+    }
 }
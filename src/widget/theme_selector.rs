@@ -1,11 +1,13 @@
-use crate::inputs::KeySeq;
+use crate::i18n::tr;
 use crate::main_app::VimNavMotions;
+use crate::main_app::commands::PopupCommand;
 use crate::theme::{
     all_theme_names, current_theme, current_theme_index, set_theme_by_index_and_save,
 };
+use crate::widget::popup_geometry::{PopupKind, popup_rect};
 use ratatui::{
     Frame,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::Rect,
     style::{Modifier, Style},
     text::{Line, Span},
     widgets::{Block, Borders, Clear, List, ListItem, ListState},
@@ -44,7 +46,7 @@ impl ThemeSelector {
     }
 
     pub fn render(&mut self, f: &mut Frame, area: Rect) {
-        let popup_area = centered_rect(40, 50, area);
+        let popup_area = popup_rect(PopupKind::ThemeSelector, area);
         self.last_popup_area = Some(popup_area);
 
         f.render_widget(Clear, popup_area);
@@ -70,7 +72,7 @@ impl ThemeSelector {
         let list = List::new(items)
             .block(
                 Block::default()
-                    .title(" Select Theme ")
+                    .title(tr("title.theme_selector"))
                     .borders(Borders::ALL)
                     .border_style(Style::default().fg(palette.base_0c))
                     .style(Style::default().bg(palette.base_00)),
@@ -154,48 +156,25 @@ impl ThemeSelector {
         }
     }
 
-    pub fn handle_key(
-        &mut self,
-        key: crossterm::event::KeyEvent,
-        key_seq: &mut KeySeq,
-    ) -> Option<ThemeSelectorAction> {
-        use crossterm::event::{KeyCode, KeyModifiers};
-
-        match key.code {
-            KeyCode::Char('j') => {
-                self.handle_j();
-                None
-            }
-            KeyCode::Char('k') => {
-                self.handle_k();
-                None
-            }
-            KeyCode::Char('g') if key_seq.handle_key('g') == "gg" => {
-                self.handle_gg();
-                None
-            }
-            KeyCode::Char('G') => {
-                self.handle_upper_g();
-                None
-            }
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_d();
-                None
-            }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.handle_ctrl_u();
-                None
-            }
-            KeyCode::Esc => Some(ThemeSelectorAction::Close),
-            KeyCode::Enter => {
-                if self.apply_selected_theme() {
+    pub fn handle_command(&mut self, command: PopupCommand) -> Option<ThemeSelectorAction> {
+        match command {
+            PopupCommand::Down => self.handle_j(),
+            PopupCommand::Up => self.handle_k(),
+            PopupCommand::Top => self.handle_gg(),
+            PopupCommand::Bottom => self.handle_upper_g(),
+            PopupCommand::HalfPageDown => self.handle_ctrl_d(),
+            PopupCommand::HalfPageUp => self.handle_ctrl_u(),
+            PopupCommand::Close => return Some(ThemeSelectorAction::Close),
+            PopupCommand::Select => {
+                return if self.apply_selected_theme() {
                     Some(ThemeSelectorAction::ThemeChanged)
                 } else {
                     Some(ThemeSelectorAction::Close)
-                }
+                };
             }
-            _ => None,
+            _ => {}
         }
+        None
     }
 }
 
@@ -246,23 +225,3 @@ impl VimNavMotions for ThemeSelector {
         }
     }
 }
-
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage((100 - percent_y) / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage((100 - percent_y) / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage((100 - percent_x) / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage((100 - percent_x) / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
@@ -0,0 +1,93 @@
+// SVG snapshot of the content view on a 16-color terminal. The color mode
+// is global and the palettes are built on first use, so this runs in its
+// own test binary.
+use bookokrat::color_mode::{ColorMode, set_color_mode};
+use bookokrat::main_app::{App, FPSCounter};
+use bookokrat::test_utils::test_helpers::create_test_terminal;
+
+mod snapshot_assertions;
+mod svg_generation;
+mod test_report;
+mod visual_diff;
+use snapshot_assertions::assert_svg_snapshot;
+use std::sync::Once;
+use svg_generation::terminal_to_svg;
+
+static INIT: Once = Once::new();
+
+fn ensure_test_report_initialized() {
+    INIT.call_once(|| {
+        set_color_mode(ColorMode::Ansi16);
+        test_report::init_test_report();
+    });
+}
+
+/// Helper function to create standard test failure handler
+fn create_test_failure_handler(
+    test_name: &str,
+) -> impl FnOnce(String, String, String, usize, usize, usize, Option<usize>) + '_ {
+    move |expected,
+          actual,
+          snapshot_path,
+          expected_lines,
+          actual_lines,
+          diff_count,
+          first_diff_line| {
+        test_report::TestReport::add_failure(test_report::TestFailure {
+            test_name: test_name.to_string(),
+            expected,
+            actual,
+            line_stats: test_report::LineStats {
+                expected_lines,
+                actual_lines,
+                diff_count,
+                first_diff_line,
+            },
+            snapshot_path,
+        });
+    }
+}
+
+const CHAPTER: &str = r#"<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+    <title>Color Levels</title>
+</head>
+<body>
+    <h1>Color Levels</h1>
+    <p>Plain prose with <b>bold</b>, <i>italic</i>, a <a href="https://example.com">link</a> and <code>inline code</code>.</p>
+    <blockquote><p>A quoted remark that should stay readable.</p></blockquote>
+    <pre><code>fn main() {
+    println!("hello");
+}</code></pre>
+    <ul>
+        <li>first item</li>
+        <li>second item</li>
+    </ul>
+</body>
+</html>
+"#;
+
+#[test]
+fn test_content_view_in_16_colors_svg() {
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(80, 24);
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::fs::write(temp_dir.path().join("chapter.html"), CHAPTER).unwrap();
+    let mut app = App::new_with_config(Some(temp_dir.path().to_str().unwrap()), None, false);
+    let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+    app.open_book_for_reading_by_path(&path).unwrap();
+    terminal.draw(|f| app.draw(f, &FPSCounter::new())).unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_content_view_ansi16.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/content_view_ansi16.svg"),
+        "test_content_view_in_16_colors_svg",
+        create_test_failure_handler("test_content_view_in_16_colors_svg"),
+    );
+}
@@ -0,0 +1,125 @@
+// SVG snapshots of the interface in a language other than English. The
+// language is global, so these run in their own test binary.
+use bookokrat::bookmarks::{Bookmark, Bookmarks};
+use bookokrat::color_mode::{ColorMode, set_color_mode};
+use bookokrat::i18n::{Language, set_language};
+use bookokrat::main_app::FPSCounter;
+use bookokrat::reading_history::ReadingHistory;
+use bookokrat::test_utils::test_helpers::{create_test_app_with_fake_books, create_test_terminal};
+use chrono::{Duration, NaiveDate, TimeZone, Utc};
+use std::collections::HashMap;
+
+mod snapshot_assertions;
+mod svg_generation;
+mod test_report;
+mod visual_diff;
+use snapshot_assertions::assert_svg_snapshot;
+use std::sync::Once;
+use svg_generation::terminal_to_svg;
+
+static INIT: Once = Once::new();
+
+fn ensure_test_report_initialized() {
+    INIT.call_once(|| {
+        // Snapshots are recorded in full color whatever terminal runs them
+        set_color_mode(ColorMode::Truecolor);
+        set_language(Language::Ru);
+        test_report::init_test_report();
+    });
+}
+
+/// Helper function to create standard test failure handler
+fn create_test_failure_handler(
+    test_name: &str,
+) -> impl FnOnce(String, String, String, usize, usize, usize, Option<usize>) + '_ {
+    move |expected,
+          actual,
+          snapshot_path,
+          expected_lines,
+          actual_lines,
+          diff_count,
+          first_diff_line| {
+        test_report::TestReport::add_failure(test_report::TestFailure {
+            test_name: test_name.to_string(),
+            expected,
+            actual,
+            line_stats: test_report::LineStats {
+                expected_lines,
+                actual_lines,
+                diff_count,
+                first_diff_line,
+            },
+            snapshot_path,
+        });
+    }
+}
+
+#[test]
+fn test_help_bar_in_russian_svg() {
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(120, 30);
+
+    let (mut app, _temp_manager) = create_test_app_with_fake_books();
+    let path = app.book_manager.get_book_info(0).unwrap().path.clone();
+    app.open_book_for_reading_by_path(&path).unwrap();
+    terminal.draw(|f| app.draw(f, &FPSCounter::new())).unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_help_bar_ru.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/help_bar_ru.svg"),
+        "test_help_bar_in_russian_svg",
+        create_test_failure_handler("test_help_bar_in_russian_svg"),
+    );
+}
+
+#[test]
+fn test_reading_history_headers_in_russian_svg() {
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(100, 24);
+
+    // Fixed dates at midday so the day groups hold in any time zone
+    let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let noon = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+    let mut books = HashMap::new();
+    for (i, days) in [0, 0, 1, 3, 20].iter().enumerate() {
+        books.insert(
+            format!("/library/Книга {}.epub", i + 1),
+            Bookmark {
+                chapter_href: "chapter_1.html".to_string(),
+                node_index: None,
+                last_read: noon - Duration::days(*days) - Duration::minutes(i as i64),
+                chapter_index: Some(i),
+                total_chapters: Some(10),
+                furthest_chapter: None,
+                furthest_node: None,
+            },
+        );
+    }
+
+    let temp_dir = tempfile::tempdir().unwrap();
+    let bookmark_path = temp_dir.path().join("bookmarks.json");
+    std::fs::write(
+        &bookmark_path,
+        serde_json::to_string(&serde_json::json!({ "books": books })).unwrap(),
+    )
+    .unwrap();
+    let bookmarks = Bookmarks::load_from_file(&bookmark_path.to_string_lossy()).unwrap();
+
+    let mut history = ReadingHistory::new(&bookmarks).with_today(today);
+    terminal.draw(|f| history.render(f, f.area())).unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_reading_history_ru.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/reading_history_ru.svg"),
+        "test_reading_history_headers_in_russian_svg",
+        create_test_failure_handler("test_reading_history_headers_in_russian_svg"),
+    );
+}
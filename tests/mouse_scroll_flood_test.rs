@@ -58,10 +58,23 @@ impl bookokrat::event_source::EventSource for FloodEventSource {
     }
 }
 
+/// Bookmarks file in `dir`, so the files kept next to it stay out of the repo
+fn bookmarks_file(dir: &tempfile::TempDir) -> String {
+    dir.path()
+        .join("test_bookmarks.json")
+        .to_string_lossy()
+        .into_owned()
+}
+
 #[test]
 fn test_horizontal_scroll_flood_performance() {
     // Create test app
-    let mut app = App::new_with_config(Some("tests/fixtures"), Some("test_bookmarks.json"), false);
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut app = App::new_with_config(
+        Some("tests/fixtures"),
+        Some(&bookmarks_file(&data_dir)),
+        false,
+    );
 
     // Create terminal with test backend
     let backend = TestBackend::new(80, 24);
@@ -97,7 +110,12 @@ fn test_horizontal_scroll_flood_performance() {
 #[test]
 fn test_mixed_scroll_events_performance() {
     // Create test app
-    let mut app = App::new_with_config(Some("tests/fixtures"), Some("test_bookmarks.json"), false);
+    let data_dir = tempfile::tempdir().unwrap();
+    let mut app = App::new_with_config(
+        Some("tests/fixtures"),
+        Some(&bookmarks_file(&data_dir)),
+        false,
+    );
 
     // Create terminal with test backend
     let backend = TestBackend::new(80, 24);
@@ -0,0 +1,24 @@
+// SIGTERM sets a process-wide flag, so this runs in its own test binary
+#![cfg(unix)]
+
+use bookokrat::main_app::{App, run_app_with_event_source};
+use bookokrat::terminal_state;
+use bookokrat::test_utils::test_helpers::TestScenarioBuilder;
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+
+#[test]
+fn test_sigterm_ends_the_main_loop_instead_of_the_process() {
+    terminal_state::install_quit_signal_handlers();
+    assert!(!terminal_state::quit_signalled());
+
+    unsafe { libc::raise(libc::SIGTERM) };
+    assert!(terminal_state::quit_signalled());
+
+    let mut app = App::new_with_config(Some("tests/testdata"), Some("/dev/null"), false);
+    // No quit key: without the signal the loop would wait for input forever
+    let mut event_source = TestScenarioBuilder::new().navigate_down(3).build();
+    let mut terminal = Terminal::new(TestBackend::new(80, 24)).unwrap();
+
+    assert!(run_app_with_event_source(&mut terminal, &mut app, &mut event_source).is_ok());
+}
@@ -1,3 +1,4 @@
+use bookokrat::color_mode::{ColorMode, set_color_mode};
 use bookokrat::comments::{Comment, CommentTarget};
 use bookokrat::main_app::{ChapterDirection, FPSCounter};
 use bookokrat::simple_fake_books::FakeBookConfig;
@@ -24,6 +25,8 @@ static BASE_COMMENTS_DIR: OnceLock<TempDir> = OnceLock::new();
 
 fn ensure_test_report_initialized() {
     INIT.call_once(|| {
+        // Snapshots are recorded in full color whatever terminal runs them
+        set_color_mode(ColorMode::Truecolor);
         test_report::init_test_report();
         init_base_comments_dir();
     });
@@ -136,43 +139,53 @@ fn open_first_test_book(app: &mut App) {
 fn seed_sample_comments(app: &mut App) {
     let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap();
     let chapter_a = app
-        .testing_current_chapter_file()
+        .test_api()
+        .current_chapter_file()
         .unwrap_or_else(|| "chapter1.xhtml".to_string());
 
-    app.testing_add_comment(Comment {
-        chapter_href: chapter_a.clone(),
-        target: CommentTarget::Paragraph {
-            paragraph_index: 0,
-            word_range: None,
-        },
-        content: "Launch plan looks solid.".to_string(),
-        updated_at: base_time,
-    });
+    app.test_api()
+        .add_comment(Comment {
+            chapter_href: chapter_a.clone(),
+            target: CommentTarget::Paragraph {
+                paragraph_index: 0,
+                word_range: None,
+            },
+            content: "Launch plan looks solid.".to_string(),
+            updated_at: base_time,
+            fingerprint: None,
+        })
+        .unwrap();
 
-    app.testing_add_comment(Comment {
-        chapter_href: chapter_a.clone(),
-        target: CommentTarget::Paragraph {
-            paragraph_index: 3,
-            word_range: None,
-        },
-        content: "Need to revisit risk section.".to_string(),
-        updated_at: base_time + chrono::Duration::minutes(5),
-    });
+    app.test_api()
+        .add_comment(Comment {
+            chapter_href: chapter_a.clone(),
+            target: CommentTarget::Paragraph {
+                paragraph_index: 3,
+                word_range: None,
+            },
+            content: "Need to revisit risk section.".to_string(),
+            updated_at: base_time + chrono::Duration::minutes(5),
+            fingerprint: None,
+        })
+        .unwrap();
 
     if app
         .navigate_chapter_relative(ChapterDirection::Next)
         .is_ok()
     {
-        if let Some(chapter_b) = app.testing_current_chapter_file() {
-            app.testing_add_comment(Comment {
-                chapter_href: chapter_b.clone(),
-                target: CommentTarget::Paragraph {
-                    paragraph_index: 2,
-                    word_range: None,
-                },
-                content: "Great anecdote here.".to_string(),
-                updated_at: base_time + chrono::Duration::minutes(10),
-            });
+        if let Some(chapter_b) = app.test_api().current_chapter_file() {
+            app.test_api()
+                .add_comment(Comment {
+                    chapter_href: chapter_b.clone(),
+                    target: CommentTarget::Paragraph {
+                        paragraph_index: 2,
+                        word_range: None,
+                    },
+                    content: "Great anecdote here.".to_string(),
+                    updated_at: base_time + chrono::Duration::minutes(10),
+                    fingerprint: None,
+                })
+                .unwrap();
         }
         let _ = app.navigate_chapter_relative(ChapterDirection::Previous);
     }
@@ -183,6 +196,27 @@ fn open_comments_viewer(app: &mut App) {
     app.press_key(crossterm::event::KeyCode::Char('a'));
 }
 
+/// Open `html` as the only book in a temp library and draw the first chapter
+fn render_html_chapter_svg(html: &str, width: u16, height: u16) -> String {
+    let mut terminal = create_test_terminal(width, height);
+    let temp_dir = tempfile::tempdir().unwrap();
+    std::fs::write(temp_dir.path().join("chapter.html"), html).unwrap();
+
+    let mut app = App::new_with_config(Some(temp_dir.path().to_str().unwrap()), None, false);
+    if let Some(book_info) = app.book_manager.get_book_info(0) {
+        let path = book_info.path.clone();
+        let _ = app.open_book_for_reading_by_path(&path);
+    }
+
+    terminal
+        .draw(|f| {
+            let fps = create_test_fps_counter();
+            app.draw(f, &fps)
+        })
+        .unwrap();
+    terminal_to_svg(&terminal)
+}
+
 #[test]
 fn test_fake_books_file_list_svg() {
     ensure_test_report_initialized();
@@ -1869,9 +1903,8 @@ fn test_text_selection_click_on_book_text_bug_svg() {
         })
         .unwrap();
 
-    // Now simulate clicking on book text in the content area
-    // According to the bug report: "when i click on a book text: nothing got selected,
-    // but the status bar shows as if we are in text selection mode"
+    // Click on book text in the content area without dragging. This used to
+    // leave nothing selected while the status bar showed text selection mode
     let mouse_click_on_text = MouseEvent {
         kind: MouseEventKind::Down(MouseButton::Left),
         column: 50, // Click on book text in content area
@@ -1905,8 +1938,7 @@ fn test_text_selection_click_on_book_text_bug_svg() {
     )
     .unwrap();
 
-    // This test should capture the bug: if the status bar shows text selection mode
-    // but no actual text is selected, we'll see it in the snapshot
+    // Nothing is selected, so the help bar shows the regular content hints
     assert_svg_snapshot(
         svg_output.clone(),
         std::path::Path::new("tests/snapshots/text_selection_click_on_book_text_bug.svg"),
@@ -2199,9 +2231,9 @@ fn test_book_reading_history_with_many_entries_svg() {
     ensure_test_report_initialized();
     let mut terminal = create_test_terminal(120, 40); // Larger terminal for better visibility
 
-    // Create app with custom fake books - 120 books for reading history
+    // Fake books for the reading history to point at
     let mut book_configs = Vec::new();
-    for i in 0..120 {
+    for i in 0..18 {
         book_configs.push(FakeBookConfig {
             title: format!(
                 "Book {} - {}",
@@ -2224,89 +2256,60 @@ fn test_book_reading_history_with_many_entries_svg() {
         });
     }
 
-    // Create a temporary bookmark file for this test
-    let temp_dir = tempfile::tempdir().unwrap();
-    let bookmark_path = temp_dir.path().join("test_bookmarks.json");
-
-    // Create app with real bookmark file
     let temp_manager =
         bookokrat::test_utils::test_helpers::TempBookManager::new_with_configs(&book_configs)
             .expect("Failed to create temp books");
 
-    // Create bookmarks using the production format by manually crafting valid JSON
-    // This is the only way to create deterministic test data with specific timestamps
-    use chrono::{Duration, TimeZone, Utc};
+    // Bookmarks are written as JSON so they keep their timestamps, which
+    // update_bookmark would reset to now
+    use chrono::{Duration, NaiveDate, TimeZone, Utc};
     use std::collections::HashMap;
 
-    // Use a fixed date for deterministic test output
-    let now = Utc.with_ymd_and_hms(2024, 3, 15, 10, 0, 0).unwrap();
+    // Fixed dates at midday so the day groups hold in any time zone
+    let today = NaiveDate::from_ymd_opt(2024, 3, 15).unwrap();
+    let noon = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+    let bookmark = |last_read, chapter: usize, total: usize| bookokrat::bookmarks::Bookmark {
+        chapter_href: format!("chapter_{chapter}.html"),
+        node_index: None,
+        last_read,
+        chapter_index: Some(chapter),
+        total_chapters: Some(total),
+        furthest_chapter: None,
+        furthest_node: None,
+    };
 
+    let paths = temp_manager.get_book_paths();
     let mut books_map = HashMap::new();
-
-    // Add books read today (most recent - should appear at top)
-    for i in 0..10 {
-        let book_path = format!("{}/Test Book {}.epub", temp_manager.get_directory(), i);
-        let bookmark = bookokrat::bookmarks::Bookmark {
-            chapter_href: format!("chapter_{}.html", i * 2),
-            node_index: None,
-            last_read: now - Duration::hours(i as i64),
-            chapter_index: Some(i * 2),
-            total_chapters: Some(10 + (i % 20)),
-        };
-        books_map.insert(book_path, bookmark);
+    // Read today, yesterday, earlier this week and before that
+    let days_ago = [0, 0, 0, 0, 1, 1, 1, 2, 3, 5, 6, 8, 15, 30, 45, 60, 90, 120];
+    for (i, days) in days_ago.iter().enumerate() {
+        let total = 10 + (i % 20);
+        let last_read = noon - Duration::days(*days) - Duration::minutes(i as i64);
+        let path = format!("{}/{}", temp_manager.get_directory(), paths[i]);
+        books_map.insert(path, bookmark(last_read, (i * 3) % total, total));
     }
-
-    // Add books read yesterday
-    for i in 10..20 {
-        let book_path = format!("{}/Test Book {}.epub", temp_manager.get_directory(), i);
-        let bookmark = bookokrat::bookmarks::Bookmark {
-            chapter_href: format!("chapter_{}.html", (i - 10) * 3),
-            node_index: None,
-            last_read: now - Duration::days(1) - Duration::hours((i - 10) as i64),
-            chapter_index: Some((i - 10) * 3),
-            total_chapters: Some(10 + (i % 20)),
-        };
-        books_map.insert(book_path, bookmark);
-    }
-
-    // Save using the production Bookmarks struct
-    let mut prod_bookmarks =
-        bookokrat::bookmarks::Bookmarks::with_file(&bookmark_path.to_string_lossy());
-
-    // Add all the bookmarks using the production method
-    for (path, bookmark) in books_map {
-        prod_bookmarks.update_bookmark(
-            &path,
-            bookmark.chapter_href,
-            bookmark.node_index,
-            bookmark.chapter_index,
-            bookmark.total_chapters,
+    // Books whose files are gone are tagged as missing
+    for (i, days) in [4, 40].iter().enumerate() {
+        let path = format!(
+            "{}/Deleted Book {}.epub",
+            temp_manager.get_directory(),
+            i + 1
         );
+        books_map.insert(path, bookmark(noon - Duration::days(*days), 5, 10));
     }
 
-    // Save using production code
-    prod_bookmarks.save().unwrap();
-
-    // Debug: print number of bookmarks created
-    println!("Created {} bookmarks using production code", 100);
-
-    // Now reload the app to pick up the bookmarks
-    let mut app = bookokrat::App::new_with_config(
-        Some(&temp_manager.get_directory()),
-        Some(&bookmark_path.to_string_lossy()),
-        false,
-    );
-
-    // Now show the reading history popup with capital H
-    app.press_key(crossterm::event::KeyCode::Char('H'));
+    let temp_dir = tempfile::tempdir().unwrap();
+    let bookmark_path = temp_dir.path().join("test_bookmarks.json");
+    std::fs::write(
+        &bookmark_path,
+        serde_json::to_string(&serde_json::json!({ "books": books_map })).unwrap(),
+    )
+    .unwrap();
+    let bookmarks =
+        bookokrat::bookmarks::Bookmarks::load_from_file(&bookmark_path.to_string_lossy()).unwrap();
 
-    // Draw the state with the reading history popup visible
-    terminal
-        .draw(|f| {
-            let fps = create_test_fps_counter();
-            app.draw(f, &fps)
-        })
-        .unwrap();
+    let mut history = bookokrat::reading_history::ReadingHistory::new(&bookmarks).with_today(today);
+    terminal.draw(|f| history.render(f, f.area())).unwrap();
     let svg_output = terminal_to_svg(&terminal);
 
     std::fs::create_dir_all("tests/snapshots").unwrap();
@@ -3414,3 +3417,613 @@ fn test_toc_search_svg() {
         create_test_failure_handler("test_toc_search_svg"),
     );
 }
+
+#[test]
+fn test_ruby_annotations_rendering_svg() {
+    ensure_test_report_initialized();
+
+    let ruby_content = r#"<!DOCTYPE html>
+<html xml:lang="ja" lang="ja" xmlns="http://www.w3.org/1999/xhtml">
+<head>
+    <title>Ruby Rendering Test</title>
+</head>
+<body>
+    <h1><ruby>吾輩<rt>わがはい</rt></ruby>は猫である</h1>
+    <p><ruby>吾輩<rt>わがはい</rt></ruby>は猫である。名前はまだ無い。どこで<ruby>生<rt>うま</rt></ruby>れたかとんと<ruby>見当<rt>けんとう</rt></ruby>がつかぬ。</p>
+    <p>Mixed text: the word <ruby>東京<rt>とうきょう</rt></ruby> means Tokyo, and <ruby>漢<rp>(</rp><rt>かん</rt><rp>)</rp>字<rp>(</rp><rt>じ</rt><rp>)</rp></ruby> carries one reading per character.</p>
+    <p>A paragraph with no annotations at all stays exactly as written.</p>
+</body>
+</html>
+"#;
+
+    let svg_output = render_html_chapter_svg(ruby_content, 80, 24);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_ruby_annotations.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/ruby_annotations.svg"),
+        "test_ruby_annotations_rendering_svg",
+        create_test_failure_handler("test_ruby_annotations_rendering_svg"),
+    );
+}
+
+#[test]
+fn test_blank_lines_and_scene_breaks_svg() {
+    ensure_test_report_initialized();
+
+    let blank_run_content = format!(
+        r#"<!DOCTYPE html>
+<html xml:lang="en" lang="en" xmlns="http://www.w3.org/1999/xhtml">
+<head>
+    <title>Scene Break Test</title>
+</head>
+<body>
+    <p>The last paragraph of the first scene, followed by a run of empty paragraphs from a poor conversion.</p>
+    {}
+    <p>After the blank run only two blank lines remain.</p>
+    <hr/>
+    <p>A thematic break separates this scene from the one before.</p>
+    <hr class="scene-break"/>
+    <p>The final scene.</p>
+</body>
+</html>
+"#,
+        "<p><br/></p>".repeat(10)
+    );
+
+    let svg_output = render_html_chapter_svg(&blank_run_content, 80, 24);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write(
+        "tests/snapshots/debug_blank_lines_and_scene_breaks.svg",
+        &svg_output,
+    )
+    .unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/blank_lines_and_scene_breaks.svg"),
+        "test_blank_lines_and_scene_breaks_svg",
+        create_test_failure_handler("test_blank_lines_and_scene_breaks_svg"),
+    );
+}
+
+#[test]
+fn test_nested_quotes_rendering_svg() {
+    ensure_test_report_initialized();
+
+    let quote_content = r#"<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+    <title>Quote Rendering Test</title>
+</head>
+<body>
+    <h1>Quotes</h1>
+    <p>The reply quoted the whole thread:</p>
+    <blockquote>
+        <p>I think we should ship on Friday, provided the last review comes back clean and nobody finds anything new in the meantime.</p>
+        <blockquote>
+            <p>Friday is fine by me, but only if the migration has been rehearsed at least once against a copy of production.</p>
+        </blockquote>
+        <p>— Alice</p>
+    </blockquote>
+    <p>Her checklist followed:</p>
+    <blockquote>
+        <p>Before the release:</p>
+        <ul>
+            <li>rehearse the migration</li>
+            <li>freeze the dependencies</li>
+            <li>write the announcement</li>
+        </ul>
+    </blockquote>
+</body>
+</html>
+"#;
+
+    let svg_output = render_html_chapter_svg(quote_content, 80, 30);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_nested_quotes.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/nested_quotes.svg"),
+        "test_nested_quotes_rendering_svg",
+        create_test_failure_handler("test_nested_quotes_rendering_svg"),
+    );
+}
+
+#[test]
+fn test_inline_code_rendering_svg() {
+    ensure_test_report_initialized();
+
+    let code_content = r#"<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml">
+<head>
+    <title>Inline Code Test</title>
+</head>
+<body>
+    <h1>Inline Code</h1>
+    <p>Call <code>Vec::with_capacity</code> when the size is known, then <code>push</code> items one by one.</p>
+    <p>A long span such as <code>std::collections::hash_map::Entry::or_insert_with</code> wraps across the line without its shading leaking into the surrounding words.</p>
+    <p>Code can sit in <b>bold <code>text</code></b> or at the very end: <code>Ok(())</code></p>
+</body>
+</html>
+"#;
+
+    let svg_output = render_html_chapter_svg(code_content, 80, 20);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_inline_code.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/inline_code.svg"),
+        "test_inline_code_rendering_svg",
+        create_test_failure_handler("test_inline_code_rendering_svg"),
+    );
+}
+
+#[test]
+fn test_toc_selected_active_and_current_chapter_svg() {
+    use bookokrat::theme::current_theme;
+    use bookokrat::widget::navigation_panel::{CurrentBookInfo, TableOfContents, TocItem};
+    use bookokrat::widget::text_reader::ActiveSection;
+
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(40, 12);
+
+    let chapter = |title: &str, href: &str| TocItem::Chapter {
+        title: title.to_string(),
+        href: href.to_string(),
+        anchor: href.split_once('#').map(|(_, anchor)| anchor.to_string()),
+    };
+
+    // Reading "Arrival"; "Departure" shares its chapter; "Epilogue" is selected
+    let mut toc = TableOfContents::new();
+    toc.set_current_book_info(CurrentBookInfo {
+        path: "book.epub".to_string(),
+        toc_items: vec![
+            chapter("Prologue", "prologue.xhtml"),
+            TocItem::Section {
+                title: "Part One".to_string(),
+                href: None,
+                anchor: None,
+                children: vec![
+                    chapter("Arrival", "part1.xhtml#arrival"),
+                    chapter("Departure", "part1.xhtml#departure"),
+                ],
+                is_expanded: true,
+            },
+            chapter("Epilogue", "epilogue.xhtml"),
+        ],
+        current_chapter: 1,
+        current_chapter_href: Some("part1.xhtml".to_string()),
+        active_section: ActiveSection::new(1, "part1.xhtml".to_string(), Some("arrival".into())),
+    });
+    toc.selected_index = 5;
+    toc.list_state.select(Some(5));
+
+    terminal
+        .draw(|f| toc.render(f, f.area(), true, current_theme(), "Book"))
+        .unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_toc_item_states.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/toc_item_states.svg"),
+        "test_toc_selected_active_and_current_chapter_svg",
+        create_test_failure_handler("test_toc_selected_active_and_current_chapter_svg"),
+    );
+}
+
+#[test]
+fn test_reading_stats_month_chart_svg() {
+    use bookokrat::reading_goal::{GoalUnit, ReadingGoals};
+    use bookokrat::widget::reading_stats::ReadingStats;
+    use chrono::{Local, NaiveDate};
+    use std::time::Duration;
+
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(80, 30);
+
+    // A month of synthetic reading: most days in one book, a few in another
+    let mut goals = ReadingGoals::ephemeral();
+    for day in 1..=31u32 {
+        let at = Local.with_ymd_and_hms(2024, 5, day, 21, 0, 0).unwrap();
+        if day % 7 != 0 {
+            let minutes = u64::from(10 + (day * 13) % 40);
+            goals.record_time("/books/Dune.epub", at, Duration::from_secs(minutes * 60));
+            goals.record_lines("/books/Dune.epub", at, minutes * 12);
+        }
+        if day % 5 == 0 {
+            goals.record_time("/books/Emma.epub", at, Duration::from_secs(25 * 60));
+            goals.record_lines("/books/Emma.epub", at, 300);
+        }
+    }
+
+    let today = NaiveDate::from_ymd_opt(2024, 5, 31).unwrap();
+    let mut stats = ReadingStats::new(&goals, today, GoalUnit::Minutes, |path| {
+        std::path::Path::new(path)
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    });
+
+    terminal.draw(|f| stats.render(f, f.area())).unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_reading_stats_month.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/reading_stats_month.svg"),
+        "test_reading_stats_month_chart_svg",
+        create_test_failure_handler("test_reading_stats_month_chart_svg"),
+    );
+}
+
+#[test]
+fn test_reading_ruler_at_center_row_svg() {
+    use bookokrat::markdown_text_reader::MarkdownTextReader;
+    use bookokrat::search::SearchablePanel;
+    use bookokrat::theme::current_theme;
+
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(60, 16);
+
+    let html: String = (1..=12)
+        .map(|i| {
+            format!(
+                "<p>Line <b>{i}</b> mixes <em>italic</em>, <a href=\"#n{i}\">a link</a> and <code>code</code></p>"
+            )
+        })
+        .collect();
+    let mut reader = MarkdownTextReader::new();
+    reader.set_content_from_string(&html, None);
+    reader.set_reading_ruler(true);
+    terminal
+        .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+        .unwrap();
+
+    // A search match on the ruler row keeps its own highlight
+    reader.start_search();
+    reader.update_search_query("4 mixes");
+    terminal
+        .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+        .unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_reading_ruler.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/reading_ruler.svg"),
+        "test_reading_ruler_at_center_row_svg",
+        create_test_failure_handler("test_reading_ruler_at_center_row_svg"),
+    );
+}
+
+/// Draw a long chapter with the progress bar on, scrolled until at least
+/// `fraction` of it has been shown
+fn render_progress_bar_svg(fraction: f32) -> String {
+    use bookokrat::markdown_text_reader::MarkdownTextReader;
+    use bookokrat::theme::current_theme;
+
+    let mut terminal = create_test_terminal(60, 10);
+    let html: String = (1..=800)
+        .map(|i| format!("<p>Paragraph {i} of a chapter long enough to start at 0%.</p>"))
+        .collect();
+    let mut reader = MarkdownTextReader::new();
+    reader.set_content_from_string(&html, None);
+    reader.set_progress_bar(true);
+    terminal
+        .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+        .unwrap();
+
+    while reader.chapter_progress_fraction() < fraction
+        && reader.get_scroll_offset() < reader.get_max_scroll_offset()
+    {
+        reader.scroll_down();
+    }
+    terminal
+        .draw(|f| reader.render(f, f.area(), 1, 1, current_theme(), true))
+        .unwrap();
+    terminal_to_svg(&terminal)
+}
+
+#[test]
+fn test_progress_bar_at_start_svg() {
+    ensure_test_report_initialized();
+    let svg_output = render_progress_bar_svg(0.0);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_progress_bar_start.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/progress_bar_start.svg"),
+        "test_progress_bar_at_start_svg",
+        create_test_failure_handler("test_progress_bar_at_start_svg"),
+    );
+}
+
+#[test]
+fn test_progress_bar_at_half_svg() {
+    ensure_test_report_initialized();
+    let svg_output = render_progress_bar_svg(0.5);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_progress_bar_half.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/progress_bar_half.svg"),
+        "test_progress_bar_at_half_svg",
+        create_test_failure_handler("test_progress_bar_at_half_svg"),
+    );
+}
+
+#[test]
+fn test_progress_bar_at_end_svg() {
+    ensure_test_report_initialized();
+    let svg_output = render_progress_bar_svg(1.0);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_progress_bar_end.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/progress_bar_end.svg"),
+        "test_progress_bar_at_end_svg",
+        create_test_failure_handler("test_progress_bar_at_end_svg"),
+    );
+}
+
+#[test]
+fn test_comment_input_on_second_to_last_line_svg() {
+    use bookokrat::markdown_text_reader::MarkdownTextReader;
+    use bookokrat::test_utils::test_helpers::capture_terminal_state;
+    use bookokrat::theme::current_theme;
+
+    ensure_test_report_initialized();
+    let mut terminal = create_test_terminal(40, 14);
+    let html: String = (1..=20).map(|i| format!("<p>Paragraph {i}</p>")).collect();
+    let mut reader = MarkdownTextReader::new();
+    reader.set_content_from_string(&html, None);
+
+    // Scroll until "Paragraph 9" sits on the second-to-last line of the text
+    let target_row = 11;
+    loop {
+        terminal
+            .draw(|f| reader.render(f, f.area(), 0, 1, current_theme(), true))
+            .unwrap();
+        let screen = capture_terminal_state(&terminal);
+        if screen
+            .lines()
+            .nth(target_row as usize)
+            .unwrap()
+            .contains("Paragraph 9 ")
+        {
+            break;
+        }
+        reader.scroll_down();
+    }
+
+    reader.handle_mouse_down(2, target_row);
+    reader.handle_mouse_drag(12, target_row);
+    reader.handle_mouse_up(12, target_row);
+    assert!(reader.start_comment_input());
+    terminal
+        .draw(|f| reader.render(f, f.area(), 0, 1, current_theme(), true))
+        .unwrap();
+    let svg_output = terminal_to_svg(&terminal);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write(
+        "tests/snapshots/debug_comment_input_second_to_last_line.svg",
+        &svg_output,
+    )
+    .unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/comment_input_second_to_last_line.svg"),
+        "test_comment_input_on_second_to_last_line_svg",
+        create_test_failure_handler("test_comment_input_on_second_to_last_line_svg"),
+    );
+}
+
+/// Write an EPUB with one chapter showing `figures`, each a plain color image
+fn write_epub_with_figures(path: &std::path::Path, figures: &[(&str, [u8; 3])]) {
+    use std::io::Write;
+    use zip::write::FileOptions;
+
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(path).unwrap());
+    let mut add = |name: &str, bytes: &[u8]| {
+        zip.start_file(name, FileOptions::default()).unwrap();
+        zip.write_all(bytes).unwrap();
+    };
+    add("mimetype", b"application/epub+zip");
+    add(
+        "META-INF/container.xml",
+        br#"<?xml version="1.0"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+    <rootfiles>
+        <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+    </rootfiles>
+</container>"#,
+    );
+
+    let mut manifest = String::new();
+    let mut body = String::new();
+    for (name, color) in figures {
+        manifest.push_str(&format!(
+            r#"<item id="{name}" href="{name}" media-type="image/png"/>"#
+        ));
+        body.push_str(&format!(
+            r#"<p>Figure {name}.</p><p><img src="{name}"/></p>"#
+        ));
+
+        let mut png = std::io::Cursor::new(Vec::new());
+        image::DynamicImage::ImageRgb8(image::RgbImage::from_pixel(160, 80, image::Rgb(*color)))
+            .write_to(&mut png, image::ImageFormat::Png)
+            .unwrap();
+        add(&format!("OEBPS/{name}"), png.get_ref());
+    }
+    add(
+        "OEBPS/content.opf",
+        format!(
+            r#"<?xml version="1.0"?>
+<package xmlns="http://www.idpf.org/2007/opf" unique-identifier="BookId" version="2.0">
+    <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+        <dc:title>Figures</dc:title>
+        <dc:identifier id="BookId">figures</dc:identifier>
+    </metadata>
+    <manifest>
+        <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+        <item id="chapter" href="chapter.xhtml" media-type="application/xhtml+xml"/>
+        {manifest}
+    </manifest>
+    <spine toc="ncx"><itemref idref="chapter"/></spine>
+</package>"#
+        )
+        .as_bytes(),
+    );
+    add(
+        "OEBPS/toc.ncx",
+        br#"<?xml version="1.0"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+    <navMap>
+        <navPoint id="chapter" playOrder="1">
+            <navLabel><text>Figures</text></navLabel>
+            <content src="chapter.xhtml"/>
+        </navPoint>
+    </navMap>
+</ncx>"#,
+    );
+    add(
+        "OEBPS/chapter.xhtml",
+        format!(
+            r#"<?xml version="1.0"?>
+<html xmlns="http://www.w3.org/1999/xhtml"><body>{body}</body></html>"#
+        )
+        .as_bytes(),
+    );
+    zip.finish().unwrap();
+}
+
+#[test]
+fn test_image_popup_steps_to_the_next_figure_svg() {
+    use crossterm::event::KeyCode;
+
+    ensure_test_report_initialized();
+    let _comments_guard = TempCommentsDirGuard::new();
+    let mut terminal = create_test_terminal(80, 24);
+    let temp_dir = tempfile::tempdir().unwrap();
+    write_epub_with_figures(
+        &temp_dir.path().join("figures.epub"),
+        &[("whale.png", [30, 90, 160]), ("ship.png", [170, 110, 40])],
+    );
+
+    let mut app = App::new_with_config(Some(temp_dir.path().to_str().unwrap()), None, false);
+    app.test_api().use_halfblock_images((10, 20));
+    open_first_test_book(&mut app);
+    let mut draw = |app: &mut App| {
+        terminal
+            .draw(|f| {
+                let fps = create_test_fps_counter();
+                app.draw(f, &fps)
+            })
+            .unwrap();
+        terminal_to_svg(&terminal)
+    };
+    draw(&mut app);
+    app.test_api().wait_for_images();
+
+    // ]I opens the first figure, l steps to the second
+    app.press_key(KeyCode::Char(']'));
+    app.press_key(KeyCode::Char('I'));
+    draw(&mut app);
+    app.press_key(KeyCode::Char('l'));
+    let svg_output = draw(&mut app);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write(
+        "tests/snapshots/debug_image_popup_next_figure.svg",
+        &svg_output,
+    )
+    .unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/image_popup_next_figure.svg"),
+        "test_image_popup_steps_to_the_next_figure_svg",
+        create_test_failure_handler("test_image_popup_steps_to_the_next_figure_svg"),
+    );
+}
+
+/// Open the 7-chapter test book, scroll a little and draw it at `width`x`height`
+fn render_small_terminal_svg(width: u16, height: u16) -> String {
+    let mut terminal = create_test_terminal(width, height);
+    let mut app = App::new_with_config(Some("tests/testdata"), None, false);
+    if let Some(book_info) = app.book_manager.get_book_info(1) {
+        let path = book_info.path.clone();
+        let _ = app.open_book_for_reading_by_path(&path);
+    }
+
+    let mut draw = |app: &mut App| {
+        terminal
+            .draw(|f| {
+                let fps = create_test_fps_counter();
+                app.draw(f, &fps)
+            })
+            .unwrap();
+        terminal_to_svg(&terminal)
+    };
+    draw(&mut app);
+    // Keys keep working whatever the layout
+    app.press_char_times('j', 3);
+    draw(&mut app)
+}
+
+#[test]
+fn test_too_small_terminal_message_svg() {
+    ensure_test_report_initialized();
+    let _comments_guard = TempCommentsDirGuard::new();
+    let svg_output = render_small_terminal_svg(35, 10);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_terminal_35x10.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/terminal_35x10.svg"),
+        "test_too_small_terminal_message_svg",
+        create_test_failure_handler("test_too_small_terminal_message_svg"),
+    );
+}
+
+#[test]
+fn test_compact_terminal_single_panel_svg() {
+    ensure_test_report_initialized();
+    let _comments_guard = TempCommentsDirGuard::new();
+    let svg_output = render_small_terminal_svg(55, 14);
+
+    std::fs::create_dir_all("tests/snapshots").unwrap();
+    std::fs::write("tests/snapshots/debug_terminal_55x14.svg", &svg_output).unwrap();
+
+    assert_svg_snapshot(
+        svg_output.clone(),
+        std::path::Path::new("tests/snapshots/terminal_55x14.svg"),
+        "test_compact_terminal_single_panel_svg",
+        create_test_failure_handler("test_compact_terminal_single_panel_svg"),
+    );
+}
@@ -1,4 +1,5 @@
 use bookokrat::book_manager::{BookInfo, BookManager};
+use bookokrat::color_mode::{ColorMode, set_color_mode};
 use bookokrat::main_app::VimNavMotions;
 use bookokrat::markdown_text_reader::{ActiveSection, MarkdownTextReader};
 use bookokrat::navigation_panel::{CurrentBookInfo, NavigationMode, NavigationPanel};
@@ -18,6 +19,8 @@ static INIT: Once = Once::new();
 
 fn ensure_test_report_initialized() {
     INIT.call_once(|| {
+        // Snapshots are recorded in full color whatever terminal runs them
+        set_color_mode(ColorMode::Truecolor);
         test_report::init_test_report();
     });
 }
@@ -56,6 +59,7 @@ fn create_test_book_manager() -> BookManager {
         books.push(BookInfo {
             display_name: format!("Book {i}"),
             path: "book1.epub".to_string(),
+            ..Default::default()
         })
     }
     book_manager.books = books;
@@ -173,9 +177,10 @@ fn test_navigation_panel_vim_motion_g() {
     let book_manager = create_test_book_manager();
     let mut nav_panel = NavigationPanel::new(&book_manager);
 
-    // Switch to TOC mode with our test book
+    // Switch to TOC mode with our test book; the TOC is drawn for the open book
     let book_info = create_test_book_info_with_toc();
     nav_panel.switch_to_toc_mode(book_info);
+    nav_panel.current_book_index = Some(0);
 
     // Test G (go to bottom)
     nav_panel.handle_upper_g();
@@ -213,9 +218,10 @@ fn test_navigation_panel_vim_motion_gg() {
     let book_manager = create_test_book_manager();
     let mut nav_panel = NavigationPanel::new(&book_manager);
 
-    // Switch to TOC mode with our test book
+    // Switch to TOC mode with our test book; the TOC is drawn for the open book
     let book_info = create_test_book_info_with_toc();
     nav_panel.switch_to_toc_mode(book_info);
+    nav_panel.current_book_index = Some(0);
 
     // Move down to middle to test gg from a non-top position
     for _ in 0..10 {